@@ -46,6 +46,9 @@
 //!
 //! Finally, establish a connection, as below.
 //!
+//! A connection may alternatively be created with a [`SimpleBuilder`], which also allows selecting
+//! the server with a `ServerSpec`, and giving properties, such as the media role.
+//!
 //! # Connecting
 //!
 //! The first step before using the sound system is to connect to the server. This is normally done
@@ -83,17 +86,20 @@
 //!
 //! Once the connection is established to the server, data can start flowing. Using the connection
 //! is very similar to the normal read() and write() system calls using [`read`] and [`write`]
-//! methods of the [`Simple`] object. Note that these operations always block.
+//! methods of the [`Simple`] object. Note that these operations always block. Should a bound on
+//! waiting be needed, [`write_timeout`] and [`read_timeout`] variants are available.
 //!
 //! # Buffer control
 //!
 //! * [`Simple::get_latency`]: Will return the total latency of the playback or record pipeline,
-//!   respectively.
+//!   respectively. [`Simple::latency`] does the same, as a `Duration`, giving the error on failure.
 //! * [`Simple::flush`]: Will throw away all data currently in buffers.
 //!
 //! If a playback stream is used then the following operation is available:
 //!
 //! * [`Simple::drain`]: Will wait for all sent data to finish playing.
+//! * [`Simple::drain_interruptible`]: Likewise, but can be interrupted from another thread with an
+//!   [`InterruptHandle`], for instance when the user hits ‘stop’.
 //!
 //! # Cleanup
 //!
@@ -101,11 +107,17 @@
 //! is done automatically once the object is dropped.
 //!
 //! [`Simple`]: struct.Simple.html
+//! [`SimpleBuilder`]: struct.SimpleBuilder.html
 //! [`read`]: struct.Simple.html#method.read
 //! [`write`]: struct.Simple.html#method.write
+//! [`read_timeout`]: struct.Simple.html#method.read_timeout
+//! [`write_timeout`]: struct.Simple.html#method.write_timeout
+//! [`Simple::latency`]: struct.Simple.html#method.latency
 //! [`Simple::get_latency`]: struct.Simple.html#method.get_latency
 //! [`Simple::flush`]: struct.Simple.html#method.flush
 //! [`Simple::drain`]: struct.Simple.html#method.drain
+//! [`Simple::drain_interruptible`]: struct.Simple.html#method.drain_interruptible
+//! [`InterruptHandle`]: struct.InterruptHandle.html
 
 #![doc(html_logo_url = "https://github.com/jnqnfe/pulse-binding-rust/raw/master/logo.png",
        html_favicon_url = "https://github.com/jnqnfe/pulse-binding-rust/raw/master/favicon.ico")]
@@ -117,18 +129,290 @@ extern crate libpulse_simple_sys as capi;
 use std::os::raw::{c_char, c_void};
 use std::ffi::CString;
 use std::ptr::null;
-use pulse::error::PAErr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use pulse::error::{Code, PAErr};
+use pulse::context::server_spec::ServerSpec;
+use pulse::proplist::{Proplist, MediaRole, properties};
 
 use capi::pa_simple as SimpleInternal;
 
 /// An opaque simple connection object.
 pub struct Simple {
-    /// The actual C object.
+    /// The actual C object, shared with any calls pending in the background (see
+    /// [`write_timeout`](#method.write_timeout)).
+    inner: Arc<SimpleInner>,
+    /// Serialises use of the C object, holding the worker of any call given up waiting upon, which
+    /// must have finished before another call may be made.
+    worker: Mutex<Option<JoinHandle<()>>>,
+    /// Means of interrupting the call currently waited upon, if any.
+    interrupter: Arc<Interrupter>,
+}
+
+/// A function waking the call currently waited upon.
+type Waker = Box<dyn Fn() + Send>;
+
+/// Holder of the waker of the call currently waited upon, which an [`InterruptHandle`] can wake.
+///
+/// Calls are serialised, thus there is at most one.
+///
+/// [`InterruptHandle`]: struct.InterruptHandle.html
+#[derive(Default)]
+struct Interrupter {
+    waker: Mutex<Option<Waker>>,
+}
+
+impl Interrupter {
+    fn set(&self, waker: Option<Waker>) {
+        *self.waker.lock().unwrap_or_else(|e| e.into_inner()) = waker;
+    }
+
+    fn interrupt(&self) {
+        if let Some(waker) = self.waker.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            waker();
+        }
+    }
+}
+
+/// A handle able to interrupt the pending call of a [`Simple`] connection, from another thread.
+///
+/// Obtained with [`Simple::interrupt_handle`]. Only calls waited upon on a worker thread, i.e.
+/// [`Simple::drain_interruptible`], [`Simple::write_timeout`] and [`Simple::read_timeout`], can be
+/// interrupted; the plain blocking calls cannot.
+///
+/// [`Simple`]: struct.Simple.html
+/// [`Simple::interrupt_handle`]: struct.Simple.html#method.interrupt_handle
+/// [`Simple::drain_interruptible`]: struct.Simple.html#method.drain_interruptible
+/// [`Simple::write_timeout`]: struct.Simple.html#method.write_timeout
+/// [`Simple::read_timeout`]: struct.Simple.html#method.read_timeout
+#[derive(Clone)]
+pub struct InterruptHandle {
+    interrupter: Arc<Interrupter>,
+}
+
+impl InterruptHandle {
+    /// Force the interruptible call currently pending, if any, to return
+    /// [`SimpleError::Interrupted`]. Has no effect if no such call is pending.
+    ///
+    /// As with a timeout, the interrupted call is not cancelled, but is left to complete on its
+    /// worker thread, with the connection kept alive until it does. Until then, all other calls on
+    /// the connection fail with [`Code::Busy`]. Having interrupted a drain, the connection can
+    /// simply be dropped, which frees it once the drain has completed.
+    ///
+    /// [`SimpleError::Interrupted`]: enum.SimpleError.html#variant.Interrupted
+    /// [`Code::Busy`]: ../libpulse_binding/error/enum.Code.html#variant.Busy
+    pub fn interrupt(&self) {
+        self.interrupter.interrupt();
+    }
+}
+
+/// Owner of the actual C object, freeing it when dropped.
+struct SimpleInner {
     ptr: *mut SimpleInternal,
 }
 
-unsafe impl Send for Simple {}
-unsafe impl Sync for Simple {}
+// The C object takes the lock of its internal threaded mainloop in every function, thus calls may
+// be made from any thread.
+unsafe impl Send for SimpleInner {}
+unsafe impl Sync for SimpleInner {}
+
+/// Errors returned by operations having a timeout, or otherwise differentiating failure cases.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SimpleError {
+    /// An error returned by PulseAudio.
+    Pa(PAErr),
+    /// The operation did not complete within the given timeout.
+    TimedOut,
+    /// The operation was interrupted via an [`InterruptHandle`](struct.InterruptHandle.html).
+    Interrupted,
+    /// The sample spec is invalid.
+    InvalidSpec,
+    /// The channel map is invalid, or not compatible with the sample spec.
+    InvalidChannelMap,
+    /// The server specification is empty, or holds an invalid entry.
+    InvalidServer,
+    /// A name contains an interior nul byte.
+    InvalidName,
+    /// A property key is invalid, or a property value contains an interior nul byte.
+    InvalidProperty,
+}
+
+impl From<PAErr> for SimpleError {
+    fn from(e: PAErr) -> Self {
+        SimpleError::Pa(e)
+    }
+}
+
+impl std::fmt::Display for SimpleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            SimpleError::Pa(ref e) => write!(f, "{}", e),
+            SimpleError::TimedOut => write!(f, "Operation timed out"),
+            SimpleError::Interrupted => write!(f, "Operation interrupted"),
+            SimpleError::InvalidSpec => write!(f, "Invalid sample spec"),
+            SimpleError::InvalidChannelMap => write!(f, "Invalid channel map"),
+            SimpleError::InvalidServer => write!(f, "Invalid server specification"),
+            SimpleError::InvalidName => write!(f, "Invalid name"),
+            SimpleError::InvalidProperty => write!(f, "Invalid property"),
+        }
+    }
+}
+
+impl std::error::Error for SimpleError {}
+
+/// Prefix of the environment variables from which the library takes client properties.
+const PROPERTY_ENV_PREFIX: &str = "PULSE_PROP_";
+
+/// Serialises use of the property environment variables by [`SimpleBuilder::build`].
+///
+/// [`SimpleBuilder::build`]: struct.SimpleBuilder.html#method.build
+static PROPERTY_ENV_LOCK: Mutex<()> = Mutex::new(());
+
+/// A builder of [`Simple`] connections, as an alternative to the long parameter list of
+/// [`Simple::new`].
+///
+/// ```rust,ignore
+/// let s = SimpleBuilder::new("FooApp", "Music", Direction::Playback, &spec)
+///     .device("alsa_output.pci-0000_00_1b.0.analog-stereo")
+///     .buffer_attr(&attr)
+///     .build()?;
+/// ```
+///
+/// [`Simple`]: struct.Simple.html
+/// [`Simple::new`]: struct.Simple.html#method.new
+#[derive(Debug, Clone)]
+pub struct SimpleBuilder {
+    name: String,
+    stream_name: String,
+    dir: pulse::stream::Direction,
+    spec: pulse::sample::Spec,
+    map: Option<pulse::channelmap::Map>,
+    attr: Option<pulse::def::BufferAttr>,
+    dev: Option<String>,
+    server: Option<ServerSpec>,
+    properties: Vec<(String, String)>,
+}
+
+impl SimpleBuilder {
+    /// Create a new builder, for a stream named `stream_name`, of application `name`, in the
+    /// given direction and with the given sample spec.
+    pub fn new(name: &str, stream_name: &str, dir: pulse::stream::Direction,
+        spec: &pulse::sample::Spec) -> Self
+    {
+        Self {
+            name: name.to_string(),
+            stream_name: stream_name.to_string(),
+            dir,
+            spec: *spec,
+            map: None,
+            attr: None,
+            dev: None,
+            server: None,
+            properties: Vec::new(),
+        }
+    }
+
+    /// Set the channel map to use, rather than the default.
+    pub fn channel_map(mut self, map: &pulse::channelmap::Map) -> Self {
+        self.map = Some(*map);
+        self
+    }
+
+    /// Set the buffering attributes to use, rather than the default.
+    pub fn buffer_attr(mut self, attr: &pulse::def::BufferAttr) -> Self {
+        self.attr = Some(*attr);
+        self
+    }
+
+    /// Set the sink (resp. source) to connect to, by name, rather than the default.
+    pub fn device(mut self, name: &str) -> Self {
+        self.dev = Some(name.to_string());
+        self
+    }
+
+    /// Set the server(s) to connect to, rather than the default.
+    pub fn server(mut self, server: &ServerSpec) -> Self {
+        self.server = Some(server.clone());
+        self
+    }
+
+    /// Set a property of the client, which the server also applies to the stream.
+    ///
+    /// The ‘simple’ component has no means of taking a property list, thus properties are passed
+    /// via `PULSE_PROP_<key>` environment variables, which the library reads when creating the
+    /// client. These are set only for the duration of [`build`], with builders serialised among
+    /// themselves, but note that the environment is **process-global**, thus this may race with
+    /// other code in the process reading or changing it, including other connections being made.
+    ///
+    /// [`build`]: #method.build
+    pub fn property(mut self, key: &str, value: &str) -> Self {
+        self.properties.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Set the media role, along with its related properties.
+    ///
+    /// This is set as with [`property`], thus the same caveats apply.
+    ///
+    /// [`property`]: #method.property
+    pub fn media_role(mut self, role: MediaRole) -> Self {
+        self = self.property(properties::MEDIA_ROLE, role.as_str());
+        for &(key, value) in role.related_properties() {
+            self = self.property(key, value);
+        }
+        self
+    }
+
+    /// Validate the configuration and create the connection.
+    pub fn build(&self) -> Result<Simple, SimpleError> {
+        if !self.spec.is_valid() {
+            return Err(SimpleError::InvalidSpec);
+        }
+        if let Some(ref map) = self.map {
+            if !map.is_valid() || !map.is_compatible_with_sample_spec(&self.spec) {
+                return Err(SimpleError::InvalidChannelMap);
+            }
+        }
+        let server = match self.server {
+            Some(ref server) => Some(server.build().map_err(|_| SimpleError::InvalidServer)?),
+            None => None,
+        };
+        let names = [Some(&self.name), Some(&self.stream_name), self.dev.as_ref()];
+        if names.iter().flatten().any(|n| n.contains('\0')) {
+            return Err(SimpleError::InvalidName);
+        }
+        for &(ref key, ref value) in &self.properties {
+            if key.contains('\0') || key.contains('=') || !Proplist::key_is_valid(key) ||
+                value.contains('\0')
+            {
+                return Err(SimpleError::InvalidProperty);
+            }
+        }
+
+        let _guard = match self.properties.is_empty() {
+            true => None,
+            false => Some(PROPERTY_ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner())),
+        };
+        let previous: Vec<_> = self.properties.iter().map(|&(ref key, ref value)| {
+            let var = format!("{}{}", PROPERTY_ENV_PREFIX, key);
+            let old = std::env::var_os(&var);
+            std::env::set_var(&var, value);
+            (var, old)
+        }).collect();
+        let result = Simple::new(server.as_deref(), &self.name, self.dir, self.dev.as_deref(),
+            &self.stream_name, &self.spec, self.map.as_ref(), self.attr.as_ref());
+        // Restore in reverse, in case of a key given more than once
+        for (var, old) in previous.into_iter().rev() {
+            match old {
+                Some(value) => std::env::set_var(&var, value),
+                None => std::env::remove_var(&var),
+            }
+        }
+        result.map_err(SimpleError::from)
+    }
+}
 
 impl Simple {
     /// Create a new connection to the server.
@@ -143,6 +427,8 @@ impl Simple {
     /// * `ss`: The sample type to use.
     /// * `map`: The channel map to use, or `None` for default.
     /// * `attr`: Buffering attributes, or `None` for default.
+    ///
+    /// See also [`SimpleBuilder`](struct.SimpleBuilder.html).
     pub fn new(server: Option<&str>, name: &str, dir: pulse::stream::Direction, dev: Option<&str>,
         stream_name: &str, ss: &pulse::sample::Spec, map: Option<&pulse::channelmap::Map>,
         attr: Option<&pulse::def::BufferAttr>) -> Result<Self, PAErr>
@@ -200,38 +486,182 @@ impl Simple {
     /// Create a new `Simple` from an existing [`SimpleInternal`](capi/enum.pa_simple.html) pointer.
     fn from_raw(ptr: *mut SimpleInternal) -> Self {
         assert_eq!(false, ptr.is_null());
-        Self { ptr }
+        Self {
+            inner: Arc::new(SimpleInner { ptr }),
+            worker: Mutex::new(None),
+            interrupter: Arc::new(Interrupter::default()),
+        }
+    }
+
+    /// Get a handle for interrupting pending calls from another thread.
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        InterruptHandle { interrupter: Arc::clone(&self.interrupter) }
     }
 
     /// Write some data to the server.
     pub fn write(&self, data: &[u8]) -> Result<(), PAErr> {
+        let _guard = self.acquire()?;
         let mut error: i32 = 0;
-        match unsafe { capi::pa_simple_write(self.ptr, data.as_ptr() as *mut c_void, data.len(),
-            &mut error) }
-        {
+        let ptr = data.as_ptr() as *mut c_void;
+        match unsafe { capi::pa_simple_write(self.inner.ptr, ptr, data.len(), &mut error) } {
             0 => Ok(()),
             _ => Err(PAErr(error)),
         }
     }
 
+    /// Write some data to the server, giving up waiting after the specified timeout.
+    ///
+    /// The underlying library offers no means to time out a blocking call, thus this is emulated:
+    /// the write is performed on a separate thread, which this waits upon for up to `timeout`. If
+    /// [`SimpleError::TimedOut`] is returned, the write remains pending in the background, and will
+    /// complete (or fail) in its own time, with the connection kept alive until it does. Until it
+    /// has, all other calls on this connection fail with [`Code::Busy`]. Note that a thread is
+    /// spawned per call.
+    ///
+    /// The wait can likewise be given up early with an [`InterruptHandle`], returning
+    /// [`SimpleError::Interrupted`].
+    ///
+    /// [`SimpleError::TimedOut`]: enum.SimpleError.html#variant.TimedOut
+    /// [`SimpleError::Interrupted`]: enum.SimpleError.html#variant.Interrupted
+    /// [`Code::Busy`]: ../libpulse_binding/error/enum.Code.html#variant.Busy
+    /// [`InterruptHandle`]: struct.InterruptHandle.html
+    pub fn write_timeout(&self, data: &[u8], timeout: Duration) -> Result<(), SimpleError> {
+        let inner = Arc::clone(&self.inner);
+        let data = data.to_vec();
+        let result = self.run_emulated(Some(timeout), move || {
+            let mut error: i32 = 0;
+            match unsafe { capi::pa_simple_write(inner.ptr, data.as_ptr() as *mut c_void,
+                data.len(), &mut error) }
+            {
+                0 => Ok(()),
+                _ => Err(PAErr(error)),
+            }
+        })?;
+        result.map_err(SimpleError::from)
+    }
+
+    /// Read some data from the server, giving up waiting after the specified timeout.
+    ///
+    /// This is emulated in the same way as [`write_timeout`]. If [`SimpleError::TimedOut`] is
+    /// returned, the read remains pending in the background, and the data it eventually receives
+    /// is discarded.
+    ///
+    /// [`write_timeout`]: #method.write_timeout
+    /// [`SimpleError::TimedOut`]: enum.SimpleError.html#variant.TimedOut
+    pub fn read_timeout(&self, data: &mut [u8], timeout: Duration) -> Result<(), SimpleError> {
+        let inner = Arc::clone(&self.inner);
+        let len = data.len();
+        let result = self.run_emulated(Some(timeout), move || {
+            let mut buffer = vec![0u8; len];
+            let mut error: i32 = 0;
+            match unsafe { capi::pa_simple_read(inner.ptr, buffer.as_mut_ptr() as *mut c_void,
+                len, &mut error) }
+            {
+                0 => Ok(buffer),
+                _ => Err(PAErr(error)),
+            }
+        })?;
+        data.copy_from_slice(&result?);
+        Ok(())
+    }
+
+    /// Run a blocking call on a separate thread, waiting for its result for up to `timeout` (if
+    /// given), or until interrupted.
+    ///
+    /// If given up waiting upon, the worker is kept, such that no other call can be made until it
+    /// has finished.
+    fn run_emulated<T, F>(&self, timeout: Option<Duration>, f: F) -> Result<T, SimpleError>
+        where T: Send + 'static, F: FnOnce() -> T + Send + 'static
+    {
+        let mut worker = self.acquire()?;
+        let (sender, receiver) = mpsc::channel();
+        let waker = sender.clone();
+        let handle = std::thread::spawn(move || {
+            let _ = sender.send(Some(f()));
+        });
+        self.interrupter.set(Some(Box::new(move || {
+            let _ = waker.send(None);
+        })));
+        let received = match timeout {
+            Some(timeout) => receiver.recv_timeout(timeout).ok(),
+            None => receiver.recv().ok(),
+        };
+        self.interrupter.set(None);
+        match received {
+            Some(Some(value)) => {
+                let _ = handle.join();
+                Ok(value)
+            },
+            Some(None) => {
+                *worker = Some(handle);
+                Err(SimpleError::Interrupted)
+            },
+            None => {
+                *worker = Some(handle);
+                Err(SimpleError::TimedOut)
+            },
+        }
+    }
+
+    /// Obtain exclusive use of the C object.
+    ///
+    /// Fails with [`Code::Busy`] if a call given up waiting upon is still pending in the
+    /// background.
+    ///
+    /// [`Code::Busy`]: ../libpulse_binding/error/enum.Code.html#variant.Busy
+    fn acquire(&self) -> Result<MutexGuard<'_, Option<JoinHandle<()>>>, PAErr> {
+        let mut worker = self.worker.lock().unwrap_or_else(|e| e.into_inner());
+        if worker.as_ref().map_or(false, |w| !w.is_finished()) {
+            return Err(PAErr::from(Code::Busy));
+        }
+        if let Some(w) = worker.take() {
+            let _ = w.join();
+        }
+        Ok(worker)
+    }
+
     /// Wait until all data already written is played by the daemon.
     pub fn drain(&self) -> Result<(), PAErr> {
+        let _guard = self.acquire()?;
         let mut error: i32 = 0;
-        match unsafe { capi::pa_simple_drain(self.ptr, &mut error) } {
+        match unsafe { capi::pa_simple_drain(self.inner.ptr, &mut error) } {
             0 => Ok(()),
             _ => Err(PAErr(error)),
         }
     }
 
+    /// Wait until all data already written is played by the daemon, or until interrupted with an
+    /// [`InterruptHandle`], in which case [`SimpleError::Interrupted`] is returned.
+    ///
+    /// The drain is performed on a separate thread, as with [`write_timeout`], thus if interrupted,
+    /// it remains pending in the background, and all other calls on this connection fail with
+    /// [`Code::Busy`] until it has completed.
+    ///
+    /// [`InterruptHandle`]: struct.InterruptHandle.html
+    /// [`SimpleError::Interrupted`]: enum.SimpleError.html#variant.Interrupted
+    /// [`write_timeout`]: #method.write_timeout
+    /// [`Code::Busy`]: ../libpulse_binding/error/enum.Code.html#variant.Busy
+    pub fn drain_interruptible(&self) -> Result<(), SimpleError> {
+        let inner = Arc::clone(&self.inner);
+        let result = self.run_emulated(None, move || {
+            let mut error: i32 = 0;
+            match unsafe { capi::pa_simple_drain(inner.ptr, &mut error) } {
+                0 => Ok(()),
+                _ => Err(PAErr(error)),
+            }
+        })?;
+        result.map_err(SimpleError::from)
+    }
+
     /// Read some data from the server.
     ///
     /// This function blocks until `data.len()` amount of data has been received from the server,
     /// or until an error occurs.
     pub fn read(&self, data: &mut [u8]) -> Result<(), PAErr> {
+        let _guard = self.acquire()?;
         let mut error: i32 = 0;
-        match unsafe { capi::pa_simple_read(self.ptr, data.as_mut_ptr() as *mut c_void, data.len(),
-            &mut error) }
-        {
+        let ptr = data.as_mut_ptr() as *mut c_void;
+        match unsafe { capi::pa_simple_read(self.inner.ptr, ptr, data.len(), &mut error) } {
             0 => Ok(()),
             _ => Err(PAErr(error)),
         }
@@ -239,25 +669,44 @@ impl Simple {
 
     /// Return the playback or record latency.
     pub fn get_latency(&self) -> Option<pulse::time::MicroSeconds> {
+        let _guard = self.acquire().ok()?;
         let mut error: i32 = 0;
-        let ret = unsafe { capi::pa_simple_get_latency(self.ptr, &mut error) };
+        let ret = unsafe { capi::pa_simple_get_latency(self.inner.ptr, &mut error) };
         if error != 0 {
             return None;
         }
         Some(pulse::time::MicroSeconds(ret))
     }
 
+    /// Return the playback or record latency, as a `Duration`.
+    ///
+    /// Unlike [`get_latency`](#method.get_latency), this gives the error on failure.
+    pub fn latency(&self) -> Result<Duration, SimpleError> {
+        let _guard = self.acquire()?;
+        let mut error: i32 = 0;
+        let ret = unsafe { capi::pa_simple_get_latency(self.inner.ptr, &mut error) };
+        if ret == pcapi::PA_USEC_INVALID || error != 0 {
+            // Failure without an error code should not happen, but is not to be reported as success
+            return Err(SimpleError::Pa(match error {
+                0 => PAErr::from(Code::Unknown),
+                e => PAErr(e),
+            }));
+        }
+        Ok(Duration::from_micros(ret))
+    }
+
     /// Flush the playback or record buffer. This discards any audio in the buffer.
     pub fn flush(&self) -> Result<(), PAErr> {
+        let _guard = self.acquire()?;
         let mut error: i32 = 0;
-        match unsafe { capi::pa_simple_flush(self.ptr, &mut error) } {
+        match unsafe { capi::pa_simple_flush(self.inner.ptr, &mut error) } {
             0 => Ok(()),
             _ => Err(PAErr(error)),
         }
     }
 }
 
-impl Drop for Simple {
+impl Drop for SimpleInner {
     fn drop(&mut self) {
         // Close and free the connection to the server.
         unsafe { capi::pa_simple_free(self.ptr) };
@@ -0,0 +1,152 @@
+// Copyright 2018 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Write throughput of playback streams, by block size.
+//!
+//! Each iteration writes one second of (silent) audio to a corked playback stream, in blocks of a
+//! given size, through `begin_write`, then flushes it. Blocks of the stream’s tile size are
+//! compared with smaller ones, and with the size chosen by `begin_write`, showing the per-write
+//! overhead saved.
+//!
+//! The `write_small` group instead writes the second of audio in tiny blocks, as when bridging
+//! packetized network audio, both directly and with write coalescing enabled.
+//!
+//! A running server is required; nothing is measured otherwise.
+
+#[macro_use]
+extern crate criterion;
+extern crate libpulse_binding as pulse;
+
+use criterion::{Criterion, Throughput};
+use pulse::context::{self, Context};
+use pulse::mainloop::standard::{IterateResult, Mainloop};
+use pulse::sample;
+use pulse::stream::{self, SeekMode, Stream};
+
+const SPEC: sample::Spec = sample::Spec {
+    format: sample::Format::S16le,
+    channels: 2,
+    rate: 44100,
+};
+
+/// Size of the blocks of the `write_small` group, in bytes (16 frames).
+const SMALL_BLOCK: usize = 64;
+
+/// A playback stream, ready for writing.
+struct Playback {
+    mainloop: Mainloop,
+    _context: Context,
+    stream: Stream,
+}
+
+/// Connect to the server and create a corked playback stream, returning `None` upon failure.
+fn connect() -> Option<Playback> {
+    let mut mainloop = Mainloop::new()?;
+    let mut context = Context::new(&mainloop, "write-bench")?;
+    context.connect(None, context::flags::NOFLAGS, None).ok()?;
+    loop {
+        match mainloop.iterate(true) {
+            IterateResult::Success(_) => {},
+            _ => return None,
+        }
+        match context.get_state() {
+            context::State::Ready => break,
+            context::State::Failed | context::State::Terminated => return None,
+            _ => {},
+        }
+    }
+
+    let mut stream = Stream::new(&mut context, "write-bench", &SPEC, None)?;
+    stream.connect_playback(None, None, stream::flags::START_CORKED, None, None).ok()?;
+    loop {
+        match mainloop.iterate(true) {
+            IterateResult::Success(_) => {},
+            _ => return None,
+        }
+        match stream.get_state() {
+            stream::State::Ready => break,
+            stream::State::Failed | stream::State::Terminated => return None,
+            _ => {},
+        }
+    }
+    Some(Playback { mainloop: mainloop, _context: context, stream: stream })
+}
+
+/// Write one second of audio in blocks of the given size (or the size chosen by `begin_write`),
+/// then flush the stream, and dispatch pending events.
+fn write_second(playback: &mut Playback, block: Option<usize>) {
+    let mut remaining = SPEC.bytes_per_second();
+    while remaining > 0 {
+        let buffer = playback.stream.begin_write(block).unwrap().unwrap();
+        let len = std::cmp::min(buffer.len(), remaining);
+        for byte in buffer[..len].iter_mut() {
+            *byte = 0;
+        }
+        playback.stream.write(&buffer[..len], None, 0, SeekMode::Relative).unwrap();
+        remaining -= len;
+    }
+    drop(playback.stream.flush(None));
+    while let IterateResult::Success(n) = playback.mainloop.iterate(false) {
+        if n == 0 {
+            break;
+        }
+    }
+}
+
+/// Write one second of audio in tiny blocks with `write`, then flush the stream, and dispatch
+/// pending events.
+fn write_second_small(playback: &mut Playback) {
+    let block = [0u8; SMALL_BLOCK];
+    for _ in 0..(SPEC.bytes_per_second() / SMALL_BLOCK) {
+        playback.stream.write(&block, None, 0, SeekMode::Relative).unwrap();
+    }
+    playback.stream.write_staged().unwrap();
+    drop(playback.stream.flush(None));
+    while let IterateResult::Success(n) = playback.mainloop.iterate(false) {
+        if n == 0 {
+            break;
+        }
+    }
+}
+
+fn write_throughput(c: &mut Criterion) {
+    let mut playback = match connect() {
+        Some(playback) => playback,
+        None => {
+            eprintln!("no server available, skipping write benchmarks");
+            return;
+        },
+    };
+    let mut group = c.benchmark_group("write");
+    group.throughput(Throughput::Bytes(SPEC.bytes_per_second() as u64));
+    let tile_size = playback.stream.tile_size();
+    for &(name, block) in &[("tile_size", tile_size), ("auto", None), ("4096", Some(4096)),
+        ("256", Some(256))]
+    {
+        group.bench_function(name, |b| b.iter(|| write_second(&mut playback, block)));
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("write_small");
+    group.throughput(Throughput::Bytes(SPEC.bytes_per_second() as u64));
+    for &(name, coalescing) in &[("direct", None), ("coalesced", Some(0))] {
+        playback.stream.set_write_coalescing(coalescing).unwrap();
+        group.bench_function(name, |b| b.iter(|| write_second_small(&mut playback)));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, write_throughput);
+criterion_main!(benches);
@@ -0,0 +1,217 @@
+// Copyright 2018 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Pre-allocated playback buffers.
+//!
+//! # Overview
+//!
+//! Low-latency playback should avoid allocating memory in the audio path. A [`BufferPool`] holds a
+//! fixed set of buffers, allocated up front. A buffer is leased from the pool with
+//! [`BufferPool::lease`], filled, then written with [`Stream::write_pooled`], which hands the
+//! buffer to the library without copying it (where the transport allows). Once the library no
+//! longer needs the data, it releases the buffer through the write’s free callback, returning it to
+//! the pool for reuse. Neither leasing, writing nor returning a buffer allocates.
+//!
+//! A leased buffer that is dropped without being written simply returns to the pool.
+//!
+//! ```rust,ignore
+//! use pulse::buffer_pool::BufferPool;
+//!
+//! let pool = BufferPool::new(8, stream.tile_size().unwrap());
+//!
+//! // Within the write callback
+//! while let Some(mut buffer) = pool.lease() {
+//!     let filled = fill(&mut buffer);
+//!     if filled == 0 {
+//!         break;
+//!     }
+//!     stream.write_pooled(buffer, filled, 0, SeekMode::Relative).unwrap();
+//! }
+//! ```
+//!
+//! [`BufferPool`]: struct.BufferPool.html
+//! [`BufferPool::lease`]: struct.BufferPool.html#method.lease
+//! [`Stream::write_pooled`]: ../stream/struct.Stream.html#method.write_pooled
+
+use std;
+use std::os::raw::c_void;
+use std::sync::{Arc, Mutex, Weak};
+
+/// A buffer of the pool, moved between the free list and leases without reallocation.
+///
+/// The reference to the pool is weak, since the pool holds the slots available for lease. Buffers
+/// released after the pool has been dropped are simply freed.
+struct Slot {
+    data: Box<[u8]>,
+    pool: Weak<Shared>,
+}
+
+/// State shared between the pool and its buffers.
+struct Shared {
+    /// Buffers available for lease. Its capacity covers all buffers, thus returning one never
+    /// allocates.
+    free: Mutex<Vec<Box<Slot>>>,
+    buffer_size: usize,
+    count: usize,
+}
+
+impl Shared {
+    fn put(&self, slot: Box<Slot>) {
+        self.free.lock().unwrap_or_else(|e| e.into_inner()).push(slot);
+    }
+}
+
+/// A fixed set of pre-allocated buffers, for writing to playback streams without allocating.
+///
+/// See the [module level documentation](index.html) for details.
+#[derive(Clone)]
+pub struct BufferPool {
+    shared: Arc<Shared>,
+}
+
+impl BufferPool {
+    /// Create a new pool of `count` buffers, each of `buffer_size` bytes, allocating them all.
+    ///
+    /// The buffer size should typically be the [`tile_size`] of the stream written to.
+    ///
+    /// [`tile_size`]: ../stream/struct.Stream.html#method.tile_size
+    pub fn new(count: usize, buffer_size: usize) -> Self {
+        let shared = Arc::new(Shared {
+            free: Mutex::new(Vec::with_capacity(count)),
+            buffer_size: buffer_size,
+            count: count,
+        });
+        for _ in 0..count {
+            let data = vec![0u8; buffer_size].into_boxed_slice();
+            shared.put(Box::new(Slot { data: data, pool: Arc::downgrade(&shared) }));
+        }
+        Self { shared: shared }
+    }
+
+    /// Get the size of each buffer, in bytes.
+    pub fn buffer_size(&self) -> usize {
+        self.shared.buffer_size
+    }
+
+    /// Get the total number of buffers, whether leased or not.
+    pub fn count(&self) -> usize {
+        self.shared.count
+    }
+
+    /// Get the number of buffers currently available for lease.
+    pub fn available(&self) -> usize {
+        self.shared.free.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Lease a buffer, or `None` if all buffers are in use.
+    ///
+    /// The buffer holds whatever data was last written to it.
+    pub fn lease(&self) -> Option<PooledBuffer> {
+        let slot = self.shared.free.lock().unwrap_or_else(|e| e.into_inner()).pop()?;
+        Some(PooledBuffer { slot: Some(slot) })
+    }
+}
+
+/// A buffer leased from a [`BufferPool`], returned to it upon being dropped, or once written
+/// with [`Stream::write_pooled`], upon the library releasing it.
+///
+/// It dereferences to the whole buffer, of the pool’s buffer size.
+///
+/// [`BufferPool`]: struct.BufferPool.html
+/// [`Stream::write_pooled`]: ../stream/struct.Stream.html#method.write_pooled
+pub struct PooledBuffer {
+    slot: Option<Box<Slot>>,
+}
+
+impl PooledBuffer {
+    /// Convert into the userdata pointer given to [`free_cb_proxy`], along with the data pointer.
+    ///
+    /// [`free_cb_proxy`]: fn.free_cb_proxy.html
+    pub(crate) fn into_raw(mut self) -> (*mut c_void, *const u8) {
+        let slot = self.slot.take().unwrap();
+        let data = slot.data.as_ptr();
+        (Box::into_raw(slot) as *mut c_void, data)
+    }
+
+    /// Return a buffer converted with `into_raw` to its pool, should it not have been given to the
+    /// library after all.
+    pub(crate) fn from_raw(userdata: *mut c_void) -> Self {
+        assert!(!userdata.is_null());
+        Self { slot: Some(unsafe { Box::from_raw(userdata as *mut Slot) }) }
+    }
+}
+
+impl std::ops::Deref for PooledBuffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.slot.as_ref().unwrap().data
+    }
+}
+
+impl std::ops::DerefMut for PooledBuffer {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        &mut self.slot.as_mut().unwrap().data
+    }
+}
+
+impl Drop for PooledBuffer {
+    fn drop(&mut self) {
+        if let Some(slot) = self.slot.take() {
+            if let Some(pool) = slot.pool.upgrade() {
+                pool.put(slot);
+            }
+        }
+    }
+}
+
+/// Proxy for the free callback of a pooled buffer write, returning the buffer to its pool.
+pub(crate) extern "C" fn free_cb_proxy(userdata: *mut c_void) {
+    let _ = std::panic::catch_unwind(|| {
+        drop(PooledBuffer::from_raw(userdata));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lease_and_return() {
+        let pool = BufferPool::new(2, 16);
+        assert_eq!(pool.available(), 2);
+
+        let mut a = pool.lease().unwrap();
+        assert_eq!(a.len(), 16);
+        a[0] = 7;
+        let b = pool.lease().unwrap();
+        assert!(pool.lease().is_none());
+
+        drop(b);
+        assert_eq!(pool.available(), 1);
+
+        let (userdata, data) = a.into_raw();
+        assert_eq!(unsafe { *data }, 7);
+        assert_eq!(pool.available(), 1);
+        free_cb_proxy(userdata);
+        assert_eq!(pool.available(), 2);
+        assert_eq!(pool.count(), 2);
+
+        // Buffers outliving the pool are freed upon release
+        let c = pool.lease().unwrap();
+        drop(pool);
+        drop(c);
+    }
+}
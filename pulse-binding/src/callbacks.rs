@@ -18,6 +18,8 @@
 use std;
 use std::os::raw::c_void;
 use std::ptr::null_mut;
+#[cfg(debug_assertions)]
+use std::sync::atomic::{AtomicU64, Ordering};
 
 /// List result instance. Fetching a list can result in a callback being fired for each list item,
 /// and then once to signal that the end of the list having been reached. This is used to
@@ -28,9 +30,26 @@ pub enum ListResult<T> {
     /// End of list reached
     End,
     /// Failure, an error occurred
+    ///
+    /// Note that the introspection API instead uses [`ListEntry`], giving an `Err`, holding the
+    /// error code.
+    ///
+    /// [`ListEntry`]: enum.ListEntry.html
     Error,
 }
 
+/// List entry instance, as given by the introspection API. As with [`ListResult`], the callback is
+/// fired for each list item, and then once to signal the end of the list having been reached. On
+/// failure, the callback is instead given an `Err`, holding the error code, rather than an entry.
+///
+/// [`ListResult`]: enum.ListResult.html
+pub enum ListEntry<T> {
+    /// List item
+    Item(T),
+    /// End of list reached
+    End,
+}
+
 /// Unwraps optional callback function + data pointer tuple, wrapping the function pointer in an
 /// option wrapper. Used internally in passing such parameters to an underlying C function.
 ///
@@ -52,12 +71,69 @@ pub(crate) fn unwrap_optional_callback<T>(cb: Option<(T, *mut c_void)>)
     }
 }
 
+/// Userdata of a multi-use callback, as given to the C API.
+///
+/// This holds the closure, which is destroyed once the userdata is released (on change of
+/// registered callback, or on destruction of the owning object).
+///
+/// In debug builds, each instance carries a generation number, unique to it, which is poisoned upon
+/// release. The instance itself is then deliberately leaked rather than freed, such that a later
+/// use of the userdata pointer, as with a queued callback firing after the owning object was freed,
+/// is caught by a panic with a clear message, rather than calling into freed memory. In release
+/// builds there is no generation, and the instance is freed upon release.
+pub(crate) struct CallbackUserdata<ClosureProto: ?Sized> {
+    #[cfg(debug_assertions)]
+    generation: u64,
+    callback: Option<Box<ClosureProto>>,
+}
+
+/// Generation of released (poisoned) callback userdata.
+#[cfg(debug_assertions)]
+const GENERATION_RELEASED: u64 = 0;
+
+/// Next callback userdata generation.
+#[cfg(debug_assertions)]
+static NEXT_GENERATION: AtomicU64 = AtomicU64::new(GENERATION_RELEASED + 1);
+
+impl<ClosureProto: ?Sized> CallbackUserdata<ClosureProto> {
+    fn new(callback: Box<ClosureProto>) -> Self {
+        Self {
+            #[cfg(debug_assertions)]
+            generation: NEXT_GENERATION.fetch_add(1, Ordering::Relaxed),
+            callback: Some(callback),
+        }
+    }
+
+    /// Release the userdata, destroying the closure. In debug builds, the userdata is poisoned and
+    /// leaked, otherwise it is freed.
+    ///
+    /// In debug builds, panics if already released.
+    fn release(ptr: *mut Self) {
+        assert!(!ptr.is_null());
+        #[cfg(debug_assertions)]
+        {
+            let userdata = unsafe { &mut *ptr };
+            assert!(userdata.generation != GENERATION_RELEASED, "callback userdata released twice");
+            userdata.generation = GENERATION_RELEASED;
+            userdata.callback = None;
+        }
+        #[cfg(not(debug_assertions))]
+        drop(unsafe { Box::from_raw(ptr) });
+    }
+}
+
 /// A saved multi-use callback. Closures of multi-use callbacks (those that may be called multiple
 /// times) need saving, and releasing later at an appropriate time (on change of registered
 /// callback, or on destruction of associated object). This is used for saving the pointer to it
 /// for such deferred destruction.
+///
+/// The pointer given to the C API is that of a [`CallbackUserdata`], tying the closure to the
+/// lifetime of this object (and thus that of the object owning it), with use after release caught
+/// in debug builds.
+///
+/// [`CallbackUserdata`]: struct.CallbackUserdata.html
 pub(crate) struct MultiUseCallback<ClosureProto: ?Sized, ProxyProto> {
-    saved: Option<*mut Box<ClosureProto>>,
+    saved: Option<*mut CallbackUserdata<ClosureProto>>,
     proxy: std::marker::PhantomData<*const ProxyProto>,
 }
 
@@ -73,7 +149,7 @@ impl<ClosureProto: ?Sized, ProxyProto> MultiUseCallback<ClosureProto, ProxyProto
     pub fn new(cb: Option<Box<ClosureProto>>) -> Self {
         match cb {
             Some(f) => MultiUseCallback::<ClosureProto, ProxyProto> {
-                saved: Some(Box::into_raw(Box::new(f))),
+                saved: Some(Box::into_raw(Box::new(CallbackUserdata::new(f)))),
                 proxy: std::marker::PhantomData,
             },
             None => Default::default(),
@@ -92,18 +168,23 @@ impl<ClosureProto: ?Sized, ProxyProto> MultiUseCallback<ClosureProto, ProxyProto
     /// reference is returned, in order to deliberately avoid reclaiming ownership and thus
     /// triggering of destruction.
     ///
-    /// Panics if `ptr` is null.
+    /// Panics if `ptr` is null, or in debug builds, if the userdata has already been released, i.e.
+    /// the callback was replaced, or its owning object destroyed.
     pub fn get_callback<'a>(ptr: *mut c_void) -> &'a mut Box<ClosureProto> {
         assert!(!ptr.is_null());
         // Note, does NOT destroy closure callback after use - only handles pointer
-        unsafe { &mut *(ptr as *mut Box<ClosureProto>) }
+        let userdata = unsafe { &mut *(ptr as *mut CallbackUserdata<ClosureProto>) };
+        #[cfg(debug_assertions)]
+        assert!(userdata.generation != GENERATION_RELEASED, "callback userdata used after \
+            release, i.e. after its owner was destroyed, or the callback replaced");
+        userdata.callback.as_mut().unwrap()
     }
 }
 
 impl<ClosureProto: ?Sized, ProxyProto> Drop for MultiUseCallback<ClosureProto, ProxyProto> {
     fn drop(&mut self) {
-        if self.saved.is_some() {
-            let _to_drop = unsafe { Box::from_raw(self.saved.unwrap()) };
+        if let Some(ptr) = self.saved.take() {
+            CallbackUserdata::release(ptr);
         }
     }
 }
@@ -179,3 +260,50 @@ pub(crate) fn callback_for_list_instance<'a, ClosureProto: ?Sized>(eol: i32, ptr
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestCb = MultiUseCallback<dyn FnMut() -> u32, fn()>;
+
+    #[test]
+    fn multi_use_callback() {
+        let saved = TestCb::new(Some(Box::new(|| 3)));
+        let (_, ptr) = saved.get_capi_params(|| {});
+        assert_eq!((TestCb::get_callback(ptr))(), 3);
+        assert_eq!((TestCb::get_callback(ptr))(), 3);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "used after release")]
+    fn use_after_release() {
+        let saved = TestCb::new(Some(Box::new(|| 3)));
+        let (_, ptr) = saved.get_capi_params(|| {});
+        drop(saved);
+        (TestCb::get_callback(ptr))();
+    }
+
+    /// Test that each userdata instance has its own generation, poisoned upon release.
+    #[test]
+    #[cfg(debug_assertions)]
+    fn generations() {
+        let first = TestCb::new(Some(Box::new(|| 1)));
+        let second = TestCb::new(Some(Box::new(|| 2)));
+        let (_, ptr) = first.get_capi_params(|| {});
+        let (_, other) = second.get_capi_params(|| {});
+        let userdata = ptr as *mut CallbackUserdata<dyn FnMut() -> u32>;
+        let other = other as *mut CallbackUserdata<dyn FnMut() -> u32>;
+        unsafe {
+            assert_ne!((*userdata).generation, GENERATION_RELEASED);
+            assert_ne!((*userdata).generation, (*other).generation);
+        }
+        drop(first);
+        unsafe {
+            assert_eq!((*userdata).generation, GENERATION_RELEASED);
+            assert!((*userdata).callback.is_none());
+        }
+        assert_eq!((TestCb::get_callback(second.get_capi_params(|| {}).1))(), 2);
+    }
+}
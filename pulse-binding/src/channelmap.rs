@@ -35,6 +35,23 @@
 //! * [`Map::init_extend`]: Similar to [`Map::init_auto`] but synthesize a channel map if no
 //!   predefined one is known for the specified number of channels.
 //!
+//! A map can also be created directly from a list of positions with [`Map::new_from_positions`],
+//! or from a position mask with [`Map::new_from_mask`]. The positions of a map can be iterated
+//! over with [`Map::iter`].
+//!
+//! Maps for the common layouts are created with [`Map::mono`], [`Map::stereo`],
+//! [`Map::surround_51`] and [`Map::surround_71`], whose positions are given in [`layouts`], and
+//! the default map of a standard for a number of channels with [`Map::new_auto`]:
+//!
+//! ```rust,ignore
+//! use pulse::channelmap::{Map, MapDef, layouts};
+//!
+//! let map = Map::surround_51();
+//! assert_eq!(map.iter().cloned().collect::<Vec<_>>(), layouts::SURROUND_51);
+//!
+//! let wave = Map::new_auto(6, MapDef::WAVEEx);
+//! ```
+//!
 //! [`Position`]: enum.Position.html
 //! [`Map`]: struct.Map.html
 //! [`Map::init`]: struct.Map.html#method.init
@@ -42,6 +59,15 @@
 //! [`Map::init_stereo`]: struct.Map.html#method.init_stereo
 //! [`Map::init_auto`]: struct.Map.html#method.init_auto
 //! [`Map::init_extend`]: struct.Map.html#method.init_extend
+//! [`Map::new_from_positions`]: struct.Map.html#method.new_from_positions
+//! [`Map::new_from_mask`]: struct.Map.html#method.new_from_mask
+//! [`Map::iter`]: struct.Map.html#method.iter
+//! [`Map::mono`]: struct.Map.html#method.mono
+//! [`Map::stereo`]: struct.Map.html#method.stereo
+//! [`Map::surround_51`]: struct.Map.html#method.surround_51
+//! [`Map::surround_71`]: struct.Map.html#method.surround_71
+//! [`Map::new_auto`]: struct.Map.html#method.new_auto
+//! [`layouts`]: layouts/index.html
 
 use std;
 use capi;
@@ -56,6 +82,42 @@ pub type PositionMask = capi::channelmap::pa_channel_position_mask_t;
 /// Position mask covering all positions
 pub const POSITION_MASK_ALL: PositionMask = 0xffffffffffffffffu64;
 
+/// Positions of the common channel layouts, in channel order.
+///
+/// The surround layouts follow the order of the well-known `surround-51` and `surround-71` maps (as
+/// given by [`Map::to_name`](../struct.Map.html#method.to_name)).
+pub mod layouts {
+    use super::Position;
+
+    /// Mono.
+    pub const MONO: [Position; 1] = [Position::Mono];
+
+    /// Stereo.
+    pub const STEREO: [Position; 2] = [Position::FrontLeft, Position::FrontRight];
+
+    /// 5.1 surround.
+    pub const SURROUND_51: [Position; 6] = [
+        Position::FrontLeft, Position::FrontRight, Position::RearLeft, Position::RearRight,
+        Position::FrontCenter, Position::Lfe,
+    ];
+
+    /// 7.1 surround.
+    pub const SURROUND_71: [Position; 8] = [
+        Position::FrontLeft, Position::FrontRight, Position::RearLeft, Position::RearRight,
+        Position::FrontCenter, Position::Lfe, Position::SideLeft, Position::SideRight,
+    ];
+}
+
+/// Positions of the speakers of Microsoft’s WAVEFORMATEXTENSIBLE channel mask (`dwChannelMask`), in
+/// bit order, i.e. the position of bit 0 (`SPEAKER_FRONT_LEFT`) first.
+pub const WAVE_CHANNEL_POSITIONS: [Position; 18] = [
+    Position::FrontLeft, Position::FrontRight, Position::FrontCenter, Position::Lfe,
+    Position::RearLeft, Position::RearRight, Position::FrontLeftOfCenter,
+    Position::FrontRightOfCenter, Position::RearCenter, Position::SideLeft, Position::SideRight,
+    Position::TopCenter, Position::TopFrontLeft, Position::TopFrontCenter, Position::TopFrontRight,
+    Position::TopRearLeft, Position::TopRearCenter, Position::TopRearRight,
+];
+
 /// A list of channel labels
 ///
 /// Note, certain aliases, specifically `Left`, `Right`, `Center` and `Subwoofer`, available in the
@@ -197,6 +259,15 @@ impl PartialEq for Map {
     }
 }
 
+impl<'a> IntoIterator for &'a Map {
+    type Item = &'a Position;
+    type IntoIter = std::slice::Iter<'a, Position>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
 impl Position {
     /// Makes a bit mask from a channel position.
     pub fn to_mask(self) -> PositionMask {
@@ -250,22 +321,192 @@ impl Map {
         Ok(map)
     }
 
+    /// Create a channel map from a list of positions, one per channel, in channel order.
+    ///
+    /// Returns `None` if the list is empty or holds more than [`::sample::CHANNELS_MAX`]
+    /// positions.
+    ///
+    /// [`::sample::CHANNELS_MAX`]: ../sample/constant.CHANNELS_MAX.html
+    pub fn new_from_positions(positions: &[Position]) -> Option<Self> {
+        if positions.is_empty() || positions.len() > ::sample::CHANNELS_MAX {
+            return None;
+        }
+        let mut map = Self { channels: positions.len() as u8, ..Default::default() };
+        map.map[..positions.len()].copy_from_slice(positions);
+        Some(map)
+    }
+
+    /// Create a channel map from a position mask, as generated by [`get_mask`](#method.get_mask).
+    ///
+    /// Since a mask holds no ordering information, the channels are ordered by position, as
+    /// enumerated in [`Position`](enum.Position.html).
+    ///
+    /// Returns `None` if the mask is empty, includes bits not corresponding to any position, or
+    /// includes more than [`::sample::CHANNELS_MAX`] positions.
+    ///
+    /// [`::sample::CHANNELS_MAX`]: ../sample/constant.CHANNELS_MAX.html
+    pub fn new_from_mask(mask: PositionMask) -> Option<Self> {
+        const POSITION_MAX: u32 = Position::TopRearCenter as u32;
+        if mask == 0 || (mask >> (POSITION_MAX + 1)) != 0 ||
+           mask.count_ones() as usize > ::sample::CHANNELS_MAX
+        {
+            return None;
+        }
+        let mut map = Self::default();
+        for i in 0..=POSITION_MAX {
+            if mask & ((1 as PositionMask) << i) != 0 {
+                map.map[map.channels as usize] =
+                    unsafe { std::mem::transmute::<i32, Position>(i as i32) };
+                map.channels += 1;
+            }
+        }
+        Some(map)
+    }
+
+    /// Create a mono channel map.
+    pub fn mono() -> Self {
+        Self::new_from_positions(&layouts::MONO).unwrap()
+    }
+
+    /// Create a stereo channel map.
+    pub fn stereo() -> Self {
+        Self::new_from_positions(&layouts::STEREO).unwrap()
+    }
+
+    /// Create a 5.1 surround channel map, with the positions of [`layouts::SURROUND_51`].
+    ///
+    /// [`layouts::SURROUND_51`]: layouts/constant.SURROUND_51.html
+    pub fn surround_51() -> Self {
+        Self::new_from_positions(&layouts::SURROUND_51).unwrap()
+    }
+
+    /// Create a 7.1 surround channel map, with the positions of [`layouts::SURROUND_71`].
+    ///
+    /// [`layouts::SURROUND_71`]: layouts/constant.SURROUND_71.html
+    pub fn surround_71() -> Self {
+        Self::new_from_positions(&layouts::SURROUND_71).unwrap()
+    }
+
+    /// Create the default channel map of the given standard for the given number of channels, as
+    /// with [`init_auto`](#method.init_auto).
+    ///
+    /// Returns `None` if there is no default channel map known for this number of channels and
+    /// standard, or the number of channels exceeds [`::sample::CHANNELS_MAX`].
+    ///
+    /// [`::sample::CHANNELS_MAX`]: ../sample/constant.CHANNELS_MAX.html
+    pub fn new_auto(channels: u32, def: MapDef) -> Option<Self> {
+        if channels as usize > ::sample::CHANNELS_MAX {
+            return None;
+        }
+        let mut map = Self::default();
+        map.init_auto(channels, def)?;
+        Some(map)
+    }
+
+    /// Create the default channel map of the given standard for the given number of channels, as
+    /// with [`init_extend`](#method.init_extend), synthesizing one if no default is known.
+    ///
+    /// Returns `None` if the number of channels exceeds [`::sample::CHANNELS_MAX`].
+    ///
+    /// [`::sample::CHANNELS_MAX`]: ../sample/constant.CHANNELS_MAX.html
+    pub fn new_extend(channels: u32, def: MapDef) -> Option<Self> {
+        if channels as usize > ::sample::CHANNELS_MAX {
+            return None;
+        }
+        let mut map = Self::default();
+        map.init_extend(channels, def);
+        Some(map)
+    }
+
+    /// Create a channel map from a WAVEFORMATEXTENSIBLE channel mask (`dwChannelMask`), for the
+    /// given number of channels.
+    ///
+    /// As per the WAVE format, the channels are assigned the positions of the bits set, in bit
+    /// order; channels beyond those are assigned auxiliary positions, while bits set beyond the
+    /// number of channels are ignored. A mono channel with only the front center bit set is given
+    /// the [`Position::Mono`] position.
+    ///
+    /// Returns `None` if the mask is zero (i.e. unspecified, for which the default mapping can be
+    /// had with [`new_auto`] and [`MapDef::WAVEEx`]), or the number of channels is zero or exceeds
+    /// [`::sample::CHANNELS_MAX`].
+    ///
+    /// [`Position::Mono`]: enum.Position.html#variant.Mono
+    /// [`new_auto`]: #method.new_auto
+    /// [`MapDef::WAVEEx`]: enum.MapDef.html#variant.WAVEEx
+    /// [`::sample::CHANNELS_MAX`]: ../sample/constant.CHANNELS_MAX.html
+    pub fn from_wave_channel_mask(mask: u32, channels: u8) -> Option<Self> {
+        if mask == 0 || channels == 0 || channels as usize > ::sample::CHANNELS_MAX {
+            return None;
+        }
+        if channels == 1 && mask == 0x4 {
+            return Self::new_from_positions(&[Position::Mono]);
+        }
+        let mut map = Self { channels: channels, ..Default::default() };
+        let mut assigned = WAVE_CHANNEL_POSITIONS.iter().enumerate()
+            .filter(|&(bit, _)| mask & (1 << bit) != 0)
+            .map(|(_, &p)| p);
+        let mut aux = 0;
+        for slot in map.map[..channels as usize].iter_mut() {
+            *slot = match assigned.next() {
+                Some(p) => p,
+                None => {
+                    aux += 1;
+                    unsafe { std::mem::transmute::<i32, Position>(Position::Aux0 as i32 + aux - 1) }
+                },
+            };
+        }
+        Some(map)
+    }
+
+    /// Get the WAVEFORMATEXTENSIBLE channel mask (`dwChannelMask`) corresponding to the map, as
+    /// the inverse of [`from_wave_channel_mask`](#method.from_wave_channel_mask).
+    ///
+    /// Returns `None` if the map cannot be represented with a mask, i.e. it has a position with no
+    /// WAVE equivalent, or the positions are not in the order of their bits (the order required by
+    /// the WAVE format), other than auxiliary positions following all others.
+    pub fn to_wave_channel_mask(&self) -> Option<u32> {
+        if self.channels == 1 && self.map[0] == Position::Mono {
+            return Some(0x4);
+        }
+        let aux = (Position::Aux0 as i32)..=(Position::Aux31 as i32);
+        let mut mask = 0u32;
+        let mut in_aux = false;
+        for &position in self.iter() {
+            if aux.contains(&(position as i32)) {
+                in_aux = true;
+                continue;
+            }
+            let bit = WAVE_CHANNEL_POSITIONS.iter().position(|&p| p == position)?;
+            if in_aux || mask >> bit != 0 {
+                return None;
+            }
+            mask |= 1 << bit;
+        }
+        Some(mask)
+    }
+
+    /// Returns an iterator over the positions of the mapped channels, in channel order.
+    pub fn iter(&self) -> std::slice::Iter<'_, Position> {
+        let channels = std::cmp::min(self.channels as usize, ::sample::CHANNELS_MAX);
+        self.map[..channels].iter()
+    }
+
     /// Initialize the specified channel map and return a pointer to it. The map will have a defined
     /// state but [`is_valid`](#method.is_valid) will fail for it.
     pub fn init(&mut self) -> &mut Self {
-        unsafe { capi::pa_channel_map_init(std::mem::transmute(&self)) };
+        unsafe { capi::pa_channel_map_init(self as *mut Map as *mut capi::pa_channel_map) };
         self
     }
 
     /// Initialize the specified channel map for monaural audio and return a pointer to it.
     pub fn init_mono(&mut self) -> &mut Self {
-        unsafe { capi::pa_channel_map_init_mono(std::mem::transmute(&self)) };
+        unsafe { capi::pa_channel_map_init_mono(self as *mut Map as *mut capi::pa_channel_map) };
         self
     }
 
     /// Initialize the specified channel map for stereophonic audio and return a pointer to it.
     pub fn init_stereo(&mut self) -> &mut Self {
-        unsafe { capi::pa_channel_map_init_stereo(std::mem::transmute(&self)) };
+        unsafe { capi::pa_channel_map_init_stereo(self as *mut Map as *mut capi::pa_channel_map) };
         self
     }
 
@@ -277,7 +518,8 @@ impl Map {
     pub fn init_auto(&mut self, channels: u32, def: MapDef) -> Option<&mut Self> {
         debug_assert!(channels as usize <= ::sample::CHANNELS_MAX);
         unsafe {
-            if capi::pa_channel_map_init_auto(std::mem::transmute(&self), channels, def).is_null() {
+            let ptr = self as *mut Map as *mut capi::pa_channel_map;
+            if capi::pa_channel_map_init_auto(ptr, channels, def).is_null() {
                 return None;
             }
         }
@@ -289,7 +531,8 @@ impl Map {
     /// with fewer channels and fill up the rest with AUX0...AUX31 channels.
     pub fn init_extend(&mut self, channels: u32, def: MapDef) -> &mut Self {
         debug_assert!(channels as usize <= ::sample::CHANNELS_MAX);
-        unsafe { capi::pa_channel_map_init_extend(std::mem::transmute(&self), channels, def) };
+        let ptr = self as *mut Map as *mut capi::pa_channel_map;
+        unsafe { capi::pa_channel_map_init_extend(ptr, channels, def) };
         self
     }
 
@@ -376,3 +619,37 @@ impl Map {
         unsafe { capi::pa_channel_map_mask(std::mem::transmute(self)) }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wave_channel_mask() {
+        // 5.1 (`KSAUDIO_SPEAKER_5POINT1`), with one extra channel
+        let map = Map::from_wave_channel_mask(0x3f, 7).unwrap();
+        assert_eq!(map.iter().cloned().collect::<Vec<_>>(), vec![Position::FrontLeft,
+            Position::FrontRight, Position::FrontCenter, Position::Lfe, Position::RearLeft,
+            Position::RearRight, Position::Aux0]);
+        assert_eq!(map.to_wave_channel_mask(), Some(0x3f));
+
+        assert_eq!(Map::from_wave_channel_mask(0x3, 1).unwrap().to_wave_channel_mask(), Some(0x1));
+        assert_eq!(Map::from_wave_channel_mask(0x4, 1).unwrap().map[0], Position::Mono);
+        assert!(Map::from_wave_channel_mask(0, 2).is_none());
+
+        let swapped = Map::new_from_positions(&[Position::FrontRight, Position::FrontLeft]);
+        assert_eq!(swapped.unwrap().to_wave_channel_mask(), None);
+        assert_eq!(Map::stereo().to_wave_channel_mask(), Some(0x3));
+    }
+
+    #[test]
+    fn new_auto() {
+        assert_eq!(Map::new_auto(2, MapDef::WAVEEx), Some(Map::stereo()));
+        assert_eq!(Map::new_extend(2, MapDef::WAVEEx), Some(Map::stereo()));
+        assert!(Map::new_auto(::sample::CHANNELS_MAX as u32 + 1, MapDef::WAVEEx).is_none());
+
+        let mut map = Map::default();
+        map.init_stereo();
+        assert_eq!(map, Map::stereo());
+    }
+}
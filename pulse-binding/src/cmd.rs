@@ -0,0 +1,602 @@
+// Copyright 2018 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Scriptable `pactl`-like commands (requires the `cmd` feature).
+//!
+//! # Overview
+//!
+//! Applications may wish to let users script audio actions, such as binding a key to raising the
+//! volume, in the familiar form of `pactl` commands, without shelling out to `pactl` itself. A
+//! [`Command`] is parsed from such a string with [`Command::parse`], then executed with
+//! [`Command::run`], which issues the corresponding introspection calls.
+//!
+//! ```rust,ignore
+//! use pulse::cmd::Command;
+//!
+//! let command = Command::parse("set-sink-volume @DEFAULT_SINK@ +5%")?;
+//! command.run(&mut context, |result| {
+//!     if let Err(e) = result {
+//!         eprintln!("command failed: {}", e);
+//!     }
+//! });
+//! ```
+//!
+//! The following commands are supported, with the same arguments as `pactl`:
+//!
+//! * `set-sink-volume`, `set-source-volume`, `set-sink-input-volume`, `set-source-output-volume`
+//! * `set-sink-mute`, `set-source-mute`, `set-sink-input-mute`, `set-source-output-mute`
+//! * `set-default-sink`, `set-default-source`
+//! * `move-sink-input`, `move-source-output`
+//! * `set-sink-port`, `set-source-port`, `set-card-profile`
+//! * `suspend-sink`, `suspend-source`
+//! * `kill-sink-input`, `kill-source-output`, `kill-client`
+//! * `load-module`, `unload-module`
+//!
+//! Sinks and sources may be given by name or index, or as `@DEFAULT_SINK@` or `@DEFAULT_SOURCE@`,
+//! which are resolved to the current default device. Volumes are given as a [`VolumeSpec`], for
+//! instance `80%`, `-6dB` or `+5%`. Mute switches may be given as a boolean or as `toggle`.
+//!
+//! [`Command`]: enum.Command.html
+//! [`Command::parse`]: enum.Command.html#method.parse
+//! [`Command::run`]: enum.Command.html#method.run
+//! [`VolumeSpec`]: ../volume/struct.VolumeSpec.html
+
+use std;
+use callbacks::ListEntry;
+use context::{Context, ContextRef};
+use context::entity::AudioEntity;
+use context::introspect::{Introspector, SinkInfo, SourceInfo, SinkInputInfo, SourceOutputInfo};
+use error::{Code, PAErr};
+use volume::{ChannelVolumes, VolumeSpec};
+
+/// Name standing for the default sink.
+pub const DEFAULT_SINK: &str = "@DEFAULT_SINK@";
+/// Name standing for the default source.
+pub const DEFAULT_SOURCE: &str = "@DEFAULT_SOURCE@";
+
+/// A sink, source or card, as named in a command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// The default sink or source.
+    Default,
+    /// By name.
+    Name(String),
+    /// By index.
+    Index(u32),
+}
+
+impl Target {
+    fn parse(arg: &str, default: Option<&str>) -> Self {
+        if Some(arg) == default {
+            return Target::Default;
+        }
+        match arg.parse::<u32>() {
+            Ok(index) => Target::Index(index),
+            Err(_) => Target::Name(arg.to_string()),
+        }
+    }
+}
+
+/// An object having a volume and mute switch, as named in a command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Object {
+    /// A sink.
+    Sink(Target),
+    /// A source.
+    Source(Target),
+    /// A sink input, by index.
+    SinkInput(u32),
+    /// A source output, by index.
+    SourceOutput(u32),
+}
+
+/// A mute switch change, as given in a command.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MuteArg {
+    /// Set the switch.
+    Set(bool),
+    /// Toggle the switch.
+    Toggle,
+}
+
+/// Parse a boolean argument, as `pactl` does.
+fn parse_bool(arg: &str) -> Option<bool> {
+    match arg.to_lowercase().as_str() {
+        "1" | "y" | "yes" | "t" | "true" | "on" => Some(true),
+        "0" | "n" | "no" | "f" | "false" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+/// A command, as parsed with [`parse`](#method.parse).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Change the volume of a sink, source, sink input or source output.
+    SetVolume(Object, VolumeSpec),
+    /// Change the mute switch of a sink, source, sink input or source output.
+    SetMute(Object, MuteArg),
+    /// Set the default sink, by name.
+    SetDefaultSink(String),
+    /// Set the default source, by name.
+    SetDefaultSource(String),
+    /// Move a sink input, by index, to a sink.
+    MoveSinkInput(u32, Target),
+    /// Move a source output, by index, to a source.
+    MoveSourceOutput(u32, Target),
+    /// Change the active port of a sink.
+    SetSinkPort(Target, String),
+    /// Change the active port of a source.
+    SetSourcePort(Target, String),
+    /// Change the profile of a card.
+    SetCardProfile(Target, String),
+    /// Suspend or resume a sink.
+    SuspendSink(Target, bool),
+    /// Suspend or resume a source.
+    SuspendSource(Target, bool),
+    /// Kill a sink input, by index.
+    KillSinkInput(u32),
+    /// Kill a source output, by index.
+    KillSourceOutput(u32),
+    /// Kill a client, by index.
+    KillClient(u32),
+    /// Load a module, with the given name and arguments.
+    LoadModule(String, String),
+    /// Unload a module, by index.
+    UnloadModule(u32),
+}
+
+/// Failure to parse a command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The command is empty.
+    Empty,
+    /// The command is not supported.
+    UnknownCommand(String),
+    /// A required argument, described, is missing.
+    MissingArgument(&'static str),
+    /// An argument is invalid.
+    InvalidArgument(String),
+    /// More arguments were given than the command takes.
+    TooManyArguments,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            ParseError::Empty => write!(f, "no command given"),
+            ParseError::UnknownCommand(ref c) => write!(f, "unknown command: {}", c),
+            ParseError::MissingArgument(a) => write!(f, "missing argument: {}", a),
+            ParseError::InvalidArgument(ref a) => write!(f, "invalid argument: {}", a),
+            ParseError::TooManyArguments => write!(f, "too many arguments"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Arguments of a command being parsed.
+struct Args<'a> {
+    args: std::str::SplitWhitespace<'a>,
+}
+
+impl<'a> Args<'a> {
+    fn next(&mut self, what: &'static str) -> Result<&'a str, ParseError> {
+        self.args.next().ok_or(ParseError::MissingArgument(what))
+    }
+
+    fn index(&mut self, what: &'static str) -> Result<u32, ParseError> {
+        let arg = self.next(what)?;
+        arg.parse().map_err(|_| ParseError::InvalidArgument(arg.to_string()))
+    }
+
+    fn boolean(&mut self, what: &'static str) -> Result<bool, ParseError> {
+        let arg = self.next(what)?;
+        parse_bool(arg).ok_or_else(|| ParseError::InvalidArgument(arg.to_string()))
+    }
+
+    fn mute(&mut self) -> Result<MuteArg, ParseError> {
+        match self.next("mute")? {
+            "toggle" => Ok(MuteArg::Toggle),
+            arg => parse_bool(arg).map(MuteArg::Set)
+                .ok_or_else(|| ParseError::InvalidArgument(arg.to_string())),
+        }
+    }
+
+    fn volume(&mut self) -> Result<VolumeSpec, ParseError> {
+        let rest: Vec<&str> = self.args.by_ref().collect();
+        if rest.is_empty() {
+            return Err(ParseError::MissingArgument("volume"));
+        }
+        let rest = rest.join(" ");
+        rest.parse().map_err(|_| ParseError::InvalidArgument(rest))
+    }
+
+    fn end(&mut self) -> Result<(), ParseError> {
+        match self.args.next() {
+            Some(_) => Err(ParseError::TooManyArguments),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Command {
+    /// Parse a command, as given to `pactl` (without the `pactl` itself).
+    ///
+    /// See the [module level documentation](index.html) for the commands supported.
+    pub fn parse(command: &str) -> Result<Self, ParseError> {
+        let mut args = Args { args: command.split_whitespace() };
+        let name = args.args.next().ok_or(ParseError::Empty)?;
+        let sink = |arg: &str| Target::parse(arg, Some(DEFAULT_SINK));
+        let source = |arg: &str| Target::parse(arg, Some(DEFAULT_SOURCE));
+        let cmd = match name {
+            "set-sink-volume" => Command::SetVolume(Object::Sink(sink(args.next("sink")?)),
+                args.volume()?),
+            "set-source-volume" => Command::SetVolume(Object::Source(source(args.next("source")?)),
+                args.volume()?),
+            "set-sink-input-volume" => Command::SetVolume(Object::SinkInput(args.index("index")?),
+                args.volume()?),
+            "set-source-output-volume" => {
+                Command::SetVolume(Object::SourceOutput(args.index("index")?), args.volume()?)
+            },
+            "set-sink-mute" => Command::SetMute(Object::Sink(sink(args.next("sink")?)),
+                args.mute()?),
+            "set-source-mute" => Command::SetMute(Object::Source(source(args.next("source")?)),
+                args.mute()?),
+            "set-sink-input-mute" => Command::SetMute(Object::SinkInput(args.index("index")?),
+                args.mute()?),
+            "set-source-output-mute" => {
+                Command::SetMute(Object::SourceOutput(args.index("index")?), args.mute()?)
+            },
+            "set-default-sink" => Command::SetDefaultSink(args.next("sink")?.to_string()),
+            "set-default-source" => Command::SetDefaultSource(args.next("source")?.to_string()),
+            "move-sink-input" => Command::MoveSinkInput(args.index("index")?,
+                sink(args.next("sink")?)),
+            "move-source-output" => Command::MoveSourceOutput(args.index("index")?,
+                source(args.next("source")?)),
+            "set-sink-port" => Command::SetSinkPort(sink(args.next("sink")?),
+                args.next("port")?.to_string()),
+            "set-source-port" => Command::SetSourcePort(source(args.next("source")?),
+                args.next("port")?.to_string()),
+            "set-card-profile" => Command::SetCardProfile(Target::parse(args.next("card")?, None),
+                args.next("profile")?.to_string()),
+            "suspend-sink" => Command::SuspendSink(sink(args.next("sink")?),
+                args.boolean("suspend")?),
+            "suspend-source" => Command::SuspendSource(source(args.next("source")?),
+                args.boolean("suspend")?),
+            "kill-sink-input" => Command::KillSinkInput(args.index("index")?),
+            "kill-source-output" => Command::KillSourceOutput(args.index("index")?),
+            "kill-client" => Command::KillClient(args.index("index")?),
+            "load-module" => {
+                let module = args.next("module")?.to_string();
+                let arguments: Vec<&str> = args.args.by_ref().collect();
+                Command::LoadModule(module, arguments.join(" "))
+            },
+            "unload-module" => Command::UnloadModule(args.index("index")?),
+            _ => return Err(ParseError::UnknownCommand(name.to_string())),
+        };
+        args.end()?;
+        Ok(cmd)
+    }
+
+    /// Execute the command.
+    ///
+    /// The callback is given the outcome once the command has completed, with the error should
+    /// any step fail; for instance [`Code::NoEntity`] if a device does not exist, or there is no
+    /// default device.
+    ///
+    /// Panics on error, i.e. invalid arguments or state.
+    ///
+    /// [`Code::NoEntity`]: ../error/enum.Code.html#variant.NoEntity
+    pub fn run<F>(&self, context: &mut Context, callback: F)
+        where F: FnOnce(Result<(), PAErr>) + 'static
+    {
+        let owner = context;
+        let context = Context::new_ref(owner.ptr);
+        let callback: Callback = Box::new(callback);
+        match self.clone() {
+            Command::SetVolume(object, volume) => {
+                entity(&context, object, move |entity| with_state(entity,
+                    move |entity, mut new: ChannelVolumes, _| {
+                        new.apply(&volume)?;
+                        op(move |introspector, done| {
+                            drop(introspector.set_volume(entity, &new, Some(done)));
+                        })
+                    }), callback);
+            },
+            Command::SetMute(object, MuteArg::Set(mute)) => {
+                entity(&context, object, move |entity| op(
+                    move |introspector, done| {
+                        drop(introspector.set_mute(entity, mute, Some(done)));
+                    }), callback);
+            },
+            Command::SetMute(object, MuteArg::Toggle) => {
+                entity(&context, object, move |entity| with_state(entity,
+                    move |entity, _, mute| op(move |introspector, done| {
+                        drop(introspector.set_mute(entity, !mute, Some(done)));
+                    })), callback);
+            },
+            Command::SetDefaultSink(name) => {
+                drop(owner.set_default_sink(&name, completion(context, callback)));
+            },
+            Command::SetDefaultSource(name) => {
+                drop(owner.set_default_source(&name, completion(context, callback)));
+            },
+            Command::MoveSinkInput(index, sink) => {
+                device(&context, sink, false, move |sink| op(
+                    move |introspector, done| {
+                        drop(introspector.move_sink_input_by_index(index, sink, Some(done)));
+                    }), callback);
+            },
+            Command::MoveSourceOutput(index, source) => {
+                device(&context, source, true, move |source| op(
+                    move |introspector, done| {
+                        drop(introspector.move_source_output_by_index(index, source, Some(done)));
+                    }), callback);
+            },
+            Command::SetSinkPort(sink, port) => {
+                device(&context, sink, false, move |sink| op(
+                    move |introspector, done| {
+                        drop(introspector.set_sink_port_by_index(sink, &port, Some(done)));
+                    }), callback);
+            },
+            Command::SetSourcePort(source, port) => {
+                device(&context, source, true, move |source| op(
+                    move |introspector, done| {
+                        drop(introspector.set_source_port_by_index(source, &port, Some(done)));
+                    }), callback);
+            },
+            Command::SetCardProfile(card, profile) => {
+                let step = match card {
+                    Target::Index(index) => op(move |introspector, done| {
+                        drop(introspector.set_card_profile_by_index(index, &profile, Some(done)));
+                    }),
+                    Target::Name(name) => op(move |introspector, done| {
+                        drop(introspector.set_card_profile_by_name(&name, &profile, Some(done)));
+                    }),
+                    // There is no default card
+                    Target::Default => Err(PAErr::from(Code::Invalid)),
+                };
+                issue(context, step, callback);
+            },
+            Command::SuspendSink(sink, suspend) => {
+                device(&context, sink, false, move |sink| op(
+                    move |introspector, done| {
+                        drop(introspector.suspend_sink_by_index(sink, suspend, Some(done)));
+                    }), callback);
+            },
+            Command::SuspendSource(source, suspend) => {
+                device(&context, source, true, move |source| op(
+                    move |introspector, done| {
+                        drop(introspector.suspend_source_by_index(source, suspend, Some(done)));
+                    }), callback);
+            },
+            Command::KillSinkInput(index) => issue(context, op(move |introspector, done| {
+                drop(introspector.kill_sink_input(index, done));
+            }), callback),
+            Command::KillSourceOutput(index) => issue(context, op(move |introspector, done| {
+                drop(introspector.kill_source_output(index, done));
+            }), callback),
+            Command::KillClient(index) => issue(context, op(move |introspector, done| {
+                drop(introspector.kill_client(index, done));
+            }), callback),
+            Command::LoadModule(name, arguments) => {
+                issue(context, op(move |introspector, mut done| {
+                    drop(introspector.load_module(&name, &arguments, move |index| {
+                        done(index != ::def::INVALID_INDEX);
+                    }));
+                }), callback);
+            },
+            Command::UnloadModule(index) => issue(context, op(move |introspector, done| {
+                drop(introspector.unload_module(index, done));
+            }), callback),
+        }
+    }
+}
+
+/// The callback given the outcome of a command.
+type Callback = Box<dyn FnOnce(Result<(), PAErr>)>;
+
+/// The final step of a command, issued with the context, giving the outcome to the callback.
+type Step = Box<dyn FnOnce(ContextRef, Callback)>;
+
+/// Create a step issuing a single operation, given an introspector and the success callback to
+/// pass on.
+fn op<O>(operation: O) -> Result<Step, PAErr>
+    where O: FnOnce(&mut Introspector, Box<dyn FnMut(bool)>) + 'static
+{
+    Ok(Box::new(move |context: ContextRef, callback: Callback| {
+        let mut introspector = context.introspect();
+        operation(&mut introspector, completion(context, callback));
+    }))
+}
+
+/// Create the success callback of the final step, giving the outcome to the callback.
+fn completion(context: ContextRef, callback: Callback) -> Box<dyn FnMut(bool)> {
+    let mut callback = Some(callback);
+    Box::new(move |success| {
+        if let Some(callback) = callback.take() {
+            callback(match success {
+                true => Ok(()),
+                false => Err(context.errno()),
+            });
+        }
+    })
+}
+
+/// Issue the step, or give its error to the callback.
+fn issue(context: ContextRef, step: Result<Step, PAErr>, callback: Callback) {
+    match step {
+        Ok(step) => step(context, callback),
+        Err(e) => callback(Err(e)),
+    }
+}
+
+/// Create a callback for looking up the index of a device by name, from its info.
+macro_rules! index_cb {
+    ($then:expr, $info:ident) => {{
+        let mut then = Some($then);
+        move |result: Result<ListEntry<&$info>, PAErr>| {
+            let result = match result {
+                Ok(ListEntry::Item(i)) => Ok(i.index),
+                Ok(ListEntry::End) => Err(PAErr::from(Code::NoEntity)),
+                Err(e) => Err(e),
+            };
+            if let Some(then) = then.take() {
+                then(result);
+            }
+        }
+    }};
+}
+
+/// Resolve a sink or source to its index, then issue the step given by `then`.
+fn device<T>(context: &ContextRef, target: Target, source: bool, then: T, callback: Callback)
+    where T: FnOnce(u32) -> Result<Step, PAErr> + 'static
+{
+    let context2 = Context::new_ref(context.ptr);
+    let resolved = move |index: Result<u32, PAErr>| {
+        let step = index.and_then(then);
+        issue(context2, step, callback);
+    };
+    match target {
+        Target::Index(index) => resolved(Ok(index)),
+        Target::Name(name) => by_name(context, &name, source, resolved),
+        Target::Default => {
+            let context2 = Context::new_ref(context.ptr);
+            let mut resolved = Some(resolved);
+            drop(context.introspect().get_server_info(move |info| {
+                let name = match source {
+                    true => info.default_source_name.as_ref(),
+                    false => info.default_sink_name.as_ref(),
+                };
+                if let Some(resolved) = resolved.take() {
+                    match name {
+                        Some(name) => by_name(&context2, name, source, resolved),
+                        None => resolved(Err(PAErr::from(Code::NoEntity))),
+                    }
+                }
+            }));
+        },
+    }
+}
+
+/// Look up the index of a sink or source by name.
+fn by_name<T>(context: &ContextRef, name: &str, source: bool, then: T)
+    where T: FnOnce(Result<u32, PAErr>) + 'static
+{
+    let introspector = context.introspect();
+    match source {
+        true => drop(introspector.get_source_info_by_name(name, index_cb!(then, SourceInfo))),
+        false => drop(introspector.get_sink_info_by_name(name, index_cb!(then, SinkInfo))),
+    }
+}
+
+/// Resolve an object to an entity, then issue the step given by `then`.
+fn entity<T>(context: &ContextRef, object: Object, then: T, callback: Callback)
+    where T: FnOnce(AudioEntity) -> Result<Step, PAErr> + 'static
+{
+    match object {
+        Object::Sink(target) => {
+            device(context, target, false, move |i| then(AudioEntity::Sink(i)), callback)
+        },
+        Object::Source(target) => {
+            device(context, target, true, move |i| then(AudioEntity::Source(i)), callback)
+        },
+        Object::SinkInput(index) => {
+            issue(Context::new_ref(context.ptr), then(AudioEntity::SinkInput(index)), callback)
+        },
+        Object::SourceOutput(index) => {
+            issue(Context::new_ref(context.ptr), then(AudioEntity::SourceOutput(index)), callback)
+        },
+    }
+}
+
+/// Create a callback for fetching the volume and mute switch of an entity, from its info.
+macro_rules! state_cb {
+    ($then:expr, $info:ident) => {{
+        let mut then = Some($then);
+        move |result: Result<ListEntry<&$info>, PAErr>| {
+            let result = match result {
+                Ok(ListEntry::Item(i)) => Ok((i.volume, i.mute)),
+                Ok(ListEntry::End) => Err(PAErr::from(Code::NoEntity)),
+                Err(e) => Err(e),
+            };
+            if let Some(then) = then.take() {
+                then(result);
+            }
+        }
+    }};
+}
+
+/// Create a step fetching the current volume and mute switch of an entity, then issuing the step
+/// given by `then`.
+fn with_state<T>(entity: AudioEntity, then: T) -> Result<Step, PAErr>
+    where T: FnOnce(AudioEntity, ChannelVolumes, bool) -> Result<Step, PAErr> + 'static
+{
+    Ok(Box::new(move |context: ContextRef, callback: Callback| {
+        let introspector = context.introspect();
+        let fetched = move |state: Result<(ChannelVolumes, bool), PAErr>| {
+            let step = state.and_then(|(volume, mute)| then(entity, volume, mute));
+            issue(context, step, callback);
+        };
+        match entity {
+            AudioEntity::Sink(index) => {
+                drop(introspector.get_sink_info_by_index(index, state_cb!(fetched, SinkInfo)))
+            },
+            AudioEntity::Source(index) => {
+                drop(introspector.get_source_info_by_index(index, state_cb!(fetched, SourceInfo)))
+            },
+            AudioEntity::SinkInput(index) => {
+                drop(introspector.get_sink_input_info(index, state_cb!(fetched, SinkInputInfo)))
+            },
+            AudioEntity::SourceOutput(index) => drop(introspector.get_source_output_info(index,
+                state_cb!(fetched, SourceOutputInfo))),
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_commands() {
+        assert_eq!(Command::parse("set-sink-volume @DEFAULT_SINK@ +5%"), Ok(Command::SetVolume(
+            Object::Sink(Target::Default), "+5%".parse().unwrap())));
+        assert_eq!(Command::parse("set-source-volume mic 0.5 -3dB"),
+            Err(ParseError::InvalidArgument("0.5 -3dB".to_string())));
+        assert!(Command::parse("set-source-volume 2 65536 -6dB").is_err());
+        assert_eq!(Command::parse("set-sink-input-volume 12 80% 1.5"), Ok(Command::SetVolume(
+            Object::SinkInput(12), "80% 1.5".parse().unwrap())));
+        assert_eq!(Command::parse("set-source-mute @DEFAULT_SOURCE@ toggle"),
+            Ok(Command::SetMute(Object::Source(Target::Default), MuteArg::Toggle)));
+        assert_eq!(Command::parse("set-sink-mute alsa_output.foo off"),
+            Ok(Command::SetMute(Object::Sink(Target::Name("alsa_output.foo".into())),
+                MuteArg::Set(false))));
+        assert_eq!(Command::parse("  move-sink-input 7   3 "),
+            Ok(Command::MoveSinkInput(7, Target::Index(3))));
+        assert_eq!(Command::parse("load-module module-null-sink sink_name=foo rate=48000"),
+            Ok(Command::LoadModule("module-null-sink".into(),
+                "sink_name=foo rate=48000".into())));
+
+        assert_eq!(Command::parse(""), Err(ParseError::Empty));
+        assert_eq!(Command::parse("list sinks"),
+            Err(ParseError::UnknownCommand("list".into())));
+        assert_eq!(Command::parse("set-sink-volume 0"),
+            Err(ParseError::MissingArgument("volume")));
+        assert_eq!(Command::parse("kill-client foo"),
+            Err(ParseError::InvalidArgument("foo".into())));
+        assert_eq!(Command::parse("unload-module 3 4"), Err(ParseError::TooManyArguments));
+    }
+}
@@ -0,0 +1,339 @@
+// Copyright 2017 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Per-application volume persistence.
+//!
+//! # Overview
+//!
+//! The stream-restore module of the server remembers the volume, mute state and device of streams,
+//! applying them to new streams of the same identity. Entries are stored under keys derived from
+//! the stream’s properties, by rules that are easily got wrong when using the
+//! [stream restore extension](../ext_stream_restore/index.html) directly. For a playback stream,
+//! the first of the following properties present determines the key:
+//!
+//! * `module-stream-restore.id`, giving `sink-input-by-module-stream-restore.id:<id>`.
+//! * `media.role`, giving `sink-input-by-media-role:<role>`.
+//! * `application.id`, giving `sink-input-by-application-id:<id>`.
+//! * `application.name`, giving `sink-input-by-application-name:<name>`.
+//! * `media.name`, giving `sink-input-by-media-name:<name>`.
+//!
+//! Record streams use the `source-output` prefix likewise. Note in particular that streams with a
+//! media role are stored by role, not by application. [`AppIdentity`] represents these
+//! identities, with [`AppIdentity::from_proplist`] applying the rules to a stream’s properties.
+//!
+//! [`AppVolumeStore`] reads and writes entries, with [`AppVolumeStore::get`] and
+//! [`AppVolumeStore::set`] offering the common case of playback streams identified by application
+//! ID:
+//!
+//! ```rust,ignore
+//! use pulse::context::app_volume::AppVolumeStore;
+//!
+//! let store = AppVolumeStore::new(&context);
+//! store.set("org.example.Player", &volume, Some("alsa_output.usb-headset"), |success| {
+//!     // ...
+//! });
+//! store.get("org.example.Player", |result| {
+//!     if let Ok(Some(entry)) = result {
+//!         println!("stored volume: {}", entry.volume.print());
+//!     }
+//! });
+//! ```
+//!
+//! [`AppIdentity`]: enum.AppIdentity.html
+//! [`AppIdentity::from_proplist`]: enum.AppIdentity.html#method.from_proplist
+//! [`AppVolumeStore`]: struct.AppVolumeStore.html
+//! [`AppVolumeStore::get`]: struct.AppVolumeStore.html#method.get
+//! [`AppVolumeStore::set`]: struct.AppVolumeStore.html#method.set
+
+use capi;
+use std::borrow::Cow;
+use std::rc::Rc;
+use callbacks::ListResult;
+use channelmap::{Map, MapDef};
+use error::PAErr;
+use proplist::{Proplist, UpdateMode, properties};
+use volume::ChannelVolumes;
+use super::{Context, ContextRef};
+use super::ext_stream_restore::Info;
+
+/// Property overriding the identity under which a stream is stored.
+pub const IDENTIFICATION_PROPERTY: &str = "module-stream-restore.id";
+
+/// Direction of the streams an entry applies to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum StreamKind {
+    /// Playback streams (sink inputs).
+    Playback,
+    /// Record streams (source outputs).
+    Record,
+}
+
+impl StreamKind {
+    /// Get the key prefix of the kind of stream.
+    pub fn prefix(self) -> &'static str {
+        match self {
+            StreamKind::Playback => "sink-input",
+            StreamKind::Record => "source-output",
+        }
+    }
+}
+
+/// Identity under which the streams of an application are stored.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AppIdentity {
+    /// Explicit identity (the `module-stream-restore.id` property).
+    Custom(String),
+    /// Media role (the `media.role` property).
+    MediaRole(String),
+    /// Application ID (the `application.id` property).
+    ApplicationId(String),
+    /// Application name (the `application.name` property).
+    ApplicationName(String),
+    /// Media name (the `media.name` property).
+    MediaName(String),
+}
+
+/// Key infixes of the identities, following the kind prefix, in order of precedence.
+const INFIXES: &[&str] = &[
+    "-by-module-stream-restore.id:",
+    "-by-media-role:",
+    "-by-application-id:",
+    "-by-application-name:",
+    "-by-media-name:",
+];
+
+impl AppIdentity {
+    /// Determine the identity of a stream with the given properties, as the server does, or `None`
+    /// if it has none of the identifying properties.
+    pub fn from_proplist(proplist: &Proplist) -> Option<Self> {
+        let keys = [IDENTIFICATION_PROPERTY, properties::MEDIA_ROLE, properties::APPLICATION_ID,
+            properties::APPLICATION_NAME, properties::MEDIA_NAME];
+        keys.iter().enumerate()
+            .filter_map(|(i, key)| proplist.gets(key).map(|value| (i, value)))
+            .next()
+            .map(|(i, value)| Self::from_parts(i, value))
+    }
+
+    fn from_parts(i: usize, value: String) -> Self {
+        match i {
+            0 => AppIdentity::Custom(value),
+            1 => AppIdentity::MediaRole(value),
+            2 => AppIdentity::ApplicationId(value),
+            3 => AppIdentity::ApplicationName(value),
+            _ => AppIdentity::MediaName(value),
+        }
+    }
+
+    fn parts(&self) -> (usize, &str) {
+        match *self {
+            AppIdentity::Custom(ref v) => (0, v),
+            AppIdentity::MediaRole(ref v) => (1, v),
+            AppIdentity::ApplicationId(ref v) => (2, v),
+            AppIdentity::ApplicationName(ref v) => (3, v),
+            AppIdentity::MediaName(ref v) => (4, v),
+        }
+    }
+
+    /// Get the key of the entry for streams of the given kind with this identity.
+    pub fn key(&self, kind: StreamKind) -> String {
+        let (i, value) = self.parts();
+        format!("{}{}{}", kind.prefix(), INFIXES[i], value)
+    }
+
+    /// Parse an entry key, as given by [`key`](#method.key), giving the kind of stream and
+    /// identity.
+    ///
+    /// Returns `None` for keys of other forms, such as fallback entries.
+    pub fn parse_key(key: &str) -> Option<(StreamKind, Self)> {
+        let (kind, rest) = [StreamKind::Playback, StreamKind::Record].iter()
+            .filter(|kind| key.starts_with(kind.prefix()))
+            .map(|&kind| (kind, &key[kind.prefix().len()..]))
+            .next()?;
+        INFIXES.iter().enumerate()
+            .find(|&(_, infix)| rest.starts_with(infix))
+            .map(|(i, infix)| (kind, Self::from_parts(i, rest[infix.len()..].to_string())))
+    }
+}
+
+/// A stored entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppVolume {
+    /// Channel map of the volume.
+    pub channel_map: Map,
+    /// Stored volume. This is invalid if no volume is stored.
+    pub volume: ChannelVolumes,
+    /// Stored device, if any.
+    pub device: Option<String>,
+    /// Stored mute switch.
+    pub mute: bool,
+}
+
+impl<'a> From<&'a Info<'a>> for AppVolume {
+    fn from(i: &'a Info<'a>) -> Self {
+        AppVolume {
+            channel_map: i.channel_map,
+            volume: i.volume,
+            device: i.device.as_ref().filter(|d| !d.is_empty()).map(|d| d.to_string()),
+            mute: i.mute,
+        }
+    }
+}
+
+/// Reads and writes per-application entries of the stream-restore database.
+///
+/// See the [module level documentation](index.html) for details.
+pub struct AppVolumeStore {
+    context: Rc<ContextRef>,
+}
+
+impl AppVolumeStore {
+    /// Create a new store for the given context.
+    pub fn new(context: &Context) -> Self {
+        AppVolumeStore { context: Rc::new(Context::new_ref(context.ptr)) }
+    }
+
+    /// Get the entry for playback streams with the given application ID.
+    ///
+    /// The callback is given the entry, `None` if there is none, or the error should one occur.
+    pub fn get<F>(&self, app_id: &str, callback: F)
+        where F: FnOnce(Result<Option<AppVolume>, PAErr>) + 'static
+    {
+        let identity = AppIdentity::ApplicationId(app_id.to_string());
+        self.get_entry(&identity, StreamKind::Playback, callback);
+    }
+
+    /// Store the volume, and device if given, for playback streams with the given application ID,
+    /// applying them to existing such streams. The mute switch of any existing entry is kept.
+    ///
+    /// The channel map of the volume is taken to be the default for its number of channels.
+    ///
+    /// The callback must accept a `bool`, which indicates success.
+    pub fn set<F>(&self, app_id: &str, volume: &ChannelVolumes, device: Option<&str>,
+        callback: F)
+        where F: FnOnce(bool) + 'static
+    {
+        let identity = AppIdentity::ApplicationId(app_id.to_string());
+        let channel_map = match Map::new_extend(volume.channels as u32, MapDef::AIFF) {
+            Some(map) => map,
+            None => return callback(false),
+        };
+        let volume = *volume;
+        let device = device.map(|d| d.to_string());
+        let context = Rc::clone(&self.context);
+        self.get_entry(&identity.clone(), StreamKind::Playback, move |result| {
+            let mute = match result {
+                Ok(existing) => existing.map_or(false, |e| e.mute),
+                Err(_) => return callback(false),
+            };
+            let entry = AppVolume { channel_map, volume, device, mute };
+            write(&context, &identity, StreamKind::Playback, &entry, callback);
+        });
+    }
+
+    /// Get the entry for streams of the given kind with the given identity.
+    ///
+    /// The callback is given the entry, `None` if there is none, or the error should one occur.
+    pub fn get_entry<F>(&self, identity: &AppIdentity, kind: StreamKind, callback: F)
+        where F: FnOnce(Result<Option<AppVolume>, PAErr>) + 'static
+    {
+        let key = identity.key(kind);
+        let context = self.context.ptr;
+        let mut callback = Some(callback);
+        let mut found = None;
+        drop(self.context.stream_restore().read(move |result| match result {
+            ListResult::Item(info) => {
+                if info.name.as_ref().map(|n| n.as_ref()) == Some(key.as_str()) {
+                    found = Some(AppVolume::from(info));
+                }
+            },
+            ListResult::End => {
+                if let Some(callback) = callback.take() {
+                    callback(Ok(found.take()));
+                }
+            },
+            ListResult::Error => {
+                if let Some(callback) = callback.take() {
+                    callback(Err(PAErr(unsafe { capi::pa_context_errno(context) })));
+                }
+            },
+        }));
+    }
+
+    /// Store the entry for streams of the given kind with the given identity, replacing any
+    /// existing entry, and applying it to existing such streams.
+    ///
+    /// The callback must accept a `bool`, which indicates success.
+    pub fn set_entry<F>(&self, identity: &AppIdentity, kind: StreamKind, entry: &AppVolume,
+        callback: F)
+        where F: FnOnce(bool) + 'static
+    {
+        write(&self.context, identity, kind, entry, callback);
+    }
+
+    /// Delete the entry for streams of the given kind with the given identity.
+    ///
+    /// The callback must accept a `bool`, which indicates success.
+    pub fn remove_entry<F>(&self, identity: &AppIdentity, kind: StreamKind, callback: F)
+        where F: FnOnce(bool) + 'static
+    {
+        let key = identity.key(kind);
+        let mut callback = Some(callback);
+        drop(self.context.stream_restore().delete(&[&key], move |success| {
+            if let Some(callback) = callback.take() {
+                callback(success);
+            }
+        }));
+    }
+}
+
+/// Write an entry, replacing any existing entry of the same key.
+fn write<F>(context: &ContextRef, identity: &AppIdentity, kind: StreamKind, entry: &AppVolume,
+    callback: F)
+    where F: FnOnce(bool) + 'static
+{
+    let info = Info {
+        name: Some(Cow::Owned(identity.key(kind))),
+        channel_map: entry.channel_map,
+        volume: entry.volume,
+        device: entry.device.as_ref().map(|d| Cow::Borrowed(d.as_str())),
+        mute: entry.mute,
+    };
+    let mut callback = Some(callback);
+    drop(context.stream_restore().write(UpdateMode::Replace, &[&info], true, move |success| {
+        if let Some(callback) = callback.take() {
+            callback(success);
+        }
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entry_keys() {
+        let id = AppIdentity::ApplicationId("org.example.Player".to_string());
+        assert_eq!(id.key(StreamKind::Playback), "sink-input-by-application-id:org.example.Player");
+        let role = AppIdentity::MediaRole("music".to_string());
+        assert_eq!(role.key(StreamKind::Record), "source-output-by-media-role:music");
+
+        assert_eq!(AppIdentity::parse_key("sink-input-by-application-id:org.example.Player"),
+            Some((StreamKind::Playback, id)));
+        assert_eq!(AppIdentity::parse_key("source-output-by-module-stream-restore.id:x"),
+            Some((StreamKind::Record, AppIdentity::Custom("x".to_string()))));
+        assert_eq!(AppIdentity::parse_key("sink-input-fallback:foo"), None);
+        assert_eq!(AppIdentity::parse_key("card-foo"), None);
+    }
+}
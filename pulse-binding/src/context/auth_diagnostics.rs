@@ -0,0 +1,269 @@
+// Copyright 2017 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Authentication failure diagnostics.
+//!
+//! # Overview
+//!
+//! When connecting a context fails with [`Code::Access`], the server has rejected the client,
+//! most commonly because the authentication cookie presented does not match that of the server.
+//! The error alone gives no indication as to why. [`Context::auth_diagnostics`] gathers what is
+//! known about the attempt, from the binding’s own state and the client configuration, into an
+//! [`AuthDiagnostics`] report: the cookie used and whether it is valid, the server connected to
+//! and whether it is remote, and whether shared memory would have been attempted. Its `Display`
+//! implementation gives the report along with hints as to the likely cause:
+//!
+//! ```rust,ignore
+//! if context.get_state() == pulse::context::State::Failed && context.is_access_denied() {
+//!     eprintln!("{}", context.auth_diagnostics());
+//! }
+//! ```
+//!
+//! Note that the library can also take a cookie from X11 root window properties; this is not
+//! considered here.
+//!
+//! [`Code::Access`]: ../../error/enum.Code.html#variant.Access
+//! [`Context::auth_diagnostics`]: ../struct.Context.html#method.auth_diagnostics
+//! [`AuthDiagnostics`]: struct.AuthDiagnostics.html
+
+use std;
+use std::path::{Path, PathBuf};
+use error::{Code, PAErr};
+use super::{COOKIE_LENGTH, Context, find_cookie_file};
+use super::client_config::ClientConfig;
+
+/// Where the authentication cookie of a context came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CookieSource {
+    /// Loaded from the given file, with
+    /// [`Context::load_cookie_from_file`](../struct.Context.html#method.load_cookie_from_file).
+    File(PathBuf),
+    /// Set from memory, with
+    /// [`Context::set_auth_cookie`](../struct.Context.html#method.set_auth_cookie).
+    Memory,
+    /// Located by the library, per the client configuration.
+    Default,
+}
+
+/// Validity of an authentication cookie.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CookieStatus {
+    /// The cookie could be read, and is of valid length. It may still not match the server’s.
+    Valid,
+    /// No cookie file exists.
+    Missing,
+    /// The cookie file exists, but cannot be read.
+    Unreadable,
+    /// The cookie file is shorter than a cookie, with the given length in bytes.
+    InvalidLength(u64),
+}
+
+impl CookieStatus {
+    /// Check the cookie file at the given path.
+    pub fn check(path: &Path) -> Self {
+        let metadata = match std::fs::metadata(path) {
+            Ok(metadata) => metadata,
+            Err(ref e) if e.kind() == std::io::ErrorKind::NotFound => return CookieStatus::Missing,
+            Err(_) => return CookieStatus::Unreadable,
+        };
+        if std::fs::File::open(path).is_err() {
+            return CookieStatus::Unreadable;
+        }
+        match metadata.len() {
+            len if len < COOKIE_LENGTH as u64 => CookieStatus::InvalidLength(len),
+            _ => CookieStatus::Valid,
+        }
+    }
+}
+
+/// Report of what is known about a connection attempt, for diagnosing authentication failures.
+///
+/// See the [module level documentation](index.html) for details.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthDiagnostics {
+    /// The error of the context.
+    pub error: PAErr,
+    /// Where the cookie came from.
+    pub cookie_source: CookieSource,
+    /// Path of the cookie file tried, if any.
+    pub cookie_path: Option<PathBuf>,
+    /// Validity of the cookie.
+    pub cookie_status: CookieStatus,
+    /// The server connected to, as given upon connecting, otherwise as configured, or `None` for
+    /// the default (local) server.
+    pub server: Option<String>,
+    /// Whether or not the server is remote (on another host).
+    pub server_is_remote: bool,
+    /// Whether or not shared memory would have been attempted, being enabled in the configuration,
+    /// with a local server.
+    pub shm_attempted: bool,
+}
+
+/// Returns `true` if any entry of the server string is on another host.
+///
+/// Entries are Unix socket paths (local), or TCP addresses (remote, unless to the loopback
+/// interface), optionally preceded by a `{machine-id}` prefix.
+pub fn server_is_remote(server: &str) -> bool {
+    server.split_whitespace().any(|entry| {
+        let entry = match (entry.starts_with('{'), entry.find('}')) {
+            (true, Some(end)) => &entry[end + 1..],
+            _ => entry,
+        };
+        if entry.starts_with("unix:") || entry.starts_with('/') {
+            return false;
+        }
+        let address = ["tcp:", "tcp4:", "tcp6:"].iter()
+            .find(|p| entry.starts_with(*p))
+            .map_or(entry, |p| &entry[p.len()..]);
+        let host = match address.starts_with('[') {
+            true => address[1..].split(']').next().unwrap_or(""),
+            false => address.split(':').next().unwrap_or(""),
+        };
+        match host {
+            "localhost" | "127.0.0.1" | "::1" => false,
+            _ => true,
+        }
+    })
+}
+
+impl AuthDiagnostics {
+    /// Get hints as to the likely cause of the failure, in order of likelihood.
+    pub fn hints(&self) -> Vec<&'static str> {
+        let mut hints = Vec::new();
+        match self.cookie_status {
+            CookieStatus::Missing => hints.push("the cookie file does not exist; copy the \
+                server’s cookie to it, or give its location with `cookie-file` in client.conf"),
+            CookieStatus::Unreadable => hints.push("the cookie file cannot be read; check its \
+                permissions"),
+            CookieStatus::InvalidLength(_) => hints.push("the cookie file is not a valid cookie, \
+                which must be 256 bytes; copy the server’s cookie to it"),
+            CookieStatus::Valid => {},
+        }
+        if self.server_is_remote {
+            hints.push("the server is remote; the cookie must match that of the user running the \
+                remote server, or the server must allow the client with the `auth-ip-acl` or \
+                `auth-anonymous` options of module-native-protocol-tcp");
+        }
+        else if self.cookie_status == CookieStatus::Valid {
+            hints.push("the cookie does not match the server’s; the server may be running as \
+                another user, in system mode, or in another container");
+        }
+        if self.shm_attempted {
+            hints.push("shared memory was attempted; where the server is in another container \
+                or namespace, disabling it with `enable-shm = no` in client.conf may be needed");
+        }
+        hints
+    }
+}
+
+impl std::fmt::Display for AuthDiagnostics {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "connection failed: {}", self.error)?;
+        match (&self.cookie_source, &self.cookie_path) {
+            (&CookieSource::Memory, _) => writeln!(f, "cookie: set from memory")?,
+            (_, &Some(ref path)) => {
+                writeln!(f, "cookie: {} ({:?})", path.display(), self.cookie_status)?
+            },
+            (_, &None) => writeln!(f, "cookie: none found")?,
+        }
+        let location = match self.server_is_remote {
+            true => "remote",
+            false => "local",
+        };
+        writeln!(f, "server: {} ({})", self.server.as_ref().map_or("default", |s| s.as_str()),
+            location)?;
+        writeln!(f, "shared memory attempted: {}", self.shm_attempted)?;
+        for hint in self.hints() {
+            writeln!(f, "hint: {}", hint)?;
+        }
+        Ok(())
+    }
+}
+
+impl Context {
+    /// Returns `true` if the context failed due to the server denying access.
+    pub fn is_access_denied(&self) -> bool {
+        self.errno() == PAErr::from(Code::Access)
+    }
+
+    /// Gather a report for diagnosing an authentication failure. See
+    /// [`::context::auth_diagnostics`](auth_diagnostics/index.html).
+    ///
+    /// This reads the client configuration, and checks the cookie file, thus performs I/O.
+    pub fn auth_diagnostics(&self) -> AuthDiagnostics {
+        let config = ClientConfig::load();
+        let source = self.cookie_source.clone().unwrap_or(CookieSource::Default);
+        let (cookie_path, cookie_status) = match source {
+            CookieSource::File(ref path) => (Some(path.clone()), CookieStatus::check(path)),
+            CookieSource::Memory => (None, CookieStatus::Valid),
+            CookieSource::Default => match config.cookie_file.clone().or_else(find_cookie_file) {
+                Some(path) => {
+                    let status = CookieStatus::check(&path);
+                    (Some(path), status)
+                },
+                None => (None, CookieStatus::Missing),
+            },
+        };
+        let server = self.server.clone().or(config.default_server);
+        let server_is_remote = server.as_ref().map_or(false, |s| server_is_remote(s));
+        AuthDiagnostics {
+            error: self.errno(),
+            cookie_source: source,
+            cookie_path: cookie_path,
+            cookie_status: cookie_status,
+            server: server,
+            server_is_remote: server_is_remote,
+            shm_attempted: config.enable_shm && !server_is_remote,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remote_servers() {
+        assert!(!server_is_remote("unix:/run/user/1000/pulse/native"));
+        assert!(!server_is_remote("/run/pulse/native"));
+        assert!(!server_is_remote("{0123abcd}unix:/run/pulse/native tcp:localhost:4713"));
+        assert!(!server_is_remote("tcp6:[::1]:4713"));
+        assert!(server_is_remote("tcp:192.168.1.10:4713"));
+        assert!(server_is_remote("unix:/tmp/native soundbox"));
+        assert!(server_is_remote("tcp6:[fe80::1]"));
+    }
+
+    #[test]
+    fn hints() {
+        let mut diagnostics = AuthDiagnostics {
+            error: PAErr::from(Code::Access),
+            cookie_source: CookieSource::Default,
+            cookie_path: Some(PathBuf::from("/nonexistent/cookie")),
+            cookie_status: CookieStatus::check(Path::new("/nonexistent/cookie")),
+            server: Some("tcp:soundbox".to_string()),
+            server_is_remote: true,
+            shm_attempted: false,
+        };
+        assert_eq!(diagnostics.cookie_status, CookieStatus::Missing);
+        assert_eq!(diagnostics.hints().len(), 2);
+
+        diagnostics.cookie_status = CookieStatus::Valid;
+        diagnostics.server_is_remote = false;
+        diagnostics.shm_attempted = true;
+        let hints = diagnostics.hints();
+        assert_eq!(hints.len(), 2);
+        assert!(hints[0].contains("does not match"));
+    }
+}
@@ -0,0 +1,289 @@
+// Copyright 2017 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Batches of operations with a single completion.
+//!
+//! # Overview
+//!
+//! Applying a set of changes, such as a routing profile, typically involves issuing a number of
+//! operations, and then waiting until all of them have completed, noting any failures. A [`Batch`],
+//! created with [`Context::batch`], takes care of this. Operations are queued on it, then all
+//! issued together with [`Batch::run`], with a single callback called once every operation has
+//! completed, or with [`Batch::run_async`], giving a future.
+//!
+//! Operations are all issued regardless of the failure of others; the result reports each failure,
+//! identified by the position of the operation within the batch, along with the error.
+//!
+//! ```rust,ignore
+//! context.batch()
+//!     .set_sink_input_volume(12, &volume)
+//!     .set_sink_input_mute(13, true)
+//!     .move_sink_input_by_index(14, 2)
+//!     .run(|result| {
+//!         if let Err(e) = result {
+//!             for failure in e.failures {
+//!                 eprintln!("operation {} failed: {}", failure.position, failure.error);
+//!             }
+//!         }
+//!     });
+//! ```
+//!
+//! [`Batch`]: struct.Batch.html
+//! [`Context::batch`]: ../struct.Context.html#method.batch
+//! [`Batch::run`]: struct.Batch.html#method.run
+//! [`Batch::run_async`]: struct.Batch.html#method.run_async
+
+use std;
+use capi;
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context as TaskContext, Poll, Waker};
+use error::PAErr;
+use operation::Operation;
+use volume::ChannelVolumes;
+use super::Context;
+use super::introspect::Introspector;
+
+/// A queued operation, issued with the introspector and the success callback to be given to it.
+type Step = Box<dyn FnOnce(&mut Introspector, Box<dyn FnMut(bool)>) -> Operation<dyn FnMut(bool)>>;
+
+/// Failure of an operation within a batch.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BatchFailure {
+    /// Position of the operation within the batch, in the order queued, starting from zero.
+    pub position: usize,
+    /// The error, as given by the context upon failure.
+    pub error: PAErr,
+}
+
+/// Failure of one or more operations within a batch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchError {
+    /// The failed operations, in order of completion.
+    pub failures: Vec<BatchFailure>,
+}
+
+impl std::fmt::Display for BatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} operation(s) of batch failed", self.failures.len())
+    }
+}
+
+impl std::error::Error for BatchError {}
+
+/// A batch of operations, completing once all have completed.
+///
+/// See the [module level documentation](index.html) for details.
+#[must_use = "a batch does nothing until run"]
+pub struct Batch {
+    introspector: Introspector,
+    steps: Vec<Step>,
+}
+
+/// State shared with the success callbacks of the operations.
+struct Shared {
+    remaining: usize,
+    failures: Vec<BatchFailure>,
+    callback: Option<Box<dyn FnOnce(Result<(), BatchError>)>>,
+    result: Option<Result<(), BatchError>>,
+    waker: Option<Waker>,
+}
+
+impl Shared {
+    fn complete(&mut self) {
+        let result = match self.failures.is_empty() {
+            true => Ok(()),
+            false => Err(BatchError { failures: std::mem::take(&mut self.failures) }),
+        };
+        match self.callback.take() {
+            Some(callback) => callback(result),
+            None => {
+                self.result = Some(result);
+                if let Some(waker) = self.waker.take() {
+                    waker.wake();
+                }
+            },
+        }
+    }
+}
+
+impl Context {
+    /// Create a new, empty, batch of operations. See [`::context::batch`](batch/index.html).
+    pub fn batch(&self) -> Batch {
+        Batch { introspector: self.introspect(), steps: Vec::new() }
+    }
+}
+
+impl Batch {
+    /// Queue an operation, issued upon running the batch with the given introspector, to which the
+    /// given success callback must be passed.
+    pub fn add<F>(mut self, op: F) -> Self
+        where F: FnOnce(&mut Introspector, Box<dyn FnMut(bool)>) -> Operation<dyn FnMut(bool)>
+                 + 'static
+    {
+        self.steps.push(Box::new(op));
+        self
+    }
+
+    /// Queue setting the volume of a sink by index.
+    pub fn set_sink_volume_by_index(self, index: u32, volume: &ChannelVolumes) -> Self {
+        let volume = *volume;
+        self.add(move |i, cb| i.set_sink_volume_by_index(index, &volume, Some(cb)))
+    }
+
+    /// Queue setting the mute switch of a sink by index.
+    pub fn set_sink_mute_by_index(self, index: u32, mute: bool) -> Self {
+        self.add(move |i, cb| i.set_sink_mute_by_index(index, mute, Some(cb)))
+    }
+
+    /// Queue setting the volume of a source by index.
+    pub fn set_source_volume_by_index(self, index: u32, volume: &ChannelVolumes) -> Self {
+        let volume = *volume;
+        self.add(move |i, cb| i.set_source_volume_by_index(index, &volume, Some(cb)))
+    }
+
+    /// Queue setting the mute switch of a source by index.
+    pub fn set_source_mute_by_index(self, index: u32, mute: bool) -> Self {
+        self.add(move |i, cb| i.set_source_mute_by_index(index, mute, Some(cb)))
+    }
+
+    /// Queue setting the volume of a sink input.
+    pub fn set_sink_input_volume(self, index: u32, volume: &ChannelVolumes) -> Self {
+        let volume = *volume;
+        self.add(move |i, cb| i.set_sink_input_volume(index, &volume, Some(cb)))
+    }
+
+    /// Queue setting the mute switch of a sink input.
+    pub fn set_sink_input_mute(self, index: u32, mute: bool) -> Self {
+        self.add(move |i, cb| i.set_sink_input_mute(index, mute, Some(cb)))
+    }
+
+    /// Queue moving a sink input to another sink, by index.
+    pub fn move_sink_input_by_index(self, index: u32, sink_index: u32) -> Self {
+        self.add(move |i, cb| i.move_sink_input_by_index(index, sink_index, Some(cb)))
+    }
+
+    /// Queue setting the volume of a source output.
+    pub fn set_source_output_volume(self, index: u32, volume: &ChannelVolumes) -> Self {
+        let volume = *volume;
+        self.add(move |i, cb| i.set_source_output_volume(index, &volume, Some(cb)))
+    }
+
+    /// Queue setting the mute switch of a source output.
+    pub fn set_source_output_mute(self, index: u32, mute: bool) -> Self {
+        self.add(move |i, cb| i.set_source_output_mute(index, mute, Some(cb)))
+    }
+
+    /// Queue moving a source output to another source, by index.
+    pub fn move_source_output_by_index(self, index: u32, source_index: u32) -> Self {
+        self.add(move |i, cb| i.move_source_output_by_index(index, source_index, Some(cb)))
+    }
+
+    /// Queue changing the profile of a card, by index.
+    pub fn set_card_profile_by_index(self, index: u32, profile: &str) -> Self {
+        let profile = profile.to_string();
+        self.add(move |i, cb| i.set_card_profile_by_index(index, &profile, Some(cb)))
+    }
+
+    /// Get the number of operations queued.
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Returns `true` if no operations are queued.
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Issue all queued operations, calling the callback once all have completed.
+    ///
+    /// The callback is given `Ok` if all operations succeeded, otherwise an error detailing each
+    /// failure. For an empty batch, it is called immediately.
+    ///
+    /// Panics on error, i.e. invalid arguments or state.
+    pub fn run<F>(self, callback: F)
+        where F: FnOnce(Result<(), BatchError>) + 'static
+    {
+        self.start(Some(Box::new(callback)));
+    }
+
+    /// Issue all queued operations, returning a future completing once all have completed.
+    ///
+    /// The future gives `Ok` if all operations succeeded, otherwise an error detailing each
+    /// failure. It must be polled from the thread running the main loop.
+    ///
+    /// Panics on error, i.e. invalid arguments or state.
+    pub fn run_async(self) -> BatchFuture {
+        BatchFuture { shared: self.start(None) }
+    }
+
+    fn start(mut self, callback: Option<Box<dyn FnOnce(Result<(), BatchError>)>>)
+        -> Rc<RefCell<Shared>>
+    {
+        let shared = Rc::new(RefCell::new(Shared {
+            remaining: self.steps.len(),
+            failures: Vec::new(),
+            callback: callback,
+            result: None,
+            waker: None,
+        }));
+        if self.steps.is_empty() {
+            shared.borrow_mut().complete();
+            return shared;
+        }
+
+        let context = self.introspector.context;
+        let steps = std::mem::take(&mut self.steps);
+        for (position, step) in steps.into_iter().enumerate() {
+            let shared_cb = Rc::clone(&shared);
+            let done = Box::new(move |success: bool| {
+                let mut shared = shared_cb.borrow_mut();
+                if !success {
+                    let error = PAErr(unsafe { capi::pa_context_errno(context) });
+                    shared.failures.push(BatchFailure { position: position, error: error });
+                }
+                shared.remaining -= 1;
+                if shared.remaining == 0 {
+                    shared.complete();
+                }
+            });
+            drop(step(&mut self.introspector, done));
+        }
+        shared
+    }
+}
+
+/// Future completing once all operations of a batch have completed, as returned by
+/// [`Batch::run_async`](struct.Batch.html#method.run_async).
+pub struct BatchFuture {
+    shared: Rc<RefCell<Shared>>,
+}
+
+impl Future for BatchFuture {
+    type Output = Result<(), BatchError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Self::Output> {
+        let mut shared = self.shared.borrow_mut();
+        match shared.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                shared.waker = Some(cx.waker().clone());
+                Poll::Pending
+            },
+        }
+    }
+}
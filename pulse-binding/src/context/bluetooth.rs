@@ -0,0 +1,364 @@
+// Copyright 2018 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Bluetooth codec selection.
+//!
+//! # Overview
+//!
+//! Bluetooth headsets may support a number of A2DP codecs (SBC, AAC, aptX, LDAC, …). The codec in
+//! use is given as a property (`bluetooth.codec`, or `api.bluez5.codec` with PipeWire), read with
+//! [`CardInfo::bluetooth_codec`] (or its sink and source counterparts).
+//!
+//! Since PA 15 the codecs supported can be listed, and the codec switched, through the message API,
+//! by messages sent to the `/card/<name>/bluez` object of the card.
+//! [`Context::list_bluetooth_codecs`] and [`Context::set_bluetooth_codec`] take care of this
+//! (requiring the `pa_v15_compatibility` feature). Whether or not the server supports this is given
+//! by the [`supports_bluetooth_a2dp_codecs`] capability.
+//!
+//! ```rust,ignore
+//! context.list_bluetooth_codecs("bluez_card.00_11_22_33_44_55", |result| {
+//!     for codec in result.unwrap_or_default() {
+//!         println!("{} ({:?})", codec.name, codec.description);
+//!     }
+//! });
+//! context.set_bluetooth_codec("bluez_card.00_11_22_33_44_55", "aptx_hd", |success| {
+//!     assert!(success);
+//! });
+//! ```
+//!
+//! [`CardInfo::bluetooth_codec`]: ../introspect/struct.CardInfo.html#method.bluetooth_codec
+//! [`Context::list_bluetooth_codecs`]: ../struct.Context.html#method.list_bluetooth_codecs
+//! [`Context::set_bluetooth_codec`]: ../struct.Context.html#method.set_bluetooth_codec
+//! [`supports_bluetooth_a2dp_codecs`]: ../capabilities/struct.ServerCapabilities.html#structfield.supports_bluetooth_a2dp_codecs
+
+#[cfg(feature = "pa_v15_compatibility")]
+use std;
+use proplist::Proplist;
+use super::introspect::{CardInfo, SinkInfo, SourceInfo};
+#[cfg(feature = "pa_v15_compatibility")]
+use std::iter::Peekable;
+#[cfg(feature = "pa_v15_compatibility")]
+use std::str::Chars;
+#[cfg(feature = "pa_v15_compatibility")]
+use error::PAErr;
+#[cfg(feature = "pa_v15_compatibility")]
+use operation::Operation;
+#[cfg(feature = "pa_v15_compatibility")]
+use super::Context;
+
+/// Properties giving the codec in use, as set by PulseAudio and PipeWire respectively.
+pub const CODEC_PROPERTIES: [&str; 2] = ["bluetooth.codec", "api.bluez5.codec"];
+
+/// A codec supported by a Bluetooth card.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BluetoothCodec {
+    /// Name of the codec, as given to [`Context::set_bluetooth_codec`], e.g. `sbc` or `aptx_hd`.
+    ///
+    /// [`Context::set_bluetooth_codec`]: ../struct.Context.html#method.set_bluetooth_codec
+    pub name: String,
+    /// Description of the codec, e.g. `aptX HD`.
+    pub description: Option<String>,
+}
+
+/// Get the message API object path of the Bluetooth card with the given name.
+pub fn object_path(card_name: &str) -> String {
+    format!("/card/{}/bluez", card_name)
+}
+
+/// Get the codec from a property list.
+fn codec_property(proplist: &Proplist) -> Option<String> {
+    CODEC_PROPERTIES.iter().filter_map(|key| proplist.gets(key)).next()
+}
+
+impl<'a> CardInfo<'a> {
+    /// Get the codec in use by a Bluetooth card, as given by the properties of the card or of its
+    /// ports. Returns `None` for other cards, or if the server does not give the codec.
+    pub fn bluetooth_codec(&self) -> Option<String> {
+        codec_property(&self.proplist)
+            .or_else(|| self.ports.iter().filter_map(|p| codec_property(&p.proplist)).next())
+    }
+}
+
+impl<'a> SinkInfo<'a> {
+    /// Get the codec in use by a Bluetooth sink. Returns `None` for other sinks, or if the server
+    /// does not give the codec.
+    pub fn bluetooth_codec(&self) -> Option<String> {
+        codec_property(&self.proplist)
+    }
+}
+
+impl<'a> SourceInfo<'a> {
+    /// Get the codec in use by a Bluetooth source. Returns `None` for other sources, or if the
+    /// server does not give the codec.
+    pub fn bluetooth_codec(&self) -> Option<String> {
+        codec_property(&self.proplist)
+    }
+}
+
+#[cfg(feature = "pa_v15_compatibility")]
+impl Context {
+    /// List the codecs supported by the Bluetooth card with the given name.
+    ///
+    /// The callback is given the codecs, or the error should one occur, such as the card not being
+    /// a Bluetooth card, or the server not supporting codec switching.
+    ///
+    /// Requires PA version 15 (the `pa_v15_compatibility` feature).
+    pub fn list_bluetooth_codecs<F>(&mut self, card_name: &str, callback: F)
+        -> Operation<dyn FnMut(bool, Option<String>)>
+        where F: FnOnce(Result<Vec<BluetoothCodec>, PAErr>) + 'static
+    {
+        let context = self.ptr;
+        let mut callback = Some(callback);
+        self.send_message_to_object(&object_path(card_name), "list-codecs", None,
+            move |success, response| {
+                let result = match (success, response.as_ref().and_then(|r| parse_codecs(r))) {
+                    (true, Some(codecs)) => Ok(codecs),
+                    (true, None) => Err(PAErr::from(::error::Code::Protocol)),
+                    (false, _) => Err(context_error(context)),
+                };
+                if let Some(callback) = callback.take() {
+                    callback(result);
+                }
+            })
+    }
+
+    /// Get the codec in use by the Bluetooth card with the given name, asking the card itself
+    /// rather than reading its properties.
+    ///
+    /// Requires PA version 15 (the `pa_v15_compatibility` feature).
+    pub fn get_bluetooth_codec<F>(&mut self, card_name: &str, callback: F)
+        -> Operation<dyn FnMut(bool, Option<String>)>
+        where F: FnOnce(Result<String, PAErr>) + 'static
+    {
+        let context = self.ptr;
+        let mut callback = Some(callback);
+        self.send_message_to_object(&object_path(card_name), "get-codec", None,
+            move |success, response| {
+                let parsed = response.as_ref().and_then(|r| parse(r));
+                let result = match (success, parsed) {
+                    (true, Some(Json::String(codec))) => Ok(codec),
+                    (true, _) => Err(PAErr::from(::error::Code::Protocol)),
+                    (false, _) => Err(context_error(context)),
+                };
+                if let Some(callback) = callback.take() {
+                    callback(result);
+                }
+            })
+    }
+
+    /// Switch the Bluetooth card with the given name to the given codec, as named in the list given
+    /// by [`list_bluetooth_codecs`](#method.list_bluetooth_codecs).
+    ///
+    /// The callback must accept a `bool`, which indicates success.
+    ///
+    /// Requires PA version 15 (the `pa_v15_compatibility` feature).
+    pub fn set_bluetooth_codec<F>(&mut self, card_name: &str, codec: &str, mut callback: F)
+        -> Operation<dyn FnMut(bool, Option<String>)>
+        where F: FnMut(bool) + 'static
+    {
+        self.send_message_to_object(&object_path(card_name), "switch-codec",
+            Some(&json_string(codec)), move |success, _| callback(success))
+    }
+}
+
+#[cfg(feature = "pa_v15_compatibility")]
+fn context_error(context: *mut ::capi::pa_context) -> PAErr {
+    PAErr(unsafe { ::capi::pa_context_errno(context) })
+}
+
+/// A (partially) parsed JSON value, as used by the message API.
+#[cfg(feature = "pa_v15_compatibility")]
+#[derive(Debug, PartialEq)]
+enum Json {
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+    /// A number, boolean or null.
+    Other,
+}
+
+/// Encode a string as a JSON string.
+#[cfg(feature = "pa_v15_compatibility")]
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Parse a complete JSON value.
+#[cfg(feature = "pa_v15_compatibility")]
+fn parse(s: &str) -> Option<Json> {
+    let mut chars = s.chars().peekable();
+    let value = parse_value(&mut chars)?;
+    skip_whitespace(&mut chars);
+    match chars.next() {
+        None => Some(value),
+        Some(_) => None,
+    }
+}
+
+#[cfg(feature = "pa_v15_compatibility")]
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while chars.peek().map_or(false, |c| c.is_whitespace()) {
+        chars.next();
+    }
+}
+
+#[cfg(feature = "pa_v15_compatibility")]
+fn parse_value(chars: &mut Peekable<Chars>) -> Option<Json> {
+    skip_whitespace(chars);
+    match *chars.peek()? {
+        '"' => parse_string(chars).map(Json::String),
+        '[' => {
+            chars.next();
+            let mut items = Vec::new();
+            skip_whitespace(chars);
+            if chars.peek() == Some(&']') {
+                chars.next();
+                return Some(Json::Array(items));
+            }
+            loop {
+                items.push(parse_value(chars)?);
+                skip_whitespace(chars);
+                match chars.next()? {
+                    ',' => continue,
+                    ']' => return Some(Json::Array(items)),
+                    _ => return None,
+                }
+            }
+        },
+        '{' => {
+            chars.next();
+            let mut members = Vec::new();
+            skip_whitespace(chars);
+            if chars.peek() == Some(&'}') {
+                chars.next();
+                return Some(Json::Object(members));
+            }
+            loop {
+                skip_whitespace(chars);
+                let key = parse_string(chars)?;
+                skip_whitespace(chars);
+                if chars.next()? != ':' {
+                    return None;
+                }
+                members.push((key, parse_value(chars)?));
+                skip_whitespace(chars);
+                match chars.next()? {
+                    ',' => continue,
+                    '}' => return Some(Json::Object(members)),
+                    _ => return None,
+                }
+            }
+        },
+        _ => {
+            let mut any = false;
+            while chars.peek().map_or(false, |c| c.is_alphanumeric() || "+-.".contains(*c)) {
+                chars.next();
+                any = true;
+            }
+            match any {
+                true => Some(Json::Other),
+                false => None,
+            }
+        },
+    }
+}
+
+#[cfg(feature = "pa_v15_compatibility")]
+fn parse_string(chars: &mut Peekable<Chars>) -> Option<String> {
+    if chars.next()? != '"' {
+        return None;
+    }
+    let mut s = String::new();
+    loop {
+        match chars.next()? {
+            '"' => return Some(s),
+            '\\' => match chars.next()? {
+                'n' => s.push('\n'),
+                't' => s.push('\t'),
+                'r' => s.push('\r'),
+                'b' => s.push('\u{8}'),
+                'f' => s.push('\u{c}'),
+                'u' => {
+                    let hex: String = (0..4).filter_map(|_| chars.next()).collect();
+                    let code = u32::from_str_radix(&hex, 16).ok()?;
+                    s.push(std::char::from_u32(code).unwrap_or('\u{fffd}'));
+                },
+                c => s.push(c),
+            },
+            c => s.push(c),
+        }
+    }
+}
+
+/// Parse the response to the `list-codecs` message, an array of objects with `name` and
+/// `description` members.
+#[cfg(feature = "pa_v15_compatibility")]
+fn parse_codecs(response: &str) -> Option<Vec<BluetoothCodec>> {
+    let items = match parse(response)? {
+        Json::Array(items) => items,
+        _ => return None,
+    };
+    let mut codecs = Vec::with_capacity(items.len());
+    for item in items {
+        let members = match item {
+            Json::Object(members) => members,
+            _ => return None,
+        };
+        let mut name = None;
+        let mut description = None;
+        for (key, value) in members {
+            match (key.as_str(), value) {
+                ("name", Json::String(s)) => name = Some(s),
+                ("description", Json::String(s)) => description = Some(s),
+                _ => {},
+            }
+        }
+        codecs.push(BluetoothCodec { name: name?, description: description });
+    }
+    Some(codecs)
+}
+
+#[cfg(all(test, feature = "pa_v15_compatibility"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codec_messages() {
+        let response = r#"[{"name":"sbc","description":"SBC"},
+            {"name":"aptx_hd","description":"aptX \"HD\"","priority":2}]"#;
+        assert_eq!(parse_codecs(response), Some(vec![
+            BluetoothCodec { name: "sbc".into(), description: Some("SBC".into()) },
+            BluetoothCodec { name: "aptx_hd".into(), description: Some("aptX \"HD\"".into()) },
+        ]));
+        assert_eq!(parse_codecs("[]"), Some(vec![]));
+        assert_eq!(parse_codecs(r#"[{"description":"SBC"}]"#), None);
+        assert_eq!(parse_codecs("[{"), None);
+
+        assert_eq!(parse(r#" "sbc_xq_552" "#), Some(Json::String("sbc_xq_552".into())));
+        assert_eq!(json_string("a\"b\\c"), r#""a\"b\\c""#);
+        assert_eq!(parse(&json_string("a\"b\\c\n")), Some(Json::String("a\"b\\c\n".into())));
+    }
+}
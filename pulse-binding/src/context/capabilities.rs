@@ -0,0 +1,282 @@
+// Copyright 2018 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Server capability detection.
+//!
+//! # Overview
+//!
+//! Which features are available depends upon the protocol version negotiated between the library
+//! and the server, and upon the server version. Rather than having applications compare version
+//! numbers themselves, [`Context::capabilities`] gathers these, giving a [`ServerCapabilities`]
+//! object with a flag per feature.
+//!
+//! ```rust,ignore
+//! context.capabilities(|caps| {
+//!     if caps.supports_bluetooth_a2dp_codecs {
+//!         show_codec_selector();
+//!     }
+//! });
+//! ```
+//!
+//! # PipeWire
+//!
+//! Many systems now run `pipewire-pulse`, the PulseAudio protocol server of PipeWire, in place of
+//! PulseAudio. It is detected from the server information, and given as the [`ServerFlavor`]. The
+//! known behavioural differences from PulseAudio are listed as [`Quirk`]s, such that applications
+//! can branch upon them, rather than upon the flavor or version directly:
+//!
+//! ```rust,ignore
+//! use pulse::context::capabilities::Quirk;
+//!
+//! context.capabilities(|caps| {
+//!     if caps.has_quirk(Quirk::LimitedModules) {
+//!         // Use a natively supported module, rather than loading our own
+//!     }
+//! });
+//! ```
+//!
+//! [`Context::capabilities`]: ../struct.Context.html#method.capabilities
+//! [`ServerCapabilities`]: struct.ServerCapabilities.html
+//! [`ServerFlavor`]: enum.ServerFlavor.html
+//! [`Quirk`]: enum.Quirk.html
+
+use std;
+use operation::Operation;
+use super::Context;
+use super::introspect::ServerInfo;
+
+/// Protocol version from which data may be transferred through POSIX shared memory.
+pub const PROTOCOL_VERSION_SHM: u32 = 10;
+/// Protocol version from which the shared ring buffer channel (srbchannel) is supported.
+pub const PROTOCOL_VERSION_SRBCHANNEL: u32 = 30;
+/// Protocol version from which data may be transferred through memfd shared memory.
+pub const PROTOCOL_VERSION_MEMFD: u32 = 31;
+/// Protocol version from which port availability groups and types are given (PA 14).
+pub const PROTOCOL_VERSION_PORT_AVAILABILITY_GROUP: u32 = 34;
+/// Protocol version from which the message API is supported (PA 15).
+pub const PROTOCOL_VERSION_MESSAGE_API: u32 = 35;
+
+/// The implementation of the server.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ServerFlavor {
+    /// PulseAudio itself.
+    PulseAudio,
+    /// The PulseAudio protocol server of PipeWire (`pipewire-pulse`).
+    PipeWire,
+}
+
+/// A known behavioural difference of a server flavor from PulseAudio.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Quirk {
+    /// Only a limited set of modules can be loaded (those reimplemented natively, such as
+    /// `module-null-sink` and `module-loopback`); loading others fails. The modules listed by
+    /// introspection are emulated, not necessarily reflecting how devices were created.
+    LimitedModules,
+    /// The device manager protocol extension (`ext_device_manager`) is not supported.
+    NoDeviceManager,
+    /// Asking the daemon to exit is not supported.
+    NoExitDaemon,
+    /// Streams not explicitly routed follow changes of the default sink or source automatically,
+    /// as if moved, rather than remaining upon the device they were created on.
+    StreamsFollowDefault,
+    /// Latency and timing information is approximated from the graph, rather than measured per
+    /// device, and may be less accurate.
+    ApproximateTiming,
+}
+
+impl Quirk {
+    /// Get a short description of the quirk.
+    pub fn description(self) -> &'static str {
+        match self {
+            Quirk::LimitedModules => "only a limited set of modules can be loaded",
+            Quirk::NoDeviceManager => "the device manager extension is not supported",
+            Quirk::NoExitDaemon => "asking the daemon to exit is not supported",
+            Quirk::StreamsFollowDefault => "streams follow changes of the default device",
+            Quirk::ApproximateTiming => "latency and timing information is approximated",
+        }
+    }
+}
+
+/// Quirks of PipeWire.
+const PIPEWIRE_QUIRKS: &[Quirk] = &[
+    Quirk::LimitedModules,
+    Quirk::NoDeviceManager,
+    Quirk::NoExitDaemon,
+    Quirk::StreamsFollowDefault,
+    Quirk::ApproximateTiming,
+];
+
+impl ServerFlavor {
+    /// Detect the flavor from the server package name and version string, as given in the server
+    /// information. `pipewire-pulse` names itself, or notes itself in the version, as being “on
+    /// PipeWire”.
+    pub fn detect(server_name: Option<&str>, server_version: Option<&str>) -> Self {
+        let pipewire = |s: Option<&str>| s.map_or(false, |s| s.contains("PipeWire"));
+        match pipewire(server_name) || pipewire(server_version) {
+            true => ServerFlavor::PipeWire,
+            false => ServerFlavor::PulseAudio,
+        }
+    }
+
+    /// Get the known quirks of the flavor.
+    pub fn quirks(self) -> &'static [Quirk] {
+        match self {
+            ServerFlavor::PulseAudio => &[],
+            ServerFlavor::PipeWire => PIPEWIRE_QUIRKS,
+        }
+    }
+}
+
+/// Features available with the connected server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ServerCapabilities {
+    /// Protocol version of the library.
+    pub library_protocol_version: u32,
+    /// Protocol version of the server, if known.
+    pub server_protocol_version: Option<u32>,
+    /// Server package name (usually “pulseaudio”), if known.
+    pub server_name: Option<String>,
+    /// Version string of the server, if known.
+    pub server_version: Option<String>,
+    /// Major, minor and micro components of the server version, if it could be parsed.
+    pub server_version_parsed: Option<(u32, u32, u32)>,
+    /// Whether or not the server runs on the local machine, if known.
+    pub is_local: Option<bool>,
+    /// The implementation of the server.
+    pub flavor: ServerFlavor,
+    /// Whether or not data may be transferred through POSIX shared memory (local servers only).
+    pub supports_shm: bool,
+    /// Whether or not the shared ring buffer channel is supported (local servers only).
+    pub supports_srbchannel: bool,
+    /// Whether or not data may be transferred through memfd shared memory (local servers only).
+    pub supports_memfd: bool,
+    /// Whether or not port availability groups and types are given.
+    pub supports_port_availability_groups: bool,
+    /// Whether or not the message API is supported.
+    pub supports_message_api: bool,
+    /// Whether or not Bluetooth A2DP codecs can be listed and switched (through the message API).
+    pub supports_bluetooth_a2dp_codecs: bool,
+}
+
+/// Parse the leading numeric components of a version string, e.g. `15.0` or
+/// `15.0.0 (on PipeWire 0.3.40)`, with missing minor or micro components taken as zero.
+pub fn parse_version(version: &str) -> Option<(u32, u32, u32)> {
+    let end = version.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(version.len());
+    let mut parts = version[..end].split('.').map(|p| p.parse::<u32>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().unwrap_or(Some(0)).unwrap_or(0);
+    let micro = parts.next().unwrap_or(Some(0)).unwrap_or(0);
+    Some((major, minor, micro))
+}
+
+impl ServerCapabilities {
+    /// Determine the capabilities from the library and server protocol versions, and the server
+    /// name and version strings.
+    pub fn new(library_protocol_version: u32, server_protocol_version: Option<u32>,
+        server_name: Option<&str>, server_version: Option<&str>, is_local: Option<bool>) -> Self
+    {
+        let negotiated = match server_protocol_version {
+            Some(server) => std::cmp::min(library_protocol_version, server),
+            None => 0,
+        };
+        let local = is_local == Some(true);
+        let parsed = server_version.and_then(parse_version);
+        let message_api = negotiated >= PROTOCOL_VERSION_MESSAGE_API;
+        Self {
+            library_protocol_version: library_protocol_version,
+            server_protocol_version: server_protocol_version,
+            server_name: server_name.map(|s| s.to_string()),
+            server_version: server_version.map(|s| s.to_string()),
+            server_version_parsed: parsed,
+            is_local: is_local,
+            flavor: ServerFlavor::detect(server_name, server_version),
+            supports_shm: local && negotiated >= PROTOCOL_VERSION_SHM,
+            supports_srbchannel: local && negotiated >= PROTOCOL_VERSION_SRBCHANNEL,
+            supports_memfd: local && negotiated >= PROTOCOL_VERSION_MEMFD,
+            supports_port_availability_groups:
+                negotiated >= PROTOCOL_VERSION_PORT_AVAILABILITY_GROUP,
+            supports_message_api: message_api,
+            supports_bluetooth_a2dp_codecs: message_api
+                && parsed.map_or(false, |(major, _, _)| major >= 15),
+        }
+    }
+}
+
+impl ServerCapabilities {
+    /// Check whether or not the server has the given quirk.
+    pub fn has_quirk(&self, quirk: Quirk) -> bool {
+        self.flavor.quirks().contains(&quirk)
+    }
+}
+
+impl Context {
+    /// Determine the capabilities of the connected server, fetching the server information.
+    ///
+    /// The callback is given the capabilities once the server information has been received. The
+    /// context must be ready.
+    ///
+    /// Panics on error, i.e. invalid arguments or state.
+    pub fn capabilities<F>(&self, callback: F) -> Operation<dyn FnMut(&ServerInfo)>
+        where F: FnOnce(ServerCapabilities) + 'static
+    {
+        let library = self.get_protocol_version();
+        let server = self.get_server_protocol_version();
+        let is_local = self.is_local();
+        let mut callback = Some(callback);
+        self.introspect().get_server_info(move |info| {
+            if let Some(callback) = callback.take() {
+                callback(ServerCapabilities::new(library, server,
+                    info.server_name.as_ref().map(|s| s.as_ref()),
+                    info.server_version.as_ref().map(|s| s.as_ref()), is_local));
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_parsing() {
+        assert_eq!(parse_version("15.0"), Some((15, 0, 0)));
+        assert_eq!(parse_version("13.99.1-67-gdeadbeef"), Some((13, 99, 1)));
+        assert_eq!(parse_version("15.0.0 (on PipeWire 0.3.40)"), Some((15, 0, 0)));
+        assert_eq!(parse_version("unknown"), None);
+    }
+
+    #[test]
+    fn capability_flags() {
+        let caps = ServerCapabilities::new(35, Some(32), Some("pulseaudio"), Some("12.2"),
+            Some(true));
+        assert!(caps.supports_memfd);
+        assert!(!caps.supports_message_api);
+
+        let caps = ServerCapabilities::new(35, Some(35), Some("pulseaudio"), Some("15.0"),
+            Some(false));
+        assert!(!caps.supports_shm);
+        assert!(caps.supports_message_api);
+        assert!(caps.supports_bluetooth_a2dp_codecs);
+        assert_eq!(caps.flavor, ServerFlavor::PulseAudio);
+        assert!(!caps.has_quirk(Quirk::LimitedModules));
+
+        let caps = ServerCapabilities::new(35, Some(35), Some("PulseAudio (on PipeWire 0.3.40)"),
+            Some("15.0.0"), Some(true));
+        assert_eq!(caps.flavor, ServerFlavor::PipeWire);
+        assert!(caps.has_quirk(Quirk::LimitedModules));
+    }
+}
@@ -0,0 +1,305 @@
+// Copyright 2017 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Client configuration (`client.conf`) reading.
+//!
+//! # Overview
+//!
+//! The library reads the client configuration upon creating a context, determining for instance
+//! the server connected to by default, and whether or not to autospawn a daemon. The C API provides
+//! no access to this, thus the effective settings are read here with [`ClientConfig::load`], in the
+//! same manner as the library does, for display, or to be overridden deterministically (by
+//! explicitly passing a server to [`Context::connect`]).
+//!
+//! The configuration is read from the first of the following that exists:
+//!
+//! * The file named by the `PULSE_CLIENTCONFIG` environment variable.
+//! * `$XDG_CONFIG_HOME/pulse/client.conf` (where `XDG_CONFIG_HOME` defaults to `~/.config`).
+//! * `~/.pulse/client.conf`.
+//! * `/etc/pulse/client.conf`.
+//!
+//! Followed by any `*.conf` files within a `.d` directory alongside it (e.g.
+//! `/etc/pulse/client.conf.d`), in alphabetical order. Finally, the `PULSE_SERVER`, `PULSE_SINK`,
+//! `PULSE_SOURCE` and `PULSE_COOKIE` environment variables override the corresponding settings.
+//!
+//! Note that the library additionally takes settings from X11 root window properties where
+//! available; these are not read here.
+//!
+//! [`ClientConfig::load`]: struct.ClientConfig.html#method.load
+//! [`Context::connect`]: ../struct.Context.html#method.connect
+
+use std;
+use std::path::{Path, PathBuf};
+
+/// Location of the system-wide client configuration file.
+pub const SYSTEM_CONFIG_FILE: &str = "/etc/pulse/client.conf";
+
+/// Effective client configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ClientConfig {
+    /// Server connected to by default (`default-server`).
+    pub default_server: Option<String>,
+    /// Sink used for new streams by default (`default-sink`).
+    pub default_sink: Option<String>,
+    /// Source used for new streams by default (`default-source`).
+    pub default_source: Option<String>,
+    /// Whether or not a daemon is spawned if none is running (`autospawn`).
+    pub autospawn: bool,
+    /// Path of the daemon binary spawned (`daemon-binary`).
+    pub daemon_binary: Option<String>,
+    /// Extra arguments given to the spawned daemon (`extra-arguments`).
+    pub extra_arguments: Option<String>,
+    /// Path of the authentication cookie file (`cookie-file`).
+    pub cookie_file: Option<PathBuf>,
+    /// Whether or not shared memory is used for data transfer, where possible (`enable-shm`).
+    pub enable_shm: bool,
+    /// Whether or not memfd shared memory is used, where possible (`enable-memfd`).
+    pub enable_memfd: bool,
+    /// Whether or not to fall back to connecting to `localhost` (`auto-connect-localhost`).
+    pub auto_connect_localhost: bool,
+    /// Whether or not to fall back to connecting to the host of `$DISPLAY`
+    /// (`auto-connect-display`).
+    pub auto_connect_display: bool,
+    /// The configuration files read, in order.
+    pub files: Vec<PathBuf>,
+}
+
+impl Default for ClientConfig {
+    /// The built-in defaults, as used where no configuration file sets otherwise.
+    fn default() -> Self {
+        ClientConfig {
+            default_server: None,
+            default_sink: None,
+            default_source: None,
+            autospawn: true,
+            daemon_binary: None,
+            extra_arguments: None,
+            cookie_file: None,
+            enable_shm: true,
+            enable_memfd: true,
+            auto_connect_localhost: false,
+            auto_connect_display: false,
+            files: Vec::new(),
+        }
+    }
+}
+
+impl std::fmt::Display for ClientConfig {
+    /// Formats the settings as configuration file content, from which the same settings are parsed.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let strings = [
+            ("default-server", &self.default_server),
+            ("default-sink", &self.default_sink),
+            ("default-source", &self.default_source),
+            ("daemon-binary", &self.daemon_binary),
+            ("extra-arguments", &self.extra_arguments),
+        ];
+        for &(key, value) in strings.iter() {
+            if let Some(ref value) = *value {
+                writeln!(f, "{} = {}", key, value)?;
+            }
+        }
+        if let Some(ref path) = self.cookie_file {
+            writeln!(f, "cookie-file = {}", path.display())?;
+        }
+        let flags = [
+            ("autospawn", self.autospawn),
+            ("enable-shm", self.enable_shm),
+            ("enable-memfd", self.enable_memfd),
+            ("auto-connect-localhost", self.auto_connect_localhost),
+            ("auto-connect-display", self.auto_connect_display),
+        ];
+        for &(key, value) in flags.iter() {
+            writeln!(f, "{} = {}", key, match value { true => "yes", false => "no" })?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse a boolean value, as PulseAudio does.
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "1" | "y" | "yes" | "t" | "true" | "on" => Some(true),
+        "0" | "n" | "no" | "f" | "false" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+/// Get a non-empty environment variable.
+fn env_var(key: &str) -> Option<String> {
+    std::env::var(key).ok().filter(|v| !v.is_empty())
+}
+
+impl ClientConfig {
+    /// Read the effective configuration, as the library does upon creating a context.
+    ///
+    /// See the [module level documentation](index.html) for details. Files that do not exist or
+    /// cannot be read are skipped, as are unknown keys and invalid values.
+    pub fn load() -> Self {
+        let mut config = ClientConfig::default();
+        if let Some(path) = Self::find_file() {
+            config.read_file(&path);
+            let mut dir = path.into_os_string();
+            dir.push(".d");
+            if let Ok(entries) = std::fs::read_dir(&dir) {
+                let mut paths: Vec<PathBuf> = entries
+                    .filter_map(|e| e.ok().map(|e| e.path()))
+                    .filter(|p| p.extension().map_or(false, |ext| ext == "conf"))
+                    .collect();
+                paths.sort();
+                for path in paths {
+                    config.read_file(&path);
+                }
+            }
+        }
+        config.apply_env();
+        config
+    }
+
+    /// Locate the main configuration file read, if any exists.
+    pub fn find_file() -> Option<PathBuf> {
+        if let Some(path) = env_var("PULSE_CLIENTCONFIG") {
+            return Some(PathBuf::from(path));
+        }
+        let home = std::env::var_os("HOME").map(PathBuf::from);
+        let config_home = match std::env::var_os("XDG_CONFIG_HOME") {
+            Some(ref dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+            _ => home.as_ref().map(|h| h.join(".config")),
+        };
+        let candidates = [
+            config_home.map(|dir| dir.join("pulse").join("client.conf")),
+            home.map(|dir| dir.join(".pulse").join("client.conf")),
+            Some(PathBuf::from(SYSTEM_CONFIG_FILE)),
+        ];
+        candidates.iter().flatten().find(|path| path.is_file()).cloned()
+    }
+
+    /// Read a configuration file, applying its settings on top of those already held. Nothing is
+    /// changed if the file cannot be read.
+    pub fn read_file(&mut self, path: &Path) {
+        if let Ok(content) = std::fs::read_to_string(path) {
+            self.files.push(path.to_path_buf());
+            self.parse(&content);
+        }
+    }
+
+    /// Parse configuration file content, applying its settings on top of those already held.
+    pub fn parse(&mut self, content: &str) {
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';')
+                || line.starts_with('[')
+            {
+                continue;
+            }
+            let (key, value) = match line.find('=') {
+                Some(pos) => (line[..pos].trim(), line[pos + 1..].trim()),
+                None => continue,
+            };
+            self.set(key, value);
+        }
+    }
+
+    /// Apply a single setting. Unknown keys and invalid values are ignored.
+    fn set(&mut self, key: &str, value: &str) {
+        let string = || match value.is_empty() {
+            true => None,
+            false => Some(value.to_string()),
+        };
+        match key {
+            "default-server" => self.default_server = string(),
+            "default-sink" => self.default_sink = string(),
+            "default-source" => self.default_source = string(),
+            "daemon-binary" => self.daemon_binary = string(),
+            "extra-arguments" => self.extra_arguments = string(),
+            "cookie-file" => self.cookie_file = string().map(PathBuf::from),
+            _ => {
+                let flag = match key {
+                    "autospawn" => &mut self.autospawn,
+                    "enable-shm" => &mut self.enable_shm,
+                    "enable-memfd" => &mut self.enable_memfd,
+                    "auto-connect-localhost" => &mut self.auto_connect_localhost,
+                    "auto-connect-display" => &mut self.auto_connect_display,
+                    _ => return,
+                };
+                if let Some(b) = parse_bool(value) {
+                    *flag = b;
+                }
+            },
+        }
+    }
+
+    /// Apply the overrides of the `PULSE_SERVER`, `PULSE_SINK`, `PULSE_SOURCE` and `PULSE_COOKIE`
+    /// environment variables.
+    pub fn apply_env(&mut self) {
+        if let Some(server) = env_var("PULSE_SERVER") {
+            self.default_server = Some(server);
+        }
+        if let Some(sink) = env_var("PULSE_SINK") {
+            self.default_sink = Some(sink);
+        }
+        if let Some(source) = env_var("PULSE_SOURCE") {
+            self.default_source = Some(source);
+        }
+        if let Some(cookie) = env_var("PULSE_COOKIE") {
+            self.cookie_file = Some(PathBuf::from(cookie));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_settings() {
+        let mut config = ClientConfig::default();
+        config.parse("
+            ; comment
+            # another comment
+            [section]
+            default-server = tcp:host:4713
+            default-sink=alsa_output.foo
+            autospawn = no
+            enable-shm = maybe
+            cookie-file = /tmp/cookie
+            unknown-key = 1
+        ");
+        assert_eq!(config.default_server, Some("tcp:host:4713".to_string()));
+        assert_eq!(config.default_sink, Some("alsa_output.foo".to_string()));
+        assert_eq!(config.default_source, None);
+        assert!(!config.autospawn);
+        assert!(config.enable_shm);
+        assert_eq!(config.cookie_file, Some(PathBuf::from("/tmp/cookie")));
+
+        config.parse("default-server =\nautospawn = on");
+        assert_eq!(config.default_server, None);
+        assert!(config.autospawn);
+    }
+
+    #[test]
+    fn format_settings() {
+        let mut config = ClientConfig::default();
+        config.default_sink = Some("alsa_output.foo".to_string());
+        config.cookie_file = Some(PathBuf::from("/tmp/cookie"));
+        config.enable_memfd = false;
+
+        let mut parsed = ClientConfig::default();
+        parsed.enable_shm = false;
+        parsed.parse(&config.to_string());
+        assert_eq!(parsed, config);
+    }
+}
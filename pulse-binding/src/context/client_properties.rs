@@ -0,0 +1,218 @@
+// Copyright 2017 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Runtime changes to the client’s properties.
+//!
+//! # Overview
+//!
+//! The properties given upon creating a context, such as the application name and icon, are shown
+//! by mixers, and the media role is used for routing. [`Context::update_client_properties`] creates
+//! a [`ClientPropertiesUpdate`], a builder changing these at runtime, taking typed
+//! [`properties::Key`]s, and applying the entries set with [`Context::proplist_update`] and those
+//! removed with [`Context::proplist_remove`] together, with a single completion.
+//!
+//! ```rust,ignore
+//! use pulse::proplist::properties::Key;
+//!
+//! context.update_client_properties()
+//!     .set(Key::ApplicationIconName, "media-playback-start")
+//!     .set(Key::MediaRole, "music")
+//!     .remove(Key::WindowName)
+//!     .apply(|success| assert!(success));
+//! ```
+//!
+//! [`Context::update_client_properties`]: ../struct.Context.html#method.update_client_properties
+//! [`ClientPropertiesUpdate`]: struct.ClientPropertiesUpdate.html
+//! [`properties::Key`]: ../../proplist/properties/enum.Key.html
+//! [`Context::proplist_update`]: ../struct.Context.html#method.proplist_update
+//! [`Context::proplist_remove`]: ../struct.Context.html#method.proplist_remove
+
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context as TaskContext, Poll, Waker};
+use proplist::{MediaRole, PropValue, Proplist, UpdateMode};
+use proplist::properties::Key;
+use super::Context;
+
+/// Builder of a change to the client’s properties, created with
+/// [`Context::update_client_properties`].
+///
+/// See the [module level documentation](index.html) for details.
+///
+/// [`Context::update_client_properties`]: ../struct.Context.html#method.update_client_properties
+#[must_use = "an update does nothing until applied"]
+pub struct ClientPropertiesUpdate<'a> {
+    context: &'a mut Context,
+    mode: UpdateMode,
+    entries: Vec<(Key, PropValue)>,
+    removals: Vec<Key>,
+}
+
+/// State shared with the success callbacks of the operations.
+struct Shared {
+    remaining: usize,
+    success: bool,
+    callback: Option<Box<dyn FnOnce(bool)>>,
+    result: Option<bool>,
+    waker: Option<Waker>,
+}
+
+impl Shared {
+    fn complete(&mut self, success: bool) {
+        self.success &= success;
+        self.remaining -= 1;
+        if self.remaining > 0 {
+            return;
+        }
+        match self.callback.take() {
+            Some(callback) => callback(self.success),
+            None => {
+                self.result = Some(self.success);
+                if let Some(waker) = self.waker.take() {
+                    waker.wake();
+                }
+            },
+        }
+    }
+}
+
+impl Context {
+    /// Create a builder of a change to the client’s properties. See
+    /// [`::context::client_properties`](client_properties/index.html).
+    pub fn update_client_properties(&mut self) -> ClientPropertiesUpdate<'_> {
+        ClientPropertiesUpdate {
+            context: self,
+            mode: UpdateMode::Replace,
+            entries: Vec::new(),
+            removals: Vec::new(),
+        }
+    }
+}
+
+impl<'a> ClientPropertiesUpdate<'a> {
+    /// Set the mode of the update of the entries set. The default is [`UpdateMode::Replace`],
+    /// replacing any existing entries having the same keys.
+    ///
+    /// [`UpdateMode::Replace`]: ../../proplist/enum.UpdateMode.html#variant.Replace
+    pub fn mode(mut self, mode: UpdateMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Set an entry.
+    pub fn set<V: Into<PropValue>>(mut self, key: Key, value: V) -> Self {
+        self.removals.retain(|k| *k != key);
+        self.entries.retain(|&(ref k, _)| *k != key);
+        self.entries.push((key, value.into()));
+        self
+    }
+
+    /// Set the media role, along with the related properties of the role. See [`MediaRole`].
+    ///
+    /// [`MediaRole`]: ../../proplist/enum.MediaRole.html
+    pub fn media_role(mut self, role: MediaRole) -> Self {
+        self = self.set(Key::MediaRole, role.as_str());
+        for &(key, value) in role.related_properties() {
+            self = self.set(Key::from(key), value);
+        }
+        self
+    }
+
+    /// Remove an entry.
+    pub fn remove(mut self, key: Key) -> Self {
+        self.entries.retain(|&(ref k, _)| *k != key);
+        if !self.removals.contains(&key) {
+            self.removals.push(key);
+        }
+        self
+    }
+
+    /// Returns `true` if no entries are to be set or removed.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty() && self.removals.is_empty()
+    }
+
+    /// Apply the update, calling the callback once complete, with whether or not it succeeded.
+    ///
+    /// For an empty update, the callback is called immediately, with success.
+    ///
+    /// Panics on error, i.e. invalid arguments or state, or if a key is not valid.
+    pub fn apply<F>(self, callback: F)
+        where F: FnOnce(bool) + 'static
+    {
+        self.start(Some(Box::new(callback)));
+    }
+
+    /// Apply the update, returning a future giving whether or not it succeeded. It must be polled
+    /// from the thread running the main loop.
+    ///
+    /// Panics on error, i.e. invalid arguments or state, or if a key is not valid.
+    pub fn apply_async(self) -> ClientPropertiesFuture {
+        ClientPropertiesFuture { shared: self.start(None) }
+    }
+
+    fn start(self, callback: Option<Box<dyn FnOnce(bool)>>) -> Rc<RefCell<Shared>> {
+        let operations = (!self.entries.is_empty()) as usize + (!self.removals.is_empty()) as usize;
+        let shared = Rc::new(RefCell::new(Shared {
+            remaining: operations + 1,
+            success: true,
+            callback: callback,
+            result: None,
+            waker: None,
+        }));
+
+        if !self.entries.is_empty() {
+            let mut proplist = Proplist::new().unwrap();
+            for &(ref key, ref value) in &self.entries {
+                proplist.set_value(key.as_str(), value).expect("invalid property key");
+            }
+            let shared_cb = Rc::clone(&shared);
+            drop(self.context.proplist_update(self.mode, &proplist,
+                move |success| shared_cb.borrow_mut().complete(success)));
+        }
+        if !self.removals.is_empty() {
+            let keys: Vec<&str> = self.removals.iter().map(Key::as_str).collect();
+            let shared_cb = Rc::clone(&shared);
+            drop(self.context.proplist_remove(&keys,
+                move |success| shared_cb.borrow_mut().complete(success)));
+        }
+        // Completes immediately for an empty update
+        shared.borrow_mut().complete(true);
+        shared
+    }
+}
+
+/// Future completing once a change to the client’s properties has been applied, as returned by
+/// [`ClientPropertiesUpdate::apply_async`](struct.ClientPropertiesUpdate.html#method.apply_async).
+pub struct ClientPropertiesFuture {
+    shared: Rc<RefCell<Shared>>,
+}
+
+impl Future for ClientPropertiesFuture {
+    type Output = bool;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Self::Output> {
+        let mut shared = self.shared.borrow_mut();
+        match shared.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                shared.waker = Some(cx.waker().clone());
+                Poll::Pending
+            },
+        }
+    }
+}
@@ -0,0 +1,322 @@
+// Copyright 2018 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Hot-plug event coalescing.
+//!
+//! # Overview
+//!
+//! Plugging in a device typically produces a storm of subscription events: the card appears, its
+//! profile changes, sinks and sources are created, ports change, the default device changes, and
+//! modules are loaded. Refreshing a user interface upon each of these causes needless churn. An
+//! [`EventCoalescer`] instead collects such events as they arrive, and once the burst has settled,
+//! emits a single [`DeviceTopologyChanged`] summary of the objects affected.
+//!
+//! A burst is considered settled once no further event has arrived for the configured window. So
+//! that a continuous stream of events cannot delay the summary indefinitely, it is always emitted
+//! no later than a maximum delay after the first event of the burst, by default four times the
+//! window (see [`EventCoalescer::set_max_delay`]).
+//!
+//! The coalescer must be given the subscription events, by calling
+//! [`EventCoalescer::handle_event`] from the context’s subscribe callback, with the context
+//! subscribed to (at least) [`EventCoalescer::INTEREST_MASK`]. Timing is performed with a timer
+//! event of the given main loop.
+//!
+//! ```rust,ignore
+//! use pulse::context::coalesce::EventCoalescer;
+//! use pulse::time::MicroSeconds;
+//!
+//! let coalescer = Rc::new(EventCoalescer::new(&context, &mainloop,
+//!     MicroSeconds::MILLISECOND * 100, |summary| {
+//!         println!("sinks affected: {:?}", summary.indices(Facility::Sink));
+//!         rebuild_device_menu();
+//!     }));
+//!
+//! let coalescer_ref = Rc::clone(&coalescer);
+//! context.set_subscribe_callback(Some(Box::new(move |facility, operation, index| {
+//!     if !coalescer_ref.handle_event(facility, operation, index) {
+//!         // Not a topology event, e.g. a stream; handle it directly
+//!     }
+//! })));
+//! context.subscribe(EventCoalescer::INTEREST_MASK, |_| {});
+//! ```
+//!
+//! [`EventCoalescer`]: struct.EventCoalescer.html
+//! [`DeviceTopologyChanged`]: struct.DeviceTopologyChanged.html
+//! [`EventCoalescer::set_max_delay`]: struct.EventCoalescer.html#method.set_max_delay
+//! [`EventCoalescer::handle_event`]: struct.EventCoalescer.html#method.handle_event
+//! [`EventCoalescer::INTEREST_MASK`]: struct.EventCoalescer.html#associatedconstant.INTEREST_MASK
+
+use std;
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+use mainloop::api::{Mainloop, MainloopInnerType};
+use mainloop::events::timer::TimeEvent;
+use time::{MicroSeconds, MonotonicTs};
+use super::{Context, ContextRef};
+use super::subscribe::{Facility, InterestMaskSet, Operation, subscription_masks};
+
+/// Summary of a burst of device topology events.
+///
+/// Events for the same object are merged into its net change: for instance an object created and
+/// then changed is reported as new, and one created and then removed within the burst is not
+/// reported at all.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeviceTopologyChanged {
+    /// The net change of each affected object, as facility, index and operation, in order of the
+    /// first event for the object.
+    pub changes: Vec<(Facility, u32, Operation)>,
+    /// Whether or not the global server info changed, e.g. the default sink or source.
+    pub server_changed: bool,
+}
+
+impl DeviceTopologyChanged {
+    /// Returns `true` if nothing was affected.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty() && !self.server_changed
+    }
+
+    /// Get the indices of all affected objects of the given facility.
+    pub fn indices(&self, facility: Facility) -> Vec<u32> {
+        self.changes.iter()
+            .filter(|&&(f, _, _)| f == facility)
+            .map(|&(_, index, _)| index)
+            .collect()
+    }
+
+    /// Get the indices of the objects of the given facility having the given net change.
+    pub fn indices_with(&self, facility: Facility, operation: Operation) -> Vec<u32> {
+        self.changes.iter()
+            .filter(|&&(f, _, o)| f == facility && o == operation)
+            .map(|&(_, index, _)| index)
+            .collect()
+    }
+
+    /// Record an event, merging it with any earlier one for the same object.
+    fn record(&mut self, facility: Facility, operation: Operation, index: u32) {
+        if facility == Facility::Server {
+            self.server_changed = true;
+            return;
+        }
+        let pos = self.changes.iter().position(|&(f, i, _)| f == facility && i == index);
+        let pos = match pos {
+            Some(pos) => pos,
+            None => {
+                self.changes.push((facility, index, operation));
+                return;
+            },
+        };
+        let merged = match (self.changes[pos].2, operation) {
+            (Operation::New, Operation::Removed) => None,
+            (Operation::New, _) => Some(Operation::New),
+            (Operation::Removed, Operation::New) => Some(Operation::Changed),
+            (_, next) => Some(next),
+        };
+        match merged {
+            Some(operation) => self.changes[pos].2 = operation,
+            None => drop(self.changes.remove(pos)),
+        }
+    }
+}
+
+/// Events collected since the start of a burst.
+struct Burst {
+    first: MonotonicTs,
+    last: MonotonicTs,
+    summary: DeviceTopologyChanged,
+}
+
+impl Burst {
+    /// Get the time at which the burst is considered settled.
+    fn deadline(&self, window: MicroSeconds, max_delay: MicroSeconds) -> MonotonicTs {
+        std::cmp::min(self.last + window, self.first + max_delay)
+    }
+}
+
+/// Main loop handle for creating the timer event from within the subscribe callback.
+struct LoopRef<MI: MainloopInnerType>(Rc<MI>);
+
+impl<MI: MainloopInnerType> Mainloop for LoopRef<MI> {
+    type MI = MI;
+
+    fn inner(&self) -> Rc<MI> {
+        Rc::clone(&self.0)
+    }
+}
+
+/// State shared with the timer event callback.
+struct Shared<MI: MainloopInnerType + 'static> {
+    context: ContextRef,
+    mainloop: LoopRef<MI>,
+    window: MicroSeconds,
+    max_delay: MicroSeconds,
+    burst: Option<Burst>,
+    /// Timer event, created upon the first burst, and restarted for each later one.
+    timer: Option<TimeEvent<MI>>,
+}
+
+/// Coalescer of device topology subscription events.
+///
+/// See the [module level documentation](index.html) for details.
+pub struct EventCoalescer<MI: MainloopInnerType + 'static> {
+    shared: Rc<RefCell<Shared<MI>>>,
+    callback: Rc<RefCell<Box<dyn FnMut(DeviceTopologyChanged)>>>,
+}
+
+/// Emit the summary of the pending burst, if any, and if not empty.
+fn emit<MI: MainloopInnerType + 'static>(shared: &Rc<RefCell<Shared<MI>>>,
+    callback: &Rc<RefCell<Box<dyn FnMut(DeviceTopologyChanged)>>>)
+{
+    let burst = shared.borrow_mut().burst.take();
+    if let Some(burst) = burst {
+        if !burst.summary.is_empty() {
+            (callback.borrow_mut())(burst.summary);
+        }
+    }
+}
+
+impl<MI: MainloopInnerType + 'static> EventCoalescer<MI> {
+    /// The subscription interest mask covering the events coalesced.
+    pub const INTEREST_MASK: InterestMaskSet = subscription_masks::MASK_CARD
+        | subscription_masks::SINK | subscription_masks::SOURCE | subscription_masks::MODULE
+        | subscription_masks::SERVER;
+
+    /// Create a new coalescer for the given context, timed with the given main loop, emitting a
+    /// summary to the callback once no further event has arrived for the given window.
+    pub fn new<M, F>(context: &Context, mainloop: &M, window: MicroSeconds, callback: F) -> Self
+        where M: Mainloop<MI = MI>,
+              F: FnMut(DeviceTopologyChanged) + 'static
+    {
+        Self {
+            shared: Rc::new(RefCell::new(Shared {
+                context: Context::new_ref(context.ptr),
+                mainloop: LoopRef(mainloop.inner()),
+                window: window,
+                max_delay: window * 4,
+                burst: None,
+                timer: None,
+            })),
+            callback: Rc::new(RefCell::new(Box::new(callback))),
+        }
+    }
+
+    /// Set the maximum delay between the first event of a burst and the summary being emitted.
+    ///
+    /// Values below the window have no effect beyond emitting each burst after at most the window.
+    pub fn set_max_delay(&self, max_delay: MicroSeconds) {
+        self.shared.borrow_mut().max_delay = max_delay;
+    }
+
+    /// Handle a subscription event, as given to the context’s subscribe callback.
+    ///
+    /// Returns `true` if the event was collected, or `false` if it is not a device topology event
+    /// (i.e. not of a facility covered by [`INTEREST_MASK`]), in which case it is ignored.
+    ///
+    /// [`INTEREST_MASK`]: #associatedconstant.INTEREST_MASK
+    pub fn handle_event(&self, facility: Option<Facility>, operation: Option<Operation>,
+        index: u32) -> bool
+    {
+        let (facility, operation) = match (facility, operation) {
+            (Some(f @ Facility::Card), Some(o)) | (Some(f @ Facility::Sink), Some(o))
+            | (Some(f @ Facility::Source), Some(o)) | (Some(f @ Facility::Module), Some(o))
+            | (Some(f @ Facility::Server), Some(o)) => (f, o),
+            _ => return false,
+        };
+
+        let now = MonotonicTs::now();
+        let mut shared = self.shared.borrow_mut();
+        let (window, max_delay) = (shared.window, shared.max_delay);
+        let deadline = {
+            let burst = shared.burst.get_or_insert_with(|| {
+                Burst { first: now, last: now, summary: DeviceTopologyChanged::default() }
+            });
+            burst.last = now;
+            burst.summary.record(facility, operation, index);
+            burst.deadline(window, max_delay)
+        };
+
+        if let Some(timer) = shared.timer.as_mut() {
+            timer.restart_rt(deadline);
+            return true;
+        }
+        let weak_shared: Weak<RefCell<Shared<MI>>> = Rc::downgrade(&self.shared);
+        let weak_callback = Rc::downgrade(&self.callback);
+        let timer = shared.context.create_time_event(&shared.mainloop, deadline, move |_| {
+            if let (Some(shared), Some(callback)) = (weak_shared.upgrade(), weak_callback.upgrade())
+            {
+                emit(&shared, &callback);
+            }
+        });
+        shared.timer = timer;
+        true
+    }
+
+    /// Returns `true` if events have been collected, with the summary not yet emitted.
+    pub fn is_pending(&self) -> bool {
+        self.shared.borrow().burst.is_some()
+    }
+
+    /// Emit the summary of the events collected immediately, without waiting for the burst to
+    /// settle. Nothing is emitted if no events are pending.
+    ///
+    /// This must not be called from within the coalescer’s own callback.
+    pub fn flush(&self) {
+        emit(&self.shared, &self.callback);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_events() {
+        let mut summary = DeviceTopologyChanged::default();
+        assert!(summary.is_empty());
+
+        summary.record(Facility::Card, Operation::New, 3);
+        summary.record(Facility::Sink, Operation::New, 7);
+        summary.record(Facility::Sink, Operation::Changed, 7);
+        summary.record(Facility::Source, Operation::Changed, 2);
+        summary.record(Facility::Source, Operation::Removed, 2);
+        summary.record(Facility::Source, Operation::New, 9);
+        summary.record(Facility::Source, Operation::Removed, 9);
+        summary.record(Facility::Card, Operation::Changed, 3);
+        assert!(!summary.server_changed);
+
+        summary.record(Facility::Server, Operation::Changed, std::u32::MAX);
+        assert!(summary.server_changed);
+        assert_eq!(summary.changes, vec![
+            (Facility::Card, 3, Operation::New),
+            (Facility::Sink, 7, Operation::New),
+            (Facility::Source, 2, Operation::Removed),
+        ]);
+        assert_eq!(summary.indices(Facility::Sink), vec![7]);
+        assert_eq!(summary.indices_with(Facility::Source, Operation::Removed), vec![2]);
+        assert_eq!(summary.indices_with(Facility::Source, Operation::New), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn burst_deadline() {
+        let ms = MicroSeconds::MILLISECOND;
+        let start = MonotonicTs::default() + ms * 1000;
+        let mut burst = Burst { first: start, last: start,
+            summary: DeviceTopologyChanged::default() };
+        assert_eq!(burst.deadline(ms * 100, ms * 400), start + ms * 100);
+        burst.last = start + ms * 250;
+        assert_eq!(burst.deadline(ms * 100, ms * 400), start + ms * 350);
+        burst.last = start + ms * 380;
+        assert_eq!(burst.deadline(ms * 100, ms * 400), start + ms * 400);
+    }
+}
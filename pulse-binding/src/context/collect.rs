@@ -0,0 +1,259 @@
+// Copyright 2017 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Collection of introspection lists.
+//!
+//! # Overview
+//!
+//! The introspection `get_*_info_list` methods call their callback once per item, then once more to
+//! signal the end of the list (or an error). The combinators here take care of that bookkeeping,
+//! converting each item into an owned form (one implementing [`ListItem`]), and either:
+//!
+//! * Delivering the complete list as a `Vec` to a single closure, with
+//!   [`Introspector::collect_list`].
+//! * Providing the items as a `Stream`, with [`Introspector::list_stream`] (requires the
+//!   `futures-core` feature).
+//!
+//! The type of the owned items selects which list is fetched.
+//!
+//! ```rust,ignore
+//! use pulse::context::ops::Sink;
+//!
+//! context.introspect().collect_list(|result: Result<Vec<Sink>, _>| {
+//!     for sink in result.unwrap_or_default() {
+//!         println!("{}: {:?}", sink.index, sink.description);
+//!     }
+//! });
+//! ```
+//!
+//! [`ListItem`]: trait.ListItem.html
+//! [`Introspector::collect_list`]: ../introspect/struct.Introspector.html#method.collect_list
+//! [`Introspector::list_stream`]: ../introspect/struct.Introspector.html#method.list_stream
+
+use callbacks::ListEntry;
+use error::PAErr;
+use operation::Operation;
+use super::introspect::{Introspector, SinkInfo, SourceInfo, SinkInputInfo, SourceOutputInfo,
+    ClientInfo, CardInfo, SampleInfo};
+use super::ops::{Sink, Source, SinkInput, SourceOutput, Client, Card};
+
+/// An owned item of an introspection list, for use with [`Introspector::collect_list`] and
+/// [`Introspector::list_stream`].
+///
+/// This is implemented for the owned types of the [`ops`] module, and the `'static` (owned) forms
+/// of those introspection info types having a `to_owned` method.
+///
+/// [`Introspector::collect_list`]: ../introspect/struct.Introspector.html#method.collect_list
+/// [`Introspector::list_stream`]: ../introspect/struct.Introspector.html#method.list_stream
+/// [`ops`]: ../ops/index.html
+pub trait ListItem: Sized + 'static {
+    /// The closure type of the underlying list operation.
+    type Callback: ?Sized;
+
+    /// Request the list, giving each item, converted to this type, to the callback.
+    fn get_list<F>(introspector: &Introspector, callback: F) -> Operation<Self::Callback>
+        where F: FnMut(Result<ListEntry<Self>, PAErr>) + 'static;
+}
+
+/// Implement `ListItem` for a type convertible from the info type given by a list method.
+macro_rules! impl_list_item {
+    ($item:ty, $info:ident, $method:ident, $convert:expr) => {
+        impl ListItem for $item {
+            type Callback = dyn FnMut(Result<ListEntry<&$info>, PAErr>);
+
+            fn get_list<F>(introspector: &Introspector, mut callback: F)
+                -> Operation<Self::Callback>
+                where F: FnMut(Result<ListEntry<Self>, PAErr>) + 'static
+            {
+                introspector.$method(move |result: Result<ListEntry<&$info>, PAErr>| {
+                    callback(result.map(|r| match r {
+                        ListEntry::Item(item) => ListEntry::Item($convert(item)),
+                        ListEntry::End => ListEntry::End,
+                    }))
+                })
+            }
+        }
+    };
+}
+
+impl_list_item!(Sink, SinkInfo, get_sink_info_list, Sink::from);
+impl_list_item!(Source, SourceInfo, get_source_info_list, Source::from);
+impl_list_item!(SinkInput, SinkInputInfo, get_sink_input_info_list, SinkInput::from);
+impl_list_item!(SourceOutput, SourceOutputInfo, get_source_output_info_list, SourceOutput::from);
+impl_list_item!(Client, ClientInfo, get_client_info_list, Client::from);
+impl_list_item!(Card, CardInfo, get_card_info_list, Card::from);
+impl_list_item!(ClientInfo<'static>, ClientInfo, get_client_info_list, ClientInfo::to_owned);
+impl_list_item!(SampleInfo<'static>, SampleInfo, get_sample_info_list, SampleInfo::to_owned);
+
+impl Introspector {
+    /// Get a complete list, delivered as a `Vec` to the callback, once the end of the list has been
+    /// reached, or the error should one occur.
+    ///
+    /// The list fetched is selected by the item type, which must implement [`ListItem`].
+    ///
+    /// Panics on error, i.e. invalid arguments or state.
+    ///
+    /// [`ListItem`]: ../collect/trait.ListItem.html
+    pub fn collect_list<T, F>(&self, callback: F) -> Operation<T::Callback>
+        where T: ListItem,
+              F: FnOnce(Result<Vec<T>, PAErr>) + 'static
+    {
+        T::get_list(self, collector(callback))
+    }
+
+    /// Get a list as a `Stream` of items (requires the `futures-core` feature).
+    ///
+    /// The stream ends after the last item, or after giving an error should one occur. The list
+    /// fetched is selected by the item type, which must implement [`ListItem`].
+    ///
+    /// Panics on error, i.e. invalid arguments or state.
+    ///
+    /// [`ListItem`]: ../collect/trait.ListItem.html
+    #[cfg(feature = "futures-core")]
+    pub fn list_stream<T: ListItem>(&self) -> stream::ListStream<T> {
+        stream::ListStream::new(self)
+    }
+}
+
+/// Adapt a callback given a complete list to a list callback, collecting the items until the end of
+/// the list, or giving the error should one occur.
+fn collector<T, F>(callback: F) -> impl FnMut(Result<ListEntry<T>, PAErr>) + 'static
+    where T: 'static,
+          F: FnOnce(Result<Vec<T>, PAErr>) + 'static
+{
+    let mut callback = Some(callback);
+    let mut items = Vec::new();
+    move |result| match result {
+        Ok(ListEntry::Item(item)) => items.push(item),
+        Ok(ListEntry::End) => {
+            if let Some(callback) = callback.take() {
+                callback(Ok(::std::mem::take(&mut items)));
+            }
+        },
+        Err(e) => {
+            if let Some(callback) = callback.take() {
+                callback(Err(e));
+            }
+        },
+    }
+}
+
+#[cfg(feature = "futures-core")]
+pub use self::stream::ListStream;
+
+#[cfg(feature = "futures-core")]
+mod stream {
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+    use std::pin::Pin;
+    use std::rc::Rc;
+    use std::task::{Context, Poll, Waker};
+    use futures_core::Stream;
+    use callbacks::ListEntry;
+    use error::PAErr;
+    use operation::Operation;
+    use super::super::introspect::Introspector;
+    use super::ListItem;
+
+    struct Shared<T> {
+        items: VecDeque<Result<T, PAErr>>,
+        finished: bool,
+        waker: Option<Waker>,
+    }
+
+    /// A `Stream` of the items of an introspection list, as returned by
+    /// [`Introspector::list_stream`].
+    ///
+    /// The stream must be polled from the thread running the main loop.
+    ///
+    /// [`Introspector::list_stream`]: ../introspect/struct.Introspector.html#method.list_stream
+    pub struct ListStream<T: ListItem> {
+        shared: Rc<RefCell<Shared<T>>>,
+        _op: Operation<T::Callback>,
+    }
+
+    impl<T: ListItem> ListStream<T> {
+        pub(super) fn new(introspector: &Introspector) -> Self {
+            let shared = Rc::new(RefCell::new(Shared {
+                items: VecDeque::new(),
+                finished: false,
+                waker: None,
+            }));
+            let shared_cb = Rc::clone(&shared);
+            let op = T::get_list(introspector, move |result| {
+                let mut shared = shared_cb.borrow_mut();
+                match result {
+                    Ok(ListEntry::Item(item)) => shared.items.push_back(Ok(item)),
+                    Ok(_) => shared.finished = true,
+                    Err(e) => {
+                        shared.items.push_back(Err(e));
+                        shared.finished = true;
+                    },
+                }
+                if let Some(waker) = shared.waker.take() {
+                    waker.wake();
+                }
+            });
+            ListStream { shared: shared, _op: op }
+        }
+    }
+
+    impl<T: ListItem> Stream for ListStream<T> {
+        type Item = Result<T, PAErr>;
+
+        fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            let mut shared = self.shared.borrow_mut();
+            if let Some(item) = shared.items.pop_front() {
+                return Poll::Ready(Some(item));
+            }
+            if shared.finished {
+                return Poll::Ready(None);
+            }
+            shared.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use error::Code;
+
+    fn collect(entries: Vec<Result<ListEntry<u32>, PAErr>>) -> Vec<Result<Vec<u32>, PAErr>> {
+        let results = Rc::new(RefCell::new(Vec::new()));
+        let results_ref = Rc::clone(&results);
+        let mut callback = collector(move |result| results_ref.borrow_mut().push(result));
+        for entry in entries {
+            callback(entry);
+        }
+        let results = results.borrow_mut().drain(..).collect();
+        results
+    }
+
+    /// Test that the items are delivered upon the end of the list, and that an error part way
+    /// through is delivered in their place, just the once.
+    #[test]
+    fn collector_results() {
+        assert_eq!(collect(vec![Ok(ListEntry::Item(1)), Ok(ListEntry::Item(2)),
+            Ok(ListEntry::End)]), vec![Ok(vec![1, 2])]);
+
+        let error = PAErr::from(Code::ConnectionTerminated);
+        assert_eq!(collect(vec![Ok(ListEntry::Item(1)), Err(error), Err(error),
+            Ok(ListEntry::End)]), vec![Err(error)]);
+    }
+}
@@ -0,0 +1,163 @@
+// Copyright 2018 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Changing the default device, moving existing streams.
+//!
+//! # Overview
+//!
+//! Setting the default sink or source with [`Context::set_default_sink`] affects only streams
+//! created afterwards (at least with PulseAudio versions prior to 15). Desktop environments, such
+//! as GNOME, instead also move all existing streams to the new default, so that changing the output
+//! device takes effect immediately. [`Context::set_default_sink_and_move`] and
+//! [`Context::set_default_source_and_move`] do the same, giving a [`MoveSummary`] of the streams
+//! moved.
+//!
+//! ```rust,ignore
+//! context.set_default_sink_and_move("alsa_output.usb-headset", |result| {
+//!     if let Ok(summary) = result {
+//!         println!("moved {} streams", summary.moved.len());
+//!     }
+//! });
+//! ```
+//!
+//! [`Context::set_default_sink`]: ../struct.Context.html#method.set_default_sink
+//! [`Context::set_default_sink_and_move`]: ../struct.Context.html#method.set_default_sink_and_move
+//! [`Context::set_default_source_and_move`]:
+//! ../struct.Context.html#method.set_default_source_and_move
+//! [`MoveSummary`]: struct.MoveSummary.html
+
+use error::PAErr;
+use super::{Context, ContextRef};
+use super::ops::{Sink, Source, SinkInput, SourceOutput};
+
+/// Summary of the streams moved upon changing the default device.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MoveSummary {
+    /// Index of the new default device.
+    pub device: u32,
+    /// Indices of the sink inputs or source outputs moved.
+    pub moved: Vec<u32>,
+    /// Indices of those that could not be moved (e.g. created with the `DONT_MOVE` flag), along
+    /// with the error.
+    pub failed: Vec<(u32, PAErr)>,
+    /// Indices of those already connected to the device.
+    pub unchanged: Vec<u32>,
+}
+
+/// Generate the set-and-move method for a kind of device.
+///
+/// Takes the method name, the default setting method, the device type, the stream type, the field
+/// of the stream type giving its device, and the batch method for moving a stream.
+macro_rules! set_default_and_move {
+    ($(#[$attr:meta])* $method:ident, $set:ident, $device:ident, $stream:ident, $field:ident,
+     $move:ident) => {
+        $(#[$attr])*
+        pub fn $method<F>(&mut self, name: &str, callback: F)
+            where F: FnOnce(Result<MoveSummary, PAErr>) + 'static
+        {
+            let mut state = Some((Context::new_ref(self.ptr), name.to_string(), callback));
+            drop(self.$set(name, move |success| {
+                let (context, name, callback) = match state.take() {
+                    Some(state) => state,
+                    None => return,
+                };
+                if !success {
+                    return callback(Err(context.errno()));
+                }
+                let introspector = context.introspect();
+                drop(introspector.collect_list::<$device, _>(move |devices| {
+                    let device = match devices {
+                        Ok(devices) => devices.into_iter()
+                            .find(|d| d.name.as_ref() == Some(&name))
+                            .map(|d| d.index),
+                        Err(e) => return callback(Err(e)),
+                    };
+                    let device = match device {
+                        Some(device) => device,
+                        None => return callback(Err(PAErr::from(::error::Code::NoEntity))),
+                    };
+                    move_streams!(context, device, $stream, $field, $move, callback);
+                }));
+            }));
+        }
+    };
+}
+
+/// Move all streams not already connected to the device, giving the summary.
+macro_rules! move_streams {
+    ($context:expr, $device:expr, $stream:ident, $field:ident, $move:ident, $callback:expr) => {{
+        let context: ContextRef = $context;
+        let device: u32 = $device;
+        let callback = $callback;
+        let introspector = context.introspect();
+        drop(introspector.collect_list::<$stream, _>(move |streams| {
+            let streams = match streams {
+                Ok(streams) => streams,
+                Err(e) => return callback(Err(e)),
+            };
+            let mut summary = MoveSummary { device: device, ..MoveSummary::default() };
+            let mut batch = context.batch();
+            let mut to_move = Vec::new();
+            for stream in streams {
+                match stream.$field == device {
+                    true => summary.unchanged.push(stream.index),
+                    false => {
+                        batch = batch.$move(stream.index, device);
+                        to_move.push(stream.index);
+                    },
+                }
+            }
+            batch.run(move |result| {
+                if let Err(e) = result {
+                    for failure in e.failures {
+                        summary.failed.push((to_move[failure.position], failure.error));
+                    }
+                }
+                summary.moved = to_move.into_iter()
+                    .filter(|i| !summary.failed.iter().any(|&(f, _)| f == *i))
+                    .collect();
+                callback(Ok(summary));
+            });
+        }));
+    }};
+}
+
+impl Context {
+    set_default_and_move!(
+        /// Set the default sink, and move all existing sink inputs to it.
+        ///
+        /// The callback is given a summary of the sink inputs moved, or the error should setting
+        /// the default sink, or listing the sinks or sink inputs, fail. Failure to move individual
+        /// sink inputs is reported within the summary.
+        ///
+        /// Panics on error, i.e. invalid arguments or state.
+        set_default_sink_and_move, set_default_sink, Sink, SinkInput, sink,
+        move_sink_input_by_index
+    );
+
+    set_default_and_move!(
+        /// Set the default source, and move all existing source outputs to it.
+        ///
+        /// The callback is given a summary of the source outputs moved, or the error should
+        /// setting the default source, or listing the sources or source outputs, fail. Failure to
+        /// move individual source outputs is reported within the summary.
+        ///
+        /// Note that this includes source outputs recording from monitor sources.
+        ///
+        /// Panics on error, i.e. invalid arguments or state.
+        set_default_source_and_move, set_default_source, Source, SourceOutput, source,
+        move_source_output_by_index
+    );
+}
@@ -0,0 +1,494 @@
+// Copyright 2017 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Ducking of streams during phone calls.
+//!
+//! # Overview
+//!
+//! The `module-role-ducking` and `module-role-cork` server modules lower the volume of, or pause,
+//! streams of some roles whilst streams of other roles (such as phone calls) are playing. A
+//! [`Ducker`] provides the same, under the control of the application, for its own streams: whilst
+//! any sink input with a trigger role (by default [`MediaRole::Phone`]) exists, the streams
+//! registered with it are corked, or have their volume scaled down, as per the
+//! [`DuckingAction`] chosen. Once the last such sink input is removed, they are restored.
+//!
+//! The ducker is written against the [`IntrospectorOps`] trait, and so can be tested with the mock
+//! server of the `testing` module. Registered streams are those implementing [`Duckable`], as
+//! [`Stream`] does.
+//!
+//! As with the [`volume_cache`](../volume_cache/index.html) module, the ducker must be given the
+//! subscription events, by calling [`Ducker::handle_event`] from the context’s subscribe callback,
+//! with the context subscribed to (at least) [`Ducker::INTEREST_MASK`], and sink inputs existing
+//! beforehand are found with [`Ducker::refresh_now`].
+//!
+//! ```rust,ignore
+//! use pulse::context::ducking::{Ducker, DuckingAction};
+//! use pulse::volume::{Volume, VolumeDB};
+//!
+//! let action = DuckingAction::Attenuate(Volume::from(VolumeDB(-20.0)));
+//! let ducker = Rc::new(Ducker::new(&context, action));
+//! ducker.register(&music_stream);
+//! ducker.refresh_now();
+//!
+//! let ducker_ref = Rc::clone(&ducker);
+//! context.set_subscribe_callback(Some(Box::new(move |facility, operation, index| {
+//!     ducker_ref.handle_event(facility, operation, index);
+//! })));
+//! context.subscribe(Ducker::INTEREST_MASK, |_| {});
+//! ```
+//!
+//! Streams are only restored to the state they were in prior to ducking: a stream already corked
+//! is not uncorked, and the volume restored is that found upon ducking.
+//!
+//! [`Ducker`]: struct.Ducker.html
+//! [`MediaRole::Phone`]: ../../proplist/enum.MediaRole.html#variant.Phone
+//! [`DuckingAction`]: enum.DuckingAction.html
+//! [`Ducker::handle_event`]: struct.Ducker.html#method.handle_event
+//! [`Ducker::INTEREST_MASK`]: struct.Ducker.html#associatedconstant.INTEREST_MASK
+//! [`Ducker::refresh_now`]: struct.Ducker.html#method.refresh_now
+//! [`IntrospectorOps`]: ../ops/trait.IntrospectorOps.html
+//! [`Duckable`]: trait.Duckable.html
+//! [`Stream`]: ../../stream/struct.Stream.html
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::rc::{Rc, Weak};
+use error::PAErr;
+use proplist::MediaRole;
+use stream::Stream;
+use volume::{ChannelVolumes, Volume};
+use super::ops::{ContextOps, IntrospectorOps, SinkInput};
+use super::subscribe::{Facility, InterestMaskSet, Operation, subscription_masks};
+
+/// What is done to registered streams whilst ducked.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DuckingAction {
+    /// Cork (pause) the streams.
+    Cork,
+    /// Scale the volume of the streams by the given (software) volume factor, e.g.
+    /// `Volume::from(VolumeDB(-20.0))`.
+    Attenuate(Volume),
+}
+
+/// A playback stream which can be ducked, as implemented for [`Stream`].
+///
+/// [`Stream`]: ../../stream/struct.Stream.html
+pub trait Duckable {
+    /// Get the index of the sink input of the stream, or `None` if not connected.
+    fn sink_input_index(&self) -> Option<u32>;
+
+    /// Returns `true` if the stream is corked.
+    fn is_corked(&self) -> Result<bool, PAErr>;
+
+    /// Cork or uncork the stream.
+    fn set_corked(&mut self, corked: bool);
+}
+
+impl Duckable for Stream {
+    fn sink_input_index(&self) -> Option<u32> {
+        self.get_index()
+    }
+
+    fn is_corked(&self) -> Result<bool, PAErr> {
+        Stream::is_corked(self)
+    }
+
+    fn set_corked(&mut self, corked: bool) {
+        match corked {
+            true => drop(self.cork(None)),
+            false => drop(self.uncork(None)),
+        }
+    }
+}
+
+/// Ducks registered streams whilst streams of trigger roles exist.
+///
+/// See the [module level documentation](index.html) for details.
+pub struct Ducker {
+    shared: Rc<RefCell<Shared>>,
+}
+
+type StreamRef = Weak<RefCell<dyn Duckable>>;
+
+/// State shared with the introspection callbacks.
+struct Shared {
+    introspector: Box<dyn IntrospectorOps>,
+    action: DuckingAction,
+    trigger_roles: Vec<MediaRole>,
+    streams: Vec<Registered>,
+    /// Indices of the sink inputs of trigger roles.
+    triggers: HashSet<u32>,
+    ducked: bool,
+}
+
+/// A registered stream.
+struct Registered {
+    stream: StreamRef,
+    /// Whether or not the stream was corked upon ducking.
+    corked: bool,
+    /// Index of the sink input and its volume prior to ducking, if attenuated.
+    saved_volume: Option<(u32, ChannelVolumes)>,
+}
+
+/// The address of a referenced stream, for identifying it irrespective of its type.
+fn address(stream: &StreamRef) -> *const u8 {
+    stream.as_ptr() as *const u8
+}
+
+impl Shared {
+    /// Whether or not a sink input is one of the registered streams.
+    fn is_registered(&self, index: u32) -> bool {
+        self.streams.iter().filter_map(|r| r.stream.upgrade())
+            .any(|s| s.try_borrow().ok().and_then(|s| s.sink_input_index()) == Some(index))
+    }
+
+    /// Note whether or not a sink input has a trigger role, ducking or restoring as necessary.
+    fn update_trigger(shared_rc: &Rc<RefCell<Shared>>, index: u32, role: Option<MediaRole>) {
+        let mut shared = shared_rc.borrow_mut();
+        let triggers = match role {
+            Some(role) => shared.trigger_roles.contains(&role) && !shared.is_registered(index),
+            None => false,
+        };
+        match triggers {
+            true => { shared.triggers.insert(index); },
+            false => { shared.triggers.remove(&index); },
+        }
+        shared.apply(shared_rc);
+    }
+
+    /// Duck or restore the registered streams, as necessary for the current triggers.
+    fn apply(&mut self, shared_rc: &Rc<RefCell<Shared>>) {
+        let duck = !self.triggers.is_empty();
+        if duck == self.ducked {
+            return;
+        }
+        self.ducked = duck;
+        for i in 0..self.streams.len() {
+            match duck {
+                true => self.duck(shared_rc, i),
+                false => self.restore(i),
+            }
+        }
+    }
+
+    /// Duck a registered stream.
+    fn duck(&mut self, shared_rc: &Rc<RefCell<Shared>>, i: usize) {
+        let stream_rc = match self.streams[i].stream.upgrade() {
+            Some(stream) => stream,
+            None => return,
+        };
+        let mut stream = match stream_rc.try_borrow_mut() {
+            Ok(stream) => stream,
+            Err(_) => return,
+        };
+        match self.action {
+            DuckingAction::Cork => {
+                if stream.is_corked() == Ok(false) {
+                    stream.set_corked(true);
+                    self.streams[i].corked = true;
+                }
+            },
+            DuckingAction::Attenuate(factor) => {
+                let index = match stream.sink_input_index() {
+                    Some(index) => index,
+                    None => return,
+                };
+                let weak = Rc::downgrade(shared_rc);
+                let target = address(&self.streams[i].stream);
+                self.introspector.get_sink_input_info(index, Box::new(move |result| {
+                    let info = match result {
+                        Ok(info) => info,
+                        Err(_) => return,
+                    };
+                    let shared_rc = match weak.upgrade() {
+                        Some(shared) => shared,
+                        None => return,
+                    };
+                    let mut shared = shared_rc.borrow_mut();
+                    let shared = &mut *shared;
+                    let entry = shared.streams.iter_mut()
+                        .find(|r| address(&r.stream) == target);
+                    let entry = match entry {
+                        Some(entry) if shared.ducked && entry.saved_volume.is_none() => entry,
+                        _ => return,
+                    };
+                    entry.saved_volume = Some((index, info.volume));
+                    let mut volume = info.volume;
+                    volume.sw_multiply_scalar(factor);
+                    shared.introspector.set_sink_input_volume(index, &volume, None);
+                }));
+            },
+        }
+    }
+
+    /// Restore a registered stream.
+    fn restore(&mut self, i: usize) {
+        if let Some((index, volume)) = self.streams[i].saved_volume.take() {
+            self.introspector.set_sink_input_volume(index, &volume, None);
+        }
+        if self.streams[i].corked {
+            self.streams[i].corked = false;
+            if let Some(stream) = self.streams[i].stream.upgrade() {
+                if let Ok(mut stream) = stream.try_borrow_mut() {
+                    stream.set_corked(false);
+                }
+            }
+        }
+    }
+}
+
+impl Ducker {
+    /// The subscription interest mask needed for watching for streams of trigger roles.
+    pub const INTEREST_MASK: InterestMaskSet = subscription_masks::SINK_INPUT;
+
+    /// Create a new ducker for the given context, with no registered streams, taking the given
+    /// action upon ducking, and with [`MediaRole::Phone`] as the trigger role.
+    ///
+    /// [`MediaRole::Phone`]: ../../proplist/enum.MediaRole.html#variant.Phone
+    pub fn new(context: &dyn ContextOps, action: DuckingAction) -> Self {
+        Self {
+            shared: Rc::new(RefCell::new(Shared {
+                introspector: context.introspector(),
+                action: action,
+                trigger_roles: vec![MediaRole::Phone],
+                streams: Vec::new(),
+                triggers: HashSet::new(),
+                ducked: false,
+            })),
+        }
+    }
+
+    /// Set the roles of the sink inputs causing ducking.
+    ///
+    /// This only applies to sink inputs seen subsequently; use [`refresh_now`] to re-examine
+    /// existing ones.
+    ///
+    /// [`refresh_now`]: #method.refresh_now
+    pub fn set_trigger_roles(&self, roles: &[MediaRole]) {
+        self.shared.borrow_mut().trigger_roles = roles.to_vec();
+    }
+
+    /// Register a playback stream to be ducked. If currently ducked, it is ducked immediately.
+    ///
+    /// Only a weak reference to the stream is held. The stream is skipped whilst borrowed
+    /// elsewhere upon ducking or restoring.
+    pub fn register<S: Duckable + 'static>(&self, stream: &Rc<RefCell<S>>) {
+        let mut shared = self.shared.borrow_mut();
+        shared.streams.retain(|r| r.stream.upgrade().is_some());
+        let weak: Weak<RefCell<S>> = Rc::downgrade(stream);
+        shared.streams.push(Registered {
+            stream: weak,
+            corked: false,
+            saved_volume: None,
+        });
+        if shared.ducked {
+            let i = shared.streams.len() - 1;
+            shared.duck(&self.shared, i);
+        }
+    }
+
+    /// Unregister a stream, restoring it first if ducked.
+    pub fn unregister<S: Duckable + 'static>(&self, stream: &Rc<RefCell<S>>) {
+        let mut shared = self.shared.borrow_mut();
+        let target = Rc::as_ptr(stream) as *const u8;
+        if let Some(i) = shared.streams.iter().position(|r| address(&r.stream) == target) {
+            shared.restore(i);
+            shared.streams.remove(i);
+        }
+    }
+
+    /// Returns `true` if the registered streams are currently ducked.
+    pub fn is_ducked(&self) -> bool {
+        self.shared.borrow().ducked
+    }
+
+    /// Handle a subscription event, as given to the context’s subscribe callback.
+    ///
+    /// New and changed sink inputs are fetched, to check their role, and removed ones cease to
+    /// trigger ducking. Other events are ignored.
+    pub fn handle_event(&self, facility: Option<Facility>, operation: Option<Operation>,
+        index: u32)
+    {
+        if facility != Some(Facility::SinkInput) {
+            return;
+        }
+        match operation {
+            Some(Operation::Removed) => Shared::update_trigger(&self.shared, index, None),
+            Some(_) => {
+                let weak = Rc::downgrade(&self.shared);
+                self.shared.borrow().introspector.get_sink_input_info(index,
+                    Box::new(move |result: Result<SinkInput, PAErr>| {
+                        // An error most likely means it was removed since the request was made
+                        let role = result.ok().and_then(|info| info.media_role);
+                        if let Some(shared_rc) = weak.upgrade() {
+                            Shared::update_trigger(&shared_rc, index, role);
+                        }
+                    }));
+            },
+            None => {},
+        }
+    }
+
+    /// Examine all existing sink inputs afresh, ducking or restoring as necessary.
+    pub fn refresh_now(&self) {
+        let weak = Rc::downgrade(&self.shared);
+        self.shared.borrow().introspector.get_sink_input_info_list(
+            Box::new(move |result: Result<Vec<SinkInput>, PAErr>| {
+                let shared_rc = match weak.upgrade() {
+                    Some(shared) => shared,
+                    None => return,
+                };
+                let sink_inputs = match result {
+                    Ok(sink_inputs) => sink_inputs,
+                    Err(_) => return,
+                };
+                for info in &sink_inputs {
+                    Shared::update_trigger(&shared_rc, info.index, info.media_role);
+                }
+                let mut shared = shared_rc.borrow_mut();
+                shared.triggers.retain(|index| sink_inputs.iter().any(|i| i.index == *index));
+                shared.apply(&shared_rc);
+            }));
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use context::flags;
+    use testing::{MockContext, MockServer};
+    use volume::VOLUME_NORM;
+
+    /// A stream standing in for one connected to the mock server.
+    struct TestStream {
+        index: u32,
+        corked: bool,
+    }
+
+    impl Duckable for TestStream {
+        fn sink_input_index(&self) -> Option<u32> {
+            Some(self.index)
+        }
+
+        fn is_corked(&self) -> Result<bool, PAErr> {
+            Ok(self.corked)
+        }
+
+        fn set_corked(&mut self, corked: bool) {
+            self.corked = corked;
+        }
+    }
+
+    /// Create a ducker connected to a mock server, being given its subscription events, along with
+    /// the index of a sink, and the music stream of a sink input on it.
+    fn setup(action: DuckingAction)
+        -> (MockServer, MockContext, Rc<Ducker>, u32, Rc<RefCell<TestStream>>)
+    {
+        let server = MockServer::new();
+        let sink = server.add_sink("speakers", "Speakers");
+        let music = server.add_sink_input_with_role("music", sink, MediaRole::Music);
+        let mut context = server.context();
+        context.connect(None, flags::NOFLAGS).unwrap();
+        server.dispatch();
+
+        let ducker = Rc::new(Ducker::new(&context, action));
+        let ducker_ref = Rc::clone(&ducker);
+        context.set_subscribe_callback(Some(Box::new(move |facility, operation, index| {
+            ducker_ref.handle_event(facility, operation, index);
+        })));
+        context.subscribe(Ducker::INTEREST_MASK, Box::new(|_| {}));
+        server.dispatch();
+
+        let stream = Rc::new(RefCell::new(TestStream { index: music, corked: false }));
+        (server, context, ducker, sink, stream)
+    }
+
+    #[test]
+    fn attenuate_and_restore() {
+        let half = Volume(VOLUME_NORM.0 / 2);
+        let (server, _context, ducker, sink, stream) = setup(DuckingAction::Attenuate(half));
+        let music = stream.borrow().index;
+        ducker.register(&stream);
+        ducker.refresh_now();
+        server.dispatch();
+        assert!(!ducker.is_ducked());
+
+        let call = server.add_sink_input_with_role("call", sink, MediaRole::Phone);
+        server.dispatch();
+        assert!(ducker.is_ducked());
+        assert_eq!(server.sink_input(music).unwrap().volume.values[0].0, half.0);
+        assert_eq!(server.sink_input(call).unwrap().volume.values[0].0, VOLUME_NORM.0);
+
+        server.remove_sink_input(call);
+        server.dispatch();
+        assert!(!ducker.is_ducked());
+        assert_eq!(server.sink_input(music).unwrap().volume.values[0].0, VOLUME_NORM.0);
+    }
+
+    #[test]
+    fn cork_and_restore() {
+        let (server, _context, ducker, sink, stream) = setup(DuckingAction::Cork);
+        // Already corked, so to be left corked upon restoring
+        let paused = Rc::new(RefCell::new(TestStream { index: 100, corked: true }));
+        ducker.register(&stream);
+        ducker.register(&paused);
+
+        let call = server.add_sink_input_with_role("call", sink, MediaRole::Phone);
+        server.dispatch();
+        assert!(stream.borrow().corked);
+
+        server.remove_sink_input(call);
+        server.dispatch();
+        assert!(!stream.borrow().corked);
+        assert!(paused.borrow().corked);
+    }
+
+    #[test]
+    fn register_while_ducked() {
+        let (server, _context, ducker, sink, stream) = setup(DuckingAction::Cork);
+        server.add_sink_input_with_role("call", sink, MediaRole::Phone);
+        ducker.refresh_now();
+        server.dispatch();
+        assert!(ducker.is_ducked());
+
+        ducker.register(&stream);
+        assert!(stream.borrow().corked);
+
+        ducker.unregister(&stream);
+        assert!(!stream.borrow().corked);
+        assert!(ducker.is_ducked());
+    }
+
+    #[test]
+    fn remove_trigger() {
+        let (server, _context, ducker, sink, stream) = setup(DuckingAction::Cork);
+        ducker.register(&stream);
+        let first = server.add_sink_input_with_role("call", sink, MediaRole::Phone);
+        let second = server.add_sink_input_with_role("call", sink, MediaRole::Phone);
+        server.add_sink_input_with_role("music", sink, MediaRole::Music);
+        server.dispatch();
+        assert!(ducker.is_ducked());
+
+        // Ducked until the last trigger is removed
+        server.remove_sink_input(first);
+        server.dispatch();
+        assert!(ducker.is_ducked());
+        assert!(stream.borrow().corked);
+        server.remove_sink_input(second);
+        server.dispatch();
+        assert!(!ducker.is_ducked());
+        assert!(!stream.borrow().corked);
+    }
+}
@@ -0,0 +1,113 @@
+// Copyright 2017 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Volume control, generic over the kind of entity.
+//!
+//! # Overview
+//!
+//! Sinks, sources, sink inputs and source outputs each have their own volume and mute functions.
+//! Mixers, which typically treat these uniformly, can instead identify the entity with an
+//! [`AudioEntity`], and use [`Introspector::set_volume`] and [`Introspector::set_mute`], which
+//! dispatch to the appropriate function.
+//!
+//! ```rust,ignore
+//! use pulse::context::entity::AudioEntity;
+//!
+//! for entity in &[AudioEntity::Sink(0), AudioEntity::SinkInput(12)] {
+//!     introspector.set_mute(*entity, true, None);
+//! }
+//! ```
+//!
+//! [`AudioEntity`]: enum.AudioEntity.html
+//! [`Introspector::set_volume`]: ../introspect/struct.Introspector.html#method.set_volume
+//! [`Introspector::set_mute`]: ../introspect/struct.Introspector.html#method.set_mute
+
+use operation::Operation;
+use volume::ChannelVolumes;
+use super::introspect::Introspector;
+use super::subscribe::Facility;
+
+/// An entity having a volume and mute switch, identified by index.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum AudioEntity {
+    /// A sink.
+    Sink(u32),
+    /// A source.
+    Source(u32),
+    /// A sink input.
+    SinkInput(u32),
+    /// A source output.
+    SourceOutput(u32),
+}
+
+impl AudioEntity {
+    /// Get the entity corresponding to a subscription event, as given to the context’s subscribe
+    /// callback, or `None` for facilities without a volume.
+    pub fn from_facility(facility: Facility, index: u32) -> Option<Self> {
+        match facility {
+            Facility::Sink => Some(AudioEntity::Sink(index)),
+            Facility::Source => Some(AudioEntity::Source(index)),
+            Facility::SinkInput => Some(AudioEntity::SinkInput(index)),
+            Facility::SourceOutput => Some(AudioEntity::SourceOutput(index)),
+            _ => None,
+        }
+    }
+
+    /// Get the index of the entity.
+    pub fn index(&self) -> u32 {
+        match *self {
+            AudioEntity::Sink(index) |
+            AudioEntity::Source(index) |
+            AudioEntity::SinkInput(index) |
+            AudioEntity::SourceOutput(index) => index,
+        }
+    }
+}
+
+impl Introspector {
+    /// Set the volume of a sink, source, sink input or source output.
+    ///
+    /// Panics on error, i.e. invalid arguments or state.
+    ///
+    /// The optional callback must accept a `bool`, which indicates success.
+    pub fn set_volume(&mut self, entity: AudioEntity, volume: &ChannelVolumes,
+        callback: Option<Box<dyn FnMut(bool) + 'static>>) -> Operation<dyn FnMut(bool)>
+    {
+        match entity {
+            AudioEntity::Sink(index) => self.set_sink_volume_by_index(index, volume, callback),
+            AudioEntity::Source(index) => self.set_source_volume_by_index(index, volume, callback),
+            AudioEntity::SinkInput(index) => self.set_sink_input_volume(index, volume, callback),
+            AudioEntity::SourceOutput(index) => {
+                self.set_source_output_volume(index, volume, callback)
+            },
+        }
+    }
+
+    /// Set the mute switch of a sink, source, sink input or source output.
+    ///
+    /// Panics on error, i.e. invalid arguments or state.
+    ///
+    /// The optional callback must accept a `bool`, which indicates success.
+    pub fn set_mute(&mut self, entity: AudioEntity, mute: bool,
+        callback: Option<Box<dyn FnMut(bool) + 'static>>) -> Operation<dyn FnMut(bool)>
+    {
+        match entity {
+            AudioEntity::Sink(index) => self.set_sink_mute_by_index(index, mute, callback),
+            AudioEntity::Source(index) => self.set_source_mute_by_index(index, mute, callback),
+            AudioEntity::SinkInput(index) => self.set_sink_input_mute(index, mute, callback),
+            AudioEntity::SourceOutput(index) => self.set_source_output_mute(index, mute, callback),
+        }
+    }
+}
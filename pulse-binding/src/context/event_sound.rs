@@ -0,0 +1,276 @@
+// Copyright 2017 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Event sounds.
+//!
+//! # Overview
+//!
+//! Applications wanting to give audible feedback, such as a ‘ping’ upon a message arriving, can do
+//! so without a further dependency (such as libcanberra) using an [`EventSoundPlayer`]. Each sound
+//! is described by an [`EventSound`], identified by its event ID (e.g. `message-new-instant`, as
+//! per the freedesktop.org sound naming specification), which is played from the sample cache,
+//! with the `event.id` and `media.role` (`event`) properties set, such that the server can apply
+//! its policy for event sounds.
+//!
+//! [`EventSoundPlayer::play`] plays a sound already present in the sample cache (as uploaded by the
+//! desktop environment for the sound theme, or previously by the application), whilst
+//! [`EventSoundPlayer::play_or_upload`] falls back to uploading the given (short) buffer under the
+//! event ID, and then playing it, should the sound not be cached:
+//!
+//! ```rust,ignore
+//! use pulse::context::event_sound::{EventSound, EventSoundPlayer};
+//!
+//! let player = EventSoundPlayer::new(&context);
+//! let sound = EventSound::new("message-new-instant").with_description("Message received");
+//! player.play_or_upload(&sound, &spec, &ping_data, |result| {
+//!     if let Err(e) = result {
+//!         eprintln!("failed to play event sound: {}", e);
+//!     }
+//! });
+//! ```
+//!
+//! The player must be kept for as long as sounds are being uploaded.
+//!
+//! [`EventSoundPlayer`]: struct.EventSoundPlayer.html
+//! [`EventSound`]: struct.EventSound.html
+//! [`EventSoundPlayer::play`]: struct.EventSoundPlayer.html#method.play
+//! [`EventSoundPlayer::play_or_upload`]: struct.EventSoundPlayer.html#method.play_or_upload
+
+use capi;
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+use error::{Code, PAErr};
+use proplist::{MediaRole, Proplist, properties};
+use sample::Spec;
+use stream::State;
+use volume::{Volume, VOLUME_INVALID};
+use super::{Context, ContextRef};
+use super::scache::UploadStream;
+
+/// Callback given the index of the sink input playing the sound, or the error.
+type PlayCallback = Box<dyn FnOnce(Result<u32, PAErr>)>;
+
+/// Description of an event sound.
+#[derive(Debug, Clone)]
+pub struct EventSound {
+    /// Event ID, naming the sample in the cache.
+    pub id: String,
+    /// Sink to play the sound on, or `None` for the default.
+    pub device: Option<String>,
+    /// Volume to play the sound with. By default [`VOLUME_INVALID`], leaving the volume to the
+    /// server.
+    ///
+    /// [`VOLUME_INVALID`]: ../../volume/constant.VOLUME_INVALID.html
+    pub volume: Volume,
+    /// Properties of the sound, merged into those of the sample upon playing.
+    pub proplist: Proplist,
+}
+
+impl EventSound {
+    /// Create a new sound with the given event ID, with the `event.id` and `media.role` properties
+    /// set.
+    ///
+    /// Panics if the property list cannot be created.
+    pub fn new(id: &str) -> Self {
+        let mut proplist = Proplist::new().unwrap();
+        let _ = proplist.sets(properties::EVENT_ID, id);
+        let _ = proplist.set_media_role(MediaRole::Event);
+        EventSound {
+            id: id.to_string(),
+            device: None,
+            volume: VOLUME_INVALID,
+            proplist: proplist,
+        }
+    }
+
+    /// Set the human readable description of the event (`event.description`).
+    pub fn with_description(mut self, description: &str) -> Self {
+        let _ = self.proplist.sets(properties::EVENT_DESCRIPTION, description);
+        self
+    }
+
+    /// Set the sink to play the sound on.
+    pub fn with_device(mut self, device: &str) -> Self {
+        self.device = Some(device.to_string());
+        self
+    }
+
+    /// Set the volume to play the sound with.
+    pub fn with_volume(mut self, volume: Volume) -> Self {
+        self.volume = volume;
+        self
+    }
+}
+
+/// Plays event sounds, uploading them to the sample cache where necessary.
+///
+/// See the [module level documentation](index.html) for details.
+pub struct EventSoundPlayer {
+    shared: Rc<RefCell<Shared>>,
+}
+
+/// State shared with the stream and operation callbacks.
+struct Shared {
+    context: ContextRef,
+    /// Uploads in progress, or finished but not yet released, with their identifiers.
+    uploads: Vec<(u64, UploadStream)>,
+    next_upload: u64,
+}
+
+impl Shared {
+    /// Release finished uploads.
+    ///
+    /// This must not be called from within the state callback of an upload stream, since that
+    /// would free the callback whilst it is running.
+    fn release_finished(&mut self) {
+        self.uploads.retain(|&(_, ref upload)| match upload.get_state() {
+            State::Terminated | State::Failed => false,
+            _ => true,
+        });
+    }
+}
+
+/// Play a cached sound, calling the callback upon completion.
+fn play_cached(shared_rc: &Rc<RefCell<Shared>>, sound: &EventSound, callback: PlayCallback) {
+    let weak = Rc::downgrade(shared_rc);
+    let mut shared = shared_rc.borrow_mut();
+    let context = shared.context.ptr;
+    let mut callback = Some(callback);
+    let done = move |result: Result<u32, ()>| {
+        if let Some(shared) = weak.upgrade() {
+            shared.borrow_mut().release_finished();
+        }
+        if let Some(callback) = callback.take() {
+            callback(result.map_err(|_| PAErr(unsafe { capi::pa_context_errno(context) })));
+        }
+    };
+    let device = sound.device.as_ref().map(|d| d.as_str());
+    drop(shared.context.0.play_sample_with_proplist(&sound.id, device, sound.volume,
+        &sound.proplist, Some(Box::new(done))));
+}
+
+/// Upload a sound, playing it once uploaded.
+fn upload_and_play(shared_rc: &Rc<RefCell<Shared>>, sound: EventSound, spec: Spec, data: Vec<u8>,
+    callback: PlayCallback)
+{
+    let mut shared = shared_rc.borrow_mut();
+    let mut proplist = sound.proplist.clone();
+    let mut upload = match UploadStream::new_with_proplist(&mut shared.context.0, &sound.id,
+        &spec, None, &mut proplist, data.len())
+    {
+        Ok(upload) => upload,
+        Err(e) => {
+            drop(shared);
+            callback(Err(e));
+            return;
+        },
+    };
+    let id = shared.next_upload;
+    shared.next_upload += 1;
+
+    let context = shared.context.ptr;
+    let weak: Weak<RefCell<Shared>> = Rc::downgrade(shared_rc);
+    let mut pending = Some((sound, data, callback));
+    upload.set_state_callback(Some(Box::new(move || {
+        let shared_rc = match weak.upgrade() {
+            Some(shared) => shared,
+            None => return,
+        };
+        let failure = {
+            let mut shared = shared_rc.borrow_mut();
+            let upload = match shared.uploads.iter_mut().find(|&&mut (i, _)| i == id) {
+                Some(&mut (_, ref mut upload)) => upload,
+                None => return,
+            };
+            match upload.get_state() {
+                State::Ready => match pending.as_ref() {
+                    Some(&(_, ref data, _)) => {
+                        upload.write(data).and_then(|_| upload.finish()).err()
+                    },
+                    None => None,
+                },
+                State::Terminated => {
+                    if let Some((sound, _, callback)) = pending.take() {
+                        drop(shared);
+                        play_cached(&shared_rc, &sound, callback);
+                    }
+                    return;
+                },
+                State::Failed => Some(PAErr(unsafe { capi::pa_context_errno(context) })),
+                _ => None,
+            }
+        };
+        if let Some(e) = failure {
+            if let Some((_, _, callback)) = pending.take() {
+                callback(Err(e));
+            }
+        }
+    })));
+    shared.uploads.push((id, upload));
+}
+
+impl EventSoundPlayer {
+    /// Create a new player for the given context.
+    pub fn new(context: &Context) -> Self {
+        Self {
+            shared: Rc::new(RefCell::new(Shared {
+                context: Context::new_ref(context.ptr),
+                uploads: Vec::new(),
+                next_upload: 0,
+            })),
+        }
+    }
+
+    /// Play a sound present in the sample cache.
+    ///
+    /// The callback is given the index of the sink input playing the sound, or the error should
+    /// one occur, including [`Code::NoEntity`] if the sound is not cached.
+    ///
+    /// [`Code::NoEntity`]: ../../error/enum.Code.html#variant.NoEntity
+    pub fn play<F>(&self, sound: &EventSound, callback: F)
+        where F: FnOnce(Result<u32, PAErr>) + 'static
+    {
+        play_cached(&self.shared, sound, Box::new(callback));
+    }
+
+    /// Play a sound, uploading the given data to the sample cache under the sound’s event ID first,
+    /// should the sound not already be cached.
+    ///
+    /// The data must be in the format of the given sample spec, and should be short, since it is
+    /// uploaded in a single write.
+    ///
+    /// The callback is given the index of the sink input playing the sound, or the error should
+    /// one occur.
+    pub fn play_or_upload<F>(&self, sound: &EventSound, spec: &Spec, data: &[u8], callback: F)
+        where F: FnOnce(Result<u32, PAErr>) + 'static
+    {
+        let weak = Rc::downgrade(&self.shared);
+        let retry = sound.clone();
+        let spec = *spec;
+        let data = data.to_vec();
+        play_cached(&self.shared, sound, Box::new(move |result| match result {
+            Err(e) if e == PAErr::from(Code::NoEntity) => match weak.upgrade() {
+                Some(shared) => upload_and_play(&shared, retry, spec, data, Box::new(callback)),
+                None => callback(Err(e)),
+            },
+            result => callback(result),
+        }));
+    }
+
+    /// Get the number of uploads in progress (or finished, but not yet released).
+    pub fn pending_uploads(&self) -> usize {
+        self.shared.borrow().uploads.len()
+    }
+}
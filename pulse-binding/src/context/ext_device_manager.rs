@@ -149,6 +149,7 @@ impl DeviceManager {
             Some(super::ext_test_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(u32)>)
+            .traced("ext_device_manager::test", None)
     }
 
     /// Read all entries from the device database.
@@ -162,6 +163,7 @@ impl DeviceManager {
             cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(ListResult<&Info>)>)
+            .traced("ext_device_manager::read", None)
     }
 
     /// Sets the description for a device.
@@ -185,6 +187,7 @@ impl DeviceManager {
         };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("ext_device_manager::set_device_description", None)
     }
 
     /// Delete entries from the device database.
@@ -215,6 +218,7 @@ impl DeviceManager {
             Some(super::success_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("ext_device_manager::delete", None)
     }
 
     /// Enable the role-based device-priority routing mode.
@@ -233,6 +237,7 @@ impl DeviceManager {
         };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("ext_device_manager::enable_role_device_priority_routing", None)
     }
 
     /// Prefer a given device in the priority list.
@@ -267,6 +272,7 @@ impl DeviceManager {
         };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("ext_device_manager::reorder_devices_for_role", None)
     }
 
     /// Subscribe to changes in the device database.
@@ -282,6 +288,7 @@ impl DeviceManager {
             Some(super::success_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("ext_device_manager::subscribe", None)
     }
 
     /// Set the subscription callback that is called when [`subscribe`](#method.subscribe) was
@@ -108,6 +108,7 @@ impl DeviceRestore {
             Some(super::ext_test_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(u32)>)
+            .traced("ext_device_restore::test", None)
     }
 
     /// Subscribe to changes in the device database.
@@ -123,6 +124,7 @@ impl DeviceRestore {
             Some(super::success_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("ext_device_restore::subscribe", None)
     }
 
     /// Set the subscription callback that is called when [`subscribe`](#method.subscribe) was
@@ -152,6 +154,7 @@ impl DeviceRestore {
             Some(read_list_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(ListResult<&Info>)>)
+            .traced("ext_device_restore::read_formats_all", None)
     }
 
     /// Read an entry from the device database.
@@ -166,6 +169,7 @@ impl DeviceRestore {
             Some(read_list_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(ListResult<&Info>)>)
+            .traced("ext_device_restore::read_formats", Some(index))
     }
 
     /// Read an entry from the device database.
@@ -180,7 +184,7 @@ impl DeviceRestore {
         // Capture array of pointers to the above ::format::InfoInternal objects
         let mut format_ptrs: Vec<*mut capi::pa_format_info> = Vec::with_capacity(formats.len());
         for format in formats {
-            format_ptrs.push(unsafe { std::mem::transmute(&format.ptr) });
+            format_ptrs.push(unsafe { std::mem::transmute(format.ptr) });
         }
 
         let cb_data = box_closure_get_capi_ptr::<dyn FnMut(bool)>(Box::new(callback));
@@ -191,6 +195,7 @@ impl DeviceRestore {
         };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("ext_device_restore::save_formats", Some(index))
     }
 }
 
@@ -55,7 +55,7 @@ impl<'a> Info<'a> {
                 },
                 channel_map: std::mem::transmute(src.channel_map),
                 volume: std::mem::transmute(src.volume),
-                device: match src.name.is_null() {
+                device: match src.device.is_null() {
                     false => Some(CStr::from_ptr(src.device).to_string_lossy()),
                     true => None,
                 },
@@ -110,6 +110,7 @@ impl StreamRestore {
             Some(super::ext_test_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(u32)>)
+            .traced("ext_stream_restore::test", None)
     }
 
     /// Read all entries from the stream database.
@@ -123,6 +124,7 @@ impl StreamRestore {
             cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(ListResult<&Info>)>)
+            .traced("ext_stream_restore::read", None)
     }
 
     /// Store entries in the stream database.
@@ -134,14 +136,41 @@ impl StreamRestore {
         apply_immediately: bool, callback: F) -> Operation<dyn FnMut(bool)>
         where F: FnMut(bool) + 'static
     {
+        // Warning: New CStrings will be immediately freed if not bound to a variable, leading to
+        // as_ptr() giving dangling pointers!
+        let mut c_strings: Vec<CString> = Vec::with_capacity(data.len() * 2);
+        let mut entries: Vec<InfoInternal> = Vec::with_capacity(data.len());
+        for info in data {
+            let mut c_ptr = |s: &Option<Cow<str>>| match *s {
+                Some(ref s) => {
+                    let c_str = CString::new(s.as_ref()).unwrap();
+                    let ptr = c_str.as_ptr();
+                    c_strings.push(c_str);
+                    ptr
+                },
+                None => null::<c_char>(),
+            };
+            let name = c_ptr(&info.name);
+            let device = c_ptr(&info.device);
+            entries.push(InfoInternal {
+                name: name,
+                channel_map: unsafe { std::mem::transmute(info.channel_map) },
+                volume: unsafe { std::mem::transmute(info.volume) },
+                device: device,
+                mute: info.mute as i32,
+            });
+        }
+
         let cb_data = box_closure_get_capi_ptr::<dyn FnMut(bool)>(Box::new(callback));
+        // Note, the C function takes an array of entries, not of pointers to them
         let ptr = unsafe {
             capi::pa_ext_stream_restore_write(self.context, mode,
-                std::mem::transmute(data.as_ptr()), data.len() as u32, apply_immediately as i32,
-                Some(super::success_cb_proxy), cb_data)
+                entries.as_ptr() as *const *const InfoInternal, entries.len() as u32,
+                apply_immediately as i32, Some(super::success_cb_proxy), cb_data)
         };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("ext_stream_restore::write", None)
     }
 
     /// Delete entries from the stream database.
@@ -172,6 +201,7 @@ impl StreamRestore {
             Some(super::success_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("ext_stream_restore::delete", None)
     }
 
     /// Subscribe to changes in the stream database.
@@ -187,6 +217,7 @@ impl StreamRestore {
             Some(super::success_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("ext_stream_restore::subscribe", None)
     }
 
     /// Set the subscription callback that is called when [`subscribe`](#method.subscribe) was
@@ -0,0 +1,288 @@
+// Copyright 2017 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Sink and source lookup.
+//!
+//! # Overview
+//!
+//! [`Introspector::find_sink`] and [`Introspector::find_source`] fetch the sink or source list, and
+//! give the best match found for a matcher, which can be either a [`DeviceMatcher`] or a closure
+//! taking a reference to the info object and returning a `bool`.
+//!
+//! ```rust,ignore
+//! use pulse::context::find::DeviceMatcher;
+//!
+//! let introspector = context.introspect();
+//! introspector.find_sink(DeviceMatcher::Fuzzy("headphones".into()), |result| {
+//!     if let Ok(Some(sink)) = result {
+//!         println!("using {:?}", sink.name);
+//!     }
+//! });
+//! introspector.find_source(DeviceMatcher::Property("device.bus".into(), "bluetooth".into()),
+//!     |result| { /* ... */ });
+//! ```
+//!
+//! [`Introspector::find_sink`]: ../introspect/struct.Introspector.html#method.find_sink
+//! [`Introspector::find_source`]: ../introspect/struct.Introspector.html#method.find_source
+//! [`DeviceMatcher`]: enum.DeviceMatcher.html
+
+use callbacks::ListEntry;
+use error::PAErr;
+use operation::Operation;
+use super::introspect::{Introspector, SinkInfo, SourceInfo};
+use super::ops::{Sink, Source};
+
+/// Common attributes of sinks and sources, as examined by [`DeviceMatcher`].
+///
+/// [`DeviceMatcher`]: enum.DeviceMatcher.html
+pub trait DeviceInfo {
+    /// Name of the device.
+    fn name(&self) -> Option<&str>;
+    /// Description of the device.
+    fn description(&self) -> Option<&str>;
+    /// Get the value of a property of the device, as a string.
+    fn property(&self, key: &str) -> Option<String>;
+}
+
+impl<'a> DeviceInfo for SinkInfo<'a> {
+    fn name(&self) -> Option<&str> {
+        self.name.as_ref().map(|s| s.as_ref())
+    }
+
+    fn description(&self) -> Option<&str> {
+        self.description.as_ref().map(|s| s.as_ref())
+    }
+
+    fn property(&self, key: &str) -> Option<String> {
+        self.proplist.gets(key)
+    }
+}
+
+impl<'a> DeviceInfo for SourceInfo<'a> {
+    fn name(&self) -> Option<&str> {
+        self.name.as_ref().map(|s| s.as_ref())
+    }
+
+    fn description(&self) -> Option<&str> {
+        self.description.as_ref().map(|s| s.as_ref())
+    }
+
+    fn property(&self, key: &str) -> Option<String> {
+        self.proplist.gets(key)
+    }
+}
+
+/// A matcher of devices, used for lookup.
+///
+/// This is implemented for [`DeviceMatcher`], and closures taking a reference to the info object
+/// and returning whether or not it matches.
+///
+/// [`DeviceMatcher`]: enum.DeviceMatcher.html
+pub trait Matcher<I: ?Sized> {
+    /// Check the device, giving a score if it matches, with higher scores indicating better
+    /// matches, or `None` if it does not match.
+    fn score(&mut self, info: &I) -> Option<u32>;
+}
+
+impl<I: ?Sized, F> Matcher<I> for F
+    where F: FnMut(&I) -> bool
+{
+    fn score(&mut self, info: &I) -> Option<u32> {
+        match (self)(info) {
+            true => Some(0),
+            false => None,
+        }
+    }
+}
+
+/// Common device matching criteria.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeviceMatcher {
+    /// Exact name match.
+    Name(String),
+    /// Case-insensitive description substring match. A complete match is preferred over a prefix,
+    /// which is preferred over any other substring.
+    Description(String),
+    /// Property match, where the property with the given key (e.g. `device.bus`) has the given
+    /// value (e.g. `bluetooth`).
+    Property(String, String),
+    /// Case-insensitive name or description match, as typically given by a user. In order of
+    /// preference: exact name; complete description; description prefix; name substring;
+    /// description substring.
+    Fuzzy(String),
+}
+
+/// Case-insensitive match of `pattern` (already lowercase) within `s`, giving a score of 2 for a
+/// complete match, 1 for a prefix and 0 for any other substring.
+fn substring_score(s: &str, pattern: &str) -> Option<u32> {
+    let s = s.to_lowercase();
+    if s == pattern {
+        Some(2)
+    }
+    else if s.starts_with(pattern) {
+        Some(1)
+    }
+    else if s.contains(pattern) {
+        Some(0)
+    }
+    else {
+        None
+    }
+}
+
+impl<I: DeviceInfo + ?Sized> Matcher<I> for DeviceMatcher {
+    fn score(&mut self, info: &I) -> Option<u32> {
+        match *self {
+            DeviceMatcher::Name(ref name) => match info.name() == Some(name.as_str()) {
+                true => Some(0),
+                false => None,
+            },
+            DeviceMatcher::Description(ref pattern) => {
+                info.description().and_then(|d| substring_score(d, &pattern.to_lowercase()))
+            },
+            DeviceMatcher::Property(ref key, ref value) => {
+                match info.property(key).as_ref() == Some(value) {
+                    true => Some(0),
+                    false => None,
+                }
+            },
+            DeviceMatcher::Fuzzy(ref pattern) => {
+                if info.name() == Some(pattern.as_str()) {
+                    return Some(5);
+                }
+                let pattern = pattern.to_lowercase();
+                let description = info.description().and_then(|d| substring_score(d, &pattern));
+                match description {
+                    Some(score) if score > 0 => Some(score + 2),
+                    _ => match info.name().and_then(|n| substring_score(n, &pattern)) {
+                        Some(_) => Some(1),
+                        None => description,
+                    },
+                }
+            },
+        }
+    }
+}
+
+/// Adapt a callback to a list callback, giving the best match, converted to owned form.
+///
+/// Takes the matcher, the callback, and the info type given to the list callback.
+macro_rules! find_adapter {
+    ($matcher:expr, $callback:expr, $info:ident) => {{
+        let mut matcher = $matcher;
+        let mut callback = Some($callback);
+        let mut best = None;
+        move |result: Result<ListEntry<&$info>, PAErr>| match result {
+            Ok(ListEntry::Item(item)) => {
+                if let Some(score) = matcher.score(item) {
+                    match best {
+                        Some((best_score, _)) if best_score >= score => {},
+                        _ => best = Some((score, item.into())),
+                    }
+                }
+            },
+            Ok(ListEntry::End) => {
+                if let Some(callback) = callback.take() {
+                    callback(Ok(best.take().map(|(_, item)| item)));
+                }
+            },
+            Err(e) => {
+                if let Some(callback) = callback.take() {
+                    callback(Err(e));
+                }
+            },
+        }
+    }};
+}
+
+impl Introspector {
+    /// Find the sink best matching the given matcher, which can be a [`DeviceMatcher`], or a
+    /// closure taking a `&SinkInfo` and returning whether or not it matches.
+    ///
+    /// The callback is given the best match, if any, or the error should one occur. Where multiple
+    /// sinks match equally well, the first listed is given.
+    ///
+    /// Panics on error, i.e. invalid arguments or state.
+    ///
+    /// [`DeviceMatcher`]: ../find/enum.DeviceMatcher.html
+    pub fn find_sink<M, F>(&self, matcher: M, callback: F)
+        -> Operation<dyn FnMut(Result<ListEntry<&SinkInfo>, PAErr>)>
+        where M: for<'a> Matcher<SinkInfo<'a>> + 'static,
+              F: FnOnce(Result<Option<Sink>, PAErr>) + 'static
+    {
+        self.get_sink_info_list(find_adapter!(matcher, callback, SinkInfo))
+    }
+
+    /// Find the source best matching the given matcher, which can be a [`DeviceMatcher`], or a
+    /// closure taking a `&SourceInfo` and returning whether or not it matches.
+    ///
+    /// The callback is given the best match, if any, or the error should one occur. Where multiple
+    /// sources match equally well, the first listed is given.
+    ///
+    /// Panics on error, i.e. invalid arguments or state.
+    ///
+    /// [`DeviceMatcher`]: ../find/enum.DeviceMatcher.html
+    pub fn find_source<M, F>(&self, matcher: M, callback: F)
+        -> Operation<dyn FnMut(Result<ListEntry<&SourceInfo>, PAErr>)>
+        where M: for<'a> Matcher<SourceInfo<'a>> + 'static,
+              F: FnOnce(Result<Option<Source>, PAErr>) + 'static
+    {
+        self.get_source_info_list(find_adapter!(matcher, callback, SourceInfo))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Device(&'static str, &'static str);
+
+    impl DeviceInfo for Device {
+        fn name(&self) -> Option<&str> { Some(self.0) }
+        fn description(&self) -> Option<&str> { Some(self.1) }
+        fn property(&self, key: &str) -> Option<String> {
+            match key {
+                "device.bus" => Some("usb".to_string()),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn device_matcher_scores() {
+        let dev = Device("alsa_output.usb-headset", "USB Headset Analog Stereo");
+
+        assert_eq!(DeviceMatcher::Name("alsa_output.usb-headset".into()).score(&dev), Some(0));
+        assert_eq!(DeviceMatcher::Name("usb-headset".into()).score(&dev), None);
+
+        assert_eq!(DeviceMatcher::Description("usb headset".into()).score(&dev), Some(1));
+        assert_eq!(DeviceMatcher::Description("analog".into()).score(&dev), Some(0));
+        assert_eq!(DeviceMatcher::Description("hdmi".into()).score(&dev), None);
+
+        let bus = |v: &str| DeviceMatcher::Property("device.bus".into(), v.into());
+        assert_eq!(bus("usb").score(&dev), Some(0));
+        assert_eq!(bus("bluetooth").score(&dev), None);
+
+        assert_eq!(DeviceMatcher::Fuzzy("alsa_output.usb-headset".into()).score(&dev), Some(5));
+        assert_eq!(DeviceMatcher::Fuzzy("usb headset analog stereo".into()).score(&dev), Some(4));
+        assert_eq!(DeviceMatcher::Fuzzy("USB Headset".into()).score(&dev), Some(3));
+        assert_eq!(DeviceMatcher::Fuzzy("headset".into()).score(&dev), Some(1));
+        assert_eq!(DeviceMatcher::Fuzzy("stereo".into()).score(&dev), Some(0));
+        assert_eq!(DeviceMatcher::Fuzzy("hdmi".into()).score(&dev), None);
+
+        let mut closure = |d: &Device| d.0.ends_with("headset");
+        assert_eq!(closure.score(&dev), Some(0));
+    }
+}
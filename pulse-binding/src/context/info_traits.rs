@@ -0,0 +1,136 @@
+// Copyright 2017 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Traits common to introspection info objects.
+//!
+//! # Overview
+//!
+//! The info objects given by the [`Introspector`] share many attributes, such as an index and
+//! property list. The traits here give access to these, allowing generic code, such as filters,
+//! caches and renderers, to operate upon any of them:
+//!
+//! * [`Indexed`]: objects with an index, i.e. all of them.
+//! * [`HasProplist`]: objects with a property list, i.e. all of them.
+//! * [`HasVolume`]: objects with a volume and mute switch, i.e. sinks, sources, sink inputs and
+//!   source outputs.
+//!
+//! ```rust,ignore
+//! use pulse::context::info_traits::{Indexed, HasProplist};
+//!
+//! fn app_names<T: Indexed + HasProplist>(items: &[T]) -> Vec<(u32, Option<String>)> {
+//!     items.iter().map(|i| (i.index(), i.proplist().gets("application.name"))).collect()
+//! }
+//! ```
+//!
+//! [`Introspector`]: ../introspect/struct.Introspector.html
+//! [`Indexed`]: trait.Indexed.html
+//! [`HasProplist`]: trait.HasProplist.html
+//! [`HasVolume`]: trait.HasVolume.html
+
+use proplist::Proplist;
+use volume::ChannelVolumes;
+use super::introspect::{SinkInfo, SourceInfo, SinkInputInfo, SourceOutputInfo, CardInfo,
+    ClientInfo, ModuleInfo, SampleInfo};
+
+/// Objects identified by an index.
+pub trait Indexed {
+    /// Index of the object.
+    fn index(&self) -> u32;
+}
+
+/// Objects having a property list.
+pub trait HasProplist {
+    /// Property list of the object.
+    fn proplist(&self) -> &Proplist;
+}
+
+/// Objects having a volume and mute switch.
+pub trait HasVolume {
+    /// Volume of the object.
+    fn volume(&self) -> &ChannelVolumes;
+    /// Mute switch of the object.
+    fn mute(&self) -> bool;
+    /// Whether or not the volume can be set. This is always `true` for sinks and sources.
+    fn volume_writable(&self) -> bool {
+        true
+    }
+}
+
+macro_rules! impl_indexed {
+    ( $( $info:ident ),* ) => { $(
+        impl<'a> Indexed for $info<'a> {
+            fn index(&self) -> u32 {
+                self.index
+            }
+        }
+
+        impl<'a> HasProplist for $info<'a> {
+            fn proplist(&self) -> &Proplist {
+                &self.proplist
+            }
+        }
+    )* };
+}
+
+impl_indexed!(SinkInfo, SourceInfo, SinkInputInfo, SourceOutputInfo, CardInfo, ClientInfo,
+    ModuleInfo, SampleInfo);
+
+impl<'a> HasVolume for SinkInfo<'a> {
+    fn volume(&self) -> &ChannelVolumes {
+        &self.volume
+    }
+
+    fn mute(&self) -> bool {
+        self.mute
+    }
+}
+
+impl<'a> HasVolume for SourceInfo<'a> {
+    fn volume(&self) -> &ChannelVolumes {
+        &self.volume
+    }
+
+    fn mute(&self) -> bool {
+        self.mute
+    }
+}
+
+impl<'a> HasVolume for SinkInputInfo<'a> {
+    fn volume(&self) -> &ChannelVolumes {
+        &self.volume
+    }
+
+    fn mute(&self) -> bool {
+        self.mute
+    }
+
+    fn volume_writable(&self) -> bool {
+        self.volume_writable
+    }
+}
+
+impl<'a> HasVolume for SourceOutputInfo<'a> {
+    fn volume(&self) -> &ChannelVolumes {
+        &self.volume
+    }
+
+    fn mute(&self) -> bool {
+        self.mute
+    }
+
+    fn volume_writable(&self) -> bool {
+        self.volume_writable
+    }
+}
@@ -34,10 +34,12 @@
 //! with the desired data.
 //!
 //! Some objects can have multiple instances on the server. When requesting all of these at once,
-//! the callback will be called multiple times, each time with an [`ListResult`] variant. It will be
-//! called once for each item in turn, using the `Item` variant, and then once more time with the
-//! `End` variant to signal that the end of the list has been reached. If an error occurs, then
-//! the `Error` variant will be given.
+//! the callback will be called multiple times, each time with an `Ok` wrapped [`ListEntry`]
+//! variant. It will be called once for each item in turn, using the `Item` variant, and then once
+//! more time with the `End` variant to signal that the end of the list has been reached. If an
+//! error occurs, then instead an `Err` will be given, holding the context’s error code at the time
+//! of failure (as from [`Context::errno`]), allowing for instance a non-existent object to be
+//! distinguished from a connection failure.
 //!
 //! Note that even if a single object is requested, and not the entire list, the terminating call
 //! will still be made.
@@ -128,6 +130,13 @@
 //! * By index: [`Introspector::set_sink_mute_by_index`], [`Introspector::set_source_mute_by_index`]
 //! * By name:  [`Introspector::set_sink_mute_by_name`], [`Introspector::set_source_mute_by_name`]
 //!
+//! Sinks and sources can also be suspended (and resumed), which closes the underlying audio device
+//! allowing it to idle:
+//!
+//! * By index: [`Introspector::suspend_sink_by_index`], [`Introspector::suspend_source_by_index`]
+//! * By name:  [`Introspector::suspend_sink_by_name`], [`Introspector::suspend_source_by_name`]
+//! * All:      [`Introspector::suspend_all`]
+//!
 //! # Sink Inputs and Source Outputs
 //!
 //! If an application desires to modify the volume of just a single stream (commonly one of its own
@@ -176,7 +185,8 @@
 //! [`::subscribe`]: ../subscribe/index.html
 //!
 //! [`Context`]: ../struct.Context.html
-//! [`ListResult`]: ../../callbacks/enum.ListResult.html
+//! [`Context::errno`]: ../struct.Context.html#method.errno
+//! [`ListEntry`]: ../../callbacks/enum.ListEntry.html
 //! [`Introspector`]: struct.Introspector.html
 //! [`ClientInfo`]: struct.ClientInfo.html
 //! [`ModuleInfo`]: struct.ModuleInfo.html
@@ -221,6 +231,11 @@
 //! [`Introspector::set_source_volume_by_index`]: struct.Introspector.html#method.set_source_volume_by_index
 //! [`Introspector::set_source_volume_by_name`]: struct.Introspector.html#method.set_source_volume_by_name
 //! [`Introspector::stat`]: struct.Introspector.html#method.stat
+//! [`Introspector::suspend_all`]: struct.Introspector.html#method.suspend_all
+//! [`Introspector::suspend_sink_by_index`]: struct.Introspector.html#method.suspend_sink_by_index
+//! [`Introspector::suspend_sink_by_name`]: struct.Introspector.html#method.suspend_sink_by_name
+//! [`Introspector::suspend_source_by_index`]: struct.Introspector.html#method.suspend_source_by_index
+//! [`Introspector::suspend_source_by_name`]: struct.Introspector.html#method.suspend_source_by_name
 //! [`Introspector::unload_module`]: struct.Introspector.html#method.unload_module
 
 use std;
@@ -229,10 +244,13 @@ use std::os::raw::c_void;
 use std::ffi::{CStr, CString};
 use std::borrow::Cow;
 use std::ptr::null_mut;
+use std::rc::Rc;
+use std::cell::RefCell;
 use super::{Context, ContextInternal};
 use time::MicroSeconds;
-use callbacks::{ListResult, box_closure_get_capi_ptr, callback_for_list_instance, ListInstanceCallback};
+use callbacks::{ListEntry, box_closure_get_capi_ptr, callback_for_list_instance, ListInstanceCallback};
 use operation::Operation;
+use error::PAErr;
 
 use capi::pa_sink_port_info as SinkPortInfoInternal;
 use capi::pa_sink_info as SinkInfoInternal;
@@ -252,7 +270,7 @@ pub use capi::pa_stat_info as StatInfo;
 
 /// A wrapper object providing introspection routines to a context.
 pub struct Introspector {
-    context: *mut super::ContextInternal,
+    pub(crate) context: *mut super::ContextInternal,
 }
 
 unsafe impl Send for Introspector {}
@@ -301,6 +319,22 @@ pub struct SinkPortInfo<'a> {
     pub priority: u32,
     /// A flag indicating availability status of this port.
     pub available: ::def::PortAvailable,
+    /// An identifier for the group of ports that share their availability status with each
+    /// other. This is meant especially for handling cases where one 3.5 mm connector is used for
+    /// headphones, headsets and microphones, and the hardware can only tell that something was
+    /// plugged in but not what exactly. In this situation the ports for all those devices share
+    /// their availability status, and PulseAudio can’t tell which one is actually plugged in, and
+    /// some application may ask the user what was plugged in. Such applications should get a list
+    /// of all card ports and compare their `availability_group` fields. Ports that have the same
+    /// group are those that need input from the user to determine which device was plugged in.
+    /// The application should then activate the user-chosen port.
+    ///
+    /// May be `None`, in which case the port is not part of any availability group.
+    #[cfg(feature = "pa_v14_compatibility")]
+    pub availability_group: Option<Cow<'a, str>>,
+    /// Port device type.
+    #[cfg(feature = "pa_v14_compatibility")]
+    pub r#type: ::def::DevicePortType,
 }
 
 impl<'a> SinkPortInfo<'a> {
@@ -318,7 +352,14 @@ impl<'a> SinkPortInfo<'a> {
                     true => None,
                 },
                 priority: src.priority,
-                available: std::mem::transmute(src.available),
+                available: src.available.into(),
+                #[cfg(feature = "pa_v14_compatibility")]
+                availability_group: match src.availability_group.is_null() {
+                    false => Some(CStr::from_ptr(src.availability_group).to_string_lossy()),
+                    true => None,
+                },
+                #[cfg(feature = "pa_v14_compatibility")]
+                r#type: src.r#type.into(),
             }
         }
     }
@@ -432,7 +473,7 @@ impl<'a> SinkInfo<'a> {
                 proplist: ::proplist::Proplist::from_raw_weak(src.proplist),
                 configured_latency: MicroSeconds(src.configured_latency),
                 base_volume: ::volume::Volume(src.base_volume),
-                state: std::mem::transmute(src.state),
+                state: src.state.into(),
                 n_volume_steps: src.n_volume_steps,
                 card: match src.card {
                     ::def::INVALID_INDEX => None,
@@ -454,45 +495,49 @@ impl Introspector {
     ///
     /// Panics on error, i.e. invalid arguments or state.
     pub fn get_sink_info_by_name<F>(&self, name: &str, callback: F)
-        -> Operation<dyn FnMut(ListResult<&SinkInfo>)>
-        where F: FnMut(ListResult<&SinkInfo>) + 'static
+        -> Operation<dyn FnMut(Result<ListEntry<&SinkInfo>, PAErr>)>
+        where F: FnMut(Result<ListEntry<&SinkInfo>, PAErr>) + 'static
     {
         // Warning: New CStrings will be immediately freed if not bound to a variable, leading to
         // as_ptr() giving dangling pointers!
         let c_name = CString::new(name.clone()).unwrap();
 
-        let cb_data = box_closure_get_capi_ptr::<dyn FnMut(ListResult<&SinkInfo>)>(Box::new(callback));
+        let cb_data = box_closure_get_capi_ptr::<dyn FnMut(Result<ListEntry<&SinkInfo>, PAErr>)>(Box::new(callback));
         let ptr = unsafe { capi::pa_context_get_sink_info_by_name(self.context, c_name.as_ptr(),
             Some(get_sink_info_list_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
-        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(ListResult<&SinkInfo>)>)
+        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(Result<ListEntry<&SinkInfo>, PAErr>)>)
+            .traced("introspect::get_sink_info_by_name", None)
     }
 
     /// Get information about a sink by its index.
     ///
     /// Panics on error, i.e. invalid arguments or state.
     pub fn get_sink_info_by_index<F>(&self, index: u32, callback: F)
-        -> Operation<dyn FnMut(ListResult<&SinkInfo>)>
-        where F: FnMut(ListResult<&SinkInfo>) + 'static
+        -> Operation<dyn FnMut(Result<ListEntry<&SinkInfo>, PAErr>)>
+        where F: FnMut(Result<ListEntry<&SinkInfo>, PAErr>) + 'static
     {
-        let cb_data = box_closure_get_capi_ptr::<dyn FnMut(ListResult<&SinkInfo>)>(Box::new(callback));
+        let cb_data = box_closure_get_capi_ptr::<dyn FnMut(Result<ListEntry<&SinkInfo>, PAErr>)>(Box::new(callback));
         let ptr = unsafe { capi::pa_context_get_sink_info_by_index(self.context, index,
             Some(get_sink_info_list_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
-        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(ListResult<&SinkInfo>)>)
+        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(Result<ListEntry<&SinkInfo>, PAErr>)>)
+            .traced("introspect::get_sink_info_by_index", Some(index))
     }
 
     /// Get the complete sink list.
     ///
     /// Panics on error, i.e. invalid arguments or state.
-    pub fn get_sink_info_list<F>(&self, callback: F) -> Operation<dyn FnMut(ListResult<&SinkInfo>)>
-        where F: FnMut(ListResult<&SinkInfo>) + 'static
+    pub fn get_sink_info_list<F>(&self, callback: F)
+        -> Operation<dyn FnMut(Result<ListEntry<&SinkInfo>, PAErr>)>
+        where F: FnMut(Result<ListEntry<&SinkInfo>, PAErr>) + 'static
     {
-        let cb_data = box_closure_get_capi_ptr::<dyn FnMut(ListResult<&SinkInfo>)>(Box::new(callback));
+        let cb_data = box_closure_get_capi_ptr::<dyn FnMut(Result<ListEntry<&SinkInfo>, PAErr>)>(Box::new(callback));
         let ptr = unsafe { capi::pa_context_get_sink_info_list(self.context,
             Some(get_sink_info_list_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
-        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(ListResult<&SinkInfo>)>)
+        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(Result<ListEntry<&SinkInfo>, PAErr>)>)
+            .traced("introspect::get_sink_info_list", None)
     }
 
     /// Set the volume of a sink device specified by its index.
@@ -509,6 +554,7 @@ impl Introspector {
             std::mem::transmute(volume), cb_fn, cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("introspect::set_sink_volume_by_index", Some(index))
     }
 
     /// Set the volume of a sink device specified by its name.
@@ -529,6 +575,7 @@ impl Introspector {
             std::mem::transmute(volume), cb_fn, cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("introspect::set_sink_volume_by_name", None)
     }
 
     /// Set the mute switch of a sink device specified by its index.
@@ -545,6 +592,7 @@ impl Introspector {
             cb_fn, cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("introspect::set_sink_mute_by_index", Some(index))
     }
 
     /// Set the mute switch of a sink device specified by its name.
@@ -565,6 +613,7 @@ impl Introspector {
             mute as i32, cb_fn, cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("introspect::set_sink_mute_by_name", None)
     }
 
     /// Suspend/Resume a sink.
@@ -585,6 +634,7 @@ impl Introspector {
             suspend as i32, cb_fn, cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("introspect::suspend_sink_by_name", None)
     }
 
     /// Suspend/Resume a sink.
@@ -602,6 +652,7 @@ impl Introspector {
             suspend as i32, cb_fn, cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("introspect::suspend_sink_by_index", Some(index))
     }
 
     /// Change the profile of a sink.
@@ -622,6 +673,7 @@ impl Introspector {
             c_port.as_ptr(), cb_fn, cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("introspect::set_sink_port_by_index", Some(index))
     }
 
     /// Change the profile of a sink.
@@ -643,24 +695,27 @@ impl Introspector {
             c_port.as_ptr(), cb_fn, cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("introspect::set_sink_port_by_name", None)
     }
 }
 
 /// Proxy for get sink info list callbacks.
 /// Warning: This is for list cases only! On EOL it destroys the actual closure callback.
 extern "C"
-fn get_sink_info_list_cb_proxy(_: *mut ContextInternal, i: *const SinkInfoInternal, eol: i32,
+fn get_sink_info_list_cb_proxy(c: *mut ContextInternal, i: *const SinkInfoInternal, eol: i32,
     userdata: *mut c_void)
 {
     let _ = std::panic::catch_unwind(|| {
-        match callback_for_list_instance::<dyn FnMut(ListResult<&SinkInfo>)>(eol, userdata) {
+        match callback_for_list_instance::<dyn FnMut(Result<ListEntry<&SinkInfo>, PAErr>)>(eol, userdata) {
             ListInstanceCallback::Entry(callback) => {
                 assert!(!i.is_null());
                 let obj = SinkInfo::new_from_raw(i);
-                (callback)(ListResult::Item(&obj));
+                (callback)(Ok(ListEntry::Item(&obj)));
+            },
+            ListInstanceCallback::End(mut callback) => { (callback)(Ok(ListEntry::End)); },
+            ListInstanceCallback::Error(mut callback) => {
+                (callback)(Err(PAErr(unsafe { capi::pa_context_errno(c) })));
             },
-            ListInstanceCallback::End(mut callback) => { (callback)(ListResult::End); },
-            ListInstanceCallback::Error(mut callback) => { (callback)(ListResult::Error); },
         }
     });
 }
@@ -683,6 +738,22 @@ pub struct SourcePortInfo<'a> {
     pub priority: u32,
     /// A flag indicating availability status of this port.
     pub available: ::def::PortAvailable,
+    /// An identifier for the group of ports that share their availability status with each
+    /// other. This is meant especially for handling cases where one 3.5 mm connector is used for
+    /// headphones, headsets and microphones, and the hardware can only tell that something was
+    /// plugged in but not what exactly. In this situation the ports for all those devices share
+    /// their availability status, and PulseAudio can’t tell which one is actually plugged in, and
+    /// some application may ask the user what was plugged in. Such applications should get a list
+    /// of all card ports and compare their `availability_group` fields. Ports that have the same
+    /// group are those that need input from the user to determine which device was plugged in.
+    /// The application should then activate the user-chosen port.
+    ///
+    /// May be `None`, in which case the port is not part of any availability group.
+    #[cfg(feature = "pa_v14_compatibility")]
+    pub availability_group: Option<Cow<'a, str>>,
+    /// Port device type.
+    #[cfg(feature = "pa_v14_compatibility")]
+    pub r#type: ::def::DevicePortType,
 }
 
 impl<'a> SourcePortInfo<'a> {
@@ -700,7 +771,14 @@ impl<'a> SourcePortInfo<'a> {
                     true => None,
                 },
                 priority: src.priority,
-                available: std::mem::transmute(src.available),
+                available: src.available.into(),
+                #[cfg(feature = "pa_v14_compatibility")]
+                availability_group: match src.availability_group.is_null() {
+                    false => Some(CStr::from_ptr(src.availability_group).to_string_lossy()),
+                    true => None,
+                },
+                #[cfg(feature = "pa_v14_compatibility")]
+                r#type: src.r#type.into(),
             }
         }
     }
@@ -834,51 +912,59 @@ impl<'a> SourceInfo<'a> {
     }
 }
 
+/// Shared state of the callbacks used by [`Introspector::suspend_all`].
+///
+/// [`Introspector::suspend_all`]: struct.Introspector.html#method.suspend_all
+type SuspendAllState = Rc<RefCell<(Box<dyn FnMut(bool)>, u8, bool)>>;
+
 impl Introspector {
     /// Get information about a source by its name.
     ///
     /// Panics on error, i.e. invalid arguments or state.
     pub fn get_source_info_by_name<F>(&self, name: &str, callback: F)
-        -> Operation<dyn FnMut(ListResult<&SourceInfo>)>
-        where F: FnMut(ListResult<&SourceInfo>) + 'static
+        -> Operation<dyn FnMut(Result<ListEntry<&SourceInfo>, PAErr>)>
+        where F: FnMut(Result<ListEntry<&SourceInfo>, PAErr>) + 'static
     {
         // Warning: New CStrings will be immediately freed if not bound to a variable, leading to
         // as_ptr() giving dangling pointers!
         let c_name = CString::new(name.clone()).unwrap();
 
-        let cb_data = box_closure_get_capi_ptr::<dyn FnMut(ListResult<&SourceInfo>)>(Box::new(callback));
+        let cb_data = box_closure_get_capi_ptr::<dyn FnMut(Result<ListEntry<&SourceInfo>, PAErr>)>(Box::new(callback));
         let ptr = unsafe { capi::pa_context_get_source_info_by_name(self.context, c_name.as_ptr(),
             Some(get_source_info_list_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
-        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(ListResult<&SourceInfo>)>)
+        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(Result<ListEntry<&SourceInfo>, PAErr>)>)
+            .traced("introspect::get_source_info_by_name", None)
     }
 
     /// Get information about a source by its index.
     ///
     /// Panics on error, i.e. invalid arguments or state.
     pub fn get_source_info_by_index<F>(&self, index: u32, callback: F)
-        -> Operation<dyn FnMut(ListResult<&SourceInfo>)>
-        where F: FnMut(ListResult<&SourceInfo>) + 'static
+        -> Operation<dyn FnMut(Result<ListEntry<&SourceInfo>, PAErr>)>
+        where F: FnMut(Result<ListEntry<&SourceInfo>, PAErr>) + 'static
     {
-        let cb_data = box_closure_get_capi_ptr::<dyn FnMut(ListResult<&SourceInfo>)>(Box::new(callback));
+        let cb_data = box_closure_get_capi_ptr::<dyn FnMut(Result<ListEntry<&SourceInfo>, PAErr>)>(Box::new(callback));
         let ptr = unsafe { capi::pa_context_get_source_info_by_index(self.context, index,
             Some(get_source_info_list_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
-        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(ListResult<&SourceInfo>)>)
+        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(Result<ListEntry<&SourceInfo>, PAErr>)>)
+            .traced("introspect::get_source_info_by_index", Some(index))
     }
 
     /// Get the complete source list.
     ///
     /// Panics on error, i.e. invalid arguments or state.
     pub fn get_source_info_list<F>(&self, callback: F)
-        -> Operation<dyn FnMut(ListResult<&SourceInfo>)>
-        where F: FnMut(ListResult<&SourceInfo>) + 'static
+        -> Operation<dyn FnMut(Result<ListEntry<&SourceInfo>, PAErr>)>
+        where F: FnMut(Result<ListEntry<&SourceInfo>, PAErr>) + 'static
     {
-        let cb_data = box_closure_get_capi_ptr::<dyn FnMut(ListResult<&SourceInfo>)>(Box::new(callback));
+        let cb_data = box_closure_get_capi_ptr::<dyn FnMut(Result<ListEntry<&SourceInfo>, PAErr>)>(Box::new(callback));
         let ptr = unsafe { capi::pa_context_get_source_info_list(self.context,
             Some(get_source_info_list_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
-        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(ListResult<&SourceInfo>)>)
+        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(Result<ListEntry<&SourceInfo>, PAErr>)>)
+            .traced("introspect::get_source_info_list", None)
     }
 
     /// Set the volume of a source device specified by its index.
@@ -895,6 +981,7 @@ impl Introspector {
             std::mem::transmute(volume), cb_fn, cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("introspect::set_source_volume_by_index", Some(index))
     }
 
     /// Set the volume of a source device specified by its name.
@@ -915,6 +1002,7 @@ impl Introspector {
             std::mem::transmute(volume), cb_fn, cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("introspect::set_source_volume_by_name", None)
     }
 
     /// Set the mute switch of a source device specified by its index.
@@ -931,6 +1019,7 @@ impl Introspector {
             mute as i32, cb_fn, cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("introspect::set_source_mute_by_index", Some(index))
     }
 
     /// Set the mute switch of a source device specified by its name.
@@ -951,6 +1040,7 @@ impl Introspector {
             mute as i32, cb_fn, cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("introspect::set_source_mute_by_name", None)
     }
 
     /// Suspend/Resume a source.
@@ -971,6 +1061,7 @@ impl Introspector {
             suspend as i32, cb_fn, cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("introspect::suspend_source_by_name", None)
     }
 
     /// Suspend/Resume a source.
@@ -988,6 +1079,48 @@ impl Introspector {
             suspend as i32, cb_fn, cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("introspect::suspend_source_by_index", Some(index))
+    }
+
+    /// Suspend/Resume all sinks and sources.
+    ///
+    /// This issues both [`suspend_sink_by_index`] and [`suspend_source_by_index`] with
+    /// [`::def::INVALID_INDEX`], returning the sink and source operations respectively. Panics on
+    /// error, i.e. invalid arguments or state.
+    ///
+    /// The optional callback must accept a `bool`, which indicates success. It is called only once,
+    /// after both operations have completed, indicating success only if both succeeded.
+    ///
+    /// [`suspend_sink_by_index`]: #method.suspend_sink_by_index
+    /// [`suspend_source_by_index`]: #method.suspend_source_by_index
+    /// [`::def::INVALID_INDEX`]: ../../def/constant.INVALID_INDEX.html
+    pub fn suspend_all(&mut self, suspend: bool,
+        callback: Option<Box<dyn FnMut(bool) + 'static>>)
+        -> (Operation<dyn FnMut(bool)>, Operation<dyn FnMut(bool)>)
+    {
+        let (sink_cb, source_cb) = match callback {
+            Some(callback) => {
+                // Shared between the two callbacks: the user callback, the number of operations yet
+                // to complete, and whether all completed so far succeeded.
+                let shared: SuspendAllState = Rc::new(RefCell::new((callback, 2u8, true)));
+                let make_cb = |shared: SuspendAllState| {
+                    Box::new(move |success: bool| {
+                        let mut state = shared.borrow_mut();
+                        state.1 -= 1;
+                        state.2 &= success;
+                        if state.1 == 0 {
+                            let all_succeeded = state.2;
+                            (state.0)(all_succeeded);
+                        }
+                    }) as Box<dyn FnMut(bool)>
+                };
+                (Some(make_cb(shared.clone())), Some(make_cb(shared)))
+            },
+            None => (None, None),
+        };
+        let sinks_op = self.suspend_sink_by_index(::def::INVALID_INDEX, suspend, sink_cb);
+        let sources_op = self.suspend_source_by_index(::def::INVALID_INDEX, suspend, source_cb);
+        (sinks_op, sources_op)
     }
 
     /// Change the profile of a source.
@@ -1008,6 +1141,7 @@ impl Introspector {
             c_port.as_ptr(), cb_fn, cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("introspect::set_source_port_by_index", Some(index))
     }
 
     /// Change the profile of a source.
@@ -1029,24 +1163,27 @@ impl Introspector {
             c_port.as_ptr(), cb_fn, cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("introspect::set_source_port_by_name", None)
     }
 }
 
 /// Proxy for get source info list callbacks.
 /// Warning: This is for list cases only! On EOL it destroys the actual closure callback.
 extern "C"
-fn get_source_info_list_cb_proxy(_: *mut ContextInternal, i: *const SourceInfoInternal, eol: i32,
+fn get_source_info_list_cb_proxy(c: *mut ContextInternal, i: *const SourceInfoInternal, eol: i32,
     userdata: *mut c_void)
 {
     let _ = std::panic::catch_unwind(|| {
-        match callback_for_list_instance::<dyn FnMut(ListResult<&SourceInfo>)>(eol, userdata) {
+        match callback_for_list_instance::<dyn FnMut(Result<ListEntry<&SourceInfo>, PAErr>)>(eol, userdata) {
             ListInstanceCallback::Entry(callback) => {
                 assert!(!i.is_null());
                 let obj = SourceInfo::new_from_raw(i);
-                (callback)(ListResult::Item(&obj));
+                (callback)(Ok(ListEntry::Item(&obj)));
+            },
+            ListInstanceCallback::End(mut callback) => { (callback)(Ok(ListEntry::End)); },
+            ListInstanceCallback::Error(mut callback) => {
+                (callback)(Err(PAErr(unsafe { capi::pa_context_errno(c) })));
             },
-            ListInstanceCallback::End(mut callback) => { (callback)(ListResult::End); },
-            ListInstanceCallback::Error(mut callback) => { (callback)(ListResult::Error); },
         }
     });
 }
@@ -1117,6 +1254,87 @@ impl<'a> ServerInfo<'a> {
             }
         }
     }
+
+    /// Create an owned copy of this information, which, unlike the original, may be kept beyond
+    /// the scope of the callback it was provided to.
+    ///
+    /// This is useful for keeping a copy to compare against with [`diff`] when a change is
+    /// notified via a subscription to [`::context::subscribe::Facility::Server`] events.
+    ///
+    /// [`diff`]: #method.diff
+    /// [`::context::subscribe::Facility::Server`]: ../subscribe/enum.Facility.html#variant.Server
+    pub fn to_owned(&self) -> ServerInfo<'static> {
+        ServerInfo {
+            user_name: self.user_name.as_ref().map(|s| Cow::Owned(s.to_string())),
+            host_name: self.host_name.as_ref().map(|s| Cow::Owned(s.to_string())),
+            server_version: self.server_version.as_ref().map(|s| Cow::Owned(s.to_string())),
+            server_name: self.server_name.as_ref().map(|s| Cow::Owned(s.to_string())),
+            sample_spec: self.sample_spec,
+            default_sink_name: self.default_sink_name.as_ref().map(|s| Cow::Owned(s.to_string())),
+            default_source_name: self.default_source_name.as_ref().map(|s| Cow::Owned(s.to_string())),
+            cookie: self.cookie,
+            channel_map: self.channel_map,
+        }
+    }
+
+    /// Compare two sets of server information, returning the set of changes going from `old` to
+    /// `new`. An empty set is returned if nothing changed.
+    pub fn diff(old: &ServerInfo, new: &ServerInfo) -> Vec<ServerChange> {
+        let mut changes = Vec::new();
+        if old.user_name != new.user_name {
+            changes.push(ServerChange::UserName);
+        }
+        if old.host_name != new.host_name {
+            changes.push(ServerChange::HostName);
+        }
+        if old.server_version != new.server_version {
+            changes.push(ServerChange::ServerVersion);
+        }
+        if old.server_name != new.server_name {
+            changes.push(ServerChange::ServerName);
+        }
+        if old.sample_spec != new.sample_spec {
+            changes.push(ServerChange::SampleSpec);
+        }
+        if old.default_sink_name != new.default_sink_name {
+            changes.push(ServerChange::DefaultSink);
+        }
+        if old.default_source_name != new.default_source_name {
+            changes.push(ServerChange::DefaultSource);
+        }
+        if old.cookie != new.cookie {
+            changes.push(ServerChange::Cookie);
+        }
+        if old.channel_map != new.channel_map {
+            changes.push(ServerChange::ChannelMap);
+        }
+        changes
+    }
+}
+
+/// A change in server information, as found by [`ServerInfo::diff`].
+///
+/// [`ServerInfo::diff`]: struct.ServerInfo.html#method.diff
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ServerChange {
+    /// User name of the daemon process changed.
+    UserName,
+    /// Host name the daemon is running on changed.
+    HostName,
+    /// Version string of the daemon changed.
+    ServerVersion,
+    /// Server package name changed.
+    ServerName,
+    /// Default sample specification changed.
+    SampleSpec,
+    /// Default sink changed.
+    DefaultSink,
+    /// Default source changed.
+    DefaultSource,
+    /// Cookie changed, i.e. this is a different PulseAudio instance.
+    Cookie,
+    /// Default channel map changed.
+    ChannelMap,
 }
 
 impl Introspector {
@@ -1131,6 +1349,7 @@ impl Introspector {
             Some(get_server_info_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(&ServerInfo)>)
+            .traced("introspect::get_server_info", None)
     }
 }
 
@@ -1202,28 +1421,30 @@ impl Introspector {
     ///
     /// Panics on error, i.e. invalid arguments or state.
     pub fn get_module_info<F>(&self, index: u32, callback: F)
-        -> Operation<dyn FnMut(ListResult<&ModuleInfo>)>
-        where F: FnMut(ListResult<&ModuleInfo>) + 'static
+        -> Operation<dyn FnMut(Result<ListEntry<&ModuleInfo>, PAErr>)>
+        where F: FnMut(Result<ListEntry<&ModuleInfo>, PAErr>) + 'static
     {
-        let cb_data = box_closure_get_capi_ptr::<dyn FnMut(ListResult<&ModuleInfo>)>(Box::new(callback));
+        let cb_data = box_closure_get_capi_ptr::<dyn FnMut(Result<ListEntry<&ModuleInfo>, PAErr>)>(Box::new(callback));
         let ptr = unsafe { capi::pa_context_get_module_info(self.context, index,
             Some(mod_info_list_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
-        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(ListResult<&ModuleInfo>)>)
+        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(Result<ListEntry<&ModuleInfo>, PAErr>)>)
+            .traced("introspect::get_module_info", Some(index))
     }
 
     /// Get the complete list of currently loaded modules.
     ///
     /// Panics on error, i.e. invalid arguments or state.
     pub fn get_module_info_list<F>(&self, callback: F)
-        -> Operation<dyn FnMut(ListResult<&ModuleInfo>)>
-        where F: FnMut(ListResult<&ModuleInfo>) + 'static
+        -> Operation<dyn FnMut(Result<ListEntry<&ModuleInfo>, PAErr>)>
+        where F: FnMut(Result<ListEntry<&ModuleInfo>, PAErr>) + 'static
     {
-        let cb_data = box_closure_get_capi_ptr::<dyn FnMut(ListResult<&ModuleInfo>)>(Box::new(callback));
+        let cb_data = box_closure_get_capi_ptr::<dyn FnMut(Result<ListEntry<&ModuleInfo>, PAErr>)>(Box::new(callback));
         let ptr = unsafe { capi::pa_context_get_module_info_list(self.context,
             Some(mod_info_list_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
-        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(ListResult<&ModuleInfo>)>)
+        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(Result<ListEntry<&ModuleInfo>, PAErr>)>)
+            .traced("introspect::get_module_info_list", None)
     }
 
     /// Load a module.
@@ -1244,6 +1465,7 @@ impl Introspector {
             c_arg.as_ptr(), Some(context_index_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(u32)>)
+            .traced("introspect::load_module", None)
     }
 
     /// Unload a module.
@@ -1259,24 +1481,27 @@ impl Introspector {
             Some(super::success_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("introspect::unload_module", Some(index))
     }
 }
 
 /// Proxy for get module info list callbacks.
 /// Warning: This is for list cases only! On EOL it destroys the actual closure callback.
 extern "C"
-fn mod_info_list_cb_proxy(_: *mut ContextInternal, i: *const ModuleInfoInternal, eol: i32,
+fn mod_info_list_cb_proxy(c: *mut ContextInternal, i: *const ModuleInfoInternal, eol: i32,
     userdata: *mut c_void)
 {
     let _ = std::panic::catch_unwind(|| {
-        match callback_for_list_instance::<dyn FnMut(ListResult<&ModuleInfo>)>(eol, userdata) {
+        match callback_for_list_instance::<dyn FnMut(Result<ListEntry<&ModuleInfo>, PAErr>)>(eol, userdata) {
             ListInstanceCallback::Entry(callback) => {
                 assert!(!i.is_null());
                 let obj = ModuleInfo::new_from_raw(i);
-                (callback)(ListResult::Item(&obj));
+                (callback)(Ok(ListEntry::Item(&obj)));
+            },
+            ListInstanceCallback::End(mut callback) => { (callback)(Ok(ListEntry::End)); },
+            ListInstanceCallback::Error(mut callback) => {
+                (callback)(Err(PAErr(unsafe { capi::pa_context_errno(c) })));
             },
-            ListInstanceCallback::End(mut callback) => { (callback)(ListResult::End); },
-            ListInstanceCallback::Error(mut callback) => { (callback)(ListResult::Error); },
         }
     });
 }
@@ -1337,6 +1562,20 @@ impl<'a> ClientInfo<'a> {
             }
         }
     }
+
+    /// Create an owned copy of this information, which, unlike the original, may be kept beyond
+    /// the scope of the callback it was provided to.
+    ///
+    /// The proplist is copied, thus the copy is independent of the server-side object.
+    pub fn to_owned(&self) -> ClientInfo<'static> {
+        ClientInfo {
+            index: self.index,
+            name: self.name.as_ref().map(|s| Cow::Owned(s.to_string())),
+            owner_module: self.owner_module,
+            driver: self.driver.as_ref().map(|s| Cow::Owned(s.to_string())),
+            proplist: self.proplist.clone(),
+        }
+    }
 }
 
 impl Introspector {
@@ -1344,28 +1583,30 @@ impl Introspector {
     ///
     /// Panics on error, i.e. invalid arguments or state.
     pub fn get_client_info<F>(&self, index: u32, callback: F)
-        -> Operation<dyn FnMut(ListResult<&ClientInfo>)>
-        where F: FnMut(ListResult<&ClientInfo>) + 'static
+        -> Operation<dyn FnMut(Result<ListEntry<&ClientInfo>, PAErr>)>
+        where F: FnMut(Result<ListEntry<&ClientInfo>, PAErr>) + 'static
     {
-        let cb_data = box_closure_get_capi_ptr::<dyn FnMut(ListResult<&ClientInfo>)>(Box::new(callback));
+        let cb_data = box_closure_get_capi_ptr::<dyn FnMut(Result<ListEntry<&ClientInfo>, PAErr>)>(Box::new(callback));
         let ptr = unsafe { capi::pa_context_get_client_info(self.context, index,
             Some(get_client_info_list_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
-        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(ListResult<&ClientInfo>)>)
+        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(Result<ListEntry<&ClientInfo>, PAErr>)>)
+            .traced("introspect::get_client_info", Some(index))
     }
 
     /// Get the complete client list.
     ///
     /// Panics on error, i.e. invalid arguments or state.
     pub fn get_client_info_list<F>(&self, callback: F)
-        -> Operation<dyn FnMut(ListResult<&ClientInfo>)>
-        where F: FnMut(ListResult<&ClientInfo>) + 'static
+        -> Operation<dyn FnMut(Result<ListEntry<&ClientInfo>, PAErr>)>
+        where F: FnMut(Result<ListEntry<&ClientInfo>, PAErr>) + 'static
     {
-        let cb_data = box_closure_get_capi_ptr::<dyn FnMut(ListResult<&ClientInfo>)>(Box::new(callback));
+        let cb_data = box_closure_get_capi_ptr::<dyn FnMut(Result<ListEntry<&ClientInfo>, PAErr>)>(Box::new(callback));
         let ptr = unsafe { capi::pa_context_get_client_info_list(self.context,
             Some(get_client_info_list_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
-        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(ListResult<&ClientInfo>)>)
+        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(Result<ListEntry<&ClientInfo>, PAErr>)>)
+            .traced("introspect::get_client_info_list", None)
     }
 
     /// Kill a client.
@@ -1381,24 +1622,27 @@ impl Introspector {
             Some(super::success_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("introspect::kill_client", Some(index))
     }
 }
 
 /// Proxy for get sink info list callbacks.
 /// Warning: This is for list cases only! On EOL it destroys the actual closure callback.
 extern "C"
-fn get_client_info_list_cb_proxy(_: *mut ContextInternal, i: *const ClientInfoInternal, eol: i32,
+fn get_client_info_list_cb_proxy(c: *mut ContextInternal, i: *const ClientInfoInternal, eol: i32,
     userdata: *mut c_void)
 {
     let _ = std::panic::catch_unwind(|| {
-        match callback_for_list_instance::<dyn FnMut(ListResult<&ClientInfo>)>(eol, userdata) {
+        match callback_for_list_instance::<dyn FnMut(Result<ListEntry<&ClientInfo>, PAErr>)>(eol, userdata) {
             ListInstanceCallback::Entry(callback) => {
                 assert!(!i.is_null());
                 let obj = ClientInfo::new_from_raw(i);
-                (callback)(ListResult::Item(&obj));
+                (callback)(Ok(ListEntry::Item(&obj)));
+            },
+            ListInstanceCallback::End(mut callback) => { (callback)(Ok(ListEntry::End)); },
+            ListInstanceCallback::Error(mut callback) => {
+                (callback)(Err(PAErr(unsafe { capi::pa_context_errno(c) })));
             },
-            ListInstanceCallback::End(mut callback) => { (callback)(ListResult::End); },
-            ListInstanceCallback::Error(mut callback) => { (callback)(ListResult::Error); },
         }
     });
 }
@@ -1469,7 +1713,7 @@ pub struct CardPortInfo<'a> {
     /// Availability status of this port.
     pub available: ::def::PortAvailable,
     /// The direction of this port.
-    pub direction: ::direction::FlagSet,
+    pub direction: ::direction::Direction,
     /// Property list.
     pub proplist: ::proplist::Proplist,
     /// Latency offset of the port that gets added to the sink/source latency when the port is
@@ -1477,6 +1721,15 @@ pub struct CardPortInfo<'a> {
     pub latency_offset: i64,
     /// Set of available profiles.
     pub profiles: Vec<CardProfileInfo2<'a>>,
+    /// An identifier for the group of ports that share their availability status with each
+    /// other, as with [`SinkPortInfo::availability_group`].
+    ///
+    /// [`SinkPortInfo::availability_group`]: struct.SinkPortInfo.html#structfield.availability_group
+    #[cfg(feature = "pa_v14_compatibility")]
+    pub availability_group: Option<Cow<'a, str>>,
+    /// Port device type.
+    #[cfg(feature = "pa_v14_compatibility")]
+    pub r#type: ::def::DevicePortType,
 }
 
 impl<'a> CardPortInfo<'a> {
@@ -1504,11 +1757,18 @@ impl<'a> CardPortInfo<'a> {
                     true => None,
                 },
                 priority: src.priority,
-                available: std::mem::transmute(src.available),
-                direction: src.direction,
+                available: src.available.into(),
+                direction: src.direction.into(),
                 proplist: ::proplist::Proplist::from_raw_weak(src.proplist),
                 latency_offset: src.latency_offset,
                 profiles: profiles_vec,
+                #[cfg(feature = "pa_v14_compatibility")]
+                availability_group: match src.availability_group.is_null() {
+                    false => Some(CStr::from_ptr(src.availability_group).to_string_lossy()),
+                    true => None,
+                },
+                #[cfg(feature = "pa_v14_compatibility")]
+                r#type: src.r#type.into(),
             }
         }
     }
@@ -1592,45 +1852,49 @@ impl Introspector {
     ///
     /// Panics on error, i.e. invalid arguments or state.
     pub fn get_card_info_by_index<F>(&self, index: u32, callback: F)
-        -> Operation<dyn FnMut(ListResult<&CardInfo>)>
-        where F: FnMut(ListResult<&CardInfo>) + 'static
+        -> Operation<dyn FnMut(Result<ListEntry<&CardInfo>, PAErr>)>
+        where F: FnMut(Result<ListEntry<&CardInfo>, PAErr>) + 'static
     {
-        let cb_data = box_closure_get_capi_ptr::<dyn FnMut(ListResult<&CardInfo>)>(Box::new(callback));
+        let cb_data = box_closure_get_capi_ptr::<dyn FnMut(Result<ListEntry<&CardInfo>, PAErr>)>(Box::new(callback));
         let ptr = unsafe { capi::pa_context_get_card_info_by_index(self.context, index,
             Some(get_card_info_list_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
-        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(ListResult<&CardInfo>)>)
+        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(Result<ListEntry<&CardInfo>, PAErr>)>)
+            .traced("introspect::get_card_info_by_index", Some(index))
     }
 
     /// Get information about a card by its name.
     ///
     /// Panics on error, i.e. invalid arguments or state.
     pub fn get_card_info_by_name<F>(&self, name: &str, callback: F)
-        -> Operation<dyn FnMut(ListResult<&CardInfo>)>
-        where F: FnMut(ListResult<&CardInfo>) + 'static
+        -> Operation<dyn FnMut(Result<ListEntry<&CardInfo>, PAErr>)>
+        where F: FnMut(Result<ListEntry<&CardInfo>, PAErr>) + 'static
     {
         // Warning: New CStrings will be immediately freed if not bound to a variable, leading to
         // as_ptr() giving dangling pointers!
         let c_name = CString::new(name.clone()).unwrap();
 
-        let cb_data = box_closure_get_capi_ptr::<dyn FnMut(ListResult<&CardInfo>)>(Box::new(callback));
+        let cb_data = box_closure_get_capi_ptr::<dyn FnMut(Result<ListEntry<&CardInfo>, PAErr>)>(Box::new(callback));
         let ptr = unsafe { capi::pa_context_get_card_info_by_name(self.context, c_name.as_ptr(),
             Some(get_card_info_list_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
-        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(ListResult<&CardInfo>)>)
+        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(Result<ListEntry<&CardInfo>, PAErr>)>)
+            .traced("introspect::get_card_info_by_name", None)
     }
 
     /// Get the complete card list.
     ///
     /// Panics on error, i.e. invalid arguments or state.
-    pub fn get_card_info_list<F>(&self, callback: F) -> Operation<dyn FnMut(ListResult<&CardInfo>)>
-        where F: FnMut(ListResult<&CardInfo>) + 'static
+    pub fn get_card_info_list<F>(&self, callback: F)
+        -> Operation<dyn FnMut(Result<ListEntry<&CardInfo>, PAErr>)>
+        where F: FnMut(Result<ListEntry<&CardInfo>, PAErr>) + 'static
     {
-        let cb_data = box_closure_get_capi_ptr::<dyn FnMut(ListResult<&CardInfo>)>(Box::new(callback));
+        let cb_data = box_closure_get_capi_ptr::<dyn FnMut(Result<ListEntry<&CardInfo>, PAErr>)>(Box::new(callback));
         let ptr = unsafe { capi::pa_context_get_card_info_list(self.context,
             Some(get_card_info_list_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
-        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(ListResult<&CardInfo>)>)
+        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(Result<ListEntry<&CardInfo>, PAErr>)>)
+            .traced("introspect::get_card_info_list", None)
     }
 
     /// Change the profile of a card.
@@ -1651,6 +1915,7 @@ impl Introspector {
             c_profile.as_ptr(), cb_fn, cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("introspect::set_card_profile_by_index", Some(index))
     }
 
     /// Change the profile of a card.
@@ -1672,6 +1937,7 @@ impl Introspector {
             c_profile.as_ptr(), cb_fn, cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("introspect::set_card_profile_by_name", None)
     }
 
     /// Set the latency offset of a port.
@@ -1693,24 +1959,27 @@ impl Introspector {
             c_port.as_ptr(), offset, cb_fn, cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("introspect::set_port_latency_offset", None)
     }
 }
 
 /// Proxy for get card info list callbacks.
 /// Warning: This is for list cases only! On EOL it destroys the actual closure callback.
 extern "C"
-fn get_card_info_list_cb_proxy(_: *mut ContextInternal, i: *const CardInfoInternal, eol: i32,
+fn get_card_info_list_cb_proxy(c: *mut ContextInternal, i: *const CardInfoInternal, eol: i32,
     userdata: *mut c_void)
 {
     let _ = std::panic::catch_unwind(|| {
-        match callback_for_list_instance::<dyn FnMut(ListResult<&CardInfo>)>(eol, userdata) {
+        match callback_for_list_instance::<dyn FnMut(Result<ListEntry<&CardInfo>, PAErr>)>(eol, userdata) {
             ListInstanceCallback::Entry(callback) => {
                 assert!(!i.is_null());
                 let obj = CardInfo::new_from_raw(i);
-                (callback)(ListResult::Item(&obj));
+                (callback)(Ok(ListEntry::Item(&obj)));
+            },
+            ListInstanceCallback::End(mut callback) => { (callback)(Ok(ListEntry::End)); },
+            ListInstanceCallback::Error(mut callback) => {
+                (callback)(Err(PAErr(unsafe { capi::pa_context_errno(c) })));
             },
-            ListInstanceCallback::End(mut callback) => { (callback)(ListResult::End); },
-            ListInstanceCallback::Error(mut callback) => { (callback)(ListResult::Error); },
         }
     });
 }
@@ -1817,30 +2086,32 @@ impl Introspector {
     ///
     /// Panics on error, i.e. invalid arguments or state.
     pub fn get_sink_input_info<F>(&self, index: u32, callback: F)
-        -> Operation<dyn FnMut(ListResult<&SinkInputInfo>)>
-        where F: FnMut(ListResult<&SinkInputInfo>) + 'static
+        -> Operation<dyn FnMut(Result<ListEntry<&SinkInputInfo>, PAErr>)>
+        where F: FnMut(Result<ListEntry<&SinkInputInfo>, PAErr>) + 'static
     {
         let cb_data =
-            box_closure_get_capi_ptr::<dyn FnMut(ListResult<&SinkInputInfo>)>(Box::new(callback));
+            box_closure_get_capi_ptr::<dyn FnMut(Result<ListEntry<&SinkInputInfo>, PAErr>)>(Box::new(callback));
         let ptr = unsafe { capi::pa_context_get_sink_input_info(self.context, index,
             Some(get_sink_input_info_list_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
-        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(ListResult<&SinkInputInfo>)>)
+        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(Result<ListEntry<&SinkInputInfo>, PAErr>)>)
+            .traced("introspect::get_sink_input_info", Some(index))
     }
 
     /// Get the complete sink input list.
     ///
     /// Panics on error, i.e. invalid arguments or state.
     pub fn get_sink_input_info_list<F>(&self, callback: F)
-        -> Operation<dyn FnMut(ListResult<&SinkInputInfo>)>
-        where F: FnMut(ListResult<&SinkInputInfo>) + 'static
+        -> Operation<dyn FnMut(Result<ListEntry<&SinkInputInfo>, PAErr>)>
+        where F: FnMut(Result<ListEntry<&SinkInputInfo>, PAErr>) + 'static
     {
         let cb_data =
-            box_closure_get_capi_ptr::<dyn FnMut(ListResult<&SinkInputInfo>)>(Box::new(callback));
+            box_closure_get_capi_ptr::<dyn FnMut(Result<ListEntry<&SinkInputInfo>, PAErr>)>(Box::new(callback));
         let ptr = unsafe { capi::pa_context_get_sink_input_info_list(self.context,
             Some(get_sink_input_info_list_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
-        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(ListResult<&SinkInputInfo>)>)
+        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(Result<ListEntry<&SinkInputInfo>, PAErr>)>)
+            .traced("introspect::get_sink_input_info_list", None)
     }
 
     /// Move the specified sink input to a different sink.
@@ -1861,6 +2132,7 @@ impl Introspector {
             c_name.as_ptr(), cb_fn, cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("introspect::move_sink_input_by_name", Some(index))
     }
 
     /// Move the specified sink input to a different sink.
@@ -1877,6 +2149,7 @@ impl Introspector {
             sink_index, cb_fn, cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("introspect::move_sink_input_by_index", Some(index))
     }
 
     /// Set the volume of a sink input stream.
@@ -1893,6 +2166,7 @@ impl Introspector {
             std::mem::transmute(volume), cb_fn, cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("introspect::set_sink_input_volume", Some(index))
     }
 
     /// Set the mute switch of a sink input stream.
@@ -1909,6 +2183,7 @@ impl Introspector {
             cb_fn, cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("introspect::set_sink_input_mute", Some(index))
     }
 
     /// Kill a sink input.
@@ -1924,24 +2199,27 @@ impl Introspector {
             Some(super::success_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("introspect::kill_sink_input", Some(index))
     }
 }
 
 /// Proxy for get sink input info list callbacks.
 /// Warning: This is for list cases only! On EOL it destroys the actual closure callback.
 extern "C"
-fn get_sink_input_info_list_cb_proxy(_: *mut ContextInternal, i: *const SinkInputInfoInternal,
+fn get_sink_input_info_list_cb_proxy(c: *mut ContextInternal, i: *const SinkInputInfoInternal,
     eol: i32, userdata: *mut c_void)
 {
     let _ = std::panic::catch_unwind(|| {
-        match callback_for_list_instance::<dyn FnMut(ListResult<&SinkInputInfo>)>(eol, userdata) {
+        match callback_for_list_instance::<dyn FnMut(Result<ListEntry<&SinkInputInfo>, PAErr>)>(eol, userdata) {
             ListInstanceCallback::Entry(callback) => {
                 assert!(!i.is_null());
                 let obj = SinkInputInfo::new_from_raw(i);
-                (callback)(ListResult::Item(&obj));
+                (callback)(Ok(ListEntry::Item(&obj)));
+            },
+            ListInstanceCallback::End(mut callback) => { (callback)(Ok(ListEntry::End)); },
+            ListInstanceCallback::Error(mut callback) => {
+                (callback)(Err(PAErr(unsafe { capi::pa_context_errno(c) })));
             },
-            ListInstanceCallback::End(mut callback) => { (callback)(ListResult::End); },
-            ListInstanceCallback::Error(mut callback) => { (callback)(ListResult::Error); },
         }
     });
 }
@@ -2048,30 +2326,32 @@ impl Introspector {
     ///
     /// Panics on error, i.e. invalid arguments or state.
     pub fn get_source_output_info<F>(&self, index: u32, callback: F)
-        -> Operation<dyn FnMut(ListResult<&SourceOutputInfo>)>
-        where F: FnMut(ListResult<&SourceOutputInfo>) + 'static
+        -> Operation<dyn FnMut(Result<ListEntry<&SourceOutputInfo>, PAErr>)>
+        where F: FnMut(Result<ListEntry<&SourceOutputInfo>, PAErr>) + 'static
     {
         let cb_data =
-            box_closure_get_capi_ptr::<dyn FnMut(ListResult<&SourceOutputInfo>)>(Box::new(callback));
+            box_closure_get_capi_ptr::<dyn FnMut(Result<ListEntry<&SourceOutputInfo>, PAErr>)>(Box::new(callback));
         let ptr = unsafe { capi::pa_context_get_source_output_info(self.context, index,
             Some(get_source_output_info_list_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
-        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(ListResult<&SourceOutputInfo>)>)
+        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(Result<ListEntry<&SourceOutputInfo>, PAErr>)>)
+            .traced("introspect::get_source_output_info", Some(index))
     }
 
     /// Get the complete list of source outputs.
     ///
     /// Panics on error, i.e. invalid arguments or state.
     pub fn get_source_output_info_list<F>(&self, callback: F)
-        -> Operation<dyn FnMut(ListResult<&SourceOutputInfo>)>
-        where F: FnMut(ListResult<&SourceOutputInfo>) + 'static
+        -> Operation<dyn FnMut(Result<ListEntry<&SourceOutputInfo>, PAErr>)>
+        where F: FnMut(Result<ListEntry<&SourceOutputInfo>, PAErr>) + 'static
     {
         let cb_data =
-            box_closure_get_capi_ptr::<dyn FnMut(ListResult<&SourceOutputInfo>)>(Box::new(callback));
+            box_closure_get_capi_ptr::<dyn FnMut(Result<ListEntry<&SourceOutputInfo>, PAErr>)>(Box::new(callback));
         let ptr = unsafe { capi::pa_context_get_source_output_info_list(self.context,
             Some(get_source_output_info_list_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
-        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(ListResult<&SourceOutputInfo>)>)
+        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(Result<ListEntry<&SourceOutputInfo>, PAErr>)>)
+            .traced("introspect::get_source_output_info_list", None)
     }
 
     /// Move the specified source output to a different source.
@@ -2092,6 +2372,7 @@ impl Introspector {
             c_name.as_ptr(), cb_fn, cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("introspect::move_source_output_by_name", Some(index))
     }
 
     /// Move the specified source output to a different source.
@@ -2108,6 +2389,7 @@ impl Introspector {
             source_index, cb_fn, cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("introspect::move_source_output_by_index", Some(index))
     }
 
     /// Set the volume of a source output stream.
@@ -2124,6 +2406,7 @@ impl Introspector {
             std::mem::transmute(volume), cb_fn, cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("introspect::set_source_output_volume", Some(index))
     }
 
     /// Set the mute switch of a source output stream.
@@ -2140,6 +2423,7 @@ impl Introspector {
             cb_fn, cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("introspect::set_source_output_mute", Some(index))
     }
 
     /// Kill a source output.
@@ -2155,24 +2439,27 @@ impl Introspector {
             Some(super::success_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("introspect::kill_source_output", Some(index))
     }
 }
 
 /// Proxy for get source output info list callbacks.
 /// Warning: This is for list cases only! On EOL it destroys the actual closure callback.
 extern "C"
-fn get_source_output_info_list_cb_proxy(_: *mut ContextInternal, i: *const SourceOutputInfoInternal,
+fn get_source_output_info_list_cb_proxy(c: *mut ContextInternal, i: *const SourceOutputInfoInternal,
     eol: i32, userdata: *mut c_void)
 {
     let _ = std::panic::catch_unwind(|| {
-        match callback_for_list_instance::<dyn FnMut(ListResult<&SourceOutputInfo>)>(eol, userdata) {
+        match callback_for_list_instance::<dyn FnMut(Result<ListEntry<&SourceOutputInfo>, PAErr>)>(eol, userdata) {
             ListInstanceCallback::Entry(callback) => {
                 assert!(!i.is_null());
                 let obj = SourceOutputInfo::new_from_raw(i);
-                (callback)(ListResult::Item(&obj));
+                (callback)(Ok(ListEntry::Item(&obj)));
+            },
+            ListInstanceCallback::End(mut callback) => { (callback)(Ok(ListEntry::End)); },
+            ListInstanceCallback::Error(mut callback) => {
+                (callback)(Err(PAErr(unsafe { capi::pa_context_errno(c) })));
             },
-            ListInstanceCallback::End(mut callback) => { (callback)(ListResult::End); },
-            ListInstanceCallback::Error(mut callback) => { (callback)(ListResult::Error); },
         }
     });
 }
@@ -2193,6 +2480,7 @@ impl Introspector {
             cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(&StatInfo)>)
+            .traced("introspect::stat", None)
     }
 }
 
@@ -2265,6 +2553,23 @@ impl<'a> SampleInfo<'a> {
             }
         }
     }
+
+    /// Create an owned copy of this information, which, unlike the original, may be kept beyond
+    /// the scope of the callback it was provided to.
+    pub fn to_owned(&self) -> SampleInfo<'static> {
+        SampleInfo {
+            index: self.index,
+            name: self.name.as_ref().map(|s| Cow::Owned(s.to_string())),
+            volume: self.volume,
+            sample_spec: self.sample_spec,
+            channel_map: self.channel_map,
+            duration: self.duration,
+            bytes: self.bytes,
+            lazy: self.lazy,
+            filename: self.filename.as_ref().map(|s| Cow::Owned(s.to_string())),
+            proplist: self.proplist.clone(),
+        }
+    }
 }
 
 impl Introspector {
@@ -2272,64 +2577,69 @@ impl Introspector {
     ///
     /// Panics on error, i.e. invalid arguments or state.
     pub fn get_sample_info_by_name<F>(&self, name: &str, callback: F)
-        -> Operation<dyn FnMut(ListResult<&SampleInfo>)>
-        where F: FnMut(ListResult<&SampleInfo>) + 'static
+        -> Operation<dyn FnMut(Result<ListEntry<&SampleInfo>, PAErr>)>
+        where F: FnMut(Result<ListEntry<&SampleInfo>, PAErr>) + 'static
     {
         // Warning: New CStrings will be immediately freed if not bound to a variable, leading to
         // as_ptr() giving dangling pointers!
         let c_name = CString::new(name.clone()).unwrap();
 
-        let cb_data = box_closure_get_capi_ptr::<dyn FnMut(ListResult<&SampleInfo>)>(Box::new(callback));
+        let cb_data = box_closure_get_capi_ptr::<dyn FnMut(Result<ListEntry<&SampleInfo>, PAErr>)>(Box::new(callback));
         let ptr = unsafe { capi::pa_context_get_sample_info_by_name(self.context, c_name.as_ptr(),
             Some(get_sample_info_list_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
-        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(ListResult<&SampleInfo>)>)
+        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(Result<ListEntry<&SampleInfo>, PAErr>)>)
+            .traced("introspect::get_sample_info_by_name", None)
     }
 
     /// Get information about a sample by its index.
     ///
     /// Panics on error, i.e. invalid arguments or state.
     pub fn get_sample_info_by_index<F>(&self, index: u32, callback: F)
-        -> Operation<dyn FnMut(ListResult<&SampleInfo>)>
-        where F: FnMut(ListResult<&SampleInfo>) + 'static
+        -> Operation<dyn FnMut(Result<ListEntry<&SampleInfo>, PAErr>)>
+        where F: FnMut(Result<ListEntry<&SampleInfo>, PAErr>) + 'static
     {
-        let cb_data = box_closure_get_capi_ptr::<dyn FnMut(ListResult<&SampleInfo>)>(Box::new(callback));
+        let cb_data = box_closure_get_capi_ptr::<dyn FnMut(Result<ListEntry<&SampleInfo>, PAErr>)>(Box::new(callback));
         let ptr = unsafe { capi::pa_context_get_sample_info_by_index(self.context, index,
             Some(get_sample_info_list_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
-        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(ListResult<&SampleInfo>)>)
+        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(Result<ListEntry<&SampleInfo>, PAErr>)>)
+            .traced("introspect::get_sample_info_by_index", Some(index))
     }
 
     /// Get the complete list of samples stored in the daemon.
     ///
     /// Panics on error, i.e. invalid arguments or state.
     pub fn get_sample_info_list<F>(&self, callback: F)
-        -> Operation<dyn FnMut(ListResult<&SampleInfo>)>
-        where F: FnMut(ListResult<&SampleInfo>) + 'static
+        -> Operation<dyn FnMut(Result<ListEntry<&SampleInfo>, PAErr>)>
+        where F: FnMut(Result<ListEntry<&SampleInfo>, PAErr>) + 'static
     {
-        let cb_data = box_closure_get_capi_ptr::<dyn FnMut(ListResult<&SampleInfo>)>(Box::new(callback));
+        let cb_data = box_closure_get_capi_ptr::<dyn FnMut(Result<ListEntry<&SampleInfo>, PAErr>)>(Box::new(callback));
         let ptr = unsafe { capi::pa_context_get_sample_info_list(self.context,
             Some(get_sample_info_list_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
-        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(ListResult<&SampleInfo>)>)
+        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(Result<ListEntry<&SampleInfo>, PAErr>)>)
+            .traced("introspect::get_sample_info_list", None)
     }
 }
 
 /// Proxy for get sample info list callbacks.
 /// Warning: This is for list cases only! On EOL it destroys the actual closure callback.
 extern "C"
-fn get_sample_info_list_cb_proxy(_: *mut ContextInternal, i: *const SampleInfoInternal, eol: i32,
+fn get_sample_info_list_cb_proxy(c: *mut ContextInternal, i: *const SampleInfoInternal, eol: i32,
     userdata: *mut c_void)
 {
     let _ = std::panic::catch_unwind(|| {
-        match callback_for_list_instance::<dyn FnMut(ListResult<&SampleInfo>)>(eol, userdata) {
+        match callback_for_list_instance::<dyn FnMut(Result<ListEntry<&SampleInfo>, PAErr>)>(eol, userdata) {
             ListInstanceCallback::Entry(callback) => {
                 assert!(!i.is_null());
                 let obj = SampleInfo::new_from_raw(i);
-                (callback)(ListResult::Item(&obj));
+                (callback)(Ok(ListEntry::Item(&obj)));
+            },
+            ListInstanceCallback::End(mut callback) => { (callback)(Ok(ListEntry::End)); },
+            ListInstanceCallback::Error(mut callback) => {
+                (callback)(Err(PAErr(unsafe { capi::pa_context_errno(c) })));
             },
-            ListInstanceCallback::End(mut callback) => { (callback)(ListResult::End); },
-            ListInstanceCallback::Error(mut callback) => { (callback)(ListResult::Error); },
         }
     });
 }
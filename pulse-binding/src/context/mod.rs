@@ -64,7 +64,26 @@
 //! * [`::context::introspect`]
 //! * [`::context::subscribe`]
 //!
+//! # Reconnecting
+//!
+//! A context which has failed or been terminated cannot be reconnected. For automatically
+//! connecting anew, see [`::context::reconnect::ReconnectingContext`].
+//!
+//! # Server strings
+//!
+//! Server strings, specifying which server(s) to connect to, can be built with
+//! [`::context::server_spec::ServerSpec`] and given to [`Context::connect_to`].
+//!
+//! # Abstraction
+//!
+//! The commonly used parts of the context and introspector are captured by the traits of the
+//! [`::context::ops`] module, against which application logic can be written for testability.
+//!
 //! [`Context::connect`]: struct.Context.html#method.connect
+//! [`Context::connect_to`]: struct.Context.html#method.connect_to
+//! [`::context::reconnect::ReconnectingContext`]: reconnect/struct.ReconnectingContext.html
+//! [`::context::server_spec::ServerSpec`]: server_spec/struct.ServerSpec.html
+//! [`::context::ops`]: ops/index.html
 //! [`Context::disconnect`]: struct.Context.html#method.disconnect
 //! [`Context::set_state_callback`]: struct.Context.html#method.set_state_callback
 //! [`::context::introspect`]: ../context/introspect/index.html 
@@ -75,23 +94,53 @@
 //! [`::operation::Operation`]: ../operation/struct.Operation.html
 //! [`::stream`]: ../stream/index.html
 
+pub mod app_volume;
+pub mod auth_diagnostics;
+pub mod batch;
+pub mod bluetooth;
+pub mod capabilities;
+pub mod client_config;
+pub mod client_properties;
+pub mod coalesce;
+pub mod collect;
+pub mod default_device;
+pub mod ducking;
+pub mod entity;
+pub mod event_sound;
 pub mod ext_device_manager;
 pub mod ext_device_restore;
 pub mod ext_stream_restore;
+pub mod find;
+pub mod info_traits;
 pub mod introspect;
+pub mod options;
+pub mod ops;
+pub mod port_select;
+#[cfg(feature = "recording")]
+pub mod recording;
+pub mod reconnect;
 pub mod scache;
+pub mod server_spec;
+pub mod sink_formats;
+pub mod stream_directory;
 pub mod subscribe;
+pub mod topology;
+pub mod volume_cache;
 
 use std;
 use capi;
+use std::convert::TryFrom;
 use std::os::raw::{c_char, c_void};
 use std::ffi::{CStr, CString};
 use std::ptr::{null, null_mut};
 use std::rc::Rc;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicPtr, Ordering};
 use mainloop::api::MainloopInnerType;
 use mainloop::events::timer::{TimeEvent, TimeEventRef};
 use operation::Operation;
-use error::PAErr;
+use error::{PAErr, ObjectDead, ObjectKind, ReleaseSite};
 use time::MonotonicTs;
 use proplist::Proplist;
 use callbacks::box_closure_get_capi_ptr;
@@ -107,11 +156,39 @@ pub struct Context {
     weak: bool,
     /// Multi-use callback closure pointers
     cb_ptrs: CallbackPointers,
+    /// Memory-backed file holding a cookie set with `set_auth_cookie`, if any
+    cookie_fd: Option<i32>,
+    /// Where the cookie came from, if set explicitly, for diagnostics
+    cookie_source: Option<self::auth_diagnostics::CookieSource>,
+    /// Server given upon connecting, if any, for diagnostics
+    server: Option<String>,
+    /// Whether to drain pending commands before disconnecting upon drop
+    drain_on_drop: bool,
+    /// Spawn API hooks given on connecting, if any, kept alive whilst possibly in use
+    spawn_api: Option<Arc<::def::SpawnApi>>,
+    /// Where the context was disconnected, for diagnostics
+    released: ReleaseSite,
 }
 
 unsafe impl Send for Context {}
 unsafe impl Sync for Context {}
 
+/// A shared handle to a context owned elsewhere, as returned by
+/// [`::stream::Stream::get_context`](../stream/struct.Stream.html#method.get_context).
+///
+/// The handle holds a reference to the underlying C object, keeping it alive for as long as the
+/// handle exists, even should the owning `Context` object be dropped. Only the methods of
+/// `Context` taking `&self` are available, through `Deref`, since callbacks belong to the owner.
+pub struct ContextRef(Context);
+
+impl std::ops::Deref for ContextRef {
+    type Target = Context;
+
+    fn deref(&self) -> &Context {
+        &self.0
+    }
+}
+
 /// Holds copies of callback closure pointers, for those that are “multi-use” (may be fired multiple
 /// times), for freeing at the appropriate time.
 #[derive(Default)]
@@ -132,7 +209,6 @@ type ExtSubscribeCb = ::callbacks::MultiUseCallback<dyn FnMut(),
     extern "C" fn(*mut ContextInternal, *mut c_void)>;
 
 /// The state of a connection context
-#[repr(C)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum State {
     /// The context hasn’t been connected yet.
@@ -149,17 +225,49 @@ pub enum State {
     Failed,
     /// The connection was terminated cleanly.
     Terminated,
+    /// A state not known to this binding, as may be introduced by newer versions of PulseAudio.
+    Other(i32),
 }
 
 impl From<State> for capi::pa_context_state_t {
     fn from(s: State) -> Self {
-        unsafe { std::mem::transmute(s) }
+        match s {
+            State::Unconnected => capi::PA_CONTEXT_UNCONNECTED,
+            State::Connecting => capi::PA_CONTEXT_CONNECTING,
+            State::Authorizing => capi::PA_CONTEXT_AUTHORIZING,
+            State::SettingName => capi::PA_CONTEXT_SETTING_NAME,
+            State::Ready => capi::PA_CONTEXT_READY,
+            State::Failed => capi::PA_CONTEXT_FAILED,
+            State::Terminated => capi::PA_CONTEXT_TERMINATED,
+            State::Other(v) => capi::pa_context_state_t(v),
+        }
     }
 }
 
 impl From<capi::pa_context_state_t> for State {
     fn from(s: capi::pa_context_state_t) -> Self {
-        unsafe { std::mem::transmute(s) }
+        match s {
+            capi::PA_CONTEXT_UNCONNECTED => State::Unconnected,
+            capi::PA_CONTEXT_CONNECTING => State::Connecting,
+            capi::PA_CONTEXT_AUTHORIZING => State::Authorizing,
+            capi::PA_CONTEXT_SETTING_NAME => State::SettingName,
+            capi::PA_CONTEXT_READY => State::Ready,
+            capi::PA_CONTEXT_FAILED => State::Failed,
+            capi::PA_CONTEXT_TERMINATED => State::Terminated,
+            capi::pa_context_state_t(v) => State::Other(v),
+        }
+    }
+}
+
+/// Strict conversion from a C value, failing with the value if it is not known to this binding.
+impl TryFrom<i32> for State {
+    type Error = i32;
+
+    fn try_from(v: i32) -> Result<Self, i32> {
+        match State::from(capi::pa_context_state_t(v)) {
+            State::Other(v) => Err(v),
+            s => Ok(s),
+        }
     }
 }
 
@@ -223,18 +331,46 @@ impl Context {
         Some(Self::from_raw(ptr))
     }
 
+    /// Instantiate a new connection context from a mainloop API pointer, rather than a mainloop.
+    pub(crate) fn new_from_api(api: *const ::mainloop::api::MainloopApi, name: &str,
+        proplist: Option<&Proplist>) -> Option<Self>
+    {
+        // Warning: New CStrings will be immediately freed if not bound to a variable, leading to
+        // as_ptr() giving dangling pointers!
+        let c_name = CString::new(name).unwrap();
+        let p_proplist = match proplist {
+            Some(pl) => pl.0.ptr,
+            None => null_mut::<::proplist::ProplistInternal>(),
+        };
+        let ptr = unsafe { capi::pa_context_new_with_proplist(
+            api as *const capi::pa_mainloop_api, c_name.as_ptr(), p_proplist) };
+        if ptr.is_null() {
+            return None;
+        }
+        Some(Self::from_raw(ptr))
+    }
+
     /// Create a new `Context` from an existing [`ContextInternal`](enum.ContextInternal.html)
     /// pointer.
     pub(crate) fn from_raw(ptr: *mut ContextInternal) -> Self {
         assert_eq!(false, ptr.is_null());
-        Self { ptr: ptr, weak: false, cb_ptrs: Default::default() }
+        Self { ptr: ptr, weak: false, cb_ptrs: Default::default(), cookie_fd: None,
+            cookie_source: None, server: None, drain_on_drop: false, spawn_api: None,
+            released: Default::default() }
+    }
+
+    /// Create a new shared handle to the context at the given pointer, taking a reference to it.
+    pub(crate) fn new_ref(ptr: *mut ContextInternal) -> ContextRef {
+        assert_eq!(false, ptr.is_null());
+        unsafe { capi::pa_context_ref(ptr) };
+        ContextRef(Self::from_raw(ptr))
     }
 
     /// Set a callback function that is called whenever the context status changes.
     pub fn set_state_callback(&mut self, callback: Option<Box<dyn FnMut() + 'static>>) {
         let saved = &mut self.cb_ptrs.set_state;
         *saved = NotifyCb::new(callback);
-        let (cb_fn, cb_data) = saved.get_capi_params(notify_cb_proxy_multi);
+        let (cb_fn, cb_data) = saved.get_capi_params(state_cb_proxy);
         unsafe { capi::pa_context_set_state_callback(self.ptr, cb_fn, cb_data); }
     }
 
@@ -268,6 +404,17 @@ impl Context {
         unsafe { capi::pa_context_get_state(self.ptr).into() }
     }
 
+    /// Check that the context is still usable, failing with [`ObjectDead`] if it has been
+    /// terminated, whether by [`disconnect`](#method.disconnect) or upon failure.
+    ///
+    /// [`ObjectDead`]: ../error/struct.ObjectDead.html
+    pub fn check_alive(&self) -> Result<(), ObjectDead> {
+        match self.get_state() {
+            State::Failed | State::Terminated => Err(self.released.dead(ObjectKind::Context)),
+            _ => Ok(()),
+        }
+    }
+
     /// Connect the context to the specified server.
     ///
     /// If server is `None`, connect to the default server. This routine may but will not always
@@ -275,7 +422,11 @@ impl Context {
     /// notified when the connection is established. If `flags` doesn’t have
     /// [`flags::NOAUTOSPAWN`](flags/constant.NOAUTOSPAWN.html) set and no specific server is
     /// specified or accessible, a new daemon is spawned. If `api` is not `None`, the functions
-    /// specified in the structure are used when forking a new child process.
+    /// specified in the structure are used when forking a new child process. Only one context may
+    /// hold such hooks at any one time, thus this fails with [`Code::Busy`] if `api` is given
+    /// while those of another context are still registered.
+    ///
+    /// [`Code::Busy`]: ../error/enum.Code.html#variant.Busy
     pub fn connect(&mut self, server: Option<&str>, flags: FlagSet, api: Option<&::def::SpawnApi>)
         -> Result<(), PAErr>
     {
@@ -286,14 +437,19 @@ impl Context {
             None => CString::new("").unwrap(),
         };
 
-        let p_api: *const capi::pa_spawn_api = match api {
-            Some(api) => unsafe { std::mem::transmute(api) },
+        let c_api = match api {
+            Some(api) => Some(self.register_spawn_api(api)?),
+            None => None,
+        };
+        let p_api: *const capi::pa_spawn_api = match c_api {
+            Some(ref c_api) => c_api,
             None => null::<capi::pa_spawn_api>(),
         };
         let p_server: *const c_char = match server {
             Some(_) => c_server.as_ptr(),
             None => null::<c_char>(),
         };
+        self.server = server.map(|s| s.to_string());
 
         match unsafe { capi::pa_context_connect(self.ptr, p_server, flags, p_api) } {
             0 => Ok(()),
@@ -301,9 +457,53 @@ impl Context {
         }
     }
 
+    /// Connect the context to the server(s) specified by a [`ServerSpec`].
+    ///
+    /// This is equivalent to [`connect`], except that the server string is built from `spec`,
+    /// returning an error if it is invalid (see [`ServerSpec::build`]).
+    ///
+    /// [`ServerSpec`]: server_spec/struct.ServerSpec.html
+    /// [`ServerSpec::build`]: server_spec/struct.ServerSpec.html#method.build
+    /// [`connect`]: #method.connect
+    pub fn connect_to(&mut self, spec: &self::server_spec::ServerSpec, flags: FlagSet,
+        api: Option<&::def::SpawnApi>) -> Result<(), PAErr>
+    {
+        let server = spec.build()?;
+        self.connect(Some(&server), flags, api)
+    }
+
     /// Terminate the context connection immediately.
+    ///
+    /// The context is dead thereafter, see [`check_alive`](#method.check_alive).
+    #[track_caller]
     pub fn disconnect(&mut self) {
         unsafe { capi::pa_context_disconnect(self.ptr); }
+        self.released.record();
+    }
+
+    /// Terminate the context connection once all pending commands have been sent to the server.
+    ///
+    /// Unlike with [`disconnect`](#method.disconnect), commands issued just before (such as a
+    /// volume change) are not lost. The main loop must continue to be run until the callback is
+    /// called, which happens once the connection has been terminated (immediately, if there is
+    /// nothing to drain).
+    #[track_caller]
+    pub fn disconnect_and_drain<F>(&mut self, callback: F)
+        where F: FnMut() + 'static
+    {
+        drain_and_disconnect(self.ptr, Some(Box::new(callback)));
+        self.released.record();
+    }
+
+    /// Set whether or not dropping the context drains pending commands before disconnecting, as
+    /// with [`disconnect_and_drain`](#method.disconnect_and_drain). Disabled by default.
+    ///
+    /// With this enabled, the underlying context is kept alive after drop until draining has
+    /// completed, which requires that the main loop continues to be run for some time after drop.
+    /// If the main loop is not run further, the commands are lost as they otherwise would have
+    /// been, and the underlying context object is leaked.
+    pub fn set_drain_on_drop(&mut self, enable: bool) {
+        self.drain_on_drop = enable;
     }
 
     /// Drain the context.
@@ -328,7 +528,8 @@ impl Context {
         if ptr.is_null() {
             return None;
         }
-        Some(Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut()>))
+        Some(Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut()>)
+            .traced("context::drain", None))
     }
 
     /// Tell the daemon to exit.
@@ -346,44 +547,49 @@ impl Context {
         let ptr = unsafe { capi::pa_context_exit_daemon(self.ptr, Some(success_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("context::exit_daemon", None)
     }
 
     /// Set the name of the default sink.
     ///
     /// The callback must accept a `bool`, which indicates success.
     ///
-    /// Panics if the underlying C function returns a null pointer.
+    /// Panics if the name contains a nul byte, or if the underlying C function returns a null
+    /// pointer.
     pub fn set_default_sink<F>(&mut self, name: &str, callback: F) -> Operation<dyn FnMut(bool)>
         where F: FnMut(bool) + 'static
     {
         // Warning: New CStrings will be immediately freed if not bound to a variable, leading to
         // as_ptr() giving dangling pointers!
-        let c_name = CString::new(name.clone()).unwrap();
+        let c_name = CString::new(name).unwrap();
 
         let cb_data = box_closure_get_capi_ptr::<dyn FnMut(bool)>(Box::new(callback));
         let ptr = unsafe { capi::pa_context_set_default_sink(self.ptr, c_name.as_ptr(),
             Some(success_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("context::set_default_sink", None)
     }
 
     /// Set the name of the default source.
     ///
     /// The callback must accept a `bool`, which indicates success.
     ///
-    /// Panics if the underlying C function returns a null pointer.
+    /// Panics if the name contains a nul byte, or if the underlying C function returns a null
+    /// pointer.
     pub fn set_default_source<F>(&mut self, name: &str, callback: F) -> Operation<dyn FnMut(bool)>
         where F: FnMut(bool) + 'static
     {
         // Warning: New CStrings will be immediately freed if not bound to a variable, leading to
         // as_ptr() giving dangling pointers!
-        let c_name = CString::new(name.clone()).unwrap();
+        let c_name = CString::new(name).unwrap();
 
         let cb_data = box_closure_get_capi_ptr::<dyn FnMut(bool)>(Box::new(callback));
         let ptr = unsafe { capi::pa_context_set_default_source(self.ptr, c_name.as_ptr(),
             Some(success_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("context::set_default_source", None)
     }
 
     /// Returns `true` when the connection is to a local daemon. Returns `None` on error, for
@@ -398,22 +604,29 @@ impl Context {
 
     /// Set a different application name for context on the server.
     ///
-    /// Panics if the underlying C function returns a null pointer.
+    /// The callback must accept a `bool`, which indicates success.
+    ///
+    /// Panics if the name contains a nul byte, or if the underlying C function returns a null
+    /// pointer.
     pub fn set_name<F>(&mut self, name: &str, callback: F) -> Operation<dyn FnMut(bool)>
         where F: FnMut(bool) + 'static
     {
         // Warning: New CStrings will be immediately freed if not bound to a variable, leading to
         // as_ptr() giving dangling pointers!
-        let c_name = CString::new(name.clone()).unwrap();
+        let c_name = CString::new(name).unwrap();
 
         let cb_data = box_closure_get_capi_ptr::<dyn FnMut(bool)>(Box::new(callback));
         let ptr = unsafe { capi::pa_context_set_name(self.ptr, c_name.as_ptr(),
             Some(success_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("context::set_name", None)
     }
 
     /// Return the server name this context is connected to.
+    ///
+    /// Returns `None` if not (yet) connected. For a context connected via the default server
+    /// lookup, this is the address actually used, e.g. `/run/user/1000/pulse/native`.
     pub fn get_server(&self) -> Option<String> {
         let ptr = unsafe { capi::pa_context_get_server(self.ptr) };
         if ptr.is_null() {
@@ -454,6 +667,7 @@ impl Context {
             Some(success_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("context::proplist_update", None)
     }
 
     /// Update the property list of the client, remove entries.
@@ -472,7 +686,7 @@ impl Context {
         // Capture array of pointers to the above CString values.
         // We also add a NULL pointer entry on the end, as expected by the C function called here.
         let mut c_key_ptrs: Vec<*const c_char> = Vec::with_capacity(c_keys.len() + 1);
-        for c_key in c_keys {
+        for c_key in &c_keys {
             c_key_ptrs.push(c_key.as_ptr());
         }
         c_key_ptrs.push(null());
@@ -482,6 +696,7 @@ impl Context {
             Some(success_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("context::proplist_remove", None)
     }
 
     /// Return the client index this context is identified in the server with.
@@ -503,46 +718,65 @@ impl Context {
     ///
     /// This is an alternative to the mainloop `new_timer_event_rt` method.
     ///
+    /// **Deprecated**: use [`create_time_event`] instead, which infers the mainloop type.
+    ///
+    /// [`create_time_event`]: #method.create_time_event
+    #[deprecated(since = "2.7.0", note="use `create_time_event` instead")]
+    pub fn rttime_new<T, F>(&self, mainloop: &::mainloop::api::Mainloop<MI=T::MI>,
+        time: MonotonicTs, callback: F) -> Option<TimeEvent<T::MI>>
+        where T: ::mainloop::api::Mainloop + 'static,
+              F: FnMut(TimeEventRef<T::MI>) + 'static
+    {
+        self.create_time_event(mainloop, time, callback)
+    }
+
+    /// Create a new timer event source for the specified monotonic time.
+    ///
+    /// This is an alternative to the mainloop `new_timer_event_rt` method.
+    ///
     /// A reference to the mainloop object is needed, in order to associate the event object with
-    /// it. The association is done to ensure the event does not outlive the mainloop.
+    /// it. The association is done to ensure the event does not outlive the mainloop. The returned
+    /// [`TimeEvent`] can be re-armed with its [`restart_rt`] method, and is freed automatically
+    /// when dropped.
     ///
-    /// If pointer returned by underlying C function is `NULL`, `None` will be returned, otherwise a
-    /// [`::mainloop::events::timer::TimeEvent`] object will be returned.
+    /// If pointer returned by underlying C function is `NULL`, `None` will be returned.
     ///
     /// Example event set to fire in five seconds time:
     ///
     /// ```rust,ignore
-    /// use pulse::time::{MonotonicTs, MicroSeconds, MICROS_PER_SEC};
-    /// let _t_event = context.rttime_new::<Mainloop, _>(&mainloop,
-    ///     MonotonicTs::now() + MicroSeconds(5 * MICROS_PER_SEC),
-    ///     || { println!("Timer event fired!"); });
+    /// use pulse::time::{MonotonicTs, MicroSeconds};
+    /// let _t_event = context.create_time_event(&mainloop,
+    ///     MonotonicTs::now() + MicroSeconds::SECOND * 5,
+    ///     |_| { println!("Timer event fired!"); });
     /// ```
     ///
     /// **Note**: You must ensure that the returned event object lives for as long as you want its
     /// event(s) to fire, as its `Drop` implementation destroys the event source. I.e. if you create
     /// a new event, but then immediately drop the object returned here, no event will fire!
     ///
-    /// [`::mainloop::events::timer::TimeEvent`]: ../mainloop/events/timer/struct.TimeEvent.html
-    pub fn rttime_new<T, F>(&self, mainloop: &::mainloop::api::Mainloop<MI=T::MI>,
-        time: MonotonicTs, mut callback: F) -> Option<TimeEvent<T::MI>>
-        where T: ::mainloop::api::Mainloop + 'static,
-              F: FnMut(TimeEventRef<T::MI>) + 'static
+    /// [`TimeEvent`]: ../mainloop/events/timer/struct.TimeEvent.html
+    /// [`restart_rt`]: ../mainloop/events/timer/struct.TimeEvent.html#method.restart_rt
+    pub fn create_time_event<M, F>(&self, mainloop: &M, at: MonotonicTs, mut callback: F)
+        -> Option<TimeEvent<M::MI>>
+        where M: ::mainloop::api::Mainloop + ?Sized,
+              M::MI: 'static,
+              F: FnMut(TimeEventRef<M::MI>) + 'static
     {
         let inner_for_wrapper = mainloop.inner();
         let wrapper_cb = Box::new(move |ptr| {
-            let ref_obj = TimeEventRef::<T::MI>::from_raw(ptr, Rc::clone(&inner_for_wrapper));
+            let ref_obj = TimeEventRef::<M::MI>::from_raw(ptr, Rc::clone(&inner_for_wrapper));
             callback(ref_obj);
         });
 
         let to_save = ::mainloop::events::timer::EventCb::new(Some(wrapper_cb));
         let (cb_fn, cb_data) = to_save.get_capi_params(::mainloop::events::timer::event_cb_proxy);
 
-        let ptr = unsafe { capi::pa_context_rttime_new(self.ptr, (time.0).0,
+        let ptr = unsafe { capi::pa_context_rttime_new(self.ptr, (at.0).0,
             std::mem::transmute(cb_fn), cb_data) };
         if ptr.is_null() {
             return None;
         }
-        Some(TimeEvent::<T::MI>::from_raw(ptr, mainloop.inner(), to_save))
+        Some(TimeEvent::<M::MI>::from_raw(ptr, mainloop.inner(), to_save))
     }
 
     /// Return the optimal block size for passing around audio buffers.
@@ -562,7 +796,7 @@ impl Context {
     /// ```
     pub fn get_tile_size(&self, ss: &::sample::Spec) -> Option<usize> {
         // Note: C function doc comments mention possibility of passing in a NULL pointer for ss.
-        // We do not allow this, since 
+        // We do not allow this, since the size would then not be rounded down to the frame size.
         match unsafe { capi::pa_context_get_tile_size(self.ptr, std::mem::transmute(ss)) } {
             std::usize::MAX => None,
             r => Some(r),
@@ -580,26 +814,295 @@ impl Context {
         // as_ptr() giving dangling pointers!
         let c_path = CString::new(cookie_file_path.clone()).unwrap();
         match unsafe { capi::pa_context_load_cookie_from_file(self.ptr, c_path.as_ptr()) } {
-            0 => Ok(()),
+            0 => {
+                let path = PathBuf::from(cookie_file_path);
+                self.cookie_source = Some(self::auth_diagnostics::CookieSource::File(path));
+                Ok(())
+            },
             e => Err(PAErr(e)),
         }
     }
+
+    /// Set the authentication cookie from memory.
+    ///
+    /// This is an alternative to [`load_cookie_from_file`] for applications that obtain the cookie
+    /// by other means, for instance over IPC from outside of a container, and do not want to write
+    /// it to disk.
+    ///
+    /// The underlying library only supports loading the cookie from a file, which it reads upon
+    /// connecting, thus the cookie is placed in an anonymous memory-backed file which is kept open
+    /// for the lifetime of the context (or until replaced by another call to this function).
+    /// Failure to create or write this file is returned as [`CookieError::Os`], holding the OS
+    /// error.
+    ///
+    /// [`load_cookie_from_file`]: #method.load_cookie_from_file
+    /// [`CookieError::Os`]: enum.CookieError.html#variant.Os
+    #[cfg(target_os = "linux")]
+    pub fn set_auth_cookie(&mut self, cookie: &[u8; COOKIE_LENGTH]) -> Result<(), CookieError> {
+        let name = CString::new("pulse-cookie").unwrap();
+        let fd = unsafe { libc::memfd_create(name.as_ptr(), libc::MFD_CLOEXEC) };
+        if fd < 0 {
+            return Err(CookieError::Os(std::io::Error::last_os_error()));
+        }
+        let written = unsafe { libc::write(fd, cookie.as_ptr() as *const c_void, cookie.len()) };
+        if written != cookie.len() as isize {
+            let error = match written {
+                -1 => std::io::Error::last_os_error(),
+                _ => std::io::Error::from(std::io::ErrorKind::WriteZero),
+            };
+            unsafe { libc::close(fd) };
+            return Err(CookieError::Os(error));
+        }
+        if let Some(old) = self.cookie_fd.replace(fd) {
+            unsafe { libc::close(old) };
+        }
+        self.load_cookie_from_file(&format!("/proc/self/fd/{}", fd))?;
+        self.cookie_source = Some(self::auth_diagnostics::CookieSource::Memory);
+        Ok(())
+    }
+
+    /// Send a message to an object registered with the server’s message API, such as
+    /// `/core`, or the `/card/<name>/bluez` object of a Bluetooth card.
+    ///
+    /// The message parameters are optional; where given, they are in the format expected by the
+    /// recipient (JSON, for PulseAudio’s own objects). Sending the `list-handlers` message to
+    /// `/core` lists the available objects.
+    ///
+    /// The callback must accept a `bool`, which indicates success, and the response, if any.
+    ///
+    /// Requires PA version 15 (the `pa_v15_compatibility` feature).
+    ///
+    /// Panics if any of the strings contain a nul byte, or if the underlying C function returns a
+    /// null pointer.
+    #[cfg(feature = "pa_v15_compatibility")]
+    pub fn send_message_to_object<F>(&mut self, recipient: &str, message: &str,
+        params: Option<&str>, callback: F) -> Operation<dyn FnMut(bool, Option<String>)>
+        where F: FnMut(bool, Option<String>) + 'static
+    {
+        // Warning: New CStrings will be immediately freed if not bound to a variable, leading to
+        // as_ptr() giving dangling pointers!
+        let c_recipient = CString::new(recipient).unwrap();
+        let c_message = CString::new(message).unwrap();
+        let c_params = params.map(|p| CString::new(p).unwrap());
+        let p_params = c_params.as_ref().map_or(null::<c_char>(), |p| p.as_ptr());
+
+        let cb_data = box_closure_get_capi_ptr::<dyn FnMut(bool, Option<String>)>(
+            Box::new(callback));
+        let ptr = unsafe { capi::pa_context_send_message_to_object(self.ptr,
+            c_recipient.as_ptr(), c_message.as_ptr(), p_params, Some(string_cb_proxy), cb_data) };
+        assert!(!ptr.is_null());
+        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool, Option<String>)>)
+            .traced("context::send_message_to_object", None)
+    }
+}
+
+/// Length of an authentication cookie, in bytes.
+pub const COOKIE_LENGTH: usize = 256;
+
+/// An error setting the authentication cookie with [`Context::set_auth_cookie`].
+///
+/// [`Context::set_auth_cookie`]: struct.Context.html#method.set_auth_cookie
+#[cfg(target_os = "linux")]
+#[derive(Debug)]
+pub enum CookieError {
+    /// Creating or writing the memory-backed file holding the cookie failed.
+    Os(std::io::Error),
+    /// An error returned by PulseAudio upon loading the cookie.
+    Pa(PAErr),
+}
+
+#[cfg(target_os = "linux")]
+impl From<PAErr> for CookieError {
+    fn from(e: PAErr) -> Self {
+        CookieError::Pa(e)
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl std::fmt::Display for CookieError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            CookieError::Os(ref e) => write!(f, "Failed to store cookie: {}", e),
+            CookieError::Pa(ref e) => write!(f, "{}", e),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl std::error::Error for CookieError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            CookieError::Os(ref e) => Some(e),
+            CookieError::Pa(_) => None,
+        }
+    }
+}
+
+/// Locate the authentication cookie file of the current user.
+///
+/// This looks for `$XDG_CONFIG_HOME/pulse/cookie` (where `XDG_CONFIG_HOME` defaults to
+/// `~/.config`), falling back to the legacy `~/.pulse-cookie`. Returns `None` if neither exists.
+///
+/// The path found can be given to [`Context::load_cookie_from_file`].
+///
+/// [`Context::load_cookie_from_file`]: struct.Context.html#method.load_cookie_from_file
+pub fn find_cookie_file() -> Option<PathBuf> {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    let config_home = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(ref dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        _ => home.as_ref().map(|h| h.join(".config")),
+    };
+    let candidates = [
+        config_home.map(|dir| dir.join("pulse").join("cookie")),
+        home.map(|dir| dir.join(".pulse-cookie")),
+    ];
+    candidates.iter().flatten().find(|path| path.is_file()).cloned()
 }
 
 impl Drop for Context {
     fn drop(&mut self) {
+        self.release_spawn_api();
+        if self.drain_on_drop && !self.weak && self.get_state() == State::Ready {
+            drain_and_disconnect(self.ptr, None);
+        }
         if !self.weak {
             unsafe { capi::pa_context_unref(self.ptr) };
         }
+        if let Some(fd) = self.cookie_fd.take() {
+            unsafe { libc::close(fd) };
+        }
         self.ptr = null_mut::<ContextInternal>();
     }
 }
 
+/// Spawn API hooks currently registered, as given to the connection of a context.
+static SPAWN_API: Mutex<Option<Arc<::def::SpawnApi>>> = Mutex::new(None);
+
+/// Spawn API hooks in use across a fork, held from the prefork hook until the postfork hook, such
+/// that the hook called in the child need not take a lock.
+static SPAWN_FORKING: AtomicPtr<::def::SpawnApi> = AtomicPtr::new(null_mut());
+
+impl Context {
+    /// Register spawn API hooks, returning the C structure to give on connecting.
+    ///
+    /// Fails with `Code::Busy` if those of another context are registered.
+    fn register_spawn_api(&mut self, api: &::def::SpawnApi) -> Result<capi::pa_spawn_api, PAErr> {
+        // Autospawning may occur later, upon failure to connect, thus the hooks are kept
+        let api = Arc::new(api.clone());
+        {
+            let mut registered = SPAWN_API.lock().unwrap_or_else(|e| e.into_inner());
+            let own = match (registered.as_ref(), self.spawn_api.as_ref()) {
+                (Some(current), Some(own)) => Arc::ptr_eq(current, own),
+                _ => false,
+            };
+            if registered.is_some() && !own {
+                return Err(PAErr::from(::error::Code::Busy));
+            }
+            *registered = Some(Arc::clone(&api));
+        }
+        // The prefork and postfork proxies are always given, taking and releasing the hooks for
+        // the duration of the fork
+        let c_api = capi::pa_spawn_api {
+            prefork: Some(spawn_prefork_proxy),
+            postfork: Some(spawn_postfork_proxy),
+            atfork: api.atfork.as_ref().map(|_| spawn_atfork_proxy as extern "C" fn()),
+        };
+        self.spawn_api = Some(api);
+        Ok(c_api)
+    }
+
+    /// Release any spawn API hooks held, deactivating them if currently registered.
+    fn release_spawn_api(&mut self) {
+        if let Some(api) = self.spawn_api.take() {
+            let mut registered = SPAWN_API.lock().unwrap_or_else(|e| e.into_inner());
+            if registered.as_ref().map_or(false, |current| Arc::ptr_eq(current, &api)) {
+                *registered = None;
+            }
+        }
+    }
+}
+
+/// Proxy for the spawn API prefork hook.
+///
+/// Takes a reference to the registered hooks, kept until the postfork hook.
+extern "C"
+fn spawn_prefork_proxy() {
+    let _ = std::panic::catch_unwind(|| {
+        let api = match SPAWN_API.lock().unwrap_or_else(|e| e.into_inner()).as_ref() {
+            Some(api) => Arc::clone(api),
+            None => return,
+        };
+        if let Some(ref hook) = api.prefork {
+            (hook)();
+        }
+        let previous = SPAWN_FORKING.swap(Arc::into_raw(api) as *mut _, Ordering::SeqCst);
+        if !previous.is_null() {
+            drop(unsafe { Arc::from_raw(previous) });
+        }
+    });
+}
+
+/// Proxy for the spawn API postfork hook.
+///
+/// Releases the reference to the hooks taken by the prefork hook.
+extern "C"
+fn spawn_postfork_proxy() {
+    let _ = std::panic::catch_unwind(|| {
+        let api = SPAWN_FORKING.swap(null_mut(), Ordering::SeqCst);
+        if api.is_null() {
+            return;
+        }
+        let api = unsafe { Arc::from_raw(api) };
+        if let Some(ref hook) = api.postfork {
+            (hook)();
+        }
+    });
+}
+
+/// Proxy for the spawn API atfork hook.
+///
+/// This runs in the child, thus must not take locks, using the hooks held across the fork.
+extern "C"
+fn spawn_atfork_proxy() {
+    let _ = std::panic::catch_unwind(|| {
+        let api = SPAWN_FORKING.load(Ordering::SeqCst);
+        if let Some(hook) = unsafe { api.as_ref() }.and_then(|api| api.atfork.as_ref()) {
+            (hook)();
+        }
+    });
+}
+
+/// Drain the context, then disconnect it, calling the callback (if any) once done. A reference to
+/// the context is held until then.
+fn drain_and_disconnect(ptr: *mut ContextInternal, mut callback: Option<Box<dyn FnMut()>>) {
+    unsafe { capi::pa_context_ref(ptr) };
+    let done = move || {
+        unsafe {
+            capi::pa_context_disconnect(ptr);
+            capi::pa_context_unref(ptr);
+        }
+        if let Some(ref mut callback) = callback {
+            (callback)();
+        }
+    };
+    let cb_data = box_closure_get_capi_ptr::<dyn FnMut()>(Box::new(done));
+    let op = unsafe { capi::pa_context_drain(ptr, Some(notify_cb_proxy_single), cb_data) };
+    match op.is_null() {
+        // Nothing to drain (or not connected), so disconnect immediately
+        true => {
+            let mut done = ::callbacks::get_su_callback::<dyn FnMut()>(cb_data);
+            (done)();
+        },
+        false => unsafe { capi::pa_operation_unref(op) },
+    }
+}
+
 /// Proxy for completion success callbacks.
 /// Warning: This is for single-use cases only! It destroys the actual closure callback.
 extern "C"
-fn success_cb_proxy(_: *mut ContextInternal, success: i32, userdata: *mut c_void) {
+fn success_cb_proxy(c: *mut ContextInternal, success: i32, userdata: *mut c_void) {
     let success_actual = match success { 0 => false, _ => true };
+    ::logging::operation_result(c, success_actual);
     let _ = std::panic::catch_unwind(|| {
         assert!(!userdata.is_null());
         // Note, destroys closure callback after use - restoring outer box means it gets dropped
@@ -608,6 +1111,29 @@ fn success_cb_proxy(_: *mut ContextInternal, success: i32, userdata: *mut c_void
     });
 }
 
+/// Proxy for message API response callbacks.
+/// Warning: This is for single-use cases only! It destroys the actual closure callback.
+#[cfg(feature = "pa_v15_compatibility")]
+extern "C"
+fn string_cb_proxy(c: *mut ContextInternal, success: i32, response: *mut c_char,
+    userdata: *mut c_void)
+{
+    let success_actual = match success { 0 => false, _ => true };
+    ::logging::operation_result(c, success_actual);
+    let _ = std::panic::catch_unwind(|| {
+        assert!(!userdata.is_null());
+        let response = match response.is_null() {
+            false => Some(unsafe { CStr::from_ptr(response) }.to_string_lossy().into_owned()),
+            true => None,
+        };
+        // Note, destroys closure callback after use - restoring outer box means it gets dropped
+        let mut callback = unsafe {
+            Box::from_raw(userdata as *mut Box<dyn FnMut(bool, Option<String>)>)
+        };
+        (callback)(success_actual, response);
+    });
+}
+
 /// Proxy for notification callbacks (single use).
 /// Warning: This is for single-use cases only! It destroys the actual closure callback.
 extern "C"
@@ -631,6 +1157,15 @@ fn notify_cb_proxy_multi(_: *mut ContextInternal, userdata: *mut c_void) {
     });
 }
 
+/// Proxy for state callbacks, logging the new state.
+/// Warning: This is for multi-use cases! It does **not** destroy the actual closure callback, which
+/// must be accomplished separately to avoid a memory leak.
+extern "C"
+fn state_cb_proxy(c: *mut ContextInternal, userdata: *mut c_void) {
+    ::logging::context_state(c);
+    notify_cb_proxy_multi(c, userdata);
+}
+
 /// Proxy for event callbacks.
 /// Warning: This is for multi-use cases! It does **not** destroy the actual closure callback, which
 /// must be accomplished separately to avoid a memory leak.
@@ -672,3 +1207,22 @@ fn ext_subscribe_cb_proxy(_: *mut ContextInternal, userdata: *mut c_void) {
         (callback)();
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_conversion() {
+        for &value in &[State::Unconnected, State::Connecting, State::Authorizing,
+            State::SettingName, State::Ready, State::Failed, State::Terminated]
+        {
+            let raw = capi::pa_context_state_t::from(value);
+            assert_eq!(State::from(raw), value);
+            assert_eq!(State::try_from(raw.0), Ok(value));
+        }
+        assert_eq!(State::from(capi::pa_context_state_t(42)), State::Other(42));
+        assert_eq!(capi::pa_context_state_t::from(State::Other(42)), capi::pa_context_state_t(42));
+        assert_eq!(State::try_from(42), Err(42));
+    }
+}
@@ -0,0 +1,1042 @@
+// Copyright 2017 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Trait abstraction over contexts and introspectors.
+//!
+//! # Overview
+//!
+//! The [`ContextOps`] and [`IntrospectorOps`] traits capture the commonly used parts of
+//! [`Context`] and [`Introspector`], allowing application logic to be written against the traits,
+//! and thus be tested with a substitute, such as the mock server of the `testing` module (with the
+//! `testing` feature enabled).
+//!
+//! The traits are object-safe. Callbacks are boxed, and results are delivered as simple owned
+//! types, such as [`Server`] and [`Sink`], with lists delivered whole. Unlike with the underlying
+//! methods, no [`Operation`] object is returned; the callbacks are always called upon completion.
+//!
+//! The owned types are marked `#[non_exhaustive]`, such that fields can be added as new PA versions
+//! provide more information, without breaking code using them. Substitute implementations can
+//! create them with their `new` constructors, and then set the fields as required.
+//!
+//! ```rust,ignore
+//! use pulse::context::ops::{ContextOps, Sink};
+//!
+//! fn list_sinks(context: &dyn ContextOps) {
+//!     context.introspector().get_sink_info_list(Box::new(|result| {
+//!         for sink in result.unwrap_or_default() {
+//!             println!("{}: {:?}", sink.index, sink.description);
+//!         }
+//!     }));
+//! }
+//! ```
+//!
+//! [`ContextOps`]: trait.ContextOps.html
+//! [`IntrospectorOps`]: trait.IntrospectorOps.html
+//! [`Context`]: ../struct.Context.html
+//! [`Introspector`]: ../introspect/struct.Introspector.html
+//! [`Server`]: struct.Server.html
+//! [`Sink`]: struct.Sink.html
+//! [`Operation`]: ../../operation/struct.Operation.html
+
+use callbacks::ListEntry;
+use error::PAErr;
+use proplist::MediaRole;
+use super::{Context, State, FlagSet};
+use super::introspect::{Introspector, ServerInfo, SinkInfo, SourceInfo, SinkInputInfo,
+    SourceOutputInfo, ClientInfo, CardInfo, CardProfileInfo2, CardPortInfo};
+use super::subscribe::{Facility, InterestMaskSet, Operation as SubscribeOperation};
+
+/// A callback given the result of a request.
+pub type ResultCb<T> = Box<dyn FnOnce(Result<T, PAErr>) + 'static>;
+
+/// A callback given the success of a request.
+pub type SuccessCb = Box<dyn FnOnce(bool) + 'static>;
+
+/// A subscription event callback, as for [`Context::set_subscribe_callback`].
+///
+/// [`Context::set_subscribe_callback`]: ../struct.Context.html#method.set_subscribe_callback
+pub type SubscribeCb =
+    Box<dyn FnMut(Option<Facility>, Option<SubscribeOperation>, u32) + 'static>;
+
+/// Server information.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Server {
+    /// User name of the daemon process.
+    pub user_name: Option<String>,
+    /// Host name the daemon is running on.
+    pub host_name: Option<String>,
+    /// Version string of the daemon.
+    pub server_version: Option<String>,
+    /// Server package name (usually “pulseaudio”).
+    pub server_name: Option<String>,
+    /// Default sample specification.
+    pub sample_spec: ::sample::Spec,
+    /// Default channel map.
+    pub channel_map: ::channelmap::Map,
+    /// Name of default sink.
+    pub default_sink_name: Option<String>,
+    /// Name of default source.
+    pub default_source_name: Option<String>,
+}
+
+/// Sink information.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Sink {
+    /// Index of the sink.
+    pub index: u32,
+    /// Name of the sink.
+    pub name: Option<String>,
+    /// Description of this sink.
+    pub description: Option<String>,
+    /// Sample spec of this sink.
+    pub sample_spec: ::sample::Spec,
+    /// Channel map.
+    pub channel_map: ::channelmap::Map,
+    /// Volume of the sink.
+    pub volume: ::volume::ChannelVolumes,
+    /// Mute switch of the sink.
+    pub mute: bool,
+    /// State.
+    pub state: ::def::SinkState,
+    /// Index of the monitor source connected to this sink.
+    pub monitor_source: u32,
+    /// Card index, if any.
+    pub card: Option<u32>,
+    /// Name of the active port, if any.
+    pub active_port: Option<String>,
+    /// Flags.
+    pub flags: ::def::SinkFlagSet,
+    /// Set of formats supported by the sink.
+    pub formats: Vec<::format::Info>,
+}
+
+/// Source information.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Source {
+    /// Index of the source.
+    pub index: u32,
+    /// Name of the source.
+    pub name: Option<String>,
+    /// Description of this source.
+    pub description: Option<String>,
+    /// Sample spec of this source.
+    pub sample_spec: ::sample::Spec,
+    /// Channel map.
+    pub channel_map: ::channelmap::Map,
+    /// Volume of the source.
+    pub volume: ::volume::ChannelVolumes,
+    /// Mute switch of the source.
+    pub mute: bool,
+    /// State.
+    pub state: ::def::SourceState,
+    /// If this is a monitor source, the index of the owning sink.
+    pub monitor_of_sink: Option<u32>,
+    /// Card index, if any.
+    pub card: Option<u32>,
+    /// Name of the active port, if any.
+    pub active_port: Option<String>,
+}
+
+/// Sink input information.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct SinkInput {
+    /// Index of the sink input.
+    pub index: u32,
+    /// Name of the sink input.
+    pub name: Option<String>,
+    /// Index of the client this sink input belongs to, if any.
+    pub client: Option<u32>,
+    /// Index of the connected sink.
+    pub sink: u32,
+    /// The sample specification of the sink input.
+    pub sample_spec: ::sample::Spec,
+    /// Channel map.
+    pub channel_map: ::channelmap::Map,
+    /// The volume of this sink input.
+    pub volume: ::volume::ChannelVolumes,
+    /// Stream muted.
+    pub mute: bool,
+    /// Stream corked.
+    pub corked: bool,
+    /// Media role of the stream, if any.
+    pub media_role: Option<MediaRole>,
+}
+
+/// Source output information.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct SourceOutput {
+    /// Index of the source output.
+    pub index: u32,
+    /// Name of the source output.
+    pub name: Option<String>,
+    /// Index of the client this source output belongs to, if any.
+    pub client: Option<u32>,
+    /// Index of the connected source.
+    pub source: u32,
+    /// The sample specification of the source output.
+    pub sample_spec: ::sample::Spec,
+    /// Channel map.
+    pub channel_map: ::channelmap::Map,
+    /// The volume of this source output.
+    pub volume: ::volume::ChannelVolumes,
+    /// Stream muted.
+    pub mute: bool,
+    /// Stream corked.
+    pub corked: bool,
+}
+
+/// Client information.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Client {
+    /// Index of this client.
+    pub index: u32,
+    /// Name of this client.
+    pub name: Option<String>,
+    /// Index of the owning module, or `None`.
+    pub owner_module: Option<u32>,
+    /// Driver name.
+    pub driver: Option<String>,
+}
+
+/// Card information.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Card {
+    /// Index of this card.
+    pub index: u32,
+    /// Name of this card.
+    pub name: Option<String>,
+    /// Driver name.
+    pub driver: Option<String>,
+    /// Profiles of this card.
+    pub profiles: Vec<CardProfile>,
+    /// Ports of this card.
+    pub ports: Vec<CardPort>,
+    /// Name of the active profile, if any.
+    pub active_profile: Option<String>,
+}
+
+/// Card profile information.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct CardProfile {
+    /// Name of this profile.
+    pub name: String,
+    /// Description of this profile.
+    pub description: Option<String>,
+    /// Number of sinks this profile would create.
+    pub n_sinks: u32,
+    /// Number of sources this profile would create.
+    pub n_sources: u32,
+    /// The higher this value is, the more useful this profile is as a default.
+    pub priority: u32,
+    /// Whether or not this profile is available, i.e. not known to be useless to activate.
+    pub available: bool,
+}
+
+/// Card port information.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct CardPort {
+    /// Name of this port.
+    pub name: String,
+    /// Description of this port.
+    pub description: Option<String>,
+    /// The higher this value is, the more useful this port is as a default.
+    pub priority: u32,
+    /// Availability status of this port.
+    pub available: ::def::PortAvailable,
+    /// The direction of this port.
+    pub direction: ::direction::Direction,
+    /// Latency offset of the port.
+    pub latency_offset: i64,
+    /// Names of the profiles this port belongs to.
+    pub profiles: Vec<String>,
+}
+
+/// An invalid sample spec, used by default.
+fn invalid_spec() -> ::sample::Spec {
+    ::sample::Spec { format: ::sample::Format::Invalid, channels: 0, rate: 0 }
+}
+
+impl Server {
+    /// Create a new instance, with no names, and an invalid sample spec and empty channel map.
+    pub fn new() -> Self {
+        Server {
+            user_name: None,
+            host_name: None,
+            server_version: None,
+            server_name: None,
+            sample_spec: invalid_spec(),
+            channel_map: ::channelmap::Map::default(),
+            default_sink_name: None,
+            default_source_name: None,
+        }
+    }
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sink {
+    /// Create a new instance with the given index, no names, an invalid sample spec, empty channel
+    /// map and volume, and otherwise default values.
+    pub fn new(index: u32) -> Self {
+        Sink {
+            index,
+            name: None,
+            description: None,
+            sample_spec: invalid_spec(),
+            channel_map: ::channelmap::Map::default(),
+            volume: ::volume::ChannelVolumes::default(),
+            mute: false,
+            state: ::def::SinkState::Invalid,
+            monitor_source: ::def::INVALID_INDEX,
+            card: None,
+            active_port: None,
+            flags: ::def::sink_flags::NOFLAGS,
+            formats: Vec::new(),
+        }
+    }
+}
+
+impl Source {
+    /// Create a new instance with the given index, no names, an invalid sample spec, empty channel
+    /// map and volume, and otherwise default values.
+    pub fn new(index: u32) -> Self {
+        Source {
+            index,
+            name: None,
+            description: None,
+            sample_spec: invalid_spec(),
+            channel_map: ::channelmap::Map::default(),
+            volume: ::volume::ChannelVolumes::default(),
+            mute: false,
+            state: ::def::SourceState::Invalid,
+            monitor_of_sink: None,
+            card: None,
+            active_port: None,
+        }
+    }
+}
+
+impl SinkInput {
+    /// Create a new instance with the given index, connected to the given sink, with no name, an
+    /// invalid sample spec, empty channel map and volume, and otherwise default values.
+    pub fn new(index: u32, sink: u32) -> Self {
+        SinkInput {
+            index,
+            name: None,
+            client: None,
+            sink,
+            sample_spec: invalid_spec(),
+            channel_map: ::channelmap::Map::default(),
+            volume: ::volume::ChannelVolumes::default(),
+            mute: false,
+            corked: false,
+            media_role: None,
+        }
+    }
+}
+
+impl SourceOutput {
+    /// Create a new instance with the given index, connected to the given source, with no name, an
+    /// invalid sample spec, empty channel map and volume, and otherwise default values.
+    pub fn new(index: u32, source: u32) -> Self {
+        SourceOutput {
+            index,
+            name: None,
+            client: None,
+            source,
+            sample_spec: invalid_spec(),
+            channel_map: ::channelmap::Map::default(),
+            volume: ::volume::ChannelVolumes::default(),
+            mute: false,
+            corked: false,
+        }
+    }
+}
+
+impl Client {
+    /// Create a new instance with the given index, and no name.
+    pub fn new(index: u32) -> Self {
+        Client { index, name: None, owner_module: None, driver: None }
+    }
+}
+
+impl Card {
+    /// Create a new instance with the given index, no name, and no profiles or ports.
+    pub fn new(index: u32) -> Self {
+        Card {
+            index,
+            name: None,
+            driver: None,
+            profiles: Vec::new(),
+            ports: Vec::new(),
+            active_profile: None,
+        }
+    }
+
+    /// Get the profile with the given name, if any.
+    pub fn profile(&self, name: &str) -> Option<&CardProfile> {
+        self.profiles.iter().find(|p| p.name == name)
+    }
+
+    /// Get the port with the given name, if any.
+    pub fn port(&self, name: &str) -> Option<&CardPort> {
+        self.ports.iter().find(|p| p.name == name)
+    }
+
+    /// Get the active profile, if any.
+    pub fn get_active_profile(&self) -> Option<&CardProfile> {
+        self.active_profile.as_ref().and_then(|name| self.profile(name))
+    }
+
+    /// Get the ports belonging to the profile with the given name.
+    pub fn ports_for_profile(&self, profile: &str) -> Vec<&CardPort> {
+        self.ports.iter().filter(|p| p.profiles.iter().any(|n| n == profile)).collect()
+    }
+
+    /// Get the profiles which the port with the given name belongs to.
+    pub fn profiles_with_port(&self, port: &str) -> Vec<&CardProfile> {
+        match self.port(port) {
+            Some(port) => self.profiles.iter()
+                .filter(|p| port.profiles.contains(&p.name))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+impl CardProfile {
+    /// Create a new (available) instance with the given name, and no description, sinks or sources.
+    pub fn new(name: &str) -> Self {
+        CardProfile {
+            name: name.to_string(),
+            description: None,
+            n_sinks: 0,
+            n_sources: 0,
+            priority: 0,
+            available: true,
+        }
+    }
+}
+
+impl CardPort {
+    /// Create a new instance with the given name and direction, of unknown availability, and
+    /// belonging to no profiles.
+    pub fn new(name: &str, direction: ::direction::Direction) -> Self {
+        CardPort {
+            name: name.to_string(),
+            description: None,
+            priority: 0,
+            available: ::def::PortAvailable::Unknown,
+            direction: direction,
+            latency_offset: 0,
+            profiles: Vec::new(),
+        }
+    }
+}
+
+impl<'a> From<&'a ServerInfo<'a>> for Server {
+    fn from(i: &'a ServerInfo<'a>) -> Self {
+        Server {
+            user_name: i.user_name.as_ref().map(|s| s.to_string()),
+            host_name: i.host_name.as_ref().map(|s| s.to_string()),
+            server_version: i.server_version.as_ref().map(|s| s.to_string()),
+            server_name: i.server_name.as_ref().map(|s| s.to_string()),
+            sample_spec: i.sample_spec,
+            channel_map: i.channel_map,
+            default_sink_name: i.default_sink_name.as_ref().map(|s| s.to_string()),
+            default_source_name: i.default_source_name.as_ref().map(|s| s.to_string()),
+        }
+    }
+}
+
+impl<'a> From<&'a SinkInfo<'a>> for Sink {
+    fn from(i: &'a SinkInfo<'a>) -> Self {
+        Sink {
+            index: i.index,
+            name: i.name.as_ref().map(|s| s.to_string()),
+            description: i.description.as_ref().map(|s| s.to_string()),
+            sample_spec: i.sample_spec,
+            channel_map: i.channel_map,
+            volume: i.volume,
+            mute: i.mute,
+            state: i.state,
+            monitor_source: i.monitor_source,
+            card: i.card,
+            active_port: i.active_port.as_ref()
+                .and_then(|p| p.name.as_ref().map(|s| s.to_string())),
+            flags: i.flags,
+            formats: i.formats.clone(),
+        }
+    }
+}
+
+impl<'a> From<&'a SourceInfo<'a>> for Source {
+    fn from(i: &'a SourceInfo<'a>) -> Self {
+        Source {
+            index: i.index,
+            name: i.name.as_ref().map(|s| s.to_string()),
+            description: i.description.as_ref().map(|s| s.to_string()),
+            sample_spec: i.sample_spec,
+            channel_map: i.channel_map,
+            volume: i.volume,
+            mute: i.mute,
+            state: i.state,
+            monitor_of_sink: i.monitor_of_sink,
+            card: i.card,
+            active_port: i.active_port.as_ref()
+                .and_then(|p| p.name.as_ref().map(|s| s.to_string())),
+        }
+    }
+}
+
+impl<'a> From<&'a SinkInputInfo<'a>> for SinkInput {
+    fn from(i: &'a SinkInputInfo<'a>) -> Self {
+        SinkInput {
+            index: i.index,
+            name: i.name.as_ref().map(|s| s.to_string()),
+            client: i.client,
+            sink: i.sink,
+            sample_spec: i.sample_spec,
+            channel_map: i.channel_map,
+            volume: i.volume,
+            mute: i.mute,
+            corked: i.corked,
+            media_role: i.proplist.media_role(),
+        }
+    }
+}
+
+impl<'a> From<&'a SourceOutputInfo<'a>> for SourceOutput {
+    fn from(i: &'a SourceOutputInfo<'a>) -> Self {
+        SourceOutput {
+            index: i.index,
+            name: i.name.as_ref().map(|s| s.to_string()),
+            client: i.client,
+            source: i.source,
+            sample_spec: i.sample_spec,
+            channel_map: i.channel_map,
+            volume: i.volume,
+            mute: i.mute,
+            corked: i.corked,
+        }
+    }
+}
+
+impl<'a> From<&'a ClientInfo<'a>> for Client {
+    fn from(i: &'a ClientInfo<'a>) -> Self {
+        Client {
+            index: i.index,
+            name: i.name.as_ref().map(|s| s.to_string()),
+            owner_module: i.owner_module,
+            driver: i.driver.as_ref().map(|s| s.to_string()),
+        }
+    }
+}
+
+impl<'a> From<&'a CardInfo<'a>> for Card {
+    fn from(i: &'a CardInfo<'a>) -> Self {
+        Card {
+            index: i.index,
+            name: i.name.as_ref().map(|s| s.to_string()),
+            driver: i.driver.as_ref().map(|s| s.to_string()),
+            profiles: i.profiles.iter().filter_map(|p| p.into()).collect(),
+            ports: i.ports.iter().filter_map(|p| p.into()).collect(),
+            active_profile: i.active_profile.as_ref()
+                .and_then(|p| p.name.as_ref().map(|s| s.to_string())),
+        }
+    }
+}
+
+impl<'a> From<&'a CardProfileInfo2<'a>> for Option<CardProfile> {
+    /// Converts the profile, giving `None` if it has no name.
+    fn from(i: &'a CardProfileInfo2<'a>) -> Self {
+        let name = i.name.as_ref()?;
+        Some(CardProfile {
+            name: name.to_string(),
+            description: i.description.as_ref().map(|s| s.to_string()),
+            n_sinks: i.n_sinks,
+            n_sources: i.n_sources,
+            priority: i.priority,
+            available: i.available,
+        })
+    }
+}
+
+impl<'a> From<&'a CardPortInfo<'a>> for Option<CardPort> {
+    /// Converts the port, giving `None` if it has no name.
+    fn from(i: &'a CardPortInfo<'a>) -> Self {
+        let name = i.name.as_ref()?;
+        Some(CardPort {
+            name: name.to_string(),
+            description: i.description.as_ref().map(|s| s.to_string()),
+            priority: i.priority,
+            available: i.available,
+            direction: i.direction,
+            latency_offset: i.latency_offset,
+            profiles: i.profiles.iter().filter_map(|p| p.name.as_ref().map(|s| s.to_string()))
+                .collect(),
+        })
+    }
+}
+
+/// Commonly used context functionality.
+///
+/// See the [module level documentation](index.html) for details.
+pub trait ContextOps {
+    /// Return the current context status.
+    fn get_state(&self) -> State;
+
+    /// Connect the context to the specified server, or the default server if `None`.
+    fn connect(&mut self, server: Option<&str>, flags: FlagSet) -> Result<(), PAErr>;
+
+    /// Terminate the context connection immediately.
+    fn disconnect(&mut self);
+
+    /// Returns `Some(true)` when the connection is to a local daemon, `None` if not connected yet.
+    fn is_local(&self) -> Option<bool>;
+
+    /// Return the server name this context is connected to.
+    fn get_server(&self) -> Option<String>;
+
+    /// Set a callback function that is called whenever the context status changes.
+    fn set_state_callback(&mut self, callback: Option<Box<dyn FnMut() + 'static>>);
+
+    /// Set the callback function that is called whenever a subscription event happens.
+    fn set_subscribe_callback(&mut self, callback: Option<SubscribeCb>);
+
+    /// Enable event notification, for the given event types.
+    fn subscribe(&mut self, mask: InterestMaskSet, callback: SuccessCb);
+
+    /// Set the name of the default sink.
+    fn set_default_sink(&mut self, name: &str, callback: SuccessCb);
+
+    /// Set the name of the default source.
+    fn set_default_source(&mut self, name: &str, callback: SuccessCb);
+
+    /// Get an introspector for this context.
+    fn introspector(&self) -> Box<dyn IntrospectorOps>;
+}
+
+/// Commonly used introspection functionality.
+///
+/// See the [module level documentation](index.html) for details.
+pub trait IntrospectorOps {
+    /// Get some information about the server.
+    fn get_server_info(&self, callback: ResultCb<Server>);
+
+    /// Get information about a sink by its name.
+    fn get_sink_info_by_name(&self, name: &str, callback: ResultCb<Sink>);
+
+    /// Get information about a sink by its index.
+    fn get_sink_info_by_index(&self, index: u32, callback: ResultCb<Sink>);
+
+    /// Get the complete sink list.
+    fn get_sink_info_list(&self, callback: ResultCb<Vec<Sink>>);
+
+    /// Set the volume of a sink device specified by its index.
+    fn set_sink_volume_by_index(&mut self, index: u32, volume: &::volume::ChannelVolumes,
+        callback: Option<SuccessCb>);
+
+    /// Set the mute switch of a sink device specified by its index.
+    fn set_sink_mute_by_index(&mut self, index: u32, mute: bool, callback: Option<SuccessCb>);
+
+    /// Get information about a source by its name.
+    fn get_source_info_by_name(&self, name: &str, callback: ResultCb<Source>);
+
+    /// Get information about a source by its index.
+    fn get_source_info_by_index(&self, index: u32, callback: ResultCb<Source>);
+
+    /// Get the complete source list.
+    fn get_source_info_list(&self, callback: ResultCb<Vec<Source>>);
+
+    /// Set the volume of a source device specified by its index.
+    fn set_source_volume_by_index(&mut self, index: u32, volume: &::volume::ChannelVolumes,
+        callback: Option<SuccessCb>);
+
+    /// Set the mute switch of a source device specified by its index.
+    fn set_source_mute_by_index(&mut self, index: u32, mute: bool, callback: Option<SuccessCb>);
+
+    /// Get information about a sink input by its index.
+    fn get_sink_input_info(&self, index: u32, callback: ResultCb<SinkInput>);
+
+    /// Get the complete sink input list.
+    fn get_sink_input_info_list(&self, callback: ResultCb<Vec<SinkInput>>);
+
+    /// Move the specified sink input to a different sink.
+    fn move_sink_input_by_index(&mut self, index: u32, sink_index: u32,
+        callback: Option<SuccessCb>);
+
+    /// Set the volume of a sink input stream.
+    fn set_sink_input_volume(&mut self, index: u32, volume: &::volume::ChannelVolumes,
+        callback: Option<SuccessCb>);
+
+    /// Set the mute switch of a sink input stream.
+    fn set_sink_input_mute(&mut self, index: u32, mute: bool, callback: Option<SuccessCb>);
+
+    /// Kill a sink input.
+    fn kill_sink_input(&mut self, index: u32, callback: Option<SuccessCb>);
+
+    /// Get information about a source output by its index.
+    fn get_source_output_info(&self, index: u32, callback: ResultCb<SourceOutput>);
+
+    /// Get the complete source output list.
+    fn get_source_output_info_list(&self, callback: ResultCb<Vec<SourceOutput>>);
+
+    /// Move the specified source output to a different source.
+    fn move_source_output_by_index(&mut self, index: u32, source_index: u32,
+        callback: Option<SuccessCb>);
+
+    /// Set the volume of a source output stream.
+    fn set_source_output_volume(&mut self, index: u32, volume: &::volume::ChannelVolumes,
+        callback: Option<SuccessCb>);
+
+    /// Set the mute switch of a source output stream.
+    fn set_source_output_mute(&mut self, index: u32, mute: bool, callback: Option<SuccessCb>);
+
+    /// Kill a source output.
+    fn kill_source_output(&mut self, index: u32, callback: Option<SuccessCb>);
+
+    /// Get the complete client list.
+    fn get_client_info_list(&self, callback: ResultCb<Vec<Client>>);
+
+    /// Get the complete card list.
+    fn get_card_info_list(&self, callback: ResultCb<Vec<Card>>);
+
+    /// Change the profile of a card.
+    fn set_card_profile_by_index(&mut self, index: u32, profile: &str,
+        callback: Option<SuccessCb>);
+}
+
+impl<T: ContextOps + ?Sized> ContextOps for Box<T> {
+    fn get_state(&self) -> State {
+        (**self).get_state()
+    }
+
+    fn connect(&mut self, server: Option<&str>, flags: FlagSet) -> Result<(), PAErr> {
+        (**self).connect(server, flags)
+    }
+
+    fn disconnect(&mut self) {
+        (**self).disconnect()
+    }
+
+    fn is_local(&self) -> Option<bool> {
+        (**self).is_local()
+    }
+
+    fn get_server(&self) -> Option<String> {
+        (**self).get_server()
+    }
+
+    fn set_state_callback(&mut self, callback: Option<Box<dyn FnMut() + 'static>>) {
+        (**self).set_state_callback(callback)
+    }
+
+    fn set_subscribe_callback(&mut self, callback: Option<SubscribeCb>) {
+        (**self).set_subscribe_callback(callback)
+    }
+
+    fn subscribe(&mut self, mask: InterestMaskSet, callback: SuccessCb) {
+        (**self).subscribe(mask, callback)
+    }
+
+    fn set_default_sink(&mut self, name: &str, callback: SuccessCb) {
+        (**self).set_default_sink(name, callback)
+    }
+
+    fn set_default_source(&mut self, name: &str, callback: SuccessCb) {
+        (**self).set_default_source(name, callback)
+    }
+
+    fn introspector(&self) -> Box<dyn IntrospectorOps> {
+        (**self).introspector()
+    }
+}
+
+/// Adapt a single-use success callback to the multi-use form taken by the underlying methods.
+fn success_adapter(callback: SuccessCb) -> impl FnMut(bool) + 'static {
+    let mut callback = Some(callback);
+    move |success| {
+        if let Some(callback) = callback.take() {
+            callback(success);
+        }
+    }
+}
+
+/// Adapt an optional success callback to the form taken by the underlying methods.
+fn optional_success_adapter(callback: Option<SuccessCb>) -> Option<Box<dyn FnMut(bool) + 'static>> {
+    callback.map(|cb| Box::new(success_adapter(cb)) as Box<dyn FnMut(bool) + 'static>)
+}
+
+/// Adapt an optional success callback for the kill functions, which require a callback.
+fn optional_kill_adapter(callback: Option<SuccessCb>) -> impl FnMut(bool) + 'static {
+    let mut callback = callback;
+    move |success| {
+        if let Some(callback) = callback.take() {
+            callback(success);
+        }
+    }
+}
+
+/// Adapt a result callback to a list callback, collecting the items, converted to owned form.
+///
+/// Takes the callback, and the info type given to the list callback.
+macro_rules! list_adapter {
+    ($callback:expr, $info:ident) => {{
+        let mut callback = Some($callback);
+        let mut items = Vec::new();
+        move |result: Result<ListEntry<&$info>, PAErr>| match result {
+            Ok(ListEntry::Item(item)) => items.push(item.into()),
+            Ok(ListEntry::End) => {
+                if let Some(callback) = callback.take() {
+                    callback(Ok(::std::mem::take(&mut items)));
+                }
+            },
+            Err(e) => {
+                if let Some(callback) = callback.take() {
+                    callback(Err(e));
+                }
+            },
+        }
+    }};
+}
+
+/// Adapt a result callback to a list callback expected to give a single item.
+///
+/// Takes the callback, and the info type given to the list callback.
+macro_rules! single_adapter {
+    ($callback:expr, $info:ident) => {{
+        let mut callback = Some($callback);
+        move |result: Result<ListEntry<&$info>, PAErr>| {
+            let result = match result {
+                Ok(ListEntry::Item(item)) => Ok(item.into()),
+                Ok(_) => return,
+                Err(e) => Err(e),
+            };
+            if let Some(callback) = callback.take() {
+                callback(result);
+            }
+        }
+    }};
+}
+
+impl ContextOps for Context {
+    fn get_state(&self) -> State {
+        Context::get_state(self)
+    }
+
+    fn connect(&mut self, server: Option<&str>, flags: FlagSet) -> Result<(), PAErr> {
+        Context::connect(self, server, flags, None)
+    }
+
+    fn disconnect(&mut self) {
+        Context::disconnect(self)
+    }
+
+    fn is_local(&self) -> Option<bool> {
+        Context::is_local(self)
+    }
+
+    fn get_server(&self) -> Option<String> {
+        Context::get_server(self)
+    }
+
+    fn set_state_callback(&mut self, callback: Option<Box<dyn FnMut() + 'static>>) {
+        Context::set_state_callback(self, callback)
+    }
+
+    fn set_subscribe_callback(&mut self, callback: Option<SubscribeCb>) {
+        Context::set_subscribe_callback(self, callback)
+    }
+
+    fn subscribe(&mut self, mask: InterestMaskSet, callback: SuccessCb) {
+        drop(Context::subscribe(self, mask, success_adapter(callback)));
+    }
+
+    fn set_default_sink(&mut self, name: &str, callback: SuccessCb) {
+        drop(Context::set_default_sink(self, name, success_adapter(callback)));
+    }
+
+    fn set_default_source(&mut self, name: &str, callback: SuccessCb) {
+        drop(Context::set_default_source(self, name, success_adapter(callback)));
+    }
+
+    fn introspector(&self) -> Box<dyn IntrospectorOps> {
+        Box::new(self.introspect())
+    }
+}
+
+impl IntrospectorOps for Introspector {
+    fn get_server_info(&self, callback: ResultCb<Server>) {
+        let mut callback = Some(callback);
+        drop(Introspector::get_server_info(self, move |info| {
+            if let Some(callback) = callback.take() {
+                callback(Ok(Server::from(info)));
+            }
+        }));
+    }
+
+    fn get_sink_info_by_name(&self, name: &str, callback: ResultCb<Sink>) {
+        let cb = single_adapter!(callback, SinkInfo);
+        drop(Introspector::get_sink_info_by_name(self, name, cb));
+    }
+
+    fn get_sink_info_by_index(&self, index: u32, callback: ResultCb<Sink>) {
+        let cb = single_adapter!(callback, SinkInfo);
+        drop(Introspector::get_sink_info_by_index(self, index, cb));
+    }
+
+    fn get_sink_info_list(&self, callback: ResultCb<Vec<Sink>>) {
+        let cb = list_adapter!(callback, SinkInfo);
+        drop(Introspector::get_sink_info_list(self, cb));
+    }
+
+    fn set_sink_volume_by_index(&mut self, index: u32, volume: &::volume::ChannelVolumes,
+        callback: Option<SuccessCb>)
+    {
+        let cb = optional_success_adapter(callback);
+        drop(Introspector::set_sink_volume_by_index(self, index, volume, cb));
+    }
+
+    fn set_sink_mute_by_index(&mut self, index: u32, mute: bool, callback: Option<SuccessCb>) {
+        let cb = optional_success_adapter(callback);
+        drop(Introspector::set_sink_mute_by_index(self, index, mute, cb));
+    }
+
+    fn get_source_info_by_name(&self, name: &str, callback: ResultCb<Source>) {
+        let cb = single_adapter!(callback, SourceInfo);
+        drop(Introspector::get_source_info_by_name(self, name, cb));
+    }
+
+    fn get_source_info_by_index(&self, index: u32, callback: ResultCb<Source>) {
+        let cb = single_adapter!(callback, SourceInfo);
+        drop(Introspector::get_source_info_by_index(self, index, cb));
+    }
+
+    fn get_source_info_list(&self, callback: ResultCb<Vec<Source>>) {
+        let cb = list_adapter!(callback, SourceInfo);
+        drop(Introspector::get_source_info_list(self, cb));
+    }
+
+    fn set_source_volume_by_index(&mut self, index: u32, volume: &::volume::ChannelVolumes,
+        callback: Option<SuccessCb>)
+    {
+        let cb = optional_success_adapter(callback);
+        drop(Introspector::set_source_volume_by_index(self, index, volume, cb));
+    }
+
+    fn set_source_mute_by_index(&mut self, index: u32, mute: bool, callback: Option<SuccessCb>) {
+        let cb = optional_success_adapter(callback);
+        drop(Introspector::set_source_mute_by_index(self, index, mute, cb));
+    }
+
+    fn get_sink_input_info(&self, index: u32, callback: ResultCb<SinkInput>) {
+        let cb = single_adapter!(callback, SinkInputInfo);
+        drop(Introspector::get_sink_input_info(self, index, cb));
+    }
+
+    fn get_sink_input_info_list(&self, callback: ResultCb<Vec<SinkInput>>) {
+        let cb = list_adapter!(callback, SinkInputInfo);
+        drop(Introspector::get_sink_input_info_list(self, cb));
+    }
+
+    fn move_sink_input_by_index(&mut self, index: u32, sink_index: u32,
+        callback: Option<SuccessCb>)
+    {
+        let cb = optional_success_adapter(callback);
+        drop(Introspector::move_sink_input_by_index(self, index, sink_index, cb));
+    }
+
+    fn set_sink_input_volume(&mut self, index: u32, volume: &::volume::ChannelVolumes,
+        callback: Option<SuccessCb>)
+    {
+        let cb = optional_success_adapter(callback);
+        drop(Introspector::set_sink_input_volume(self, index, volume, cb));
+    }
+
+    fn set_sink_input_mute(&mut self, index: u32, mute: bool, callback: Option<SuccessCb>) {
+        let cb = optional_success_adapter(callback);
+        drop(Introspector::set_sink_input_mute(self, index, mute, cb));
+    }
+
+    fn kill_sink_input(&mut self, index: u32, callback: Option<SuccessCb>) {
+        let cb = optional_kill_adapter(callback);
+        drop(Introspector::kill_sink_input(self, index, cb));
+    }
+
+    fn get_source_output_info(&self, index: u32, callback: ResultCb<SourceOutput>) {
+        let cb = single_adapter!(callback, SourceOutputInfo);
+        drop(Introspector::get_source_output_info(self, index, cb));
+    }
+
+    fn get_source_output_info_list(&self, callback: ResultCb<Vec<SourceOutput>>) {
+        let cb = list_adapter!(callback, SourceOutputInfo);
+        drop(Introspector::get_source_output_info_list(self, cb));
+    }
+
+    fn move_source_output_by_index(&mut self, index: u32, source_index: u32,
+        callback: Option<SuccessCb>)
+    {
+        let cb = optional_success_adapter(callback);
+        drop(Introspector::move_source_output_by_index(self, index, source_index, cb));
+    }
+
+    fn set_source_output_volume(&mut self, index: u32, volume: &::volume::ChannelVolumes,
+        callback: Option<SuccessCb>)
+    {
+        let cb = optional_success_adapter(callback);
+        drop(Introspector::set_source_output_volume(self, index, volume, cb));
+    }
+
+    fn set_source_output_mute(&mut self, index: u32, mute: bool, callback: Option<SuccessCb>) {
+        let cb = optional_success_adapter(callback);
+        drop(Introspector::set_source_output_mute(self, index, mute, cb));
+    }
+
+    fn kill_source_output(&mut self, index: u32, callback: Option<SuccessCb>) {
+        let cb = optional_kill_adapter(callback);
+        drop(Introspector::kill_source_output(self, index, cb));
+    }
+
+    fn get_client_info_list(&self, callback: ResultCb<Vec<Client>>) {
+        let cb = list_adapter!(callback, ClientInfo);
+        drop(Introspector::get_client_info_list(self, cb));
+    }
+
+    fn get_card_info_list(&self, callback: ResultCb<Vec<Card>>) {
+        let cb = list_adapter!(callback, CardInfo);
+        drop(Introspector::get_card_info_list(self, cb));
+    }
+
+    fn set_card_profile_by_index(&mut self, index: u32, profile: &str,
+        callback: Option<SuccessCb>)
+    {
+        let cb = optional_success_adapter(callback);
+        drop(Introspector::set_card_profile_by_index(self, index, profile, cb));
+    }
+}
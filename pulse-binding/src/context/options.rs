@@ -0,0 +1,151 @@
+// Copyright 2018 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Context creation options.
+//!
+//! # Overview
+//!
+//! Some client-side behaviour is controlled only by the client configuration (see
+//! [`client_config`]), read by the library upon creating a context, with no corresponding context
+//! flag or property. Most notably this covers the use of shared memory for data transfer, which
+//! can cause subtle failures where the client and server do not share it, for instance across user
+//! namespaces or containers, or over network transports. [`ContextOptions`] creates a context with
+//! such settings overridden:
+//!
+//! ```rust,ignore
+//! use pulse::context::options::ContextOptions;
+//!
+//! let context = ContextOptions::new()
+//!     .disable_shm(true)
+//!     .create(&mainloop, "MyApp")
+//!     .unwrap();
+//! ```
+//!
+//! Disabling shared memory entirely ([`ContextOptions::disable_shm`]) makes all audio data, and the
+//! control commands otherwise passed through a shared ringbuffer (“srbchannel”), pass through the
+//! socket. Disabling only memfd shared memory ([`ContextOptions::disable_memfd`]) falls back to
+//! POSIX shared memory, as used with servers older than version 9.0.
+//!
+//! Where overrides are given, the effective client configuration (as given by
+//! [`ClientConfig::load`]), with the overrides applied, is written to a temporary file, which the
+//! library is directed to with the `PULSE_CLIENTCONFIG` environment variable whilst creating the
+//! context. The variable is restored afterwards. Since the environment is process-wide, other
+//! threads creating contexts concurrently, other than through `ContextOptions`, may observe it.
+//!
+//! [`client_config`]: ../client_config/index.html
+//! [`ContextOptions`]: struct.ContextOptions.html
+//! [`ContextOptions::disable_shm`]: struct.ContextOptions.html#method.disable_shm
+//! [`ContextOptions::disable_memfd`]: struct.ContextOptions.html#method.disable_memfd
+//! [`ClientConfig::load`]: ../client_config/struct.ClientConfig.html#method.load
+
+use std;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use mainloop::api::Mainloop;
+use proplist::Proplist;
+use super::Context;
+use super::client_config::ClientConfig;
+
+/// Serialises the use of the `PULSE_CLIENTCONFIG` environment variable.
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+/// Distinguishes the temporary configuration files of a process.
+static NEXT_FILE: AtomicUsize = AtomicUsize::new(0);
+
+/// Options for creating a context.
+///
+/// See the [module level documentation](index.html) for details.
+#[derive(Default)]
+pub struct ContextOptions {
+    proplist: Option<Proplist>,
+    disable_shm: Option<bool>,
+    disable_memfd: Option<bool>,
+}
+
+impl ContextOptions {
+    /// Create a new set of options, overriding nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the initial client property list.
+    pub fn proplist(mut self, proplist: Proplist) -> Self {
+        self.proplist = Some(proplist);
+        self
+    }
+
+    /// Set whether or not to disable the use of shared memory for data transfer, overriding the
+    /// `enable-shm` setting of the client configuration.
+    pub fn disable_shm(mut self, disable: bool) -> Self {
+        self.disable_shm = Some(disable);
+        self
+    }
+
+    /// Set whether or not to disable the use of memfd shared memory, falling back to POSIX shared
+    /// memory, overriding the `enable-memfd` setting of the client configuration.
+    pub fn disable_memfd(mut self, disable: bool) -> Self {
+        self.disable_memfd = Some(disable);
+        self
+    }
+
+    /// Get the client configuration the context is created with, i.e. the effective configuration
+    /// with the overrides applied.
+    pub fn client_config(&self) -> ClientConfig {
+        let mut config = ClientConfig::load();
+        if let Some(disable) = self.disable_shm {
+            config.enable_shm = !disable;
+        }
+        if let Some(disable) = self.disable_memfd {
+            config.enable_memfd = !disable;
+        }
+        config
+    }
+
+    /// Create a new context with these options, with the given main loop and application name.
+    ///
+    /// Returns `None` if the context could not be created, or the temporary configuration file
+    /// could not be written.
+    pub fn create(self, mainloop: &impl Mainloop, name: &str) -> Option<Context> {
+        if self.disable_shm.is_none() && self.disable_memfd.is_none() {
+            return self.new_context(mainloop, name);
+        }
+
+        let file = format!("pulse-client-{}-{}.conf", std::process::id(),
+            NEXT_FILE.fetch_add(1, Ordering::Relaxed));
+        let path = std::env::temp_dir().join(file);
+        std::fs::write(&path, self.client_config().to_string()).ok()?;
+
+        let context = {
+            let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            let previous = std::env::var_os("PULSE_CLIENTCONFIG");
+            std::env::set_var("PULSE_CLIENTCONFIG", &path);
+            let context = self.new_context(mainloop, name);
+            match previous {
+                Some(value) => std::env::set_var("PULSE_CLIENTCONFIG", value),
+                None => std::env::remove_var("PULSE_CLIENTCONFIG"),
+            }
+            context
+        };
+        let _ = std::fs::remove_file(&path);
+        context
+    }
+
+    fn new_context(&self, mainloop: &impl Mainloop, name: &str) -> Option<Context> {
+        match self.proplist {
+            Some(ref proplist) => Context::new_with_proplist(mainloop, name, proplist),
+            None => Context::new(mainloop, name),
+        }
+    }
+}
@@ -0,0 +1,162 @@
+// Copyright 2017 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Automatic port selection.
+//!
+//! # Overview
+//!
+//! Upon a change in port availability, such as headphones being plugged in, desktop environments
+//! typically switch to the most preferable port that is available. The [`PortSelection`] trait,
+//! implemented for [`SinkInfo`] and [`SourceInfo`], provides this policy: the port of highest
+//! priority known to be available is selected, falling back to that of highest priority whose
+//! availability is unknown (as for ports not supporting jack detection). Ports known to be
+//! unavailable are never selected.
+//!
+//! [`Introspector::auto_select_port`] applies the selection, changing the active port if
+//! necessary:
+//!
+//! ```rust,ignore
+//! introspector.get_sink_info_by_index(index, move |result| {
+//!     if let Ok(ListEntry::Item(sink)) = result {
+//!         introspector2.auto_select_port(sink, None);
+//!     }
+//! });
+//! ```
+//!
+//! [`PortSelection`]: trait.PortSelection.html
+//! [`SinkInfo`]: ../introspect/struct.SinkInfo.html
+//! [`SourceInfo`]: ../introspect/struct.SourceInfo.html
+//! [`Introspector::auto_select_port`]: ../introspect/struct.Introspector.html#method.auto_select_port
+
+use def::PortAvailable;
+use operation::Operation;
+use super::introspect::{Introspector, SinkInfo, SourceInfo};
+
+/// Devices having ports to select from.
+pub trait PortSelection {
+    /// Get the name of the best port, as per the selection policy described in the
+    /// [module level documentation](index.html), or `None` if no port is selectable.
+    fn best_port(&self) -> Option<&str>;
+
+    /// Get the name of the active port, if any.
+    fn active_port_name(&self) -> Option<&str>;
+
+    /// Set the active port of the device.
+    #[doc(hidden)]
+    fn set_port(&self, introspector: &mut Introspector, port: &str,
+        callback: Option<Box<dyn FnMut(bool) + 'static>>) -> Operation<dyn FnMut(bool)>;
+}
+
+/// Select the best port from the (name, priority, availability) of each port.
+fn select<'a, I>(ports: I) -> Option<&'a str>
+    where I: Iterator<Item = (Option<&'a str>, u32, PortAvailable)>
+{
+    let mut best: Option<(&'a str, u32, bool)> = None;
+    for (name, priority, available) in ports {
+        let name = match name {
+            Some(name) => name,
+            None => continue,
+        };
+        let known = match available {
+            PortAvailable::Yes => true,
+            PortAvailable::Unknown => false,
+            _ => continue,
+        };
+        let better = match best {
+            None => true,
+            Some((_, best_priority, best_known)) => {
+                (known && !best_known) || (known == best_known && priority > best_priority)
+            },
+        };
+        if better {
+            best = Some((name, priority, known));
+        }
+    }
+    best.map(|(name, _, _)| name)
+}
+
+impl<'a> PortSelection for SinkInfo<'a> {
+    fn best_port(&self) -> Option<&str> {
+        select(self.ports.iter().map(|p| (p.name.as_ref().map(|n| n.as_ref()), p.priority,
+            p.available)))
+    }
+
+    fn active_port_name(&self) -> Option<&str> {
+        self.active_port.as_ref().and_then(|p| p.name.as_ref()).map(|n| n.as_ref())
+    }
+
+    fn set_port(&self, introspector: &mut Introspector, port: &str,
+        callback: Option<Box<dyn FnMut(bool) + 'static>>) -> Operation<dyn FnMut(bool)>
+    {
+        introspector.set_sink_port_by_index(self.index, port, callback)
+    }
+}
+
+impl<'a> PortSelection for SourceInfo<'a> {
+    fn best_port(&self) -> Option<&str> {
+        select(self.ports.iter().map(|p| (p.name.as_ref().map(|n| n.as_ref()), p.priority,
+            p.available)))
+    }
+
+    fn active_port_name(&self) -> Option<&str> {
+        self.active_port.as_ref().and_then(|p| p.name.as_ref()).map(|n| n.as_ref())
+    }
+
+    fn set_port(&self, introspector: &mut Introspector, port: &str,
+        callback: Option<Box<dyn FnMut(bool) + 'static>>) -> Operation<dyn FnMut(bool)>
+    {
+        introspector.set_source_port_by_index(self.index, port, callback)
+    }
+}
+
+impl Introspector {
+    /// Switch the given sink or source to its best port, as per the policy described in the
+    /// [`port_select`](../port_select/index.html) module documentation.
+    ///
+    /// Returns `None` (without calling the callback) if the best port is already active, or no
+    /// port is selectable.
+    ///
+    /// The optional callback must accept a `bool`, which indicates success.
+    pub fn auto_select_port<D>(&mut self, device: &D,
+        callback: Option<Box<dyn FnMut(bool) + 'static>>) -> Option<Operation<dyn FnMut(bool)>>
+        where D: PortSelection + ?Sized
+    {
+        let best = device.best_port()?;
+        if device.active_port_name() == Some(best) {
+            return None;
+        }
+        Some(device.set_port(self, best, callback))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selection_policy() {
+        let ports = |list: &[(&'static str, u32, PortAvailable)]| {
+            select(list.iter().map(|&(n, p, a)| (Some(n), p, a)))
+        };
+        assert_eq!(ports(&[]), None);
+        assert_eq!(ports(&[("speaker", 100, PortAvailable::Unknown),
+            ("headphones", 200, PortAvailable::No)]), Some("speaker"));
+        assert_eq!(ports(&[("speaker", 100, PortAvailable::Unknown),
+            ("headphones", 50, PortAvailable::Yes)]), Some("headphones"));
+        assert_eq!(ports(&[("line", 300, PortAvailable::Yes),
+            ("headphones", 200, PortAvailable::Yes)]), Some("line"));
+        assert_eq!(ports(&[("speaker", 100, PortAvailable::No)]), None);
+    }
+}
@@ -0,0 +1,374 @@
+// Copyright 2017 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Automatically reconnecting contexts.
+//!
+//! # Overview
+//!
+//! A context cannot be reconnected once its connection has failed or been terminated; a new
+//! context must be created instead, along with any streams and subscriptions belonging to it.
+//! Long-running applications wishing to survive a restart of the server can use a
+//! [`ReconnectingContext`] to take care of this.
+//!
+//! A [`ReconnectingContext`] watches the state of its current context, and upon it becoming
+//! [`State::Failed`] or [`State::Terminated`] (other than through [`ReconnectingContext::disconnect`])
+//! schedules a new connection attempt, with an exponentially increasing delay between successive
+//! failed attempts. Upon a connection becoming ready, the subscription mask set with
+//! [`ReconnectingContext::subscribe`] is re-applied, and each registered stream factory is called
+//! with the new context, to re-create streams.
+//!
+//! Since the context is replaced upon reconnection, applications should not hold on to the context
+//! given out by [`ReconnectingContext::get_context`] beyond the current connection.
+//!
+//! ```rust,ignore
+//! extern crate libpulse_binding as pulse;
+//!
+//! use pulse::context::reconnect::ReconnectingContext;
+//!
+//! let mut rc = ReconnectingContext::new(&mut mainloop, "My application", None).unwrap();
+//! rc.add_stream_factory(Box::new(move |context| {
+//!     // Create and connect streams here, storing them somewhere
+//! }));
+//! rc.connect(None, pulse::context::flags::NOFLAGS).unwrap();
+//! ```
+//!
+//! [`ReconnectingContext`]: struct.ReconnectingContext.html
+//! [`ReconnectingContext::disconnect`]: struct.ReconnectingContext.html#method.disconnect
+//! [`ReconnectingContext::subscribe`]: struct.ReconnectingContext.html#method.subscribe
+//! [`ReconnectingContext::get_context`]: struct.ReconnectingContext.html#method.get_context
+//! [`State::Failed`]: ../enum.State.html#variant.Failed
+//! [`State::Terminated`]: ../enum.State.html#variant.Terminated
+
+use std;
+use capi;
+use std::rc::{Rc, Weak};
+use std::cell::RefCell;
+use super::{Context, ContextInternal, State, FlagSet};
+use super::subscribe::InterestMaskSet;
+use mainloop::api::{Mainloop, MainloopInnerType};
+use mainloop::events::timer::TimeEvent;
+use error::PAErr;
+use proplist::Proplist;
+use time::{MicroSeconds, MonotonicTs, MICROS_PER_SEC};
+
+/// Default delay before the first reconnection attempt.
+pub const DEFAULT_INITIAL_DELAY: MicroSeconds = MicroSeconds(MICROS_PER_SEC);
+
+/// Default maximum delay between reconnection attempts.
+pub const DEFAULT_MAX_DELAY: MicroSeconds = MicroSeconds(30 * MICROS_PER_SEC);
+
+/// A stream factory, called with each newly ready context.
+pub type StreamFactory = Box<dyn FnMut(&Rc<RefCell<Context>>) + 'static>;
+
+/// A context wrapper that automatically reconnects.
+///
+/// See the [module level documentation](index.html) for details.
+pub struct ReconnectingContext<M>
+    where M: Mainloop + 'static
+{
+    shared: Rc<RefCell<Shared<M::MI>>>,
+}
+
+/// State shared with the context and timer callbacks.
+struct Shared<T>
+    where T: MainloopInnerType + 'static
+{
+    mainloop: Rc<T>,
+    name: String,
+    proplist: Option<Proplist>,
+    server: Option<String>,
+    flags: FlagSet,
+    context: Option<Rc<RefCell<Context>>>,
+    /// Pointer of the current context, for identifying it within its state callback (where it may
+    /// already be mutably borrowed).
+    context_ptr: *mut ContextInternal,
+    subscription: Option<InterestMaskSet>,
+    stream_factories: Vec<StreamFactory>,
+    state_callback: Option<Box<dyn FnMut(State)>>,
+    initial_delay: MicroSeconds,
+    max_delay: MicroSeconds,
+    /// Number of consecutive failed connection attempts.
+    attempts: u32,
+    /// Timer used for scheduling reconnection attempts.
+    timer: Option<TimeEvent<T>>,
+    /// Whether a reconnection attempt is due upon the timer firing.
+    pending: bool,
+    /// Set upon deliberate disconnection, to suppress reconnection.
+    stopped: bool,
+}
+
+impl<M> ReconnectingContext<M>
+    where M: Mainloop + 'static
+{
+    /// Create a new reconnecting context, with an application name and optionally an initial
+    /// client property list, both used for each context created.
+    ///
+    /// Returns `None` if creating the timer event used for scheduling reconnection attempts fails.
+    pub fn new(mainloop: &mut M, name: &str, proplist: Option<&Proplist>) -> Option<Self> {
+        let shared = Rc::new(RefCell::new(Shared {
+            mainloop: mainloop.inner(),
+            name: name.to_string(),
+            proplist: proplist.cloned(),
+            server: None,
+            flags: super::flags::NOFLAGS,
+            context: None,
+            context_ptr: std::ptr::null_mut(),
+            subscription: None,
+            stream_factories: Vec::new(),
+            state_callback: None,
+            initial_delay: DEFAULT_INITIAL_DELAY,
+            max_delay: DEFAULT_MAX_DELAY,
+            attempts: 0,
+            timer: None,
+            pending: false,
+            stopped: true,
+        }));
+
+        let weak = Rc::downgrade(&shared);
+        // Note, the timer fires once upon creation, which is ignored since nothing is pending.
+        let timer = mainloop.new_timer_event_rt(MonotonicTs::now(), Box::new(move |_| {
+            if let Some(shared) = weak.upgrade() {
+                let due = std::mem::replace(&mut shared.borrow_mut().pending, false);
+                if due {
+                    // Failure will have been handled by the state callback
+                    let _ = Self::reconnect(&shared);
+                }
+            }
+        }))?;
+        shared.borrow_mut().timer = Some(timer);
+        Some(Self { shared })
+    }
+
+    /// Set the delay before the first reconnection attempt, and the maximum delay between
+    /// attempts. The delay doubles with each consecutive failed attempt, up to the maximum.
+    pub fn set_backoff(&mut self, initial: MicroSeconds, max: MicroSeconds) {
+        let mut shared = self.shared.borrow_mut();
+        shared.initial_delay = initial;
+        shared.max_delay = std::cmp::max(initial, max);
+    }
+
+    /// Set a callback function that is called whenever the state of the current context changes.
+    pub fn set_state_callback(&mut self, callback: Option<Box<dyn FnMut(State) + 'static>>) {
+        self.shared.borrow_mut().state_callback = callback;
+    }
+
+    /// Set the subscription mask, to be applied to every context upon becoming ready.
+    ///
+    /// If currently connected, the subscription is applied to the current context immediately.
+    /// Note that the subscription callback must be set on the context by a stream factory (see
+    /// [`add_stream_factory`]), or otherwise upon each connection.
+    ///
+    /// [`add_stream_factory`]: #method.add_stream_factory
+    pub fn subscribe(&mut self, mask: InterestMaskSet) {
+        let context = {
+            let mut shared = self.shared.borrow_mut();
+            shared.subscription = Some(mask);
+            shared.context.clone()
+        };
+        if let Some(context) = context {
+            if context.borrow().get_state() == State::Ready {
+                context.borrow_mut().subscribe(mask, |_| {});
+            }
+        }
+    }
+
+    /// Register a stream factory, called with the context each time a connection becomes ready,
+    /// for creating streams (or otherwise setting up the context, e.g. its subscription callback).
+    pub fn add_stream_factory(&mut self, factory: StreamFactory) {
+        self.shared.borrow_mut().stream_factories.push(factory);
+    }
+
+    /// Get the current context, if any.
+    ///
+    /// This is replaced upon each reconnection attempt, so should not be held on to.
+    pub fn get_context(&self) -> Option<Rc<RefCell<Context>>> {
+        self.shared.borrow().context.clone()
+    }
+
+    /// Connect to the specified server, reconnecting whenever the connection is lost, until
+    /// [`disconnect`] is called.
+    ///
+    /// The server and flags have the same meaning as with [`Context::connect`], and are used for
+    /// each connection attempt. An error is returned only if the first attempt fails immediately.
+    ///
+    /// [`disconnect`]: #method.disconnect
+    /// [`Context::connect`]: ../struct.Context.html#method.connect
+    pub fn connect(&mut self, server: Option<&str>, flags: FlagSet) -> Result<(), PAErr> {
+        {
+            let mut shared = self.shared.borrow_mut();
+            shared.server = server.map(|s| s.to_string());
+            shared.flags = flags;
+            shared.attempts = 0;
+            shared.stopped = false;
+        }
+        Self::reconnect(&self.shared)
+    }
+
+    /// Disconnect the current context, if any, and stop reconnecting.
+    pub fn disconnect(&mut self) {
+        let context = {
+            let mut shared = self.shared.borrow_mut();
+            shared.stopped = true;
+            shared.pending = false;
+            shared.context.clone()
+        };
+        if let Some(context) = context {
+            context.borrow_mut().disconnect();
+        }
+    }
+
+    /// Create a new context and connect it.
+    ///
+    /// No borrow of the shared state is held while connecting, since state changes may be notified
+    /// synchronously.
+    fn reconnect(shared_rc: &Rc<RefCell<Shared<M::MI>>>) -> Result<(), PAErr> {
+        let (context, server, flags) = {
+            let mut shared = shared_rc.borrow_mut();
+            if shared.stopped {
+                return Ok(());
+            }
+            let api = shared.mainloop.get_api() as *const _;
+            let mut context = match Context::new_from_api(api, &shared.name,
+                shared.proplist.as_ref())
+            {
+                Some(context) => context,
+                None => return Err(PAErr::from(::error::Code::Internal)),
+            };
+            let ptr = context.ptr;
+            let weak = Rc::downgrade(shared_rc);
+            context.set_state_callback(Some(Box::new(move || {
+                Self::handle_state_change(&weak, ptr);
+            })));
+            let context = Rc::new(RefCell::new(context));
+            // Note, any previous context has failed or terminated, so can simply be dropped
+            shared.context = Some(Rc::clone(&context));
+            shared.context_ptr = ptr;
+            (context, shared.server.clone(), shared.flags)
+        };
+
+        let result = context.borrow_mut().connect(server.as_deref(), flags, None);
+        if result.is_err() && context.borrow().get_state() != State::Failed {
+            // Failure without a state change, thus not handled by the state callback
+            Self::schedule(shared_rc);
+        }
+        result
+    }
+
+    /// Handle a state change of the context, which may be a previous one.
+    fn handle_state_change(weak: &Weak<RefCell<Shared<M::MI>>>, ptr: *mut ContextInternal) {
+        let shared_rc = match weak.upgrade() {
+            Some(shared) => shared,
+            None => return,
+        };
+        if shared_rc.borrow().context_ptr != ptr {
+            return;
+        }
+        let state: State = unsafe { capi::pa_context_get_state(ptr).into() };
+
+        // Take the user callback out while calling it, to allow it to use this object
+        let callback = shared_rc.borrow_mut().state_callback.take();
+        if let Some(mut callback) = callback {
+            callback(state);
+            let mut shared = shared_rc.borrow_mut();
+            if shared.state_callback.is_none() {
+                shared.state_callback = Some(callback);
+            }
+        }
+
+        match state {
+            State::Ready => Self::handle_ready(&shared_rc),
+            State::Failed | State::Terminated => Self::schedule(&shared_rc),
+            _ => {},
+        }
+    }
+
+    /// Re-apply the subscription and re-create streams upon a connection becoming ready.
+    fn handle_ready(shared_rc: &Rc<RefCell<Shared<M::MI>>>) {
+        let (context, subscription, mut factories) = {
+            let mut shared = shared_rc.borrow_mut();
+            shared.attempts = 0;
+            let context = match shared.context {
+                Some(ref context) => Rc::clone(context),
+                None => return,
+            };
+            (context, shared.subscription, std::mem::take(&mut shared.stream_factories))
+        };
+        if let Some(mask) = subscription {
+            context.borrow_mut().subscribe(mask, |_| {});
+        }
+        for factory in factories.iter_mut() {
+            factory(&context);
+        }
+        // Restore, keeping any factories added by the factories themselves
+        let mut shared = shared_rc.borrow_mut();
+        factories.append(&mut shared.stream_factories);
+        shared.stream_factories = factories;
+    }
+
+    /// Schedule a reconnection attempt, unless stopped.
+    fn schedule(shared_rc: &Rc<RefCell<Shared<M::MI>>>) {
+        let mut shared = shared_rc.borrow_mut();
+        if shared.stopped {
+            return;
+        }
+        let delay = delay_for(shared.initial_delay, shared.max_delay, shared.attempts);
+        shared.attempts = shared.attempts.saturating_add(1);
+        shared.pending = true;
+        if let Some(ref mut timer) = shared.timer {
+            timer.restart_rt(MonotonicTs::now() + delay);
+        }
+    }
+}
+
+/// Delay before the next attempt, doubling with each previous failed attempt, up to `max`.
+fn delay_for(initial: MicroSeconds, max: MicroSeconds, attempts: u32) -> MicroSeconds {
+    let factor = 1u64.checked_shl(attempts).unwrap_or(u64::MAX);
+    MicroSeconds(std::cmp::min(initial.0.saturating_mul(factor), max.0))
+}
+
+impl<M> Drop for ReconnectingContext<M>
+    where M: Mainloop + 'static
+{
+    fn drop(&mut self) {
+        self.disconnect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_sequence() {
+        let delays: Vec<u64> = (0..8)
+            .map(|n| delay_for(DEFAULT_INITIAL_DELAY, DEFAULT_MAX_DELAY, n).0 / MICROS_PER_SEC)
+            .collect();
+        assert_eq!(delays, [1, 2, 4, 8, 16, 30, 30, 30]);
+    }
+
+    #[test]
+    fn backoff_cap() {
+        let initial = MicroSeconds(250_000);
+        let max = MicroSeconds(5 * MICROS_PER_SEC);
+        assert_eq!(delay_for(initial, max, 4), MicroSeconds(4_000_000));
+        assert_eq!(delay_for(initial, max, 5), max);
+        // No overflow with many attempts
+        assert_eq!(delay_for(initial, max, 63), max);
+        assert_eq!(delay_for(initial, max, 64), max);
+        assert_eq!(delay_for(initial, max, u32::MAX), max);
+        // A maximum below the initial delay caps even the first attempt
+        assert_eq!(delay_for(max, initial, 0), initial);
+    }
+}
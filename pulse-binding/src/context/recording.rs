@@ -0,0 +1,234 @@
+// Copyright 2017 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Recording and replay of subscription events (requires the `recording` feature).
+//!
+//! # Overview
+//!
+//! Bugs concerning hot-plugging and the like depend upon the sequence of subscription events
+//! given by the server, which is difficult to reproduce without the hardware involved. A
+//! [`Recorder`] writes a timestamped log of these events, along with any [`Topology`] snapshots
+//! taken, to a file, for instance from an application run by a user reporting a bug. The log,
+//! loaded as a [`Recording`], can then be replayed into anything handling subscription events,
+//! such as a [`VolumeCache`], [`StreamDirectory`], or the application’s own subscribe callback.
+//!
+//! ```rust,ignore
+//! use pulse::context::recording::{Recorder, Recording};
+//!
+//! let recorder = Rc::new(RefCell::new(Recorder::create("events.log")?));
+//! let recorder_ref = Rc::clone(&recorder);
+//! context.set_subscribe_callback(Some(Box::new(move |facility, operation, index| {
+//!     recorder_ref.borrow_mut().record_event(facility, operation, index).ok();
+//!     handle_event(facility, operation, index);
+//! })));
+//!
+//! // Later, without the hardware
+//! let recording = Recording::load("events.log")?;
+//! recording.replay(|facility, operation, index| handle_event(facility, operation, index));
+//! ```
+//!
+//! The log holds one JSON object per line, giving the time of the entry in microseconds since
+//! the start of recording, and either the event or the snapshot.
+//!
+//! [`Recorder`]: struct.Recorder.html
+//! [`Topology`]: ../topology/struct.Topology.html
+//! [`Recording`]: struct.Recording.html
+//! [`VolumeCache`]: ../volume_cache/struct.VolumeCache.html
+//! [`StreamDirectory`]: ../stream_directory/struct.StreamDirectory.html
+
+use std;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::Instant;
+use serde::{Deserialize, Serialize};
+use super::subscribe::{Facility, Operation};
+use super::topology::Topology;
+
+/// A recorded item.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Record {
+    /// A subscription event.
+    Event {
+        /// Facility of the event.
+        facility: Facility,
+        /// Operation of the event.
+        operation: Operation,
+        /// Index of the object concerned.
+        index: u32,
+    },
+    /// A snapshot of the audio graph.
+    Snapshot(Topology),
+}
+
+/// A timestamped recorded item.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    /// Time of the entry, in microseconds since the start of recording.
+    pub time: u64,
+    /// The recorded item.
+    pub record: Record,
+}
+
+/// Writer of a log of subscription events and snapshots.
+///
+/// See the [module level documentation](index.html) for details.
+pub struct Recorder<W: Write> {
+    writer: W,
+    start: Instant,
+}
+
+impl Recorder<BufWriter<File>> {
+    /// Create a recorder writing to the file at the given path, replacing any existing file.
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self::new(BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl<W: Write> Recorder<W> {
+    /// Create a recorder writing to the given writer, with recording starting now.
+    pub fn new(writer: W) -> Self {
+        Self { writer: writer, start: Instant::now() }
+    }
+
+    /// Record a subscription event, as given to the context’s subscribe callback.
+    ///
+    /// Events with an unknown facility or operation are not recorded.
+    pub fn record_event(&mut self, facility: Option<Facility>, operation: Option<Operation>,
+        index: u32) -> io::Result<()>
+    {
+        match (facility, operation) {
+            (Some(facility), Some(operation)) => self.record(Record::Event {
+                facility: facility,
+                operation: operation,
+                index: index,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Record a snapshot of the audio graph, as taken with [`Introspector::snapshot_topology`].
+    ///
+    /// [`Introspector::snapshot_topology`]: ../introspect/struct.Introspector.html#method.snapshot_topology
+    pub fn record_snapshot(&mut self, topology: &Topology) -> io::Result<()> {
+        self.record(Record::Snapshot(topology.clone()))
+    }
+
+    /// Record an item, timestamped with the current time.
+    pub fn record(&mut self, record: Record) -> io::Result<()> {
+        let elapsed = self.start.elapsed();
+        let time = elapsed.as_secs() * 1_000_000 + elapsed.subsec_micros() as u64;
+        let entry = Entry { time: time, record: record };
+        serde_json::to_writer(&mut self.writer, &entry)?;
+        self.writer.write_all(b"\n")?;
+        // Flushed per entry, such that the log is complete should the application crash
+        self.writer.flush()
+    }
+
+    /// Consume the recorder, returning the writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// A log of subscription events and snapshots, as written by a [`Recorder`].
+///
+/// [`Recorder`]: struct.Recorder.html
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Recording {
+    /// The entries, in order of recording.
+    pub entries: Vec<Entry>,
+}
+
+impl Recording {
+    /// Load a recording from the file at the given path.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::read(BufReader::new(File::open(path)?))
+    }
+
+    /// Read a recording from the given reader. Blank lines are ignored.
+    ///
+    /// Returns an error of kind `InvalidData` if an entry cannot be parsed.
+    pub fn read<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut entries = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            entries.push(serde_json::from_str(&line)?);
+        }
+        Ok(Recording { entries: entries })
+    }
+
+    /// Replay the recorded events, in order, to the given handler, taking the same arguments as
+    /// the context’s subscribe callback. Snapshots are skipped.
+    pub fn replay<F>(&self, mut handler: F)
+        where F: FnMut(Option<Facility>, Option<Operation>, u32)
+    {
+        for entry in &self.entries {
+            if let Record::Event { facility, operation, index } = entry.record {
+                handler(Some(facility), Some(operation), index);
+            }
+        }
+    }
+
+    /// Get the recorded snapshots, in order, along with their times.
+    pub fn snapshots(&self) -> impl Iterator<Item = (u64, &Topology)> {
+        self.entries.iter().filter_map(|entry| match entry.record {
+            Record::Snapshot(ref topology) => Some((entry.time, topology)),
+            _ => None,
+        })
+    }
+
+    /// Get the duration of the recording, i.e. the time of the last entry, in microseconds.
+    pub fn duration(&self) -> u64 {
+        self.entries.last().map_or(0, |entry| entry.time)
+    }
+}
+
+impl std::iter::FromIterator<Entry> for Recording {
+    fn from_iter<I: IntoIterator<Item = Entry>>(iter: I) -> Self {
+        Recording { entries: iter.into_iter().collect() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_and_replay() {
+        let mut recorder = Recorder::new(Vec::new());
+        recorder.record_event(Some(Facility::Card), Some(Operation::Changed), 3).unwrap();
+        recorder.record_event(None, Some(Operation::New), 4).unwrap();
+        recorder.record_snapshot(&Topology::default()).unwrap();
+        recorder.record_event(Some(Facility::Sink), Some(Operation::Removed), 5).unwrap();
+        let log = recorder.into_inner();
+
+        let recording = Recording::read(&log[..]).unwrap();
+        assert_eq!(recording.entries.len(), 3);
+        assert_eq!(recording.snapshots().count(), 1);
+        let mut events = Vec::new();
+        recording.replay(|facility, operation, index| events.push((facility, operation, index)));
+        assert_eq!(events, vec![
+            (Some(Facility::Card), Some(Operation::Changed), 3),
+            (Some(Facility::Sink), Some(Operation::Removed), 5),
+        ]);
+
+        let err = Recording::read(&b"{\"time\": 0}\n"[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -23,13 +23,23 @@
 //!
 //! # Creation
 //!
-//! To create a sample, the normal stream API is used (see [`::stream`]). The function
-//! [`::stream::Stream::connect_upload`] will make sure the stream is stored as a sample on the
-//! server.
+//! To create a sample, an [`UploadStream`] is used, which offers only the operations applicable to
+//! uploading: data is written to it, and the upload then completed with [`UploadStream::finish`],
+//! the sample receiving the same name as the stream, or aborted with [`UploadStream::cancel`].
 //!
-//! To complete the upload, [`::stream::Stream::finish_upload`] is called and the sample will
-//! receive the same name as the stream. If the upload should be aborted, simply call
-//! [`::stream::Stream::disconnect`].
+//! ```rust,ignore
+//! use pulse::context::scache::UploadStream;
+//!
+//! let mut upload = UploadStream::new(&mut context, "bell", &spec, None, data.len())?;
+//! // Once the stream is ready (see `set_state_callback`)
+//! upload.write(&data)?;
+//! upload.finish()?;
+//! ```
+//!
+//! Alternatively, the normal stream API can be used (see [`::stream`]). The function
+//! [`::stream::Stream::connect_upload`] will make sure the stream is stored as a sample on the
+//! server. To complete the upload, [`::stream::Stream::finish_upload`] is called. If the upload
+//! should be aborted, simply call [`::stream::Stream::disconnect`].
 //!
 //! # Playing samples
 //!
@@ -55,6 +65,9 @@
 //! When a sample is no longer needed, it should be removed on the server to save resources. The
 //! sample is deleted using [`::context::Context::remove_sample`].
 //!
+//! [`UploadStream`]: struct.UploadStream.html
+//! [`UploadStream::finish`]: struct.UploadStream.html#method.finish
+//! [`UploadStream::cancel`]: struct.UploadStream.html#method.cancel
 //! [`::stream`]: ../../stream/index.html
 //! [`::stream::Stream::connect_upload`]: ../../stream/struct.Stream.html#method.connect_upload
 //! [`::stream::Stream::finish_upload`]: ../../stream/struct.Stream.html#method.finish_upload
@@ -69,7 +82,10 @@ use std::ffi::CString;
 use std::ptr::null;
 use super::{ContextInternal, Context};
 use callbacks::box_closure_get_capi_ptr;
+use ::error::PAErr;
 use ::operation::Operation;
+use ::proplist::Proplist;
+use ::stream::{SeekMode, State, Stream};
 
 impl Context {
     /// Remove a sample from the sample cache.
@@ -91,6 +107,7 @@ impl Context {
             Some(super::success_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("scache::remove_sample", None)
     }
 
     /// Play a sample from the sample cache to the specified device.
@@ -131,6 +148,7 @@ impl Context {
             cb_fn, cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("scache::play_sample", None)
     }
 
     /// Play a sample from the sample cache to the specified device, allowing specification of a
@@ -180,6 +198,7 @@ impl Context {
         };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(Result<u32, ()>)>)
+            .traced("scache::play_sample_with_proplist", None)
     }
 }
 
@@ -194,3 +213,83 @@ fn play_sample_success_cb_proxy(_: *mut ContextInternal, index: u32, userdata: *
         (callback)(index_actual);
     });
 }
+
+/// A stream uploading a sample to the sample cache.
+///
+/// This wraps a [`Stream`](../../stream/struct.Stream.html) connected for uploading, offering only
+/// the operations applicable to it. See the [module level documentation](index.html).
+pub struct UploadStream {
+    stream: Stream,
+}
+
+impl UploadStream {
+    /// Create a new stream, connected for uploading a sample of the given length in bytes, to be
+    /// named as the stream.
+    ///
+    /// Returns the error of the context upon failure.
+    pub fn new(ctx: &mut Context, name: &str, ss: &::sample::Spec,
+        map: Option<&::channelmap::Map>, length: usize) -> Result<Self, PAErr>
+    {
+        let stream = Stream::new(ctx, name, ss, map).ok_or_else(|| ctx.errno())?;
+        Self::connect(stream, length)
+    }
+
+    /// Create a new stream with the given property list, connected for uploading a sample of the
+    /// given length in bytes, to be named as the stream.
+    ///
+    /// Returns the error of the context upon failure.
+    pub fn new_with_proplist(ctx: &mut Context, name: &str, ss: &::sample::Spec,
+        map: Option<&::channelmap::Map>, proplist: &mut Proplist, length: usize)
+        -> Result<Self, PAErr>
+    {
+        let stream = Stream::new_with_proplist(ctx, name, ss, map, proplist)
+            .ok_or_else(|| ctx.errno())?;
+        Self::connect(stream, length)
+    }
+
+    fn connect(mut stream: Stream, length: usize) -> Result<Self, PAErr> {
+        stream.connect_upload(length)?;
+        Ok(UploadStream { stream: stream })
+    }
+
+    /// Write data to the sample, in multiples of the frame size of the sample spec.
+    pub fn write(&mut self, data: &[u8]) -> Result<(), PAErr> {
+        self.stream.write(data, None, 0, SeekMode::Relative)
+    }
+
+    /// Complete the upload, storing the sample.
+    ///
+    /// The stream is then terminated by the server. It must be kept until its state becomes
+    /// [`State::Terminated`], since dropping it beforehand aborts the upload.
+    ///
+    /// [`State::Terminated`]: ../../stream/enum.State.html#variant.Terminated
+    pub fn finish(&mut self) -> Result<(), PAErr> {
+        self.stream.finish_upload()
+    }
+
+    /// Abort the upload, discarding the data written.
+    pub fn cancel(mut self) -> Result<(), PAErr> {
+        self.stream.disconnect()
+    }
+
+    /// Get the current state of the stream.
+    pub fn get_state(&self) -> State {
+        self.stream.get_state()
+    }
+
+    /// Set the callback function that is called whenever the state of the stream changes.
+    pub fn set_state_callback(&mut self, callback: Option<Box<dyn FnMut() + 'static>>) {
+        self.stream.set_state_callback(callback);
+    }
+
+    /// Set the callback function that is called when new data may be written to the stream. The
+    /// callback is given the number of bytes requested.
+    pub fn set_write_callback(&mut self, callback: Option<Box<dyn FnMut(usize) + 'static>>) {
+        self.stream.set_write_callback(callback);
+    }
+
+    /// Return the number of bytes requested by the server that have not yet been written.
+    pub fn writable_size(&self) -> Option<usize> {
+        self.stream.writable_size()
+    }
+}
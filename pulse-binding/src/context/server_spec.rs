@@ -0,0 +1,205 @@
+// Copyright 2017 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Server connection target specification.
+//!
+//! # Overview
+//!
+//! When connecting a context, a server string can be given to specify which server(s) to connect
+//! to. This string holds one or more whitespace separated entries, each describing a server
+//! address, which are tried in turn until a connection succeeds. An entry is for instance
+//! `unix:/run/user/1000/pulse/native` for a local Unix socket, or `tcp:host:4713` for a TCP
+//! connection.
+//!
+//! Rather than building such strings by hand, a [`ServerSpec`] can be built, with each entry
+//! validated, and then given to [`Context::connect_to`]:
+//!
+//! ```rust,ignore
+//! extern crate libpulse_binding as pulse;
+//!
+//! use pulse::context::server_spec::ServerSpec;
+//!
+//! let spec = ServerSpec::new()
+//!     .unix("/run/user/1000/pulse/native")
+//!     .tcp("192.168.1.10", Some(4713));
+//!
+//! context.connect_to(&spec, pulse::context::flags::NOFLAGS, None).unwrap();
+//! ```
+//!
+//! [`ServerSpec`]: struct.ServerSpec.html
+//! [`Context::connect_to`]: ../struct.Context.html#method.connect_to
+
+use std::path::{Path, PathBuf};
+use error::PAErr;
+
+/// A single server address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Entry {
+    /// Unix socket path.
+    Unix(PathBuf),
+    /// TCP host and optional port, either IP version.
+    Tcp(String, Option<u16>),
+    /// TCP host and optional port, IPv4 only.
+    Tcp4(String, Option<u16>),
+    /// TCP host and optional port, IPv6 only.
+    Tcp6(String, Option<u16>),
+}
+
+/// A builder of server strings, as accepted by [`Context::connect`].
+///
+/// Entries are tried by the library in the order they were added.
+///
+/// [`Context::connect`]: ../struct.Context.html#method.connect
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ServerSpec {
+    entries: Vec<Entry>,
+}
+
+impl ServerSpec {
+    /// Create a new, empty, specification.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a Unix socket entry, e.g. `/run/user/1000/pulse/native`.
+    pub fn unix<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.entries.push(Entry::Unix(path.as_ref().to_path_buf()));
+        self
+    }
+
+    /// Add a TCP entry, for host name or address `host`, and optionally a specific port (otherwise
+    /// the default port is used).
+    pub fn tcp(mut self, host: &str, port: Option<u16>) -> Self {
+        self.entries.push(Entry::Tcp(host.to_string(), port));
+        self
+    }
+
+    /// Add an IPv4-only TCP entry, for host name or address `host`, and optionally a specific port.
+    pub fn tcp4(mut self, host: &str, port: Option<u16>) -> Self {
+        self.entries.push(Entry::Tcp4(host.to_string(), port));
+        self
+    }
+
+    /// Add an IPv6-only TCP entry, for host name or address `host`, and optionally a specific port.
+    ///
+    /// An IPv6 address should be given without surrounding brackets, these are added as necessary.
+    pub fn tcp6(mut self, host: &str, port: Option<u16>) -> Self {
+        self.entries.push(Entry::Tcp6(host.to_string(), port));
+        self
+    }
+
+    /// Returns `true` if no entries have been added.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Build the server string.
+    ///
+    /// Returns an error of [`::error::Code::Invalid`] if there are no entries, or if any entry is
+    /// invalid, i.e. a socket path that is not absolute or not UTF-8, an empty host, or a path or
+    /// host containing whitespace (which would split the entry in two).
+    ///
+    /// [`::error::Code::Invalid`]: ../../error/enum.Code.html#variant.Invalid
+    pub fn build(&self) -> Result<String, PAErr> {
+        if self.entries.is_empty() {
+            return Err(PAErr::from(::error::Code::Invalid));
+        }
+        let mut parts = Vec::with_capacity(self.entries.len());
+        for entry in &self.entries {
+            parts.push(Self::build_entry(entry)?);
+        }
+        Ok(parts.join(" "))
+    }
+
+    fn build_entry(entry: &Entry) -> Result<String, PAErr> {
+        let invalid = || PAErr::from(::error::Code::Invalid);
+        let check = |s: &str| !s.is_empty() && !s.contains(char::is_whitespace);
+        match *entry {
+            Entry::Unix(ref path) => {
+                let path_str = path.to_str().ok_or_else(invalid)?;
+                if !path.is_absolute() || !check(path_str) {
+                    return Err(invalid());
+                }
+                Ok(format!("unix:{}", path_str))
+            },
+            Entry::Tcp(ref host, port) |
+            Entry::Tcp4(ref host, port) |
+            Entry::Tcp6(ref host, port) => {
+                let host = host.trim_start_matches('[').trim_end_matches(']');
+                if !check(host) {
+                    return Err(invalid());
+                }
+                let prefix = match *entry {
+                    Entry::Tcp4(..) => "tcp4",
+                    Entry::Tcp6(..) => "tcp6",
+                    _ => "tcp",
+                };
+                // IPv6 addresses must be bracketed to separate them from the port
+                let host = match host.contains(':') {
+                    true => format!("[{}]", host),
+                    false => host.to_string(),
+                };
+                match port {
+                    Some(port) => Ok(format!("{}:{}:{}", prefix, host, port)),
+                    None => Ok(format!("{}:{}", prefix, host)),
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use error::Code;
+
+    #[test]
+    fn unix() {
+        let spec = ServerSpec::new().unix("/run/user/1000/pulse/native");
+        assert_eq!(spec.build(), Ok("unix:/run/user/1000/pulse/native".to_string()));
+    }
+
+    #[test]
+    fn tcp() {
+        let spec = ServerSpec::new()
+            .tcp("192.168.1.10", Some(4713))
+            .tcp("localhost", None)
+            .tcp4("10.0.0.1", None);
+        assert_eq!(spec.build(),
+            Ok("tcp:192.168.1.10:4713 tcp:localhost tcp4:10.0.0.1".to_string()));
+    }
+
+    #[test]
+    fn tcp6() {
+        let spec = ServerSpec::new()
+            .tcp6("::1", Some(4713))
+            .tcp6("[fe80::1]", None)
+            .tcp("::1", None);
+        assert_eq!(spec.build(), Ok("tcp6:[::1]:4713 tcp6:[fe80::1] tcp:[::1]".to_string()));
+    }
+
+    #[test]
+    fn invalid() {
+        let invalid = Err(PAErr::from(Code::Invalid));
+        assert!(ServerSpec::new().is_empty());
+        assert_eq!(ServerSpec::new().build(), invalid);
+        assert_eq!(ServerSpec::new().unix("run/pulse/native").build(), invalid);
+        assert_eq!(ServerSpec::new().unix("/run/my pulse/native").build(), invalid);
+        assert_eq!(ServerSpec::new().tcp("", Some(4713)).build(), invalid);
+        assert_eq!(ServerSpec::new().tcp6("[]", None).build(), invalid);
+        // A single invalid entry invalidates the whole
+        assert_eq!(ServerSpec::new().tcp("localhost", None).tcp("my host", None).build(), invalid);
+    }
+}
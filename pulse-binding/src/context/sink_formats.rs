@@ -0,0 +1,185 @@
+// Copyright 2017 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Sink format support.
+//!
+//! # Overview
+//!
+//! Sinks accepting compressed audio for passthrough, such as to an S/PDIF or HDMI receiver, list
+//! the encodings supported alongside PCM. The [`SinkFormats`] trait, implemented for [`SinkInfo`]
+//! and its owned form, [`Sink`], gives access to these as typed values, with helpers such as
+//! [`SinkFormats::supports_encoding`].
+//!
+//! [`Introspector::sink_supports_passthrough`] fetches a sink and gives a [`Passthrough`] summary,
+//! combining the encodings supported with whether the set of formats can be configured (per the
+//! [`SET_FORMATS`] flag), as is typically the case for HDMI sinks, where the connected receiver’s
+//! capabilities are unknown:
+//!
+//! ```rust,ignore
+//! use pulse::format::Encoding;
+//!
+//! introspector.sink_supports_passthrough(index, |result| {
+//!     if let Ok(passthrough) = result {
+//!         if passthrough.supports(Encoding::EAC3_IEC61937) {
+//!             enable_eac3_output();
+//!         }
+//!     }
+//! });
+//! ```
+//!
+//! [`SinkFormats`]: trait.SinkFormats.html
+//! [`SinkInfo`]: ../introspect/struct.SinkInfo.html
+//! [`Sink`]: ../ops/struct.Sink.html
+//! [`SinkFormats::supports_encoding`]: trait.SinkFormats.html#method.supports_encoding
+//! [`Introspector::sink_supports_passthrough`]: ../introspect/struct.Introspector.html#method.sink_supports_passthrough
+//! [`Passthrough`]: struct.Passthrough.html
+//! [`SET_FORMATS`]: ../../def/sink_flags/constant.SET_FORMATS.html
+
+use callbacks::ListEntry;
+use def::{SinkFlagSet, sink_flags};
+use error::{Code, PAErr};
+use format::{Encoding, Info};
+use operation::Operation;
+use super::introspect::{Introspector, SinkInfo};
+use super::ops::Sink;
+
+/// Sinks with a set of supported formats.
+pub trait SinkFormats {
+    /// Get the formats supported by the sink.
+    fn formats(&self) -> &[Info];
+
+    /// Get the flags of the sink.
+    fn sink_flags(&self) -> SinkFlagSet;
+
+    /// Returns `true` if the sink supports the given encoding.
+    fn supports_encoding(&self, encoding: Encoding) -> bool {
+        self.formats().iter().any(|f| f.get_encoding() == encoding)
+    }
+
+    /// Get the encodings supported by the sink, without duplicates, in the order listed.
+    fn encodings(&self) -> Vec<Encoding> {
+        let mut encodings = Vec::new();
+        for encoding in self.formats().iter().map(|f| f.get_encoding()) {
+            if !encodings.contains(&encoding) {
+                encodings.push(encoding);
+            }
+        }
+        encodings
+    }
+
+    /// Summarise the passthrough (compressed audio) support of the sink.
+    fn passthrough(&self) -> Passthrough {
+        Passthrough::new(self.encodings(), self.sink_flags())
+    }
+}
+
+impl<'a> SinkFormats for SinkInfo<'a> {
+    fn formats(&self) -> &[Info] {
+        &self.formats
+    }
+
+    fn sink_flags(&self) -> SinkFlagSet {
+        self.flags
+    }
+}
+
+impl SinkFormats for Sink {
+    fn formats(&self) -> &[Info] {
+        &self.formats
+    }
+
+    fn sink_flags(&self) -> SinkFlagSet {
+        self.flags
+    }
+}
+
+/// Passthrough (compressed audio) support of a sink.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Passthrough {
+    /// The compressed encodings currently supported, i.e. those other than PCM.
+    pub encodings: Vec<Encoding>,
+    /// Whether or not the set of supported formats can be configured (the sink has the
+    /// `SET_FORMATS` flag), such that further encodings may be enabled.
+    pub configurable: bool,
+}
+
+impl Passthrough {
+    /// Create from the encodings supported by a sink, and its flags.
+    pub fn new(encodings: Vec<Encoding>, flags: SinkFlagSet) -> Self {
+        Passthrough {
+            encodings: encodings.into_iter()
+                .filter(|&e| e != Encoding::PCM && e != Encoding::Any && e != Encoding::Invalid)
+                .collect(),
+            configurable: flags & sink_flags::SET_FORMATS != 0,
+        }
+    }
+
+    /// Returns `true` if the sink currently supports any compressed encoding.
+    pub fn is_supported(&self) -> bool {
+        !self.encodings.is_empty()
+    }
+
+    /// Returns `true` if the sink currently supports the given compressed encoding.
+    pub fn supports(&self, encoding: Encoding) -> bool {
+        self.encodings.contains(&encoding)
+    }
+}
+
+impl Introspector {
+    /// Get the passthrough support of a sink, by index.
+    ///
+    /// The callback is given the support, or the error should one occur, including
+    /// [`Code::NoEntity`] if no such sink exists.
+    ///
+    /// Panics on error, i.e. invalid arguments or state.
+    ///
+    /// [`Code::NoEntity`]: ../../error/enum.Code.html#variant.NoEntity
+    pub fn sink_supports_passthrough<F>(&self, index: u32, callback: F)
+        -> Operation<dyn FnMut(Result<ListEntry<&SinkInfo>, PAErr>)>
+        where F: FnOnce(Result<Passthrough, PAErr>) + 'static
+    {
+        let mut callback = Some(callback);
+        self.get_sink_info_by_index(index, move |result| {
+            let result = match result {
+                Ok(ListEntry::Item(sink)) => Ok(sink.passthrough()),
+                Ok(ListEntry::End) => Err(PAErr::from(Code::NoEntity)),
+                Err(e) => Err(e),
+            };
+            if let Some(callback) = callback.take() {
+                callback(result);
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passthrough_summary() {
+        let encodings = vec![Encoding::PCM, Encoding::AC3_IEC61937, Encoding::DTS_IEC61937];
+        let passthrough = Passthrough::new(encodings, sink_flags::SET_FORMATS);
+        assert!(passthrough.is_supported());
+        assert!(passthrough.configurable);
+        assert!(passthrough.supports(Encoding::AC3_IEC61937));
+        assert!(!passthrough.supports(Encoding::EAC3_IEC61937));
+        assert_eq!(passthrough.encodings, vec![Encoding::AC3_IEC61937, Encoding::DTS_IEC61937]);
+
+        let pcm_only = Passthrough::new(vec![Encoding::PCM], sink_flags::NOFLAGS);
+        assert!(!pcm_only.is_supported());
+        assert!(!pcm_only.configurable);
+    }
+}
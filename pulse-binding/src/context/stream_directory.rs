@@ -0,0 +1,302 @@
+// Copyright 2018 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Correlation of local streams with their server-side counterparts.
+//!
+//! # Overview
+//!
+//! Each stream is represented within the server by a sink input (for playback) or source output
+//! (for recording), which other clients, such as mixers, may change. Subscription events about
+//! these identify them only by index. A [`StreamDirectory`] tracks the streams registered with it,
+//! resolving their indices once they are ready, and turns the subscription events concerning them
+//! into [`StreamEvent`]s, given to a callback per stream: a change of volume or mute switch, a move
+//! to another device, or the stream being killed.
+//!
+//! As with the [`volume_cache`](../volume_cache/index.html) module, the directory must be given the
+//! subscription events, by calling [`StreamDirectory::handle_event`] from the context’s subscribe
+//! callback, with the context subscribed to (at least) [`StreamDirectory::INTEREST_MASK`].
+//!
+//! ```rust,ignore
+//! use pulse::context::stream_directory::{StreamDirectory, StreamEvent};
+//! use pulse::direction::Direction;
+//!
+//! let directory = Rc::new(StreamDirectory::new(&context));
+//! let id = directory.register(&stream, Direction::OUTPUT, |event| match event {
+//!     StreamEvent::VolumeChanged { volume, mute } => update_slider(volume.avg(), mute),
+//!     StreamEvent::Moved { device } => println!("now playing on sink {}", device),
+//!     StreamEvent::Killed => println!("stream killed"),
+//! });
+//!
+//! let directory_ref = Rc::clone(&directory);
+//! context.set_subscribe_callback(Some(Box::new(move |facility, operation, index| {
+//!     directory_ref.handle_event(facility, operation, index);
+//! })));
+//! context.subscribe(StreamDirectory::INTEREST_MASK, |_| {});
+//! ```
+//!
+//! [`StreamDirectory`]: struct.StreamDirectory.html
+//! [`StreamEvent`]: enum.StreamEvent.html
+//! [`StreamDirectory::handle_event`]: struct.StreamDirectory.html#method.handle_event
+//! [`StreamDirectory::INTEREST_MASK`]: struct.StreamDirectory.html#associatedconstant.INTEREST_MASK
+
+use capi;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::{Rc, Weak};
+use callbacks::ListEntry;
+use capi::pa_stream as StreamInternal;
+use direction::Direction;
+use error::PAErr;
+use stream::{self, Stream};
+use volume::ChannelVolumes;
+use super::Context;
+use super::introspect::{Introspector, SinkInputInfo, SourceOutputInfo};
+use super::subscribe::{Facility, InterestMaskSet, Operation, subscription_masks};
+
+/// Identifies a stream registered with a directory.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct StreamId(u64);
+
+/// A change to the server-side counterpart of a stream.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum StreamEvent {
+    /// The volume or mute switch changed.
+    VolumeChanged {
+        /// The new volume.
+        volume: ChannelVolumes,
+        /// The new state of the mute switch.
+        mute: bool,
+    },
+    /// The stream was moved to another sink or source.
+    Moved {
+        /// Index of the sink or source now connected to.
+        device: u32,
+    },
+    /// The stream was removed by the server, rather than disconnected locally. The stream is
+    /// unregistered automatically.
+    Killed,
+}
+
+/// Tracks local streams, giving events about changes to their server-side counterparts.
+///
+/// See the [module level documentation](index.html) for details.
+pub struct StreamDirectory {
+    shared: Rc<RefCell<Shared>>,
+}
+
+/// State shared with the introspection callbacks.
+struct Shared {
+    introspector: Introspector,
+    next_id: u64,
+    entries: HashMap<StreamId, Entry>,
+}
+
+struct Entry {
+    /// The stream, to which a reference is held.
+    ptr: *mut StreamInternal,
+    direction: Direction,
+    /// Server-side index, once resolved.
+    index: Option<u32>,
+    /// Last known volume, mute switch and device.
+    last: Option<(ChannelVolumes, bool, u32)>,
+    /// Taken whilst being called.
+    callback: Option<Box<dyn FnMut(StreamEvent)>>,
+}
+
+impl Drop for Entry {
+    fn drop(&mut self) {
+        unsafe { capi::pa_stream_unref(self.ptr) };
+    }
+}
+
+/// Call the callback of a stream with the given events, without holding a borrow of the shared
+/// state, such that the callback may use the directory.
+fn dispatch(shared_rc: &Rc<RefCell<Shared>>, id: StreamId, events: Vec<StreamEvent>) {
+    for event in events {
+        let callback = shared_rc.borrow_mut().entries.get_mut(&id).and_then(|e| e.callback.take());
+        let mut callback = match callback {
+            Some(callback) => callback,
+            None => return,
+        };
+        (callback)(event);
+        if let Some(entry) = shared_rc.borrow_mut().entries.get_mut(&id) {
+            entry.callback = Some(callback);
+        }
+    }
+}
+
+/// Create a callback for fetching the state of a stream, comparing it against the last known.
+macro_rules! fetch_cb {
+    ($weak:expr, $id:expr, $info:ident, $device:ident) => {{
+        let weak: Weak<RefCell<Shared>> = $weak;
+        let id: StreamId = $id;
+        move |result: Result<ListEntry<&$info>, PAErr>| {
+            let i = match result {
+                Ok(ListEntry::Item(i)) => i,
+                _ => return,
+            };
+            let shared_rc = match weak.upgrade() {
+                Some(shared) => shared,
+                None => return,
+            };
+            let mut events = Vec::new();
+            {
+                let mut shared = shared_rc.borrow_mut();
+                let entry = match shared.entries.get_mut(&id) {
+                    Some(entry) if entry.index == Some(i.index) => entry,
+                    _ => return,
+                };
+                if let Some((volume, mute, device)) = entry.last {
+                    if device != i.$device {
+                        events.push(StreamEvent::Moved { device: i.$device });
+                    }
+                    if volume != i.volume || mute != i.mute {
+                        events.push(StreamEvent::VolumeChanged { volume: i.volume, mute: i.mute });
+                    }
+                }
+                entry.last = Some((i.volume, i.mute, i.$device));
+            }
+            dispatch(&shared_rc, id, events);
+        }
+    }};
+}
+
+/// Fetch the state of a stream.
+fn fetch(shared_rc: &Rc<RefCell<Shared>>, id: StreamId, direction: Direction, index: u32) {
+    let weak = Rc::downgrade(shared_rc);
+    let shared = shared_rc.borrow();
+    match direction.is_output() {
+        true => drop(shared.introspector.get_sink_input_info(index,
+            fetch_cb!(weak, id, SinkInputInfo, sink))),
+        false => drop(shared.introspector.get_source_output_info(index,
+            fetch_cb!(weak, id, SourceOutputInfo, source))),
+    }
+}
+
+impl StreamDirectory {
+    /// The subscription interest mask needed for tracking streams.
+    pub const INTEREST_MASK: InterestMaskSet =
+        subscription_masks::SINK_INPUT | subscription_masks::SOURCE_OUTPUT;
+
+    /// Create a new (empty) directory for the given context.
+    pub fn new(context: &Context) -> Self {
+        Self {
+            shared: Rc::new(RefCell::new(Shared {
+                introspector: context.introspect(),
+                next_id: 0,
+                entries: HashMap::new(),
+            })),
+        }
+    }
+
+    /// Register a stream, with its direction (output for playback, input for recording), and a
+    /// callback to be given events about it.
+    ///
+    /// The stream may be registered before being connected; its index is resolved once it is
+    /// ready. A reference to the underlying stream object is held until the stream is unregistered.
+    ///
+    /// Panics if the direction is not exactly one of output or input.
+    pub fn register<F>(&self, stream: &Stream, direction: Direction, callback: F) -> StreamId
+        where F: FnMut(StreamEvent) + 'static
+    {
+        assert!(direction.is_output() != direction.is_input());
+        let mut shared = self.shared.borrow_mut();
+        let id = StreamId(shared.next_id);
+        shared.next_id += 1;
+        unsafe { capi::pa_stream_ref(stream.ptr) };
+        shared.entries.insert(id, Entry {
+            ptr: stream.ptr,
+            direction: direction,
+            index: None,
+            last: None,
+            callback: Some(Box::new(callback)),
+        });
+        drop(shared);
+        self.resolve();
+        id
+    }
+
+    /// Unregister a stream, such that no further events are given for it.
+    pub fn unregister(&self, id: StreamId) {
+        // Drop outside of the borrow, in case the callback holds something using the directory
+        let entry = self.shared.borrow_mut().entries.remove(&id);
+        drop(entry);
+    }
+
+    /// Get the server-side index of a registered stream, if resolved.
+    pub fn index_of(&self, id: StreamId) -> Option<u32> {
+        self.resolve();
+        self.shared.borrow().entries.get(&id).and_then(|e| e.index)
+    }
+
+    /// Resolve the indices of streams that have since become ready, fetching their initial state.
+    fn resolve(&self) {
+        let mut resolved = Vec::new();
+        for (id, entry) in self.shared.borrow_mut().entries.iter_mut() {
+            if entry.index.is_some() {
+                continue;
+            }
+            let state: stream::State = unsafe { capi::pa_stream_get_state(entry.ptr).into() };
+            if state != stream::State::Ready {
+                continue;
+            }
+            match unsafe { capi::pa_stream_get_index(entry.ptr) } {
+                ::def::INVALID_INDEX => {},
+                index => {
+                    entry.index = Some(index);
+                    resolved.push((*id, entry.direction, index));
+                },
+            }
+        }
+        for (id, direction, index) in resolved {
+            fetch(&self.shared, id, direction, index);
+        }
+    }
+
+    /// Handle a subscription event, as given to the context’s subscribe callback.
+    ///
+    /// Events concerning registered streams are turned into [`StreamEvent`]s given to their
+    /// callbacks. Other events are ignored.
+    ///
+    /// [`StreamEvent`]: enum.StreamEvent.html
+    pub fn handle_event(&self, facility: Option<Facility>, operation: Option<Operation>,
+        index: u32)
+    {
+        let output = match facility {
+            Some(Facility::SinkInput) => true,
+            Some(Facility::SourceOutput) => false,
+            _ => return,
+        };
+        self.resolve();
+        let found = self.shared.borrow().entries.iter()
+            .find(|&(_, e)| e.direction.is_output() == output && e.index == Some(index))
+            .map(|(id, e)| (*id, e.direction, e.ptr));
+        let (id, direction, ptr) = match found {
+            Some(found) => found,
+            None => return,
+        };
+        match operation {
+            Some(Operation::Removed) => {
+                let state: stream::State = unsafe { capi::pa_stream_get_state(ptr).into() };
+                if state != stream::State::Terminated {
+                    dispatch(&self.shared, id, vec![StreamEvent::Killed]);
+                }
+                self.unregister(id);
+            },
+            Some(Operation::Changed) => fetch(&self.shared, id, direction, index),
+            _ => {},
+        }
+    }
+}
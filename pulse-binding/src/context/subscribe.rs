@@ -61,6 +61,8 @@ use capi;
 use std::os::raw::c_void;
 use super::{ContextInternal, Context};
 use callbacks::box_closure_get_capi_ptr;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 pub use capi::context::subscribe::pa_subscription_event_type_t as EventType;
 pub use capi::PA_SUBSCRIPTION_EVENT_FACILITY_MASK as FACILITY_MASK;
@@ -92,6 +94,7 @@ pub mod subscription_masks {
 /// Facility component of an event.
 #[repr(u32)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Facility {
     Sink = 0,
     Source = 1,
@@ -110,6 +113,7 @@ pub enum Facility {
 /// Operation component of an event.
 #[repr(u32)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Operation {
     /// A new object was created
     New = 0,
@@ -184,6 +188,7 @@ impl Context {
             cb_data) };
         assert!(!ptr.is_null());
         ::operation::Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("subscribe::subscribe", None)
     }
 
     /// Set the context specific call back function that is called whenever a subscribed-to event
@@ -215,6 +220,7 @@ fn cb_proxy(_: *mut ContextInternal, et: EventType, index: u32, userdata: *mut c
     let _ = std::panic::catch_unwind(|| {
         let facility = get_facility(et);
         let operation = get_operation(et);
+        ::logging::subscription_event(facility, operation, index);
         let callback = Callback::get_callback(userdata);
         (callback)(facility, operation, index);
     });
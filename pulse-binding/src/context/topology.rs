@@ -0,0 +1,403 @@
+// Copyright 2017 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Snapshots of the audio graph.
+//!
+//! # Overview
+//!
+//! [`Introspector::snapshot_topology`] fetches the cards, sinks, sources, sink inputs, source
+//! outputs and clients, giving a [`Topology`]: a plain description of how they are connected,
+//! useful for debugging routing, and for tooling such as dashboards. With the `serde` feature
+//! enabled, its types implement `Serialize` and `Deserialize`, for export as JSON and the like, and
+//! [`Topology::to_dot`] renders it as a Graphviz DOT graph.
+//!
+//! ```rust,ignore
+//! context.introspect().snapshot_topology(|result| {
+//!     if let Ok(topology) = result {
+//!         std::fs::write("graph.dot", topology.to_dot()).unwrap();
+//!         println!("{}", serde_json::to_string_pretty(&topology).unwrap());
+//!     }
+//! });
+//! ```
+//!
+//! The lists are fetched independently, thus a snapshot taken whilst the graph is changing may be
+//! inconsistent, e.g. a sink input may refer to a sink not listed.
+//!
+//! [`Introspector::snapshot_topology`]: ../introspect/struct.Introspector.html#method.snapshot_topology
+//! [`Topology`]: struct.Topology.html
+//! [`Topology::to_dot`]: struct.Topology.html#method.to_dot
+
+use std;
+use std::cell::RefCell;
+use std::fmt::Write;
+use std::rc::Rc;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use error::PAErr;
+use super::collect::ListItem;
+use super::introspect::Introspector;
+use super::ops::{Card, Client, Sink, SinkInput, Source, SourceOutput};
+
+/// A card.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CardNode {
+    /// Index of the card.
+    pub index: u32,
+    /// Name of the card.
+    pub name: Option<String>,
+    /// Name of the active profile, if any.
+    pub active_profile: Option<String>,
+}
+
+/// A sink or source.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DeviceNode {
+    /// Index of the device.
+    pub index: u32,
+    /// Name of the device.
+    pub name: Option<String>,
+    /// Description of the device.
+    pub description: Option<String>,
+    /// Index of the card the device belongs to, if any.
+    pub card: Option<u32>,
+    /// Name of the active port, if any.
+    pub active_port: Option<String>,
+    /// Mute switch of the device.
+    pub mute: bool,
+    /// For a sink, the index of its monitor source; for a monitor source, the index of the sink it
+    /// monitors.
+    pub monitor: Option<u32>,
+}
+
+/// A sink input or source output.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StreamNode {
+    /// Index of the stream.
+    pub index: u32,
+    /// Name of the stream.
+    pub name: Option<String>,
+    /// Index of the client the stream belongs to, if any.
+    pub client: Option<u32>,
+    /// Index of the connected sink or source.
+    pub device: u32,
+    /// Mute switch of the stream.
+    pub mute: bool,
+    /// Whether or not the stream is corked.
+    pub corked: bool,
+}
+
+/// A client.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ClientNode {
+    /// Index of the client.
+    pub index: u32,
+    /// Name of the client.
+    pub name: Option<String>,
+}
+
+/// A snapshot of the audio graph.
+///
+/// See the [module level documentation](index.html) for details.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Topology {
+    /// The cards.
+    pub cards: Vec<CardNode>,
+    /// The sinks.
+    pub sinks: Vec<DeviceNode>,
+    /// The sources, including monitor sources.
+    pub sources: Vec<DeviceNode>,
+    /// The sink inputs, connected to sinks.
+    pub sink_inputs: Vec<StreamNode>,
+    /// The source outputs, connected to sources.
+    pub source_outputs: Vec<StreamNode>,
+    /// The clients.
+    pub clients: Vec<ClientNode>,
+}
+
+impl<'a> From<&'a Card> for CardNode {
+    fn from(c: &'a Card) -> Self {
+        CardNode { index: c.index, name: c.name.clone(), active_profile: c.active_profile.clone() }
+    }
+}
+
+impl<'a> From<&'a Sink> for DeviceNode {
+    fn from(s: &'a Sink) -> Self {
+        DeviceNode {
+            index: s.index,
+            name: s.name.clone(),
+            description: s.description.clone(),
+            card: s.card,
+            active_port: s.active_port.clone(),
+            mute: s.mute,
+            monitor: Some(s.monitor_source),
+        }
+    }
+}
+
+impl<'a> From<&'a Source> for DeviceNode {
+    fn from(s: &'a Source) -> Self {
+        DeviceNode {
+            index: s.index,
+            name: s.name.clone(),
+            description: s.description.clone(),
+            card: s.card,
+            active_port: s.active_port.clone(),
+            mute: s.mute,
+            monitor: s.monitor_of_sink,
+        }
+    }
+}
+
+impl<'a> From<&'a SinkInput> for StreamNode {
+    fn from(s: &'a SinkInput) -> Self {
+        StreamNode {
+            index: s.index,
+            name: s.name.clone(),
+            client: s.client,
+            device: s.sink,
+            mute: s.mute,
+            corked: s.corked,
+        }
+    }
+}
+
+impl<'a> From<&'a SourceOutput> for StreamNode {
+    fn from(s: &'a SourceOutput) -> Self {
+        StreamNode {
+            index: s.index,
+            name: s.name.clone(),
+            client: s.client,
+            device: s.source,
+            mute: s.mute,
+            corked: s.corked,
+        }
+    }
+}
+
+impl<'a> From<&'a Client> for ClientNode {
+    fn from(c: &'a Client) -> Self {
+        ClientNode { index: c.index, name: c.name.clone() }
+    }
+}
+
+/// Quote a string for use as a DOT identifier or label.
+fn dot_quote(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' | '\\' => { quoted.push('\\'); quoted.push(c); },
+            '\n' => quoted.push_str("\\n"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Label of a node, given its kind, index and name.
+fn dot_label(kind: &str, index: u32, name: &Option<String>) -> String {
+    match *name {
+        Some(ref name) => dot_quote(&format!("{} #{}\n{}", kind, index, name)),
+        None => dot_quote(&format!("{} #{}", kind, index)),
+    }
+}
+
+impl Topology {
+    /// Get the client with the given index, if listed.
+    pub fn client(&self, index: u32) -> Option<&ClientNode> {
+        self.clients.iter().find(|c| c.index == index)
+    }
+
+    /// Render the graph in the Graphviz DOT language.
+    ///
+    /// Cards are linked to their sinks and sources, sink inputs to their sinks, sources to their
+    /// source outputs, and sinks to their monitor sources (dashed). Streams are labelled with the
+    /// name of their client, where known; muted and corked nodes are drawn grey.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph pulseaudio {\n    rankdir=LR;\n");
+        let mut node = |id: String, label: String, shape: &str, inactive: bool| {
+            let style = match inactive { true => ", color=grey, fontcolor=grey", false => "" };
+            let _ = writeln!(dot, "    {} [label={}, shape={}{}];", id, label, shape, style);
+        };
+        for c in &self.cards {
+            node(format!("card{}", c.index), dot_label("card", c.index, &c.name), "box3d", false);
+        }
+        for s in &self.sinks {
+            node(format!("sink{}", s.index), dot_label("sink", s.index, &s.description), "box",
+                s.mute);
+        }
+        for s in &self.sources {
+            node(format!("source{}", s.index), dot_label("source", s.index, &s.description), "box",
+                s.mute);
+        }
+        let stream_label = |kind: &str, s: &StreamNode| {
+            let client = s.client.and_then(|i| self.client(i)).and_then(|c| c.name.as_ref());
+            let name = match (client, s.name.as_ref()) {
+                (Some(client), Some(name)) => Some(format!("{}: {}", client, name)),
+                (client, name) => client.or(name).cloned(),
+            };
+            dot_label(kind, s.index, &name)
+        };
+        for s in &self.sink_inputs {
+            node(format!("sink_input{}", s.index), stream_label("sink input", s), "ellipse",
+                s.mute || s.corked);
+        }
+        for s in &self.source_outputs {
+            node(format!("source_output{}", s.index), stream_label("source output", s), "ellipse",
+                s.mute || s.corked);
+        }
+
+        for s in &self.sinks {
+            if let Some(card) = s.card {
+                let _ = writeln!(dot, "    card{} -> sink{};", card, s.index);
+            }
+            if let Some(monitor) = s.monitor {
+                let _ = writeln!(dot, "    sink{} -> source{} [style=dashed];", s.index, monitor);
+            }
+        }
+        for s in &self.sources {
+            if let Some(card) = s.card {
+                let _ = writeln!(dot, "    card{} -> source{};", card, s.index);
+            }
+        }
+        for s in &self.sink_inputs {
+            let _ = writeln!(dot, "    sink_input{} -> sink{};", s.index, s.device);
+        }
+        for s in &self.source_outputs {
+            let _ = writeln!(dot, "    source{} -> source_output{};", s.device, s.index);
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// State shared with the list callbacks.
+struct Pending {
+    topology: Topology,
+    remaining: usize,
+    error: Option<PAErr>,
+    callback: Option<Box<dyn FnOnce(Result<Topology, PAErr>)>>,
+}
+
+/// Fetch a list, storing the items with `store`, and completing the snapshot if it was the last
+/// list outstanding.
+fn gather<T, F>(introspector: &Introspector, shared: &Rc<RefCell<Pending>>, store: F)
+    where T: ListItem,
+          F: FnOnce(&mut Topology, Vec<T>) + 'static
+{
+    let shared = Rc::clone(shared);
+    drop(introspector.collect_list(move |result: Result<Vec<T>, PAErr>| {
+        let done = {
+            let mut pending = shared.borrow_mut();
+            match result {
+                Ok(items) => store(&mut pending.topology, items),
+                Err(e) => { pending.error.get_or_insert(e); },
+            }
+            pending.remaining -= 1;
+            match pending.remaining {
+                0 => pending.callback.take().map(|cb| (cb, std::mem::take(&mut pending.topology),
+                    pending.error.take())),
+                _ => None,
+            }
+        };
+        if let Some((callback, topology, error)) = done {
+            callback(match error {
+                Some(e) => Err(e),
+                None => Ok(topology),
+            });
+        }
+    }));
+}
+
+impl Introspector {
+    /// Take a snapshot of the audio graph, giving it to the callback once all lists have been
+    /// fetched, or the first error should one occur. See the
+    /// [`topology`](../topology/index.html) module documentation.
+    ///
+    /// Panics on error, i.e. invalid arguments or state.
+    pub fn snapshot_topology<F>(&self, callback: F)
+        where F: FnOnce(Result<Topology, PAErr>) + 'static
+    {
+        let shared = Rc::new(RefCell::new(Pending {
+            topology: Topology::default(),
+            remaining: 6,
+            error: None,
+            callback: Some(Box::new(callback)),
+        }));
+        gather(self, &shared, |t, cards: Vec<Card>| {
+            t.cards = cards.iter().map(CardNode::from).collect();
+        });
+        gather(self, &shared, |t, sinks: Vec<Sink>| {
+            t.sinks = sinks.iter().map(DeviceNode::from).collect();
+        });
+        gather(self, &shared, |t, sources: Vec<Source>| {
+            t.sources = sources.iter().map(DeviceNode::from).collect();
+        });
+        gather(self, &shared, |t, inputs: Vec<SinkInput>| {
+            t.sink_inputs = inputs.iter().map(StreamNode::from).collect();
+        });
+        gather(self, &shared, |t, outputs: Vec<SourceOutput>| {
+            t.source_outputs = outputs.iter().map(StreamNode::from).collect();
+        });
+        gather(self, &shared, |t, clients: Vec<Client>| {
+            t.clients = clients.iter().map(ClientNode::from).collect();
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dot_rendering() {
+        let topology = Topology {
+            cards: vec![CardNode { index: 0, ..Default::default() }],
+            sinks: vec![DeviceNode {
+                index: 1,
+                description: Some("Built-in \"Audio\"".into()),
+                card: Some(0),
+                monitor: Some(2),
+                ..Default::default()
+            }],
+            sources: vec![DeviceNode { index: 2, monitor: Some(1), ..Default::default() }],
+            sink_inputs: vec![StreamNode {
+                index: 7,
+                name: Some("Playback".into()),
+                client: Some(3),
+                device: 1,
+                corked: true,
+                ..Default::default()
+            }],
+            source_outputs: Vec::new(),
+            clients: vec![ClientNode { index: 3, name: Some("Music".into()) }],
+        };
+        let dot = topology.to_dot();
+        assert!(dot.starts_with("digraph pulseaudio {\n"));
+        assert!(dot.contains(r#"sink1 [label="sink #1\nBuilt-in \"Audio\"", shape=box];"#));
+        assert!(dot.contains(r#"label="sink input #7\nMusic: Playback", shape=ellipse"#));
+        assert!(dot.contains("shape=ellipse, color=grey, fontcolor=grey];\n"));
+        assert!(dot.contains("    card0 -> sink1;\n"));
+        assert!(dot.contains("    sink1 -> source2 [style=dashed];\n"));
+        assert!(dot.contains("    sink_input7 -> sink1;\n"));
+        assert!(dot.ends_with("}\n"));
+    }
+}
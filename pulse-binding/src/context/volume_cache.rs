@@ -0,0 +1,242 @@
+// Copyright 2017 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Cached sink and source volumes.
+//!
+//! # Overview
+//!
+//! Applications displaying volumes, such as mixers, may wish to read them far more frequently than
+//! they change, for instance once per frame. Rather than issuing an introspection request each
+//! time, a [`VolumeCache`] can be used. It holds the volume and mute state of each sink and source,
+//! served from memory, refreshing an entry only upon a subscription event indicating that it has
+//! changed.
+//!
+//! The cache must be given the subscription events, by calling [`VolumeCache::handle_event`] from
+//! the context’s subscribe callback, with the context subscribed to (at least)
+//! [`VolumeCache::INTEREST_MASK`]. Initially, and whenever necessary (e.g. after reconnection),
+//! [`VolumeCache::refresh_now`] fetches all entries afresh.
+//!
+//! ```rust,ignore
+//! use pulse::context::volume_cache::{VolumeCache, DeviceKind};
+//!
+//! let cache = Rc::new(VolumeCache::new(&context));
+//! cache.refresh_now();
+//!
+//! let cache_ref = Rc::clone(&cache);
+//! context.set_subscribe_callback(Some(Box::new(move |facility, operation, index| {
+//!     cache_ref.handle_event(facility, operation, index);
+//! })));
+//! context.subscribe(VolumeCache::INTEREST_MASK, |_| {});
+//!
+//! // Then, for each frame
+//! if let Some(entry) = cache.get(DeviceKind::Sink, sink_index) {
+//!     draw_slider(entry.volume.avg(), entry.mute);
+//! }
+//! ```
+//!
+//! [`VolumeCache`]: struct.VolumeCache.html
+//! [`VolumeCache::handle_event`]: struct.VolumeCache.html#method.handle_event
+//! [`VolumeCache::INTEREST_MASK`]: struct.VolumeCache.html#associatedconstant.INTEREST_MASK
+//! [`VolumeCache::refresh_now`]: struct.VolumeCache.html#method.refresh_now
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::{Rc, Weak};
+use callbacks::ListEntry;
+use error::PAErr;
+use volume::ChannelVolumes;
+use super::Context;
+use super::introspect::{Introspector, SinkInfo, SourceInfo};
+use super::subscribe::{Facility, InterestMaskSet, Operation, subscription_masks};
+
+/// Kind of device an entry belongs to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum DeviceKind {
+    /// A sink.
+    Sink,
+    /// A source.
+    Source,
+}
+
+/// A cached volume entry.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CachedVolume {
+    /// Volume of the device.
+    pub volume: ChannelVolumes,
+    /// Mute switch of the device.
+    pub mute: bool,
+}
+
+/// A cache of sink and source volumes, refreshed upon subscription events.
+///
+/// See the [module level documentation](index.html) for details.
+pub struct VolumeCache {
+    shared: Rc<RefCell<Shared>>,
+}
+
+/// State shared with the introspection callbacks.
+struct Shared {
+    introspector: Introspector,
+    entries: HashMap<(DeviceKind, u32), CachedVolume>,
+    /// Entries with a request in flight.
+    pending: HashSet<(DeviceKind, u32)>,
+    /// Entries changed whilst a request was in flight, thus needing requesting again.
+    dirty: HashSet<(DeviceKind, u32)>,
+}
+
+/// Create a list callback for refreshing all entries of a kind.
+macro_rules! refresh_cb {
+    ($weak:expr, $kind:expr, $info:ident) => {{
+        let weak: Weak<RefCell<Shared>> = $weak;
+        let mut seen = HashSet::new();
+        move |result: Result<ListEntry<&$info>, PAErr>| {
+            let shared = match weak.upgrade() {
+                Some(shared) => shared,
+                None => return,
+            };
+            let mut shared = shared.borrow_mut();
+            match result {
+                Ok(ListEntry::Item(i)) => {
+                    seen.insert(i.index);
+                    let entry = CachedVolume { volume: i.volume, mute: i.mute };
+                    shared.entries.insert(($kind, i.index), entry);
+                },
+                Ok(ListEntry::End) => {
+                    shared.entries.retain(|&(k, index), _| k != $kind || seen.contains(&index));
+                },
+                Err(_) => {},
+            }
+        }
+    }};
+}
+
+/// Create a callback for fetching a single entry.
+macro_rules! fetch_cb {
+    ($weak:expr, $kind:expr, $index:expr, $info:ident) => {{
+        let weak: Weak<RefCell<Shared>> = $weak;
+        let key = ($kind, $index);
+        move |result: Result<ListEntry<&$info>, PAErr>| {
+            let shared_rc = match weak.upgrade() {
+                Some(shared) => shared,
+                None => return,
+            };
+            let again = {
+                let mut shared = shared_rc.borrow_mut();
+                match result {
+                    Ok(ListEntry::Item(i)) => {
+                        let entry = CachedVolume { volume: i.volume, mute: i.mute };
+                        shared.entries.insert(key, entry);
+                        return;
+                    },
+                    Ok(ListEntry::End) => {},
+                    Err(_) => {
+                        // Most likely removed since the request was made
+                        shared.entries.remove(&key);
+                    },
+                }
+                shared.pending.remove(&key);
+                shared.dirty.remove(&key)
+            };
+            if again {
+                fetch(&shared_rc, key.0, key.1);
+            }
+        }
+    }};
+}
+
+/// Fetch a single entry, unless a request for it is already in flight, in which case it is marked
+/// for fetching again upon completion.
+fn fetch(shared_rc: &Rc<RefCell<Shared>>, kind: DeviceKind, index: u32) {
+    let mut shared = shared_rc.borrow_mut();
+    if !shared.pending.insert((kind, index)) {
+        shared.dirty.insert((kind, index));
+        return;
+    }
+    let weak = Rc::downgrade(shared_rc);
+    match kind {
+        DeviceKind::Sink => drop(shared.introspector.get_sink_info_by_index(index,
+            fetch_cb!(weak, DeviceKind::Sink, index, SinkInfo))),
+        DeviceKind::Source => drop(shared.introspector.get_source_info_by_index(index,
+            fetch_cb!(weak, DeviceKind::Source, index, SourceInfo))),
+    }
+}
+
+impl VolumeCache {
+    /// The subscription interest mask needed for keeping the cache up to date.
+    pub const INTEREST_MASK: InterestMaskSet =
+        subscription_masks::SINK | subscription_masks::SOURCE;
+
+    /// Create a new (empty) cache for the given context.
+    ///
+    /// Use [`refresh_now`](#method.refresh_now) to populate it, once the context is ready.
+    pub fn new(context: &Context) -> Self {
+        Self {
+            shared: Rc::new(RefCell::new(Shared {
+                introspector: context.introspect(),
+                entries: HashMap::new(),
+                pending: HashSet::new(),
+                dirty: HashSet::new(),
+            })),
+        }
+    }
+
+    /// Get the cached volume and mute state of a device, without contacting the server.
+    ///
+    /// Returns `None` if the device is unknown, or has not yet been fetched.
+    pub fn get(&self, kind: DeviceKind, index: u32) -> Option<CachedVolume> {
+        self.shared.borrow().entries.get(&(kind, index)).cloned()
+    }
+
+    /// Get the indices of all cached devices of the given kind.
+    pub fn indices(&self, kind: DeviceKind) -> Vec<u32> {
+        self.shared.borrow().entries.keys()
+            .filter(|&&(k, _)| k == kind)
+            .map(|&(_, index)| index)
+            .collect()
+    }
+
+    /// Handle a subscription event, as given to the context’s subscribe callback.
+    ///
+    /// Changed or new sinks and sources are fetched afresh, and removed ones dropped from the
+    /// cache. Other events are ignored.
+    pub fn handle_event(&self, facility: Option<Facility>, operation: Option<Operation>,
+        index: u32)
+    {
+        let kind = match facility {
+            Some(Facility::Sink) => DeviceKind::Sink,
+            Some(Facility::Source) => DeviceKind::Source,
+            _ => return,
+        };
+        match operation {
+            Some(Operation::Removed) => {
+                let mut shared = self.shared.borrow_mut();
+                shared.entries.remove(&(kind, index));
+                shared.dirty.remove(&(kind, index));
+            },
+            Some(_) => fetch(&self.shared, kind, index),
+            None => {},
+        }
+    }
+
+    /// Fetch all sinks and sources afresh, replacing the cached entries.
+    pub fn refresh_now(&self) {
+        let weak = Rc::downgrade(&self.shared);
+        let shared = self.shared.borrow();
+        drop(shared.introspector.get_sink_info_list(
+            refresh_cb!(weak.clone(), DeviceKind::Sink, SinkInfo)));
+        drop(shared.introspector.get_source_info_list(
+            refresh_cb!(weak, DeviceKind::Source, SourceInfo)));
+    }
+}
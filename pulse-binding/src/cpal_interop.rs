@@ -0,0 +1,143 @@
+// Copyright 2018 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Interoperation with the `cpal` crate.
+//!
+//! # Overview
+//!
+//! With the `cpal` feature enabled, a [`Spec`] and [`Map`] can be created from the [`StreamConfig`]
+//! and [`SampleFormat`] of a `cpal` stream, and vice versa, for applications using `cpal` for
+//! playback or capture whilst using this crate for control, or moving between the two:
+//!
+//! ```rust,ignore
+//! use pulse::sample::Spec;
+//! use pulse::channelmap::Map;
+//!
+//! let supported = device.default_output_config()?;
+//! let config = supported.config();
+//! let spec = Spec::try_from_cpal(&config, supported.sample_format()).expect("unsupported");
+//! let map = Map::from_cpal_config(&config);
+//!
+//! let (config, sample_format) = spec.to_cpal().expect("unsupported");
+//! ```
+//!
+//! `cpal` samples are in native endian byte order, thus only native endian (or single byte)
+//! formats have an equivalent; the signed 8 bit, unsigned 16 and 32 bit, and 64 bit formats of
+//! `cpal`, and the A-law, μ-law and 24 bit formats of PulseAudio, have none.
+//!
+//! [`Spec`]: ../sample/struct.Spec.html
+//! [`Map`]: ../channelmap/struct.Map.html
+//! [`StreamConfig`]: https://docs.rs/cpal/0.15/cpal/struct.StreamConfig.html
+//! [`SampleFormat`]: https://docs.rs/cpal/0.15/cpal/enum.SampleFormat.html
+
+use cpal::{BufferSize, SampleFormat, SampleRate, StreamConfig};
+use channelmap::{Map, MapDef};
+use sample::{Format, Spec, SAMPLE_FLOAT32NE, SAMPLE_S16NE, SAMPLE_S32NE};
+
+impl Format {
+    /// Get the sample format equivalent to a `cpal` sample format, if any.
+    pub fn from_cpal(format: SampleFormat) -> Option<Self> {
+        match format {
+            SampleFormat::U8 => Some(Format::U8),
+            SampleFormat::I16 => Some(SAMPLE_S16NE),
+            SampleFormat::I32 => Some(SAMPLE_S32NE),
+            SampleFormat::F32 => Some(SAMPLE_FLOAT32NE),
+            _ => None,
+        }
+    }
+
+    /// Get the `cpal` sample format equivalent to this sample format, if any.
+    pub fn to_cpal(self) -> Option<SampleFormat> {
+        match self {
+            Format::U8 => Some(SampleFormat::U8),
+            SAMPLE_S16NE => Some(SampleFormat::I16),
+            SAMPLE_S32NE => Some(SampleFormat::I32),
+            SAMPLE_FLOAT32NE => Some(SampleFormat::F32),
+            _ => None,
+        }
+    }
+}
+
+impl Spec {
+    /// Create a sample spec from the config and sample format of a `cpal` stream.
+    ///
+    /// Returns `None` if the sample format has no equivalent, or the rate or number of channels is
+    /// not supported.
+    pub fn try_from_cpal(config: &StreamConfig, format: SampleFormat) -> Option<Self> {
+        let format = Format::from_cpal(format)?;
+        let SampleRate(rate) = config.sample_rate;
+        if rate == 0 || rate > ::sample::RATE_MAX || config.channels == 0
+            || config.channels as usize > ::sample::CHANNELS_MAX
+        {
+            return None;
+        }
+        Some(Spec { format: format, rate: rate, channels: config.channels as u8 })
+    }
+
+    /// Get the config and sample format of a `cpal` stream for samples of this spec.
+    ///
+    /// The config is given the default buffer size. Returns `None` if the sample format has no
+    /// `cpal` equivalent.
+    pub fn to_cpal(&self) -> Option<(StreamConfig, SampleFormat)> {
+        let format = self.format.to_cpal()?;
+        let config = StreamConfig {
+            channels: self.channels as u16,
+            sample_rate: SampleRate(self.rate),
+            buffer_size: BufferSize::Default,
+        };
+        Some((config, format))
+    }
+}
+
+impl Map {
+    /// Create a channel map for the config of a `cpal` stream.
+    ///
+    /// `cpal` does not give the positions of channels; on Linux, where it uses ALSA, channels are
+    /// in the default order of ALSA, thus this gives the [`MapDef::ALSA`] mapping, synthesized
+    /// where ALSA has no default for the number of channels.
+    ///
+    /// Returns `None` if the number of channels is not supported.
+    ///
+    /// [`MapDef::ALSA`]: enum.MapDef.html#variant.ALSA
+    pub fn from_cpal_config(config: &StreamConfig) -> Option<Self> {
+        if config.channels == 0 {
+            return None;
+        }
+        Map::new_extend(config.channels as u32, MapDef::ALSA)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sample::SAMPLE_S24NE;
+
+    #[test]
+    fn cpal_conversion() {
+        let config = StreamConfig {
+            channels: 2,
+            sample_rate: SampleRate(48000),
+            buffer_size: BufferSize::Default,
+        };
+        let spec = Spec::try_from_cpal(&config, SampleFormat::F32).unwrap();
+        assert_eq!(spec, Spec { format: SAMPLE_FLOAT32NE, rate: 48000, channels: 2 });
+        assert_eq!(spec.to_cpal(), Some((config.clone(), SampleFormat::F32)));
+
+        assert_eq!(Spec::try_from_cpal(&config, SampleFormat::I8), None);
+        assert_eq!(Spec::try_from_cpal(&StreamConfig { channels: 0, ..config }, SampleFormat::U8),
+            None);
+        assert_eq!(Spec { format: SAMPLE_S24NE, ..spec }.to_cpal(), None);
+    }
+}
@@ -17,25 +17,267 @@
 
 use std;
 use capi;
+use std::convert::TryFrom;
 use std::os::raw::c_void;
+use std::time::Duration;
 use time::{Timeval, MicroSeconds};
 
 pub use capi::PA_INVALID_INDEX as INVALID_INDEX;
 pub use capi::pa_device_type_t as Device;
-pub use capi::pa_port_available_t as PortAvailable;
 
 pub type FreeCb = extern "C" fn(p: *mut c_void);
 
 pub type RetvalActual = i32;
 
+/// Port availability.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PortAvailable {
+    /// This port does not support jack detection.
+    Unknown,
+    /// This port is not available, likely because the jack is not plugged in.
+    No,
+    /// This port is available, likely because the jack is plugged in.
+    Yes,
+    /// A value not known to this binding, as may be introduced by newer versions of PulseAudio.
+    Other(i32),
+}
+
+impl From<PortAvailable> for capi::pa_port_available_t {
+    fn from(a: PortAvailable) -> Self {
+        match a {
+            PortAvailable::Unknown => capi::PA_PORT_AVAILABLE_UNKNOWN,
+            PortAvailable::No => capi::PA_PORT_AVAILABLE_NO,
+            PortAvailable::Yes => capi::PA_PORT_AVAILABLE_YES,
+            PortAvailable::Other(v) => capi::pa_port_available_t(v),
+        }
+    }
+}
+
+impl From<capi::pa_port_available_t> for PortAvailable {
+    fn from(a: capi::pa_port_available_t) -> Self {
+        match a {
+            capi::PA_PORT_AVAILABLE_UNKNOWN => PortAvailable::Unknown,
+            capi::PA_PORT_AVAILABLE_NO => PortAvailable::No,
+            capi::PA_PORT_AVAILABLE_YES => PortAvailable::Yes,
+            capi::pa_port_available_t(v) => PortAvailable::Other(v),
+        }
+    }
+}
+
+/// Strict conversion from a C value, failing with the value if it is not known to this binding.
+impl TryFrom<i32> for PortAvailable {
+    type Error = i32;
+
+    fn try_from(v: i32) -> Result<Self, i32> {
+        match PortAvailable::from(capi::pa_port_available_t(v)) {
+            PortAvailable::Other(v) => Err(v),
+            s => Ok(s),
+        }
+    }
+}
+
+/// Port type, as may be used for instance for choosing an icon for the port.
+///
+/// Requires PA version 14 (the `pa_v14_compatibility` feature).
+#[cfg(feature = "pa_v14_compatibility")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum DevicePortType {
+    /// Unknown type.
+    Unknown,
+    /// Auxiliary input or output.
+    Aux,
+    /// Speaker.
+    Speaker,
+    /// Headphones.
+    Headphones,
+    /// Line in or out.
+    Line,
+    /// Microphone.
+    Mic,
+    /// Headset, with both a microphone and headphones.
+    Headset,
+    /// Handset, as of a telephone.
+    Handset,
+    /// Earpiece, as of a mobile phone.
+    Earpiece,
+    /// S/PDIF connection.
+    SPDIF,
+    /// HDMI connection.
+    HDMI,
+    /// Television.
+    TV,
+    /// Radio.
+    Radio,
+    /// Video input.
+    Video,
+    /// USB device.
+    USB,
+    /// Bluetooth device.
+    Bluetooth,
+    /// Portable device.
+    Portable,
+    /// Hands-free device.
+    Handsfree,
+    /// Car audio system.
+    Car,
+    /// Hi-fi system.
+    HiFi,
+    /// Telephone.
+    Phone,
+    /// Network device.
+    Network,
+    /// Analog connection.
+    Analog,
+    /// A value not known to this binding, as may be introduced by newer versions of PulseAudio.
+    Other(u32),
+}
+
+#[cfg(feature = "pa_v14_compatibility")]
+impl From<DevicePortType> for capi::pa_device_port_type_t {
+    fn from(t: DevicePortType) -> Self {
+        match t {
+            DevicePortType::Unknown => capi::PA_DEVICE_PORT_TYPE_UNKNOWN,
+            DevicePortType::Aux => capi::PA_DEVICE_PORT_TYPE_AUX,
+            DevicePortType::Speaker => capi::PA_DEVICE_PORT_TYPE_SPEAKER,
+            DevicePortType::Headphones => capi::PA_DEVICE_PORT_TYPE_HEADPHONES,
+            DevicePortType::Line => capi::PA_DEVICE_PORT_TYPE_LINE,
+            DevicePortType::Mic => capi::PA_DEVICE_PORT_TYPE_MIC,
+            DevicePortType::Headset => capi::PA_DEVICE_PORT_TYPE_HEADSET,
+            DevicePortType::Handset => capi::PA_DEVICE_PORT_TYPE_HANDSET,
+            DevicePortType::Earpiece => capi::PA_DEVICE_PORT_TYPE_EARPIECE,
+            DevicePortType::SPDIF => capi::PA_DEVICE_PORT_TYPE_SPDIF,
+            DevicePortType::HDMI => capi::PA_DEVICE_PORT_TYPE_HDMI,
+            DevicePortType::TV => capi::PA_DEVICE_PORT_TYPE_TV,
+            DevicePortType::Radio => capi::PA_DEVICE_PORT_TYPE_RADIO,
+            DevicePortType::Video => capi::PA_DEVICE_PORT_TYPE_VIDEO,
+            DevicePortType::USB => capi::PA_DEVICE_PORT_TYPE_USB,
+            DevicePortType::Bluetooth => capi::PA_DEVICE_PORT_TYPE_BLUETOOTH,
+            DevicePortType::Portable => capi::PA_DEVICE_PORT_TYPE_PORTABLE,
+            DevicePortType::Handsfree => capi::PA_DEVICE_PORT_TYPE_HANDSFREE,
+            DevicePortType::Car => capi::PA_DEVICE_PORT_TYPE_CAR,
+            DevicePortType::HiFi => capi::PA_DEVICE_PORT_TYPE_HIFI,
+            DevicePortType::Phone => capi::PA_DEVICE_PORT_TYPE_PHONE,
+            DevicePortType::Network => capi::PA_DEVICE_PORT_TYPE_NETWORK,
+            DevicePortType::Analog => capi::PA_DEVICE_PORT_TYPE_ANALOG,
+            DevicePortType::Other(v) => capi::pa_device_port_type_t(v),
+        }
+    }
+}
+
+#[cfg(feature = "pa_v14_compatibility")]
+impl From<capi::pa_device_port_type_t> for DevicePortType {
+    fn from(t: capi::pa_device_port_type_t) -> Self {
+        match t {
+            capi::PA_DEVICE_PORT_TYPE_UNKNOWN => DevicePortType::Unknown,
+            capi::PA_DEVICE_PORT_TYPE_AUX => DevicePortType::Aux,
+            capi::PA_DEVICE_PORT_TYPE_SPEAKER => DevicePortType::Speaker,
+            capi::PA_DEVICE_PORT_TYPE_HEADPHONES => DevicePortType::Headphones,
+            capi::PA_DEVICE_PORT_TYPE_LINE => DevicePortType::Line,
+            capi::PA_DEVICE_PORT_TYPE_MIC => DevicePortType::Mic,
+            capi::PA_DEVICE_PORT_TYPE_HEADSET => DevicePortType::Headset,
+            capi::PA_DEVICE_PORT_TYPE_HANDSET => DevicePortType::Handset,
+            capi::PA_DEVICE_PORT_TYPE_EARPIECE => DevicePortType::Earpiece,
+            capi::PA_DEVICE_PORT_TYPE_SPDIF => DevicePortType::SPDIF,
+            capi::PA_DEVICE_PORT_TYPE_HDMI => DevicePortType::HDMI,
+            capi::PA_DEVICE_PORT_TYPE_TV => DevicePortType::TV,
+            capi::PA_DEVICE_PORT_TYPE_RADIO => DevicePortType::Radio,
+            capi::PA_DEVICE_PORT_TYPE_VIDEO => DevicePortType::Video,
+            capi::PA_DEVICE_PORT_TYPE_USB => DevicePortType::USB,
+            capi::PA_DEVICE_PORT_TYPE_BLUETOOTH => DevicePortType::Bluetooth,
+            capi::PA_DEVICE_PORT_TYPE_PORTABLE => DevicePortType::Portable,
+            capi::PA_DEVICE_PORT_TYPE_HANDSFREE => DevicePortType::Handsfree,
+            capi::PA_DEVICE_PORT_TYPE_CAR => DevicePortType::Car,
+            capi::PA_DEVICE_PORT_TYPE_HIFI => DevicePortType::HiFi,
+            capi::PA_DEVICE_PORT_TYPE_PHONE => DevicePortType::Phone,
+            capi::PA_DEVICE_PORT_TYPE_NETWORK => DevicePortType::Network,
+            capi::PA_DEVICE_PORT_TYPE_ANALOG => DevicePortType::Analog,
+            capi::pa_device_port_type_t(v) => DevicePortType::Other(v),
+        }
+    }
+}
+
+#[cfg(feature = "pa_v14_compatibility")]
+impl DevicePortType {
+    /// Get the name of a suitable icon for the port type, from the freedesktop.org icon naming
+    /// specification (or the extensions commonly provided by icon themes), falling back to
+    /// `audio-card`.
+    pub fn icon_name(&self) -> &'static str {
+        match *self {
+            DevicePortType::Speaker => "audio-speakers",
+            DevicePortType::Headphones => "audio-headphones",
+            DevicePortType::Headset
+            | DevicePortType::Handset
+            | DevicePortType::Earpiece
+            | DevicePortType::Handsfree => "audio-headset",
+            DevicePortType::Mic => "audio-input-microphone",
+            DevicePortType::HDMI | DevicePortType::TV | DevicePortType::Video => "video-display",
+            DevicePortType::Bluetooth => "bluetooth",
+            DevicePortType::Portable => "multimedia-player",
+            DevicePortType::Phone => "phone",
+            DevicePortType::Network => "network-wired",
+            _ => "audio-card",
+        }
+    }
+}
+
 /// A wrapper around integer ‘quit return values’ returned by PulseAudio.
 #[repr(C)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct Retval(pub RetvalActual);
 
+/// The reason a main loop exited, as an exit code given to its `quit` routine.
+///
+/// This allows an application to propagate an exit reason from within a callback, through the main
+/// loop, to its `main()`, for instance with [`Mainloop::quit_with`] and [`Mainloop::run_to_exit`]
+/// of the standard main loop. By convention, zero indicates success.
+///
+/// [`Mainloop::quit_with`]: ../mainloop/standard/struct.Mainloop.html#method.quit_with
+/// [`Mainloop::run_to_exit`]: ../mainloop/standard/struct.Mainloop.html#method.run_to_exit
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct LoopExit {
+    /// The exit code.
+    pub code: RetvalActual,
+}
+
+impl LoopExit {
+    /// Exit indicating success.
+    pub const SUCCESS: Self = LoopExit { code: 0 };
+
+    /// Create a new exit with the given code.
+    #[inline]
+    pub fn new(code: RetvalActual) -> Self {
+        LoopExit { code: code }
+    }
+
+    /// Returns `true` if the exit code indicates success (is zero).
+    #[inline]
+    pub fn is_success(&self) -> bool {
+        self.code == 0
+    }
+
+    /// Terminate the process with the exit code, as per `std::process::exit`.
+    pub fn exit_process(self) -> ! {
+        std::process::exit(self.code)
+    }
+}
+
+impl From<Retval> for LoopExit {
+    fn from(r: Retval) -> Self {
+        LoopExit { code: r.0 }
+    }
+}
+
+impl From<LoopExit> for Retval {
+    fn from(e: LoopExit) -> Self {
+        Retval(e.code)
+    }
+}
+
 /// Playback and record buffer metrics
+///
+/// The default has all attributes set to `std::u32::MAX`, leaving them for the server to choose.
 #[repr(C)]
-#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct BufferAttr {
     /// Maximum length of the buffer in bytes.
     ///
@@ -113,6 +355,234 @@ pub struct BufferAttr {
     pub fragsize: u32,
 }
 
+impl Default for BufferAttr {
+    fn default() -> Self {
+        Self {
+            maxlength: u32::MAX,
+            tlength: u32::MAX,
+            prebuf: u32::MAX,
+            minreq: u32::MAX,
+            fragsize: u32::MAX,
+        }
+    }
+}
+
+impl BufferAttr {
+    /// Create buffer metrics targeting the specified latency, for the specified sample spec.
+    ///
+    /// This sets `tlength` (for playback) and `fragsize` (for recording) to the number of bytes
+    /// corresponding to `latency`, leaving all other attributes as `std::u32::MAX`, for the server
+    /// to choose. Use together with [`stream::flags::ADJUST_LATENCY`] to configure the overall
+    /// latency.
+    ///
+    /// **Deprecated**: use [`for_playback_latency`] or [`for_low_latency_capture`] instead, for
+    /// the direction of the stream.
+    ///
+    /// [`stream::flags::ADJUST_LATENCY`]: ../stream/flags/constant.ADJUST_LATENCY.html
+    /// [`for_playback_latency`]: #method.for_playback_latency
+    /// [`for_low_latency_capture`]: #method.for_low_latency_capture
+    #[deprecated(since = "2.7.0", note="use `for_playback_latency` or `for_low_latency_capture` instead")]
+    pub fn new_for_latency(ss: &::sample::Spec, latency: Duration) -> Self {
+        let bytes = Self::to_bytes(latency, ss);
+        Self { tlength: bytes, fragsize: bytes, ..Self::default() }
+    }
+
+    /// Create buffer metrics for a playback stream targeting the specified latency, for the
+    /// specified sample spec.
+    ///
+    /// This sets `tlength` to the number of bytes corresponding to `latency`, leaving all other
+    /// attributes for the server to choose. Use together with [`stream::flags::ADJUST_LATENCY`] to
+    /// configure the overall latency.
+    ///
+    /// [`stream::flags::ADJUST_LATENCY`]: ../stream/flags/constant.ADJUST_LATENCY.html
+    pub fn for_playback_latency(ss: &::sample::Spec, latency: Duration) -> Self {
+        Self { tlength: Self::to_bytes(latency, ss), ..Self::default() }
+    }
+
+    /// Create buffer metrics for a record stream delivering data with the specified latency, for
+    /// the specified sample spec.
+    ///
+    /// This sets `fragsize` to the number of bytes corresponding to `latency`, leaving all other
+    /// attributes for the server to choose. Use together with [`stream::flags::ADJUST_LATENCY`] to
+    /// have the source latency adjusted accordingly.
+    ///
+    /// [`stream::flags::ADJUST_LATENCY`]: ../stream/flags/constant.ADJUST_LATENCY.html
+    pub fn for_low_latency_capture(ss: &::sample::Spec, latency: Duration) -> Self {
+        Self { fragsize: Self::to_bytes(latency, ss), ..Self::default() }
+    }
+
+    /// Get an object for displaying the attributes both in bytes and in milliseconds, for the
+    /// specified sample spec, e.g. `tlength=35280 (200.0 ms)`.
+    ///
+    /// This is useful for showing the attributes chosen by the server, as given by
+    /// [`stream::Stream::get_buffer_attr`].
+    ///
+    /// [`stream::Stream::get_buffer_attr`]: ../stream/struct.Stream.html#method.get_buffer_attr
+    pub fn display_with_spec<'a>(&'a self, ss: &'a ::sample::Spec) -> BufferAttrDisplay<'a> {
+        BufferAttrDisplay { attr: self, spec: Some(ss) }
+    }
+
+    /// Get the duration of `maxlength` for the specified sample spec, or `None` if left for the
+    /// server to choose (`std::u32::MAX`).
+    pub fn maxlength_duration(&self, ss: &::sample::Spec) -> Option<Duration> {
+        Self::to_duration(self.maxlength, ss)
+    }
+
+    /// Get the duration of `tlength` for the specified sample spec, or `None` if left for the
+    /// server to choose (`std::u32::MAX`).
+    pub fn tlength_duration(&self, ss: &::sample::Spec) -> Option<Duration> {
+        Self::to_duration(self.tlength, ss)
+    }
+
+    /// Get the duration of `prebuf` for the specified sample spec, or `None` if left for the
+    /// server to choose (`std::u32::MAX`).
+    pub fn prebuf_duration(&self, ss: &::sample::Spec) -> Option<Duration> {
+        Self::to_duration(self.prebuf, ss)
+    }
+
+    /// Get the duration of `minreq` for the specified sample spec, or `None` if left for the
+    /// server to choose (`std::u32::MAX`).
+    pub fn minreq_duration(&self, ss: &::sample::Spec) -> Option<Duration> {
+        Self::to_duration(self.minreq, ss)
+    }
+
+    /// Get the duration of `fragsize` for the specified sample spec, or `None` if left for the
+    /// server to choose (`std::u32::MAX`).
+    pub fn fragsize_duration(&self, ss: &::sample::Spec) -> Option<Duration> {
+        Self::to_duration(self.fragsize, ss)
+    }
+
+    fn to_duration(bytes: u32, ss: &::sample::Spec) -> Option<Duration> {
+        match bytes {
+            u32::MAX => None,
+            b => Some(ss.bytes_to_duration(b as u64)),
+        }
+    }
+
+    fn to_bytes(latency: Duration, ss: &::sample::Spec) -> u32 {
+        std::cmp::min(ss.duration_to_bytes(latency), (u32::MAX - 1) as usize) as u32
+    }
+}
+
+/// Shows each attribute in bytes, or `default` where left for the server to choose, e.g.
+/// `maxlength=default tlength=35280 prebuf=default minreq=default fragsize=default`.
+impl std::fmt::Display for BufferAttr {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        std::fmt::Display::fmt(&BufferAttrDisplay { attr: self, spec: None }, f)
+    }
+}
+
+/// Display of buffer metrics in bytes and milliseconds, as returned by
+/// [`BufferAttr::display_with_spec`](struct.BufferAttr.html#method.display_with_spec).
+#[derive(Debug, Copy, Clone)]
+pub struct BufferAttrDisplay<'a> {
+    attr: &'a BufferAttr,
+    spec: Option<&'a ::sample::Spec>,
+}
+
+impl<'a> std::fmt::Display for BufferAttrDisplay<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let fields = [
+            ("maxlength", self.attr.maxlength),
+            ("tlength", self.attr.tlength),
+            ("prebuf", self.attr.prebuf),
+            ("minreq", self.attr.minreq),
+            ("fragsize", self.attr.fragsize),
+        ];
+        for (i, &(name, bytes)) in fields.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            match (bytes, self.spec) {
+                (u32::MAX, _) => write!(f, "{}=default", name)?,
+                (_, None) => write!(f, "{}={}", name, bytes)?,
+                (_, Some(ss)) => {
+                    let ms = ss.bytes_to_duration(bytes as u64).as_micros() as f64 / 1000.0;
+                    write!(f, "{}={} ({:.1} ms)", name, bytes, ms)?
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sample::{Format, Spec};
+
+    const SPEC: Spec = Spec { format: Format::S16le, rate: 44100, channels: 2 };
+
+    #[test]
+    fn buffer_attr_default() {
+        let attr = BufferAttr::default();
+        assert_eq!(attr.tlength, u32::MAX);
+        assert_eq!(attr.tlength_duration(&SPEC), None);
+        assert_eq!(attr.to_string(),
+            "maxlength=default tlength=default prebuf=default minreq=default fragsize=default");
+    }
+
+    #[test]
+    fn buffer_attr_display() {
+        let attr = BufferAttr { tlength: 35280, minreq: 0, ..BufferAttr::default() };
+        assert_eq!(attr.to_string(),
+            "maxlength=default tlength=35280 prebuf=default minreq=0 fragsize=default");
+        assert_eq!(attr.display_with_spec(&SPEC).to_string(), "maxlength=default \
+            tlength=35280 (200.0 ms) prebuf=default minreq=0 (0.0 ms) fragsize=default");
+    }
+
+    #[test]
+    fn buffer_attr_for_latency() {
+        let latency = Duration::from_millis(200);
+        let playback = BufferAttr::for_playback_latency(&SPEC, latency);
+        assert_eq!((playback.tlength, playback.fragsize), (35280, u32::MAX));
+        assert_eq!(playback.tlength_duration(&SPEC), Some(latency));
+        let capture = BufferAttr::for_low_latency_capture(&SPEC, latency);
+        assert_eq!((capture.tlength, capture.fragsize), (u32::MAX, 35280));
+    }
+    #[test]
+    fn sink_state_conversion() {
+        for &value in &[SinkState::Invalid, SinkState::Running,
+            SinkState::Idle, SinkState::Suspended]
+        {
+            let raw = capi::pa_sink_state_t::from(value);
+            assert_eq!(SinkState::from(raw), value);
+            assert_eq!(SinkState::try_from(raw.0), Ok(value));
+        }
+        assert_eq!(SinkState::from(capi::pa_sink_state_t(42)), SinkState::Other(42));
+        assert_eq!(capi::pa_sink_state_t::from(SinkState::Other(42)), capi::pa_sink_state_t(42));
+        assert_eq!(SinkState::try_from(42), Err(42));
+    }
+
+    #[test]
+    fn source_state_conversion() {
+        for &value in &[SourceState::Invalid, SourceState::Running,
+            SourceState::Idle, SourceState::Suspended]
+        {
+            let raw = capi::pa_source_state_t::from(value);
+            assert_eq!(SourceState::from(raw), value);
+            assert_eq!(SourceState::try_from(raw.0), Ok(value));
+        }
+        assert_eq!(SourceState::from(capi::pa_source_state_t(42)), SourceState::Other(42));
+        assert_eq!(capi::pa_source_state_t::from(SourceState::Other(42)),
+            capi::pa_source_state_t(42));
+        assert_eq!(SourceState::try_from(42), Err(42));
+    }
+
+    #[test]
+    fn port_available_conversion() {
+        for &value in &[PortAvailable::Unknown, PortAvailable::No, PortAvailable::Yes] {
+            let raw = capi::pa_port_available_t::from(value);
+            assert_eq!(PortAvailable::from(raw), value);
+            assert_eq!(PortAvailable::try_from(raw.0), Ok(value));
+        }
+        assert_eq!(PortAvailable::from(capi::pa_port_available_t(42)), PortAvailable::Other(42));
+        assert_eq!(capi::pa_port_available_t::from(PortAvailable::Other(42)),
+            capi::pa_port_available_t(42));
+        assert_eq!(PortAvailable::try_from(42), Err(42));
+    }
+}
+
 /// A structure for all kinds of timing information of a stream.
 ///
 /// See [`stream::Stream::update_timing_info`] and [`stream::Stream::get_timing_info`].
@@ -220,28 +690,85 @@ pub struct TimingInfo {
     pub since_underrun: i64,
 }
 
-/// A structure for the spawn API.
+/// Spawn API hooks.
 ///
 /// This may be used to integrate auto spawned daemons into your application. For more information
 /// see [`context::Context::connect`]. When spawning a new child process the `waitpid()` is used on
 /// the child’s PID. The spawn routine will not block or ignore SIGCHLD signals, since this cannot
 /// be done in a thread compatible way. You might have to do this in prefork/postfork.
 ///
+/// The hooks are set with the builder style methods, for instance:
+///
+/// ```rust,ignore
+/// let api = SpawnApi::new()
+///     .prefork(|| block_sigchld())
+///     .postfork(|| unblock_sigchld());
+/// context.connect(None, pulse::context::flags::NOFLAGS, Some(&api)).unwrap();
+/// ```
+///
+/// Since the underlying C API does not support passing any user data to the hooks, the hooks of
+/// only one context can be registered at any one time. They remain registered until that context
+/// is dropped, with connecting another context with hooks failing until then.
+///
+/// **Note**: The `atfork` hook runs in the child process immediately after the fork. If other
+/// threads exist, only async-signal-safe operations may be performed within it; in particular it
+/// must not allocate memory, take locks (including those taken implicitly, such as by printing),
+/// or panic.
+///
 /// [`context::Context::connect`]: ../context/struct.Context.html#method.connect
-#[repr(C)]
-#[derive(Debug)]
+#[derive(Clone, Default)]
 pub struct SpawnApi {
-    /// Is called just before the fork in the parent process.
-    pub prefork: Option<extern "C" fn()>,
+    pub(crate) prefork: Option<SpawnHook>,
+    pub(crate) postfork: Option<SpawnHook>,
+    pub(crate) atfork: Option<SpawnHook>,
+}
+
+/// A spawn API hook.
+pub(crate) type SpawnHook = std::sync::Arc<dyn Fn() + Send + Sync + 'static>;
+
+impl SpawnApi {
+    /// Create a new instance, with no hooks set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the hook called just before the fork in the parent process.
+    pub fn prefork<F>(mut self, hook: F) -> Self
+        where F: Fn() + Send + Sync + 'static
+    {
+        self.prefork = Some(std::sync::Arc::new(hook));
+        self
+    }
 
-    /// Is called immediately after the fork in the parent process.
-    pub postfork: Option<extern "C" fn()>,
+    /// Set the hook called immediately after the fork in the parent process.
+    pub fn postfork<F>(mut self, hook: F) -> Self
+        where F: Fn() + Send + Sync + 'static
+    {
+        self.postfork = Some(std::sync::Arc::new(hook));
+        self
+    }
 
-    /// Is called immediately after the fork in the child process.
+    /// Set the hook called immediately after the fork in the child process.
     ///
     /// It is not safe to close all file descriptors in this function unconditionally, since a UNIX
-    /// socket (created using socketpair()) is passed to the new process.
-    pub atfork: Option<extern "C" fn()>,
+    /// socket (created using socketpair()) is passed to the new process. See also the note on
+    /// signal safety in the [type level documentation](struct.SpawnApi.html).
+    pub fn atfork<F>(mut self, hook: F) -> Self
+        where F: Fn() + Send + Sync + 'static
+    {
+        self.atfork = Some(std::sync::Arc::new(hook));
+        self
+    }
+}
+
+impl std::fmt::Debug for SpawnApi {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("SpawnApi")
+            .field("prefork", &self.prefork.is_some())
+            .field("postfork", &self.postfork.is_some())
+            .field("atfork", &self.atfork.is_some())
+            .finish()
+    }
 }
 
 pub type SinkFlagSet = capi::def::pa_sink_flags_t;
@@ -289,31 +816,57 @@ pub mod sink_flags {
     pub const SET_FORMATS: SinkFlagSet = capi::PA_SINK_SET_FORMATS;
 }
 
-#[repr(C)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum SinkState {
     /// This state is used when the server does not support sink state introspection.
-    Invalid = -1,
+    Invalid,
 
     /// Running, sink is playing and used by at least one non-corked sink-input.
-    Running = 0,
+    Running,
 
     /// When idle, the sink is playing but there is no non-corked sink-input attached to it.
-    Idle = 1,
+    Idle,
 
     /// When suspended, actual sink access can be closed, for instance.
-    Suspended = 2,
+    Suspended,
+
+    /// A state not known to this binding, as may be introduced by newer versions of PulseAudio.
+    Other(i32),
 }
 
 impl From<SinkState> for capi::pa_sink_state_t {
     fn from(s: SinkState) -> Self {
-        unsafe { std::mem::transmute(s) }
+        match s {
+            SinkState::Invalid => capi::PA_SINK_INVALID_STATE,
+            SinkState::Running => capi::PA_SINK_RUNNING,
+            SinkState::Idle => capi::PA_SINK_IDLE,
+            SinkState::Suspended => capi::PA_SINK_SUSPENDED,
+            SinkState::Other(v) => capi::pa_sink_state_t(v),
+        }
     }
 }
 
 impl From<capi::pa_sink_state_t> for SinkState {
     fn from(s: capi::pa_sink_state_t) -> Self {
-        unsafe { std::mem::transmute(s) }
+        match s {
+            capi::PA_SINK_INVALID_STATE => SinkState::Invalid,
+            capi::PA_SINK_RUNNING => SinkState::Running,
+            capi::PA_SINK_IDLE => SinkState::Idle,
+            capi::PA_SINK_SUSPENDED => SinkState::Suspended,
+            capi::pa_sink_state_t(v) => SinkState::Other(v),
+        }
+    }
+}
+
+/// Strict conversion from a C value, failing with the value if it is not known to this binding.
+impl TryFrom<i32> for SinkState {
+    type Error = i32;
+
+    fn try_from(v: i32) -> Result<Self, i32> {
+        match SinkState::from(capi::pa_sink_state_t(v)) {
+            SinkState::Other(v) => Err(v),
+            s => Ok(s),
+        }
     }
 }
 
@@ -374,31 +927,57 @@ pub mod source_flags {
 }
 
 /// Source state.
-#[repr(C)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum SourceState {
     /// This state is used when the server does not support source state introspection.
-    Invalid = -1,
+    Invalid,
 
     /// Running, source is recording and used by at least one non-corked source-output.
-    Running = 0,
+    Running,
 
     /// When idle, the source is still recording but there is no non-corked source-output.
-    Idle = 1,
+    Idle,
 
     /// When suspended, actual source access can be closed, for instance.
-    Suspended = 2,
+    Suspended,
+
+    /// A state not known to this binding, as may be introduced by newer versions of PulseAudio.
+    Other(i32),
 }
 
 impl From<SourceState> for capi::pa_source_state_t {
     fn from(s: SourceState) -> Self {
-        unsafe { std::mem::transmute(s) }
+        match s {
+            SourceState::Invalid => capi::PA_SOURCE_INVALID_STATE,
+            SourceState::Running => capi::PA_SOURCE_RUNNING,
+            SourceState::Idle => capi::PA_SOURCE_IDLE,
+            SourceState::Suspended => capi::PA_SOURCE_SUSPENDED,
+            SourceState::Other(v) => capi::pa_source_state_t(v),
+        }
     }
 }
 
 impl From<capi::pa_source_state_t> for SourceState {
     fn from(s: capi::pa_source_state_t) -> Self {
-        unsafe { std::mem::transmute(s) }
+        match s {
+            capi::PA_SOURCE_INVALID_STATE => SourceState::Invalid,
+            capi::PA_SOURCE_RUNNING => SourceState::Running,
+            capi::PA_SOURCE_IDLE => SourceState::Idle,
+            capi::PA_SOURCE_SUSPENDED => SourceState::Suspended,
+            capi::pa_source_state_t(v) => SourceState::Other(v),
+        }
+    }
+}
+
+/// Strict conversion from a C value, failing with the value if it is not known to this binding.
+impl TryFrom<i32> for SourceState {
+    type Error = i32;
+
+    fn try_from(v: i32) -> Result<Self, i32> {
+        match SourceState::from(capi::pa_source_state_t(v)) {
+            SourceState::Other(v) => Err(v),
+            s => Ok(s),
+        }
     }
 }
 
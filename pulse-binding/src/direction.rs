@@ -15,8 +15,10 @@
 
 //! Utility functions for Direction.
 
+use std;
 use capi;
 use std::ffi::CStr;
+use std::ops::{BitOr, BitOrAssign};
 
 pub type FlagSet = capi::direction::pa_direction_t;
 
@@ -28,6 +30,78 @@ pub mod flags {
     pub const INPUT: FlagSet = capi::PA_DIRECTION_INPUT;
 }
 
+/// A direction, being output, input, or both (bidirectional).
+///
+/// This wraps a direction [`FlagSet`](type.FlagSet.html). It may hold an invalid set of flags, if
+/// created from such, which can be checked with [`is_valid`](#method.is_valid).
+///
+/// Note that while nothing bidirectional is currently exposed by PulseAudio, one should test
+/// against the individual directions with [`is_output`](#method.is_output) and
+/// [`is_input`](#method.is_input), rather than comparing against a specific value, since that may
+/// change in future.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Direction(FlagSet);
+
+impl Direction {
+    /// Output direction.
+    pub const OUTPUT: Self = Direction(flags::OUTPUT);
+    /// Input direction.
+    pub const INPUT: Self = Direction(flags::INPUT);
+    /// Both output and input directions.
+    pub const BIDIRECTIONAL: Self = Direction(flags::OUTPUT | flags::INPUT);
+
+    /// Returns the underlying set of direction flags.
+    pub fn bits(self) -> FlagSet {
+        self.0
+    }
+
+    /// Returns whether direction is valid (either input, output or bidirectional).
+    pub fn is_valid(self) -> bool {
+        is_valid(self.0)
+    }
+
+    /// Returns whether the output direction is included.
+    pub fn is_output(self) -> bool {
+        self.0 & flags::OUTPUT != 0
+    }
+
+    /// Returns whether the input direction is included.
+    pub fn is_input(self) -> bool {
+        self.0 & flags::INPUT != 0
+    }
+}
+
+impl From<FlagSet> for Direction {
+    fn from(f: FlagSet) -> Self {
+        Direction(f)
+    }
+}
+
+impl From<Direction> for FlagSet {
+    fn from(d: Direction) -> Self {
+        d.0
+    }
+}
+
+impl BitOr for Direction {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Direction(self.0 | rhs.0)
+    }
+}
+impl BitOrAssign for Direction {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl std::fmt::Display for Direction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", to_string(self.0))
+    }
+}
+
 /// Returns whether direction is valid (either input, output or bidirectional).
 pub fn is_valid(f: FlagSet) -> bool {
     unsafe { capi::pa_direction_valid(f) != 0 }
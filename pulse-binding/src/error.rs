@@ -151,3 +151,108 @@ impl From<PAErr> for Code {
         }
     }
 }
+
+/// The kind of object an [`ObjectDead`](struct.ObjectDead.html) error refers to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ObjectKind {
+    Context,
+    Stream,
+    Operation,
+}
+
+/// Error given on using an object which is no longer usable: a context or stream which has been
+/// terminated (whether by disconnecting it, or upon failure), or an operation which has finished.
+///
+/// The C API unlinks such objects from their context, thus rather than calling into it, the
+/// binding gives this error.
+///
+/// In debug builds, where the object was released through the binding (by disconnecting or
+/// cancelling it), the location of that call is recorded, and included in the error, as well as
+/// being logged (with the `log` feature) when the error is given.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ObjectDead {
+    /// The kind of object.
+    pub kind: ObjectKind,
+    /// Where the object was released, if known (debug builds only).
+    pub released_at: Option<&'static std::panic::Location<'static>>,
+}
+
+impl std::fmt::Display for ObjectDead {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let kind = match self.kind {
+            ObjectKind::Context => "context",
+            ObjectKind::Stream => "stream",
+            ObjectKind::Operation => "operation",
+        };
+        match self.released_at {
+            Some(location) => write!(f, "The {} is dead, having been released at {}", kind,
+                location),
+            None => write!(f, "The {} is dead", kind),
+        }
+    }
+}
+
+impl std::error::Error for ObjectDead {}
+
+impl From<ObjectDead> for PAErr {
+    fn from(_: ObjectDead) -> Self {
+        PAErr::from(Code::BadState)
+    }
+}
+
+/// Where an object was released through the binding, recorded in debug builds only.
+#[derive(Debug, Default)]
+pub(crate) struct ReleaseSite {
+    #[cfg(debug_assertions)]
+    location: Option<&'static std::panic::Location<'static>>,
+}
+
+impl ReleaseSite {
+    /// Record the location of the caller of the (`#[track_caller]`) function calling this.
+    #[track_caller]
+    pub(crate) fn record(&mut self) {
+        #[cfg(debug_assertions)]
+        {
+            self.location = Some(std::panic::Location::caller());
+        }
+    }
+
+    /// Create the error for using the dead object of the given kind, logging it in debug builds.
+    pub(crate) fn dead(&self, kind: ObjectKind) -> ObjectDead {
+        #[cfg(debug_assertions)]
+        let released_at = self.location;
+        #[cfg(not(debug_assertions))]
+        let released_at = None;
+        let err = ObjectDead { kind, released_at };
+        #[cfg(debug_assertions)]
+        ::logging::object_dead(&err);
+        err
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn object_dead_release_site() {
+        let mut site = ReleaseSite::default();
+        let err = site.dead(ObjectKind::Stream);
+        assert_eq!(err.released_at, None);
+        assert_eq!(err.to_string(), "The stream is dead");
+
+        let line = line!() + 1;
+        site.record();
+        let err = site.dead(ObjectKind::Operation);
+        match cfg!(debug_assertions) {
+            true => {
+                let location = err.released_at.unwrap();
+                assert_eq!((location.file(), location.line()), (file!(), line));
+                assert_eq!(err.to_string(), format!("The operation is dead, having been released \
+                    at {}", location));
+            },
+            false => assert_eq!(err.released_at, None),
+        }
+        assert_eq!(PAErr::from(err), PAErr::from(Code::BadState));
+    }
+}
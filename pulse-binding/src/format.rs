@@ -34,9 +34,12 @@ use error::PAErr;
 pub use capi::pa_prop_type_t as PropType;
 
 /// Represents the type of encoding used in a stream or accepted by a sink.
+///
+/// New encodings may be added in future PA versions, hence this is marked `#[non_exhaustive]`.
 #[repr(C)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[allow(non_camel_case_types)]
+#[non_exhaustive]
 pub enum Encoding {
     /// Any encoding format, PCM or compressed.
     Any,
@@ -52,6 +55,16 @@ pub enum Encoding {
     DTS_IEC61937,
     /// MPEG-2 AAC data encapsulated in IEC 61937 header/padding.
     MPEG2_AAC_IEC61937,
+    /// Dolby TrueHD data encapsulated in IEC 61937 header/padding.
+    ///
+    /// Requires PA version 14 (the `pa_v14_compatibility` feature).
+    #[cfg(feature = "pa_v14_compatibility")]
+    TRUEHD_IEC61937,
+    /// DTS-HD Master Audio data encapsulated in IEC 61937 header/padding.
+    ///
+    /// Requires PA version 14 (the `pa_v14_compatibility` feature).
+    #[cfg(feature = "pa_v14_compatibility")]
+    DTSHD_IEC61937,
 
     /// Represents an invalid encoding.
     Invalid = -1,
@@ -75,6 +88,48 @@ impl Default for Encoding {
     }
 }
 
+impl std::fmt::Display for Encoding {
+    /// Formats the encoding as per [`Encoding::to_string`](#method.to_string), or as `invalid` if
+    /// it has no string representation.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match Encoding::to_string(*self) {
+            Some(s) => f.write_str(&s),
+            None => f.write_str("invalid"),
+        }
+    }
+}
+
+#[cfg(feature = "pa_v12_compatibility")]
+impl std::str::FromStr for Encoding {
+    type Err = PAErr;
+
+    /// Parses an encoding as per [`Encoding::from_string`](#method.from_string), failing for
+    /// unknown encodings.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Encoding::from_string(s) {
+            Encoding::Invalid => Err(PAErr::from(::error::Code::Invalid)),
+            e => Ok(e),
+        }
+    }
+}
+
+impl Encoding {
+    /// All valid encodings, in order of their numeric value.
+    pub const ALL: &'static [Encoding] = &[
+        Encoding::Any,
+        Encoding::PCM,
+        Encoding::AC3_IEC61937,
+        Encoding::EAC3_IEC61937,
+        Encoding::MPEG_IEC61937,
+        Encoding::DTS_IEC61937,
+        Encoding::MPEG2_AAC_IEC61937,
+        #[cfg(feature = "pa_v14_compatibility")]
+        Encoding::TRUEHD_IEC61937,
+        #[cfg(feature = "pa_v14_compatibility")]
+        Encoding::DTSHD_IEC61937,
+    ];
+}
+
 /// Represents the format of data provided in a stream or processed by a sink.
 pub struct Info {
     /// The actual C object
@@ -178,6 +233,43 @@ impl Info {
         Some(Self::from_raw(unsafe { std::mem::transmute(ptr) }))
     }
 
+    /// Utility function to create an `Info` for a compressed (non-PCM) encoding, as used for
+    /// passthrough of IEC 61937 encapsulated data (AC3, E-AC3, DTS, etc.) to an external decoder,
+    /// such as an AV receiver connected over HDMI or S/PDIF.
+    ///
+    /// The `rate` and `channels` parameters describe the IEC 61937 transport, i.e. the sample rate
+    /// and channel count of the PCM-like stream carrying the encapsulated data, not those of the
+    /// audio once decoded. For example, E-AC3 is normally carried at four times the rate of the
+    /// encoded audio. Either may be given as `None` to leave it unspecified, allowing the server to
+    /// choose.
+    ///
+    /// Such a format can be given to [`::stream::Stream::new_extended`], and the resulting stream
+    /// should then be connected with the [`::stream::flags::PASSTHROUGH`] flag.
+    ///
+    /// Returns `None` if `encoding` is not a compressed encoding (i.e. is PCM, `Any` or `Invalid`),
+    /// or on failure.
+    ///
+    /// [`::stream::Stream::new_extended`]: ../stream/struct.Stream.html#method.new_extended
+    /// [`::stream::flags::PASSTHROUGH`]: ../stream/flags/constant.PASSTHROUGH.html
+    pub fn new_encoded(encoding: Encoding, rate: Option<u32>, channels: Option<u8>)
+        -> Option<Self>
+    {
+        match encoding {
+            Encoding::Any | Encoding::PCM | Encoding::Invalid => return None,
+            _ => {},
+        }
+        let mut info = Self::new()?;
+        info.set_encoding(encoding);
+        if let Some(rate) = rate {
+            debug_assert!(rate <= i32::MAX as u32);
+            info.set_rate(rate as i32);
+        }
+        if let Some(channels) = channels {
+            info.set_channels(channels as u32);
+        }
+        Some(info)
+    }
+
     /// Create a new `Info` from an existing [`InfoInternal`](struct.InfoInternal.html) pointer.
     pub(crate) fn from_raw(ptr: *mut InfoInternal) -> Self {
         assert_eq!(false, ptr.is_null());
@@ -207,12 +299,12 @@ impl Info {
 
     /// Returns whether the `Info` structure is valid.
     pub fn is_valid(&self) -> bool {
-        unsafe { capi::pa_format_info_valid(std::mem::transmute(&self.ptr)) != 0 }
+        unsafe { capi::pa_format_info_valid(std::mem::transmute(self.ptr)) != 0 }
     }
 
     /// Returns whether the `Info` structure represents a PCM (i.e. uncompressed data) format.
     pub fn is_pcm(&self) -> bool {
-        unsafe { capi::pa_format_info_is_pcm(std::mem::transmute(&self.ptr)) != 0 }
+        unsafe { capi::pa_format_info_is_pcm(std::mem::transmute(self.ptr)) != 0 }
     }
 
     /// Returns whether the format represented by self is a subset of the format represented by
@@ -221,8 +313,22 @@ impl Info {
     /// compatible with a given sink. In such a case, self would be the sink’s format and `with`
     /// would be the streams.
     pub fn is_compatible_with(&self, with: &Self) -> bool {
-        unsafe { capi::pa_format_info_is_compatible(std::mem::transmute(&self.ptr),
-            std::mem::transmute(&with.ptr)) != 0 }
+        unsafe { capi::pa_format_info_is_compatible(std::mem::transmute(self.ptr),
+            std::mem::transmute(with.ptr)) != 0 }
+    }
+
+    /// Returns whether the given sample spec and channel map would be compatible with the format
+    /// represented by self, as with [`is_compatible_with`](#method.is_compatible_with), using
+    /// `Info` generated from them with [`new_from_sample_spec`](#method.new_from_sample_spec).
+    ///
+    /// Returns `false` if no `Info` could be generated from the given spec and map.
+    pub fn is_compatible_with_sample_spec(&self, ss: &::sample::Spec,
+        map: Option<&::channelmap::Map>) -> bool
+    {
+        match Self::new_from_sample_spec(ss, map) {
+            Some(with) => self.is_compatible_with(&with),
+            None => false,
+        }
     }
 
     /// Return a human-readable string representing the given format.
@@ -231,7 +337,7 @@ impl Info {
         let mut tmp = Vec::with_capacity(PRINT_MAX);
         unsafe {
             capi::pa_format_info_snprint(tmp.as_mut_ptr(), PRINT_MAX,
-                std::mem::transmute(&self.ptr));
+                std::mem::transmute(self.ptr));
             CStr::from_ptr(tmp.as_mut_ptr()).to_string_lossy().into_owned()
         }
     }
@@ -249,7 +355,7 @@ impl Info {
     pub fn to_sample_spec(&self, ss: &mut ::sample::Spec, map: &mut ::channelmap::Map)
         -> Result<(), PAErr>
     {
-        match unsafe { capi::pa_format_info_to_sample_spec(std::mem::transmute(&self.ptr),
+        match unsafe { capi::pa_format_info_to_sample_spec(std::mem::transmute(self.ptr),
             std::mem::transmute(ss), std::mem::transmute(map)) }
         {
             0 => Ok(()),
@@ -282,7 +388,7 @@ impl Info {
         // Warning: New CStrings will be immediately freed if not bound to a variable, leading to
         // as_ptr() giving dangling pointers!
         let c_key = CString::new(key.clone()).unwrap();
-        unsafe { capi::pa_format_info_get_prop_type(std::mem::transmute(&self.ptr), c_key.as_ptr()) }
+        unsafe { capi::pa_format_info_get_prop_type(std::mem::transmute(self.ptr), c_key.as_ptr()) }
     }
 
     /// Gets an integer property.
@@ -291,7 +397,7 @@ impl Info {
         // as_ptr() giving dangling pointers!
         let mut i: i32 = 0;
         let c_key = CString::new(key.clone()).unwrap();
-        match unsafe { capi::pa_format_info_get_prop_int(std::mem::transmute(&self.ptr),
+        match unsafe { capi::pa_format_info_get_prop_int(std::mem::transmute(self.ptr),
             c_key.as_ptr(), &mut i) }
         {
             0 => Ok(i),
@@ -306,7 +412,7 @@ impl Info {
         let mut min: i32 = 0;
         let mut max: i32 = 0;
         let c_key = CString::new(key.clone()).unwrap();
-        match unsafe { capi::pa_format_info_get_prop_int_range(std::mem::transmute(&self.ptr),
+        match unsafe { capi::pa_format_info_get_prop_int_range(std::mem::transmute(self.ptr),
             c_key.as_ptr(), &mut min, &mut max) }
         {
             0 => Ok((min, max)),
@@ -324,7 +430,7 @@ impl Info {
         let mut count: i32 = 0;
         let mut p_ints = null_mut::<i32>();
         let result = unsafe { capi::pa_format_info_get_prop_int_array(
-            std::mem::transmute(&self.ptr), c_key.as_ptr(), &mut p_ints, &mut count) };
+            std::mem::transmute(self.ptr), c_key.as_ptr(), &mut p_ints, &mut count) };
         if result != 0 {
             return None;
         }
@@ -345,7 +451,7 @@ impl Info {
         // as_ptr() giving dangling pointers!
         let c_key = CString::new(key.clone()).unwrap();
         let mut p_str = null_mut::<c_char>();
-        let result = unsafe { capi::pa_format_info_get_prop_string(std::mem::transmute(&self.ptr),
+        let result = unsafe { capi::pa_format_info_get_prop_string(std::mem::transmute(self.ptr),
             c_key.as_ptr(), &mut p_str) };
         if result != 0 || p_str.is_null() {
             return None;
@@ -365,7 +471,7 @@ impl Info {
         let mut count: i32 = 0;
         let mut pp_str = null_mut::<*mut c_char>();
         let result = unsafe { capi::pa_format_info_get_prop_string_array(
-            std::mem::transmute(&self.ptr), c_key.as_ptr(), &mut pp_str, &mut count) };
+            std::mem::transmute(self.ptr), c_key.as_ptr(), &mut pp_str, &mut count) };
         if result != 0 || pp_str.is_null() {
             return None;
         }
@@ -383,12 +489,55 @@ impl Info {
         Some(values)
     }
 
+    /// Gets the sample rate property.
+    ///
+    /// Returns an error if the property is not set, or is not a single integer value (for instance
+    /// if it is a range or list of values).
+    pub fn get_rate(&self) -> Result<u32, PAErr> {
+        match self.get_prop_int(::proplist::properties::FORMAT_RATE)? {
+            r if r < 0 => Err(PAErr::from(::error::Code::Invalid)),
+            r => Ok(r as u32),
+        }
+    }
+
+    /// Gets the channel count property.
+    ///
+    /// Returns an error if the property is not set, or is not a single valid channel count (for
+    /// instance if it is a range or list of values).
+    pub fn get_channels(&self) -> Result<u8, PAErr> {
+        match self.get_prop_int(::proplist::properties::FORMAT_CHANNELS)? {
+            c if c < 0 || c > ::sample::CHANNELS_MAX as i32 => {
+                Err(PAErr::from(::error::Code::Invalid))
+            },
+            c => Ok(c as u8),
+        }
+    }
+
+    /// Gets the sample format property.
+    ///
+    /// Returns `None` if the property is not set, or is not a single recognised sample format.
+    pub fn get_sample_format(&self) -> Option<::sample::Format> {
+        let value = self.get_prop_string(::proplist::properties::FORMAT_SAMPLE_FORMAT)?;
+        match ::sample::Format::parse(&value) {
+            ::sample::Format::Invalid => None,
+            f => Some(f),
+        }
+    }
+
+    /// Gets the channel map property.
+    ///
+    /// Returns `None` if the property is not set, or is not a single valid channel map.
+    pub fn get_channel_map(&self) -> Option<::channelmap::Map> {
+        let value = self.get_prop_string(::proplist::properties::FORMAT_CHANNEL_MAP)?;
+        ::channelmap::Map::new_from_string(&value).ok()
+    }
+
     /// Sets an integer property.
     pub fn set_prop_int(&mut self, key: &str, value: i32) {
         // Warning: New CStrings will be immediately freed if not bound to a variable, leading to
         // as_ptr() giving dangling pointers!
         let c_key = CString::new(key.clone()).unwrap();
-        unsafe { capi::pa_format_info_set_prop_int(std::mem::transmute(&self.ptr), c_key.as_ptr(),
+        unsafe { capi::pa_format_info_set_prop_int(std::mem::transmute(self.ptr), c_key.as_ptr(),
             value); }
     }
 
@@ -397,7 +546,7 @@ impl Info {
         // Warning: New CStrings will be immediately freed if not bound to a variable, leading to
         // as_ptr() giving dangling pointers!
         let c_key = CString::new(key.clone()).unwrap();
-        unsafe { capi::pa_format_info_set_prop_int_array(std::mem::transmute(&self.ptr),
+        unsafe { capi::pa_format_info_set_prop_int_array(std::mem::transmute(self.ptr),
             c_key.as_ptr(), values.as_ptr(), values.len() as i32); }
     }
 
@@ -406,7 +555,7 @@ impl Info {
         // Warning: New CStrings will be immediately freed if not bound to a variable, leading to
         // as_ptr() giving dangling pointers!
         let c_key = CString::new(key.clone()).unwrap();
-        unsafe { capi::pa_format_info_set_prop_int_range(std::mem::transmute(&self.ptr),
+        unsafe { capi::pa_format_info_set_prop_int_range(std::mem::transmute(self.ptr),
             c_key.as_ptr(), min, max); }
     }
 
@@ -416,7 +565,7 @@ impl Info {
         // as_ptr() giving dangling pointers!
         let c_key = CString::new(key.clone()).unwrap();
         let c_value = CString::new(value.clone()).unwrap();
-        unsafe { capi::pa_format_info_set_prop_string(std::mem::transmute(&self.ptr),
+        unsafe { capi::pa_format_info_set_prop_string(std::mem::transmute(self.ptr),
             c_key.as_ptr(), c_value.as_ptr()); }
     }
 
@@ -436,7 +585,7 @@ impl Info {
             c_value_ptrs.push(v.as_ptr());
         }
         unsafe {
-            capi::pa_format_info_set_prop_string_array(std::mem::transmute(&self.ptr),
+            capi::pa_format_info_set_prop_string_array(std::mem::transmute(self.ptr),
                 c_key.as_ptr(), c_value_ptrs.as_ptr(), c_value_ptrs.len() as i32);
         }
     }
@@ -447,7 +596,7 @@ impl Info {
     /// will select the stream sample format. In that case the stream sample format will most likely
     /// match the device sample format, meaning that sample format conversion will be avoided.
     pub fn set_sample_format(&mut self, sf: ::sample::Format) {
-        unsafe { capi::pa_format_info_set_sample_format(std::mem::transmute(&self.ptr),
+        unsafe { capi::pa_format_info_set_sample_format(std::mem::transmute(self.ptr),
             sf.into()); }
     }
 
@@ -457,7 +606,7 @@ impl Info {
     /// will select the stream sample rate. In that case the stream sample rate will most likely
     /// match the device sample rate, meaning that sample rate conversion will be avoided.
     pub fn set_rate(&mut self, rate: i32) {
-        unsafe { capi::pa_format_info_set_rate(std::mem::transmute(&self.ptr), rate) }
+        unsafe { capi::pa_format_info_set_rate(std::mem::transmute(self.ptr), rate) }
     }
 
     /// Convenience method to set the number of channels as a property.
@@ -467,7 +616,7 @@ impl Info {
     /// match the device channel count, meaning that up/downmixing will be avoided.
     pub fn set_channels(&mut self, channels: u32) {
         debug_assert!(channels <= std::i32::MAX as u32);
-        unsafe { capi::pa_format_info_set_channels(std::mem::transmute(&self.ptr), channels as i32) }
+        unsafe { capi::pa_format_info_set_channels(std::mem::transmute(self.ptr), channels as i32) }
     }
 
     /// Convenience method to set the channel map as a property.
@@ -476,7 +625,7 @@ impl Info {
     /// will select the stream channel map. In that case the stream channel map will most likely
     /// match the device channel map, meaning that remixing will be avoided.
     pub fn set_channel_map(&mut self, map: &::channelmap::Map) {
-        unsafe { capi::pa_format_info_set_channel_map(std::mem::transmute(&self.ptr),
+        unsafe { capi::pa_format_info_set_channel_map(std::mem::transmute(self.ptr),
             std::mem::transmute(map)) }
     }
 }
@@ -484,7 +633,7 @@ impl Info {
 impl Drop for Info {
     fn drop(&mut self) {
         if !self.weak {
-            unsafe { capi::pa_format_info_free(std::mem::transmute(&self.ptr)) };
+            unsafe { capi::pa_format_info_free(std::mem::transmute(self.ptr)) };
         }
     }
 }
@@ -493,8 +642,40 @@ impl Clone for Info {
     /// Returns a new `Info` struct and representing the same format. If this is called on a ‘weak’
     /// instance, a non-weak object is returned.
     fn clone(&self) -> Self {
-        let ptr = unsafe { capi::pa_format_info_copy(std::mem::transmute(&self.ptr)) };
+        let ptr = unsafe { capi::pa_format_info_copy(std::mem::transmute(self.ptr)) };
         assert_eq!(false, ptr.is_null());
         Self::from_raw(unsafe { std::mem::transmute(ptr) })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encoding_strings() {
+        for &e in Encoding::ALL {
+            // Exhaustive, such that new variants must be considered for `Encoding::ALL`
+            let name = match e {
+                Encoding::Any => "any",
+                Encoding::PCM => "pcm",
+                Encoding::AC3_IEC61937 => "ac3-iec61937",
+                Encoding::EAC3_IEC61937 => "eac3-iec61937",
+                Encoding::MPEG_IEC61937 => "mpeg-iec61937",
+                Encoding::DTS_IEC61937 => "dts-iec61937",
+                Encoding::MPEG2_AAC_IEC61937 => "mpeg2-aac-iec61937",
+                #[cfg(feature = "pa_v14_compatibility")]
+                Encoding::TRUEHD_IEC61937 => "truehd-iec61937",
+                #[cfg(feature = "pa_v14_compatibility")]
+                Encoding::DTSHD_IEC61937 => "dtshd-iec61937",
+                Encoding::Invalid => panic!("invalid encoding listed"),
+            };
+            assert_eq!(e.to_string(), name);
+            #[cfg(feature = "pa_v12_compatibility")]
+            assert_eq!(name.parse::<Encoding>(), Ok(e));
+        }
+        assert_eq!(Encoding::Invalid.to_string(), "invalid");
+        #[cfg(feature = "pa_v12_compatibility")]
+        assert!("bogus".parse::<Encoding>().is_err());
+    }
+}
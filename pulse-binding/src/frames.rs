@@ -0,0 +1,175 @@
+// Copyright 2018 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Typed frames, with samples of the `dasp_sample` crate.
+//!
+//! # Overview
+//!
+//! With the `dasp_sample` feature enabled, audio can be written to and read from streams as
+//! slices of frames, rather than of bytes. A frame is an array of samples, one per channel, of a
+//! type implementing [`FrameSample`], i.e. a [`dasp_sample::Sample`] having an equivalent
+//! [`Format`]: `u8`, `i16`, `i32` or `f32`, in native endian byte order.
+//!
+//! The number of channels is thus fixed at compile time by the frame type. Upon writing or reading,
+//! it is checked against the stream’s channel map, and the sample type against its sample spec,
+//! an error of [`Code::Invalid`] being given upon mismatch, rather than the data being
+//! misinterpreted.
+//!
+//! ```rust,ignore
+//! use pulse::sample::Spec;
+//!
+//! let spec = Spec::for_frame::<[f32; 2]>(44100);
+//! // (Create and connect the stream with this spec...)
+//!
+//! let frames: Vec<[f32; 2]> = render();
+//! stream.write_frames::<[f32; 2]>(&frames)?;
+//! ```
+//!
+//! Samples of other types can be converted with the [`dasp_sample::Sample`] methods, for instance
+//! `sample.to_sample::<f32>()`.
+//!
+//! [`FrameSample`]: trait.FrameSample.html
+//! [`dasp_sample::Sample`]: https://docs.rs/dasp_sample/0.11/dasp_sample/trait.Sample.html
+//! [`Format`]: ../sample/enum.Format.html
+//! [`Code::Invalid`]: ../error/enum.Code.html#variant.Invalid
+
+use std;
+use dasp_sample::Sample;
+use error::{Code, PAErr};
+use sample::{Format, Spec, SAMPLE_FLOAT32NE, SAMPLE_S16NE, SAMPLE_S32NE};
+use stream::{PeekResult, SeekMode, Stream};
+
+/// A sample type with an equivalent sample format.
+pub trait FrameSample: Sample + 'static {
+    /// The equivalent sample format.
+    const FORMAT: Format;
+}
+
+impl FrameSample for u8 {
+    const FORMAT: Format = Format::U8;
+}
+
+impl FrameSample for i16 {
+    const FORMAT: Format = SAMPLE_S16NE;
+}
+
+impl FrameSample for i32 {
+    const FORMAT: Format = SAMPLE_S32NE;
+}
+
+impl FrameSample for f32 {
+    const FORMAT: Format = SAMPLE_FLOAT32NE;
+}
+
+/// A frame, holding one sample per channel.
+///
+/// This is implemented for arrays of [`FrameSample`] types, e.g. `[f32; 2]` for stereo.
+///
+/// # Safety
+///
+/// Implementors must consist of exactly [`CHANNELS`] samples, with no padding, since frames are
+/// reinterpreted as bytes.
+///
+/// [`FrameSample`]: trait.FrameSample.html
+/// [`CHANNELS`]: #associatedconstant.CHANNELS
+pub unsafe trait Frame: Copy + 'static {
+    /// The type of each sample.
+    type Sample: FrameSample;
+    /// The number of channels.
+    const CHANNELS: usize;
+}
+
+unsafe impl<S: FrameSample, const N: usize> Frame for [S; N] {
+    type Sample = S;
+    const CHANNELS: usize = N;
+}
+
+impl Spec {
+    /// Create a sample spec for frames of the given type, at the given rate.
+    pub fn for_frame<F: Frame>(rate: u32) -> Self {
+        Spec { format: F::Sample::FORMAT, rate: rate, channels: F::CHANNELS as u8 }
+    }
+}
+
+impl Stream {
+    /// Check that frames of the given type match the stream’s sample spec and channel map.
+    fn check_frame<F: Frame>(&self) -> Result<(), PAErr> {
+        let spec = self.get_sample_spec_checked()?;
+        let map = self.get_channel_map_checked()?;
+        match spec.format == F::Sample::FORMAT && map.channels as usize == F::CHANNELS {
+            true => Ok(()),
+            false => Err(PAErr::from(Code::Invalid)),
+        }
+    }
+
+    /// Write frames to the stream, as with [`write`](#method.write) (with relative seeking, and the
+    /// data copied).
+    ///
+    /// Returns an error of [`Code::Invalid`] if the frame type does not match the stream’s sample
+    /// spec and channel map. See the [`frames`](../frames/index.html) module documentation.
+    ///
+    /// [`Code::Invalid`]: ../error/enum.Code.html#variant.Invalid
+    pub fn write_frames<F: Frame>(&mut self, frames: &[F]) -> Result<(), PAErr> {
+        self.check_frame::<F>()?;
+        let data = unsafe {
+            std::slice::from_raw_parts(frames.as_ptr() as *const u8, std::mem::size_of_val(frames))
+        };
+        self.write(data, None, 0, SeekMode::Relative)
+    }
+
+    /// Read the current fragment of a record stream as frames, appending them to `frames`, and
+    /// removing the fragment from the buffer, as with [`peek`](#method.peek) and
+    /// [`discard`](#method.discard).
+    ///
+    /// Returns the number of frames read, which is zero if the buffer is empty, or the fragment is
+    /// a hole (which is discarded). Returns an error of [`Code::Invalid`] if the frame type does
+    /// not match the stream’s sample spec and channel map.
+    ///
+    /// [`Code::Invalid`]: ../error/enum.Code.html#variant.Invalid
+    pub fn read_frames<F: Frame>(&mut self, frames: &mut Vec<F>) -> Result<usize, PAErr> {
+        self.check_frame::<F>()?;
+        let count = match self.peek()? {
+            PeekResult::Empty => return Ok(0),
+            PeekResult::Hole(_) => 0,
+            PeekResult::Data(data) => {
+                let count = data.len() / std::mem::size_of::<F>();
+                frames.reserve(count);
+                unsafe {
+                    // The data is not necessarily aligned for the frame type
+                    let dst = frames.as_mut_ptr().add(frames.len()) as *mut u8;
+                    std::ptr::copy_nonoverlapping(data.as_ptr(), dst,
+                        count * std::mem::size_of::<F>());
+                    frames.set_len(frames.len() + count);
+                }
+                count
+            },
+        };
+        self.discard()?;
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_spec() {
+        let spec = Spec::for_frame::<[f32; 2]>(44100);
+        assert_eq!(spec, Spec { format: SAMPLE_FLOAT32NE, rate: 44100, channels: 2 });
+        assert_eq!(Spec::for_frame::<[i16; 6]>(48000).channels, 6);
+        assert_eq!(<[u8; 1] as Frame>::Sample::FORMAT, Format::U8);
+    }
+}
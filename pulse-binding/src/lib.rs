@@ -51,6 +51,13 @@
 //! By using the built-in threaded main loop, it is possible to achieve a pseudo-synchronous API,
 //! which can be useful in synchronous applications where the simple API is insufficient.
 //!
+//! For simply playing or recording audio, the [`::player::Player`] and [`::recorder::Recorder`]
+//! types wrap up all of the necessary mainloop, context and stream handling.
+//!
+//! Application logic may be written against the traits of the [`::context::ops`] module rather
+//! than directly against the context, allowing it to be tested with the mock server of the
+//! `testing` module, available with the `testing` feature enabled.
+//!
 //! ## Threads
 //!
 //! The PulseAudio client libraries are not designed to be directly thread-safe. They are however
@@ -99,6 +106,11 @@
 //! * `PULSE_LOG_NO_RATE_LIMIT`: If defined, do not rate limit the logging output. Rate limiting
 //!   skips certain log messages when their frequency is considered too high.
 //!
+//! Separately, with the `log` feature enabled, the binding itself logs notable events, such as
+//! state transitions and operation failures, through the `log` crate facade. Likewise, with the
+//! `tracing` feature enabled, operations are covered by `tracing` spans and stream lifecycle events
+//! are emitted. See the [`::logging`] module for details.
+//!
 //! # Usage
 //!
 //! Firstly, add a dependency on the crate in your program’s `Cargo.toml` file. Secondly, import the
@@ -111,6 +123,10 @@
 //! See sub-modules for further information.
 //!
 //! [`::mainloop`]: mainloop/index.html
+//! [`::player::Player`]: player/struct.Player.html
+//! [`::logging`]: logging/index.html
+//! [`::context::ops`]: context/ops/index.html
+//! [`::recorder::Recorder`]: recorder/struct.Recorder.html
 //! [`::error::Code`]: error/enum.Code.html
 //! [`::context::Context::errno`]: context/struct.Context.html#method.errno
 //! [`::error::strerror`]: error/fn.strerror.html
@@ -120,22 +136,57 @@
 
 extern crate libc;
 extern crate libpulse_sys as capi;
+#[cfg(feature = "log")]
+#[macro_use]
+extern crate log;
+#[cfg(feature = "tracing")]
+extern crate tracing;
+#[cfg(feature = "async-io")]
+extern crate async_io;
+#[cfg(feature = "futures-core")]
+extern crate futures_core;
+#[cfg(feature = "ringbuf")]
+extern crate ringbuf;
+#[cfg(feature = "hound")]
+extern crate hound;
+#[cfg(feature = "cpal")]
+extern crate cpal;
+#[cfg(feature = "dasp_sample")]
+extern crate dasp_sample;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "serde_json")]
+extern crate serde_json;
 
+pub mod buffer_pool;
 pub mod callbacks;
 pub mod channelmap;
+#[cfg(feature = "cmd")]
+pub mod cmd;
 pub mod context;
+#[cfg(feature = "cpal")]
+pub mod cpal_interop;
 pub mod def;
 pub mod direction;
 pub mod error;
 pub mod format;
+#[cfg(feature = "dasp_sample")]
+pub mod frames;
+pub mod logging;
 pub mod mainloop;
 pub mod operation;
+pub mod player;
 pub mod proplist;
+pub mod recorder;
 pub mod sample;
 pub mod stream;
+#[cfg(feature = "testing")]
+pub mod testing;
 pub mod time;
 pub mod utf8;
 pub mod util;
 pub mod version;
 pub mod volume;
+#[cfg(feature = "hound")]
+pub mod wav;
 
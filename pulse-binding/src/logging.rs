@@ -0,0 +1,176 @@
+// Copyright 2017 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Logging through the `log` crate facade.
+//!
+//! # Overview
+//!
+//! With the `log` feature enabled, the binding logs notable events through the [`log`] facade,
+//! which can then be collected by whichever logger implementation the application uses:
+//!
+//! * Context state transitions, at `info` level (`warn` for failure, `debug` for intermediate
+//!   states), under the [`TARGET_CONTEXT`] target.
+//! * Operation failures, with the error string, at `warn` level, under the [`TARGET_OPERATION`]
+//!   target.
+//! * Stream state transitions (as for context ones), and stream underflows and overflows at `warn`
+//!   level, under the [`TARGET_STREAM`] target.
+//! * Subscription events, at `debug` level, under the [`TARGET_SUBSCRIBE`] target.
+//! * In debug builds, use of a dead context, stream or operation (see [`::error::ObjectDead`]),
+//!   along with where it was released, at `warn` level, under the target of the object.
+//!
+//! Each subsystem logs under its own target, allowing them to be controlled individually through
+//! the logger’s filtering, for instance with `env_logger`:
+//!
+//! ```text
+//! RUST_LOG=libpulse_binding::stream=warn,libpulse_binding::context=debug
+//! ```
+//!
+//! Note that events are observed through the callback proxies of the binding, thus events are
+//! logged only as they are delivered to a callback set by the application, e.g. context state
+//! transitions only once a state callback is set with [`Context::set_state_callback`].
+//!
+//! Without the `log` feature, nothing is logged, and this costs nothing.
+//!
+//! # Tracing
+//!
+//! Similarly, with the `tracing` feature enabled, stream lifecycle events (state transitions,
+//! underflows and overflows) are emitted as `tracing` events, under the [`TARGET_STREAM`] target,
+//! carrying the stream index and new state as fields. Operations are additionally covered by
+//! spans, see the [`::operation`] module.
+//!
+//! [`log`]: https://docs.rs/log
+//! [`TARGET_CONTEXT`]: constant.TARGET_CONTEXT.html
+//! [`TARGET_OPERATION`]: constant.TARGET_OPERATION.html
+//! [`TARGET_STREAM`]: constant.TARGET_STREAM.html
+//! [`TARGET_SUBSCRIBE`]: constant.TARGET_SUBSCRIBE.html
+//! [`Context::set_state_callback`]: ../context/struct.Context.html#method.set_state_callback
+//! [`::operation`]: ../operation/index.html
+//! [`::error::ObjectDead`]: ../error/struct.ObjectDead.html
+
+#![cfg_attr(not(all(feature = "log", feature = "tracing")), allow(unused_variables))]
+
+use capi;
+use capi::pa_context as ContextInternal;
+use capi::pa_stream as StreamInternal;
+
+/// Target used for logging context state transitions.
+pub const TARGET_CONTEXT: &str = "libpulse_binding::context";
+/// Target used for logging operation failures.
+pub const TARGET_OPERATION: &str = "libpulse_binding::operation";
+/// Target used for logging stream state transitions, underflows and overflows.
+pub const TARGET_STREAM: &str = "libpulse_binding::stream";
+/// Target used for logging subscription events.
+pub const TARGET_SUBSCRIBE: &str = "libpulse_binding::subscribe";
+
+/// Log the current state of a context.
+pub(crate) fn context_state(c: *mut ContextInternal) {
+    #[cfg(feature = "log")]
+    {
+        use context::State;
+        let state: State = unsafe { capi::pa_context_get_state(c).into() };
+        let level = match state {
+            State::Failed => ::log::Level::Warn,
+            State::Ready | State::Terminated => ::log::Level::Info,
+            _ => ::log::Level::Debug,
+        };
+        log!(target: TARGET_CONTEXT, level, "context state changed: {:?}", state);
+    }
+}
+
+/// Log failure of an operation on a context, if `success` is `false`.
+pub(crate) fn operation_result(c: *mut ContextInternal, success: bool) {
+    #[cfg(feature = "log")]
+    {
+        if !success && !c.is_null() {
+            let err = ::error::PAErr(unsafe { capi::pa_context_errno(c) });
+            warn!(target: TARGET_OPERATION, "operation failed: {}", err);
+        }
+    }
+}
+
+/// Log failure of an operation on a stream, if `success` is `false`.
+pub(crate) fn stream_operation_result(s: *mut StreamInternal, success: bool) {
+    if !success {
+        operation_result(unsafe { capi::pa_stream_get_context(s) }, success);
+    }
+}
+
+/// Log (and trace) the current state of a stream.
+pub(crate) fn stream_state(s: *mut StreamInternal) {
+    #[cfg(any(feature = "log", feature = "tracing"))]
+    let state: ::stream::State = unsafe { capi::pa_stream_get_state(s).into() };
+    #[cfg(feature = "log")]
+    {
+        use stream::State;
+        let level = match state {
+            State::Failed => ::log::Level::Warn,
+            State::Ready | State::Terminated => ::log::Level::Info,
+            _ => ::log::Level::Debug,
+        };
+        log!(target: TARGET_STREAM, level, "stream {} state changed: {:?}", stream_index(s), state);
+    }
+    #[cfg(feature = "tracing")]
+    ::tracing::info!(target: TARGET_STREAM, index = stream_index(s), state = ?state,
+        "stream state changed");
+}
+
+/// Log (and trace) an underflow of a stream.
+pub(crate) fn stream_underflow(s: *mut StreamInternal) {
+    #[cfg(feature = "log")]
+    warn!(target: TARGET_STREAM, "stream {} underflow", stream_index(s));
+    #[cfg(feature = "tracing")]
+    ::tracing::warn!(target: TARGET_STREAM, index = stream_index(s), "stream underflow");
+}
+
+/// Log (and trace) an overflow of a stream.
+pub(crate) fn stream_overflow(s: *mut StreamInternal) {
+    #[cfg(feature = "log")]
+    warn!(target: TARGET_STREAM, "stream {} overflow", stream_index(s));
+    #[cfg(feature = "tracing")]
+    ::tracing::warn!(target: TARGET_STREAM, index = stream_index(s), "stream overflow");
+}
+
+/// Log a subscription event.
+pub(crate) fn subscription_event(facility: Option<::context::subscribe::Facility>,
+    operation: Option<::context::subscribe::Operation>, index: u32)
+{
+    #[cfg(feature = "log")]
+    debug!(target: TARGET_SUBSCRIBE, "subscription event: {:?} {:?} index {}", facility, operation,
+        index);
+}
+
+/// Log use of a dead object, along with where it was released, if known.
+#[cfg(debug_assertions)]
+pub(crate) fn object_dead(err: &::error::ObjectDead) {
+    #[cfg(feature = "log")]
+    {
+        use error::ObjectKind;
+        let target = match err.kind {
+            ObjectKind::Context => TARGET_CONTEXT,
+            ObjectKind::Stream => TARGET_STREAM,
+            ObjectKind::Operation => TARGET_OPERATION,
+        };
+        warn!(target: target, "{}", err);
+    }
+}
+
+/// Get the index of a stream, for display, `-1` if not yet connected.
+#[cfg(any(feature = "log", feature = "tracing"))]
+fn stream_index(s: *mut StreamInternal) -> i64 {
+    match unsafe { capi::pa_stream_get_index(s) } {
+        ::def::INVALID_INDEX => -1,
+        i => i64::from(i),
+    }
+}
@@ -362,6 +362,18 @@ pub struct MainloopApi {
     pub quit: Option<extern "C" fn(a: *const MainloopApi, retval: ::def::RetvalActual)>,
 }
 
+impl MainloopApi {
+    /// Exit the main loop with the given exit code, as later returned from running it.
+    ///
+    /// Unlike the `quit` method of the main loop object, this only needs the API vtable, and thus
+    /// can be used from within callbacks dispatched whilst the main loop object is borrowed for
+    /// running it.
+    pub fn quit_with(&self, code: ::def::RetvalActual) {
+        let fn_ptr = self.quit.unwrap();
+        fn_ptr(self, code);
+    }
+}
+
 impl<'a> From<*const ApiInternal> for &'a MainloopApi {
     fn from(a: *const ApiInternal) -> Self {
         unsafe { std::mem::transmute(a) }
@@ -0,0 +1,270 @@
+// Copyright 2017 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! `async-io` based main loop backend (requires the `async-io` feature).
+//!
+//! # Overview
+//!
+//! This provides an [`EventLoop`] implementation, [`AsyncIoLoop`], using the reactor of the
+//! `async-io` crate (as used by smol and async-std), allowing lightweight async applications to
+//! drive PulseAudio from their own executor, without a separate thread or polling loop.
+//!
+//! The main loop is driven by the future returned from [`Mainloop::run`], which must be polled
+//! (e.g. spawned as a task on a single-threaded executor) for as long as PulseAudio is in use. It
+//! completes when PulseAudio requests that the main loop quit, giving the return value.
+//!
+//! ```rust,ignore
+//! use pulse::mainloop::async_io::AsyncIoMainloop;
+//!
+//! let mainloop = AsyncIoMainloop::new_async_io();
+//! let context = Context::new(&mainloop, "FooApp").unwrap();
+//!
+//! let ex = smol::LocalExecutor::new();
+//! ex.spawn(mainloop.run()).detach();
+//! ```
+//!
+//! [`EventLoop`]: ../custom/trait.EventLoop.html
+//! [`AsyncIoLoop`]: struct.AsyncIoLoop.html
+//! [`Mainloop::run`]: ../custom/struct.Mainloop.html#method.run
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::io;
+use std::os::unix::io::{AsFd, BorrowedFd, RawFd};
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
+use std::time::{Instant, SystemTime};
+use async_io::{Async, Timer};
+use def::Retval;
+use super::custom::{EventId, EventLoop, Mainloop};
+use super::events::io::{IoEventFlagSet, flags as io_flags};
+
+/// A main loop driven by the `async-io` reactor.
+pub type AsyncIoMainloop = Mainloop<AsyncIoLoop>;
+
+/// A file descriptor owned by PulseAudio, registered with the reactor (but not closed by it).
+struct FdSource(RawFd);
+
+impl AsFd for FdSource {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        // PulseAudio frees the IO event before closing the file descriptor
+        unsafe { BorrowedFd::borrow_raw(self.0) }
+    }
+}
+
+struct IoWatch {
+    source: Async<FdSource>,
+    events: IoEventFlagSet,
+}
+
+/// An [`EventLoop`](../custom/trait.EventLoop.html) using the `async-io` reactor.
+///
+/// See the [module level documentation](index.html) for details.
+#[derive(Default)]
+pub struct AsyncIoLoop {
+    io: HashMap<EventId, IoWatch>,
+    timers: HashMap<EventId, SystemTime>,
+    wakeup: bool,
+    quit: Option<Retval>,
+    waker: Option<Waker>,
+}
+
+impl AsyncIoLoop {
+    /// Create a new instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wake the task driving the main loop, such that changes are picked up.
+    fn wake(&mut self) {
+        if let Some(ref waker) = self.waker {
+            waker.wake_by_ref();
+        }
+    }
+}
+
+impl EventLoop for AsyncIoLoop {
+    fn register_io(&mut self, id: EventId, fd: i32, events: IoEventFlagSet) {
+        if let Some(watch) = self.io.get_mut(&id) {
+            watch.events = events;
+            self.wake();
+            return;
+        }
+        match Async::new(FdSource(fd)) {
+            Ok(source) => {
+                self.io.insert(id, IoWatch { source, events });
+            },
+            Err(_) => {
+                // Nothing better to do than to let PulseAudio find out upon using the descriptor
+            },
+        }
+        self.wake();
+    }
+
+    fn deregister_io(&mut self, id: EventId) {
+        self.io.remove(&id);
+    }
+
+    fn set_timer(&mut self, id: EventId, deadline: Option<SystemTime>) {
+        match deadline {
+            Some(deadline) => { self.timers.insert(id, deadline); },
+            None => { self.timers.remove(&id); },
+        }
+        self.wake();
+    }
+
+    fn deregister_timer(&mut self, id: EventId) {
+        self.timers.remove(&id);
+    }
+
+    fn wakeup(&mut self) {
+        self.wakeup = true;
+        self.wake();
+    }
+
+    fn quit(&mut self, retval: Retval) {
+        self.quit = Some(retval);
+        self.wake();
+    }
+}
+
+/// Future driving a main loop, as returned by [`Mainloop::run`].
+///
+/// [`Mainloop::run`]: ../custom/struct.Mainloop.html#method.run
+pub struct Run<'a> {
+    mainloop: &'a AsyncIoMainloop,
+    timer: Timer,
+}
+
+impl Mainloop<AsyncIoLoop> {
+    /// Create a new main loop driven by the `async-io` reactor.
+    pub fn new_async_io() -> Self {
+        Self::new(AsyncIoLoop::new())
+    }
+
+    /// Get a future driving the main loop, which completes when PulseAudio requests that the main
+    /// loop quit, giving the return value.
+    ///
+    /// Only one such future should be polled at a time.
+    pub fn run(&self) -> Run<'_> {
+        Run { mainloop: self, timer: Timer::never() }
+    }
+}
+
+/// Convert a readiness poll result to the IO events it indicates.
+fn poll_events(result: Poll<io::Result<()>>, events: IoEventFlagSet) -> IoEventFlagSet {
+    match result {
+        Poll::Ready(Ok(())) => events,
+        Poll::Ready(Err(_)) => io_flags::ERROR,
+        Poll::Pending => io_flags::NULL,
+    }
+}
+
+impl<'a> Future for Run<'a> {
+    type Output = Retval;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Retval> {
+        let this = self.get_mut();
+        let mainloop = this.mainloop;
+
+        // Gather what needs dispatching, without holding the event loop borrowed whilst dispatching
+        let (deferred, ready_io, expired) = {
+            let mut event_loop = mainloop.event_loop();
+            event_loop.waker = Some(cx.waker().clone());
+            if let Some(retval) = event_loop.quit.take() {
+                return Poll::Ready(retval);
+            }
+
+            let deferred = event_loop.wakeup;
+            event_loop.wakeup = false;
+
+            let mut ready_io = Vec::new();
+            for (id, watch) in event_loop.io.iter() {
+                let mut events = io_flags::NULL;
+                if watch.events & io_flags::INPUT != 0 {
+                    events |= poll_events(watch.source.poll_readable(cx), io_flags::INPUT);
+                }
+                if watch.events & io_flags::OUTPUT != 0 {
+                    events |= poll_events(watch.source.poll_writable(cx), io_flags::OUTPUT);
+                }
+                if events != io_flags::NULL {
+                    ready_io.push((*id, events));
+                }
+            }
+
+            let now = SystemTime::now();
+            let expired: Vec<EventId> = event_loop.timers.iter()
+                .filter(|&(_, deadline)| *deadline <= now)
+                .map(|(id, _)| *id)
+                .collect();
+            (deferred, ready_io, expired)
+        };
+
+        let mut dispatched = false;
+        if deferred || mainloop.has_deferred() {
+            dispatched |= mainloop.dispatch_deferred() > 0;
+        }
+        for (id, events) in ready_io {
+            dispatched |= mainloop.dispatch_io(id, events);
+        }
+        for id in expired {
+            dispatched |= mainloop.dispatch_timer(id);
+        }
+
+        let (quit, next_deadline) = {
+            let event_loop = mainloop.event_loop();
+            (event_loop.quit.is_some(), event_loop.timers.values().min().cloned())
+        };
+        if dispatched || quit || mainloop.has_deferred() {
+            // Yield to other tasks before continuing
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+
+        if let Some(deadline) = next_deadline {
+            let delay = deadline.duration_since(SystemTime::now()).unwrap_or_default();
+            this.timer.set_at(Instant::now() + delay);
+            if Pin::new(&mut this.timer).poll(cx).is_ready() {
+                cx.waker().wake_by_ref();
+            }
+        }
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mainloop::api::Mainloop as MainloopTrait;
+
+    #[test]
+    fn io_event_and_quit() {
+        let mut fds = [0; 2];
+        assert_eq!(unsafe { ::libc::pipe(fds.as_mut_ptr()) }, 0);
+
+        let mut mainloop = AsyncIoMainloop::new_async_io();
+        let api = mainloop.get_api();
+        let _event = mainloop.new_io_event(fds[0], io_flags::INPUT, Box::new(move |_, fd, events| {
+            assert_eq!(events, io_flags::INPUT);
+            let mut buf = [0u8; 1];
+            unsafe { ::libc::read(fd, buf.as_mut_ptr() as *mut _, 1) };
+            (api.quit.unwrap())(api, i32::from(buf[0]));
+        })).unwrap();
+
+        assert_eq!(unsafe { ::libc::write(fds[1], [7u8].as_ptr() as *const _, 1) }, 1);
+        assert_eq!(::async_io::block_on(mainloop.run()), Retval(7));
+        unsafe { ::libc::close(fds[0]); ::libc::close(fds[1]); }
+    }
+}
@@ -0,0 +1,707 @@
+// Copyright 2017 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Main loop implementation driven by a custom event loop.
+//!
+//! # Overview
+//!
+//! This allows PulseAudio to be integrated with any event loop or reactor (mio, smol, a custom
+//! epoll loop, etc.), by implementing the single [`EventLoop`] trait, rather than having to
+//! construct a [`MainloopApi`] vtable of raw function pointers.
+//!
+//! The [`Mainloop`] type here builds the vtable, and keeps track of the event sources PulseAudio
+//! creates through it. It informs the [`EventLoop`] implementation of the file descriptors and
+//! timers it needs to watch, each identified by an [`EventId`], and of when deferred events are
+//! pending. The event loop in turn reports back readiness and expiry through the
+//! [`Mainloop::dispatch_io`], [`Mainloop::dispatch_timer`] and [`Mainloop::dispatch_deferred`]
+//! methods, which run the PulseAudio callbacks.
+//!
+//! All of this happens on a single thread. The [`EventLoop`] methods are called whilst PulseAudio
+//! is working with the main loop, and so must not call back into PulseAudio (including the dispatch
+//! methods) themselves; they should just record the change.
+//!
+//! Timer deadlines are given as wall clock times (`SystemTime`). Timer events fire only once per
+//! deadline; PulseAudio restarts them as required.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use pulse::mainloop::custom::{EventLoop, EventId, Mainloop};
+//!
+//! struct MyLoop { /* ... */ }
+//!
+//! impl EventLoop for MyLoop {
+//!     fn register_io(&mut self, id: EventId, fd: i32, events: IoEventFlagSet) { /* ... */ }
+//!     fn deregister_io(&mut self, id: EventId) { /* ... */ }
+//!     fn set_timer(&mut self, id: EventId, deadline: Option<SystemTime>) { /* ... */ }
+//!     fn deregister_timer(&mut self, id: EventId) { /* ... */ }
+//!     fn wakeup(&mut self) { /* ... */ }
+//! }
+//!
+//! let mainloop = Mainloop::new(MyLoop { /* ... */ });
+//! let context = Context::new(&mainloop, "FooApp").unwrap();
+//!
+//! // Within the event loop, upon readiness of a file descriptor registered with `id`:
+//! mainloop.dispatch_io(id, events);
+//! ```
+//!
+//! [`EventLoop`]: trait.EventLoop.html
+//! [`EventId`]: struct.EventId.html
+//! [`Mainloop`]: struct.Mainloop.html
+//! [`MainloopApi`]: ../api/struct.MainloopApi.html
+//! [`Mainloop::dispatch_io`]: struct.Mainloop.html#method.dispatch_io
+//! [`Mainloop::dispatch_timer`]: struct.Mainloop.html#method.dispatch_timer
+//! [`Mainloop::dispatch_deferred`]: struct.Mainloop.html#method.dispatch_deferred
+
+use std;
+use std::cell::{Cell, RefCell, RefMut};
+use std::collections::HashMap;
+use std::os::raw::c_void;
+use std::ptr::{null, null_mut};
+use std::rc::Rc;
+use std::time::SystemTime;
+use libc::timeval;
+use def::{Retval, RetvalActual};
+use time::Timeval;
+use super::api::{MainloopApi, MainloopInner, MainloopInternalType, IoEventCb, IoEventDestroyCb,
+    TimeEventCb, TimeEventDestroyCb, DeferEventCb, DeferEventDestroyCb};
+use super::events::io::{IoEventFlagSet, IoEventInternal};
+use super::events::timer::TimeEventInternal;
+use super::events::deferred::DeferEventInternal;
+
+/// Identifies an IO or timer event source registered with an [`EventLoop`](trait.EventLoop.html).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EventId(pub u64);
+
+/// An event loop capable of driving PulseAudio.
+///
+/// See the [module level documentation](index.html) for details.
+pub trait EventLoop {
+    /// Start watching file descriptor `fd` for the given events, or change the events watched if
+    /// `id` is already registered. An empty set of events means not to watch for any for now.
+    ///
+    /// Upon readiness, [`Mainloop::dispatch_io`] should be called.
+    ///
+    /// [`Mainloop::dispatch_io`]: struct.Mainloop.html#method.dispatch_io
+    fn register_io(&mut self, id: EventId, fd: i32, events: IoEventFlagSet);
+
+    /// Stop watching the file descriptor registered with `id`.
+    fn deregister_io(&mut self, id: EventId);
+
+    /// Set the deadline of a timer, adding it if `id` is not yet known. `None` means disabled.
+    ///
+    /// Upon the deadline passing, [`Mainloop::dispatch_timer`] should be called.
+    ///
+    /// [`Mainloop::dispatch_timer`]: struct.Mainloop.html#method.dispatch_timer
+    fn set_timer(&mut self, id: EventId, deadline: Option<SystemTime>);
+
+    /// Remove the timer with `id`.
+    fn deregister_timer(&mut self, id: EventId);
+
+    /// Deferred events are pending, and [`Mainloop::dispatch_deferred`] should be called as soon as
+    /// possible (and for as long as [`Mainloop::has_deferred`] returns `true`).
+    ///
+    /// [`Mainloop::dispatch_deferred`]: struct.Mainloop.html#method.dispatch_deferred
+    /// [`Mainloop::has_deferred`]: struct.Mainloop.html#method.has_deferred
+    fn wakeup(&mut self);
+
+    /// PulseAudio requested that the main loop quit, with the given return value.
+    ///
+    /// By default, this does nothing.
+    fn quit(&mut self, _retval: Retval) {}
+}
+
+struct IoEntry<L: EventLoop> {
+    owner: *mut MainloopInternal<L>,
+    id: EventId,
+    fd: i32,
+    dead: bool,
+    cb: Option<IoEventCb>,
+    destroy_cb: Option<IoEventDestroyCb>,
+    userdata: *mut c_void,
+}
+
+struct TimeEntry<L: EventLoop> {
+    owner: *mut MainloopInternal<L>,
+    id: EventId,
+    deadline: Option<timeval>,
+    dead: bool,
+    cb: Option<TimeEventCb>,
+    destroy_cb: Option<TimeEventDestroyCb>,
+    userdata: *mut c_void,
+}
+
+struct DeferEntry<L: EventLoop> {
+    owner: *mut MainloopInternal<L>,
+    enabled: bool,
+    dead: bool,
+    cb: Option<DeferEventCb>,
+    destroy_cb: Option<DeferEventDestroyCb>,
+    userdata: *mut c_void,
+}
+
+/// An event source pending destruction.
+enum Dead<L: EventLoop> {
+    Io(*mut IoEntry<L>),
+    Time(*mut TimeEntry<L>),
+    Defer(*mut DeferEntry<L>),
+}
+
+/// The main loop state, pointed to by the API vtable’s `userdata`, and by all event sources.
+pub struct MainloopInternal<L: EventLoop> {
+    api: MainloopApi,
+    event_loop: RefCell<L>,
+    next_id: Cell<u64>,
+    io: RefCell<HashMap<EventId, *mut IoEntry<L>>>,
+    timers: RefCell<HashMap<EventId, *mut TimeEntry<L>>>,
+    deferred: RefCell<Vec<*mut DeferEntry<L>>>,
+    /// Event sources freed whilst dispatching, destroyed once dispatching is complete
+    dead: RefCell<Vec<Dead<L>>>,
+    /// Depth of dispatch calls in progress
+    dispatching: Cell<u32>,
+}
+
+impl<L: EventLoop> MainloopInternalType for MainloopInternal<L> {}
+
+/// A main loop driven by a custom event loop.
+///
+/// See the [module level documentation](index.html) for details.
+pub struct Mainloop<L: EventLoop> {
+    /// The ref-counted inner data
+    pub _inner: Rc<MainloopInner<MainloopInternal<L>>>,
+}
+
+impl<L: EventLoop> super::api::Mainloop for Mainloop<L> {
+    type MI = MainloopInner<MainloopInternal<L>>;
+
+    fn inner(&self) -> Rc<MainloopInner<MainloopInternal<L>>> {
+        Rc::clone(&self._inner)
+    }
+}
+
+impl<L: EventLoop> super::signal::MainloopSignals for Mainloop<L> {}
+
+fn drop_actual<L: EventLoop>(self_: &mut MainloopInner<MainloopInternal<L>>) {
+    let internal = unsafe { Box::from_raw(self_.ptr) };
+    let io: Vec<_> = internal.io.borrow_mut().drain().map(|(_, e)| Dead::Io(e)).collect();
+    let timers: Vec<_> = internal.timers.borrow_mut().drain().map(|(_, e)| Dead::Time(e)).collect();
+    let deferred: Vec<_> = internal.deferred.borrow_mut().drain(..).map(Dead::Defer).collect();
+    internal.dead.borrow_mut().extend(io.into_iter().chain(timers).chain(deferred));
+    internal.destroy_dead();
+    drop(internal);
+    self_.ptr = null_mut::<MainloopInternal<L>>();
+    self_.api = null::<MainloopApi>();
+}
+
+impl<L: EventLoop + 'static> Mainloop<L> {
+    /// Create a new main loop driven by the given event loop.
+    pub fn new(event_loop: L) -> Self {
+        let api = MainloopApi {
+            userdata: null_mut(),
+            io_new: Some(io_new::<L>),
+            io_enable: Some(io_enable::<L>),
+            io_free: Some(io_free::<L>),
+            io_set_destroy: Some(io_set_destroy::<L>),
+            time_new: Some(time_new::<L>),
+            time_restart: Some(time_restart::<L>),
+            time_free: Some(time_free::<L>),
+            time_set_destroy: Some(time_set_destroy::<L>),
+            defer_new: Some(defer_new::<L>),
+            defer_enable: Some(defer_enable::<L>),
+            defer_free: Some(defer_free::<L>),
+            defer_set_destroy: Some(defer_set_destroy::<L>),
+            quit: Some(quit::<L>),
+        };
+        let internal = Box::into_raw(Box::new(MainloopInternal {
+            api,
+            event_loop: RefCell::new(event_loop),
+            next_id: Cell::new(0),
+            io: RefCell::new(HashMap::new()),
+            timers: RefCell::new(HashMap::new()),
+            deferred: RefCell::new(Vec::new()),
+            dead: RefCell::new(Vec::new()),
+            dispatching: Cell::new(0),
+        }));
+        unsafe { (*internal).api.userdata = internal as *mut c_void };
+        let api_ptr = unsafe { &(*internal).api as *const MainloopApi };
+        Self {
+            _inner: Rc::new(MainloopInner::<MainloopInternal<L>> {
+                ptr: internal,
+                api: api_ptr,
+                dropfn: drop_actual::<L>,
+                supports_rtclock: false,
+            }),
+        }
+    }
+
+    /// Return the abstract main loop abstraction layer vtable for this main loop.
+    pub fn get_api<'a>(&self) -> &'a MainloopApi {
+        let ptr = (*self._inner).api;
+        assert_eq!(false, ptr.is_null());
+        unsafe { &*ptr }
+    }
+
+    /// Access the event loop.
+    ///
+    /// Panics if called from within an [`EventLoop`](trait.EventLoop.html) method.
+    pub fn event_loop(&self) -> RefMut<'_, L> {
+        self.internal().event_loop.borrow_mut()
+    }
+
+    /// Dispatch the IO event registered with `id`, for the given events having occurred.
+    ///
+    /// Returns `false` if no such IO event exists (as may happen if it is freed after the event
+    /// loop observed readiness).
+    pub fn dispatch_io(&self, id: EventId, events: IoEventFlagSet) -> bool {
+        let internal = self.internal();
+        let entry = match internal.io.borrow().get(&id) {
+            Some(entry) => *entry,
+            None => return false,
+        };
+        internal.begin_dispatch();
+        let (fd, cb, userdata) = unsafe { ((*entry).fd, (*entry).cb, (*entry).userdata) };
+        if let Some(cb) = cb {
+            cb(&internal.api, entry as *mut IoEventInternal, fd, events, userdata);
+        }
+        internal.end_dispatch();
+        true
+    }
+
+    /// Dispatch the timer event registered with `id`, its deadline having passed.
+    ///
+    /// The timer is disabled before calling the callback, which may restart it. Returns `false` if
+    /// no such timer exists, or it is disabled.
+    pub fn dispatch_timer(&self, id: EventId) -> bool {
+        let internal = self.internal();
+        let entry = match internal.timers.borrow().get(&id) {
+            Some(entry) => *entry,
+            None => return false,
+        };
+        let deadline = match unsafe { (*entry).deadline.take() } {
+            Some(deadline) => deadline,
+            None => return false,
+        };
+        internal.event_loop.borrow_mut().set_timer(id, None);
+        internal.begin_dispatch();
+        let (cb, userdata) = unsafe { ((*entry).cb, (*entry).userdata) };
+        if let Some(cb) = cb {
+            cb(&internal.api, entry as *mut TimeEventInternal, &deadline, userdata);
+        }
+        internal.end_dispatch();
+        true
+    }
+
+    /// Dispatch all enabled deferred events once, returning the number dispatched.
+    pub fn dispatch_deferred(&self) -> usize {
+        let internal = self.internal();
+        let entries: Vec<_> = internal.deferred.borrow().iter()
+            .filter(|e| unsafe { (***e).enabled })
+            .cloned()
+            .collect();
+        internal.begin_dispatch();
+        let mut count = 0;
+        for entry in entries {
+            // May have been disabled or freed by an earlier callback
+            let (enabled, dead, cb, userdata) = unsafe {
+                ((*entry).enabled, (*entry).dead, (*entry).cb, (*entry).userdata)
+            };
+            if !enabled || dead {
+                continue;
+            }
+            if let Some(cb) = cb {
+                cb(&internal.api, entry as *mut DeferEventInternal, userdata);
+            }
+            count += 1;
+        }
+        internal.end_dispatch();
+        count
+    }
+
+    /// Returns `true` if any deferred events are enabled, thus pending dispatch.
+    pub fn has_deferred(&self) -> bool {
+        self.internal().has_deferred()
+    }
+
+    fn internal(&self) -> &MainloopInternal<L> {
+        unsafe { &*self._inner.ptr }
+    }
+}
+
+impl<L: EventLoop> MainloopInternal<L> {
+    fn allocate_id(&self) -> EventId {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        EventId(id)
+    }
+
+    fn has_deferred(&self) -> bool {
+        self.deferred.borrow().iter().any(|e| unsafe { (**e).enabled })
+    }
+
+    fn begin_dispatch(&self) {
+        self.dispatching.set(self.dispatching.get() + 1);
+    }
+
+    fn end_dispatch(&self) {
+        self.dispatching.set(self.dispatching.get() - 1);
+        if self.dispatching.get() == 0 {
+            self.destroy_dead();
+        }
+    }
+
+    /// Queue destruction of an event source, destroying it immediately if not dispatching.
+    fn kill(&self, dead: Dead<L>) {
+        self.dead.borrow_mut().push(dead);
+        if self.dispatching.get() == 0 {
+            self.destroy_dead();
+        }
+    }
+
+    /// Destroy event sources pending destruction, calling their destroy callbacks.
+    fn destroy_dead(&self) {
+        loop {
+            let dead = self.dead.borrow_mut().pop();
+            let api: *const MainloopApi = &self.api;
+            match dead {
+                Some(Dead::Io(e)) => {
+                    let entry = unsafe { Box::from_raw(e) };
+                    if let Some(cb) = entry.destroy_cb {
+                        cb(api, e as *mut IoEventInternal, entry.userdata);
+                    }
+                },
+                Some(Dead::Time(e)) => {
+                    let entry = unsafe { Box::from_raw(e) };
+                    if let Some(cb) = entry.destroy_cb {
+                        cb(api, e as *mut TimeEventInternal, entry.userdata);
+                    }
+                },
+                Some(Dead::Defer(e)) => {
+                    let entry = unsafe { Box::from_raw(e) };
+                    if let Some(cb) = entry.destroy_cb {
+                        cb(api, e as *mut DeferEventInternal, entry.userdata);
+                    }
+                },
+                None => return,
+            }
+        }
+    }
+}
+
+fn get_internal<'a, L: EventLoop>(a: *const MainloopApi) -> &'a MainloopInternal<L> {
+    assert!(!a.is_null());
+    unsafe { &*((*a).userdata as *const MainloopInternal<L>) }
+}
+
+/// Convert a timeval given to the API to a wall clock deadline.
+fn to_deadline(tv: *const timeval) -> Option<timeval> {
+    match tv.is_null() {
+        true => None,
+        false => Some(Timeval(unsafe { *tv }).strip_rtclock().0),
+    }
+}
+
+fn to_system_time(tv: &Option<timeval>) -> Option<SystemTime> {
+    // Deadlines not representable as a `SystemTime` (i.e. before the epoch) are simply overdue
+    tv.map(|tv| Timeval(tv).to_system_time().unwrap_or(std::time::UNIX_EPOCH))
+}
+
+extern "C"
+fn io_new<L: EventLoop>(a: *const MainloopApi, fd: i32, events: IoEventFlagSet,
+    cb: Option<IoEventCb>, userdata: *mut c_void) -> *mut IoEventInternal
+{
+    let ret = std::panic::catch_unwind(|| {
+        let internal = get_internal::<L>(a);
+        let id = internal.allocate_id();
+        let entry = Box::into_raw(Box::new(IoEntry {
+            owner: internal as *const _ as *mut _,
+            id, fd, dead: false, cb, destroy_cb: None, userdata,
+        }));
+        internal.io.borrow_mut().insert(id, entry);
+        internal.event_loop.borrow_mut().register_io(id, fd, events);
+        entry as *mut IoEventInternal
+    });
+    ret.unwrap_or(null_mut())
+}
+
+extern "C"
+fn io_enable<L: EventLoop>(e: *mut IoEventInternal, events: IoEventFlagSet) {
+    let _ = std::panic::catch_unwind(|| {
+        let entry = unsafe { &*(e as *mut IoEntry<L>) };
+        assert!(!entry.dead);
+        let internal = unsafe { &*entry.owner };
+        internal.event_loop.borrow_mut().register_io(entry.id, entry.fd, events);
+    });
+}
+
+extern "C"
+fn io_free<L: EventLoop>(e: *mut IoEventInternal) {
+    let _ = std::panic::catch_unwind(|| {
+        let entry = unsafe { &mut *(e as *mut IoEntry<L>) };
+        assert!(!entry.dead);
+        entry.dead = true;
+        let internal = unsafe { &*entry.owner };
+        internal.io.borrow_mut().remove(&entry.id);
+        internal.event_loop.borrow_mut().deregister_io(entry.id);
+        internal.kill(Dead::Io(e as *mut IoEntry<L>));
+    });
+}
+
+extern "C"
+fn io_set_destroy<L: EventLoop>(e: *mut IoEventInternal, cb: Option<IoEventDestroyCb>) {
+    let _ = std::panic::catch_unwind(|| {
+        let entry = unsafe { &mut *(e as *mut IoEntry<L>) };
+        entry.destroy_cb = cb;
+    });
+}
+
+extern "C"
+fn time_new<L: EventLoop>(a: *const MainloopApi, tv: *const timeval, cb: Option<TimeEventCb>,
+    userdata: *mut c_void) -> *mut TimeEventInternal
+{
+    let ret = std::panic::catch_unwind(|| {
+        let internal = get_internal::<L>(a);
+        let id = internal.allocate_id();
+        let deadline = to_deadline(tv);
+        let entry = Box::into_raw(Box::new(TimeEntry {
+            owner: internal as *const _ as *mut _,
+            id, deadline, dead: false, cb, destroy_cb: None, userdata,
+        }));
+        internal.timers.borrow_mut().insert(id, entry);
+        internal.event_loop.borrow_mut().set_timer(id, to_system_time(&deadline));
+        entry as *mut TimeEventInternal
+    });
+    ret.unwrap_or(null_mut())
+}
+
+extern "C"
+fn time_restart<L: EventLoop>(e: *mut TimeEventInternal, tv: *const timeval) {
+    let _ = std::panic::catch_unwind(|| {
+        let entry = unsafe { &mut *(e as *mut TimeEntry<L>) };
+        assert!(!entry.dead);
+        entry.deadline = to_deadline(tv);
+        let internal = unsafe { &*entry.owner };
+        internal.event_loop.borrow_mut().set_timer(entry.id, to_system_time(&entry.deadline));
+    });
+}
+
+extern "C"
+fn time_free<L: EventLoop>(e: *mut TimeEventInternal) {
+    let _ = std::panic::catch_unwind(|| {
+        let entry = unsafe { &mut *(e as *mut TimeEntry<L>) };
+        assert!(!entry.dead);
+        entry.dead = true;
+        let internal = unsafe { &*entry.owner };
+        internal.timers.borrow_mut().remove(&entry.id);
+        internal.event_loop.borrow_mut().deregister_timer(entry.id);
+        internal.kill(Dead::Time(e as *mut TimeEntry<L>));
+    });
+}
+
+extern "C"
+fn time_set_destroy<L: EventLoop>(e: *mut TimeEventInternal, cb: Option<TimeEventDestroyCb>) {
+    let _ = std::panic::catch_unwind(|| {
+        let entry = unsafe { &mut *(e as *mut TimeEntry<L>) };
+        entry.destroy_cb = cb;
+    });
+}
+
+extern "C"
+fn defer_new<L: EventLoop>(a: *const MainloopApi, cb: Option<DeferEventCb>, userdata: *mut c_void)
+    -> *mut DeferEventInternal
+{
+    let ret = std::panic::catch_unwind(|| {
+        let internal = get_internal::<L>(a);
+        let entry = Box::into_raw(Box::new(DeferEntry {
+            owner: internal as *const _ as *mut _,
+            enabled: true, dead: false, cb, destroy_cb: None, userdata,
+        }));
+        internal.deferred.borrow_mut().push(entry);
+        internal.event_loop.borrow_mut().wakeup();
+        entry as *mut DeferEventInternal
+    });
+    ret.unwrap_or(null_mut())
+}
+
+extern "C"
+fn defer_enable<L: EventLoop>(e: *mut DeferEventInternal, b: i32) {
+    let _ = std::panic::catch_unwind(|| {
+        let entry = unsafe { &mut *(e as *mut DeferEntry<L>) };
+        assert!(!entry.dead);
+        let was_enabled = entry.enabled;
+        entry.enabled = b != 0;
+        if entry.enabled && !was_enabled {
+            let internal = unsafe { &*entry.owner };
+            internal.event_loop.borrow_mut().wakeup();
+        }
+    });
+}
+
+extern "C"
+fn defer_free<L: EventLoop>(e: *mut DeferEventInternal) {
+    let _ = std::panic::catch_unwind(|| {
+        let entry = unsafe { &mut *(e as *mut DeferEntry<L>) };
+        assert!(!entry.dead);
+        entry.dead = true;
+        entry.enabled = false;
+        let internal = unsafe { &*entry.owner };
+        let ptr = e as *mut DeferEntry<L>;
+        internal.deferred.borrow_mut().retain(|d| *d != ptr);
+        internal.kill(Dead::Defer(ptr));
+    });
+}
+
+extern "C"
+fn defer_set_destroy<L: EventLoop>(e: *mut DeferEventInternal, cb: Option<DeferEventDestroyCb>) {
+    let _ = std::panic::catch_unwind(|| {
+        let entry = unsafe { &mut *(e as *mut DeferEntry<L>) };
+        entry.destroy_cb = cb;
+    });
+}
+
+extern "C"
+fn quit<L: EventLoop>(a: *const MainloopApi, retval: RetvalActual) {
+    let _ = std::panic::catch_unwind(|| {
+        let internal = get_internal::<L>(a);
+        internal.event_loop.borrow_mut().quit(Retval(retval));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mainloop::api::Mainloop as MainloopTrait;
+    use mainloop::events::io::IoEvent;
+    use mainloop::events::deferred::DeferEvent;
+    use mainloop::events::io::flags as io_flags;
+
+    #[derive(Default)]
+    struct RecordingLoop {
+        io: HashMap<EventId, (i32, IoEventFlagSet)>,
+        timers: HashMap<EventId, Option<SystemTime>>,
+        wakeups: usize,
+    }
+
+    impl EventLoop for RecordingLoop {
+        fn register_io(&mut self, id: EventId, fd: i32, events: IoEventFlagSet) {
+            self.io.insert(id, (fd, events));
+        }
+        fn deregister_io(&mut self, id: EventId) {
+            self.io.remove(&id);
+        }
+        fn set_timer(&mut self, id: EventId, deadline: Option<SystemTime>) {
+            self.timers.insert(id, deadline);
+        }
+        fn deregister_timer(&mut self, id: EventId) {
+            self.timers.remove(&id);
+        }
+        fn wakeup(&mut self) {
+            self.wakeups += 1;
+        }
+    }
+
+    #[test]
+    fn deferred_and_timer_events() {
+        let mut mainloop = Mainloop::new(RecordingLoop::default());
+        let fired = Rc::new(Cell::new(0));
+
+        let fired_ref = Rc::clone(&fired);
+        let mut deferred = mainloop.new_deferred_event(Box::new(move |mut e| {
+            fired_ref.set(fired_ref.get() + 1);
+            e.disable();
+        })).unwrap();
+        assert_eq!(mainloop.event_loop().wakeups, 1);
+        assert_eq!(mainloop.dispatch_deferred(), 1);
+        assert!(!mainloop.has_deferred());
+        deferred.enable();
+        assert!(mainloop.has_deferred());
+
+        let fired_ref = Rc::clone(&fired);
+        let timer = mainloop.new_timer_event(&::time::UnixTs::now(), Box::new(move |_| {
+            fired_ref.set(fired_ref.get() + 10);
+        })).unwrap();
+        let id = *mainloop.event_loop().timers.keys().next().unwrap();
+        assert!(mainloop.dispatch_timer(id));
+        assert!(!mainloop.dispatch_timer(id));
+        assert_eq!(mainloop.event_loop().timers[&id], None);
+        assert_eq!(fired.get(), 11);
+
+        drop(timer);
+        assert!(mainloop.event_loop().timers.is_empty());
+        drop(deferred);
+        assert!(!mainloop.has_deferred());
+    }
+
+    /// Find the ID of the IO event registered for `fd`.
+    fn io_id(mainloop: &Mainloop<RecordingLoop>, fd: i32) -> EventId {
+        *mainloop.event_loop().io.iter().find(|&(_, &(f, _))| f == fd).unwrap().0
+    }
+
+    #[test]
+    fn io_events() {
+        let mut mainloop = Mainloop::new(RecordingLoop::default());
+        let fired = Rc::new(Cell::new(0));
+
+        let fired_ref = Rc::clone(&fired);
+        let mut event = mainloop.new_io_event(5, io_flags::INPUT, Box::new(move |_, fd, events| {
+            assert_eq!((fd, events), (5, io_flags::INPUT));
+            fired_ref.set(fired_ref.get() + 1);
+        })).unwrap();
+        let id = io_id(&mainloop, 5);
+        assert!(mainloop.dispatch_io(id, io_flags::INPUT));
+        assert_eq!(fired.get(), 1);
+
+        event.enable(io_flags::OUTPUT);
+        assert_eq!(mainloop.event_loop().io[&id], (5, io_flags::OUTPUT));
+
+        drop(event);
+        assert!(mainloop.event_loop().io.is_empty());
+        assert!(!mainloop.dispatch_io(id, io_flags::INPUT));
+        assert_eq!(fired.get(), 1);
+    }
+
+    /// Test that event sources freed from within a callback, whilst dispatching, are not
+    /// dispatched thereafter.
+    #[test]
+    fn free_during_dispatch() {
+        let mut mainloop = Mainloop::new(RecordingLoop::default());
+        let fired = Rc::new(Cell::new(0));
+
+        // The first IO event frees the second
+        let second: Rc<RefCell<Option<IoEvent<_>>>> = Rc::new(RefCell::new(None));
+        let second_ref = Rc::clone(&second);
+        let _first = mainloop.new_io_event(5, io_flags::INPUT, Box::new(move |_, _, _| {
+            drop(second_ref.borrow_mut().take());
+        })).unwrap();
+        let fired_ref = Rc::clone(&fired);
+        *second.borrow_mut() = mainloop.new_io_event(6, io_flags::INPUT,
+            Box::new(move |_, _, _| fired_ref.set(fired_ref.get() + 1)));
+        let (first_id, second_id) = (io_id(&mainloop, 5), io_id(&mainloop, 6));
+        assert!(mainloop.dispatch_io(first_id, io_flags::INPUT));
+        assert!(!mainloop.dispatch_io(second_id, io_flags::INPUT));
+        assert_eq!(fired.get(), 0);
+
+        // The first deferred event frees the second, which is then skipped within the same round
+        let second: Rc<RefCell<Option<DeferEvent<_>>>> = Rc::new(RefCell::new(None));
+        let second_ref = Rc::clone(&second);
+        let _first = mainloop.new_deferred_event(Box::new(move |_| {
+            drop(second_ref.borrow_mut().take());
+        })).unwrap();
+        let fired_ref = Rc::clone(&fired);
+        *second.borrow_mut() = mainloop.new_deferred_event(
+            Box::new(move |_| fired_ref.set(fired_ref.get() + 1)));
+        assert_eq!(mainloop.dispatch_deferred(), 1);
+        assert_eq!(fired.get(), 0);
+    }
+}
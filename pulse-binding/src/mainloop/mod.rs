@@ -42,6 +42,12 @@
 //! * ‘Glib’: A wrapper around GLib’s main loop. This is provided in the separate
 //!   `libpulse_glib_binding` crate.
 //!
+//! Additionally, this binding provides [`Custom`], for driving PulseAudio from any other event loop
+//! by implementing a single trait, along with an implementation of this for the `async-io` reactor,
+//! in [`async_io`] (requires the `async-io` feature). Alternatively, on Linux, [`poll_fd`] gathers
+//! the file descriptors of the [`Standard`] implementation behind a single one, for waiting upon
+//! within the event loop of a GUI toolkit.
+//!
 //! UNIX signals may be hooked to a main loop using the functionality from [`::mainloop::signal`].
 //! This relies only on the main loop abstraction and can therefore be used with any of the
 //! implementations.
@@ -89,13 +95,21 @@
 //!
 //! [`Standard`]: standard/index.html
 //! [`Threaded`]: threaded/index.html
+//! [`Custom`]: custom/index.html
+//! [`async_io`]: async_io/index.html
+//! [`poll_fd`]: poll_fd/index.html
 //! [`::mainloop::signal`]: signal/index.html
 //! [`::mainloop::api::MainloopApi`]: api/struct.MainloopApi.html
 //! [standard mainloop documentation]: standard/index.html
 //! [`in_thread`]: threaded/struct.Mainloop.html#method.in_thread
 
 pub mod api;
+#[cfg(feature = "async-io")]
+pub mod async_io;
+pub mod custom;
 pub mod events;
+#[cfg(target_os = "linux")]
+pub mod poll_fd;
 pub mod signal;
 pub mod standard;
 pub mod threaded;
@@ -0,0 +1,289 @@
+// Copyright 2018 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Single file descriptor integration of the standard main loop (Linux only).
+//!
+//! # Overview
+//!
+//! Applications with an event or frame loop of their own, such as those of GUI toolkits, may wish
+//! to drive the [`standard`] main loop from it, without a separate thread. The set of file
+//! descriptors the main loop waits upon, along with its timeout, changes from one iteration to the
+//! next though, which is awkward to mirror. A [`PollFd`], created with [`Mainloop::poll_fd`],
+//! overrides the poll function of the main loop so as to gather these together behind a single
+//! file descriptor (an epoll instance, along with a timer for the timeout), which becomes readable
+//! whenever the main loop has work to do.
+//!
+//! The application waits upon this file descriptor along with its own, and calls
+//! [`PollFd::iterate`] whenever it becomes readable. This never blocks.
+//!
+//! ```rust,ignore
+//! use std::os::unix::io::AsFd;
+//!
+//! let mut poll_fd = mainloop.poll_fd()?;
+//! poll_fd.iterate(); // Initial iteration, registering the file descriptors
+//!
+//! // Within the frame loop, with `fd` waited upon alongside those of the toolkit
+//! let fd = poll_fd.as_fd();
+//! if readable(fd) {
+//!     poll_fd.iterate();
+//! }
+//! ```
+//!
+//! Note that the main loop must not be iterated by other means (such as [`Mainloop::iterate`])
+//! whilst the `PollFd` exists, since those would not block, rather than waiting for events.
+//!
+//! [`standard`]: ../standard/index.html
+//! [`PollFd`]: struct.PollFd.html
+//! [`Mainloop::poll_fd`]: ../standard/struct.Mainloop.html#method.poll_fd
+//! [`PollFd::iterate`]: struct.PollFd.html#method.iterate
+//! [`Mainloop::iterate`]: ../standard/struct.Mainloop.html#method.iterate
+
+use std;
+use capi;
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::os::raw::{c_ulong, c_void};
+use std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, FromRawFd, OwnedFd, RawFd};
+use std::ptr::null_mut;
+use std::rc::Rc;
+use libc::pollfd;
+use def::Retval;
+use error::PAErr;
+use super::api::MainloopInner;
+use super::standard::{IterateResult, Mainloop, MainloopInternal};
+
+/// State of the poll function override.
+struct State {
+    epoll: OwnedFd,
+    timer: OwnedFd,
+    /// File descriptors registered with the epoll instance.
+    registered: HashSet<RawFd>,
+}
+
+/// A single file descriptor, readable whenever the standard main loop has work to do.
+///
+/// See the [module level documentation](index.html) for details.
+pub struct PollFd {
+    mainloop: Rc<MainloopInner<MainloopInternal>>,
+    state: Box<State>,
+}
+
+/// Maximum number of main loop iterations run by a single call to `PollFd::iterate`.
+const MAX_PASSES: usize = 16;
+
+/// Convert the result of a system call.
+fn check(r: i32) -> io::Result<i32> {
+    match r {
+        -1 => Err(io::Error::last_os_error()),
+        r => Ok(r),
+    }
+}
+
+impl Mainloop {
+    /// Create a single file descriptor integration of the main loop, overriding its poll function.
+    ///
+    /// See [`::mainloop::poll_fd`](../poll_fd/index.html) for details.
+    pub fn poll_fd(&mut self) -> io::Result<PollFd> {
+        let epoll = check(unsafe { libc::epoll_create1(libc::EPOLL_CLOEXEC) })?;
+        let epoll = unsafe { OwnedFd::from_raw_fd(epoll) };
+        let timer = check(unsafe { libc::timerfd_create(libc::CLOCK_MONOTONIC,
+            libc::TFD_CLOEXEC | libc::TFD_NONBLOCK) })?;
+        let timer = unsafe { OwnedFd::from_raw_fd(timer) };
+
+        let mut event = libc::epoll_event { events: libc::EPOLLIN as u32, u64: 0 };
+        check(unsafe { libc::epoll_ctl(epoll.as_raw_fd(), libc::EPOLL_CTL_ADD,
+            timer.as_raw_fd(), &mut event) })?;
+
+        let mut state = Box::new(State { epoll: epoll, timer: timer, registered: HashSet::new() });
+        let userdata = &mut *state as *mut State as *mut c_void;
+        unsafe { capi::pa_mainloop_set_poll_func((*self._inner).ptr, Some(poll_proxy), userdata) };
+        Ok(PollFd { mainloop: Rc::clone(&self._inner), state: state })
+    }
+}
+
+impl PollFd {
+    /// Run the main loop, without blocking, until no work remains pending.
+    ///
+    /// This differs from [`Mainloop::iterate`] in passing the main loop’s own timeout on to the
+    /// file descriptor, rather than polling with none. Since dispatching may itself create work
+    /// (or change the file descriptors waited upon), iterations are repeated until one dispatches
+    /// nothing, up to a limit, such that the file descriptor reflects the state left behind.
+    ///
+    /// Returns the total number of sources dispatched, unless quit was called, or an error
+    /// occurred, as with [`Mainloop::iterate`].
+    ///
+    /// [`Mainloop::iterate`]: ../standard/struct.Mainloop.html#method.iterate
+    pub fn iterate(&mut self) -> IterateResult {
+        let ptr = self.mainloop.ptr;
+        let mut total = 0;
+        for _ in 0..MAX_PASSES {
+            let result = unsafe {
+                match capi::pa_mainloop_prepare(ptr, -1) {
+                    0 => match capi::pa_mainloop_poll(ptr) {
+                        r if r < 0 => r,
+                        _ => capi::pa_mainloop_dispatch(ptr),
+                    },
+                    r => r,
+                }
+            };
+            match result {
+                0 => break,
+                r if r > 0 => total += r as u32,
+                -2 => {
+                    let retval = unsafe { capi::pa_mainloop_get_retval(ptr) };
+                    return IterateResult::Quit(Retval(retval));
+                },
+                e => return IterateResult::Err(PAErr(e)),
+            }
+        }
+        IterateResult::Success(total)
+    }
+}
+
+impl AsFd for PollFd {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.state.epoll.as_fd()
+    }
+}
+
+impl AsRawFd for PollFd {
+    fn as_raw_fd(&self) -> RawFd {
+        self.state.epoll.as_raw_fd()
+    }
+}
+
+impl Drop for PollFd {
+    fn drop(&mut self) {
+        // Restore the default poll function, before the state it was given is freed
+        unsafe { capi::pa_mainloop_set_poll_func(self.mainloop.ptr, None, null_mut()) };
+    }
+}
+
+impl State {
+    /// Bring the epoll registrations in line with the file descriptors polled.
+    fn sync(&mut self, fds: &[pollfd]) {
+        let mut wanted: HashMap<RawFd, u32> = HashMap::with_capacity(fds.len());
+        for fd in fds.iter().filter(|fd| fd.fd >= 0) {
+            // The `POLL*` and `EPOLL*` event flags share their values
+            *wanted.entry(fd.fd).or_insert(0) |= fd.events as u32;
+        }
+        let epoll = self.epoll.as_raw_fd();
+        for &fd in self.registered.iter().filter(|fd| !wanted.contains_key(fd)) {
+            unsafe { libc::epoll_ctl(epoll, libc::EPOLL_CTL_DEL, fd, null_mut()) };
+        }
+        // A file descriptor number may have been closed and reused for another file since the last
+        // sync, with the epoll instance having silently dropped the old registration, thus every
+        // file descriptor is (re-)added each time, modifying those still registered.
+        for (&fd, &events) in wanted.iter() {
+            let mut event = libc::epoll_event { events: events, u64: fd as u64 };
+            let r = unsafe { libc::epoll_ctl(epoll, libc::EPOLL_CTL_ADD, fd, &mut event) };
+            if r == -1 && io::Error::last_os_error().raw_os_error() == Some(libc::EEXIST) {
+                unsafe { libc::epoll_ctl(epoll, libc::EPOLL_CTL_MOD, fd, &mut event) };
+            }
+        }
+        self.registered = wanted.into_iter().map(|(fd, _)| fd).collect();
+    }
+
+    /// Arm the timer for the timeout in milliseconds, or disarm it for a negative timeout.
+    fn set_timeout(&mut self, timeout: i32) {
+        let mut expirations = 0u64;
+        let buf = &mut expirations as *mut u64 as *mut c_void;
+        unsafe { libc::read(self.timer.as_raw_fd(), buf, 8) };
+        let value = match timeout {
+            t if t < 0 => libc::timespec { tv_sec: 0, tv_nsec: 0 },
+            // A zero value would disarm the timer
+            0 => libc::timespec { tv_sec: 0, tv_nsec: 1 },
+            t => libc::timespec {
+                tv_sec: (t / 1000) as libc::time_t,
+                tv_nsec: ((t % 1000) * 1_000_000) as libc::c_long,
+            },
+        };
+        let spec = libc::itimerspec {
+            it_interval: libc::timespec { tv_sec: 0, tv_nsec: 0 },
+            it_value: value,
+        };
+        unsafe { libc::timerfd_settime(self.timer.as_raw_fd(), 0, &spec, null_mut()) };
+    }
+}
+
+/// Poll function override, registering the file descriptors and timeout with the epoll instance,
+/// then polling without blocking.
+extern "C" fn poll_proxy(ufds: *mut pollfd, nfds: c_ulong, timeout: i32, userdata: *mut c_void)
+    -> i32
+{
+    let ret = std::panic::catch_unwind(|| {
+        assert!(!userdata.is_null());
+        let state = unsafe { &mut *(userdata as *mut State) };
+        let fds = match ufds.is_null() {
+            true => &[][..],
+            false => unsafe { std::slice::from_raw_parts(ufds, nfds as usize) },
+        };
+        state.sync(fds);
+        state.set_timeout(timeout);
+        unsafe { libc::poll(ufds, nfds as libc::nfds_t, 0) }
+    });
+    // A panic is reported as a failure of the poll
+    ret.unwrap_or(-1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_state() -> State {
+        let epoll = check(unsafe { libc::epoll_create1(libc::EPOLL_CLOEXEC) }).unwrap();
+        let timer = check(unsafe { libc::timerfd_create(libc::CLOCK_MONOTONIC,
+            libc::TFD_CLOEXEC | libc::TFD_NONBLOCK) }).unwrap();
+        State {
+            epoll: unsafe { OwnedFd::from_raw_fd(epoll) },
+            timer: unsafe { OwnedFd::from_raw_fd(timer) },
+            registered: HashSet::new(),
+        }
+    }
+
+    fn new_pipe() -> (OwnedFd, OwnedFd) {
+        let mut fds = [0; 2];
+        check(unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) }).unwrap();
+        unsafe { (OwnedFd::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])) }
+    }
+
+    fn ready(state: &State) -> i32 {
+        let mut event = libc::epoll_event { events: 0, u64: 0 };
+        check(unsafe { libc::epoll_wait(state.epoll.as_raw_fd(), &mut event, 1, 0) }).unwrap()
+    }
+
+    /// Test that a file descriptor number reused for another file between syncs is registered
+    /// anew, the epoll instance having dropped the registration of the old file.
+    #[test]
+    fn sync_reused_fd() {
+        let mut state = new_state();
+        let (read_a, _write_a) = new_pipe();
+        let fds = [pollfd { fd: read_a.as_raw_fd(), events: libc::POLLIN, revents: 0 }];
+        state.sync(&fds);
+        assert_eq!(ready(&state), 0);
+
+        // Replace the file behind the number, closing the old one
+        let (read_b, write_b) = new_pipe();
+        check(unsafe { libc::dup2(read_b.as_raw_fd(), read_a.as_raw_fd()) }).unwrap();
+        state.sync(&fds);
+        check(unsafe { libc::write(write_b.as_raw_fd(), b"x".as_ptr() as *const c_void, 1) }
+            as i32).unwrap();
+        assert_eq!(ready(&state), 1);
+
+        // Dropped once no longer polled
+        state.sync(&[]);
+        assert_eq!(ready(&state), 0);
+    }
+}
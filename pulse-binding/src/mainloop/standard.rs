@@ -168,7 +168,7 @@
 //!             let drain_state_ref = Rc::clone(&drained);
 //!             stream.borrow_mut().drain(Some(Box::new(move |_success: bool| {
 //!                 drain_state_ref.store(true, atomic::Ordering::Relaxed);
-//!             })))
+//!             }))).unwrap()
 //!         };
 //!         while !drained.compare_and_swap(true, false, atomic::Ordering::Relaxed) {
 //!             match mainloop.borrow_mut().iterate(false) {
@@ -384,6 +384,22 @@ impl Mainloop {
         }
     }
 
+    /// Run unlimited iterations of the main loop object until the main loop’s quit routine is
+    /// called, returning the exit given to it.
+    ///
+    /// This is as [`run`](#method.run), but with the exit code typed. On error, returns `Err`
+    /// containing the error value.
+    pub fn run_to_exit(&mut self) -> Result<::def::LoopExit, PAErr> {
+        self.run().map(::def::LoopExit::from).map_err(|(e, _)| e)
+    }
+
+    /// Get the exit given to the main loop’s quit routine, as per [`get_retval`].
+    ///
+    /// [`get_retval`]: #method.get_retval
+    pub fn get_exit(&self) -> ::def::LoopExit {
+        self.get_retval().into()
+    }
+
     /// Return the abstract main loop abstraction layer vtable for this main loop.
     ///
     /// No need to free the API as it is owned by the loop and is destroyed when the loop is freed.
@@ -403,6 +419,17 @@ impl Mainloop {
         unsafe { capi::pa_mainloop_quit((*self._inner).ptr, retval.0); }
     }
 
+    /// Shutdown the main loop with the given exit code, returned from [`run_to_exit`].
+    ///
+    /// Where the main loop object is borrowed for running it, use
+    /// [`MainloopApi::quit_with`](../api/struct.MainloopApi.html#method.quit_with) from callbacks
+    /// instead.
+    ///
+    /// [`run_to_exit`]: #method.run_to_exit
+    pub fn quit_with(&mut self, code: ::def::RetvalActual) {
+        self.quit(::def::Retval(code));
+    }
+
     /// Interrupt a running poll (for threaded systems)
     pub fn wakeup(&mut self) {
         unsafe { capi::pa_mainloop_wakeup((*self._inner).ptr); }
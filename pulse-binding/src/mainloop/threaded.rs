@@ -42,6 +42,17 @@
 //! Destruction of the [`Mainloop`] object is done automatically when the object falls out of scope.
 //! (Rust’s `Drop` trait has been implemented and takes care of it).
 //!
+//! Objects freed whilst the event loop thread is still running may be touched by it during
+//! teardown, commonly crashing the process upon exit. Starting the thread with
+//! [`Mainloop::start_scoped`] instead gives a [`MainloopGuard`], which upon being dropped takes the
+//! lock, disconnects the contexts registered with it, and then stops the thread, in that order:
+//!
+//! ```rust,ignore
+//! let guard = mainloop.start_scoped()?;
+//! guard.register_context(&context);
+//! // ... (returning early, or panicking, still shuts down tidily)
+//! ```
+//!
 //! # Locking
 //!
 //! Since the PulseAudio API doesn’t allow concurrent accesses to objects, a locking scheme must be
@@ -112,7 +123,7 @@
 //!         let ml_ref = Rc::clone(&m);
 //!         s.borrow_mut().drain(Some(Box::new(move |_success: bool| {
 //!             unsafe { (*ml_ref.as_ptr()).signal(false); }
-//!         })))
+//!         }))).unwrap()
 //!     };
 //!     while o.get_state() != pulse::operation::State::Done {
 //!         m.borrow_mut().wait();
@@ -168,7 +179,7 @@
 //!         s.borrow_mut().drain(Some(Box::new(move |success: bool| {
 //!             data.0 = Some(&mut success);
 //!             unsafe { (*ml_ref.as_ptr()).signal(true); }
-//!         })))
+//!         }))).unwrap()
 //!     };
 //!     while o.get_state() != pulse::operation::State::Done {
 //!         m.borrow_mut().wait();
@@ -349,7 +360,7 @@
 //!             let ml_ref = Rc::clone(&mainloop);
 //!             stream.borrow_mut().drain(Some(Box::new(move |_success: bool| {
 //!                 unsafe { (*ml_ref.as_ptr()).signal(false); }
-//!             })))
+//!             }))).unwrap()
 //!         };
 //!         while o.get_state() != pulse::operation::State::Done {
 //!             mainloop.borrow_mut().wait();
@@ -379,6 +390,8 @@
 //! [`Mainloop`]: struct.Mainloop.html
 //! [`Mainloop::new`]: struct.Mainloop.html#method.new
 //! [`Mainloop::start`]: struct.Mainloop.html#method.start
+//! [`Mainloop::start_scoped`]: struct.Mainloop.html#method.start_scoped
+//! [`MainloopGuard`]: struct.MainloopGuard.html
 //! [`Mainloop::stop`]: struct.Mainloop.html#method.stop
 //! [`Mainloop::lock`]: struct.Mainloop.html#method.lock
 //! [`Mainloop::unlock`]: struct.Mainloop.html#method.unlock
@@ -388,7 +401,8 @@
 
 use std;
 use capi;
-use std::rc::Rc;
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
 use std::ffi::CString;
 use std::ptr::null_mut;
 use error::PAErr;
@@ -407,6 +421,9 @@ impl super::api::MainloopInternalType for MainloopInternal {}
 pub struct Mainloop {
     /// The ref-counted inner data
     pub _inner: Rc<super::api::MainloopInner<MainloopInternal>>,
+    /// Number of times the lock is currently held through this object (or the guard of a scoped
+    /// start, sharing it).
+    lock_depth: Rc<Cell<u32>>,
 }
 
 impl super::api::Mainloop for Mainloop {
@@ -448,6 +465,7 @@ impl Mainloop {
                         supports_rtclock: true,
                     }
                 ),
+                lock_depth: Rc::new(Cell::new(0)),
             }
         )
     }
@@ -460,6 +478,20 @@ impl Mainloop {
         }
     }
 
+    /// Start the event loop thread, returning a guard stopping it tidily upon being dropped.
+    ///
+    /// See [`MainloopGuard`](struct.MainloopGuard.html).
+    pub fn start_scoped(&mut self) -> Result<MainloopGuard, PAErr> {
+        self.start()?;
+        Ok(MainloopGuard {
+            mainloop: Mainloop {
+                _inner: Rc::clone(&self._inner),
+                lock_depth: Rc::clone(&self.lock_depth),
+            },
+            contexts: RefCell::new(Vec::new()),
+        })
+    }
+
     /// Terminate the event loop thread cleanly. Make sure to unlock the mainloop object before
     /// calling this function.
     pub fn stop(&mut self) {
@@ -473,10 +505,12 @@ impl Mainloop {
     pub fn lock(&mut self) {
         assert!(!self.in_thread(), "lock() can not be called from within the event loop thread!");
         unsafe { capi::pa_threaded_mainloop_lock((*self._inner).ptr); }
+        self.lock_depth.set(self.lock_depth.get() + 1);
     }
 
     /// Unlock the event loop object, inverse of [`lock`](#method.lock).
     pub fn unlock(&mut self) {
+        self.lock_depth.set(self.lock_depth.get().saturating_sub(1));
         unsafe { capi::pa_threaded_mainloop_unlock((*self._inner).ptr); }
     }
 
@@ -514,6 +548,26 @@ impl Mainloop {
         ::def::Retval(unsafe { capi::pa_threaded_mainloop_get_retval((*self._inner).ptr) })
     }
 
+    /// Get the exit given to the main loop’s quit routine, as per [`get_retval`].
+    ///
+    /// Along with [`quit_with`], this allows an exit reason determined within a callback to be
+    /// propagated to the application’s main thread, once the event loop thread has stopped.
+    ///
+    /// [`get_retval`]: #method.get_retval
+    /// [`quit_with`]: #method.quit_with
+    pub fn get_exit(&self) -> ::def::LoopExit {
+        self.get_retval().into()
+    }
+
+    /// Exit the event loop with the given exit code, as then returned by
+    /// [`get_exit`](#method.get_exit).
+    ///
+    /// This may be called from within the event loop thread, i.e. from callbacks, or with the lock
+    /// held. The event loop thread must still be stopped with [`stop`](#method.stop) afterwards.
+    pub fn quit_with(&self, code: ::def::RetvalActual) {
+        self.get_api().quit_with(code);
+    }
+
     /// Return the main loop abstraction layer vtable for this main loop.
     ///
     /// There is no need to free this object as it is owned by the loop and is destroyed when the
@@ -530,10 +584,43 @@ impl Mainloop {
     }
 
     /// Returns `true` when called from within the event loop thread.
+    ///
+    /// This is useful for callbacks which may be run either directly, or from within the event
+    /// loop thread (see the [`::mainloop`](../index.html#execution) documentation), to determine
+    /// whether or not the lock must be taken.
     pub fn in_thread(&self) -> bool {
         unsafe { capi::pa_threaded_mainloop_in_thread((*self._inner).ptr) != 0 }
     }
 
+    /// Run the callback once, from within the event loop thread, upon the next iteration of the
+    /// event loop.
+    ///
+    /// This is the way to act upon PulseAudio objects in response to something happening outside
+    /// of the event loop thread, such as in another thread, without holding the lock for the
+    /// duration. The lock is taken (and released again) as necessary in scheduling the callback,
+    /// thus this may be called whether or not the lock is already held, including from within the
+    /// event loop thread (in which case the callback runs upon the following iteration).
+    ///
+    /// The callback is run with the lock held, as with any other event dispatched.
+    pub fn once<F>(&mut self, callback: F)
+        where F: FnOnce() + 'static
+    {
+        let mut callback = Some(callback);
+        let wrapper = Box::new(move || {
+            if let Some(callback) = callback.take() {
+                callback();
+            }
+        });
+        let in_thread = self.in_thread();
+        if !in_thread {
+            self.lock();
+        }
+        super::api::Mainloop::once_event(self, wrapper);
+        if !in_thread {
+            self.unlock();
+        }
+    }
+
     /// Sets the name of the thread.
     pub fn set_name(&mut self, name: &str) {
         // Warning: New CStrings will be immediately freed if not bound to a variable, leading to
@@ -542,3 +629,75 @@ impl Mainloop {
         unsafe { capi::pa_threaded_mainloop_set_name((*self._inner).ptr, c_name.as_ptr()); }
     }
 }
+
+/// Guard of a running event loop thread, as returned by
+/// [`Mainloop::start_scoped`](struct.Mainloop.html#method.start_scoped).
+///
+/// Upon being dropped, the lock is taken, the registered contexts (those still existing) are
+/// disconnected, the lock released, and the thread stopped (joining it). The guard dereferences
+/// to the main loop.
+///
+/// The guard must not be dropped within the event loop thread, nor with the lock held (the thread
+/// would never stop). The exception is unwinding from a panic occurring with the lock held (taken
+/// through the main loop the guard was obtained from, or the guard itself), in which case the lock
+/// is released first.
+#[must_use = "the event loop thread is stopped immediately if the guard is not held"]
+pub struct MainloopGuard {
+    mainloop: Mainloop,
+    contexts: RefCell<Vec<Weak<RefCell<::context::Context>>>>,
+}
+
+impl MainloopGuard {
+    /// Register a context to be disconnected upon shutdown, before the thread is stopped.
+    ///
+    /// Only a weak reference to the context is held.
+    pub fn register_context(&self, context: &Rc<RefCell<::context::Context>>) {
+        let mut contexts = self.contexts.borrow_mut();
+        contexts.retain(|c| c.upgrade().is_some());
+        contexts.push(Rc::downgrade(context));
+    }
+}
+
+impl std::ops::Deref for MainloopGuard {
+    type Target = Mainloop;
+
+    fn deref(&self) -> &Mainloop {
+        &self.mainloop
+    }
+}
+
+impl std::ops::DerefMut for MainloopGuard {
+    fn deref_mut(&mut self) -> &mut Mainloop {
+        &mut self.mainloop
+    }
+}
+
+impl Drop for MainloopGuard {
+    fn drop(&mut self) {
+        let contexts: Vec<_> = self.contexts.borrow_mut().drain(..)
+            .filter_map(|c| c.upgrade())
+            .collect();
+        // A panic with the lock held leaves it held, the matching `unlock()` having been skipped
+        let depth = self.mainloop.lock_depth.get();
+        if depth > 0 {
+            debug_assert!(std::thread::panicking(), "MainloopGuard dropped with the lock held");
+            for _ in 0..depth {
+                self.mainloop.unlock();
+            }
+        }
+        self.mainloop.lock();
+        for context in &contexts {
+            // No borrow is held whilst disconnecting, since the state callback is called, which
+            // may well use the context
+            let ptr = match context.try_borrow() {
+                Ok(context) => context.ptr,
+                Err(_) => continue,
+            };
+            unsafe { capi::pa_context_disconnect(ptr) };
+        }
+        self.mainloop.unlock();
+        self.mainloop.stop();
+        // The contexts are only released once the thread is stopped
+        drop(contexts);
+    }
+}
@@ -14,14 +14,85 @@
 // if not, see <http://www.gnu.org/licenses/>.
 
 //! Asynchronous operations.
+//!
+//! # Completion
+//!
+//! The functions that create [`Operation`] objects take a callback that is called when the
+//! operation finishes. Alternatively, an `Operation` is also a `Future`, completing once it is no
+//! longer running, with the final [`State`]. Note that the mainloop must be iterated (i.e. run) for
+//! the operation to make progress.
+//!
+//! # Tracing
+//!
+//! With the `tracing` feature enabled, each operation is covered by a `debug` level span named
+//! `pa_operation`, from issue to completion, carrying the kind of operation (e.g.
+//! `introspect::get_sink_info_by_index`) and the index of the object it targets, if any, allowing
+//! the latency of server round-trips to be observed. An event with the final state is emitted
+//! within the span upon completion. The span remains open until completion even if the `Operation`
+//! object is dropped beforehand.
+//!
+//! [`Operation`]: struct.Operation.html
+//! [`State`]: enum.State.html
 
 use std;
 use capi;
+use std::convert::TryFrom;
 use std::os::raw::c_void;
+use error::{ObjectDead, ObjectKind, ReleaseSite};
 use std::ptr::null_mut;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
 
 use capi::pa_operation as OperationInternal;
-pub use capi::pa_operation_state_t as State;
+
+/// The state of an operation.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum State {
+    /// The operation is still running.
+    Running,
+    /// The operation has completed.
+    Done,
+    /// The operation has been cancelled. Operations may get cancelled by the application, or as a
+    /// result of the context getting disconnected while the operation is pending.
+    Cancelled,
+    /// A state not known to this binding, as may be introduced by newer versions of PulseAudio.
+    Other(i32),
+}
+
+impl From<State> for capi::pa_operation_state_t {
+    fn from(s: State) -> Self {
+        match s {
+            State::Running => capi::PA_OPERATION_RUNNING,
+            State::Done => capi::PA_OPERATION_DONE,
+            State::Cancelled => capi::PA_OPERATION_CANCELLED,
+            State::Other(v) => capi::pa_operation_state_t(v),
+        }
+    }
+}
+
+impl From<capi::pa_operation_state_t> for State {
+    fn from(s: capi::pa_operation_state_t) -> Self {
+        match s {
+            capi::PA_OPERATION_RUNNING => State::Running,
+            capi::PA_OPERATION_DONE => State::Done,
+            capi::PA_OPERATION_CANCELLED => State::Cancelled,
+            capi::pa_operation_state_t(v) => State::Other(v),
+        }
+    }
+}
+
+/// Strict conversion from a C value, failing with the value if it is not known to this binding.
+impl TryFrom<i32> for State {
+    type Error = i32;
+
+    fn try_from(v: i32) -> Result<Self, i32> {
+        match State::from(capi::pa_operation_state_t(v)) {
+            State::Other(v) => Err(v),
+            s => Ok(s),
+        }
+    }
+}
 
 /// An asynchronous operation object.
 ///
@@ -37,6 +108,11 @@ pub struct Operation<ClosureProto: ?Sized> {
     saved_cb: Option<*mut Box<ClosureProto>>,
     /// Saved multi-use state callback closure, for later destruction
     state_cb: NotifyCb,
+    /// Where the operation was cancelled, for diagnostics
+    released: ReleaseSite,
+    /// Span covering the operation, until completion.
+    #[cfg(feature = "tracing")]
+    span: SharedSpan,
 }
 
 unsafe impl<ClosureProto: ?Sized> Send for Operation<ClosureProto> {}
@@ -45,6 +121,9 @@ unsafe impl<ClosureProto: ?Sized> Sync for Operation<ClosureProto> {}
 type NotifyCb = ::callbacks::MultiUseCallback<dyn FnMut(),
     extern "C" fn(*mut OperationInternal, *mut c_void)>;
 
+#[cfg(feature = "tracing")]
+type SharedSpan = std::sync::Arc<std::sync::Mutex<Option<::tracing::Span>>>;
+
 impl<ClosureProto: ?Sized> Operation<ClosureProto> {
     /// Create a new `Operation` from an existing [`OperationInternal`](enum.OperationInternal.html)
     /// pointer. We also take a copy of the closure callback pointer, in order to free the memory
@@ -54,10 +133,34 @@ impl<ClosureProto: ?Sized> Operation<ClosureProto> {
     {
         assert_eq!(false, ptr.is_null());
         let saved_cb_actual = match saved_cb.is_null() {
-            true => Some(saved_cb),
-            false => None,
+            true => None,
+            false => Some(saved_cb),
         };
-        Self { ptr: ptr, saved_cb: saved_cb_actual, state_cb: Default::default() }
+        Self {
+            ptr: ptr,
+            saved_cb: saved_cb_actual,
+            state_cb: Default::default(),
+            released: Default::default(),
+            #[cfg(feature = "tracing")]
+            span: Default::default(),
+        }
+    }
+
+    /// Attach a tracing span to the operation, identifying the kind of operation, and the index of
+    /// the object it targets, if any. This does nothing without the `tracing` feature.
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    pub(crate) fn traced(self, kind: &'static str, index: Option<u32>) -> Self {
+        #[cfg(feature = "tracing")]
+        {
+            let mut op = self;
+            let span = ::tracing::debug_span!("pa_operation", kind = kind, index = ?index);
+            *op.span.lock().unwrap_or_else(|e| e.into_inner()) = Some(span);
+            // Install the state callback that closes the span upon completion
+            op.set_state_callback(None);
+            op
+        }
+        #[cfg(not(feature = "tracing"))]
+        self
     }
 
     /// Cancel the operation.
@@ -70,22 +173,38 @@ impl<ClosureProto: ?Sized> Operation<ClosureProto> {
     /// execution of that callback itself. This should go without saying, since it makes absolutely
     /// no sense to try and do this, but be aware that this is not supported by the C API and
     /// **will** break things.
-    pub fn cancel(&mut self) {
-        unsafe { capi::pa_operation_cancel(self.ptr); }
-        // Release the memory allocated for the closure.
-        // Note, we `take()` here to help avoid issues if this function is mistakenly called more
-        // than once.
-        let callback = self.saved_cb.take();
-        if let Some(ptr) = callback {
-            if !ptr.is_null() {
-                drop(unsafe { Box::from_raw(ptr as *mut Box<ClosureProto>) });
-            }
+    ///
+    /// Cancelling an operation that is no longer running fails with [`ObjectDead`]; in particular,
+    /// the closure of a completed operation has already been consumed by its callback, and is not
+    /// released again.
+    ///
+    /// [`ObjectDead`]: ../error/struct.ObjectDead.html
+    #[track_caller]
+    pub fn cancel(&mut self) -> Result<(), ObjectDead> {
+        let state = self.get_state();
+        let alive = self.check_alive();
+        if alive.is_ok() {
+            unsafe { capi::pa_operation_cancel(self.ptr); }
+            self.released.record();
         }
+        release_closure(&mut self.saved_cb, state);
+        alive
     }
 
     /// Return the current status of the operation
     pub fn get_state(&self) -> State {
-        unsafe { capi::pa_operation_get_state(self.ptr) }
+        unsafe { capi::pa_operation_get_state(self.ptr).into() }
+    }
+
+    /// Check that the operation is still running, failing with [`ObjectDead`] if it has completed
+    /// or been cancelled.
+    ///
+    /// [`ObjectDead`]: ../error/struct.ObjectDead.html
+    pub fn check_alive(&self) -> Result<(), ObjectDead> {
+        match self.get_state() {
+            State::Running => Ok(()),
+            _ => Err(self.released.dead(ObjectKind::Operation)),
+        }
     }
 
     /// Set the callback function that is called when the operation state changes.
@@ -95,17 +214,90 @@ impl<ClosureProto: ?Sized> Operation<ClosureProto> {
     /// callback is mainly useful, if you want to get called back also if the operation gets
     /// cancelled.
     pub fn set_state_callback(&mut self, callback: Option<Box<dyn FnMut() + 'static>>) {
+        #[cfg(feature = "tracing")]
+        let callback = self.wrap_state_callback(callback);
         let saved = &mut self.state_cb;
         *saved = NotifyCb::new(callback);
         let (cb_fn, cb_data) = saved.get_capi_params(notify_cb_proxy);
         unsafe { capi::pa_operation_set_state_callback(self.ptr, cb_fn, cb_data); }
     }
+
+    /// Wrap a state callback such that the span (if any) is closed upon completion.
+    #[cfg(feature = "tracing")]
+    fn wrap_state_callback(&self, mut callback: Option<Box<dyn FnMut() + 'static>>)
+        -> Option<Box<dyn FnMut() + 'static>>
+    {
+        if self.span.lock().unwrap_or_else(|e| e.into_inner()).is_none() {
+            return callback;
+        }
+        let span = self.span.clone();
+        let ptr = self.ptr;
+        Some(Box::new(move || {
+            let state: State = unsafe { capi::pa_operation_get_state(ptr).into() };
+            if state != State::Running {
+                if let Some(span) = span.lock().unwrap_or_else(|e| e.into_inner()).take() {
+                    finish_span(&span, state);
+                }
+            }
+            if let Some(ref mut callback) = callback {
+                (callback)();
+            }
+        }))
+    }
+
+    /// Hand the span (if any) over to a standalone state callback, keeping it open until the
+    /// operation completes, since this object is being destroyed.
+    #[cfg(feature = "tracing")]
+    fn detach_span(&mut self) {
+        let span = self.span.lock().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(span) = span {
+            match self.get_state() {
+                State::Running => {
+                    let userdata = Box::into_raw(Box::new(span)) as *mut c_void;
+                    unsafe { capi::pa_operation_set_state_callback(self.ptr, Some(span_cb_proxy),
+                        userdata) };
+                },
+                state => finish_span(&span, state),
+            }
+        }
+    }
+}
+
+impl<ClosureProto: ?Sized> Future for Operation<ClosureProto> {
+    type Output = State;
+
+    /// Polls for completion of the operation, giving the final state once it is no longer running.
+    ///
+    /// Note, this replaces any callback set with [`set_state_callback`], which is used for waking
+    /// the task.
+    ///
+    /// [`set_state_callback`]: #method.set_state_callback
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<State> {
+        let op = self.get_mut();
+        match op.get_state() {
+            State::Running => {
+                let waker = cx.waker().clone();
+                op.set_state_callback(Some(Box::new(move || waker.wake_by_ref())));
+                // Check again, in case completion raced with registering the callback
+                match op.get_state() {
+                    State::Running => Poll::Pending,
+                    s => Poll::Ready(s),
+                }
+            },
+            s => Poll::Ready(s),
+        }
+    }
 }
 
 impl<ClosureProto: ?Sized> Drop for Operation<ClosureProto> {
     fn drop(&mut self) {
         // Note, we deliberately do not destroy the `saved_cb` closure here. That should only be
         // destroyed either separately by a callback proxy, or by the `Operation`’s `cancel` method.
+        // The operation may however outlive this object (it is held by the context until finished),
+        // thus the state callback, which is about to be destroyed, must be unregistered.
+        unsafe { capi::pa_operation_set_state_callback(self.ptr, None, null_mut()) };
+        #[cfg(feature = "tracing")]
+        self.detach_span();
         unsafe { capi::pa_operation_unref(self.ptr) };
         self.ptr = null_mut::<OperationInternal>();
     }
@@ -121,3 +313,94 @@ fn notify_cb_proxy(_: *mut OperationInternal, userdata: *mut c_void) {
         (callback)();
     });
 }
+
+/// Release the memory allocated for the closure of an operation in the given state, unless already
+/// released by the callback proxy upon completion.
+///
+/// Note, we `take()` here to help avoid issues if this is mistakenly called more than once.
+fn release_closure<ClosureProto: ?Sized>(saved_cb: &mut Option<*mut Box<ClosureProto>>,
+    state: State)
+{
+    if let Some(ptr) = saved_cb.take() {
+        if state != State::Done {
+            drop(unsafe { Box::from_raw(ptr) });
+        }
+    }
+}
+
+/// Proxy for the standalone state callback of a detached span.
+/// Note, destroys the span once the operation is no longer running.
+#[cfg(feature = "tracing")]
+extern "C"
+fn span_cb_proxy(o: *mut OperationInternal, userdata: *mut c_void) {
+    let _ = std::panic::catch_unwind(|| {
+        let state: State = unsafe { capi::pa_operation_get_state(o).into() };
+        if state != State::Running {
+            unsafe { capi::pa_operation_set_state_callback(o, None, null_mut()) };
+            let span = unsafe { Box::from_raw(userdata as *mut ::tracing::Span) };
+            finish_span(&span, state);
+        }
+    });
+}
+
+/// Emit the completion event within the span of an operation.
+#[cfg(feature = "tracing")]
+fn finish_span(span: &::tracing::Span, state: State) {
+    span.in_scope(|| ::tracing::debug!(state = ?state, "operation finished"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    /// Counts drops of the closure holding it.
+    struct DropCounter(Rc<Cell<u32>>);
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    fn new_closure(drops: &Rc<Cell<u32>>) -> *mut Box<dyn FnMut()> {
+        let counter = DropCounter(Rc::clone(drops));
+        let closure: Box<dyn FnMut()> = Box::new(move || { let _ = &counter; });
+        Box::into_raw(Box::new(closure))
+    }
+
+    /// Test that cancelling a completed operation does not release its closure a second time, and
+    /// that that of a cancelled operation is released exactly once.
+    #[test]
+    fn release_closure_once() {
+        let drops = Rc::new(Cell::new(0));
+        let ptr = new_closure(&drops);
+        // The callback proxy consumes the closure upon completion
+        drop(unsafe { Box::from_raw(ptr) });
+        let mut saved_cb = Some(ptr);
+        release_closure(&mut saved_cb, State::Done);
+        release_closure(&mut saved_cb, State::Done);
+        assert_eq!(drops.get(), 1);
+        assert!(saved_cb.is_none());
+
+        let drops = Rc::new(Cell::new(0));
+        let mut saved_cb = Some(new_closure(&drops));
+        release_closure(&mut saved_cb, State::Cancelled);
+        release_closure(&mut saved_cb, State::Cancelled);
+        assert_eq!(drops.get(), 1);
+    }
+
+    #[test]
+    fn state_conversion() {
+        for &value in &[State::Running, State::Done, State::Cancelled] {
+            let raw = capi::pa_operation_state_t::from(value);
+            assert_eq!(State::from(raw), value);
+            assert_eq!(State::try_from(raw.0), Ok(value));
+        }
+        assert_eq!(State::from(capi::pa_operation_state_t(42)), State::Other(42));
+        assert_eq!(capi::pa_operation_state_t::from(State::Other(42)),
+            capi::pa_operation_state_t(42));
+        assert_eq!(State::try_from(42), Err(42));
+    }
+}
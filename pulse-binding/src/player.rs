@@ -0,0 +1,413 @@
+// Copyright 2017 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! High-level playback convenience.
+//!
+//! # Overview
+//!
+//! Playing a buffer of audio with the asynchronous API requires setting up a mainloop, a context
+//! and a stream, and waiting upon each to become ready. The [`Player`] type takes care of all of
+//! this, using a threaded mainloop under the hood, offering a simple interface for playback.
+//!
+//! Data can either be pushed with [`Player::play`], which blocks until all of the data has been
+//! written, or pulled from a callback set with [`Player::set_refill_callback`], which is asked for
+//! data whenever the server requests more. The buffers given to the refill callback are leased from
+//! a pre-allocated [`BufferPool`], thus pulling data does not allocate.
+//!
+//! ```rust,ignore
+//! extern crate libpulse_binding as pulse;
+//!
+//! use pulse::player::Player;
+//! use pulse::sample;
+//!
+//! let spec = sample::Spec {
+//!     format: sample::SAMPLE_S16NE,
+//!     channels: 2,
+//!     rate: 44100,
+//! };
+//! let mut player = Player::new("My application", "Music", &spec, None).unwrap();
+//! player.play(&data).unwrap();
+//! let success = player.drain().await;
+//! ```
+//!
+//! [`Player`]: struct.Player.html
+//! [`Player::play`]: struct.Player.html#method.play
+//! [`Player::set_refill_callback`]: struct.Player.html#method.set_refill_callback
+//! [`BufferPool`]: ../buffer_pool/struct.BufferPool.html
+
+use std;
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::task::{Context as TaskContext, Poll, Waker};
+use buffer_pool::BufferPool;
+use context::{self, Context};
+use error::PAErr;
+use mainloop::threaded::Mainloop;
+use stream::{self, Stream, SeekMode};
+
+pub(crate) type SharedMainloop = Rc<RefCell<Mainloop>>;
+pub(crate) type SharedContext = Rc<RefCell<Context>>;
+
+/// A refill callback, given a buffer to fill, returning the number of bytes filled.
+///
+/// This is called from the mainloop thread, thus must be `Send`.
+pub type RefillCb = Box<dyn FnMut(&mut [u8]) -> usize + Send + 'static>;
+
+/// Number of buffers of the refill buffer pool.
+const REFILL_BUFFERS: usize = 8;
+
+/// A simple playback stream, with its own connection and threaded mainloop.
+///
+/// See the [module level documentation](index.html) for details.
+pub struct Player {
+    // Note, order matters for destruction
+    stream: Rc<RefCell<Stream>>,
+    context: Rc<RefCell<Context>>,
+    mainloop: Rc<RefCell<Mainloop>>,
+    refill: Rc<RefCell<Option<RefillCb>>>,
+    pool: BufferPool,
+}
+
+impl Player {
+    /// Connect to the default server and create a playback stream on the default sink.
+    ///
+    /// This blocks until the stream is ready (or failed).
+    pub fn new(app_name: &str, stream_name: &str, spec: &::sample::Spec,
+        map: Option<&::channelmap::Map>) -> Result<Self, PAErr>
+    {
+        let (mainloop, context) = connect_context(app_name)?;
+
+        mainloop.borrow_mut().lock();
+        let stream = match create_stream(&mainloop, &context, stream_name, spec, map) {
+            Ok(stream) => stream,
+            Err(e) => {
+                mainloop.borrow_mut().unlock();
+                shutdown(&mainloop, &context);
+                return Err(e);
+            },
+        };
+        let result = stream.borrow_mut().connect_playback(None, None, stream::flags::NOFLAGS,
+            None, None);
+        let result = result.and_then(|_| wait_stream_ready(&mainloop, &context, &stream));
+        let refill = Rc::new(RefCell::new(None));
+        let tile_size = stream.borrow().tile_size().unwrap_or(0);
+        let pool = BufferPool::new(REFILL_BUFFERS, tile_size);
+        if result.is_ok() {
+            let ml_ref = Rc::clone(&mainloop);
+            let stream_ref = Rc::downgrade(&stream);
+            let refill_ref = Rc::clone(&refill);
+            let pool_ref = pool.clone();
+            stream.borrow_mut().set_write_callback(Some(Box::new(move |nbytes| {
+                if let Some(stream) = stream_ref.upgrade() {
+                    do_refill(&stream, &refill_ref, &pool_ref, nbytes);
+                }
+                unsafe { (*ml_ref.as_ptr()).signal(false); }
+            })));
+        }
+        mainloop.borrow_mut().unlock();
+
+        let player = Self { stream, context, mainloop, refill, pool };
+        result.map(|_| player)
+    }
+
+    /// Play the given data, blocking until all of it has been written to the server.
+    ///
+    /// The data should be a whole number of frames, any trailing partial frame is discarded.
+    ///
+    /// If playback is paused, data is written only as long as there is space in the buffer, since
+    /// it would otherwise never drain; should the buffer fill up, an error of [`Code::BadState`] is
+    /// returned, with the data not yet written discarded.
+    ///
+    /// [`Code::BadState`]: ../error/enum.Code.html#variant.BadState
+    pub fn play(&mut self, data: &[u8]) -> Result<(), PAErr> {
+        self.mainloop.borrow_mut().lock();
+        let result = self.play_locked(data);
+        self.mainloop.borrow_mut().unlock();
+        result
+    }
+
+    fn play_locked(&mut self, mut data: &[u8]) -> Result<(), PAErr> {
+        let frame_size = self.stream.borrow_mut().get_sample_spec().unwrap().frame_size();
+        while data.len() >= frame_size {
+            let state = self.stream.borrow().get_state();
+            if state != stream::State::Ready {
+                return Err(self.context.borrow().errno());
+            }
+            let writable = match self.stream.borrow().writable_size() {
+                Some(size) => size,
+                None => return Err(self.context.borrow().errno()),
+            };
+            let len = std::cmp::min(writable, data.len()) / frame_size * frame_size;
+            if len == 0 {
+                // Whilst paused, the buffer does not drain, thus waiting for space would block
+                // forever
+                if self.stream.borrow().is_paused() {
+                    return Err(PAErr::from(::error::Code::BadState));
+                }
+                self.mainloop.borrow_mut().wait();
+                continue;
+            }
+            self.stream.borrow_mut().write(&data[..len], None, 0, SeekMode::Relative)?;
+            data = &data[len..];
+        }
+        Ok(())
+    }
+
+    /// Set a callback to be asked for data whenever the server requests more, i.e. to
+    /// automatically refill the buffer.
+    ///
+    /// The callback is given a buffer to fill, and must return the number of bytes filled. It is
+    /// called from the mainloop thread, possibly several times per request, with buffers of up to
+    /// the stream’s tile size. Filling less than the buffer ends the request.
+    pub fn set_refill_callback(&mut self, callback: Option<RefillCb>) {
+        self.mainloop.borrow_mut().lock();
+        let have_callback = callback.is_some();
+        *self.refill.borrow_mut() = callback;
+        // Serve any request already outstanding
+        if have_callback {
+            let writable = self.stream.borrow().writable_size().unwrap_or(0);
+            if writable > 0 {
+                do_refill(&self.stream, &self.refill, &self.pool, writable);
+            }
+        }
+        self.mainloop.borrow_mut().unlock();
+    }
+
+    /// Set the volume of the stream.
+    pub fn set_volume(&mut self, volume: &::volume::ChannelVolumes) -> Result<(), PAErr> {
+        self.mainloop.borrow_mut().lock();
+        let result = match self.stream.borrow().get_index() {
+            Some(index) => {
+                let mut introspect = self.context.borrow().introspect();
+                drop(introspect.set_sink_input_volume(index, volume, None));
+                Ok(())
+            },
+            None => Err(PAErr::from(::error::Code::BadState)),
+        };
+        self.mainloop.borrow_mut().unlock();
+        result
+    }
+
+    /// Pause playback.
+    pub fn pause(&mut self) {
+        self.mainloop.borrow_mut().lock();
+        drop(self.stream.borrow_mut().pause());
+        self.mainloop.borrow_mut().unlock();
+    }
+
+    /// Resume playback.
+    pub fn resume(&mut self) {
+        self.mainloop.borrow_mut().lock();
+        drop(self.stream.borrow_mut().resume());
+        self.mainloop.borrow_mut().unlock();
+    }
+
+    /// Returns `true` if playback is paused.
+    pub fn is_paused(&self) -> bool {
+        self.mainloop.borrow_mut().lock();
+        let paused = self.stream.borrow().is_paused();
+        self.mainloop.borrow_mut().unlock();
+        paused
+    }
+
+    /// Drain the stream, i.e. wait for all data written to have been played.
+    ///
+    /// This returns a future, which completes once draining has finished, giving `true` on success.
+    pub fn drain(&mut self) -> Drain {
+        let shared = Arc::new(Mutex::new(DrainState { result: None, waker: None }));
+        let shared_ref = Arc::clone(&shared);
+        self.mainloop.borrow_mut().lock();
+        let op = self.stream.borrow_mut().drain(Some(Box::new(move |success| {
+            let mut state = shared_ref.lock().unwrap();
+            state.result = Some(success);
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        })));
+        if op.is_err() {
+            // Staged data could not be written, thus the drain was never issued
+            shared.lock().unwrap().result = Some(false);
+        }
+        self.mainloop.borrow_mut().unlock();
+        Drain { shared }
+    }
+}
+
+impl Drop for Player {
+    fn drop(&mut self) {
+        self.mainloop.borrow_mut().lock();
+        self.stream.borrow_mut().set_write_callback(None);
+        let _ = self.stream.borrow_mut().disconnect();
+        self.mainloop.borrow_mut().unlock();
+        shutdown(&self.mainloop, &self.context);
+    }
+}
+
+/// State shared between a [`Drain`] future and the drain callback.
+///
+/// [`Drain`]: struct.Drain.html
+struct DrainState {
+    result: Option<bool>,
+    waker: Option<Waker>,
+}
+
+/// Future returned by [`Player::drain`], giving `true` on success.
+///
+/// [`Player::drain`]: struct.Player.html#method.drain
+pub struct Drain {
+    shared: Arc<Mutex<DrainState>>,
+}
+
+impl Future for Drain {
+    type Output = bool;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut TaskContext) -> Poll<Self::Output> {
+        let mut state = self.shared.lock().unwrap();
+        match state.result {
+            Some(success) => Poll::Ready(success),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            },
+        }
+    }
+}
+
+/// Ask the refill callback (if any) for up to `nbytes` of data, in buffers leased from the pool,
+/// and write it. Requests are cut short should the pool run out of buffers.
+///
+/// Must be called with the mainloop lock held.
+fn do_refill(stream: &Rc<RefCell<Stream>>, refill: &Rc<RefCell<Option<RefillCb>>>,
+    pool: &BufferPool, mut nbytes: usize)
+{
+    let mut refill = refill.borrow_mut();
+    let callback = match refill.as_mut() {
+        Some(callback) => callback,
+        None => return,
+    };
+    let mut stream = stream.borrow_mut();
+    let frame_size = stream.get_sample_spec().unwrap().frame_size();
+    while nbytes >= frame_size {
+        let mut buffer = match pool.lease() {
+            Some(buffer) => buffer,
+            None => break,
+        };
+        let len = std::cmp::min(buffer.len(), nbytes) / frame_size * frame_size;
+        if len == 0 {
+            break;
+        }
+        let filled = std::cmp::min(callback(&mut buffer[..len]), len) / frame_size * frame_size;
+        if filled == 0 || stream.write_pooled(buffer, filled, 0, SeekMode::Relative).is_err() {
+            break;
+        }
+        if filled < len {
+            break;
+        }
+        nbytes -= filled;
+    }
+}
+
+/// Create a threaded mainloop and context, connected to the default server.
+///
+/// This blocks until the context is ready (or failed).
+pub(crate) fn connect_context(app_name: &str)
+    -> Result<(SharedMainloop, SharedContext), PAErr>
+{
+    let mainloop = match Mainloop::new() {
+        Some(mainloop) => Rc::new(RefCell::new(mainloop)),
+        None => return Err(PAErr::from(::error::Code::Internal)),
+    };
+    let context = match Context::new(&*mainloop.borrow(), app_name) {
+        Some(context) => Rc::new(RefCell::new(context)),
+        None => return Err(PAErr::from(::error::Code::Internal)),
+    };
+
+    {
+        let ml_ref = Rc::clone(&mainloop);
+        context.borrow_mut().set_state_callback(Some(Box::new(move || {
+            unsafe { (*ml_ref.as_ptr()).signal(false); }
+        })));
+    }
+    context.borrow_mut().connect(None, context::flags::NOFLAGS, None)?;
+
+    mainloop.borrow_mut().lock();
+    if let Err(e) = mainloop.borrow_mut().start() {
+        mainloop.borrow_mut().unlock();
+        return Err(e);
+    }
+    let result = loop {
+        let state = context.borrow().get_state();
+        match state {
+            context::State::Ready => break Ok(()),
+            context::State::Failed |
+            context::State::Terminated => break Err(context.borrow().errno()),
+            _ => mainloop.borrow_mut().wait(),
+        }
+    };
+    context.borrow_mut().set_state_callback(None);
+    mainloop.borrow_mut().unlock();
+
+    if let Err(e) = result {
+        mainloop.borrow_mut().stop();
+        return Err(e);
+    }
+    Ok((mainloop, context))
+}
+
+/// Create a new stream, with a state callback signalling the mainloop.
+///
+/// Must be called with the mainloop lock held.
+pub(crate) fn create_stream(mainloop: &Rc<RefCell<Mainloop>>, context: &Rc<RefCell<Context>>,
+    name: &str, spec: &::sample::Spec, map: Option<&::channelmap::Map>)
+    -> Result<Rc<RefCell<Stream>>, PAErr>
+{
+    let stream = match Stream::new(&mut context.borrow_mut(), name, spec, map) {
+        Some(stream) => Rc::new(RefCell::new(stream)),
+        None => return Err(context.borrow().errno()),
+    };
+    let ml_ref = Rc::clone(mainloop);
+    stream.borrow_mut().set_state_callback(Some(Box::new(move || {
+        unsafe { (*ml_ref.as_ptr()).signal(false); }
+    })));
+    Ok(stream)
+}
+
+/// Wait for a connecting stream to become ready.
+///
+/// Must be called with the mainloop lock held.
+pub(crate) fn wait_stream_ready(mainloop: &Rc<RefCell<Mainloop>>, context: &Rc<RefCell<Context>>,
+    stream: &Rc<RefCell<Stream>>) -> Result<(), PAErr>
+{
+    loop {
+        let state = stream.borrow().get_state();
+        match state {
+            stream::State::Ready => return Ok(()),
+            stream::State::Failed |
+            stream::State::Terminated => return Err(context.borrow().errno()),
+            _ => mainloop.borrow_mut().wait(),
+        }
+    }
+}
+
+/// Disconnect the context and stop the mainloop.
+pub(crate) fn shutdown(mainloop: &Rc<RefCell<Mainloop>>, context: &Rc<RefCell<Context>>) {
+    mainloop.borrow_mut().lock();
+    context.borrow_mut().disconnect();
+    mainloop.borrow_mut().unlock();
+    mainloop.borrow_mut().stop();
+}
@@ -21,6 +21,7 @@ use std::os::raw::{c_char, c_void};
 use std::ffi::{CStr, CString};
 use std::ptr::{null, null_mut};
 use std::marker::PhantomData;
+use std::collections::BTreeMap;
 use error::PAErr;
 
 pub(crate) use capi::pa_proplist as ProplistInternal;
@@ -133,6 +134,69 @@ pub mod properties {
     /// For PCM formats: the channel map of the stream as returned by
     /// [`channelmap::Map::print`](../../channelmap/struct.Map.html#method.print).
     pub const FORMAT_CHANNEL_MAP: &str = capi::PA_PROP_FORMAT_CHANNEL_MAP;
+
+    /// A typed property key, for the properties commonly changed by clients at runtime, such as
+    /// with [`Context::update_client_properties`].
+    ///
+    /// [`Context::update_client_properties`]: ../../context/struct.Context.html#method.update_client_properties
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    pub enum Key {
+        /// [`APPLICATION_NAME`](constant.APPLICATION_NAME.html).
+        ApplicationName,
+        /// [`APPLICATION_ID`](constant.APPLICATION_ID.html).
+        ApplicationId,
+        /// [`APPLICATION_VERSION`](constant.APPLICATION_VERSION.html).
+        ApplicationVersion,
+        /// [`APPLICATION_ICON_NAME`](constant.APPLICATION_ICON_NAME.html).
+        ApplicationIconName,
+        /// [`APPLICATION_LANGUAGE`](constant.APPLICATION_LANGUAGE.html).
+        ApplicationLanguage,
+        /// [`MEDIA_NAME`](constant.MEDIA_NAME.html).
+        MediaName,
+        /// [`MEDIA_ROLE`](constant.MEDIA_ROLE.html).
+        MediaRole,
+        /// [`MEDIA_ICON_NAME`](constant.MEDIA_ICON_NAME.html).
+        MediaIconName,
+        /// [`WINDOW_NAME`](constant.WINDOW_NAME.html).
+        WindowName,
+        /// Any other key.
+        Other(String),
+    }
+
+    impl Key {
+        /// Get the key string.
+        pub fn as_str(&self) -> &str {
+            match *self {
+                Key::ApplicationName => APPLICATION_NAME,
+                Key::ApplicationId => APPLICATION_ID,
+                Key::ApplicationVersion => APPLICATION_VERSION,
+                Key::ApplicationIconName => APPLICATION_ICON_NAME,
+                Key::ApplicationLanguage => APPLICATION_LANGUAGE,
+                Key::MediaName => MEDIA_NAME,
+                Key::MediaRole => MEDIA_ROLE,
+                Key::MediaIconName => MEDIA_ICON_NAME,
+                Key::WindowName => WINDOW_NAME,
+                Key::Other(ref key) => key,
+            }
+        }
+    }
+
+    impl AsRef<str> for Key {
+        fn as_ref(&self) -> &str {
+            self.as_str()
+        }
+    }
+
+    impl<'a> From<&'a str> for Key {
+        /// Get the key for a key string, giving the typed variant for those known.
+        fn from(key: &'a str) -> Self {
+            let known = [Key::ApplicationName, Key::ApplicationId, Key::ApplicationVersion,
+                Key::ApplicationIconName, Key::ApplicationLanguage, Key::MediaName, Key::MediaRole,
+                Key::MediaIconName, Key::WindowName];
+            known.iter().find(|k| k.as_str() == key).cloned()
+                .unwrap_or_else(|| Key::Other(key.to_string()))
+        }
+    }
 }
 
 /// A property list object. Basically a dictionary with ASCII strings as keys and arbitrary data as
@@ -469,6 +533,322 @@ impl Proplist {
     pub fn equal_to(&self, to: &Self) -> bool {
         unsafe { capi::pa_proplist_equal(self.0.ptr, to.0.ptr) != 0 }
     }
+
+    /// Compare with a newer version of the property list, such as that fetched upon a change
+    /// subscription event for a client or stream, giving the keys added, removed and changed.
+    pub fn diff(&self, other: &Self) -> ProplistDiff {
+        ProplistDiff::compute(&self.entry_map(), &other.entry_map())
+    }
+
+    /// Get a copy of self with property list “other” merged into it, adhering to the merge mode
+    /// specified, exactly as the server would apply an update of the given mode.
+    ///
+    /// Comparing the result with [`diff`](#method.diff) shows what an update would change, before
+    /// sending it.
+    pub fn merge_with(&self, mode: UpdateMode, other: &Self) -> Self {
+        let mut merged = self.clone();
+        merged.merge(other, mode);
+        merged
+    }
+
+    /// Get all entries, sorted by key.
+    fn entry_map(&self) -> BTreeMap<String, Vec<u8>> {
+        self.iter()
+            .map(|key| {
+                let value = self.get(&key).map(|v| v.to_vec()).unwrap_or_default();
+                (key, value)
+            })
+            .collect()
+    }
+
+    /// Get an immutable iterator over the list’s entries, giving each key along with its value.
+    ///
+    /// The same restrictions apply as for [`iter`](#method.iter).
+    pub fn entries(&self) -> Entries<'_> {
+        Entries { list: self, keys: self.iter() }
+    }
+
+    /// Append a new entry to the property list, of either kind of value, possibly overwriting an
+    /// already existing entry with the same key.
+    pub fn set_value(&mut self, key: &str, value: &PropValue) -> Result<(), ()> {
+        match *value {
+            PropValue::String(ref s) => self.sets(key, s),
+            PropValue::Bytes(ref b) => self.set(key, b),
+        }
+    }
+}
+
+/// The value of a property list entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PropValue {
+    /// A string value.
+    String(String),
+    /// An arbitrary data value, not being a valid string.
+    Bytes(Vec<u8>),
+}
+
+impl PropValue {
+    /// Convert the raw value of an entry, as given by [`Proplist::get`]. String values are stored
+    /// nul terminated; those not valid UTF-8 are treated as arbitrary data.
+    ///
+    /// [`Proplist::get`]: struct.Proplist.html#method.get
+    fn from_raw(data: &[u8]) -> Self {
+        if let Some((&0, string)) = data.split_last() {
+            if !string.contains(&0) {
+                if let Ok(s) = std::str::from_utf8(string) {
+                    return PropValue::String(s.to_string());
+                }
+            }
+        }
+        PropValue::Bytes(data.to_vec())
+    }
+
+    /// Get the value as a string, if it is one.
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            PropValue::String(ref s) => Some(s),
+            PropValue::Bytes(_) => None,
+        }
+    }
+}
+
+impl<'a> From<&'a str> for PropValue {
+    fn from(s: &'a str) -> Self {
+        PropValue::String(s.to_string())
+    }
+}
+
+impl From<String> for PropValue {
+    fn from(s: String) -> Self {
+        PropValue::String(s)
+    }
+}
+
+impl<'a> From<&'a [u8]> for PropValue {
+    fn from(b: &'a [u8]) -> Self {
+        PropValue::Bytes(b.to_vec())
+    }
+}
+
+impl From<Vec<u8>> for PropValue {
+    fn from(b: Vec<u8>) -> Self {
+        PropValue::Bytes(b)
+    }
+}
+
+/// Proplist entry iterator, giving each key along with its value. Returned by the
+/// [`entries`](struct.Proplist.html#method.entries) method.
+pub struct Entries<'a> {
+    list: &'a Proplist,
+    keys: Iterator<'a>,
+}
+
+impl<'a> std::iter::Iterator for Entries<'a> {
+    type Item = (String, PropValue);
+    fn next(&mut self) -> Option<Self::Item> {
+        let key = self.keys.next()?;
+        let value = PropValue::from_raw(self.list.get(&key).unwrap_or(&[]));
+        Some((key, value))
+    }
+}
+
+impl<'a> IntoIterator for &'a Proplist {
+    type Item = (String, PropValue);
+    type IntoIter = Entries<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries()
+    }
+}
+
+impl<K: AsRef<str>, V: Into<PropValue>> Extend<(K, V)> for Proplist {
+    /// Append the entries, overwriting any already existing ones with the same key.
+    ///
+    /// Panics if a key is invalid (see [`key_is_valid`](#method.key_is_valid)).
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            let key = key.as_ref();
+            if self.set_value(key, &value.into()).is_err() {
+                panic!("invalid property list key: {:?}", key);
+            }
+        }
+    }
+}
+
+impl<K: AsRef<str>, V: Into<PropValue>> std::iter::FromIterator<(K, V)> for Proplist {
+    /// Create a property list from the entries, with later entries overwriting earlier ones with
+    /// the same key.
+    ///
+    /// Panics if a key is invalid (see [`key_is_valid`](#method.key_is_valid)).
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut list = Proplist::new().unwrap();
+        list.extend(iter);
+        list
+    }
+}
+
+/// The role of a stream’s media, as given by the [`properties::MEDIA_ROLE`] property.
+///
+/// Role based routing and ducking (e.g. by the `module-intended-roles` and `module-role-cork`
+/// server modules) match these exact strings, thus setting the role with [`set_media_role`]
+/// (or [`Stream::set_media_role`], or [`ClientPropertiesUpdate::media_role`]) is preferable to
+/// setting the property by hand. This also sets the related properties of the role, as given by
+/// [`related_properties`].
+///
+/// [`properties::MEDIA_ROLE`]: properties/constant.MEDIA_ROLE.html
+/// [`set_media_role`]: struct.Proplist.html#method.set_media_role
+/// [`Stream::set_media_role`]: ../stream/struct.Stream.html#method.set_media_role
+/// [`ClientPropertiesUpdate::media_role`]: ../context/client_properties/struct.ClientPropertiesUpdate.html#method.media_role
+/// [`related_properties`]: #method.related_properties
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum MediaRole {
+    /// Music playback.
+    Music,
+    /// Video playback (including its audio).
+    Video,
+    /// Game audio.
+    Game,
+    /// Event sounds, such as notifications.
+    Event,
+    /// Telephony, e.g. VoIP calls.
+    Phone,
+    /// Animations.
+    Animation,
+    /// Audio production.
+    Production,
+    /// Accessibility, e.g. screen readers.
+    A11y,
+}
+
+impl MediaRole {
+    /// All roles.
+    pub const ALL: [MediaRole; 8] = [MediaRole::Music, MediaRole::Video, MediaRole::Game,
+        MediaRole::Event, MediaRole::Phone, MediaRole::Animation, MediaRole::Production,
+        MediaRole::A11y];
+
+    /// Get the property value of the role.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            MediaRole::Music => "music",
+            MediaRole::Video => "video",
+            MediaRole::Game => "game",
+            MediaRole::Event => "event",
+            MediaRole::Phone => "phone",
+            MediaRole::Animation => "animation",
+            MediaRole::Production => "production",
+            MediaRole::A11y => "a11y",
+        }
+    }
+
+    /// Get the role of a property value, if known.
+    pub fn from_name(s: &str) -> Option<Self> {
+        MediaRole::ALL.iter().find(|r| r.as_str() == s).cloned()
+    }
+
+    /// Get the properties, other than [`properties::MEDIA_ROLE`], expected for streams of the role,
+    /// as key-value pairs.
+    ///
+    /// Phone streams want echo cancellation (the [`properties::FILTER_WANT`] property), as applied
+    /// by the `module-filter-apply` server module.
+    ///
+    /// [`properties::MEDIA_ROLE`]: properties/constant.MEDIA_ROLE.html
+    /// [`properties::FILTER_WANT`]: properties/constant.FILTER_WANT.html
+    pub fn related_properties(&self) -> &'static [(&'static str, &'static str)] {
+        match *self {
+            MediaRole::Phone => &[(properties::FILTER_WANT, "echo-cancel")],
+            _ => &[],
+        }
+    }
+}
+
+impl std::fmt::Display for MediaRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Proplist {
+    /// Set the media role, and the related properties of the role, overwriting any existing
+    /// entries. See [`MediaRole`](enum.MediaRole.html).
+    pub fn set_media_role(&mut self, role: MediaRole) -> Result<(), ()> {
+        self.sets(properties::MEDIA_ROLE, role.as_str())?;
+        for &(key, value) in role.related_properties() {
+            self.sets(key, value)?;
+        }
+        Ok(())
+    }
+
+    /// Get the media role, if set to a known role.
+    pub fn media_role(&self) -> Option<MediaRole> {
+        self.gets(properties::MEDIA_ROLE).and_then(|s| MediaRole::from_name(&s))
+    }
+}
+
+/// Differences between two property lists, as given by [`Proplist::diff`]. Keys are sorted.
+///
+/// [`Proplist::diff`]: struct.Proplist.html#method.diff
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProplistDiff {
+    /// Keys present only in the newer list.
+    pub added: Vec<String>,
+    /// Keys present only in the older list.
+    pub removed: Vec<String>,
+    /// Keys present in both, with differing values.
+    pub changed: Vec<String>,
+}
+
+impl ProplistDiff {
+    fn compute(old: &BTreeMap<String, Vec<u8>>, new: &BTreeMap<String, Vec<u8>>) -> Self {
+        let mut diff = ProplistDiff::default();
+        for (key, value) in new {
+            match old.get(key) {
+                None => diff.added.push(key.clone()),
+                Some(old_value) if old_value != value => diff.changed.push(key.clone()),
+                Some(_) => {},
+            }
+        }
+        diff.removed = old.keys().filter(|k| !new.contains_key(*k)).cloned().collect();
+        diff
+    }
+
+    /// Returns `true` if the property lists compared are equal.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Get the minimal update turning the older property list compared into `new` (the newer one).
+    ///
+    /// Gives the entries to send with [`UpdateMode::Replace`], and the keys to remove (e.g. with
+    /// [`Context::proplist_remove`]). Either is empty if there is nothing of the sort to do.
+    ///
+    /// [`UpdateMode::Replace`]: enum.UpdateMode.html#variant.Replace
+    /// [`Context::proplist_remove`]: ../context/struct.Context.html#method.proplist_remove
+    pub fn update_payload(&self, new: &Proplist) -> (Proplist, Vec<String>) {
+        let mut entries = Proplist::new().unwrap();
+        for key in self.added.iter().chain(self.changed.iter()) {
+            if let Some(value) = new.get(key) {
+                entries.set(key, value).unwrap();
+            }
+        }
+        (entries, self.removed.clone())
+    }
+}
+
+impl std::fmt::Display for ProplistDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut first = true;
+        let groups = [("+", &self.added), ("-", &self.removed), ("~", &self.changed)];
+        for &(sign, keys) in groups.iter() {
+            for key in keys {
+                if !first {
+                    f.write_str(", ")?;
+                }
+                first = false;
+                write!(f, "{}{}", sign, key)?;
+            }
+        }
+        Ok(())
+    }
 }
 
 impl Drop for ProplistInner {
@@ -521,4 +901,49 @@ mod tests {
             println!("key: {}", key);
         }
     }
+
+    #[test]
+    fn diff_entries() {
+        let map = |entries: &[(&str, &str)]| -> BTreeMap<String, Vec<u8>> {
+            entries.iter().map(|&(k, v)| (k.to_string(), v.as_bytes().to_vec())).collect()
+        };
+        let old = map(&[("application.name", "foo"), ("media.role", "music"), ("a", "1")]);
+        let new = map(&[("application.name", "foo"), ("media.role", "video"), ("b", "2")]);
+
+        let diff = ProplistDiff::compute(&old, &new);
+        assert_eq!(diff.added, vec!["b".to_string()]);
+        assert_eq!(diff.removed, vec!["a".to_string()]);
+        assert_eq!(diff.changed, vec!["media.role".to_string()]);
+        assert_eq!(diff.to_string(), "+b, -a, ~media.role");
+
+        assert!(ProplistDiff::compute(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn prop_value_from_raw() {
+        assert_eq!(PropValue::from_raw(b"music\0"), PropValue::String("music".to_string()));
+        assert_eq!(PropValue::from_raw(b"\0"), PropValue::String(String::new()));
+        assert_eq!(PropValue::from_raw(b"music"), PropValue::Bytes(b"music".to_vec()));
+        assert_eq!(PropValue::from_raw(b"a\0b\0"), PropValue::Bytes(b"a\0b\0".to_vec()));
+        assert_eq!(PropValue::from_raw(b"\xff\0"), PropValue::Bytes(b"\xff\0".to_vec()));
+    }
+
+    #[test]
+    fn typed_keys() {
+        use self::properties::Key;
+        assert_eq!(Key::MediaRole.as_str(), "media.role");
+        assert_eq!(Key::from("application.icon_name"), Key::ApplicationIconName);
+        assert_eq!(Key::from("foo.bar"), Key::Other("foo.bar".to_string()));
+        assert_eq!(Key::Other("foo.bar".to_string()).as_str(), "foo.bar");
+    }
+
+    #[test]
+    fn media_roles() {
+        for role in MediaRole::ALL.iter() {
+            assert_eq!(MediaRole::from_name(role.as_str()), Some(*role));
+        }
+        assert_eq!(MediaRole::A11y.to_string(), "a11y");
+        assert_eq!(MediaRole::from_name("test"), None);
+        assert_eq!(MediaRole::Phone.related_properties(), &[("filter.want", "echo-cancel")]);
+    }
 }
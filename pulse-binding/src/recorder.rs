@@ -0,0 +1,256 @@
+// Copyright 2017 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! High-level recording convenience.
+//!
+//! # Overview
+//!
+//! The [`Recorder`] type is the recording companion of [`::player::Player`], taking care of setting
+//! up a threaded mainloop, a context and a record stream, offering a simple interface for
+//! recording.
+//!
+//! Recorded audio is delivered in chunks, either to a callback set with
+//! [`Recorder::set_data_callback`], or through a bounded channel obtained with
+//! [`Recorder::channel`]. With the `ringbuf` feature enabled, it can instead be written directly
+//! into a lock-free single-producer single-consumer ring buffer with [`Recorder::ring_buffer`],
+//! for consumption by a real-time thread, avoiding the allocation of each chunk made by the
+//! channel.
+//!
+//! ```rust,ignore
+//! extern crate libpulse_binding as pulse;
+//!
+//! use pulse::recorder::Recorder;
+//! use pulse::sample;
+//!
+//! let spec = sample::Spec {
+//!     format: sample::SAMPLE_S16NE,
+//!     channels: 1,
+//!     rate: 16000,
+//! };
+//! let mut recorder = Recorder::new("My application", "Dictation", &spec, None, None).unwrap();
+//! let (chunks, _dropped) = recorder.channel(32);
+//! for chunk in chunks.iter() {
+//!     // Process the chunk
+//! }
+//! ```
+//!
+//! [`Recorder`]: struct.Recorder.html
+//! [`Recorder::set_data_callback`]: struct.Recorder.html#method.set_data_callback
+//! [`Recorder::channel`]: struct.Recorder.html#method.channel
+//! [`Recorder::ring_buffer`]: struct.Recorder.html#method.ring_buffer
+//! [`::player::Player`]: ../player/struct.Player.html
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use context::Context;
+use error::PAErr;
+use mainloop::threaded::Mainloop;
+use player::{connect_context, create_stream, wait_stream_ready, shutdown};
+use stream::{self, Stream, PeekResult};
+
+/// A data callback, given each chunk of recorded audio.
+///
+/// This is called from the mainloop thread, thus must be `Send`.
+pub type DataCb = Box<dyn FnMut(&[u8]) + Send + 'static>;
+
+/// A simple record stream, with its own connection and threaded mainloop.
+///
+/// See the [module level documentation](index.html) for details.
+pub struct Recorder {
+    // Note, order matters for destruction
+    stream: Rc<RefCell<Stream>>,
+    context: Rc<RefCell<Context>>,
+    mainloop: Rc<RefCell<Mainloop>>,
+    data_cb: Rc<RefCell<Option<DataCb>>>,
+    spec: ::sample::Spec,
+}
+
+impl Recorder {
+    /// Connect to the default server and create a record stream on the specified source, or the
+    /// default source if `None`.
+    ///
+    /// This blocks until the stream is ready (or failed). Note that the stream’s sample spec may
+    /// differ from that requested, see [`get_sample_spec`].
+    ///
+    /// [`get_sample_spec`]: #method.get_sample_spec
+    pub fn new(app_name: &str, stream_name: &str, spec: &::sample::Spec,
+        map: Option<&::channelmap::Map>, source: Option<&str>) -> Result<Self, PAErr>
+    {
+        let (mainloop, context) = connect_context(app_name)?;
+
+        mainloop.borrow_mut().lock();
+        let stream = match create_stream(&mainloop, &context, stream_name, spec, map) {
+            Ok(stream) => stream,
+            Err(e) => {
+                mainloop.borrow_mut().unlock();
+                shutdown(&mainloop, &context);
+                return Err(e);
+            },
+        };
+        let data_cb = Rc::new(RefCell::new(None));
+        {
+            let stream_ref = Rc::downgrade(&stream);
+            let data_cb_ref = Rc::clone(&data_cb);
+            stream.borrow_mut().set_read_callback(Some(Box::new(move |_| {
+                if let Some(stream) = stream_ref.upgrade() {
+                    deliver(&stream, &data_cb_ref);
+                }
+            })));
+        }
+        let result = stream.borrow_mut().connect_record(source, None, stream::flags::NOFLAGS);
+        let result = result.and_then(|_| wait_stream_ready(&mainloop, &context, &stream));
+        let actual_spec = match stream.borrow_mut().get_sample_spec() {
+            Some(ss) => *ss,
+            None => *spec,
+        };
+        mainloop.borrow_mut().unlock();
+
+        let recorder = Self { stream, context, mainloop, data_cb, spec: actual_spec };
+        result.map(|_| recorder)
+    }
+
+    /// Get the sample spec negotiated with the server, which describes the recorded data.
+    pub fn get_sample_spec(&self) -> ::sample::Spec {
+        self.spec
+    }
+
+    /// Set the callback to be given each chunk of recorded audio.
+    ///
+    /// The callback is called from the mainloop thread. Data recorded while no callback is set is
+    /// discarded.
+    pub fn set_data_callback(&mut self, callback: Option<DataCb>) {
+        self.mainloop.borrow_mut().lock();
+        *self.data_cb.borrow_mut() = callback;
+        self.mainloop.borrow_mut().unlock();
+    }
+
+    /// Deliver recorded audio chunks through a bounded channel, holding up to `bound` chunks.
+    ///
+    /// This replaces any callback set with [`set_data_callback`]. Since the mainloop thread must
+    /// not block, chunks arriving while the channel is full are dropped. The returned counter gives
+    /// the number of chunks dropped so far, allowing overflow to be detected.
+    ///
+    /// [`set_data_callback`]: #method.set_data_callback
+    pub fn channel(&mut self, bound: usize) -> (Receiver<Vec<u8>>, Arc<AtomicUsize>) {
+        let (sender, receiver) = mpsc::sync_channel(bound);
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let dropped_ref = Arc::clone(&dropped);
+        self.set_data_callback(Some(Box::new(move |data| {
+            if sender.try_send(data.to_vec()).is_err() {
+                dropped_ref.fetch_add(1, Ordering::Relaxed);
+            }
+        })));
+        (receiver, dropped)
+    }
+
+    /// Deliver recorded audio by writing it directly into the given lock-free ring buffer producer,
+    /// such as that of a `ringbuf::HeapRb<u8>`, whose consumer is read from another thread.
+    ///
+    /// This replaces any callback set with [`set_data_callback`]. No allocation is made per chunk.
+    /// Since the mainloop thread must not block, audio arriving while the ring buffer is full is
+    /// dropped, in whole frames, such that the data remains frame aligned. The returned counter
+    /// gives the number of bytes dropped so far.
+    ///
+    /// [`set_data_callback`]: #method.set_data_callback
+    #[cfg(feature = "ringbuf")]
+    pub fn ring_buffer<P>(&mut self, mut producer: P) -> Arc<AtomicUsize>
+        where P: ::ringbuf::traits::Producer<Item = u8> + Send + 'static
+    {
+        let frame_size = self.spec.frame_size();
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let dropped_ref = Arc::clone(&dropped);
+        self.set_data_callback(Some(Box::new(move |data| {
+            let vacant = producer.vacant_len();
+            let len = std::cmp::min(data.len(), vacant - vacant % frame_size);
+            let written = producer.push_slice(&data[..len]);
+            if written < data.len() {
+                dropped_ref.fetch_add(data.len() - written, Ordering::Relaxed);
+            }
+        })));
+        dropped
+    }
+
+    /// Move recording to the source with the specified name.
+    pub fn set_source(&mut self, name: &str) -> Result<(), PAErr> {
+        self.mainloop.borrow_mut().lock();
+        let result = match self.stream.borrow().get_index() {
+            Some(index) => {
+                let mut introspect = self.context.borrow().introspect();
+                drop(introspect.move_source_output_by_name(index, name, None));
+                Ok(())
+            },
+            None => Err(PAErr::from(::error::Code::BadState)),
+        };
+        self.mainloop.borrow_mut().unlock();
+        result
+    }
+
+    /// Pause recording.
+    pub fn pause(&mut self) {
+        self.mainloop.borrow_mut().lock();
+        drop(self.stream.borrow_mut().pause());
+        self.mainloop.borrow_mut().unlock();
+    }
+
+    /// Resume recording.
+    pub fn resume(&mut self) {
+        self.mainloop.borrow_mut().lock();
+        drop(self.stream.borrow_mut().resume());
+        self.mainloop.borrow_mut().unlock();
+    }
+
+    /// Returns `true` if recording is paused.
+    pub fn is_paused(&self) -> bool {
+        self.mainloop.borrow_mut().lock();
+        let paused = self.stream.borrow().is_paused();
+        self.mainloop.borrow_mut().unlock();
+        paused
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        self.mainloop.borrow_mut().lock();
+        self.stream.borrow_mut().set_read_callback(None);
+        let _ = self.stream.borrow_mut().disconnect();
+        self.mainloop.borrow_mut().unlock();
+        shutdown(&self.mainloop, &self.context);
+    }
+}
+
+/// Read all available data, giving it to the data callback (if any).
+///
+/// Must be called with the mainloop lock held.
+fn deliver(stream: &Rc<RefCell<Stream>>, data_cb: &Rc<RefCell<Option<DataCb>>>) {
+    let mut stream = stream.borrow_mut();
+    let mut data_cb = data_cb.borrow_mut();
+    loop {
+        match stream.peek() {
+            Ok(PeekResult::Empty) | Err(_) => break,
+            Ok(PeekResult::Hole(_)) => {},
+            Ok(PeekResult::Data(data)) => {
+                if let Some(ref mut callback) = *data_cb {
+                    callback(data);
+                }
+            },
+        }
+        if stream.discard().is_err() {
+            break;
+        }
+    }
+}
@@ -63,17 +63,27 @@
 //!   channel).
 //! * [`Spec::sample_size`]: The size, in bytes, of one sample.
 //! * [`Spec::bytes_to_usec`]: Calculate the time it would take to play a buffer of a certain size.
+//! * [`Spec::usec_to_bytes`]: Calculate the size of buffer required to play for a certain time.
+//! * [`Spec::frame_align`]: Round a buffer size down to a whole number of frames.
+//!
+//! The time based calculations are also available taking or giving a `std::time::Duration`, with
+//! [`Spec::bytes_to_duration`] and [`Spec::duration_to_bytes`].
 //!
 //! [`Spec::bytes_per_second`]: struct.Spec.html#method.bytes_per_second
 //! [`Spec::frame_size`]: struct.Spec.html#method.frame_size
 //! [`Spec::sample_size`]: struct.Spec.html#method.sample_size
 //! [`Spec::bytes_to_usec`]: struct.Spec.html#method.bytes_to_usec
+//! [`Spec::usec_to_bytes`]: struct.Spec.html#method.usec_to_bytes
+//! [`Spec::frame_align`]: struct.Spec.html#method.frame_align
+//! [`Spec::bytes_to_duration`]: struct.Spec.html#method.bytes_to_duration
+//! [`Spec::duration_to_bytes`]: struct.Spec.html#method.duration_to_bytes
 
 use std;
 use capi;
 use std::ffi::{CStr, CString};
 use std::borrow::Cow;
-use time::MicroSeconds;
+use std::time::Duration;
+use time::{MicroSeconds, MICROS_PER_SEC};
 
 pub use capi::PA_CHANNELS_MAX as CHANNELS_MAX;
 pub use capi::PA_RATE_MAX as RATE_MAX;
@@ -251,6 +261,43 @@ impl Spec {
         unsafe { capi::pa_usec_to_bytes(t.0, std::mem::transmute(self)) }
     }
 
+    /// Calculate the time it would take to play a buffer of the specified size, as a `Duration`.
+    /// The return value will always be rounded down to the microsecond for non-integral values.
+    pub fn bytes_to_duration(&self, length: u64) -> Duration {
+        Duration::from_micros(self.bytes_to_usec(length).0)
+    }
+
+    /// Calculates the size of a buffer required, for playback duration of the time specified as a
+    /// `Duration`. The duration is truncated to microsecond precision, and the return value will
+    /// always be rounded down for non-integral return values.
+    ///
+    /// Durations too large to be represented in microseconds are saturated.
+    pub fn duration_to_bytes(&self, t: Duration) -> usize {
+        let usecs = t.as_secs().checked_mul(MICROS_PER_SEC)
+            .and_then(|u| u.checked_add(t.subsec_micros() as u64))
+            .unwrap_or(::time::USEC_MAX.0);
+        self.usec_to_bytes(MicroSeconds(usecs))
+    }
+
+    /// Round the specified buffer size down to a whole number of frames.
+    ///
+    /// Returns `None` if the sample spec is invalid (giving a frame size of zero).
+    pub fn frame_align(&self, length: usize) -> Option<usize> {
+        let frame_size = self.frame_size();
+        length.checked_rem(frame_size).map(|r| length - r)
+    }
+
+    /// Returns the time it takes to play a single frame. The return value will always be rounded
+    /// down for non-integral return values.
+    ///
+    /// Returns `None` if the sample spec is invalid.
+    pub fn usec_per_frame(&self) -> Option<MicroSeconds> {
+        match self.is_valid() {
+            true => Some(self.bytes_to_usec(self.frame_size() as u64)),
+            false => None,
+        }
+    }
+
     /// Pretty print a sample type specification to a string
     pub fn print(&self) -> String {
         const PRINT_MAX: usize = capi::PA_SAMPLE_SPEC_SNPRINT_MAX;
@@ -353,4 +400,68 @@ impl Format {
     pub fn is_re(&self) -> Option<bool> {
         self.is_ne().and_then(|b| Some(!b))
     }
+
+    /// Returns `true` when the specified format is a floating point format.
+    pub fn is_float(&self) -> bool {
+        matches!(*self, Format::F32le | Format::F32be)
+    }
+
+    /// Returns `true` when the specified format holds signed linear PCM samples (including
+    /// floating point).
+    ///
+    /// Returns `false` for `U8`, for the companded `ALaw` and `ULaw` formats, and for `Invalid`.
+    pub fn is_signed(&self) -> bool {
+        !matches!(*self, Format::U8 | Format::ALaw | Format::ULaw | Format::Invalid)
+    }
+
+    /// Returns the number of significant bits per sample.
+    ///
+    /// This differs from the storage size (see [`size`](#method.size)) for the `S24_32` formats,
+    /// which hold 24 significant bits within 32 bit words. Returns `None` for `Invalid`.
+    pub fn bits_per_sample(&self) -> Option<u32> {
+        match *self {
+            Format::U8 | Format::ALaw | Format::ULaw => Some(8),
+            Format::S16le | Format::S16be => Some(16),
+            Format::S24le | Format::S24be | Format::S24_32le | Format::S24_32be => Some(24),
+            Format::F32le | Format::F32be | Format::S32le | Format::S32be => Some(32),
+            Format::Invalid => None,
+        }
+    }
+
+    /// Returns the native endian variant of the specified format.
+    ///
+    /// Formats for which endianness does not apply, and `Invalid`, are returned unchanged.
+    pub fn to_ne(&self) -> Self {
+        match *self {
+            Format::S16le | Format::S16be => SAMPLE_S16NE,
+            Format::F32le | Format::F32be => SAMPLE_FLOAT32NE,
+            Format::S32le | Format::S32be => SAMPLE_S32NE,
+            Format::S24le | Format::S24be => SAMPLE_S24NE,
+            Format::S24_32le | Format::S24_32be => SAMPLE_S24_32NE,
+            f => f,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_properties() {
+        assert!(Format::F32be.is_float());
+        assert!(!Format::S32le.is_float());
+        assert!(Format::S16le.is_signed());
+        assert!(!Format::U8.is_signed());
+        assert_eq!(Format::S24_32le.bits_per_sample(), Some(24));
+        assert_eq!(Format::Invalid.bits_per_sample(), None);
+    }
+
+    #[test]
+    fn format_to_ne() {
+        assert_eq!(Format::S16le.to_ne(), SAMPLE_S16NE);
+        assert_eq!(Format::S16be.to_ne(), SAMPLE_S16NE);
+        assert_eq!(Format::S24_32be.to_ne(), SAMPLE_S24_32NE);
+        assert_eq!(Format::U8.to_ne(), Format::U8);
+    }
 }
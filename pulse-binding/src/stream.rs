@@ -189,7 +189,9 @@
 //!
 //! Even with the best precautions, buffers will sometime over - or underflow. To handle this
 //! gracefully, the application can be notified when this happens. Callbacks are registered using
-//! [`Stream::set_overflow_callback`] and [`Stream::set_underflow_callback`].
+//! [`Stream::set_overflow_callback`] and [`Stream::set_underflow_callback`]. Alternatively, all
+//! such stream notifications can be received through a single handler taking a [`StreamEvent`],
+//! registered with [`Stream::set_stream_event_callback`].
 //!
 //! # Synchronizing Multiple Playback Streams
 //!
@@ -245,6 +247,7 @@
 //! [`Stream::set_overflow_callback`]: struct.Stream.html#method.set_overflow_callback
 //! [`Stream::set_read_callback`]: struct.Stream.html#method.set_read_callback
 //! [`Stream::set_state_callback`]: struct.Stream.html#method.set_state_callback
+//! [`Stream::set_stream_event_callback`]: struct.Stream.html#method.set_stream_event_callback
 //! [`Stream::set_underflow_callback`]: struct.Stream.html#method.set_underflow_callback
 //! [`Stream::set_write_callback`]: struct.Stream.html#method.set_write_callback
 //! [`Stream::trigger`]: struct.Stream.html#method.trigger
@@ -252,15 +255,20 @@
 //! [`Stream::update_timing_info`]: struct.Stream.html#method.update_timing_info
 //! [`Stream::writable_size`]: struct.Stream.html#method.writable_size
 //! [`Stream::write`]: struct.Stream.html#method.write
+//! [`StreamEvent`]: enum.StreamEvent.html
 
 use std;
 use capi;
+use std::convert::TryFrom;
 use std::os::raw::{c_char, c_void};
 use std::ffi::{CStr, CString};
 use std::ptr::{null, null_mut};
 use std::borrow::Cow;
-use callbacks::unwrap_optional_callback;
-use error::PAErr;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+use callbacks::{unwrap_optional_callback, ListEntry};
+use error::{PAErr, ObjectDead, ObjectKind, ReleaseSite};
 use time::MicroSeconds;
 use proplist::Proplist;
 use callbacks::box_closure_get_capi_ptr;
@@ -275,9 +283,31 @@ pub use capi::pa_stream_direction_t as Direction;
 /// Note: Saves a copy of active multi-use closure callbacks, which it frees on drop.
 pub struct Stream {
     /// The actual C object.
-    ptr: *mut StreamInternal,
+    pub(crate) ptr: *mut StreamInternal,
     /// Multi-use callback closure pointers
     cb_ptrs: CallbackPointers,
+    /// Flags given upon connecting the stream
+    connect_flags: FlagSet,
+    /// Locally tracked corked state, as last requested
+    corked: bool,
+    /// Formats offered on creation with `new_extended`, as printed, for diagnostics
+    offered_formats: Vec<String>,
+    /// Sink given on connecting for playback, for diagnostics
+    playback_device: Option<String>,
+    /// Staging of small writes, where write coalescing is enabled
+    staging: Option<WriteStaging>,
+    /// Whether a buffer obtained with `begin_write` is outstanding
+    begin_write_pending: bool,
+    /// Where the stream was disconnected, for diagnostics
+    released: ReleaseSite,
+}
+
+/// Staging buffer of write coalescing.
+struct WriteStaging {
+    /// Size at which staged data is written out, or zero for the stream’s `minreq`.
+    threshold: usize,
+    /// Staged data, retaining its capacity when written out.
+    buffer: Vec<u8>,
 }
 
 unsafe impl Send for Stream {}
@@ -311,7 +341,6 @@ type EventCb = ::callbacks::MultiUseCallback<dyn FnMut(String, Proplist),
         *mut c_void)>;
 
 /// The state of a stream
-#[repr(C)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum State {
     /// The stream is not yet connected to any sink or source.
@@ -324,17 +353,45 @@ pub enum State {
     Failed,
     /// The stream has been terminated cleanly.
     Terminated,
+    /// A state not known to this binding, as may be introduced by newer versions of PulseAudio.
+    Other(i32),
 }
 
 impl From<State> for capi::pa_stream_state_t {
     fn from(s: State) -> Self {
-        unsafe { std::mem::transmute(s) }
+        match s {
+            State::Unconnected => capi::PA_STREAM_UNCONNECTED,
+            State::Creating => capi::PA_STREAM_CREATING,
+            State::Ready => capi::PA_STREAM_READY,
+            State::Failed => capi::PA_STREAM_FAILED,
+            State::Terminated => capi::PA_STREAM_TERMINATED,
+            State::Other(v) => capi::pa_stream_state_t(v),
+        }
     }
 }
 
 impl From<capi::pa_stream_state_t> for State {
     fn from(s: capi::pa_stream_state_t) -> Self {
-        unsafe { std::mem::transmute(s) }
+        match s {
+            capi::PA_STREAM_UNCONNECTED => State::Unconnected,
+            capi::PA_STREAM_CREATING => State::Creating,
+            capi::PA_STREAM_READY => State::Ready,
+            capi::PA_STREAM_FAILED => State::Failed,
+            capi::PA_STREAM_TERMINATED => State::Terminated,
+            capi::pa_stream_state_t(v) => State::Other(v),
+        }
+    }
+}
+
+/// Strict conversion from a C value, failing with the value if it is not known to this binding.
+impl TryFrom<i32> for State {
+    type Error = i32;
+
+    fn try_from(v: i32) -> Result<Self, i32> {
+        match State::from(capi::pa_stream_state_t(v)) {
+            State::Other(v) => Err(v),
+            s => Ok(s),
+        }
     }
 }
 
@@ -515,6 +572,12 @@ pub mod flags {
 
     /// Used to tag content that will be rendered by passthrough sinks. The data will be left as is
     /// and not reformatted, resampled.
+    ///
+    /// This should be used for streams created with [`Stream::new_extended`] with compressed
+    /// formats, such as those created with [`::format::Info::new_encoded`].
+    ///
+    /// [`Stream::new_extended`]: ../struct.Stream.html#method.new_extended
+    /// [`::format::Info::new_encoded`]: ../../format/struct.Info.html#method.new_encoded
     pub const PASSTHROUGH: FlagSet = capi::PA_STREAM_PASSTHROUGH;
 }
 
@@ -536,6 +599,175 @@ pub mod event_names {
     pub const EVENT_FORMAT_LOST: &str = capi::PA_STREAM_EVENT_FORMAT_LOST;
 }
 
+/// A builder of stream connection flags, validating the combination chosen.
+///
+/// Each method sets the corresponding flag of the [`flags`](flags/index.html) module, where it is
+/// fully documented. [`build`](#method.build) then gives the flag set, for passing to
+/// [`Stream::connect_playback`] or [`Stream::connect_record`], or an error should the combination
+/// be invalid, rather than the connection failing with an opaque error from the server.
+///
+/// ```rust,ignore
+/// let flags = ConnectOptions::new()
+///     .start_corked()
+///     .interpolate_timing()
+///     .auto_timing_update()
+///     .adjust_latency()
+///     .build()?;
+/// stream.connect_playback(None, Some(&attr), flags, None, None)?;
+/// ```
+///
+/// [`Stream::connect_playback`]: struct.Stream.html#method.connect_playback
+/// [`Stream::connect_record`]: struct.Stream.html#method.connect_record
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ConnectOptions {
+    flags: FlagSet,
+}
+
+/// An invalid combination of flags, as rejected by
+/// [`ConnectOptions::build`](struct.ConnectOptions.html#method.build).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ConnectOptionsError {
+    /// Name of the first conflicting flag.
+    pub first: &'static str,
+    /// Name of the second conflicting flag.
+    pub second: &'static str,
+}
+
+impl std::fmt::Display for ConnectOptionsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "stream flags {} and {} may not be used together", self.first, self.second)
+    }
+}
+
+impl std::error::Error for ConnectOptionsError {}
+
+/// Diagnostics of a failure to negotiate the format of a playback stream, as given by
+/// [`Stream::diagnose_negotiation`](struct.Stream.html#method.diagnose_negotiation).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NegotiationError {
+    /// The error the stream failed with, typically [`::error::Code::NotSupported`] where no
+    /// offered format is supported.
+    ///
+    /// [`::error::Code::NotSupported`]: ../error/enum.Code.html#variant.NotSupported
+    pub error: PAErr,
+    /// Name of the sink connected to, if known (the default sink, unless given on connecting).
+    pub sink: Option<String>,
+    /// The formats offered by the stream, as printed by [`::format::Info::print`].
+    ///
+    /// [`::format::Info::print`]: ../format/struct.Info.html#method.print
+    pub offered: Vec<String>,
+    /// The formats supported by the sink, as printed by [`::format::Info::print`], or empty if
+    /// they could not be fetched.
+    ///
+    /// [`::format::Info::print`]: ../format/struct.Info.html#method.print
+    pub supported: Vec<String>,
+}
+
+impl std::fmt::Display for NegotiationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "format negotiation with sink {} failed ({}); offered: [{}]; supported: [{}]",
+            self.sink.as_ref().map_or("(unknown)", |s| s.as_str()), self.error,
+            self.offered.join("; "), self.supported.join("; "))
+    }
+}
+
+impl std::error::Error for NegotiationError {}
+
+/// Pairs of flags that may not be combined.
+const CONFLICTING_FLAGS: &[(FlagSet, &str, FlagSet, &str)] = &[
+    (flags::ADJUST_LATENCY, "ADJUST_LATENCY", flags::EARLY_REQUESTS, "EARLY_REQUESTS"),
+    (flags::START_MUTED, "START_MUTED", flags::START_UNMUTED, "START_UNMUTED"),
+    (flags::PASSTHROUGH, "PASSTHROUGH", flags::VARIABLE_RATE, "VARIABLE_RATE"),
+    (flags::PASSTHROUGH, "PASSTHROUGH", flags::FIX_FORMAT, "FIX_FORMAT"),
+    (flags::PASSTHROUGH, "PASSTHROUGH", flags::FIX_RATE, "FIX_RATE"),
+    (flags::PASSTHROUGH, "PASSTHROUGH", flags::FIX_CHANNELS, "FIX_CHANNELS"),
+];
+
+/// Generate the flag setting methods of `ConnectOptions`.
+macro_rules! connect_option {
+    ( $( $(#[$attr:meta])* $method:ident => $flag:ident ),* $(,)* ) => { $(
+        $(#[$attr])*
+        pub fn $method(mut self) -> Self {
+            self.flags |= flags::$flag;
+            self
+        }
+    )* };
+}
+
+impl Default for ConnectOptions {
+    fn default() -> Self {
+        Self { flags: flags::NOFLAGS }
+    }
+}
+
+impl ConnectOptions {
+    /// Create a new set of options, with no flags set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    connect_option! {
+        /// Set the [`START_CORKED`](flags/constant.START_CORKED.html) flag.
+        start_corked => START_CORKED,
+        /// Set the [`INTERPOLATE_TIMING`](flags/constant.INTERPOLATE_TIMING.html) flag.
+        interpolate_timing => INTERPOLATE_TIMING,
+        /// Set the [`NOT_MONOTONIC`](flags/constant.NOT_MONOTONIC.html) flag.
+        not_monotonic => NOT_MONOTONIC,
+        /// Set the [`AUTO_TIMING_UPDATE`](flags/constant.AUTO_TIMING_UPDATE.html) flag.
+        auto_timing_update => AUTO_TIMING_UPDATE,
+        /// Set the [`NO_REMAP_CHANNELS`](flags/constant.NO_REMAP_CHANNELS.html) flag.
+        no_remap_channels => NO_REMAP_CHANNELS,
+        /// Set the [`NO_REMIX_CHANNELS`](flags/constant.NO_REMIX_CHANNELS.html) flag.
+        no_remix_channels => NO_REMIX_CHANNELS,
+        /// Set the [`FIX_FORMAT`](flags/constant.FIX_FORMAT.html) flag.
+        fix_format => FIX_FORMAT,
+        /// Set the [`FIX_RATE`](flags/constant.FIX_RATE.html) flag.
+        fix_rate => FIX_RATE,
+        /// Set the [`FIX_CHANNELS`](flags/constant.FIX_CHANNELS.html) flag.
+        fix_channels => FIX_CHANNELS,
+        /// Set the [`DONT_MOVE`](flags/constant.DONT_MOVE.html) flag.
+        dont_move => DONT_MOVE,
+        /// Set the [`VARIABLE_RATE`](flags/constant.VARIABLE_RATE.html) flag.
+        variable_rate => VARIABLE_RATE,
+        /// Set the [`PEAK_DETECT`](flags/constant.PEAK_DETECT.html) flag.
+        peak_detect => PEAK_DETECT,
+        /// Set the [`START_MUTED`](flags/constant.START_MUTED.html) flag.
+        start_muted => START_MUTED,
+        /// Set the [`ADJUST_LATENCY`](flags/constant.ADJUST_LATENCY.html) flag.
+        adjust_latency => ADJUST_LATENCY,
+        /// Set the [`EARLY_REQUESTS`](flags/constant.EARLY_REQUESTS.html) flag.
+        early_requests => EARLY_REQUESTS,
+        /// Set the [`DONT_INHIBIT_AUTO_SUSPEND`] flag.
+        ///
+        /// [`DONT_INHIBIT_AUTO_SUSPEND`]: flags/constant.DONT_INHIBIT_AUTO_SUSPEND.html
+        dont_inhibit_auto_suspend => DONT_INHIBIT_AUTO_SUSPEND,
+        /// Set the [`START_UNMUTED`](flags/constant.START_UNMUTED.html) flag.
+        start_unmuted => START_UNMUTED,
+        /// Set the [`FAIL_ON_SUSPEND`](flags/constant.FAIL_ON_SUSPEND.html) flag.
+        fail_on_suspend => FAIL_ON_SUSPEND,
+        /// Set the [`RELATIVE_VOLUME`](flags/constant.RELATIVE_VOLUME.html) flag.
+        relative_volume => RELATIVE_VOLUME,
+        /// Set the [`PASSTHROUGH`](flags/constant.PASSTHROUGH.html) flag.
+        passthrough => PASSTHROUGH,
+    }
+
+    /// Set additional flags directly.
+    pub fn flags(mut self, flags: FlagSet) -> Self {
+        self.flags |= flags;
+        self
+    }
+
+    /// Validate the combination of flags, giving the flag set.
+    pub fn build(&self) -> Result<FlagSet, ConnectOptionsError> {
+        for &(a, a_name, b, b_name) in CONFLICTING_FLAGS {
+            if self.flags & a != 0 && self.flags & b != 0 {
+                return Err(ConnectOptionsError { first: a_name, second: b_name });
+            }
+        }
+        Ok(self.flags)
+    }
+}
+
 /// Result type for the [`Stream::Peek`](struct.Stream.html#method.peek) method. See documentation
 /// of the method itself for more information.
 #[derive(Debug)]
@@ -556,6 +788,85 @@ pub enum Latency {
     Negative(MicroSeconds),
 }
 
+/// A consistent snapshot of the latency of a stream, as given by
+/// [`Stream::latency_report`](struct.Stream.html#method.latency_report).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct LatencyReport {
+    /// Time a sample takes to be played on the sink, for playback streams and record streams
+    /// connected to a monitor source.
+    pub sink: MicroSeconds,
+    /// Time a sample takes from being recorded to being delivered to the application, for record
+    /// streams.
+    pub source: MicroSeconds,
+    /// Estimated time a sample takes to be transferred to/from the daemon.
+    pub transport: MicroSeconds,
+    /// Fill level of the server-side buffer in bytes (the write index less the read index), or
+    /// `None` if either index is out of date due to a local request made since the last timing
+    /// update. Negative in the case of an underrun.
+    pub buffer_fill: Option<i64>,
+    /// Fill level of the server-side buffer, as time at the stream’s sample rate, or `None` as for
+    /// `buffer_fill`. Zero in the case of an underrun.
+    pub buffer_fill_time: Option<MicroSeconds>,
+    /// The buffer metrics of the stream.
+    pub buffer_attr: ::def::BufferAttr,
+    /// Whether or not the local and remote clocks are synchronized, making `transport` much more
+    /// reliable.
+    pub synchronized_clocks: bool,
+    /// The total end-to-end latency, as from [`Stream::get_latency`].
+    ///
+    /// [`Stream::get_latency`]: struct.Stream.html#method.get_latency
+    pub total: Latency,
+}
+
+/// Identifies the sink/source a stream has been moved to. See
+/// [`Stream::set_device_changed_callback`](struct.Stream.html#method.set_device_changed_callback).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceChanged {
+    /// Index of the new sink/source, if known.
+    pub index: Option<u32>,
+    /// Name of the new sink/source, if known.
+    pub name: Option<String>,
+}
+
+impl DeviceChanged {
+    /// Capture the details of the device the given stream is currently connected to.
+    fn from_stream(ptr: *mut StreamInternal) -> Self {
+        let index = match unsafe { capi::pa_stream_get_device_index(ptr) } {
+            ::def::INVALID_INDEX => None,
+            r => Some(r),
+        };
+        let name_ptr = unsafe { capi::pa_stream_get_device_name(ptr) };
+        let name = match name_ptr.is_null() {
+            true => None,
+            false => Some(unsafe { CStr::from_ptr(name_ptr).to_string_lossy().into_owned() }),
+        };
+        Self { index, name }
+    }
+}
+
+/// Stream notification, as delivered to the handler registered with
+/// [`Stream::set_stream_event_callback`](struct.Stream.html#method.set_stream_event_callback).
+#[derive(Debug)]
+pub enum StreamEvent {
+    /// A buffer overflow happened. (Only for playback streams).
+    Overflow,
+    /// A buffer underflow happened, with the position at which it occurred, if known. (Only for
+    /// playback streams).
+    Underflow(Option<u64>),
+    /// The server started playback after an underrun or on initial startup. (Only for playback
+    /// streams).
+    Started,
+    /// The sink/source this stream is connected to was suspended (`true`) or resumed (`false`).
+    Suspended(bool),
+    /// The stream was moved to a different sink/source.
+    Moved(DeviceChanged),
+    /// The buffer attributes on the server side changed.
+    BufferAttrChanged,
+    /// A meta/policy control event was received, with its name and (owned) property list. See
+    /// [`event_names`](event_names/index.html) for some well known event names.
+    Event(String, Proplist),
+}
+
 impl Stream {
     /// Create a new, unconnected stream with the specified name and sample type.
     ///
@@ -625,12 +936,21 @@ impl Stream {
     /// provide, and an initial list of properties. While connecting, the server will select the
     /// most appropriate format which the client must then provide.
     ///
+    /// This is also the means of creating a passthrough stream for compressed audio (AC3, E-AC3,
+    /// DTS, etc.), for which suitable formats can be created with [`::format::Info::new_encoded`].
+    /// Such streams should be connected with the [`flags::PASSTHROUGH`] flag. Once connected, the
+    /// format selected by the server can be obtained with [`get_format_info`].
+    ///
     /// # Params
     ///
     /// * `ctx`: The context to create this stream in
     /// * `name`: A name for this stream
     /// * `formats`: The list of formats that can be provided
     /// * `proplist`: The initial property list
+    ///
+    /// [`::format::Info::new_encoded`]: ../format/struct.Info.html#method.new_encoded
+    /// [`flags::PASSTHROUGH`]: flags/constant.PASSTHROUGH.html
+    /// [`get_format_info`]: #method.get_format_info
     pub fn new_extended(ctx: &mut ::context::Context, name: &str, formats: &[&::format::Info],
         proplist: &mut Proplist) -> Option<Self>
     {
@@ -652,13 +972,88 @@ impl Stream {
         if ptr.is_null() {
             return None;
         }
-        Some(Self::from_raw(ptr))
+        let mut stream = Self::from_raw(ptr);
+        stream.offered_formats = formats.iter().map(|f| f.print()).collect();
+        Some(stream)
     }
 
     /// Create a new `Stream` from an existing [`StreamInternal`](enum.StreamInternal.html) pointer.
     fn from_raw(ptr: *mut StreamInternal) -> Self {
         assert_eq!(false, ptr.is_null());
-        Self { ptr: ptr, cb_ptrs: Default::default() }
+        Self {
+            ptr: ptr,
+            cb_ptrs: Default::default(),
+            connect_flags: flags::NOFLAGS,
+            corked: false,
+            offered_formats: Vec::new(),
+            playback_device: None,
+            staging: None,
+            begin_write_pending: false,
+            released: Default::default(),
+        }
+    }
+
+    /// Gather diagnostics of a failure of the stream, as for a playback stream created with
+    /// [`new_extended`](#method.new_extended) for which no offered format is supported by the sink.
+    ///
+    /// The formats supported by the sink are fetched through introspection, with the callback
+    /// given the result, comparing them against those offered. This should be called upon the
+    /// stream entering the `Failed` state, before the context error code changes, i.e. from within
+    /// the state callback. Fails with [`ObjectDead`] if called later, once the stream has been
+    /// unlinked from its context.
+    ///
+    /// [`ObjectDead`]: ../error/struct.ObjectDead.html
+    pub fn diagnose_negotiation<F>(&self, callback: F) -> Result<(), ObjectDead>
+        where F: FnOnce(NegotiationError) + 'static
+    {
+        let context = self.get_context()?;
+        let diagnostics = NegotiationError {
+            error: context.errno(),
+            sink: self.playback_device.clone(),
+            offered: self.offered_formats.clone(),
+            supported: Vec::new(),
+        };
+        let introspector = context.introspect();
+
+        // Fetch the formats of the sink, then give the diagnostics
+        let fetch = |introspector: &::context::introspect::Introspector,
+                     diagnostics: NegotiationError,
+                     callback: F| {
+            let name = match diagnostics.sink.clone() {
+                Some(name) => name,
+                None => return callback(diagnostics),
+            };
+            let mut callback = Some(callback);
+            let mut diagnostics = Some(diagnostics);
+            drop(introspector.get_sink_info_by_name(&name, move |result| {
+                match result {
+                    Ok(ListEntry::Item(sink)) => {
+                        if let Some(d) = diagnostics.as_mut() {
+                            d.supported = sink.formats.iter().map(|f| f.print()).collect();
+                        }
+                    },
+                    _ => {
+                        if let (Some(callback), Some(d)) = (callback.take(), diagnostics.take()) {
+                            callback(d);
+                        }
+                    },
+                }
+            }));
+        };
+
+        match diagnostics.sink.is_some() {
+            true => fetch(&introspector, diagnostics, callback),
+            false => {
+                let mut state = Some((context, diagnostics, callback));
+                drop(introspector.get_server_info(move |info| {
+                    if let Some((context, mut diagnostics, callback)) = state.take() {
+                        diagnostics.sink = info.default_sink_name.as_ref().map(|s| s.to_string());
+                        fetch(&context.introspect(), diagnostics, callback);
+                    }
+                }));
+            },
+        }
+        Ok(())
     }
 
     /// Return the current state of the stream.
@@ -666,10 +1061,28 @@ impl Stream {
         unsafe { capi::pa_stream_get_state(self.ptr).into() }
     }
 
+    /// Check that the stream is still usable, failing with [`ObjectDead`] if it has been
+    /// terminated, whether by [`disconnect`](#method.disconnect) or upon failure.
+    ///
+    /// Note that the C API unlinks a terminated stream from its context only once the state
+    /// callback has been called for the `Failed` or `Terminated` state, thus within that callback
+    /// the stream is still usable.
+    ///
+    /// [`ObjectDead`]: ../error/struct.ObjectDead.html
+    pub fn check_alive(&self) -> Result<(), ObjectDead> {
+        match unsafe { capi::pa_stream_get_context(self.ptr) }.is_null() {
+            true => Err(self.released.dead(ObjectKind::Stream)),
+            false => Ok(()),
+        }
+    }
+
     /// Return the sink input resp. source output index this stream is identified in the server
     /// with. This is useful with the introspection functions such as
     /// [`::context::introspect::Introspector::get_sink_input_info`] or
-    /// [`::context::introspect::Introspector::get_source_output_info`].
+    /// [`::context::introspect::Introspector::get_source_output_info`], and for correlating
+    /// subscription events about sink inputs or source outputs with local streams.
+    ///
+    /// Returns `None` until the stream is ready.
     ///
     /// [`::context::introspect::Introspector::get_sink_input_info`]:
     /// ../context/introspect/struct.Introspector.html#method.get_sink_input_info
@@ -682,6 +1095,19 @@ impl Stream {
         }
     }
 
+    /// Get a shared handle to the context the stream belongs to.
+    ///
+    /// The handle holds a reference to the context, keeping it alive whilst the handle exists.
+    ///
+    /// Fails with [`ObjectDead`] if the stream has been terminated, since it then no longer belongs
+    /// to a context.
+    ///
+    /// [`ObjectDead`]: ../error/struct.ObjectDead.html
+    pub fn get_context(&self) -> Result<::context::ContextRef, ObjectDead> {
+        self.check_alive()?;
+        Ok(::context::Context::new_ref(unsafe { capi::pa_stream_get_context(self.ptr) }))
+    }
+
     /// Return the index of the sink or source this stream is connected to in the server. This is
     /// useful with the introspection functions such as
     /// [`::context::introspect::Introspector::get_sink_info_by_index`] or
@@ -809,7 +1235,12 @@ impl Stream {
             capi::pa_stream_connect_playback(self.ptr, p_dev, p_attr, flags, p_vol, p_sync)
         };
         match r {
-            0 => Ok(()),
+            0 => {
+                self.playback_device = dev.map(|d| d.to_string());
+                self.connect_flags = flags;
+                self.corked = flags & self::flags::START_CORKED != 0;
+                Ok(())
+            },
             e => Err(PAErr(e)),
         }
     }
@@ -841,7 +1272,11 @@ impl Stream {
         };
 
         match unsafe { capi::pa_stream_connect_record(self.ptr, p_dev, p_attr, flags) } {
-            0 => Ok(()),
+            0 => {
+                self.connect_flags = flags;
+                self.corked = flags & self::flags::START_CORKED != 0;
+                Ok(())
+            },
             e => Err(PAErr(e)),
         }
     }
@@ -864,9 +1299,19 @@ impl Stream {
     }
 
     /// Disconnect a stream from a source/sink.
+    ///
+    /// Any data staged by [write coalescing](#method.set_write_coalescing) is discarded.
+    ///
+    /// The stream is dead thereafter, see [`check_alive`](#method.check_alive).
+    #[track_caller]
     pub fn disconnect(&mut self) -> Result<(), PAErr> {
+        self.clear_staged();
+        self.check_alive()?;
         match unsafe { capi::pa_stream_disconnect(self.ptr) } {
-            0 => Ok(()),
+            0 => {
+                self.released.record();
+                Ok(())
+            },
             e => Err(PAErr(e)),
         }
     }
@@ -908,6 +1353,8 @@ impl Stream {
     pub fn begin_write<'a>(&mut self, nbytes: Option<usize>)
         -> Result<Option<&'a mut [u8]>, PAErr>
     {
+        // Staged data must precede that written from the buffer
+        self.write_staged()?;
         let mut data_ptr = null_mut::<c_void>();
         // If user asks for size to be automatically chosen by PA, we pass in std::usize::MAX
         // (-1 as size_t) to signal this.
@@ -920,6 +1367,7 @@ impl Stream {
                         let slice = unsafe {
                             std::slice::from_raw_parts_mut(data_ptr as *mut u8, nbytes_tmp)
                         };
+                        self.begin_write_pending = true;
                         Ok(Some(slice))
                     },
                 }
@@ -937,6 +1385,7 @@ impl Stream {
     /// [`cancel_write`]: #method.cancel_write
     /// [`write`]: #method.write
     pub fn cancel_write(&mut self) -> Result<(), PAErr> {
+        self.begin_write_pending = false;
         match unsafe { capi::pa_stream_cancel_write(self.ptr) } {
             0 => Ok(()),
             e => Err(PAErr(e)),
@@ -972,6 +1421,11 @@ impl Stream {
     ///   stream’s sample spec frame size.
     /// * `seek`: Seek mode. Must be [`SeekMode::Relative`] for upload streams.
     ///
+    /// With [write coalescing](#method.set_write_coalescing) enabled, data written without a
+    /// `free_cb`, at `offset` `0` with [`SeekMode::Relative`], and not from a buffer obtained with
+    /// [`begin_write`], may be staged rather than written immediately. Any other write first writes
+    /// out the staged data.
+    ///
     /// [`SeekMode::Relative`]: enum.SeekMode.html#Relative.v
     /// [`begin_write`]: #method.begin_write
     /// [`write`]: #method.write
@@ -980,6 +1434,24 @@ impl Stream {
     {
         debug_assert_eq!(0, data.len().checked_rem(self.get_sample_spec().unwrap().frame_size())
             .unwrap());
+        if self.staging.is_some() {
+            if free_cb.is_none() && offset == 0 && seek == SeekMode::Relative
+                && !self.begin_write_pending
+            {
+                return self.write_coalesced(data);
+            }
+            if !self.begin_write_pending {
+                self.write_staged()?;
+            }
+        }
+        self.write_direct(data, free_cb, offset, seek)
+    }
+
+    /// Write data with `pa_stream_write`, bypassing write coalescing.
+    fn write_direct(&mut self, data: &[u8], free_cb: Option<::def::FreeCb>, offset: i64,
+        seek: SeekMode) -> Result<(), PAErr>
+    {
+        self.begin_write_pending = false;
         let r = unsafe {
             capi::pa_stream_write(self.ptr, data.as_ptr() as *const c_void, data.len(), free_cb,
                 offset, seek)
@@ -990,6 +1462,90 @@ impl Stream {
         }
     }
 
+    /// Enable coalescing of small writes, or disable it with `None`.
+    ///
+    /// With coalescing enabled, data given to [`write`] is copied into a staging buffer, and only
+    /// written to the server once at least `threshold` bytes have accumulated, reducing the
+    /// protocol overhead of many tiny writes, as is common when bridging packetized network audio.
+    /// A threshold of zero uses the stream’s `minreq` buffer metric. Writes at least as large as
+    /// the threshold, with nothing staged, are written directly, without copying.
+    ///
+    /// Staged data is written out upon [`write_staged`], [`drain`], [`trigger`], [`begin_write`],
+    /// and any write not eligible for coalescing (see [`write`]), including those made with
+    /// [`write_ext_free`] and [`write_pooled`]. It is discarded upon [`flush`] and [`disconnect`].
+    /// The size of [`writable_size`] accounts for the data staged.
+    ///
+    /// Changing the setting first writes out any staged data.
+    ///
+    /// [`write`]: #method.write
+    /// [`write_staged`]: #method.write_staged
+    /// [`drain`]: #method.drain
+    /// [`trigger`]: #method.trigger
+    /// [`begin_write`]: #method.begin_write
+    /// [`write_ext_free`]: #method.write_ext_free
+    /// [`write_pooled`]: #method.write_pooled
+    /// [`flush`]: #method.flush
+    /// [`disconnect`]: #method.disconnect
+    /// [`writable_size`]: #method.writable_size
+    pub fn set_write_coalescing(&mut self, threshold: Option<usize>) -> Result<(), PAErr> {
+        self.write_staged()?;
+        self.staging = threshold.map(|threshold| {
+            WriteStaging { threshold: threshold, buffer: Vec::with_capacity(threshold) }
+        });
+        Ok(())
+    }
+
+    /// Get the number of bytes staged by write coalescing, not yet written to the server.
+    pub fn staged_size(&self) -> usize {
+        self.staging.as_ref().map_or(0, |staging| staging.buffer.len())
+    }
+
+    /// Write out any data staged by [write coalescing](#method.set_write_coalescing).
+    ///
+    /// Upon error, the staged data is discarded.
+    pub fn write_staged(&mut self) -> Result<(), PAErr> {
+        let buffer = match self.staging.as_mut() {
+            Some(ref mut staging) if !staging.buffer.is_empty() => {
+                std::mem::replace(&mut staging.buffer, Vec::new())
+            },
+            _ => return Ok(()),
+        };
+        let result = self.write_direct(&buffer, None, 0, SeekMode::Relative);
+        if let Some(staging) = self.staging.as_mut() {
+            // Keep the capacity, such that staging does not allocate again
+            staging.buffer = buffer;
+            staging.buffer.clear();
+        }
+        result
+    }
+
+    /// Stage data for writing, writing out the staged data once the threshold is reached.
+    fn write_coalesced(&mut self, data: &[u8]) -> Result<(), PAErr> {
+        let threshold = match self.staging.as_ref().map(|staging| staging.threshold) {
+            Some(0) => self.get_buffer_attr_copy().map_or(0, |attr| attr.minreq as usize),
+            Some(threshold) => threshold,
+            None => 0,
+        };
+        let staged = self.staged_size();
+        if staged == 0 && data.len() >= threshold {
+            return self.write_direct(data, None, 0, SeekMode::Relative);
+        }
+        if let Some(staging) = self.staging.as_mut() {
+            staging.buffer.extend_from_slice(data);
+        }
+        match staged + data.len() >= threshold {
+            true => self.write_staged(),
+            false => Ok(()),
+        }
+    }
+
+    /// Discard any data staged by write coalescing.
+    fn clear_staged(&mut self) {
+        if let Some(staging) = self.staging.as_mut() {
+            staging.buffer.clear();
+        }
+    }
+
     /// Write some data to the server (for playback streams).
     ///
     /// This function does exactly the same as [`write`] with the only difference being that a void
@@ -1013,6 +1569,10 @@ impl Stream {
         let (cb_f, cb_d) = unwrap_optional_callback::<::def::FreeCb>(free_cb);
         debug_assert_eq!(0, data.len().checked_rem(self.get_sample_spec().unwrap().frame_size())
             .unwrap());
+        if !self.begin_write_pending {
+            self.write_staged()?;
+        }
+        self.begin_write_pending = false;
         let r = unsafe {
             capi::pa_stream_write_ext_free(self.ptr, data.as_ptr() as *const c_void, data.len(),
                 cb_f, cb_d, offset, seek.into())
@@ -1023,6 +1583,39 @@ impl Stream {
         }
     }
 
+    /// Write the first `len` bytes of a buffer leased from a [`BufferPool`] (for playback streams).
+    ///
+    /// The buffer is given to the library without copying (where the transport allows), and is
+    /// returned to its pool once no longer needed, as described in the [`buffer_pool`] module
+    /// documentation. Upon error, it is returned to its pool immediately.
+    ///
+    /// The length must be a multiple of the stream’s sample spec frame size, and no greater than
+    /// the size of the buffer. The `offset` and `seek` parameters are as for [`write`].
+    ///
+    /// [`BufferPool`]: ../buffer_pool/struct.BufferPool.html
+    /// [`buffer_pool`]: ../buffer_pool/index.html
+    /// [`write`]: #method.write
+    pub fn write_pooled(&mut self, buffer: ::buffer_pool::PooledBuffer, len: usize, offset: i64,
+        seek: SeekMode) -> Result<(), PAErr>
+    {
+        assert!(len <= buffer.len());
+        debug_assert_eq!(0, len.checked_rem(self.get_sample_spec().unwrap().frame_size())
+            .unwrap());
+        self.write_staged()?;
+        let (userdata, data) = buffer.into_raw();
+        let r = unsafe {
+            capi::pa_stream_write_ext_free(self.ptr, data as *const c_void, len,
+                Some(::buffer_pool::free_cb_proxy), userdata, offset, seek.into())
+        };
+        match r {
+            0 => Ok(()),
+            e => {
+                drop(::buffer_pool::PooledBuffer::from_raw(userdata));
+                Err(PAErr(e))
+            },
+        }
+    }
+
     /// Read the next fragment from the buffer (for recording streams).
     ///
     /// This function returns one of the [`PeekResult`] variants - either [`Empty`], [`Hole`] or
@@ -1085,16 +1678,25 @@ impl Stream {
     /// bytes. This is usually not desirable, though, as it would increase stream latency to be
     /// higher than requested ([`buffer_attr.tlength`]).
     ///
+    /// Returns `None` on error, in which case the error can be obtained from the context with
+    /// [`::context::Context::errno`].
+    ///
     /// [`buffer_attr.maxlength`]: ../def/struct.BufferAttr.html#structfield.maxlength
     /// [`buffer_attr.tlength`]: ../def/struct.BufferAttr.html#structfield.tlength
+    /// [`::context::Context::errno`]: ../context/struct.Context.html#method.errno
     pub fn writable_size(&self) -> Option<usize> {
         match unsafe { capi::pa_stream_writable_size(self.ptr) } {
             std::usize::MAX => None,
-            r => Some(r),
+            r => Some(r.saturating_sub(self.staged_size())),
         }
     }
 
     /// Return the number of bytes that may be read using [`peek`](#method.peek).
+    ///
+    /// Returns `None` on error, in which case the error can be obtained from the context with
+    /// [`::context::Context::errno`].
+    ///
+    /// [`::context::Context::errno`]: ../context/struct.Context.html#method.errno
     pub fn readable_size(&self) -> Option<usize> {
         match unsafe { capi::pa_stream_readable_size(self.ptr) } {
             std::usize::MAX => None,
@@ -1109,15 +1711,20 @@ impl Stream {
     ///
     /// The optional callback must accept a `bool`, which indicates success.
     ///
+    /// Any data staged by [write coalescing](#method.set_write_coalescing) is written out first;
+    /// should that fail, the error is returned, and the drain is not issued.
+    ///
     /// Panics if the underlying C function returns a null pointer.
     pub fn drain(&mut self, callback: Option<Box<dyn FnMut(bool) + 'static>>)
-        -> Operation<dyn FnMut(bool)>
+        -> Result<Operation<dyn FnMut(bool)>, PAErr>
     {
+        self.write_staged()?;
         let (cb_fn, cb_data): (Option<extern "C" fn(_, _, _)>, _) =
             ::callbacks::get_su_capi_params::<_, _>(callback, success_cb_proxy);
         let ptr = unsafe { capi::pa_stream_drain(self.ptr, cb_fn, cb_data) };
         assert!(!ptr.is_null());
-        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+        Ok(Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("stream::drain", self.get_index()))
     }
 
     /// Request a timing info structure update for a stream.
@@ -1140,19 +1747,25 @@ impl Stream {
         let ptr = unsafe { capi::pa_stream_update_timing_info(self.ptr, cb_fn, cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("stream::update_timing_info", self.get_index())
     }
 
     /// Set the callback function that is called whenever the state of the stream changes.
     pub fn set_state_callback(&mut self, callback: Option<Box<dyn FnMut() + 'static>>) {
         let saved = &mut self.cb_ptrs.set_state;
         *saved = NotifyCb::new(callback);
-        let (cb_fn, cb_data) = saved.get_capi_params(notify_cb_proxy);
+        let (cb_fn, cb_data) = saved.get_capi_params(state_cb_proxy);
         unsafe { capi::pa_stream_set_state_callback(self.ptr, cb_fn, cb_data); }
     }
 
     /// Set the callback function that is called when new data may be written to the stream.
     ///
-    /// The callback accepts an argument giving the number of bytes.
+    /// The callback accepts an argument giving the number of bytes requested by the server, i.e.
+    /// the same value as would be returned by [`writable_size`] at that point. Pull-model producers
+    /// can simply generate and [`write`] exactly this amount of data.
+    ///
+    /// [`writable_size`]: #method.writable_size
+    /// [`write`]: #method.write
     pub fn set_write_callback(&mut self, callback: Option<Box<dyn FnMut(usize) + 'static>>) {
         let saved = &mut self.cb_ptrs.write;
         *saved = RequestCb::new(callback);
@@ -1162,7 +1775,10 @@ impl Stream {
 
     /// Set the callback function that is called when new data is available from the stream.
     ///
-    /// The callback accepts an argument giving the number of bytes.
+    /// The callback accepts an argument giving the number of bytes available, i.e. the same value
+    /// as would be returned by [`readable_size`] at that point.
+    ///
+    /// [`readable_size`]: #method.readable_size
     pub fn set_read_callback(&mut self, callback: Option<Box<dyn FnMut(usize) + 'static>>) {
         let saved = &mut self.cb_ptrs.read;
         *saved = RequestCb::new(callback);
@@ -1175,7 +1791,7 @@ impl Stream {
     pub fn set_overflow_callback(&mut self, callback: Option<Box<dyn FnMut() + 'static>>) {
         let saved = &mut self.cb_ptrs.overflow;
         *saved = NotifyCb::new(callback);
-        let (cb_fn, cb_data) = saved.get_capi_params(notify_cb_proxy);
+        let (cb_fn, cb_data) = saved.get_capi_params(overflow_cb_proxy);
         unsafe { capi::pa_stream_set_overflow_callback(self.ptr, cb_fn, cb_data); }
     }
 
@@ -1197,7 +1813,7 @@ impl Stream {
     pub fn set_underflow_callback(&mut self, callback: Option<Box<dyn FnMut() + 'static>>) {
         let saved = &mut self.cb_ptrs.underflow;
         *saved = NotifyCb::new(callback);
-        let (cb_fn, cb_data) = saved.get_capi_params(notify_cb_proxy);
+        let (cb_fn, cb_data) = saved.get_capi_params(underflow_cb_proxy);
         unsafe { capi::pa_stream_set_underflow_callback(self.ptr, cb_fn, cb_data); }
     }
 
@@ -1234,6 +1850,29 @@ impl Stream {
         unsafe { capi::pa_stream_set_moved_callback(self.ptr, cb_fn, cb_data); }
     }
 
+    /// Set the callback function that is called whenever the stream is moved to a different
+    /// sink/source, with the index and name of the new sink/source.
+    ///
+    /// This is built upon the moved callback, and thus replaces any callback set with
+    /// [`set_moved_callback`], and vice versa.
+    ///
+    /// [`set_moved_callback`]: #method.set_moved_callback
+    pub fn set_device_changed_callback(&mut self,
+        callback: Option<Box<dyn FnMut(DeviceChanged) + 'static>>)
+    {
+        match callback {
+            Some(mut f) => {
+                // The closure is owned by this object and released with it, so the stream pointer
+                // it captures remains valid for as long as it can be called.
+                let stream = self.ptr;
+                self.set_moved_callback(Some(Box::new(move || {
+                    (f)(DeviceChanged::from_stream(stream));
+                })));
+            },
+            None => self.set_moved_callback(None),
+        }
+    }
+
     /// Set the callback function that is called whenever the sink/source this stream is connected
     /// to is suspended or resumed. Use [`is_suspended`] to query the new suspend status. Please
     /// note that the suspend status might also change when the stream is moved between devices.
@@ -1277,6 +1916,77 @@ impl Stream {
         unsafe { capi::pa_stream_set_buffer_attr_callback(self.ptr, cb_fn, cb_data); }
     }
 
+    /// Set a single callback function to be called for all stream notifications.
+    ///
+    /// This is a convenience alternative to registering each of the overflow, underflow, started,
+    /// suspended, moved, buffer attribute and event callbacks separately. It replaces any callbacks
+    /// previously registered with those individual setters, and vice versa, registering one of
+    /// those afterwards replaces delivery of that particular notification through this handler.
+    /// Passing `None` unregisters all of them.
+    ///
+    /// The callback is given a [`StreamEvent`](enum.StreamEvent.html) describing what happened.
+    pub fn set_stream_event_callback(&mut self,
+        callback: Option<Box<dyn FnMut(StreamEvent) + 'static>>)
+    {
+        let handler = match callback {
+            Some(f) => Rc::new(RefCell::new(f)),
+            None => {
+                self.set_overflow_callback(None);
+                self.set_underflow_callback(None);
+                self.set_started_callback(None);
+                self.set_suspended_callback(None);
+                self.set_moved_callback(None);
+                self.set_buffer_attr_callback(None);
+                self.set_event_callback(None);
+                return;
+            },
+        };
+
+        // The closures are owned by this object and released with it, so the stream pointer they
+        // capture remains valid for as long as they can be called.
+        let stream = self.ptr;
+
+        let h = handler.clone();
+        self.set_overflow_callback(Some(Box::new(move || {
+            (h.borrow_mut())(StreamEvent::Overflow);
+        })));
+        let h = handler.clone();
+        self.set_underflow_callback(Some(Box::new(move || {
+            let index = match unsafe { capi::pa_stream_get_underflow_index(stream) } {
+                r if r < 0 => None,
+                r => Some(r as u64),
+            };
+            (h.borrow_mut())(StreamEvent::Underflow(index));
+        })));
+        let h = handler.clone();
+        self.set_started_callback(Some(Box::new(move || {
+            (h.borrow_mut())(StreamEvent::Started);
+        })));
+        let h = handler.clone();
+        self.set_suspended_callback(Some(Box::new(move || {
+            // An error here means the stream is not in a good state; no meaningful status to give
+            match unsafe { capi::pa_stream_is_suspended(stream) } {
+                0 => (h.borrow_mut())(StreamEvent::Suspended(false)),
+                1 => (h.borrow_mut())(StreamEvent::Suspended(true)),
+                _ => {},
+            }
+        })));
+        let h = handler.clone();
+        self.set_moved_callback(Some(Box::new(move || {
+            (h.borrow_mut())(StreamEvent::Moved(DeviceChanged::from_stream(stream)));
+        })));
+        let h = handler.clone();
+        self.set_buffer_attr_callback(Some(Box::new(move || {
+            (h.borrow_mut())(StreamEvent::BufferAttrChanged);
+        })));
+        let h = handler;
+        self.set_event_callback(Some(Box::new(move |name, proplist| {
+            // The list given is only valid for the duration of the callback, thus must be copied
+            // for the event to own it
+            (h.borrow_mut())(StreamEvent::Event(name, proplist.clone()));
+        })));
+    }
+
     /// Pause playback of this stream temporarily.
     ///
     /// Available on both playback and recording streams. The pause operation is executed as
@@ -1299,7 +2009,9 @@ impl Stream {
             ::callbacks::get_su_capi_params::<_, _>(callback, success_cb_proxy);
         let ptr = unsafe { capi::pa_stream_cork(self.ptr, true as i32, cb_fn, cb_data) };
         assert!(!ptr.is_null());
+        self.corked = true;
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("stream::cork", self.get_index())
     }
 
     /// Resume playback of this stream.
@@ -1324,7 +2036,70 @@ impl Stream {
             ::callbacks::get_su_capi_params::<_, _>(callback, success_cb_proxy);
         let ptr = unsafe { capi::pa_stream_cork(self.ptr, false as i32, cb_fn, cb_data) };
         assert!(!ptr.is_null());
+        self.corked = false;
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("stream::uncork", self.get_index())
+    }
+
+    /// Pause playback or recording of this stream.
+    ///
+    /// This is a convenience form of [`cork`] without a callback. The returned operation can be
+    /// awaited as a future (see [`::operation`]) if completion needs to be known.
+    ///
+    /// Panics if the underlying C function returns a null pointer.
+    ///
+    /// [`cork`]: #method.cork
+    /// [`::operation`]: ../operation/index.html
+    pub fn pause(&mut self) -> Operation<dyn FnMut(bool)> {
+        self.cork(None)
+    }
+
+    /// Resume playback or recording of this stream.
+    ///
+    /// This is a convenience form of [`uncork`] without a callback. The returned operation can be
+    /// awaited as a future (see [`::operation`]) if completion needs to be known.
+    ///
+    /// Panics if the underlying C function returns a null pointer.
+    ///
+    /// [`uncork`]: #method.uncork
+    /// [`::operation`]: ../operation/index.html
+    pub fn resume(&mut self) -> Operation<dyn FnMut(bool)> {
+        self.uncork(None)
+    }
+
+    /// Return whether or not this stream is paused (corked).
+    ///
+    /// This reflects the state last requested through [`cork`], [`uncork`], [`pause`] or
+    /// [`resume`], or otherwise the use of [`flags::START_CORKED`] upon connecting. It is tracked
+    /// locally, thus requiring no server round trip, and unlike [`is_corked`] it is available
+    /// regardless of the state of the stream.
+    ///
+    /// [`cork`]: #method.cork
+    /// [`uncork`]: #method.uncork
+    /// [`pause`]: #method.pause
+    /// [`resume`]: #method.resume
+    /// [`is_corked`]: #method.is_corked
+    /// [`flags::START_CORKED`]: flags/constant.START_CORKED.html
+    pub fn is_paused(&self) -> bool {
+        self.corked
+    }
+
+    /// Cork the stream for the lifetime of the returned guard, such as whilst rebuilding its
+    /// buffer, restoring it upon the guard being dropped (including upon early return), or
+    /// committed.
+    ///
+    /// If the stream is already paused (see [`is_paused`]), it is left as is, and not uncorked
+    /// afterwards. The guard dereferences to the stream, for use in the meantime.
+    ///
+    /// Panics if the underlying C function returns a null pointer.
+    ///
+    /// [`is_paused`]: #method.is_paused
+    pub fn cork_transaction(&mut self) -> CorkTransaction<'_> {
+        let was_corked = self.corked;
+        if !was_corked {
+            drop(self.cork(None));
+        }
+        CorkTransaction { stream: self, restore: !was_corked }
     }
 
     /// Flush the playback or record buffer of this stream.
@@ -1338,11 +2113,13 @@ impl Stream {
     pub fn flush(&mut self, callback: Option<Box<dyn FnMut(bool) + 'static>>)
         -> Operation<dyn FnMut(bool)>
     {
+        self.clear_staged();
         let (cb_fn, cb_data): (Option<extern "C" fn(_, _, _)>, _) =
             ::callbacks::get_su_capi_params::<_, _>(callback, success_cb_proxy);
         let ptr = unsafe { capi::pa_stream_flush(self.ptr, cb_fn, cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("stream::flush", self.get_index())
     }
 
     /// Re-enable prebuffering if specified in the [`::def::BufferAttr`] structure. Available for
@@ -1361,6 +2138,7 @@ impl Stream {
         let ptr = unsafe { capi::pa_stream_prebuf(self.ptr, cb_fn, cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("stream::prebuf", self.get_index())
     }
 
     /// Request immediate start of playback on this stream.
@@ -1370,17 +2148,22 @@ impl Stream {
     ///
     /// The optional callback must accept a `bool`, which indicates success.
     ///
+    /// Any data staged by [write coalescing](#method.set_write_coalescing) is written out first;
+    /// should that fail, the error is returned, and playback is not triggered.
+    ///
     /// Panics if the underlying C function returns a null pointer.
     ///
     /// [`::def::BufferAttr`]: ../def/struct.BufferAttr.html
     pub fn trigger(&mut self, callback: Option<Box<dyn FnMut(bool) + 'static>>)
-        -> Operation<dyn FnMut(bool)>
+        -> Result<Operation<dyn FnMut(bool)>, PAErr>
     {
+        self.write_staged()?;
         let (cb_fn, cb_data): (Option<extern "C" fn(_, _, _)>, _) =
             ::callbacks::get_su_capi_params::<_, _>(callback, success_cb_proxy);
         let ptr = unsafe { capi::pa_stream_trigger(self.ptr, cb_fn, cb_data) };
         assert!(!ptr.is_null());
-        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+        Ok(Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("stream::trigger", self.get_index()))
     }
 
     /// Rename the stream.
@@ -1402,6 +2185,7 @@ impl Stream {
         };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("stream::set_name", self.get_index())
     }
 
     /// Return the current playback/recording time.
@@ -1472,6 +2256,50 @@ impl Stream {
         }
     }
 
+    /// Get a snapshot of the latency of the stream, combining the latest timing data with the
+    /// buffer metrics and sample spec.
+    ///
+    /// The snapshot is built from the timing data as of the last timing update. Use
+    /// [`flags::AUTO_TIMING_UPDATE`] or [`update_timing_info`], and call this from the callback set
+    /// with [`set_latency_update_callback`] to have a fresh snapshot on each timing update.
+    ///
+    /// Returns `Ok(None)` if no timing information has been received yet.
+    ///
+    /// [`flags::AUTO_TIMING_UPDATE`]: flags/constant.AUTO_TIMING_UPDATE.html
+    /// [`update_timing_info`]: #method.update_timing_info
+    /// [`set_latency_update_callback`]: #method.set_latency_update_callback
+    pub fn latency_report(&mut self) -> Result<Option<LatencyReport>, PAErr> {
+        let total = match self.get_latency()? {
+            Latency::None => return Ok(None),
+            latency => latency,
+        };
+        let timing = match self.get_timing_info() {
+            Some(timing) => *timing,
+            None => return Ok(None),
+        };
+        let buffer_attr = self.get_buffer_attr().cloned().unwrap_or_default();
+
+        let buffer_fill = match timing.write_index_corrupt == 0 && timing.read_index_corrupt == 0 {
+            true => Some(timing.write_index - timing.read_index),
+            false => None,
+        };
+        let buffer_fill_time = match (buffer_fill, self.get_sample_spec()) {
+            (Some(fill), Some(spec)) => Some(spec.bytes_to_usec(std::cmp::max(fill, 0) as u64)),
+            _ => None,
+        };
+
+        Ok(Some(LatencyReport {
+            sink: timing.sink_usec,
+            source: timing.source_usec,
+            transport: timing.transport_usec,
+            buffer_fill: buffer_fill,
+            buffer_fill_time: buffer_fill_time,
+            buffer_attr: buffer_attr,
+            synchronized_clocks: timing.synchronized_clocks != 0,
+            total: total,
+        }))
+    }
+
     /// Returns the latest raw timing data structure.
     ///
     /// The returned pointer refers to an internal read-only instance of the timing structure. The
@@ -1505,7 +2333,79 @@ impl Stream {
         unsafe { std::mem::transmute(ptr) }
     }
 
+    /// Get the optimal size of blocks written to the stream, i.e. the [`Context::get_tile_size`]
+    /// for the stream’s sample specification, a multiple of the frame size. Writing in blocks of
+    /// this size, as with `begin_write(Some(size))`, minimises the per-write overhead.
+    ///
+    /// Returns `None` if the stream is not ready.
+    ///
+    /// [`Context::get_tile_size`]: ../context/struct.Context.html#method.get_tile_size
+    pub fn tile_size(&self) -> Option<usize> {
+        let ss = self.get_sample_spec_checked().ok()?;
+        self.get_context().ok()?.get_tile_size(&ss)
+    }
+
+    /// Get a copy of the stream’s sample specification, as in use by the server.
+    ///
+    /// Unlike [`get_sample_spec`](#method.get_sample_spec), this checks that the stream is ready
+    /// and the specification valid.
+    ///
+    /// This may differ from that given upon creation, for instance where the
+    /// [`flags::FIX_RATE`](flags/constant.FIX_RATE.html) family of flags were used, or the stream
+    /// was created with [`new_extended`](#method.new_extended). Returns an error of
+    /// [`Code::BadState`] if the stream is not ready, or [`Code::Invalid`] if the specification is
+    /// not valid.
+    ///
+    /// [`Code::BadState`]: ../error/enum.Code.html#variant.BadState
+    /// [`Code::Invalid`]: ../error/enum.Code.html#variant.Invalid
+    pub fn get_sample_spec_checked(&self) -> Result<::sample::Spec, PAErr> {
+        if self.get_state() != State::Ready {
+            return Err(PAErr::from(::error::Code::BadState));
+        }
+        let ptr = unsafe { capi::pa_stream_get_sample_spec(self.ptr) };
+        if ptr.is_null() {
+            return Err(PAErr::from(::error::Code::BadState));
+        }
+        let spec = unsafe { *(ptr as *const ::sample::Spec) };
+        match spec.is_valid() {
+            true => Ok(spec),
+            false => Err(PAErr::from(::error::Code::Invalid)),
+        }
+    }
+
+    /// Get a copy of the stream’s channel map, as in use by the server.
+    ///
+    /// Unlike [`get_channel_map`](#method.get_channel_map), this checks that the stream is ready
+    /// and the map valid.
+    ///
+    /// This may differ from that given upon creation, for instance where the
+    /// [`flags::FIX_CHANNELS`](flags/constant.FIX_CHANNELS.html) flag was used, or the stream was
+    /// created with [`new_extended`](#method.new_extended). Returns an error of
+    /// [`Code::BadState`] if the stream is not ready, or [`Code::Invalid`] if the map is not valid.
+    ///
+    /// [`Code::BadState`]: ../error/enum.Code.html#variant.BadState
+    /// [`Code::Invalid`]: ../error/enum.Code.html#variant.Invalid
+    pub fn get_channel_map_checked(&self) -> Result<::channelmap::Map, PAErr> {
+        if self.get_state() != State::Ready {
+            return Err(PAErr::from(::error::Code::BadState));
+        }
+        let ptr = unsafe { capi::pa_stream_get_channel_map(self.ptr) };
+        if ptr.is_null() {
+            return Err(PAErr::from(::error::Code::BadState));
+        }
+        let map = unsafe { *(ptr as *const ::channelmap::Map) };
+        match map.is_valid() {
+            true => Ok(map),
+            false => Err(PAErr::from(::error::Code::Invalid)),
+        }
+    }
+
     /// Return a pointer to the stream’s format.
+    ///
+    /// For streams created with [`new_extended`], this is the format selected by the server from
+    /// those offered, once the stream has been connected.
+    ///
+    /// [`new_extended`]: #method.new_extended
     pub fn get_format_info(&self) -> Option<::format::Info> {
         let ptr = unsafe { capi::pa_stream_get_format_info(self.ptr) };
         if ptr.is_null() {
@@ -1530,6 +2430,19 @@ impl Stream {
         unsafe { std::mem::transmute(ptr) }
     }
 
+    /// Get a copy of the per-stream server-side buffer metrics of the stream, as chosen by the
+    /// server.
+    ///
+    /// This is as [`get_buffer_attr`](#method.get_buffer_attr), but giving a copy, which remains
+    /// valid should the metrics later change. Returns `None` if the stream is not connected.
+    pub fn get_buffer_attr_copy(&self) -> Option<::def::BufferAttr> {
+        let ptr = unsafe { capi::pa_stream_get_buffer_attr(self.ptr) };
+        match ptr.is_null() {
+            true => None,
+            false => Some(unsafe { *(ptr as *const ::def::BufferAttr) }),
+        }
+    }
+
     /// Change the buffer metrics of the stream during playback.
     ///
     /// The server might have chosen different buffer metrics then requested. The selected metrics
@@ -1552,6 +2465,39 @@ impl Stream {
             Some(success_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("stream::set_buffer_attr", self.get_index())
+    }
+
+    /// Renegotiate the buffer metrics of the stream to target the specified latency.
+    ///
+    /// This computes the buffer metrics with [`::def::BufferAttr::for_playback_latency`] from the
+    /// stream’s sample spec, targeting the same latency for recording, and applies them with
+    /// [`set_buffer_attr`]. The metrics actually chosen
+    /// by the server may be queried with [`get_buffer_attr`] once the callback is called.
+    ///
+    /// The callback must accept a `bool`, which indicates success.
+    ///
+    /// Returns an error of [`Code::BadState`] if the stream’s sample spec is not available (i.e.
+    /// the stream is not connected).
+    ///
+    /// Panics if the underlying C function returns a null pointer.
+    ///
+    /// [`::def::BufferAttr::for_playback_latency`]: ../def/struct.BufferAttr.html#method.for_playback_latency
+    /// [`set_buffer_attr`]: #method.set_buffer_attr
+    /// [`get_buffer_attr`]: #method.get_buffer_attr
+    /// [`Code::BadState`]: ../error/enum.Code.html#variant.BadState
+    pub fn set_latency_target<F>(&mut self, latency: Duration, callback: F)
+        -> Result<Operation<dyn FnMut(bool)>, PAErr>
+        where F: FnMut(bool) + 'static
+    {
+        let ss = match self.get_sample_spec() {
+            Some(ss) => *ss,
+            None => return Err(PAErr::from(::error::Code::BadState)),
+        };
+        // The direction of the stream is not known, so target the latency for either
+        let mut attr = ::def::BufferAttr::for_playback_latency(&ss, latency);
+        attr.fragsize = attr.tlength;
+        Ok(self.set_buffer_attr(&attr, callback))
     }
 
     /// Change the stream sampling rate during playback.
@@ -1561,18 +2507,34 @@ impl Stream {
     ///
     /// The callback must accept a `bool`, which indicates success.
     ///
+    /// Returns an error of [`Code::NotSupported`] if the stream was not connected with
+    /// [`flags::VARIABLE_RATE`], or of [`Code::Invalid`] if `rate` is not a valid sample rate.
+    ///
     /// Panics if the underlying C function returns a null pointer.
     ///
     /// [`connect_playback`]: #method.connect_playback
     /// [`flags::VARIABLE_RATE`]: flags/constant.VARIABLE_RATE.html
-    pub fn update_sample_rate<F>(&mut self, rate: u32, callback: F) -> Operation<dyn FnMut(bool)>
+    /// [`Code::NotSupported`]: ../error/enum.Code.html#variant.NotSupported
+    /// [`Code::Invalid`]: ../error/enum.Code.html#variant.Invalid
+    pub fn update_sample_rate<F>(&mut self, rate: u32, callback: F)
+        -> Result<Operation<dyn FnMut(bool)>, PAErr>
         where F: FnMut(bool) + 'static
     {
+        // The C function would just return a null pointer for these, so check them here to be able
+        // to give a meaningful error.
+        if self.connect_flags & flags::VARIABLE_RATE == 0 {
+            return Err(PAErr::from(::error::Code::NotSupported));
+        }
+        if !::sample::rate_is_valid(rate) {
+            return Err(PAErr::from(::error::Code::Invalid));
+        }
+
         let cb_data = box_closure_get_capi_ptr::<dyn FnMut(bool)>(Box::new(callback));
         let ptr = unsafe { capi::pa_stream_update_sample_rate(self.ptr, rate,
             Some(success_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
-        Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+        Ok(Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("stream::update_sample_rate", self.get_index()))
     }
 
     /// Update the property list of the sink input/source output of this stream, adding new entries.
@@ -1595,6 +2557,29 @@ impl Stream {
             Some(success_cb_proxy), cb_data) };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("stream::update_proplist", self.get_index())
+    }
+
+    /// Set the media role of the sink input/source output of this stream, along with the related
+    /// properties of the role. See [`MediaRole`].
+    ///
+    /// Setting the role initially, with [`Proplist::set_media_role`] and [`new_with_proplist`], is
+    /// preferable, since it may be used to route the stream.
+    ///
+    /// The callback must accept a `bool`, which indicates success.
+    ///
+    /// Panics if the underlying C function returns a null pointer.
+    ///
+    /// [`MediaRole`]: ../proplist/enum.MediaRole.html
+    /// [`Proplist::set_media_role`]: ../proplist/struct.Proplist.html#method.set_media_role
+    /// [`new_with_proplist`]: #method.new_with_proplist
+    pub fn set_media_role<F>(&mut self, role: ::proplist::MediaRole, callback: F)
+        -> Operation<dyn FnMut(bool)>
+        where F: FnMut(bool) + 'static
+    {
+        let mut proplist = Proplist::new().unwrap();
+        proplist.set_media_role(role).unwrap();
+        self.update_proplist(::proplist::UpdateMode::Replace, &mut proplist, callback)
     }
 
     /// Update the property list of the sink input/source output of this stream, remove entries.
@@ -1627,11 +2612,14 @@ impl Stream {
         };
         assert!(!ptr.is_null());
         Operation::from_raw(ptr, cb_data as *mut Box<dyn FnMut(bool)>)
+            .traced("stream::remove_proplist", self.get_index())
     }
 
     /// For record streams connected to a monitor source: monitor only a very specific sink input of
     /// the sink. This function needs to be called before [`connect_record`](#method.connect_record)
     /// is called.
+    ///
+    /// See also [`connect_record_sink_input`](#method.connect_record_sink_input).
     pub fn set_monitor_stream(&mut self, sink_input_index: u32) -> Result<(), PAErr> {
         match unsafe { capi::pa_stream_set_monitor_stream(self.ptr, sink_input_index) } {
             0 => Ok(()),
@@ -1639,6 +2627,28 @@ impl Stream {
         }
     }
 
+    /// Connect the stream to record only the audio of a specific sink input, i.e. the output of a
+    /// particular playback stream, such as that of a single application.
+    ///
+    /// This is a convenience combination of [`set_monitor_stream`] and [`connect_record`]. No
+    /// source need be given, the server will connect the stream to the monitor source of the sink
+    /// that the sink input is connected to.
+    ///
+    /// # Params
+    ///
+    /// * `sink_input_index`: Index of the sink input to record
+    /// * `attr`: Buffering attributes, or `None` for default
+    /// * `flags`: Additional flags, or `0` for default
+    ///
+    /// [`set_monitor_stream`]: #method.set_monitor_stream
+    /// [`connect_record`]: #method.connect_record
+    pub fn connect_record_sink_input(&mut self, sink_input_index: u32,
+        attr: Option<&::def::BufferAttr>, flags: FlagSet) -> Result<(), PAErr>
+    {
+        self.set_monitor_stream(sink_input_index)?;
+        self.connect_record(None, attr, flags)
+    }
+
     /// Return the sink input index previously set with
     /// [`set_monitor_stream`](#method.set_monitor_stream).
     pub fn get_monitor_stream(&self) -> Option<u32> {
@@ -1649,6 +2659,55 @@ impl Stream {
     }
 }
 
+/// Guard corking a stream for its lifetime, as returned by
+/// [`Stream::cork_transaction`](struct.Stream.html#method.cork_transaction).
+#[must_use = "the stream is uncorked immediately if the guard is not held"]
+pub struct CorkTransaction<'a> {
+    stream: &'a mut Stream,
+    /// Whether or not to uncork upon completion, i.e. the stream was not corked beforehand.
+    restore: bool,
+}
+
+impl<'a> CorkTransaction<'a> {
+    /// Complete the transaction, uncorking the stream, unless it was already corked beforehand.
+    ///
+    /// Returns the uncork operation, if any, which can be awaited as a future (see
+    /// [`::operation`](../operation/index.html)) if completion needs to be known.
+    pub fn commit(mut self) -> Option<Operation<dyn FnMut(bool)>> {
+        match std::mem::replace(&mut self.restore, false) {
+            true => Some(self.stream.uncork(None)),
+            false => None,
+        }
+    }
+
+    /// Complete the transaction, leaving the stream corked.
+    pub fn keep_corked(mut self) {
+        self.restore = false;
+    }
+}
+
+impl<'a> std::ops::Deref for CorkTransaction<'a> {
+    type Target = Stream;
+
+    fn deref(&self) -> &Stream {
+        self.stream
+    }
+}
+
+impl<'a> std::ops::DerefMut for CorkTransaction<'a> {
+    fn deref_mut(&mut self) -> &mut Stream {
+        self.stream
+    }
+}
+
+impl<'a> Drop for CorkTransaction<'a> {
+    fn drop(&mut self) {
+        if self.restore {
+            drop(self.stream.uncork(None));
+        }
+    }
+}
+
 impl Drop for Stream {
     fn drop(&mut self) {
         // Throw away the `Result` from disconnecting, it may legitimately be bad if stream failed.
@@ -1662,8 +2721,9 @@ impl Drop for Stream {
 /// Proxy for completion success callbacks.
 /// Warning: This is for single-use cases only! It destroys the actual closure callback.
 extern "C"
-fn success_cb_proxy(_: *mut StreamInternal, success: i32, userdata: *mut c_void) {
+fn success_cb_proxy(s: *mut StreamInternal, success: i32, userdata: *mut c_void) {
     let success_actual = match success { 0 => false, _ => true };
+    ::logging::stream_operation_result(s, success_actual);
     let _ = std::panic::catch_unwind(|| {
         // Note, destroys closure callback after use - restoring outer box means it gets dropped
         let mut callback = ::callbacks::get_su_callback::<dyn FnMut(bool)>(userdata);
@@ -1693,6 +2753,33 @@ fn notify_cb_proxy(_: *mut StreamInternal, userdata: *mut c_void) {
     });
 }
 
+/// Proxy for state callbacks, logging the new state.
+/// Warning: This is for multi-use cases! It does **not** destroy the actual closure callback, which
+/// must be accomplished separately to avoid a memory leak.
+extern "C"
+fn state_cb_proxy(s: *mut StreamInternal, userdata: *mut c_void) {
+    ::logging::stream_state(s);
+    notify_cb_proxy(s, userdata);
+}
+
+/// Proxy for underflow callbacks, logging the underflow.
+/// Warning: This is for multi-use cases! It does **not** destroy the actual closure callback, which
+/// must be accomplished separately to avoid a memory leak.
+extern "C"
+fn underflow_cb_proxy(s: *mut StreamInternal, userdata: *mut c_void) {
+    ::logging::stream_underflow(s);
+    notify_cb_proxy(s, userdata);
+}
+
+/// Proxy for overflow callbacks, logging the overflow.
+/// Warning: This is for multi-use cases! It does **not** destroy the actual closure callback, which
+/// must be accomplished separately to avoid a memory leak.
+extern "C"
+fn overflow_cb_proxy(s: *mut StreamInternal, userdata: *mut c_void) {
+    ::logging::stream_overflow(s);
+    notify_cb_proxy(s, userdata);
+}
+
 /// Proxy for event callbacks.
 /// Warning: This is for multi-use cases! It does **not** destroy the actual closure callback, which
 /// must be accomplished separately to avoid a memory leak.
@@ -1712,3 +2799,22 @@ fn event_cb_proxy(_: *mut StreamInternal, name: *const c_char,
         (callback)(n, pl);
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_conversion() {
+        for &value in &[State::Unconnected, State::Creating,
+            State::Ready, State::Failed, State::Terminated]
+        {
+            let raw = capi::pa_stream_state_t::from(value);
+            assert_eq!(State::from(raw), value);
+            assert_eq!(State::try_from(raw.0), Ok(value));
+        }
+        assert_eq!(State::from(capi::pa_stream_state_t(42)), State::Other(42));
+        assert_eq!(capi::pa_stream_state_t::from(State::Other(42)), capi::pa_stream_state_t(42));
+        assert_eq!(State::try_from(42), Err(42));
+    }
+}
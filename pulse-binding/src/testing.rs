@@ -0,0 +1,907 @@
+// Copyright 2017 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! In-process test double of a server (requires the `testing` feature).
+//!
+//! # Overview
+//!
+//! The [`MockServer`] holds a model of server state (server information, sinks, sources, their
+//! streams, clients and cards) which tests can populate and inspect, and hands out [`MockContext`]
+//! objects implementing [`ContextOps`], allowing application logic written against the traits of
+//! the [`::context::ops`] module to be unit tested without a running PulseAudio daemon.
+//!
+//! As with a real server, requests complete asynchronously: requests are queued, and their
+//! callbacks are only called once the test calls [`MockServer::dispatch`], which plays the part of
+//! iterating the mainloop. Changes made through requests, or by the test directly (e.g. with
+//! [`MockServer::add_sink`]), generate subscription events as appropriate.
+//!
+//! Streams are represented by their sink inputs and source outputs only; data transfer is not
+//! modelled.
+//!
+//! ```rust,ignore
+//! use pulse::context::ops::ContextOps;
+//! use pulse::testing::MockServer;
+//!
+//! let server = MockServer::new();
+//! let speakers = server.add_sink("speakers", "Built-in Speakers");
+//!
+//! let mut context = server.context();
+//! context.connect(None, pulse::context::flags::NOFLAGS).unwrap();
+//! server.dispatch();
+//! assert_eq!(context.get_state(), pulse::context::State::Ready);
+//!
+//! // Application logic under test, e.g. muting the sink
+//! context.introspector().set_sink_mute_by_index(speakers, true, None);
+//! server.dispatch();
+//! assert_eq!(server.sink(speakers).unwrap().mute, true);
+//! ```
+//!
+//! [`MockServer`]: struct.MockServer.html
+//! [`MockContext`]: struct.MockContext.html
+//! [`ContextOps`]: ../context/ops/trait.ContextOps.html
+//! [`::context::ops`]: ../context/ops/index.html
+//! [`MockServer::dispatch`]: struct.MockServer.html#method.dispatch
+//! [`MockServer::add_sink`]: struct.MockServer.html#method.add_sink
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use context::{State, FlagSet};
+use context::ops::{ContextOps, IntrospectorOps, ResultCb, SuccessCb, SubscribeCb, Server, Sink,
+    Source, SinkInput, SourceOutput, Client, Card, CardProfile};
+use context::subscribe::{Facility, InterestMaskSet, Operation as SubscribeOperation};
+use error::PAErr;
+use proplist::MediaRole;
+
+type SharedState = Rc<RefCell<MockState>>;
+type NotifyCb = Rc<RefCell<Box<dyn FnMut() + 'static>>>;
+type SharedSubscribeCb = Rc<RefCell<SubscribeCb>>;
+type Request = Box<dyn FnOnce() + 'static>;
+
+/// The modelled server state.
+struct MockState {
+    server: Server,
+    sinks: Vec<Sink>,
+    sources: Vec<Source>,
+    sink_inputs: Vec<SinkInput>,
+    source_outputs: Vec<SourceOutput>,
+    clients: Vec<Client>,
+    cards: Vec<Card>,
+    next_index: u32,
+    connect_error: Option<PAErr>,
+    context_state: State,
+    state_cb: Option<NotifyCb>,
+    subscribe_cb: Option<SharedSubscribeCb>,
+    subscription: InterestMaskSet,
+    pending: VecDeque<Request>,
+}
+
+/// A mock server, for testing application logic without a running daemon.
+///
+/// See the [module level documentation](index.html) for details.
+pub struct MockServer {
+    state: SharedState,
+}
+
+/// A mock context, connecting to a [`MockServer`](struct.MockServer.html).
+pub struct MockContext {
+    state: SharedState,
+}
+
+/// A mock introspector, as obtained from a [`MockContext`](struct.MockContext.html).
+pub struct MockIntrospector {
+    state: SharedState,
+}
+
+impl Default for MockServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockServer {
+    /// Create a new mock server, with no devices.
+    pub fn new() -> Self {
+        let mut server = Server::new();
+        server.user_name = Some("test".to_string());
+        server.host_name = Some("localhost".to_string());
+        server.server_version = Some("mock".to_string());
+        server.server_name = Some("pulseaudio".to_string());
+        server.sample_spec = default_spec();
+        server.channel_map = ::channelmap::Map::stereo();
+        let state = MockState {
+            server,
+            sinks: Vec::new(),
+            sources: Vec::new(),
+            sink_inputs: Vec::new(),
+            source_outputs: Vec::new(),
+            clients: Vec::new(),
+            cards: Vec::new(),
+            next_index: 0,
+            connect_error: None,
+            context_state: State::Unconnected,
+            state_cb: None,
+            subscribe_cb: None,
+            subscription: ::context::subscribe::subscription_masks::NULL,
+            pending: VecDeque::new(),
+        };
+        Self { state: Rc::new(RefCell::new(state)) }
+    }
+
+    /// Get a context for connecting to this server.
+    ///
+    /// Note that the server models a single client connection, thus all contexts obtained share
+    /// the same state.
+    pub fn context(&self) -> MockContext {
+        MockContext { state: Rc::clone(&self.state) }
+    }
+
+    /// Make connection attempts fail with the given error, or succeed if `None`.
+    pub fn set_connect_error(&self, error: Option<PAErr>) {
+        self.state.borrow_mut().connect_error = error;
+    }
+
+    /// Run all pending requests, calling their callbacks, returning the number of requests run.
+    ///
+    /// Requests made by callbacks are also run before returning.
+    pub fn dispatch(&self) -> usize {
+        let mut count = 0;
+        loop {
+            let request = self.state.borrow_mut().pending.pop_front();
+            match request {
+                Some(request) => {
+                    request();
+                    count += 1;
+                },
+                None => return count,
+            }
+        }
+    }
+
+    /// Add a stereo sink, returning its index. The first sink added becomes the default.
+    pub fn add_sink(&self, name: &str, description: &str) -> u32 {
+        let index = {
+            let mut state = self.state.borrow_mut();
+            let index = state.allocate_index();
+            let monitor_source = state.allocate_index();
+            let server = &state.server;
+            let sink = Sink {
+                index,
+                name: Some(name.to_string()),
+                description: Some(description.to_string()),
+                sample_spec: server.sample_spec,
+                channel_map: server.channel_map,
+                volume: normal_volume(&server.sample_spec),
+                mute: false,
+                state: ::def::SinkState::Idle,
+                monitor_source,
+                card: None,
+                active_port: None,
+                flags: ::def::sink_flags::NOFLAGS,
+                formats: Vec::new(),
+            };
+            let monitor = Source {
+                index: monitor_source,
+                name: Some(format!("{}.monitor", name)),
+                description: Some(format!("Monitor of {}", description)),
+                sample_spec: sink.sample_spec,
+                channel_map: sink.channel_map,
+                volume: sink.volume,
+                mute: false,
+                state: ::def::SourceState::Idle,
+                monitor_of_sink: Some(index),
+                card: None,
+                active_port: None,
+            };
+            state.sinks.push(sink);
+            state.sources.push(monitor);
+            if state.server.default_sink_name.is_none() {
+                state.server.default_sink_name = Some(name.to_string());
+            }
+            index
+        };
+        self.notify_event(Facility::Sink, SubscribeOperation::New, index);
+        index
+    }
+
+    /// Add a stereo source, returning its index. The first source added becomes the default.
+    pub fn add_source(&self, name: &str, description: &str) -> u32 {
+        let index = {
+            let mut state = self.state.borrow_mut();
+            let index = state.allocate_index();
+            let source = Source {
+                index,
+                name: Some(name.to_string()),
+                description: Some(description.to_string()),
+                sample_spec: state.server.sample_spec,
+                channel_map: state.server.channel_map,
+                volume: normal_volume(&state.server.sample_spec),
+                mute: false,
+                state: ::def::SourceState::Idle,
+                monitor_of_sink: None,
+                card: None,
+                active_port: None,
+            };
+            state.sources.push(source);
+            if state.server.default_source_name.is_none() {
+                state.server.default_source_name = Some(name.to_string());
+            }
+            index
+        };
+        self.notify_event(Facility::Source, SubscribeOperation::New, index);
+        index
+    }
+
+    /// Add a sink input (i.e. a playback stream), connected to the given sink, returning its index.
+    pub fn add_sink_input(&self, name: &str, sink: u32) -> u32 {
+        self.add_sink_input_inner(name, sink, None)
+    }
+
+    /// Add a sink input with the given media role, connected to the given sink, returning its
+    /// index.
+    pub fn add_sink_input_with_role(&self, name: &str, sink: u32, role: MediaRole) -> u32 {
+        self.add_sink_input_inner(name, sink, Some(role))
+    }
+
+    fn add_sink_input_inner(&self, name: &str, sink: u32, role: Option<MediaRole>) -> u32 {
+        let index = {
+            let mut state = self.state.borrow_mut();
+            let index = state.allocate_index();
+            let sink_input = SinkInput {
+                index,
+                name: Some(name.to_string()),
+                client: None,
+                sink,
+                sample_spec: state.server.sample_spec,
+                channel_map: state.server.channel_map,
+                volume: normal_volume(&state.server.sample_spec),
+                mute: false,
+                corked: false,
+                media_role: role,
+            };
+            state.sink_inputs.push(sink_input);
+            index
+        };
+        self.notify_event(Facility::SinkInput, SubscribeOperation::New, index);
+        index
+    }
+
+    /// Add a source output (i.e. a recording stream), connected to the given source, returning its
+    /// index.
+    pub fn add_source_output(&self, name: &str, source: u32) -> u32 {
+        let index = {
+            let mut state = self.state.borrow_mut();
+            let index = state.allocate_index();
+            let mut source_output = SourceOutput::new(index, source);
+            source_output.name = Some(name.to_string());
+            source_output.sample_spec = state.server.sample_spec;
+            source_output.channel_map = state.server.channel_map;
+            source_output.volume = normal_volume(&state.server.sample_spec);
+            state.source_outputs.push(source_output);
+            index
+        };
+        self.notify_event(Facility::SourceOutput, SubscribeOperation::New, index);
+        index
+    }
+
+    /// Add a client, returning its index.
+    pub fn add_client(&self, name: &str) -> u32 {
+        let index = {
+            let mut state = self.state.borrow_mut();
+            let index = state.allocate_index();
+            let mut client = Client::new(index);
+            client.name = Some(name.to_string());
+            client.driver = Some("mock".to_string());
+            state.clients.push(client);
+            index
+        };
+        self.notify_event(Facility::Client, SubscribeOperation::New, index);
+        index
+    }
+
+    /// Add a card with the given profiles, returning its index. The first profile (if any) is made
+    /// active.
+    pub fn add_card(&self, name: &str, profiles: &[&str]) -> u32 {
+        let index = {
+            let mut state = self.state.borrow_mut();
+            let index = state.allocate_index();
+            let mut card = Card::new(index);
+            card.name = Some(name.to_string());
+            card.driver = Some("mock".to_string());
+            card.profiles = profiles.iter().map(|p| CardProfile::new(p)).collect();
+            card.active_profile = profiles.first().map(|p| p.to_string());
+            state.cards.push(card);
+            index
+        };
+        self.notify_event(Facility::Card, SubscribeOperation::New, index);
+        index
+    }
+
+    /// Remove a sink, along with its monitor source. Returns `false` if no such sink exists.
+    pub fn remove_sink(&self, index: u32) -> bool {
+        let monitor = {
+            let mut state = self.state.borrow_mut();
+            let pos = match state.sinks.iter().position(|s| s.index == index) {
+                Some(pos) => pos,
+                None => return false,
+            };
+            let sink = state.sinks.remove(pos);
+            state.sources.retain(|s| s.index != sink.monitor_source);
+            sink.monitor_source
+        };
+        self.notify_event(Facility::Source, SubscribeOperation::Removed, monitor);
+        self.notify_event(Facility::Sink, SubscribeOperation::Removed, index);
+        true
+    }
+
+    /// Remove a source. Returns `false` if no such source exists.
+    pub fn remove_source(&self, index: u32) -> bool {
+        {
+            let mut state = self.state.borrow_mut();
+            let count = state.sources.len();
+            state.sources.retain(|s| s.index != index);
+            if state.sources.len() == count {
+                return false;
+            }
+        }
+        self.notify_event(Facility::Source, SubscribeOperation::Removed, index);
+        true
+    }
+
+    /// Remove a sink input. Returns `false` if no such sink input exists.
+    pub fn remove_sink_input(&self, index: u32) -> bool {
+        {
+            let mut state = self.state.borrow_mut();
+            let count = state.sink_inputs.len();
+            state.sink_inputs.retain(|s| s.index != index);
+            if state.sink_inputs.len() == count {
+                return false;
+            }
+        }
+        self.notify_event(Facility::SinkInput, SubscribeOperation::Removed, index);
+        true
+    }
+
+    /// Remove a source output. Returns `false` if no such source output exists.
+    pub fn remove_source_output(&self, index: u32) -> bool {
+        {
+            let mut state = self.state.borrow_mut();
+            let count = state.source_outputs.len();
+            state.source_outputs.retain(|s| s.index != index);
+            if state.source_outputs.len() == count {
+                return false;
+            }
+        }
+        self.notify_event(Facility::SourceOutput, SubscribeOperation::Removed, index);
+        true
+    }
+
+    /// Get the current server information.
+    pub fn server(&self) -> Server {
+        self.state.borrow().server.clone()
+    }
+
+    /// Get the current state of a sink.
+    pub fn sink(&self, index: u32) -> Option<Sink> {
+        self.state.borrow().sinks.iter().find(|s| s.index == index).cloned()
+    }
+
+    /// Get the current state of a source.
+    pub fn source(&self, index: u32) -> Option<Source> {
+        self.state.borrow().sources.iter().find(|s| s.index == index).cloned()
+    }
+
+    /// Get the current state of a sink input.
+    pub fn sink_input(&self, index: u32) -> Option<SinkInput> {
+        self.state.borrow().sink_inputs.iter().find(|s| s.index == index).cloned()
+    }
+
+    /// Get the current state of a source output.
+    pub fn source_output(&self, index: u32) -> Option<SourceOutput> {
+        self.state.borrow().source_outputs.iter().find(|s| s.index == index).cloned()
+    }
+
+    /// Get the current state of a client.
+    pub fn client(&self, index: u32) -> Option<Client> {
+        self.state.borrow().clients.iter().find(|c| c.index == index).cloned()
+    }
+
+    /// Get the current state of a card.
+    pub fn card(&self, index: u32) -> Option<Card> {
+        self.state.borrow().cards.iter().find(|c| c.index == index).cloned()
+    }
+
+    fn notify_event(&self, facility: Facility, operation: SubscribeOperation, index: u32) {
+        notify_event(&self.state, facility, operation, index);
+    }
+}
+
+impl MockState {
+    fn allocate_index(&mut self) -> u32 {
+        let index = self.next_index;
+        self.next_index += 1;
+        index
+    }
+}
+
+/// Queue a request, to be run upon dispatch.
+fn queue(state: &SharedState, request: Request) {
+    state.borrow_mut().pending.push_back(request);
+}
+
+/// Change the context state, calling the state callback.
+fn set_context_state(state: &SharedState, new_state: State) {
+    let callback = {
+        let mut state = state.borrow_mut();
+        state.context_state = new_state;
+        state.state_cb.clone()
+    };
+    if let Some(callback) = callback {
+        (callback.borrow_mut())();
+    }
+}
+
+/// Deliver a subscription event to the subscribe callback, if subscribed to the facility.
+fn notify_event(state: &SharedState, facility: Facility, operation: SubscribeOperation, index: u32)
+{
+    let callback = {
+        let state = state.borrow();
+        match state.subscription & facility.to_interest_mask() {
+            0 => None,
+            _ => state.subscribe_cb.clone(),
+        }
+    };
+    if let Some(callback) = callback {
+        (callback.borrow_mut())(Some(facility), Some(operation), index);
+    }
+}
+
+/// Queue a request modifying the state, the modification returning the facility changed (if the
+/// target exists), calling the callback with the success.
+fn queue_change<F>(state: &SharedState, callback: Option<SuccessCb>, change: F)
+    where F: FnOnce(&mut MockState) -> Option<(Facility, u32)> + 'static
+{
+    let shared = Rc::clone(state);
+    queue(state, Box::new(move || {
+        let changed = change(&mut shared.borrow_mut());
+        if let Some((facility, index)) = changed {
+            notify_event(&shared, facility, SubscribeOperation::Changed, index);
+        }
+        if let Some(callback) = callback {
+            callback(changed.is_some());
+        }
+    }));
+}
+
+/// Queue a request removing an entity, calling the callback with its success, and notifying of the
+/// removal of the entity given by the closure, if any.
+fn queue_removal<F>(state: &SharedState, callback: Option<SuccessCb>, remove: F)
+    where F: FnOnce(&mut MockState) -> Option<(Facility, u32)> + 'static
+{
+    let shared = Rc::clone(state);
+    queue(state, Box::new(move || {
+        let removed = remove(&mut shared.borrow_mut());
+        if let Some((facility, index)) = removed {
+            notify_event(&shared, facility, SubscribeOperation::Removed, index);
+        }
+        if let Some(callback) = callback {
+            callback(removed.is_some());
+        }
+    }));
+}
+
+/// Queue a request reading the state, calling the callback with the result.
+fn queue_query<T, F>(state: &SharedState, callback: ResultCb<T>, query: F)
+    where T: 'static, F: FnOnce(&MockState) -> Result<T, PAErr> + 'static
+{
+    let shared = Rc::clone(state);
+    queue(state, Box::new(move || {
+        let result = query(&shared.borrow());
+        callback(result);
+    }));
+}
+
+fn no_entity() -> PAErr {
+    PAErr::from(::error::Code::NoEntity)
+}
+
+fn default_spec() -> ::sample::Spec {
+    ::sample::Spec { format: ::sample::SAMPLE_S16NE, channels: 2, rate: 44100 }
+}
+
+fn normal_volume(spec: &::sample::Spec) -> ::volume::ChannelVolumes {
+    let mut volume = ::volume::ChannelVolumes::default();
+    volume.set(u32::from(spec.channels), ::volume::VOLUME_NORM);
+    volume
+}
+
+impl ContextOps for MockContext {
+    fn get_state(&self) -> State {
+        self.state.borrow().context_state
+    }
+
+    fn connect(&mut self, _server: Option<&str>, _flags: FlagSet) -> Result<(), PAErr> {
+        if self.state.borrow().context_state != State::Unconnected {
+            return Err(PAErr::from(::error::Code::BadState));
+        }
+        set_context_state(&self.state, State::Connecting);
+        let shared = Rc::clone(&self.state);
+        queue(&self.state, Box::new(move || {
+            let error = shared.borrow().connect_error;
+            match error {
+                Some(_) => set_context_state(&shared, State::Failed),
+                None => {
+                    set_context_state(&shared, State::Authorizing);
+                    set_context_state(&shared, State::SettingName);
+                    set_context_state(&shared, State::Ready);
+                },
+            }
+        }));
+        Ok(())
+    }
+
+    fn disconnect(&mut self) {
+        let mut state = self.state.borrow_mut();
+        state.pending.clear();
+        state.subscription = ::context::subscribe::subscription_masks::NULL;
+        drop(state);
+        set_context_state(&self.state, State::Terminated);
+    }
+
+    fn is_local(&self) -> Option<bool> {
+        match self.state.borrow().context_state {
+            State::Ready => Some(true),
+            _ => None,
+        }
+    }
+
+    fn get_server(&self) -> Option<String> {
+        match self.state.borrow().context_state {
+            State::Ready => Some("mock".to_string()),
+            _ => None,
+        }
+    }
+
+    fn set_state_callback(&mut self, callback: Option<Box<dyn FnMut() + 'static>>) {
+        self.state.borrow_mut().state_cb = callback.map(|cb| Rc::new(RefCell::new(cb)));
+    }
+
+    fn set_subscribe_callback(&mut self, callback: Option<SubscribeCb>) {
+        self.state.borrow_mut().subscribe_cb = callback.map(|cb| Rc::new(RefCell::new(cb)));
+    }
+
+    fn subscribe(&mut self, mask: InterestMaskSet, callback: SuccessCb) {
+        let shared = Rc::clone(&self.state);
+        queue(&self.state, Box::new(move || {
+            shared.borrow_mut().subscription = mask;
+            callback(true);
+        }));
+    }
+
+    fn set_default_sink(&mut self, name: &str, callback: SuccessCb) {
+        let name = name.to_string();
+        queue_change(&self.state, Some(callback), move |state| {
+            if !state.sinks.iter().any(|s| s.name.as_ref() == Some(&name)) {
+                return None;
+            }
+            state.server.default_sink_name = Some(name);
+            Some((Facility::Server, ::def::INVALID_INDEX))
+        });
+    }
+
+    fn set_default_source(&mut self, name: &str, callback: SuccessCb) {
+        let name = name.to_string();
+        queue_change(&self.state, Some(callback), move |state| {
+            if !state.sources.iter().any(|s| s.name.as_ref() == Some(&name)) {
+                return None;
+            }
+            state.server.default_source_name = Some(name);
+            Some((Facility::Server, ::def::INVALID_INDEX))
+        });
+    }
+
+    fn introspector(&self) -> Box<dyn IntrospectorOps> {
+        Box::new(MockIntrospector { state: Rc::clone(&self.state) })
+    }
+}
+
+impl IntrospectorOps for MockIntrospector {
+    fn get_server_info(&self, callback: ResultCb<Server>) {
+        queue_query(&self.state, callback, |state| Ok(state.server.clone()));
+    }
+
+    fn get_sink_info_by_name(&self, name: &str, callback: ResultCb<Sink>) {
+        let name = name.to_string();
+        queue_query(&self.state, callback, move |state| {
+            state.sinks.iter().find(|s| s.name.as_ref() == Some(&name)).cloned()
+                .ok_or_else(no_entity)
+        });
+    }
+
+    fn get_sink_info_by_index(&self, index: u32, callback: ResultCb<Sink>) {
+        queue_query(&self.state, callback, move |state| {
+            state.sinks.iter().find(|s| s.index == index).cloned().ok_or_else(no_entity)
+        });
+    }
+
+    fn get_sink_info_list(&self, callback: ResultCb<Vec<Sink>>) {
+        queue_query(&self.state, callback, |state| Ok(state.sinks.clone()));
+    }
+
+    fn set_sink_volume_by_index(&mut self, index: u32, volume: &::volume::ChannelVolumes,
+        callback: Option<SuccessCb>)
+    {
+        let volume = *volume;
+        queue_change(&self.state, callback, move |state| {
+            let sink = state.sinks.iter_mut().find(|s| s.index == index)?;
+            sink.volume = volume;
+            Some((Facility::Sink, index))
+        });
+    }
+
+    fn set_sink_mute_by_index(&mut self, index: u32, mute: bool, callback: Option<SuccessCb>) {
+        queue_change(&self.state, callback, move |state| {
+            let sink = state.sinks.iter_mut().find(|s| s.index == index)?;
+            sink.mute = mute;
+            Some((Facility::Sink, index))
+        });
+    }
+
+    fn get_source_info_by_name(&self, name: &str, callback: ResultCb<Source>) {
+        let name = name.to_string();
+        queue_query(&self.state, callback, move |state| {
+            state.sources.iter().find(|s| s.name.as_ref() == Some(&name)).cloned()
+                .ok_or_else(no_entity)
+        });
+    }
+
+    fn get_source_info_by_index(&self, index: u32, callback: ResultCb<Source>) {
+        queue_query(&self.state, callback, move |state| {
+            state.sources.iter().find(|s| s.index == index).cloned().ok_or_else(no_entity)
+        });
+    }
+
+    fn get_source_info_list(&self, callback: ResultCb<Vec<Source>>) {
+        queue_query(&self.state, callback, |state| Ok(state.sources.clone()));
+    }
+
+    fn set_source_volume_by_index(&mut self, index: u32, volume: &::volume::ChannelVolumes,
+        callback: Option<SuccessCb>)
+    {
+        let volume = *volume;
+        queue_change(&self.state, callback, move |state| {
+            let source = state.sources.iter_mut().find(|s| s.index == index)?;
+            source.volume = volume;
+            Some((Facility::Source, index))
+        });
+    }
+
+    fn set_source_mute_by_index(&mut self, index: u32, mute: bool, callback: Option<SuccessCb>) {
+        queue_change(&self.state, callback, move |state| {
+            let source = state.sources.iter_mut().find(|s| s.index == index)?;
+            source.mute = mute;
+            Some((Facility::Source, index))
+        });
+    }
+
+    fn get_sink_input_info(&self, index: u32, callback: ResultCb<SinkInput>) {
+        queue_query(&self.state, callback, move |state| {
+            state.sink_inputs.iter().find(|s| s.index == index).cloned().ok_or_else(no_entity)
+        });
+    }
+
+    fn get_sink_input_info_list(&self, callback: ResultCb<Vec<SinkInput>>) {
+        queue_query(&self.state, callback, |state| Ok(state.sink_inputs.clone()));
+    }
+
+    fn move_sink_input_by_index(&mut self, index: u32, sink_index: u32,
+        callback: Option<SuccessCb>)
+    {
+        queue_change(&self.state, callback, move |state| {
+            if !state.sinks.iter().any(|s| s.index == sink_index) {
+                return None;
+            }
+            let sink_input = state.sink_inputs.iter_mut().find(|s| s.index == index)?;
+            sink_input.sink = sink_index;
+            Some((Facility::SinkInput, index))
+        });
+    }
+
+    fn set_sink_input_volume(&mut self, index: u32, volume: &::volume::ChannelVolumes,
+        callback: Option<SuccessCb>)
+    {
+        let volume = *volume;
+        queue_change(&self.state, callback, move |state| {
+            let sink_input = state.sink_inputs.iter_mut().find(|s| s.index == index)?;
+            sink_input.volume = volume;
+            Some((Facility::SinkInput, index))
+        });
+    }
+
+    fn set_sink_input_mute(&mut self, index: u32, mute: bool, callback: Option<SuccessCb>) {
+        queue_change(&self.state, callback, move |state| {
+            let sink_input = state.sink_inputs.iter_mut().find(|s| s.index == index)?;
+            sink_input.mute = mute;
+            Some((Facility::SinkInput, index))
+        });
+    }
+
+    fn kill_sink_input(&mut self, index: u32, callback: Option<SuccessCb>) {
+        queue_removal(&self.state, callback, move |state| {
+            let pos = state.sink_inputs.iter().position(|s| s.index == index)?;
+            state.sink_inputs.remove(pos);
+            Some((Facility::SinkInput, index))
+        });
+    }
+
+    fn get_source_output_info(&self, index: u32, callback: ResultCb<SourceOutput>) {
+        queue_query(&self.state, callback, move |state| {
+            state.source_outputs.iter().find(|s| s.index == index).cloned().ok_or_else(no_entity)
+        });
+    }
+
+    fn get_source_output_info_list(&self, callback: ResultCb<Vec<SourceOutput>>) {
+        queue_query(&self.state, callback, |state| Ok(state.source_outputs.clone()));
+    }
+
+    fn move_source_output_by_index(&mut self, index: u32, source_index: u32,
+        callback: Option<SuccessCb>)
+    {
+        queue_change(&self.state, callback, move |state| {
+            if !state.sources.iter().any(|s| s.index == source_index) {
+                return None;
+            }
+            let source_output = state.source_outputs.iter_mut().find(|s| s.index == index)?;
+            source_output.source = source_index;
+            Some((Facility::SourceOutput, index))
+        });
+    }
+
+    fn set_source_output_volume(&mut self, index: u32, volume: &::volume::ChannelVolumes,
+        callback: Option<SuccessCb>)
+    {
+        let volume = *volume;
+        queue_change(&self.state, callback, move |state| {
+            let source_output = state.source_outputs.iter_mut().find(|s| s.index == index)?;
+            source_output.volume = volume;
+            Some((Facility::SourceOutput, index))
+        });
+    }
+
+    fn set_source_output_mute(&mut self, index: u32, mute: bool, callback: Option<SuccessCb>) {
+        queue_change(&self.state, callback, move |state| {
+            let source_output = state.source_outputs.iter_mut().find(|s| s.index == index)?;
+            source_output.mute = mute;
+            Some((Facility::SourceOutput, index))
+        });
+    }
+
+    fn kill_source_output(&mut self, index: u32, callback: Option<SuccessCb>) {
+        queue_removal(&self.state, callback, move |state| {
+            let pos = state.source_outputs.iter().position(|s| s.index == index)?;
+            state.source_outputs.remove(pos);
+            Some((Facility::SourceOutput, index))
+        });
+    }
+
+    fn get_client_info_list(&self, callback: ResultCb<Vec<Client>>) {
+        queue_query(&self.state, callback, |state| Ok(state.clients.clone()));
+    }
+
+    fn get_card_info_list(&self, callback: ResultCb<Vec<Card>>) {
+        queue_query(&self.state, callback, |state| Ok(state.cards.clone()));
+    }
+
+    fn set_card_profile_by_index(&mut self, index: u32, profile: &str,
+        callback: Option<SuccessCb>)
+    {
+        let profile = profile.to_string();
+        queue_change(&self.state, callback, move |state| {
+            let card = state.cards.iter_mut().find(|c| c.index == index)?;
+            if card.profile(&profile).is_none() {
+                return None;
+            }
+            card.active_profile = Some(profile);
+            Some((Facility::Card, index))
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn connect_and_change_volume() {
+        let server = MockServer::new();
+        let sink = server.add_sink("speakers", "Speakers");
+        let mut context = server.context();
+        context.connect(None, ::context::flags::NOFLAGS).unwrap();
+        assert_eq!(context.get_state(), State::Connecting);
+        server.dispatch();
+        assert_eq!(context.get_state(), State::Ready);
+
+        let events = Rc::new(Cell::new(0));
+        let events_ref = Rc::clone(&events);
+        context.set_subscribe_callback(Some(Box::new(move |_, _, _| {
+            events_ref.set(events_ref.get() + 1);
+        })));
+        context.subscribe(::context::subscribe::subscription_masks::SINK, Box::new(|_| {}));
+
+        let mut introspector = context.introspector();
+        introspector.set_sink_mute_by_index(sink, true, None);
+        introspector.set_sink_mute_by_index(sink + 100, true, Some(Box::new(|s| assert!(!s))));
+        assert_eq!(server.dispatch(), 3);
+        assert!(server.sink(sink).unwrap().mute);
+        assert_eq!(server.sink(sink).unwrap().channel_map, ::channelmap::Map::stereo());
+        assert_eq!(events.get(), 1);
+    }
+
+    #[test]
+    fn connect_failure() {
+        let server = MockServer::new();
+        server.set_connect_error(Some(PAErr::from(::error::Code::ConnectionRefused)));
+        let mut context = server.context();
+        context.connect(None, ::context::flags::NOFLAGS).unwrap();
+        server.dispatch();
+        assert_eq!(context.get_state(), State::Failed);
+    }
+
+    #[test]
+    fn boxed_context_and_card_profile() {
+        let server = MockServer::new();
+        let card = server.add_card("card0", &["off", "output:analog-stereo"]);
+        let mut context: Box<dyn ContextOps> = Box::new(server.context());
+        context.connect(None, ::context::flags::NOFLAGS).unwrap();
+        server.dispatch();
+        assert_eq!(context.is_local(), Some(true));
+
+        let mut introspector = context.introspector();
+        introspector.set_card_profile_by_index(card, "output:analog-stereo", None);
+        introspector.set_card_profile_by_index(card, "bogus", Some(Box::new(|s| assert!(!s))));
+        server.dispatch();
+        assert_eq!(server.card(card).unwrap().active_profile,
+            Some("output:analog-stereo".to_string()));
+    }
+
+    #[test]
+    fn source_output_control() {
+        let server = MockServer::new();
+        let mic = server.add_source("mic", "Microphone");
+        let other = server.add_source("line", "Line In");
+        let output = server.add_source_output("recorder", mic);
+        let mut context = server.context();
+        context.connect(None, ::context::flags::NOFLAGS).unwrap();
+        server.dispatch();
+
+        let mut introspector = context.introspector();
+        introspector.set_source_output_mute(output, true, None);
+        introspector.move_source_output_by_index(output, other, None);
+        let fetched = Rc::new(Cell::new(false));
+        let fetched_ref = Rc::clone(&fetched);
+        introspector.get_source_output_info(output, Box::new(move |result| {
+            let info = result.unwrap();
+            assert!(info.mute);
+            assert_eq!(info.source, other);
+            fetched_ref.set(true);
+        }));
+        introspector.kill_source_output(output, Some(Box::new(|s| assert!(s))));
+        introspector.kill_source_output(output, Some(Box::new(|s| assert!(!s))));
+        server.dispatch();
+        assert!(fetched.get());
+        assert!(server.source_output(output).is_none());
+    }
+}
@@ -0,0 +1,168 @@
+// Copyright 2018 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Clock drift estimation.
+
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::time::Duration;
+use error::PAErr;
+use stream::Stream;
+use super::{MicroSeconds, MonotonicTs, USEC_MAX};
+
+/// Minimum span of local time covered by the samples for an estimate to be given.
+const MIN_SPAN: MicroSeconds = MicroSeconds(100_000);
+
+/// Incremental estimator of the drift between the clock of a stream (that of the sound card) and
+/// the local monotonic clock.
+///
+/// Clients bridging PulseAudio to another clock domain, such as that of a network protocol, need
+/// to know how fast the sound card runs relative to the system in order to resample accordingly.
+/// The tracker is fed the stream time periodically, typically from the stream’s read or latency
+/// update callback, and fits a line to the stream time progress against local time over a sliding
+/// window, giving the drift in parts per million.
+///
+/// ```rust,ignore
+/// use pulse::time::DriftTracker;
+///
+/// let mut tracker = DriftTracker::new(Duration::from_secs(10));
+/// // Then periodically
+/// tracker.update(&stream)?;
+/// if let Some(ppm) = tracker.drift_ppm() {
+///     resampler.set_ratio(1.0 + ppm / 1_000_000.0);
+/// }
+/// ```
+///
+/// The stream should have timing updates enabled, for instance with
+/// [`::stream::flags::AUTO_TIMING_UPDATE`] and [`::stream::flags::INTERPOLATE_TIMING`].
+///
+/// [`::stream::flags::AUTO_TIMING_UPDATE`]: ../stream/flags/constant.AUTO_TIMING_UPDATE.html
+/// [`::stream::flags::INTERPOLATE_TIMING`]: ../stream/flags/constant.INTERPOLATE_TIMING.html
+#[derive(Debug, Clone)]
+pub struct DriftTracker {
+    window: MicroSeconds,
+    /// Samples of (local time, stream time), oldest first.
+    samples: VecDeque<(MicroSeconds, MicroSeconds)>,
+}
+
+impl DriftTracker {
+    /// Create a new tracker, estimating over the given window of local time.
+    ///
+    /// Longer windows give more stable estimates, but react more slowly to change.
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window: MicroSeconds::try_from(window).unwrap_or(USEC_MAX),
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Sample the stream time, as from [`Stream::get_time`], against the current local time.
+    ///
+    /// Nothing is recorded if no timing information has been received for the stream yet.
+    ///
+    /// [`Stream::get_time`]: ../stream/struct.Stream.html#method.get_time
+    pub fn update(&mut self, stream: &Stream) -> Result<(), PAErr> {
+        if let Some(stream_time) = stream.get_time()? {
+            self.add_sample(MonotonicTs::now(), stream_time);
+        }
+        Ok(())
+    }
+
+    /// Record a sample of the stream time against the given local time.
+    ///
+    /// Samples must be given in order of local time. A sample with a stream time earlier than that
+    /// of the previous sample (e.g. following a flush) resets the tracker.
+    pub fn add_sample(&mut self, local: MonotonicTs, stream_time: MicroSeconds) {
+        let local = local.0;
+        if let Some(&(last_local, last_stream)) = self.samples.back() {
+            if stream_time < last_stream || local < last_local {
+                self.samples.clear();
+            }
+        }
+        self.samples.push_back((local, stream_time));
+        while let Some(&(oldest, _)) = self.samples.front() {
+            match local.0 - oldest.0 > self.window.0 {
+                true => { self.samples.pop_front(); },
+                false => break,
+            }
+        }
+    }
+
+    /// Get the ratio of the stream clock rate to the local clock rate, or `None` if there are too
+    /// few samples for an estimate, i.e. if they span less than 100 ms.
+    ///
+    /// A ratio greater than one means the stream clock runs fast.
+    pub fn rate_ratio(&self) -> Option<f64> {
+        let &(first_local, first_stream) = self.samples.front()?;
+        let &(last_local, _) = self.samples.back()?;
+        if last_local.0 - first_local.0 < MIN_SPAN.0 {
+            return None;
+        }
+
+        // Least squares fit of stream time against local time, relative to the first sample
+        let n = self.samples.len() as f64;
+        let (mut sum_x, mut sum_y, mut sum_xx, mut sum_xy) = (0.0, 0.0, 0.0, 0.0);
+        for &(local, stream_time) in &self.samples {
+            let x = (local.0 - first_local.0) as f64;
+            let y = stream_time.0 as f64 - first_stream.0 as f64;
+            sum_x += x;
+            sum_y += y;
+            sum_xx += x * x;
+            sum_xy += x * y;
+        }
+        let denominator = n * sum_xx - sum_x * sum_x;
+        if denominator == 0.0 {
+            return None;
+        }
+        Some((n * sum_xy - sum_x * sum_y) / denominator)
+    }
+
+    /// Get the drift of the stream clock relative to the local clock, in parts per million, or
+    /// `None` if there are too few samples for an estimate.
+    ///
+    /// Positive values mean the stream clock runs fast.
+    pub fn drift_ppm(&self) -> Option<f64> {
+        self.rate_ratio().map(|ratio| (ratio - 1.0) * 1_000_000.0)
+    }
+
+    /// Discard all samples, for instance after the stream has been moved to another device.
+    pub fn reset(&mut self) {
+        self.samples.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drift_estimate() {
+        let mut tracker = DriftTracker::new(Duration::from_secs(2));
+        assert_eq!(tracker.drift_ppm(), None);
+
+        // Stream clock running 100 ppm fast, sampled every 10 ms
+        for i in 0..500u64 {
+            let local = i * 10_000;
+            tracker.add_sample(MonotonicTs(MicroSeconds(local)),
+                MicroSeconds(local + local / 10_000));
+        }
+        let ppm = tracker.drift_ppm().unwrap();
+        assert!((ppm - 100.0).abs() < 0.01, "{}", ppm);
+
+        // Stream time going backwards resets
+        tracker.add_sample(MonotonicTs(MicroSeconds(5_000_000)), MicroSeconds(0));
+        assert_eq!(tracker.drift_ppm(), None);
+    }
+}
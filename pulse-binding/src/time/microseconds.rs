@@ -17,6 +17,7 @@
 
 use std;
 use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Rem, RemAssign, Sub, SubAssign};
+use std::convert::TryFrom;
 use std::time::Duration;
 
 /// Microseconds. This is an unsigned 64-bit type.
@@ -24,6 +25,13 @@ use std::time::Duration;
 pub struct MicroSeconds(pub u64);
 
 impl MicroSeconds {
+    /// One microsecond.
+    pub const MICROSECOND: Self = MicroSeconds(1);
+    /// One millisecond.
+    pub const MILLISECOND: Self = MicroSeconds(super::MICROS_PER_MILLI);
+    /// One second.
+    pub const SECOND: Self = MicroSeconds(super::MICROS_PER_SEC);
+
     pub fn is_valid(&self) -> bool {
         *self != super::USEC_INVALID
     }
@@ -33,7 +41,7 @@ impl MicroSeconds {
     }
 
     pub fn checked_add_duration(self, rhs: Duration) -> Option<Self> {
-        let usecs = MicroSeconds::from(rhs);
+        let usecs = MicroSeconds::try_from(rhs).ok()?;
         self.0.checked_add(usecs.0).and_then(|i| Some(MicroSeconds(i)))
     }
 
@@ -42,7 +50,7 @@ impl MicroSeconds {
     }
 
     pub fn checked_sub_duration(self, rhs: Duration) -> Option<Self> {
-        let usecs = MicroSeconds::from(rhs);
+        let usecs = MicroSeconds::try_from(rhs).ok()?;
         self.0.checked_sub(usecs.0).and_then(|i| Some(MicroSeconds(i)))
     }
 
@@ -57,6 +65,38 @@ impl MicroSeconds {
     pub fn checked_rem(self, rhs: u32) -> Option<Self> {
         self.0.checked_rem(rhs as u64).and_then(|i| Some(MicroSeconds(i)))
     }
+
+    /// Saturating addition, saturating at [`USEC_MAX`] (thus never producing [`USEC_INVALID`]).
+    ///
+    /// [`USEC_MAX`]: constant.USEC_MAX.html
+    /// [`USEC_INVALID`]: constant.USEC_INVALID.html
+    pub fn saturating_add(self, other: Self) -> Self {
+        MicroSeconds(std::cmp::min(self.0.saturating_add(other.0), super::USEC_MAX.0))
+    }
+
+    /// Saturating addition of a `Duration`, saturating at [`USEC_MAX`].
+    ///
+    /// [`USEC_MAX`]: constant.USEC_MAX.html
+    pub fn saturating_add_duration(self, rhs: Duration) -> Self {
+        self.saturating_add(MicroSeconds::try_from(rhs).unwrap_or(super::USEC_MAX))
+    }
+
+    /// Saturating subtraction, saturating at zero.
+    pub fn saturating_sub(self, other: Self) -> Self {
+        MicroSeconds(self.0.saturating_sub(other.0))
+    }
+
+    /// Saturating subtraction of a `Duration`, saturating at zero.
+    pub fn saturating_sub_duration(self, rhs: Duration) -> Self {
+        self.saturating_sub(MicroSeconds::try_from(rhs).unwrap_or(super::USEC_MAX))
+    }
+
+    /// Saturating multiplication, saturating at [`USEC_MAX`].
+    ///
+    /// [`USEC_MAX`]: constant.USEC_MAX.html
+    pub fn saturating_mul(self, rhs: u32) -> Self {
+        MicroSeconds(std::cmp::min(self.0.saturating_mul(rhs as u64), super::USEC_MAX.0))
+    }
 }
 
 impl Add for MicroSeconds {
@@ -15,6 +15,7 @@
 
 //! Time handling functionality.
 
+mod drift;
 mod microseconds;
 mod monotonic;
 mod timeval;
@@ -22,8 +23,11 @@ mod unix;
 
 use libc;
 use capi;
+use std::convert::TryFrom;
 use std::time::Duration;
+use error::PAErr;
 
+pub use self::drift::*;
 pub use self::microseconds::*;
 pub use self::monotonic::*;
 pub use self::timeval::*;
@@ -57,24 +61,36 @@ impl From<MicroSeconds> for Timeval {
     }
 }
 
-impl From<Duration> for MicroSeconds {
-    fn from(t: Duration) -> Self {
-        MicroSeconds((t.as_secs() * MILLIS_PER_SEC) + t.subsec_millis() as u64)
+/// Converts a `Duration` to microseconds, failing with [`::error::Code::TooLarge`] if it is too
+/// large to be represented as a valid `MicroSeconds` value (i.e. exceeds [`USEC_MAX`]). Any
+/// sub-microsecond component is truncated.
+///
+/// [`::error::Code::TooLarge`]: ../error/enum.Code.html#variant.TooLarge
+/// [`USEC_MAX`]: constant.USEC_MAX.html
+impl TryFrom<Duration> for MicroSeconds {
+    type Error = PAErr;
+
+    fn try_from(t: Duration) -> Result<Self, Self::Error> {
+        t.as_secs().checked_mul(MICROS_PER_SEC)
+            .and_then(|us| us.checked_add(t.subsec_micros() as u64))
+            .filter(|us| *us <= USEC_MAX.0)
+            .map(MicroSeconds)
+            .ok_or_else(|| PAErr::from(::error::Code::TooLarge))
     }
 }
 impl From<MicroSeconds> for Duration {
     fn from(t: MicroSeconds) -> Self {
-        Duration::from_millis(t.0)
+        Duration::from_micros(t.0)
     }
 }
 
 impl From<Duration> for Timeval {
     fn from(t: Duration) -> Self {
-        Timeval::new(t.as_secs() as libc::time_t, t.subsec_millis() as libc::suseconds_t)
+        Timeval::new(t.as_secs() as libc::time_t, t.subsec_micros() as libc::suseconds_t)
     }
 }
 impl From<Timeval> for Duration {
     fn from(t: Timeval) -> Self {
-        Duration::from_millis((MicroSeconds::from(t)).0)
+        Duration::from_micros((MicroSeconds::from(t)).0)
     }
 }
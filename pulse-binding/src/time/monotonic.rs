@@ -18,7 +18,7 @@
 use std;
 use capi;
 use std::ops::{Add, AddAssign, Sub, SubAssign};
-use super::MicroSeconds;
+use super::{MicroSeconds, UnixTs};
 
 /// A monotonic timestamp
 #[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd)]
@@ -45,6 +45,41 @@ impl MonotonicTs {
     pub fn checked_sub(self, other: MicroSeconds) -> Option<Self> {
         self.0.checked_sub(other).and_then(|us| Some(MonotonicTs(us)))
     }
+
+    /// Return the time elapsed since this timestamp, or zero if it lies in the future.
+    ///
+    /// This is equivalent to PA’s internal `pa_rtclock_age()` function.
+    pub fn elapsed(&self) -> MicroSeconds {
+        MonotonicTs::now().0.saturating_sub(self.0)
+    }
+
+    /// Convert a Unix wallclock timestamp to a monotonic timestamp, based upon the current offset
+    /// between the two clocks.
+    ///
+    /// Returns `None` if the result would not be representable, i.e. lies before the start of the
+    /// monotonic clock.
+    pub fn from_unix_ts(ts: UnixTs) -> Option<Self> {
+        let wc_now = UnixTs::now();
+        let rt_now = MonotonicTs::now();
+        let diff = UnixTs::diff(&ts, &wc_now);
+        match ts >= wc_now {
+            true => rt_now.checked_add(diff),
+            false => rt_now.checked_sub(diff),
+        }
+    }
+
+    /// Convert to a Unix wallclock timestamp, based upon the current offset between the two
+    /// clocks.
+    ///
+    /// Returns `None` if the result would not be representable.
+    pub fn to_unix_ts(&self) -> Option<UnixTs> {
+        let wc_now = UnixTs::now();
+        let rt_now = MonotonicTs::now();
+        match *self >= rt_now {
+            true => wc_now.checked_add(self.0 - rt_now.0),
+            false => wc_now.checked_sub(rt_now.0 - self.0),
+        }
+    }
 }
 
 impl Add<MicroSeconds> for MonotonicTs {
@@ -20,8 +20,10 @@ use capi;
 use libc;
 use std::cmp::Ordering;
 use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Rem, RemAssign, Sub, SubAssign};
-use std::time::Duration;
-use super::{UnixTs, MonotonicTs, MicroSeconds, USEC_INVALID};
+use std::convert::TryFrom;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use error::PAErr;
+use super::{UnixTs, MonotonicTs, MicroSeconds, USEC_INVALID, MICROS_PER_SEC, NANOS_PER_MICRO};
 
 /// Bit to set in `timeval`’s `tv_usec` attribute to mark that the `timeval` is in monotonic time
 const PA_TIMEVAL_RTCLOCK: i64 = 1 << 30;
@@ -81,6 +83,34 @@ impl Timeval {
         MicroSeconds(unsafe { capi::pa_timeval_age(&self.0) })
     }
 
+    /// Returns `true` if the special ‘rt’ flag bit is set, marking this as holding a monotonic
+    /// (rtclock) timestamp rather than a Unix wallclock timestamp.
+    pub fn is_rtclock(&self) -> bool {
+        (self.0.tv_usec & PA_TIMEVAL_RTCLOCK as libc::suseconds_t) != 0
+    }
+
+    /// Return a copy with the special ‘rt’ flag bit cleared, leaving just the time value.
+    pub fn strip_rtclock(&self) -> Self {
+        let mut tv = *self;
+        tv.0.tv_usec &= !(PA_TIMEVAL_RTCLOCK as libc::suseconds_t);
+        tv
+    }
+
+    /// Convert to a `SystemTime`.
+    ///
+    /// Returns `None` if this holds a monotonic timestamp (see [`is_rtclock`]), which bears no
+    /// relation to the system clock, or if the value is negative or otherwise not representable.
+    ///
+    /// [`is_rtclock`]: #method.is_rtclock
+    pub fn to_system_time(&self) -> Option<SystemTime> {
+        if self.is_rtclock() || self.0.tv_sec < 0 || self.0.tv_usec < 0 {
+            return None;
+        }
+        let secs = self.0.tv_sec as u64 + (self.0.tv_usec as u64 / MICROS_PER_SEC);
+        let nanos = (self.0.tv_usec as u64 % MICROS_PER_SEC) as u32 * NANOS_PER_MICRO;
+        UNIX_EPOCH.checked_add(Duration::new(secs, nanos))
+    }
+
     /// Set to the specified (monotonic) value
     ///
     /// The `rtclock` boolean is used for indicating support of the rtclock (monotonic time). If
@@ -131,7 +161,7 @@ impl Timeval {
 
     pub fn checked_add_duration(self, rhs: Duration) -> Option<Self> {
         let self_us = MicroSeconds::from(self);
-        let rhs_us = MicroSeconds::from(rhs);
+        let rhs_us = MicroSeconds::try_from(rhs).ok()?;
         self_us.checked_add(rhs_us).and_then(|i| Some(i.into()))
     }
 
@@ -148,7 +178,7 @@ impl Timeval {
 
     pub fn checked_sub_duration(self, rhs: Duration) -> Option<Self> {
         let self_us = MicroSeconds::from(self);
-        let rhs_us = MicroSeconds::from(rhs);
+        let rhs_us = MicroSeconds::try_from(rhs).ok()?;
         self_us.checked_sub(rhs_us).and_then(|i| Some(i.into()))
     }
 
@@ -168,6 +198,23 @@ impl Timeval {
     }
 }
 
+/// Converts a `SystemTime` to a Unix wallclock timestamp, failing with [`::error::Code::Invalid`]
+/// if it is earlier than the Unix epoch, or [`::error::Code::TooLarge`] if it is not representable.
+///
+/// [`::error::Code::Invalid`]: ../error/enum.Code.html#variant.Invalid
+/// [`::error::Code::TooLarge`]: ../error/enum.Code.html#variant.TooLarge
+impl TryFrom<SystemTime> for Timeval {
+    type Error = PAErr;
+
+    fn try_from(t: SystemTime) -> Result<Self, Self::Error> {
+        let since_epoch = t.duration_since(UNIX_EPOCH)
+            .map_err(|_| PAErr::from(::error::Code::Invalid))?;
+        let secs = libc::time_t::try_from(since_epoch.as_secs())
+            .map_err(|_| PAErr::from(::error::Code::TooLarge))?;
+        Ok(Timeval::new(secs, since_epoch.subsec_micros() as libc::suseconds_t))
+    }
+}
+
 impl Add for Timeval {
     type Output = Self;
 
@@ -13,10 +13,14 @@
 // You should have received a copy of the GNU Lesser General Public License along with this library;
 // if not, see <http://www.gnu.org/licenses/>.
 
-//! UTF-8 validation functions.
+//! UTF-8 validation and conversion functions.
 //!
-//! Bindings are not provided here for most of the PulseAudio UTF-8 functions since Rust has built
-//! in UTF-8 handling and thus they should be entirely unnecessary.
+//! Rust has built in UTF-8 handling, thus these are mostly useful for dealing with raw C strings,
+//! such as those from legacy systems, possibly containing invalid sequences, or in the encoding of
+//! the current locale. Filtering matches that done by PulseAudio itself, replacing invalid
+//! sequences with `_`, unlike [`String::from_utf8_lossy`], which uses `U+FFFD`.
+//!
+//! [`String::from_utf8_lossy`]: https://doc.rust-lang.org/std/string/struct.String.html#method.from_utf8_lossy
 
 use capi;
 use std::os::raw::{c_char, c_void};
@@ -37,3 +41,50 @@ pub fn utf8_to_locale(s: &str) -> Option<String> {
         ret
     }
 }
+
+/// Convert a string in the current locale to UTF-8.
+///
+/// Returns `None` on failure.
+pub fn locale_to_utf8(s: &CStr) -> Option<String> {
+    take_string(unsafe { capi::pa_locale_to_utf8(s.as_ptr()) })
+}
+
+/// Check whether a C string is valid UTF-8.
+pub fn utf8_valid(s: &CStr) -> bool {
+    unsafe { !capi::pa_utf8_valid(s.as_ptr()).is_null() }
+}
+
+/// Check whether a C string is valid ASCII.
+pub fn ascii_valid(s: &CStr) -> bool {
+    unsafe { !capi::pa_ascii_valid(s.as_ptr()).is_null() }
+}
+
+/// Filter a string for valid UTF-8, replacing any invalid sequences with `_`.
+///
+/// Accepts anything convertible to bytes, i.e. string slices and raw byte slices (such as those
+/// from a `CStr`). Returns `None` if the input contains a nul byte, or on failure.
+pub fn filter<S: AsRef<[u8]>>(s: S) -> Option<String> {
+    let c_str = CString::new(s.as_ref()).ok()?;
+    take_string(unsafe { capi::pa_utf8_filter(c_str.as_ptr()) })
+}
+
+/// Filter a string for valid ASCII, replacing any invalid characters with `_`.
+///
+/// Accepts anything convertible to bytes, as with [`filter`](fn.filter.html). Returns `None` if
+/// the input contains a nul byte, or on failure.
+pub fn ascii_filter<S: AsRef<[u8]>>(s: S) -> Option<String> {
+    let c_str = CString::new(s.as_ref()).ok()?;
+    take_string(unsafe { capi::pa_ascii_filter(c_str.as_ptr()) })
+}
+
+/// Take ownership of a string allocated by PulseAudio, converting it, and freeing the original.
+fn take_string(ptr: *mut c_char) -> Option<String> {
+    if ptr.is_null() {
+        return None;
+    }
+    unsafe {
+        let ret = CStr::from_ptr(ptr).to_string_lossy().into_owned();
+        capi::pa_xfree(ptr as *mut c_void);
+        Some(ret)
+    }
+}
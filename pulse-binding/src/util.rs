@@ -16,7 +16,11 @@
 //! Assorted utility functions.
 
 use capi;
-use std::ffi::CStr;
+use std::ffi::{CStr, CString};
+use std::path::PathBuf;
+
+/// Buffer size used by the functions not taking one, sufficient for any path.
+const BUFFER_SIZE: usize = 4096;
 
 macro_rules! fn_string_with_buffer {
     ( $fn_name:ident, $fn_call:ident ) => {
@@ -52,3 +56,48 @@ fn_string_with_buffer!(get_home_dir, pa_get_home_dir);
 /// supported on all architectures (in which case `NULL` is returned).
 fn_string_with_buffer!(get_binary_name, pa_get_binary_name);
 
+/// Return the current username. Returns `None` on failure.
+///
+/// This is as [`get_user_name`](fn.get_user_name.html), using a buffer of sufficient size.
+pub fn user_name() -> Option<String> {
+    get_user_name(BUFFER_SIZE)
+}
+
+/// Return the current hostname. Returns `None` on failure.
+///
+/// This is as [`get_host_name`](fn.get_host_name.html), using a buffer of sufficient size.
+pub fn host_name() -> Option<String> {
+    get_host_name(BUFFER_SIZE)
+}
+
+/// Return the home directory of the current user. Returns `None` on failure.
+///
+/// This is as [`get_home_dir`](fn.get_home_dir.html), using a buffer of sufficient size.
+pub fn home_dir() -> Option<PathBuf> {
+    get_home_dir(BUFFER_SIZE).map(PathBuf::from)
+}
+
+/// Return the binary file name of the current process, as used by PulseAudio by default for the
+/// application name and `application.process.binary` property. Returns `None` on failure, or if not
+/// supported on this architecture.
+///
+/// This is as [`get_binary_name`](fn.get_binary_name.html), using a buffer of sufficient size.
+pub fn binary_name() -> Option<String> {
+    get_binary_name(BUFFER_SIZE)
+}
+
+/// Return the file name component of a path, i.e. the part following the last `/`, or the entire
+/// string if it contains none.
+pub fn path_get_filename(path: &str) -> String {
+    // Warning: New CStrings will be immediately freed if not bound to a variable, leading to
+    // as_ptr() giving dangling pointers!
+    let c_path = CString::new(path).unwrap();
+    unsafe {
+        // Note, the returned pointer points into the given string
+        let ptr = capi::pa_path_get_filename(c_path.as_ptr());
+        match ptr.is_null() {
+            true => String::new(),
+            false => CStr::from_ptr(ptr).to_string_lossy().into_owned(),
+        }
+    }
+}
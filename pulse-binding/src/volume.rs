@@ -56,7 +56,18 @@
 //! conversion functions are rarely needed anyway, most of the time it’s sufficient to treat all
 //! volumes as opaque with a range from [`VOLUME_MUTED`] \(0%) to [`VOLUME_NORM`] \(100%).
 //!
+//! # Parsing
+//!
+//! Configuration files and command line interfaces typically express volumes as a percentage (e.g.
+//! `150%`), in decibels (e.g. `-3dB`), as a linear factor (e.g. `0.5`), or in raw units (e.g.
+//! `65536`). [`Volume::from_str_ext`] parses any of these. A [`VolumeSpec`] additionally supports
+//! relative changes (e.g. `+5%`), and values per channel, as `pactl` does, applied with
+//! [`ChannelVolumes::apply`].
+//!
 //! [`Volume`]: struct.Volume.html
+//! [`Volume::from_str_ext`]: struct.Volume.html#method.from_str_ext
+//! [`VolumeSpec`]: struct.VolumeSpec.html
+//! [`ChannelVolumes::apply`]: struct.ChannelVolumes.html#method.apply
 //! [`VolumeDB`]: struct.VolumeDB.html
 //! [`VolumeLinear`]: struct.VolumeLinear.html
 //! [`ChannelVolumes`]: struct.ChannelVolumes.html
@@ -73,6 +84,7 @@ use std;
 use capi;
 use std::ffi::CStr;
 use std::ptr::null;
+use error::{Code, PAErr};
 
 pub const VOLUME_NORM: Volume = Volume(capi::PA_VOLUME_NORM);
 pub const VOLUME_MUTED: Volume = Volume(capi::PA_VOLUME_MUTED);
@@ -84,7 +96,7 @@ pub const VOLUME_INVALID: Volume = Volume(capi::PA_VOLUME_INVALID);
 pub const DECIBEL_MINUS_INFINITY: VolumeDB = VolumeDB(capi::PA_DECIBEL_MININFTY);
 
 /// Software volume expressed as an integer
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Volume(pub capi::pa_volume_t);
 
 impl Default for Volume {
@@ -109,7 +121,7 @@ impl Default for VolumeLinear {
 
 /// A structure encapsulating a per-channel volume
 #[repr(C)]
-#[derive(Debug, Copy, Clone, Default)]
+#[derive(Copy, Clone, Default)]
 pub struct ChannelVolumes {
     /// Number of channels.
     pub channels: u8,
@@ -214,6 +226,14 @@ impl Volume {
         self.0 = capi::pa_clamp_volume(self.0)
     }
 
+    /// Get the volume limited to the range between muted and [`VOLUME_NORM`], as typically wanted
+    /// for volumes set by applications, rather than users.
+    ///
+    /// [`VOLUME_NORM`]: constant.VOLUME_NORM.html
+    pub fn clamp_to_norm(self) -> Self {
+        std::cmp::min(self, VOLUME_NORM)
+    }
+
     /// Multiply two software volumes, return the result.
     /// This uses [`VOLUME_NORM`](constant.VOLUME_NORM.html) as neutral element of multiplication.
     /// This is only valid for software volumes!
@@ -264,9 +284,30 @@ impl Volume {
     }
 }
 
+impl Volume {
+    /// Write the raw value, percentage and dB value, e.g. `65536 (100% / 0.00 dB)`.
+    fn fmt_verbose(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} ({} / {})", self.0, self.print(), self.print_db())
+    }
+}
+
+/// Shows the percentage, e.g. `100%`, or with the alternate flag (`{:#}`), additionally the raw
+/// value and dB value, e.g. `65536 (100% / 0.00 dB)`.
 impl std::fmt::Display for Volume {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "{}", &self.print())
+        match f.alternate() {
+            true => self.fmt_verbose(f),
+            false => write!(f, "{}", &self.print()),
+        }
+    }
+}
+
+/// Shows the raw value, percentage and dB value, e.g. `Volume(65536 (100% / 0.00 dB))`.
+impl std::fmt::Debug for Volume {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Volume(")?;
+        self.fmt_verbose(f)?;
+        write!(f, ")")
     }
 }
 
@@ -274,13 +315,13 @@ impl ChannelVolumes {
     /// Initialize the specified volume and return a pointer to it. The sample spec will have a
     /// defined state but [`is_valid`](#method.is_valid) will fail for it.
     pub fn init(&mut self) -> &Self {
-        unsafe { capi::pa_cvolume_init(std::mem::transmute(&self)) };
+        unsafe { capi::pa_cvolume_init(std::mem::transmute(&mut *self)) };
         self
     }
 
     /// Set the volume of the specified number of channels to the supplied volume
     pub fn set(&mut self, channels: u32, v: Volume) -> &Self {
-        unsafe { capi::pa_cvolume_set(std::mem::transmute(&self), channels, v.0) };
+        unsafe { capi::pa_cvolume_set(std::mem::transmute(&mut *self), channels, v.0) };
         self
     }
 
@@ -384,8 +425,8 @@ impl ChannelVolumes {
     /// If `with` is `None`, multiplies with itself. This is only valid for software volumes!
     /// Returns pointer to self.
     pub fn sw_multiply(&mut self, with: Option<&Self>) -> &mut Self {
-        unsafe { capi::pa_sw_cvolume_multiply(std::mem::transmute(&self),
-            std::mem::transmute(&self), std::mem::transmute(with.unwrap_or(&*self))) };
+        unsafe { capi::pa_sw_cvolume_multiply(std::mem::transmute(&mut *self),
+            std::mem::transmute(&*self), std::mem::transmute(with.unwrap_or(&*self))) };
         self
     }
 
@@ -393,8 +434,8 @@ impl ChannelVolumes {
     ///
     /// This is only valid for software volumes! Returns pointer to self.
     pub fn sw_multiply_scalar(&mut self, with: Volume) -> &mut Self {
-        unsafe { capi::pa_sw_cvolume_multiply_scalar(std::mem::transmute(&self),
-            std::mem::transmute(&self), with.0) };
+        unsafe { capi::pa_sw_cvolume_multiply_scalar(std::mem::transmute(&mut *self),
+            std::mem::transmute(&*self), with.0) };
         self
     }
 
@@ -405,12 +446,12 @@ impl ChannelVolumes {
     pub fn sw_divide(&mut self, with: Option<&Self>) -> &mut Self {
         match with {
             Some(with) => unsafe {
-                capi::pa_sw_cvolume_divide(std::mem::transmute(&self), std::mem::transmute(&self),
-                    std::mem::transmute(with))
+                capi::pa_sw_cvolume_divide(std::mem::transmute(&mut *self),
+                    std::mem::transmute(&*self), std::mem::transmute(with))
             },
             None => unsafe {
-                capi::pa_sw_cvolume_divide(std::mem::transmute(&self), std::mem::transmute(&self),
-                    std::mem::transmute(&self))
+                capi::pa_sw_cvolume_divide(std::mem::transmute(&mut *self),
+                    std::mem::transmute(&*self), std::mem::transmute(&*self))
             },
         };
         self
@@ -420,8 +461,8 @@ impl ChannelVolumes {
     ///
     /// This is only valid for software volumes! Returns pointer to self.
     pub fn sw_divide_scalar(&mut self, with: Volume) -> &mut Self {
-        unsafe { capi::pa_sw_cvolume_divide_scalar(std::mem::transmute(&self),
-            std::mem::transmute(&self), with.0) };
+        unsafe { capi::pa_sw_cvolume_divide_scalar(std::mem::transmute(&mut *self),
+            std::mem::transmute(&*self), with.0) };
         self
     }
 
@@ -429,7 +470,7 @@ impl ChannelVolumes {
     ///
     /// Returns pointer to self.
     pub fn remap(&mut self, from: &::channelmap::Map, to: &::channelmap::Map) -> &mut Self {
-        unsafe { capi::pa_cvolume_remap(std::mem::transmute(&self),
+        unsafe { capi::pa_cvolume_remap(std::mem::transmute(&mut *self),
             std::mem::transmute(from), std::mem::transmute(to)) };
         self
     }
@@ -470,7 +511,7 @@ impl ChannelVolumes {
     /// [`get_balance`]: #method.get_balance
     /// [`::channelmap::Map::can_balance`]: ../channelmap/struct.Map.html#method.can_balance
     pub fn set_balance(&mut self, map: &::channelmap::Map, new_balance: f32) -> Option<&mut Self> {
-        let ptr = unsafe { capi::pa_cvolume_set_balance(std::mem::transmute(&self),
+        let ptr = unsafe { capi::pa_cvolume_set_balance(std::mem::transmute(&mut *self),
             std::mem::transmute(map), new_balance) };
         if ptr.is_null() {
             return None;
@@ -504,7 +545,7 @@ impl ChannelVolumes {
     /// [`get_fade`]: #method.get_fade
     /// [`::channelmap::Map::can_fade`]: ../channelmap/struct.Map.html#method.can_fade
     pub fn set_fade(&mut self, map: &::channelmap::Map, new_fade: f32) -> Option<&mut Self>{
-        let ptr = unsafe { capi::pa_cvolume_set_fade(std::mem::transmute(&self),
+        let ptr = unsafe { capi::pa_cvolume_set_fade(std::mem::transmute(&mut *self),
             std::mem::transmute(map), new_fade) };
         if ptr.is_null() {
             return None;
@@ -540,7 +581,7 @@ impl ChannelVolumes {
     pub fn set_lfe_balance(&mut self, map: &::channelmap::Map, new_balance: f32)
         -> Option<&mut Self>
     {
-        let ptr = unsafe { capi::pa_cvolume_set_lfe_balance(std::mem::transmute(&self),
+        let ptr = unsafe { capi::pa_cvolume_set_lfe_balance(std::mem::transmute(&mut *self),
             std::mem::transmute(map), new_balance) };
         if ptr.is_null() {
             return None;
@@ -553,7 +594,7 @@ impl ChannelVolumes {
     /// The proportions between the channel volumes are kept.
     /// Returns pointer to self, or `None` on error.
     pub fn scale(&mut self, max: Volume) -> Option<&mut Self> {
-        let ptr = unsafe { capi::pa_cvolume_scale(std::mem::transmute(&self), max.0) };
+        let ptr = unsafe { capi::pa_cvolume_scale(std::mem::transmute(&mut *self), max.0) };
         if ptr.is_null() {
             return None;
         }
@@ -573,7 +614,7 @@ impl ChannelVolumes {
         mask: Option<::channelmap::PositionMask>) -> Option<&mut Self>
     {
         let mask_actual = mask.unwrap_or(::channelmap::POSITION_MASK_ALL);
-        let ptr = unsafe { capi::pa_cvolume_scale_mask(std::mem::transmute(&self), max.0,
+        let ptr = unsafe { capi::pa_cvolume_scale_mask(std::mem::transmute(&mut *self), max.0,
             std::mem::transmute(cm), mask_actual) };
         if ptr.is_null() {
             return None;
@@ -594,7 +635,7 @@ impl ChannelVolumes {
         // Note: C function returns NULL on invalid data or no channel at position specified (no
         // change needed). We could ignore failure and always return self ptr, but it does not seem
         // ideal to leave callers unaware should they be passing in invalid data.
-        let ptr = unsafe { capi::pa_cvolume_set_position(std::mem::transmute(&self),
+        let ptr = unsafe { capi::pa_cvolume_set_position(std::mem::transmute(&mut *self),
             std::mem::transmute(map), t.into(), v.0) };
         if ptr.is_null() {
             return None;
@@ -624,8 +665,8 @@ impl ChannelVolumes {
     ///
     /// Returns pointer to self, or `None` on error.
     pub fn merge(&mut self, with: &Self) -> Option<&mut Self> {
-        let ptr = unsafe { capi::pa_cvolume_merge(std::mem::transmute(&self),
-            std::mem::transmute(&self), std::mem::transmute(with)) };
+        let ptr = unsafe { capi::pa_cvolume_merge(std::mem::transmute(&mut *self),
+            std::mem::transmute(&*self), std::mem::transmute(with)) };
         if ptr.is_null() {
             return None;
         }
@@ -636,7 +677,8 @@ impl ChannelVolumes {
     /// The proportions between the channels are kept.
     /// Returns pointer to self, or `None` on error.
     pub fn inc_clamp(&mut self, inc: Volume, limit: Volume) -> Option<&mut Self> {
-        let ptr = unsafe { capi::pa_cvolume_inc_clamp(std::mem::transmute(&self), inc.0, limit.0) };
+        let ptr = unsafe { capi::pa_cvolume_inc_clamp(std::mem::transmute(&mut *self), inc.0,
+            limit.0) };
         if ptr.is_null() {
             return None;
         }
@@ -647,7 +689,7 @@ impl ChannelVolumes {
     /// The proportions between the channels are kept.
     /// Returns pointer to self, or `None` on error.
     pub fn increase(&mut self, inc: Volume) -> Option<&mut Self> {
-        let ptr = unsafe { capi::pa_cvolume_inc(std::mem::transmute(&self), inc.0) };
+        let ptr = unsafe { capi::pa_cvolume_inc(std::mem::transmute(&mut *self), inc.0) };
         if ptr.is_null() {
             return None;
         }
@@ -658,13 +700,65 @@ impl ChannelVolumes {
     /// The proportions between the channels are kept.
     /// Returns pointer to self, or `None` on error.
     pub fn decrease(&mut self, dec: Volume) -> Option<&mut Self> {
-        let ptr = unsafe { capi::pa_cvolume_dec(std::mem::transmute(&self), dec.0) };
+        let ptr = unsafe { capi::pa_cvolume_dec(std::mem::transmute(&mut *self), dec.0) };
         if ptr.is_null() {
             return None;
         }
         Some(self)
     }
 
+    /// Increase the volume by `delta` decibels, as a volume “up” key would, but not exceeding
+    /// [`VOLUME_MAX`].
+    ///
+    /// The loudest channel is raised by `delta`, with the proportions between the channels kept. A
+    /// muted volume is left unchanged (since it has no level in decibels to raise).
+    /// Returns pointer to self, or `None` on error.
+    ///
+    /// [`VOLUME_MAX`]: constant.VOLUME_MAX.html
+    pub fn increase_by_db(&mut self, delta: VolumeDB) -> Option<&mut Self> {
+        let max = self.max();
+        let target = Volume::from(VolumeDB(VolumeDB::from(max).0 + delta.0));
+        let inc = target.0.saturating_sub(max.0);
+        self.inc_clamp(Volume(inc), VOLUME_MAX)
+    }
+
+    /// Decrease the volume by `delta` decibels, as a volume “down” key would.
+    ///
+    /// The loudest channel is lowered by `delta`, with the proportions between the channels kept.
+    /// Returns pointer to self, or `None` on error.
+    pub fn decrease_by_db(&mut self, delta: VolumeDB) -> Option<&mut Self> {
+        let max = self.max();
+        let target = Volume::from(VolumeDB(VolumeDB::from(max).0 - delta.0));
+        let dec = max.0.saturating_sub(target.0);
+        self.decrease(Volume(dec))
+    }
+
+    /// Scale so that the maximum volume of all channels equals `max`, which is first clamped to the
+    /// permitted range.
+    ///
+    /// The proportions between the channel volumes are kept.
+    /// Returns pointer to self, or `None` on error.
+    pub fn scale_to_max(&mut self, mut max: Volume) -> Option<&mut Self> {
+        Volume::clamp(&mut max);
+        self.scale(max)
+    }
+
+    /// Set the volume of the channel(s) at the given position, using the channel map to find them.
+    ///
+    /// This is as [`set_position`](#method.set_position), except that the channel map is first
+    /// checked to be compatible, and the volume clamped to the permitted range.
+    /// Returns pointer to self, or `None` if the map is incompatible, or has no channel at the
+    /// position.
+    pub fn set_channel(&mut self, map: &::channelmap::Map, position: ::channelmap::Position,
+        mut volume: Volume) -> Option<&mut Self>
+    {
+        if !self.is_compatible_with_cm(map) {
+            return None;
+        }
+        Volume::clamp(&mut volume);
+        self.set_position(map, position, volume)
+    }
+
     /// Pretty print a volume structure
     pub fn print(&self) -> String {
         const PRINT_MAX: usize = capi::PA_CVOLUME_SNPRINT_MAX;
@@ -708,8 +802,345 @@ impl ChannelVolumes {
     }
 }
 
+impl ChannelVolumes {
+    /// Get an object for displaying the volume of each channel, labelled by its position in the
+    /// given channel map, e.g. `front-left: 65536 (100% / 0.00 dB), front-right: ...`.
+    ///
+    /// Channels beyond those of the map are labelled by number.
+    pub fn display_with_map<'a>(&'a self, map: &'a ::channelmap::Map) -> DisplayWithMap<'a> {
+        DisplayWithMap { volumes: self, map: Some(map) }
+    }
+
+    /// Get the volumes of the channels in use.
+    fn active(&self) -> &[Volume] {
+        &self.values[..std::cmp::min(self.channels as usize, ::sample::CHANNELS_MAX)]
+    }
+}
+
+/// Shows the percentage of each channel, e.g. `0: 100% 1: 100%`, or with the alternate flag
+/// (`{:#}`), additionally the raw and dB values, e.g. `0: 65536 (100% / 0.00 dB), 1: ...`.
 impl std::fmt::Display for ChannelVolumes {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "{}", &self.print())
+        match f.alternate() {
+            true => std::fmt::Display::fmt(&DisplayWithMap { volumes: self, map: None }, f),
+            false => write!(f, "{}", &self.print()),
+        }
+    }
+}
+
+/// Shows the channel count and the raw value, percentage and dB value of each channel in use.
+impl std::fmt::Debug for ChannelVolumes {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("ChannelVolumes")
+            .field("channels", &self.channels)
+            .field("values", &self.active())
+            .finish()
+    }
+}
+
+/// Display of channel volumes, labelled by channel position, as returned by
+/// [`ChannelVolumes::display_with_map`](struct.ChannelVolumes.html#method.display_with_map).
+#[derive(Debug, Copy, Clone)]
+pub struct DisplayWithMap<'a> {
+    volumes: &'a ChannelVolumes,
+    map: Option<&'a ::channelmap::Map>,
+}
+
+impl<'a> std::fmt::Display for DisplayWithMap<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for (i, volume) in self.volumes.active().iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            let label = match self.map {
+                Some(map) if i < map.channels as usize => {
+                    ::channelmap::Position::to_string(map.map[i])
+                },
+                _ => None,
+            };
+            match label {
+                Some(label) => write!(f, "{}: ", label)?,
+                None => write!(f, "{}: ", i)?,
+            }
+            volume.fmt_verbose(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// A single volume value, as written in a [`VolumeSpec`](struct.VolumeSpec.html).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum VolumeValue {
+    /// In raw volume units, e.g. `65536`.
+    Raw(i64),
+    /// As a percentage, e.g. `80%`.
+    Percent(f64),
+    /// As a linear factor, e.g. `0.5`.
+    Linear(f64),
+    /// In decibels, e.g. `-6dB`.
+    Decibel(f64),
+}
+
+impl VolumeValue {
+    /// Convert to raw volume units, keeping the sign. Not for decibel values.
+    fn to_raw(&self) -> i64 {
+        match *self {
+            VolumeValue::Raw(v) => v,
+            VolumeValue::Percent(p) => (p * VOLUME_NORM.0 as f64 / 100.0).round() as i64,
+            VolumeValue::Linear(l) => {
+                let raw = Volume::from(VolumeLinear(l.abs())).0 as i64;
+                match l < 0.0 {
+                    true => -raw,
+                    false => raw,
+                }
+            },
+            VolumeValue::Decibel(_) => unreachable!(),
+        }
+    }
+
+    /// Convert to an absolute volume, failing if out of range.
+    fn to_volume(&self) -> Result<Volume, PAErr> {
+        let raw = match *self {
+            VolumeValue::Decibel(db) => return Ok(Volume::from(VolumeDB(db))),
+            VolumeValue::Linear(l) if l < 0.0 => return Err(PAErr::from(Code::Invalid)),
+            value => value.to_raw(),
+        };
+        match raw >= 0 && raw <= VOLUME_MAX.0 as i64 {
+            true => Ok(Volume(raw as u32)),
+            false => Err(PAErr::from(Code::Invalid)),
+        }
+    }
+}
+
+impl std::str::FromStr for VolumeValue {
+    type Err = PAErr;
+
+    /// Parse a value in any of the forms, with the unit (`%` or `dB`) matched case-insensitively.
+    /// A value with a decimal point, but no unit, is a linear factor.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_lowercase();
+        let value = match lower.ends_with("db") {
+            true => lower[..lower.len() - 2].trim_end().parse().ok().map(VolumeValue::Decibel),
+            false if lower.ends_with('%') => {
+                lower[..lower.len() - 1].trim_end().parse().ok().map(VolumeValue::Percent)
+            },
+            false if lower.contains('.') => lower.parse().ok().map(VolumeValue::Linear),
+            false => lower.parse().ok().map(VolumeValue::Raw),
+        };
+        value.filter(|v| match *v {
+            VolumeValue::Raw(_) => true,
+            VolumeValue::Percent(f) | VolumeValue::Linear(f) | VolumeValue::Decibel(f) => {
+                f.is_finite()
+            },
+        }).ok_or(PAErr::from(Code::Invalid))
+    }
+}
+
+/// Writes the value as parsed, e.g. `80%`, `-6dB`, `0.5` or `65536`.
+impl std::fmt::Display for VolumeValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            VolumeValue::Raw(v) => write!(f, "{}", v),
+            VolumeValue::Percent(p) => write!(f, "{}%", p),
+            VolumeValue::Linear(l) if l.fract() == 0.0 => write!(f, "{:.1}", l),
+            VolumeValue::Linear(l) => write!(f, "{}", l),
+            VolumeValue::Decibel(d) => write!(f, "{}dB", d),
+        }
+    }
+}
+
+impl Volume {
+    /// Parse a volume given as a percentage (e.g. `150%`), in decibels (e.g. `-3dB`), as a linear
+    /// factor (e.g. `0.5`), or in raw units (e.g. `65536`).
+    ///
+    /// Returns an error of [`Code::Invalid`] if the string is not of any of these forms, or is out
+    /// of range (negative, other than in decibels, or above [`VOLUME_MAX`]).
+    ///
+    /// [`Code::Invalid`]: ../error/enum.Code.html#variant.Invalid
+    /// [`VOLUME_MAX`]: constant.VOLUME_MAX.html
+    pub fn from_str_ext(s: &str) -> Result<Self, PAErr> {
+        s.parse::<VolumeValue>()?.to_volume()
+    }
+}
+
+/// A volume to set, or change to make, as typically given by a user, parsed from the form accepted
+/// by `pactl`.
+///
+/// This is one or more whitespace separated values, each as accepted by
+/// [`Volume::from_str_ext`](struct.Volume.html#method.from_str_ext): either a single value for all
+/// channels, or one per channel. A leading `+` or `-` makes the values relative to the current
+/// volume, in which case all must have one.
+///
+/// ```rust,ignore
+/// let spec: VolumeSpec = "+5%".parse()?;
+/// volumes.apply(&spec)?;
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct VolumeSpec {
+    /// Whether the values are relative to the current volume, rather than absolute.
+    pub relative: bool,
+    /// A single value for all channels, or one per channel.
+    pub values: Vec<VolumeValue>,
+}
+
+impl std::str::FromStr for VolumeSpec {
+    type Err = PAErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut relative = None;
+        let mut values = Vec::new();
+        for arg in s.split_whitespace() {
+            let is_relative = arg.starts_with('+') || arg.starts_with('-');
+            if *relative.get_or_insert(is_relative) != is_relative {
+                return Err(PAErr::from(Code::Invalid));
+            }
+            values.push(arg.trim_start_matches('+').parse()?);
+        }
+        match relative {
+            Some(relative) => Ok(VolumeSpec { relative: relative, values: values }),
+            None => Err(PAErr::from(Code::Invalid)),
+        }
+    }
+}
+
+/// Writes the values as parsed, e.g. `+5% +5%`.
+impl std::fmt::Display for VolumeSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for (i, value) in self.values.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            let negative = match *value {
+                VolumeValue::Raw(v) => v < 0,
+                VolumeValue::Percent(v) | VolumeValue::Linear(v) | VolumeValue::Decibel(v) => {
+                    v.is_sign_negative()
+                },
+            };
+            if self.relative && !negative {
+                f.write_str("+")?;
+            }
+            write!(f, "{}", value)?;
+        }
+        Ok(())
+    }
+}
+
+impl ChannelVolumes {
+    /// Apply a volume specification, setting or changing the volume of each channel.
+    ///
+    /// Relative decibel changes scale the volume, other relative changes add to it, with the
+    /// result limited to between muted and [`VOLUME_MAX`]. Returns an error of [`Code::Invalid`],
+    /// leaving the volumes unchanged, if the number of values is neither one nor the number of
+    /// channels, or an absolute value is out of range.
+    ///
+    /// [`VOLUME_MAX`]: constant.VOLUME_MAX.html
+    /// [`Code::Invalid`]: ../error/enum.Code.html#variant.Invalid
+    pub fn apply(&mut self, spec: &VolumeSpec) -> Result<&mut Self, PAErr> {
+        let channels = self.active().len();
+        if spec.values.len() != 1 && spec.values.len() != channels {
+            return Err(PAErr::from(Code::Invalid));
+        }
+        let mut new = *self;
+        for (i, volume) in new.values[..channels].iter_mut().enumerate() {
+            let value = spec.values[if spec.values.len() == 1 { 0 } else { i }];
+            *volume = match (spec.relative, value) {
+                (true, VolumeValue::Decibel(db)) => {
+                    Volume::from(VolumeDB(VolumeDB::from(*volume).0 + db))
+                },
+                (true, value) => {
+                    let raw = volume.0 as i64 + value.to_raw();
+                    Volume(raw.max(0).min(VOLUME_MAX.0 as i64) as u32)
+                },
+                (false, value) => value.to_volume()?,
+            };
+        }
+        *self = new;
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn channel_volumes_format() {
+        let mut volumes = ChannelVolumes::default();
+        volumes.channels = 2;
+        volumes.values[0] = VOLUME_NORM;
+        volumes.values[1] = Volume(98304);
+        assert_eq!(format!("{:?}", volumes), "ChannelVolumes { channels: 2, values: \
+            [Volume(65536 (100% / 0.00 dB)), Volume(98304 (150% / 10.57 dB))] }");
+        assert_eq!(format!("{}", volumes), "0: 100% 1: 150%");
+        assert_eq!(format!("{:#}", volumes),
+            "0: 65536 (100% / 0.00 dB), 1: 98304 (150% / 10.57 dB)");
+
+        let map = ::channelmap::Map::stereo();
+        assert_eq!(volumes.display_with_map(&map).to_string(),
+            "front-left: 65536 (100% / 0.00 dB), front-right: 98304 (150% / 10.57 dB)");
+    }
+
+    #[test]
+    fn channel_volumes_set() {
+        let mut volumes = ChannelVolumes::default();
+        volumes.set(2, Volume(98304));
+        assert_eq!(volumes.channels, 2);
+        assert_eq!(&volumes.values[..2], &[Volume(98304), Volume(98304)]);
+
+        volumes.sw_multiply_scalar(Volume(32768));
+        assert_eq!(&volumes.values[..2], &[Volume(49152), Volume(49152)]);
+
+        volumes.reset(1);
+        assert_eq!(volumes.channels, 1);
+        assert_eq!(volumes.values[0], VOLUME_NORM);
+    }
+
+    #[test]
+    fn parse_volume_spec() {
+        assert_eq!("80%".parse(), Ok(VolumeValue::Percent(80.0)));
+        assert_eq!("-3 dB".parse(), Ok(VolumeValue::Decibel(-3.0)));
+        assert_eq!("1.5".parse(), Ok(VolumeValue::Linear(1.5)));
+        assert_eq!("65536".parse(), Ok(VolumeValue::Raw(65536)));
+        assert!("loud".parse::<VolumeValue>().is_err());
+        assert!("nan%".parse::<VolumeValue>().is_err());
+
+        assert_eq!(Volume::from_str_ext("150%"), Ok(Volume(98304)));
+        assert_eq!(Volume::from_str_ext("65536"), Ok(VOLUME_NORM));
+        assert!(Volume::from_str_ext("-5%").is_err());
+
+        let spec: VolumeSpec = "+5% -2".parse().unwrap();
+        assert_eq!(spec.values, vec![VolumeValue::Percent(5.0), VolumeValue::Raw(-2)]);
+        let spec: VolumeSpec = "+5% +1.0 -3dB".parse().unwrap();
+        assert!(spec.relative);
+        assert_eq!(spec.values, vec![VolumeValue::Percent(5.0), VolumeValue::Linear(1.0),
+            VolumeValue::Decibel(-3.0)]);
+        assert_eq!(spec.to_string(), "+5% +1.0 -3dB");
+        assert!("".parse::<VolumeSpec>().is_err());
+        assert!("80% -3dB".parse::<VolumeSpec>().is_err());
+    }
+
+    #[test]
+    fn apply_volume_spec() {
+        let mut volumes = ChannelVolumes::default();
+        volumes.channels = 2;
+        volumes.values[0] = Volume(60000);
+        volumes.values[1] = Volume(1000);
+
+        let mut raised = volumes;
+        raised.apply(&"+5%".parse().unwrap()).unwrap();
+        assert_eq!(&raised.values[..2], &[Volume(63277), Volume(4277)]);
+
+        let mut lowered = volumes;
+        lowered.apply(&"-2000".parse().unwrap()).unwrap();
+        assert_eq!(&lowered.values[..2], &[Volume(58000), VOLUME_MUTED]);
+
+        let mut set = volumes;
+        set.apply(&"100 100%".parse().unwrap()).unwrap();
+        assert_eq!(&set.values[..2], &[Volume(100), VOLUME_NORM]);
+
+        let mut unchanged = volumes;
+        assert!(unchanged.apply(&"1 2 3".parse().unwrap()).is_err());
+        assert!(unchanged.apply(&"100 99999999999".parse().unwrap()).is_err());
+        assert_eq!(unchanged, volumes);
     }
 }
@@ -0,0 +1,135 @@
+// Copyright 2018 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language binding.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Interoperation with WAV files, as read and written with the `hound` crate.
+//!
+//! # Overview
+//!
+//! With the `hound` feature enabled, a [`Spec`] and [`Map`] can be created from the [`WavSpec`] of
+//! a WAV file, for a stream playing it, and a [`WavSpec`] from a [`Spec`], for a file recording a
+//! stream:
+//!
+//! ```rust,ignore
+//! use pulse::sample::Spec;
+//! use pulse::channelmap::Map;
+//!
+//! let reader = hound::WavReader::open("sound.wav")?;
+//! let spec = Spec::try_from_wav_spec(&reader.spec()).expect("unsupported format");
+//! let map = Map::from_wav_spec(&reader.spec());
+//! ```
+//!
+//! WAV data is always little endian, thus only little endian (or single byte) formats have an
+//! equivalent; the A-law, μ-law and 24-in-32 bit formats have none.
+//!
+//! Files of the WAVEFORMATEXTENSIBLE format give the speaker position of each channel as a mask,
+//! which `hound` does not expose; where read by other means, it can be converted with
+//! [`Map::from_wave_channel_mask`].
+//!
+//! [`Spec`]: ../sample/struct.Spec.html
+//! [`Map`]: ../channelmap/struct.Map.html
+//! [`WavSpec`]: https://docs.rs/hound/3/hound/struct.WavSpec.html
+//! [`Map::from_wave_channel_mask`]: ../channelmap/struct.Map.html#method.from_wave_channel_mask
+
+use hound::{SampleFormat, WavSpec};
+use channelmap::Map;
+use sample::{Format, Spec};
+
+impl Spec {
+    /// Create a sample spec from the spec of a WAV file.
+    ///
+    /// Returns `None` if the WAV sample format has no equivalent, or the rate or number of
+    /// channels is not supported.
+    pub fn try_from_wav_spec(spec: &WavSpec) -> Option<Self> {
+        let format = match (spec.sample_format, spec.bits_per_sample) {
+            (SampleFormat::Int, 8) => Format::U8,
+            (SampleFormat::Int, 16) => Format::S16le,
+            (SampleFormat::Int, 24) => Format::S24le,
+            (SampleFormat::Int, 32) => Format::S32le,
+            (SampleFormat::Float, 32) => Format::F32le,
+            _ => return None,
+        };
+        if spec.sample_rate == 0 || spec.sample_rate > ::sample::RATE_MAX || spec.channels == 0
+            || spec.channels as usize > ::sample::CHANNELS_MAX
+        {
+            return None;
+        }
+        Some(Spec { format: format, rate: spec.sample_rate, channels: spec.channels as u8 })
+    }
+
+    /// Get the spec of a WAV file holding samples of this spec.
+    ///
+    /// Returns `None` if the sample format has no WAV equivalent.
+    pub fn to_wav_spec(&self) -> Option<WavSpec> {
+        let (sample_format, bits_per_sample) = match self.format {
+            Format::U8 => (SampleFormat::Int, 8),
+            Format::S16le => (SampleFormat::Int, 16),
+            Format::S24le => (SampleFormat::Int, 24),
+            Format::S32le => (SampleFormat::Int, 32),
+            Format::F32le => (SampleFormat::Float, 32),
+            _ => return None,
+        };
+        Some(WavSpec {
+            channels: self.channels as u16,
+            sample_rate: self.rate,
+            bits_per_sample: bits_per_sample,
+            sample_format: sample_format,
+        })
+    }
+}
+
+impl Map {
+    /// Create a channel map for the spec of a WAV file.
+    ///
+    /// This gives the positions `hound` assigns to the channels of files it writes, i.e. those of
+    /// the first bits of a WAVEFORMATEXTENSIBLE channel mask, with a single channel taken as mono.
+    ///
+    /// Returns `None` if the number of channels is not supported.
+    pub fn from_wav_spec(spec: &WavSpec) -> Option<Self> {
+        if spec.channels == 0 || spec.channels as usize > ::sample::CHANNELS_MAX {
+            return None;
+        }
+        if spec.channels == 1 {
+            return Some(Map::mono());
+        }
+        let mask = match spec.channels >= 32 {
+            true => !0,
+            false => (1u32 << spec.channels) - 1,
+        };
+        Map::from_wave_channel_mask(mask, spec.channels as u8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wav_spec_conversion() {
+        let wav = WavSpec {
+            channels: 2,
+            sample_rate: 44100,
+            bits_per_sample: 24,
+            sample_format: SampleFormat::Int,
+        };
+        let spec = Spec::try_from_wav_spec(&wav).unwrap();
+        assert_eq!(spec, Spec { format: Format::S24le, rate: 44100, channels: 2 });
+        assert_eq!(spec.to_wav_spec(), Some(wav));
+        assert_eq!(Map::from_wav_spec(&wav), Some(Map::stereo()));
+
+        let float64 = WavSpec { bits_per_sample: 64, sample_format: SampleFormat::Float, ..wav };
+        assert_eq!(Spec::try_from_wav_spec(&float64), None);
+        assert_eq!(Spec { format: Format::S16be, ..spec }.to_wav_spec(), None);
+    }
+}
@@ -0,0 +1,288 @@
+/* Copyright 2017 Lyndon Brown
+ *
+ * This file is part of the PulseAudio Rust language linking library.
+ *
+ * This library is free software; you can redistribute it and/or modify it under the terms of the
+ * GNU Lesser General Public License as published by the Free Software Foundation; either version
+ * 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+ * even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public License along with this library;
+ * if not, see <http://www.gnu.org/licenses/>.
+ */
+
+/* Struct layout information from the installed headers, for the `abi_tests` feature tests. */
+
+#include <stddef.h>
+#include <string.h>
+#include <pulse/pulseaudio.h>
+#include <pulse/ext-stream-restore.h>
+#include <pulse/ext-device-restore.h>
+
+struct layout_entry {
+    const char *type;
+    const char *field;
+    size_t value;
+};
+
+#define SIZE(t) { #t, NULL, sizeof(t) },
+#define FIELD(t, f) { #t, #f, offsetof(t, f) },
+
+static const struct layout_entry entries[] = {
+    SIZE(pa_sample_spec)
+    FIELD(pa_sample_spec, format)
+    FIELD(pa_sample_spec, rate)
+    FIELD(pa_sample_spec, channels)
+    SIZE(pa_channel_map)
+    FIELD(pa_channel_map, channels)
+    FIELD(pa_channel_map, map)
+    SIZE(pa_cvolume)
+    FIELD(pa_cvolume, channels)
+    FIELD(pa_cvolume, values)
+    SIZE(pa_buffer_attr)
+    FIELD(pa_buffer_attr, maxlength)
+    FIELD(pa_buffer_attr, tlength)
+    FIELD(pa_buffer_attr, prebuf)
+    FIELD(pa_buffer_attr, minreq)
+    FIELD(pa_buffer_attr, fragsize)
+    SIZE(pa_timing_info)
+    FIELD(pa_timing_info, timestamp)
+    FIELD(pa_timing_info, synchronized_clocks)
+    FIELD(pa_timing_info, sink_usec)
+    FIELD(pa_timing_info, source_usec)
+    FIELD(pa_timing_info, transport_usec)
+    FIELD(pa_timing_info, playing)
+    FIELD(pa_timing_info, write_index_corrupt)
+    FIELD(pa_timing_info, write_index)
+    FIELD(pa_timing_info, read_index_corrupt)
+    FIELD(pa_timing_info, read_index)
+    FIELD(pa_timing_info, configured_sink_usec)
+    FIELD(pa_timing_info, configured_source_usec)
+    FIELD(pa_timing_info, since_underrun)
+    SIZE(pa_spawn_api)
+    FIELD(pa_spawn_api, prefork)
+    FIELD(pa_spawn_api, postfork)
+    FIELD(pa_spawn_api, atfork)
+    SIZE(pa_format_info)
+    FIELD(pa_format_info, encoding)
+    FIELD(pa_format_info, plist)
+    SIZE(pa_sink_port_info)
+    FIELD(pa_sink_port_info, name)
+    FIELD(pa_sink_port_info, description)
+    FIELD(pa_sink_port_info, priority)
+    FIELD(pa_sink_port_info, available)
+#if PA_CHECK_VERSION(14, 0, 0)
+    FIELD(pa_sink_port_info, availability_group)
+    FIELD(pa_sink_port_info, type)
+#endif
+    SIZE(pa_sink_info)
+    FIELD(pa_sink_info, name)
+    FIELD(pa_sink_info, index)
+    FIELD(pa_sink_info, description)
+    FIELD(pa_sink_info, sample_spec)
+    FIELD(pa_sink_info, channel_map)
+    FIELD(pa_sink_info, owner_module)
+    FIELD(pa_sink_info, volume)
+    FIELD(pa_sink_info, mute)
+    FIELD(pa_sink_info, monitor_source)
+    FIELD(pa_sink_info, monitor_source_name)
+    FIELD(pa_sink_info, latency)
+    FIELD(pa_sink_info, driver)
+    FIELD(pa_sink_info, flags)
+    FIELD(pa_sink_info, proplist)
+    FIELD(pa_sink_info, configured_latency)
+    FIELD(pa_sink_info, base_volume)
+    FIELD(pa_sink_info, state)
+    FIELD(pa_sink_info, n_volume_steps)
+    FIELD(pa_sink_info, card)
+    FIELD(pa_sink_info, n_ports)
+    FIELD(pa_sink_info, ports)
+    FIELD(pa_sink_info, active_port)
+    FIELD(pa_sink_info, n_formats)
+    FIELD(pa_sink_info, formats)
+    SIZE(pa_source_port_info)
+    FIELD(pa_source_port_info, name)
+    FIELD(pa_source_port_info, description)
+    FIELD(pa_source_port_info, priority)
+    FIELD(pa_source_port_info, available)
+#if PA_CHECK_VERSION(14, 0, 0)
+    FIELD(pa_source_port_info, availability_group)
+    FIELD(pa_source_port_info, type)
+#endif
+    SIZE(pa_source_info)
+    FIELD(pa_source_info, name)
+    FIELD(pa_source_info, index)
+    FIELD(pa_source_info, description)
+    FIELD(pa_source_info, sample_spec)
+    FIELD(pa_source_info, channel_map)
+    FIELD(pa_source_info, owner_module)
+    FIELD(pa_source_info, volume)
+    FIELD(pa_source_info, mute)
+    FIELD(pa_source_info, monitor_of_sink)
+    FIELD(pa_source_info, monitor_of_sink_name)
+    FIELD(pa_source_info, latency)
+    FIELD(pa_source_info, driver)
+    FIELD(pa_source_info, flags)
+    FIELD(pa_source_info, proplist)
+    FIELD(pa_source_info, configured_latency)
+    FIELD(pa_source_info, base_volume)
+    FIELD(pa_source_info, state)
+    FIELD(pa_source_info, n_volume_steps)
+    FIELD(pa_source_info, card)
+    FIELD(pa_source_info, n_ports)
+    FIELD(pa_source_info, ports)
+    FIELD(pa_source_info, active_port)
+    FIELD(pa_source_info, n_formats)
+    FIELD(pa_source_info, formats)
+    SIZE(pa_server_info)
+    FIELD(pa_server_info, user_name)
+    FIELD(pa_server_info, host_name)
+    FIELD(pa_server_info, server_version)
+    FIELD(pa_server_info, server_name)
+    FIELD(pa_server_info, sample_spec)
+    FIELD(pa_server_info, default_sink_name)
+    FIELD(pa_server_info, default_source_name)
+    FIELD(pa_server_info, cookie)
+    FIELD(pa_server_info, channel_map)
+    SIZE(pa_module_info)
+    FIELD(pa_module_info, index)
+    FIELD(pa_module_info, name)
+    FIELD(pa_module_info, argument)
+    FIELD(pa_module_info, n_used)
+    FIELD(pa_module_info, auto_unload)
+    FIELD(pa_module_info, proplist)
+    SIZE(pa_client_info)
+    FIELD(pa_client_info, index)
+    FIELD(pa_client_info, name)
+    FIELD(pa_client_info, owner_module)
+    FIELD(pa_client_info, driver)
+    FIELD(pa_client_info, proplist)
+    SIZE(pa_card_profile_info)
+    FIELD(pa_card_profile_info, name)
+    FIELD(pa_card_profile_info, description)
+    FIELD(pa_card_profile_info, n_sinks)
+    FIELD(pa_card_profile_info, n_sources)
+    FIELD(pa_card_profile_info, priority)
+    SIZE(pa_card_profile_info2)
+    FIELD(pa_card_profile_info2, name)
+    FIELD(pa_card_profile_info2, description)
+    FIELD(pa_card_profile_info2, n_sinks)
+    FIELD(pa_card_profile_info2, n_sources)
+    FIELD(pa_card_profile_info2, priority)
+    FIELD(pa_card_profile_info2, available)
+    SIZE(pa_card_port_info)
+    FIELD(pa_card_port_info, name)
+    FIELD(pa_card_port_info, description)
+    FIELD(pa_card_port_info, priority)
+    FIELD(pa_card_port_info, available)
+    FIELD(pa_card_port_info, direction)
+    FIELD(pa_card_port_info, n_profiles)
+    FIELD(pa_card_port_info, profiles)
+    FIELD(pa_card_port_info, proplist)
+    FIELD(pa_card_port_info, latency_offset)
+    FIELD(pa_card_port_info, profiles2)
+#if PA_CHECK_VERSION(14, 0, 0)
+    FIELD(pa_card_port_info, availability_group)
+    FIELD(pa_card_port_info, type)
+#endif
+    SIZE(pa_card_info)
+    FIELD(pa_card_info, index)
+    FIELD(pa_card_info, name)
+    FIELD(pa_card_info, owner_module)
+    FIELD(pa_card_info, driver)
+    FIELD(pa_card_info, n_profiles)
+    FIELD(pa_card_info, profiles)
+    FIELD(pa_card_info, active_profile)
+    FIELD(pa_card_info, proplist)
+    FIELD(pa_card_info, n_ports)
+    FIELD(pa_card_info, ports)
+    FIELD(pa_card_info, profiles2)
+    FIELD(pa_card_info, active_profile2)
+    SIZE(pa_sink_input_info)
+    FIELD(pa_sink_input_info, index)
+    FIELD(pa_sink_input_info, name)
+    FIELD(pa_sink_input_info, owner_module)
+    FIELD(pa_sink_input_info, client)
+    FIELD(pa_sink_input_info, sink)
+    FIELD(pa_sink_input_info, sample_spec)
+    FIELD(pa_sink_input_info, channel_map)
+    FIELD(pa_sink_input_info, volume)
+    FIELD(pa_sink_input_info, buffer_usec)
+    FIELD(pa_sink_input_info, sink_usec)
+    FIELD(pa_sink_input_info, resample_method)
+    FIELD(pa_sink_input_info, driver)
+    FIELD(pa_sink_input_info, mute)
+    FIELD(pa_sink_input_info, proplist)
+    FIELD(pa_sink_input_info, corked)
+    FIELD(pa_sink_input_info, has_volume)
+    FIELD(pa_sink_input_info, volume_writable)
+    FIELD(pa_sink_input_info, format)
+    SIZE(pa_source_output_info)
+    FIELD(pa_source_output_info, index)
+    FIELD(pa_source_output_info, name)
+    FIELD(pa_source_output_info, owner_module)
+    FIELD(pa_source_output_info, client)
+    FIELD(pa_source_output_info, source)
+    FIELD(pa_source_output_info, sample_spec)
+    FIELD(pa_source_output_info, channel_map)
+    FIELD(pa_source_output_info, buffer_usec)
+    FIELD(pa_source_output_info, source_usec)
+    FIELD(pa_source_output_info, resample_method)
+    FIELD(pa_source_output_info, driver)
+    FIELD(pa_source_output_info, proplist)
+    FIELD(pa_source_output_info, corked)
+    FIELD(pa_source_output_info, volume)
+    FIELD(pa_source_output_info, mute)
+    FIELD(pa_source_output_info, has_volume)
+    FIELD(pa_source_output_info, volume_writable)
+    FIELD(pa_source_output_info, format)
+    SIZE(pa_stat_info)
+    FIELD(pa_stat_info, memblock_total)
+    FIELD(pa_stat_info, memblock_total_size)
+    FIELD(pa_stat_info, memblock_allocated)
+    FIELD(pa_stat_info, memblock_allocated_size)
+    FIELD(pa_stat_info, scache_size)
+    SIZE(pa_sample_info)
+    FIELD(pa_sample_info, index)
+    FIELD(pa_sample_info, name)
+    FIELD(pa_sample_info, volume)
+    FIELD(pa_sample_info, sample_spec)
+    FIELD(pa_sample_info, channel_map)
+    FIELD(pa_sample_info, duration)
+    FIELD(pa_sample_info, bytes)
+    FIELD(pa_sample_info, lazy)
+    FIELD(pa_sample_info, filename)
+    FIELD(pa_sample_info, proplist)
+    SIZE(pa_ext_stream_restore_info)
+    FIELD(pa_ext_stream_restore_info, name)
+    FIELD(pa_ext_stream_restore_info, channel_map)
+    FIELD(pa_ext_stream_restore_info, volume)
+    FIELD(pa_ext_stream_restore_info, device)
+    FIELD(pa_ext_stream_restore_info, mute)
+    SIZE(pa_ext_device_restore_info)
+    FIELD(pa_ext_device_restore_info, dtype)
+    FIELD(pa_ext_device_restore_info, index)
+    FIELD(pa_ext_device_restore_info, n_formats)
+    FIELD(pa_ext_device_restore_info, formats)
+};
+
+/* Returns the size of the type (if `field` is null) or the offset of the field, or `(size_t)-1` if
+ * not known. */
+size_t pulse_sys_abi_layout(const char *type, const char *field) {
+    size_t i;
+    for (i = 0; i < sizeof(entries) / sizeof(entries[0]); i++) {
+        if (strcmp(entries[i].type, type) != 0) {
+            continue;
+        }
+        if (field == NULL && entries[i].field == NULL) {
+            return entries[i].value;
+        }
+        if (field != NULL && entries[i].field != NULL && strcmp(entries[i].field, field) == 0) {
+            return entries[i].value;
+        }
+    }
+    return (size_t)-1;
+}
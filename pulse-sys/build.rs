@@ -1,21 +1,46 @@
 #[cfg(target_os="linux")]
 extern crate pkg_config;
+#[cfg(feature="abi_tests")]
+extern crate cc;
 
 #[cfg(target_os="linux")]
 fn main() {
-    let min_version = match cfg!(feature="pa_v12_compatibility") {
-        true => "12.0",
-        false => "10.0",
+    let min_version = match (cfg!(feature="pa_v15_compatibility"),
+        cfg!(feature="pa_v14_compatibility"), cfg!(feature="pa_v12_compatibility"))
+    {
+        (true, _, _) => "15.0",
+        (false, true, _) => "14.0",
+        (false, false, true) => "12.0",
+        (false, false, false) => "10.0",
     };
     // Try package-config first
     let pc = pkg_config::Config::new().atleast_version(min_version).probe("libpulse");
     // Fallback to hard-coded on error (useful if user does not have *.pc file installed)
-    if pc.is_err() {
-        println!("cargo:rustc-link-lib=pulse::libpulse.so.0");
-    }
+    let include_paths = match pc {
+        Ok(lib) => lib.include_paths,
+        Err(_) => {
+            println!("cargo:rustc-link-lib=pulse::libpulse.so.0");
+            Vec::new()
+        },
+    };
+    build_abi_layout(&include_paths);
 }
 
 #[cfg(not(target_os="linux"))]
 fn main() {
     println!("cargo:rustc-link-lib=pulse");
+    build_abi_layout(&[]);
+}
+
+/// Compile the struct layout information used by the ABI tests, from the installed headers.
+#[cfg(feature="abi_tests")]
+fn build_abi_layout(include_paths: &[std::path::PathBuf]) {
+    println!("cargo:rerun-if-changed=abi/layout.c");
+    cc::Build::new()
+        .file("abi/layout.c")
+        .includes(include_paths)
+        .compile("pulse_sys_abi_layout");
 }
+
+#[cfg(not(feature="abi_tests"))]
+fn build_abi_layout(_include_paths: &[std::path::PathBuf]) {}
@@ -23,7 +23,11 @@ pub struct pa_sink_port_info {
     pub name: *const c_char,
     pub description: *const c_char,
     pub priority: u32,
-    pub available: i32,
+    pub available: ::def::pa_port_available_t,
+    #[cfg(feature = "pa_v14_compatibility")]
+    pub availability_group: *const c_char,
+    #[cfg(feature = "pa_v14_compatibility")]
+    pub r#type: ::def::pa_device_port_type_t,
 }
 
 #[repr(C)]
@@ -61,7 +65,11 @@ pub struct pa_source_port_info {
     pub name: *const c_char,
     pub description: *const c_char,
     pub priority: u32,
-    pub available: i32,
+    pub available: ::def::pa_port_available_t,
+    #[cfg(feature = "pa_v14_compatibility")]
+    pub availability_group: *const c_char,
+    #[cfg(feature = "pa_v14_compatibility")]
+    pub r#type: ::def::pa_device_port_type_t,
 }
 
 #[repr(C)]
@@ -188,7 +196,7 @@ pub struct pa_card_port_info {
     pub name: *const c_char,
     pub description: *const c_char,
     pub priority: u32,
-    pub available: i32,
+    pub available: ::def::pa_port_available_t,
     pub direction: i32,
     pub n_profiles: u32,
     #[deprecated]
@@ -197,6 +205,10 @@ pub struct pa_card_port_info {
     pub proplist: *mut ::proplist::pa_proplist,
     pub latency_offset: i64,
     pub profiles2: *mut *mut pa_card_profile_info2,
+    #[cfg(feature = "pa_v14_compatibility")]
+    pub availability_group: *const c_char,
+    #[cfg(feature = "pa_v14_compatibility")]
+    pub r#type: ::def::pa_device_port_type_t,
 }
 
 #[repr(C)]
@@ -37,16 +37,19 @@ use ::mainloop::api::{pa_time_event, pa_time_event_cb_t};
 /// An opaque connection context to a daemon
 #[repr(C)] pub struct pa_context { _private: [u8; 0] }
 
-#[repr(C)]
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub enum pa_context_state_t {
-    Unconnected,
-    Connecting,
-    Authorizing,
-    SettingName,
-    Ready,
-    Failed,
-    Terminated,
+#[repr(transparent)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct pa_context_state_t(pub i32);
+
+#[allow(non_upper_case_globals)]
+impl pa_context_state_t {
+    pub const Unconnected: Self = pa_context_state_t(0);
+    pub const Connecting: Self = pa_context_state_t(1);
+    pub const Authorizing: Self = pa_context_state_t(2);
+    pub const SettingName: Self = pa_context_state_t(3);
+    pub const Ready: Self = pa_context_state_t(4);
+    pub const Failed: Self = pa_context_state_t(5);
+    pub const Terminated: Self = pa_context_state_t(6);
 }
 
 pub const PA_CONTEXT_UNCONNECTED: pa_context_state_t = pa_context_state_t::Unconnected;
@@ -85,6 +88,9 @@ pub type pa_context_success_cb_t = Option<extern "C" fn(c: *mut pa_context, succ
 
 pub type pa_context_event_cb_t = Option<extern "C" fn(c: *mut pa_context, name: *const c_char, p: *mut ::proplist::pa_proplist, userdata: *mut c_void)>;
 
+#[cfg(feature = "pa_v15_compatibility")]
+pub type pa_context_string_cb_t = Option<extern "C" fn(c: *mut pa_context, success: i32, response: *mut c_char, userdata: *mut c_void)>;
+
 #[link(name="pulse")]
 extern "C" {
     pub fn pa_context_new(mainloop: *const ::mainloop::api::pa_mainloop_api, name: *const c_char) -> *mut pa_context;
@@ -114,4 +120,7 @@ extern "C" {
     pub fn pa_context_rttime_restart(c: *const pa_context, e: *mut pa_time_event, usec: ::sample::pa_usec_t);
     pub fn pa_context_get_tile_size(c: *const pa_context, ss: *const ::sample::pa_sample_spec) -> usize;
     pub fn pa_context_load_cookie_from_file(c: *mut pa_context, cookie_file_path: *const c_char) -> i32;
+
+    #[cfg(feature = "pa_v15_compatibility")]
+    pub fn pa_context_send_message_to_object(c: *mut pa_context, recipient_name: *const c_char, message: *const c_char, message_parameters: *const c_char, cb: pa_context_string_cb_t, userdata: *mut c_void) -> *mut ::operation::pa_operation;
 }
@@ -88,13 +88,16 @@ pub mod sink_flags {
     pub const PA_SINK_SET_FORMATS: pa_sink_flags_t = 0x100;
 }
 
-#[repr(C)]
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub enum pa_sink_state_t {
-    Invalid = -1,
-    Running = 0,
-    Idle = 1,
-    Suspended = 2,
+#[repr(transparent)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct pa_sink_state_t(pub i32);
+
+#[allow(non_upper_case_globals)]
+impl pa_sink_state_t {
+    pub const Invalid: Self = pa_sink_state_t(-1);
+    pub const Running: Self = pa_sink_state_t(0);
+    pub const Idle: Self = pa_sink_state_t(1);
+    pub const Suspended: Self = pa_sink_state_t(2);
 }
 
 pub const PA_SINK_INVALID_STATE: pa_sink_state_t = pa_sink_state_t::Invalid;
@@ -134,13 +137,16 @@ pub mod source_flags {
     pub const PA_SOURCE_FLAT_VOLUME: pa_source_flags_t = 0x80;
 }
 
-#[repr(C)]
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub enum pa_source_state_t {
-    Invalid = -1,
-    Running = 0,
-    Idle = 1,
-    Suspended = 2,
+#[repr(transparent)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct pa_source_state_t(pub i32);
+
+#[allow(non_upper_case_globals)]
+impl pa_source_state_t {
+    pub const Invalid: Self = pa_source_state_t(-1);
+    pub const Running: Self = pa_source_state_t(0);
+    pub const Idle: Self = pa_source_state_t(1);
+    pub const Suspended: Self = pa_source_state_t(2);
 }
 
 pub const PA_SOURCE_INVALID_STATE: pa_source_state_t = pa_source_state_t::Invalid;
@@ -159,18 +165,103 @@ pub fn pa_source_is_running(state: pa_source_state_t) -> bool {
     state == pa_source_state_t::Running
 }
 
-#[repr(C)]
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub enum pa_port_available_t {
+#[repr(transparent)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct pa_port_available_t(pub i32);
+
+#[allow(non_upper_case_globals)]
+impl pa_port_available_t {
     /// This port does not support jack detection
-    Unknown = 0,
+    pub const Unknown: Self = pa_port_available_t(0);
     /// This port is not available, likely because the jack is not plugged in.
-    No = 1,
+    pub const No: Self = pa_port_available_t(1);
     /// This port is available, likely because the jack is plugged in.
-    Yes = 2,
+    pub const Yes: Self = pa_port_available_t(2);
 }
 
 pub const PA_PORT_AVAILABLE_UNKNOWN: pa_port_available_t = pa_port_available_t::Unknown;
 pub const PA_PORT_AVAILABLE_NO: pa_port_available_t = pa_port_available_t::No;
 pub const PA_PORT_AVAILABLE_YES: pa_port_available_t = pa_port_available_t::Yes;
 
+/// Port type. New types can be added in the future, so applications should gracefully handle
+/// situations where a type identifier doesn’t match any item in this enumeration.
+#[cfg(feature = "pa_v14_compatibility")]
+#[repr(transparent)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct pa_device_port_type_t(pub u32);
+
+#[cfg(feature = "pa_v14_compatibility")]
+#[allow(non_upper_case_globals)]
+impl pa_device_port_type_t {
+    pub const Unknown: Self = pa_device_port_type_t(0);
+    pub const Aux: Self = pa_device_port_type_t(1);
+    pub const Speaker: Self = pa_device_port_type_t(2);
+    pub const Headphones: Self = pa_device_port_type_t(3);
+    pub const Line: Self = pa_device_port_type_t(4);
+    pub const Mic: Self = pa_device_port_type_t(5);
+    pub const Headset: Self = pa_device_port_type_t(6);
+    pub const Handset: Self = pa_device_port_type_t(7);
+    pub const Earpiece: Self = pa_device_port_type_t(8);
+    pub const SPDIF: Self = pa_device_port_type_t(9);
+    pub const HDMI: Self = pa_device_port_type_t(10);
+    pub const TV: Self = pa_device_port_type_t(11);
+    pub const Radio: Self = pa_device_port_type_t(12);
+    pub const Video: Self = pa_device_port_type_t(13);
+    pub const USB: Self = pa_device_port_type_t(14);
+    pub const Bluetooth: Self = pa_device_port_type_t(15);
+    pub const Portable: Self = pa_device_port_type_t(16);
+    pub const Handsfree: Self = pa_device_port_type_t(17);
+    pub const Car: Self = pa_device_port_type_t(18);
+    pub const HiFi: Self = pa_device_port_type_t(19);
+    pub const Phone: Self = pa_device_port_type_t(20);
+    pub const Network: Self = pa_device_port_type_t(21);
+    pub const Analog: Self = pa_device_port_type_t(22);
+}
+
+#[cfg(feature = "pa_v14_compatibility")]
+pub const PA_DEVICE_PORT_TYPE_UNKNOWN: pa_device_port_type_t = pa_device_port_type_t::Unknown;
+#[cfg(feature = "pa_v14_compatibility")]
+pub const PA_DEVICE_PORT_TYPE_AUX: pa_device_port_type_t = pa_device_port_type_t::Aux;
+#[cfg(feature = "pa_v14_compatibility")]
+pub const PA_DEVICE_PORT_TYPE_SPEAKER: pa_device_port_type_t = pa_device_port_type_t::Speaker;
+#[cfg(feature = "pa_v14_compatibility")]
+pub const PA_DEVICE_PORT_TYPE_HEADPHONES: pa_device_port_type_t = pa_device_port_type_t::Headphones;
+#[cfg(feature = "pa_v14_compatibility")]
+pub const PA_DEVICE_PORT_TYPE_LINE: pa_device_port_type_t = pa_device_port_type_t::Line;
+#[cfg(feature = "pa_v14_compatibility")]
+pub const PA_DEVICE_PORT_TYPE_MIC: pa_device_port_type_t = pa_device_port_type_t::Mic;
+#[cfg(feature = "pa_v14_compatibility")]
+pub const PA_DEVICE_PORT_TYPE_HEADSET: pa_device_port_type_t = pa_device_port_type_t::Headset;
+#[cfg(feature = "pa_v14_compatibility")]
+pub const PA_DEVICE_PORT_TYPE_HANDSET: pa_device_port_type_t = pa_device_port_type_t::Handset;
+#[cfg(feature = "pa_v14_compatibility")]
+pub const PA_DEVICE_PORT_TYPE_EARPIECE: pa_device_port_type_t = pa_device_port_type_t::Earpiece;
+#[cfg(feature = "pa_v14_compatibility")]
+pub const PA_DEVICE_PORT_TYPE_SPDIF: pa_device_port_type_t = pa_device_port_type_t::SPDIF;
+#[cfg(feature = "pa_v14_compatibility")]
+pub const PA_DEVICE_PORT_TYPE_HDMI: pa_device_port_type_t = pa_device_port_type_t::HDMI;
+#[cfg(feature = "pa_v14_compatibility")]
+pub const PA_DEVICE_PORT_TYPE_TV: pa_device_port_type_t = pa_device_port_type_t::TV;
+#[cfg(feature = "pa_v14_compatibility")]
+pub const PA_DEVICE_PORT_TYPE_RADIO: pa_device_port_type_t = pa_device_port_type_t::Radio;
+#[cfg(feature = "pa_v14_compatibility")]
+pub const PA_DEVICE_PORT_TYPE_VIDEO: pa_device_port_type_t = pa_device_port_type_t::Video;
+#[cfg(feature = "pa_v14_compatibility")]
+pub const PA_DEVICE_PORT_TYPE_USB: pa_device_port_type_t = pa_device_port_type_t::USB;
+#[cfg(feature = "pa_v14_compatibility")]
+pub const PA_DEVICE_PORT_TYPE_BLUETOOTH: pa_device_port_type_t = pa_device_port_type_t::Bluetooth;
+#[cfg(feature = "pa_v14_compatibility")]
+pub const PA_DEVICE_PORT_TYPE_PORTABLE: pa_device_port_type_t = pa_device_port_type_t::Portable;
+#[cfg(feature = "pa_v14_compatibility")]
+pub const PA_DEVICE_PORT_TYPE_HANDSFREE: pa_device_port_type_t = pa_device_port_type_t::Handsfree;
+#[cfg(feature = "pa_v14_compatibility")]
+pub const PA_DEVICE_PORT_TYPE_CAR: pa_device_port_type_t = pa_device_port_type_t::Car;
+#[cfg(feature = "pa_v14_compatibility")]
+pub const PA_DEVICE_PORT_TYPE_HIFI: pa_device_port_type_t = pa_device_port_type_t::HiFi;
+#[cfg(feature = "pa_v14_compatibility")]
+pub const PA_DEVICE_PORT_TYPE_PHONE: pa_device_port_type_t = pa_device_port_type_t::Phone;
+#[cfg(feature = "pa_v14_compatibility")]
+pub const PA_DEVICE_PORT_TYPE_NETWORK: pa_device_port_type_t = pa_device_port_type_t::Network;
+#[cfg(feature = "pa_v14_compatibility")]
+pub const PA_DEVICE_PORT_TYPE_ANALOG: pa_device_port_type_t = pa_device_port_type_t::Analog;
+
@@ -28,11 +28,18 @@ pub enum pa_encoding_t {
     MPEG_IEC61937,
     DTS_IEC61937,
     MPEG2_AAC_IEC61937,
+    #[cfg(feature = "pa_v14_compatibility")]
+    TRUEHD_IEC61937,
+    #[cfg(feature = "pa_v14_compatibility")]
+    DTSHD_IEC61937,
 
     Invalid = -1,
 }
 
+#[cfg(not(feature = "pa_v14_compatibility"))]
 pub const PA_ENCODING_MAX: usize = 7;
+#[cfg(feature = "pa_v14_compatibility")]
+pub const PA_ENCODING_MAX: usize = 9;
 
 pub const PA_ENCODING_ANY: pa_encoding_t = pa_encoding_t::Any;
 pub const PA_ENCODING_PCM: pa_encoding_t = pa_encoding_t::PCM;
@@ -41,6 +48,10 @@ pub const PA_ENCODING_EAC3_IEC61937: pa_encoding_t = pa_encoding_t::EAC3_IEC6193
 pub const PA_ENCODING_MPEG_IEC61937: pa_encoding_t = pa_encoding_t::MPEG_IEC61937;
 pub const PA_ENCODING_DTS_IEC61937: pa_encoding_t = pa_encoding_t::DTS_IEC61937;
 pub const PA_ENCODING_MPEG2_AAC_IEC61937: pa_encoding_t = pa_encoding_t::MPEG2_AAC_IEC61937;
+#[cfg(feature = "pa_v14_compatibility")]
+pub const PA_ENCODING_TRUEHD_IEC61937: pa_encoding_t = pa_encoding_t::TRUEHD_IEC61937;
+#[cfg(feature = "pa_v14_compatibility")]
+pub const PA_ENCODING_DTSHD_IEC61937: pa_encoding_t = pa_encoding_t::DTSHD_IEC61937;
 pub const PA_ENCODING_INVALID: pa_encoding_t = pa_encoding_t::Invalid;
 
 impl Default for pa_encoding_t {
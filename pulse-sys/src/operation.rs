@@ -20,16 +20,19 @@ use std::os::raw::c_void;
 /// An asynchronous operation object
 #[repr(C)] pub struct pa_operation { _private: [u8; 0] }
 
-#[repr(C)]
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub enum pa_operation_state_t {
+#[repr(transparent)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct pa_operation_state_t(pub i32);
+
+#[allow(non_upper_case_globals)]
+impl pa_operation_state_t {
     /// The operation is still running.
-    Running,
+    pub const Running: Self = pa_operation_state_t(0);
     /// The operation has completed.
-    Done,
+    pub const Done: Self = pa_operation_state_t(1);
     /// The operation has been cancelled. Operations may get cancelled by the application, or as a
     /// result of the context getting disconnected while the operation is pending.
-    Cancelled,
+    pub const Cancelled: Self = pa_operation_state_t(2);
 }
 
 pub const PA_OPERATION_RUNNING: pa_operation_state_t = pa_operation_state_t::Running;
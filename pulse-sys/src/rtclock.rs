@@ -14,6 +14,10 @@
 // if not, see <http://www.gnu.org/licenses/>.
 
 //! Monotonic clock utilities.
+//!
+//! Note, only `pa_rtclock_now()` is part of the public libpulse API. Other `pa_rtclock_*()`
+//! functions (e.g. `pa_rtclock_age()`) belong to PA’s private internal library and thus are not
+//! available to bind.
 
 #[link(name="pulse")]
 extern "C" {
@@ -20,14 +20,17 @@ use std::os::raw::{c_char, c_void};
 /// An opaque stream for playback or recording.
 #[repr(C)] pub struct pa_stream { _private: [u8; 0] }
 
-#[repr(C)]
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub enum pa_stream_state_t {
-    Unconnected,
-    Creating,
-    Ready,
-    Failed,
-    Terminated,
+#[repr(transparent)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct pa_stream_state_t(pub i32);
+
+#[allow(non_upper_case_globals)]
+impl pa_stream_state_t {
+    pub const Unconnected: Self = pa_stream_state_t(0);
+    pub const Creating: Self = pa_stream_state_t(1);
+    pub const Ready: Self = pa_stream_state_t(2);
+    pub const Failed: Self = pa_stream_state_t(3);
+    pub const Terminated: Self = pa_stream_state_t(4);
 }
 
 pub const PA_STREAM_UNCONNECTED: pa_stream_state_t = pa_stream_state_t::Unconnected;
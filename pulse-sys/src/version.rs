@@ -26,7 +26,9 @@
 //!
 //! Currently:
 //!
-//! - We primarily target PA version `12.x`
+//! - We primarily target PA version `15.x` if the `pa_v15_compatibility` feature flag is enabled,
+//!   `14.x` if the `pa_v14_compatibility` feature flag is enabled, otherwise `12.x`. PA version
+//!   `14.x` extended the port information structures, and version `15.x` added the message API.
 //! - We have backwards compatibility with PA version `11.x` (and it is believed `10.x`) if the
 //!   `pa_encoding_from_string` feature flag is disabled.
 //!
@@ -40,10 +42,38 @@ use std::os::raw::c_char;
 pub use self::actual::{TARGET_VERSION_STRING, TARGET_VERSION};
 
 // Current
-#[cfg(feature="pa_v12_compatibility")]
+#[cfg(feature="pa_v15_compatibility")]
 mod actual {
     pub const COMPATIBILITY: super::Compatibility = super::Compatibility::Latest;
 
+    /// The newest version of the PulseAudio client library this linking library is known to be
+    /// compatible with.
+    pub const TARGET_VERSION_STRING: &str = "15.0.0";
+
+    /// The major and minor components of the newest version of the PulseAudio client library this
+    /// linking library is known to be compatible with.
+    pub const TARGET_VERSION: (u8, u8) = (15, 0);
+}
+
+// Pre-v15
+#[cfg(all(feature="pa_v14_compatibility", not(feature="pa_v15_compatibility")))]
+mod actual {
+    pub const COMPATIBILITY: super::Compatibility = super::Compatibility::PreV15;
+
+    /// The newest version of the PulseAudio client library this linking library is known to be
+    /// compatible with.
+    pub const TARGET_VERSION_STRING: &str = "14.0.0";
+
+    /// The major and minor components of the newest version of the PulseAudio client library this
+    /// linking library is known to be compatible with.
+    pub const TARGET_VERSION: (u8, u8) = (14, 0);
+}
+
+// Pre-v14
+#[cfg(all(feature="pa_v12_compatibility", not(feature="pa_v14_compatibility")))]
+mod actual {
+    pub const COMPATIBILITY: super::Compatibility = super::Compatibility::PreV14;
+
     /// The newest version of the PulseAudio client library this linking library is known to be
     /// compatible with.
     pub const TARGET_VERSION_STRING: &str = "12.0.0";
@@ -77,6 +107,10 @@ pub enum Compatibility {
     Latest,
     /// Support for PA versions < 12 selected
     PreV12,
+    /// Support for PA versions < 14 selected
+    PreV14,
+    /// Support for PA versions < 15 selected
+    PreV15,
 }
 
 pub const PA_API_VERSION: u8 = 12;
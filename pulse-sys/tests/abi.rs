@@ -0,0 +1,148 @@
+// Copyright 2017 Lyndon Brown
+//
+// This file is part of the PulseAudio Rust language linking library.
+//
+// This library is free software; you can redistribute it and/or modify it under the terms of the
+// GNU Lesser General Public License as published by the Free Software Foundation; either version
+// 2.1 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License along with this library;
+// if not, see <http://www.gnu.org/licenses/>.
+
+//! Struct layout (ABI) tests, checking the sizes of structs and offsets of their fields against
+//! those of the installed libpulse headers, as compiled by the build script.
+//!
+//! Requires the `abi_tests` feature, and the libpulse development headers: `cargo test --features
+//! abi_tests`.
+
+#![cfg(feature = "abi_tests")]
+#![allow(deprecated)]
+
+extern crate libpulse_sys as capi;
+
+use std::ffi::CString;
+use std::mem::{offset_of, size_of};
+use std::os::raw::c_char;
+use std::ptr::null;
+
+extern "C" {
+    fn pulse_sys_abi_layout(type_: *const c_char, field: *const c_char) -> usize;
+}
+
+/// Get the C size of a type (`field` being `None`), or offset of a field.
+fn c_layout(type_: &str, field: Option<&str>) -> usize {
+    let type_ = CString::new(type_).unwrap();
+    let field = field.map(|f| CString::new(f.trim_start_matches("r#")).unwrap());
+    let value = unsafe {
+        pulse_sys_abi_layout(type_.as_ptr(), field.as_ref().map_or(null(), |f| f.as_ptr()))
+    };
+    assert_ne!(value, usize::max_value(), "no C layout for {:?} {:?}", type_, field);
+    value
+}
+
+macro_rules! check_layout {
+    ($t:ident { $($f:ident),* $(,)* }) => {
+        assert_eq!(size_of::<capi::$t>(), c_layout(stringify!($t), None),
+            "size of {}", stringify!($t));
+        $(
+            assert_eq!(offset_of!(capi::$t, $f), c_layout(stringify!($t), Some(stringify!($f))),
+                "offset of {}::{}", stringify!($t), stringify!($f));
+        )*
+    };
+}
+
+#[test]
+fn sample() {
+    check_layout!(pa_sample_spec { format, rate, channels });
+    check_layout!(pa_channel_map { channels, map });
+    check_layout!(pa_cvolume { channels, values });
+    check_layout!(pa_format_info { encoding, plist });
+}
+
+#[test]
+fn def() {
+    check_layout!(pa_buffer_attr { maxlength, tlength, prebuf, minreq, fragsize });
+    check_layout!(pa_timing_info {
+        timestamp, synchronized_clocks, sink_usec, source_usec, transport_usec, playing,
+        write_index_corrupt, write_index, read_index_corrupt, read_index, configured_sink_usec,
+        configured_source_usec, since_underrun,
+    });
+    check_layout!(pa_spawn_api { prefork, postfork, atfork });
+}
+
+#[test]
+fn introspect() {
+    #[cfg(not(feature = "pa_v14_compatibility"))]
+    check_layout!(pa_sink_port_info { name, description, priority, available });
+    #[cfg(feature = "pa_v14_compatibility")]
+    check_layout!(pa_sink_port_info {
+        name, description, priority, available, availability_group, r#type,
+    });
+    check_layout!(pa_sink_info {
+        name, index, description, sample_spec, channel_map, owner_module, volume, mute,
+        monitor_source, monitor_source_name, latency, driver, flags, proplist, configured_latency,
+        base_volume, state, n_volume_steps, card, n_ports, ports, active_port, n_formats, formats,
+    });
+    #[cfg(not(feature = "pa_v14_compatibility"))]
+    check_layout!(pa_source_port_info { name, description, priority, available });
+    #[cfg(feature = "pa_v14_compatibility")]
+    check_layout!(pa_source_port_info {
+        name, description, priority, available, availability_group, r#type,
+    });
+    check_layout!(pa_source_info {
+        name, index, description, sample_spec, channel_map, owner_module, volume, mute,
+        monitor_of_sink, monitor_of_sink_name, latency, driver, flags, proplist, configured_latency,
+        base_volume, state, n_volume_steps, card, n_ports, ports, active_port, n_formats, formats,
+    });
+    check_layout!(pa_server_info {
+        user_name, host_name, server_version, server_name, sample_spec, default_sink_name,
+        default_source_name, cookie, channel_map,
+    });
+    check_layout!(pa_module_info { index, name, argument, n_used, auto_unload, proplist });
+    check_layout!(pa_client_info { index, name, owner_module, driver, proplist });
+    check_layout!(pa_card_profile_info { name, description, n_sinks, n_sources, priority });
+    check_layout!(pa_card_profile_info2 {
+        name, description, n_sinks, n_sources, priority, available,
+    });
+    #[cfg(not(feature = "pa_v14_compatibility"))]
+    check_layout!(pa_card_port_info {
+        name, description, priority, available, direction, n_profiles, profiles, proplist,
+        latency_offset, profiles2,
+    });
+    #[cfg(feature = "pa_v14_compatibility")]
+    check_layout!(pa_card_port_info {
+        name, description, priority, available, direction, n_profiles, profiles, proplist,
+        latency_offset, profiles2, availability_group, r#type,
+    });
+    check_layout!(pa_card_info {
+        index, name, owner_module, driver, n_profiles, profiles, active_profile, proplist, n_ports,
+        ports, profiles2, active_profile2,
+    });
+    check_layout!(pa_sink_input_info {
+        index, name, owner_module, client, sink, sample_spec, channel_map, volume, buffer_usec,
+        sink_usec, resample_method, driver, mute, proplist, corked, has_volume, volume_writable,
+        format,
+    });
+    check_layout!(pa_source_output_info {
+        index, name, owner_module, client, source, sample_spec, channel_map, buffer_usec,
+        source_usec, resample_method, driver, proplist, corked, volume, mute, has_volume,
+        volume_writable, format,
+    });
+    check_layout!(pa_stat_info {
+        memblock_total, memblock_total_size, memblock_allocated, memblock_allocated_size,
+        scache_size,
+    });
+    check_layout!(pa_sample_info {
+        index, name, volume, sample_spec, channel_map, duration, bytes, lazy, filename, proplist,
+    });
+}
+
+#[test]
+fn ext() {
+    check_layout!(pa_ext_stream_restore_info { name, channel_map, volume, device, mute });
+    check_layout!(pa_ext_device_restore_info { dtype, index, n_formats, formats });
+}